@@ -266,6 +266,7 @@ export type CoreModule = () => Promise<
       type: "success"
       result: {
         port: number
+        token: string
         kill: () => void
       }
     }
@@ -279,10 +280,28 @@ const loadCoreModule: CoreModule = () =>
   new Promise((resolve, _) => {
     console.log("Loading core module...")
     if (isDev) {
+      // the core module is started separately in development, both read the
+      // session token from the environment at runtime
+      const token = process.env.SESSION_TOKEN
+      if (!token) {
+        resolve({
+          type: "error",
+          logs: [
+            {
+              type: "error",
+              message:
+                "SESSION_TOKEN must be set to the token the core module was started with"
+            }
+          ]
+        })
+        return
+      }
+
       resolve({
         type: "success",
         result: {
           port: 4650,
+          token,
           kill: () => {}
         }
       })
@@ -365,7 +384,7 @@ const loadCoreModule: CoreModule = () =>
 
       for (const row of rows) {
         if (row.startsWith("_STATUS_:")) {
-          const port: number = row.split("|")[1]
+          const [, port, token] = row.split("|")
           console.log(`[CORE] Port: ${port}`)
 
           started = true
@@ -373,7 +392,8 @@ const loadCoreModule: CoreModule = () =>
           resolve({
             type: "success",
             result: {
-              port,
+              port: Number(port),
+              token,
               kill: () => coreModule?.kill()
             }
           })
@@ -645,9 +665,19 @@ async function createWindow(): Promise<BrowserWindow> {
     }
 
     win?.webContents.session.webRequest.onBeforeSendHeaders(
-      (details, callback) => {
+      async (details, callback) => {
         const { requestHeaders } = details
         upsertKeyValue(requestHeaders, "Access-Control-Allow-Origin", ["*"])
+
+        // the rspc websocket cannot set headers from the renderer
+        const cm = await coreModule
+        if (
+          cm.type === "success" &&
+          details.url.startsWith(`ws://127.0.0.1:${cm.result.port}/rspc`)
+        ) {
+          requestHeaders["X-Carbon-Session"] = cm.result.token
+        }
+
         callback({ requestHeaders })
       }
     )
@@ -848,7 +878,8 @@ ipcMain.handle("getCoreModule", async () => {
   return {
     type: cm.type,
     logs: cm.type === "error" ? cm.logs : undefined,
-    port: cm.type === "success" ? cm.result.port : undefined
+    port: cm.type === "success" ? cm.result.port : undefined,
+    token: cm.type === "success" ? cm.result.token : undefined
   }
 })
 
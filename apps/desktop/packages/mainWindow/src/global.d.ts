@@ -45,6 +45,7 @@ declare global {
       | {
           type: "success"
           port: string
+          token: string
         }
       | {
           type: "error"
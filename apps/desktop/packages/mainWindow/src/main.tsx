@@ -25,13 +25,13 @@ import { GlobalStoreProvider } from "./components/GlobalStoreContext"
 
 render(() => {
   const [coreModuleLoaded] = createResource(async () => {
-    let port
+    let session
     try {
       const coreModule = await window.getCoreModule()
 
       if (coreModule?.type === "success") {
         const convertedPort = Number(coreModule.port)
-        port = convertedPort
+        session = { port: convertedPort, token: coreModule.token }
       } else {
         if (coreModule.logs) {
           console.error(
@@ -44,19 +44,19 @@ render(() => {
           window.fatalError("Unknown error", "CoreModule")
         }
 
-        port = new Error("CoreModule")
+        session = new Error("CoreModule")
       }
     } catch (e) {
       console.error("CoreModule getCoreModule failed", e)
       window.fatalError(e as any, "CoreModule")
-      port = new Error("CoreModule")
+      session = new Error("CoreModule")
     }
 
-    if (port instanceof Error) {
-      throw port
+    if (session instanceof Error) {
+      throw session
     }
 
-    return port
+    return session
   })
 
   const startTime = Date.now()
@@ -87,7 +87,10 @@ render(() => {
           <Switch>
             <Match when={isReady()}>
               <NotificationsProvider>
-                <InnerApp port={coreModuleLoaded() as unknown as number} />
+                <InnerApp
+                  port={coreModuleLoaded()!.port}
+                  token={coreModuleLoaded()!.token}
+                />
               </NotificationsProvider>
             </Match>
             <Match when={!isReady()}>
@@ -122,10 +125,11 @@ render(() => {
 
 interface InnerAppProps {
   port: number
+  token: string
 }
 
 const InnerApp = (props: InnerAppProps) => {
-  const { client, createInvalidateQuery } = initRspc(props.port)
+  const { client, createInvalidateQuery } = initRspc(props.port, props.token)
 
   return (
     <rspc.Provider client={client} queryClient={queryClient}>
@@ -10,7 +10,7 @@ import {
   Show,
   Switch
 } from "solid-js"
-import { port, rspc } from "@/utils/rspcClient"
+import { port, rspc, token } from "@/utils/rspcClient"
 import {
   CFFEModLoaderType,
   FEModdedManifestLoaderVersion,
@@ -343,16 +343,16 @@ const Custom = (props: Pick<ModalProps, "data">) => {
   }
 
   const loadIcon = (filePaths: string) => {
-    fetch(`http://127.0.0.1:${port}/instance/loadIcon?path=${filePaths}`).then(
-      async (img) => {
-        const blob = await img.blob()
-        const b64 = (await blobToBase64(blob)) as string
-
-        setBgPreview(
-          `data:image/png;base64, ${b64.substring(b64.indexOf(",") + 1)}`
-        )
-      }
-    )
+    fetch(`http://127.0.0.1:${port}/instance/loadIcon?path=${filePaths}`, {
+      headers: { "X-Carbon-Session": token! }
+    }).then(async (img) => {
+      const blob = await img.blob()
+      const b64 = (await blobToBase64(blob)) as string
+
+      setBgPreview(
+        `data:image/png;base64, ${b64.substring(b64.indexOf(",") + 1)}`
+      )
+    })
   }
 
   const isNameDiff = () => title() && title() !== instanceData()?.title
@@ -1,5 +1,5 @@
 import { LogEntry } from "@/utils/logs"
-import { port, rspc } from "@/utils/rspcClient.js"
+import { port, rspc, token } from "@/utils/rspcClient.js"
 import { useParams } from "@solidjs/router"
 import { createEffect, createSignal, onCleanup } from "solid-js"
 import LogsSidebar from "./LogsSidebar"
@@ -40,8 +40,10 @@ const Logs = () => {
   createEffect(() => {
     if (selectedLog() === undefined) return
 
+    // the session token can only be passed as a subprotocol
     const wsConnection = new WebSocket(
-      `ws://127.0.0.1:${port}/instance/log?id=${selectedLog()}`
+      `ws://127.0.0.1:${port}/instance/log?id=${selectedLog()}`,
+      [`carbon-session.${token}`]
     )
 
     wsConnection.onmessage = (event) => {
@@ -18,11 +18,13 @@ export const queryClient = new QueryClient({
 })
 
 export let port: number | null = null
+export let token: string | null = null
 
-export default function initRspc(_port: number) {
+export default function initRspc(_port: number, _token: string) {
   const addNotification = createNotification()
 
   port = _port
+  token = _token
 
   const transport = new WebsocketTransport(`ws://127.0.0.1:${_port}/rspc/ws`)
 
@@ -62,7 +64,10 @@ export default function initRspc(_port: number) {
 
     function connect() {
       // Create a new WebSocket connection
-      socket = new WebSocket(`ws://127.0.0.1:${_port}/invalidations`)
+      // the session token can only be passed as a subprotocol
+      socket = new WebSocket(`ws://127.0.0.1:${_port}/invalidations`, [
+        `carbon-session.${_token}`
+      ])
 
       socket.addEventListener("open", () => {
         console.log("Invalidations channel connected")
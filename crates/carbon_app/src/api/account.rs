@@ -7,6 +7,7 @@ use crate::managers::account::gdl_account::{
     GDLAccountStatus, GDLUser, RegisterAccountBody, RequestGDLAccountDeletionError,
     RequestNewEmailChangeError, RequestNewVerificationTokenError,
 };
+use crate::managers::account::skin_render::SkinRenderKind;
 use crate::managers::{account, App, AppInner};
 use axum::extract::{Query, State};
 use chrono::{DateTime, Utc};
@@ -14,6 +15,7 @@ use hyper::{header, StatusCode};
 use rspc::RouterBuilder;
 use serde::{Deserialize, Serialize};
 use specta::Type;
+use std::path::PathBuf;
 use std::sync::Arc;
 
 pub(super) fn mount() -> RouterBuilder<App> {
@@ -61,12 +63,78 @@ pub(super) fn mount() -> RouterBuilder<App> {
             app.account_manager().finalize_enrollment().await
         }
 
+        mutation ADD_OFFLINE_ACCOUNT[app, username: String] {
+            app.account_manager().add_offline_account(username).await
+        }
+
+        mutation ADD_YGGDRASIL_ACCOUNT[app, args: FEYggdrasilLogin] {
+            app.account_manager()
+                .add_yggdrasil_account(args.server, args.username, args.password)
+                .await
+        }
+
         mutation REFRESH_ACCOUNT[app, uuid: String] {
             app.account_manager().refresh_account(uuid).await
         }
 
         query GET_HEAD[_, _uuid: String] { Ok(()) }
 
+        mutation UPLOAD_SKIN[app, args: FEUploadSkin] {
+            app.account_manager()
+                .skin_manager()
+                .upload_skin(args.uuid, PathBuf::from(args.path), args.variant.into())
+                .await
+        }
+
+        mutation RESET_SKIN[app, uuid: String] {
+            app.account_manager().skin_manager().reset_skin(uuid).await
+        }
+
+        query GET_CAPES[app, uuid: String] {
+            Ok(app.account_manager()
+               .skin_manager()
+               .get_capes(uuid)
+               .await?
+               .into_iter()
+               .map(Cape::from)
+               .collect::<Vec<_>>())
+        }
+
+        mutation SET_ACTIVE_CAPE[app, args: FESetActiveCape] {
+            app.account_manager()
+                .skin_manager()
+                .set_active_cape(args.uuid, args.cape_id)
+                .await
+        }
+
+        query GET_SAVED_SKINS[app, args: ()] {
+            Ok(app.account_manager()
+               .skin_manager()
+               .get_saved_skins()
+               .await?
+               .into_iter()
+               .map(SavedSkin::from)
+               .collect::<Vec<_>>())
+        }
+
+        mutation SAVE_SKIN[app, args: FESaveSkin] {
+            app.account_manager()
+                .skin_manager()
+                .save_skin(args.name, PathBuf::from(args.path), args.variant.into())
+                .await
+        }
+
+        mutation DELETE_SAVED_SKIN[app, id: i32] {
+            app.account_manager().skin_manager().delete_saved_skin(id).await
+        }
+
+        mutation APPLY_SAVED_SKIN[app, args: FEApplySavedSkin] {
+            app.account_manager()
+                .skin_manager()
+                .apply_saved_skin(args.uuid, args.id)
+                .await
+        }
+
         query PEEK_GDL_ACCOUNT[app, uuid: String] {
             let gdl_user = app.account_manager().peek_gdl_account(uuid).await?;
 
@@ -137,6 +205,25 @@ pub(super) fn mount_axum_router() -> axum::Router<Arc<AppInner>> {
         uuid: String,
     }
 
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    enum RenderKind {
+        Front,
+        Back,
+        Bust,
+    }
+
+    #[derive(Deserialize)]
+    struct SkinRenderQuery {
+        uuid: String,
+        kind: RenderKind,
+    }
+
+    #[derive(Deserialize)]
+    struct SavedSkinQuery {
+        id: i32,
+    }
+
     #[derive(Deserialize)]
     struct WaitForVerificationQuery {
         uuid: String,
@@ -161,6 +248,36 @@ pub(super) fn mount_axum_router() -> axum::Router<Arc<AppInner>> {
                 },
             ),
         )
+        .route(
+            "/skinRender",
+            axum::routing::get(
+                |State(app): State<Arc<AppInner>>, Query(query): Query<SkinRenderQuery>| async move {
+                    let kind = match query.kind {
+                        RenderKind::Front => SkinRenderKind::Front,
+                        RenderKind::Back => SkinRenderKind::Back,
+                        RenderKind::Bust => SkinRenderKind::Bust,
+                    };
+
+                    app.account_manager()
+                        .skin_manager()
+                        .render(query.uuid, kind)
+                        .await
+                        .map_err(|e| FeError::from_anyhow(&e).make_axum())
+                },
+            ),
+        )
+        .route(
+            "/savedSkinImage",
+            axum::routing::get(
+                |State(app): State<Arc<AppInner>>, Query(query): Query<SavedSkinQuery>| async move {
+                    app.account_manager()
+                        .skin_manager()
+                        .get_saved_skin_image(query.id)
+                        .await
+                        .map_err(|e| FeError::from_anyhow(&e).make_axum())
+                },
+            ),
+        )
         .route(
             "/awaitForAccountVerification",
             axum::routing::get(
@@ -188,6 +305,8 @@ struct AccountEntry {
     last_used: DateTime<Utc>,
     type_: AccountType,
     status: AccountStatus,
+    // error of the last failed background token refresh
+    last_refresh_error: Option<String>,
 }
 
 #[derive(Type, Serialize)]
@@ -196,6 +315,7 @@ struct AccountEntry {
 enum AccountType {
     Microsoft { email: Option<String> },
     Offline,
+    Yggdrasil { server: String },
 }
 
 #[derive(Type, Serialize)]
@@ -207,6 +327,74 @@ enum AccountStatus {
     Invalid,
 }
 
+#[derive(Type, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FEYggdrasilLogin {
+    // yggdrasil api root, or any url advertising it through authlib-injector's
+    // api location indication
+    server: String,
+    username: String,
+    password: String,
+}
+
+#[derive(Type, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+enum SkinVariant {
+    Classic,
+    Slim,
+}
+
+#[derive(Type, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FEUploadSkin {
+    uuid: String,
+    // path of the PNG to upload
+    path: String,
+    variant: SkinVariant,
+}
+
+#[derive(Type, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FESetActiveCape {
+    uuid: String,
+    // hides the active cape if null
+    cape_id: Option<String>,
+}
+
+#[derive(Type, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Cape {
+    id: String,
+    url: String,
+    alias: String,
+    active: bool,
+}
+
+#[derive(Type, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FESaveSkin {
+    name: String,
+    // path of the PNG to save
+    path: String,
+    variant: SkinVariant,
+}
+
+#[derive(Type, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FEApplySavedSkin {
+    uuid: String,
+    id: i32,
+}
+
+#[derive(Type, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SavedSkin {
+    id: i32,
+    name: String,
+    variant: SkinVariant,
+    created_at: DateTime<Utc>,
+}
+
 #[derive(Type, Serialize)]
 #[serde(rename_all = "camelCase")]
 struct StatusFlags {
@@ -256,6 +444,7 @@ impl From<domain::AccountWithStatus> for AccountEntry {
             type_: value.account.type_.into(),
             last_used: value.account.last_used,
             status: value.status.into(),
+            last_refresh_error: value.last_refresh_error,
         }
     }
 }
@@ -265,6 +454,7 @@ impl From<domain::AccountType> for AccountType {
         match value {
             domain::AccountType::Microsoft { email } => Self::Microsoft { email },
             domain::AccountType::Offline => Self::Offline,
+            domain::AccountType::Yggdrasil { server } => Self::Yggdrasil { server },
         }
     }
 }
@@ -280,6 +470,46 @@ impl From<domain::AccountStatus> for AccountStatus {
     }
 }
 
+impl From<SkinVariant> for account::api::SkinVariant {
+    fn from(value: SkinVariant) -> Self {
+        match value {
+            SkinVariant::Classic => Self::Classic,
+            SkinVariant::Slim => Self::Slim,
+        }
+    }
+}
+
+impl From<account::api::SkinVariant> for SkinVariant {
+    fn from(value: account::api::SkinVariant) -> Self {
+        match value {
+            account::api::SkinVariant::Classic => Self::Classic,
+            account::api::SkinVariant::Slim => Self::Slim,
+        }
+    }
+}
+
+impl From<account::api::McCape> for Cape {
+    fn from(value: account::api::McCape) -> Self {
+        Self {
+            id: value.id,
+            url: value.url,
+            alias: value.alias,
+            active: value.active,
+        }
+    }
+}
+
+impl From<account::skin::SavedSkin> for SavedSkin {
+    fn from(value: account::skin::SavedSkin) -> Self {
+        Self {
+            id: value.id,
+            name: value.name,
+            variant: value.variant.into(),
+            created_at: value.created_at,
+        }
+    }
+}
+
 impl From<Option<domain::StatusFlags>> for StatusFlags {
     fn from(value: Option<domain::StatusFlags>) -> Self {
         match value {
@@ -10,6 +10,7 @@ use crate::managers::instance as manager;
 use crate::managers::instance::log::LogEntrySourceKind;
 use crate::managers::instance::InstanceMoveTarget;
 use crate::managers::{instance::importer, App, AppInner};
+use crate::session;
 use anyhow::anyhow;
 use axum::extract::{Query, State};
 use axum::http::{HeaderMap, HeaderValue, StatusCode};
@@ -79,11 +80,40 @@ pub(super) fn mount() -> RouterBuilder<App> {
                 .change_modpack(
                     details.instance.into(),
                     details.modpack.into(),
+                    details
+                        .overrides
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(|(path, file_override)| (path, file_override.into()))
+                        .collect(),
+                )
+                .await
+                .map(FETaskId::from)
+        }
+
+        mutation PREVIEW_MODPACK_CHANGE[app, details: PreviewModpackChange] {
+            app.instance_manager()
+                .preview_modpack_change(
+                    details.instance.into(),
+                    details.modpack.into(),
                 )
                 .await
                 .map(FETaskId::from)
         }
 
+        query GET_MODPACK_UPDATE_PLAN[app, id: FEInstanceId] {
+            app.instance_manager()
+                .get_modpack_update_plan(id.into())
+                .await
+                .map(|plan| plan.map(FEModpackUpdatePlan::from))
+        }
+
+        mutation CANCEL_MODPACK_CHANGE[app, id: FEInstanceId] {
+            app.instance_manager()
+                .cancel_modpack_change(id.into())
+                .await
+        }
+
         mutation LOAD_ICON_URL[app, url: String] {
             let icon = app.instance_manager()
                 .download_icon(url)
@@ -298,6 +328,18 @@ pub(super) fn mount() -> RouterBuilder<App> {
                         )
                         .await?
                 }
+                ModSource::Source(src_mod) => {
+                    app.instance_manager()
+                        .install_source_mod(
+                            imod.instance_id.into(),
+                            src_mod.source_id,
+                            src_mod.repository,
+                            src_mod.project_id,
+                            src_mod.version_id,
+                            imod.replaces_mod,
+                        )
+                        .await?
+                }
             };
 
             Ok(super::vtask::FETaskId::from(task))
@@ -327,6 +369,13 @@ pub(super) fn mount() -> RouterBuilder<App> {
                 .map(super::modplatforms::ModSources::from)
         }
 
+        query ANALYZE_MOD_DEPENDENCIES[app, instance_id: FEInstanceId] {
+            app.instance_manager()
+                .analyze_mod_dependencies(instance_id.into())
+                .await
+                .map(FEModDependencyAnalysis::from)
+        }
+
         mutation INSTALL_LATEST_MOD[app, imod: InstallLatestMod] {
             let task = match imod.mod_source {
                 LatestModSource::Curseforge(cf_mod) => {
@@ -345,6 +394,16 @@ pub(super) fn mount() -> RouterBuilder<App> {
                         )
                         .await?
                 }
+                LatestModSource::Source(src_project) => {
+                    app.instance_manager()
+                        .install_latest_source_mod(
+                            imod.instance_id.into(),
+                            src_project.source_id,
+                            src_project.repository,
+                            src_project.project_id,
+                        )
+                        .await?
+                }
             };
 
             Ok(super::vtask::FETaskId::from(task))
@@ -530,6 +589,7 @@ pub(super) fn mount_axum_router() -> axum::Router<Arc<AppInner>> {
         )
         .route(
             "/loadIcon",
+            // reads any file it is pointed at
             axum::routing::get(
                 |State(app): State<Arc<AppInner>>, Query(query): Query<IconPathQuery>| async move {
                     let icon = app.instance_manager()
@@ -543,6 +603,7 @@ pub(super) fn mount_axum_router() -> axum::Router<Arc<AppInner>> {
                     Ok::<_, AxumError>(icon_bytes)
                 }
             )
+            .layer(axum::middleware::from_fn(session::require_session_token))
         )
         .route("/log", axum::routing::get(log::log_handler))
 }
@@ -649,6 +710,269 @@ struct CreateInstance {
 struct ChangeModpack {
     instance: FEInstanceId,
     modpack: Modpack,
+    /// Per-file decisions for a previewed change, keyed by pack path.
+    #[specta(optional)]
+    overrides: Option<HashMap<String, FEFileOverride>>,
+}
+
+#[derive(Type, Debug, Deserialize)]
+struct PreviewModpackChange {
+    instance: FEInstanceId,
+    modpack: Modpack,
+}
+
+#[derive(Type, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+enum FEFileOverride {
+    TakePack,
+    KeepMine,
+}
+
+impl From<FEFileOverride> for manager::modpack::plan::FileOverride {
+    fn from(value: FEFileOverride) -> Self {
+        match value {
+            FEFileOverride::TakePack => Self::TakePack,
+            FEFileOverride::KeepMine => Self::KeepMine,
+        }
+    }
+}
+
+#[derive(Type, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FEModpackUpdatePlan {
+    added: Vec<FEPlannedFile>,
+    removed: Vec<FEPlannedFile>,
+    replaced: Vec<FEPlannedFile>,
+    skipped: Vec<FESkippedFile>,
+    mod_changes: Vec<FEModVersionChange>,
+}
+
+#[derive(Type, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FEPlannedFile {
+    path: String,
+    /// Applied over changes made by the user because of an override.
+    overridden: bool,
+}
+
+#[derive(Type, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FESkippedFile {
+    path: String,
+    reason: FESkipReason,
+}
+
+#[derive(Type, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+enum FESkipReason {
+    DeletedByUser,
+    #[serde(rename_all = "camelCase")]
+    ModifiedByUser {
+        original_md5: String,
+        current_md5: String,
+    },
+    CreatedByUser,
+    InSaveFolder,
+    KeptByUser,
+}
+
+#[derive(Type, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FEModVersionChange {
+    mod_id: String,
+    name: Option<String>,
+    old_version: Option<String>,
+    new_version: Option<String>,
+    old_path: String,
+    new_path: String,
+}
+
+impl From<manager::modpack::plan::ModpackUpdatePlan> for FEModpackUpdatePlan {
+    fn from(value: manager::modpack::plan::ModpackUpdatePlan) -> Self {
+        use manager::modpack::plan::{PlannedFile, SkipReason};
+
+        let files = |files: Vec<PlannedFile>| {
+            files
+                .into_iter()
+                .map(|file| FEPlannedFile {
+                    path: file.path,
+                    overridden: file.overridden,
+                })
+                .collect()
+        };
+
+        Self {
+            added: files(value.added),
+            removed: files(value.removed),
+            replaced: files(value.replaced),
+            skipped: value
+                .skipped
+                .into_iter()
+                .map(|file| FESkippedFile {
+                    path: file.path,
+                    reason: match file.reason {
+                        SkipReason::DeletedByUser => FESkipReason::DeletedByUser,
+                        SkipReason::ModifiedByUser {
+                            original_md5,
+                            current_md5,
+                        } => FESkipReason::ModifiedByUser {
+                            original_md5,
+                            current_md5,
+                        },
+                        SkipReason::CreatedByUser => FESkipReason::CreatedByUser,
+                        SkipReason::InSaveFolder => FESkipReason::InSaveFolder,
+                        SkipReason::KeptByUser => FESkipReason::KeptByUser,
+                    },
+                })
+                .collect(),
+            mod_changes: value
+                .mod_changes
+                .into_iter()
+                .map(|change| FEModVersionChange {
+                    mod_id: change.mod_id,
+                    name: change.name,
+                    old_version: change.old_version,
+                    new_version: change.new_version,
+                    old_path: change.old_path,
+                    new_path: change.new_path,
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Type, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FEModDependencyAnalysis {
+    issues: Vec<FEModDependencyIssue>,
+}
+
+#[derive(Type, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+enum FEModDependencyIssue {
+    #[serde(rename_all = "camelCase")]
+    Missing {
+        mod_id: String,
+        filename: String,
+        dependency: String,
+        versions: Vec<String>,
+    },
+    #[serde(rename_all = "camelCase")]
+    UnsatisfiedVersion {
+        mod_id: String,
+        filename: String,
+        dependency: String,
+        versions: Vec<String>,
+        found: Vec<FEDependencyProvider>,
+    },
+    #[serde(rename_all = "camelCase")]
+    Incompatible {
+        mod_id: String,
+        filename: String,
+        dependency: String,
+        versions: Vec<String>,
+        found: Vec<FEDependencyProvider>,
+        breaking: bool,
+    },
+    #[serde(rename_all = "camelCase")]
+    DuplicateModId {
+        modid: String,
+        mods: Vec<FEDependencyProvider>,
+    },
+}
+
+#[derive(Type, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+enum FEDependencyProvider {
+    #[serde(rename_all = "camelCase")]
+    Instance { version: Option<String> },
+    #[serde(rename_all = "camelCase")]
+    Mod {
+        mod_id: String,
+        filename: String,
+        version: Option<String>,
+    },
+}
+
+impl From<domain::ModDependencyAnalysis> for FEModDependencyAnalysis {
+    fn from(value: domain::ModDependencyAnalysis) -> Self {
+        Self {
+            issues: value.issues.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<domain::ModDependencyIssue> for FEModDependencyIssue {
+    fn from(value: domain::ModDependencyIssue) -> Self {
+        use domain::ModDependencyIssue as Issue;
+
+        let providers = |found: Vec<domain::DependencyProvider>| -> Vec<FEDependencyProvider> {
+            found.into_iter().map(FEDependencyProvider::from).collect()
+        };
+
+        match value {
+            Issue::Missing {
+                mod_id,
+                filename,
+                dependency,
+                versions,
+            } => Self::Missing {
+                mod_id,
+                filename,
+                dependency,
+                versions,
+            },
+            Issue::UnsatisfiedVersion {
+                mod_id,
+                filename,
+                dependency,
+                versions,
+                found,
+            } => Self::UnsatisfiedVersion {
+                mod_id,
+                filename,
+                dependency,
+                versions,
+                found: providers(found),
+            },
+            Issue::Incompatible {
+                mod_id,
+                filename,
+                dependency,
+                versions,
+                found,
+                breaking,
+            } => Self::Incompatible {
+                mod_id,
+                filename,
+                dependency,
+                versions,
+                found: providers(found),
+                breaking,
+            },
+            Issue::DuplicateModId { modid, mods } => Self::DuplicateModId {
+                modid,
+                mods: providers(mods),
+            },
+        }
+    }
+}
+
+impl From<domain::DependencyProvider> for FEDependencyProvider {
+    fn from(value: domain::DependencyProvider) -> Self {
+        match value {
+            domain::DependencyProvider::Instance { version } => Self::Instance { version },
+            domain::DependencyProvider::Mod {
+                mod_id,
+                filename,
+                version,
+            } => Self::Mod {
+                mod_id,
+                filename,
+                version,
+            },
+        }
+    }
 }
 
 #[derive(Type, Debug, Deserialize, Serialize)]
@@ -733,6 +1057,7 @@ struct InstanceMod {
 enum ModSource {
     Curseforge(CurseforgeMod),
     Modrinth(ModrinthMod),
+    Source(SourceMod),
 }
 
 #[derive(Type, Debug, Deserialize)]
@@ -753,6 +1078,21 @@ struct ModrinthMod {
     version_id: String,
 }
 
+#[derive(Type, Debug, Deserialize)]
+struct SourceMod {
+    source_id: String,
+    repository: Option<String>,
+    project_id: String,
+    version_id: String,
+}
+
+#[derive(Type, Debug, Deserialize)]
+struct SourceProject {
+    source_id: String,
+    repository: Option<String>,
+    project_id: String,
+}
+
 #[derive(Type, Debug, Deserialize)]
 struct InstallMod {
     instance_id: FEInstanceId,
@@ -777,6 +1117,7 @@ struct InstallLatestMod {
 enum LatestModSource {
     Curseforge(u32),
     Modrinth(String),
+    Source(SourceProject),
 }
 
 #[derive(Type, Debug, Serialize, Deserialize)]
@@ -987,6 +1328,7 @@ struct Mod {
     metadata: Option<ModFileMetadata>,
     curseforge: Option<CurseForgeModMetadata>,
     modrinth: Option<ModrinthModMetadata>,
+    source: Option<SourceModMetadata>,
     has_update: bool,
 }
 
@@ -1029,6 +1371,16 @@ struct ModrinthModMetadata {
     has_image: bool,
 }
 
+#[derive(Type, Serialize, Debug)]
+struct SourceModMetadata {
+    source_id: String,
+    repository: String,
+    project_id: String,
+    version_id: String,
+    name: String,
+    version: String,
+}
+
 #[derive(Type, Deserialize, Debug)]
 struct ExploreQuery {
     instance_id: FEInstanceId,
@@ -1437,6 +1789,7 @@ impl From<domain::Mod> for Mod {
             metadata: value.metadata.map(Into::into),
             curseforge: value.curseforge.map(Into::into),
             modrinth: value.modrinth.map(Into::into),
+            source: value.source.map(Into::into),
             has_update: value.has_update,
         }
     }
@@ -1490,6 +1843,19 @@ impl From<domain::ModrinthModMetadata> for ModrinthModMetadata {
     }
 }
 
+impl From<domain::SourceModMetadata> for SourceModMetadata {
+    fn from(value: domain::SourceModMetadata) -> Self {
+        Self {
+            source_id: value.source_id,
+            repository: value.repository,
+            project_id: value.project_id,
+            version_id: value.version_id,
+            name: value.name,
+            version: value.version,
+        }
+    }
+}
+
 impl From<domain::GameLogId> for GameLogId {
     fn from(value: domain::GameLogId) -> Self {
         Self(value.0)
@@ -1698,21 +2064,32 @@ impl From<importer::FullImportScanStatus> for FullImportScanStatus {
 
 mod log {
     use axum::extract::{ws::Message, WebSocketUpgrade};
+    use axum::response::Response;
+    use axum::Extension;
     use tracing::{error, trace};
 
     use super::*;
+    use crate::session::SessionToken;
 
     #[derive(Debug, Deserialize)]
     pub struct LogQuery {
         id: i32,
     }
 
-    #[tracing::instrument(skip(app))]
+    #[tracing::instrument(skip(app, headers, token))]
     pub async fn log_handler(
         Query(query): Query<LogQuery>,
         req: WebSocketUpgrade,
+        headers: HeaderMap,
         State(app): State<App>,
-    ) -> impl IntoResponse {
+        Extension(token): Extension<SessionToken>,
+    ) -> Response {
+        let Some(protocol) = token.verify_protocol(&headers) else {
+            return StatusCode::UNAUTHORIZED.into_response();
+        };
+
+        let req = req.protocols([protocol]);
+
         req.on_upgrade(move |mut socket| async move {
             tracing::info!("starting log stream");
 
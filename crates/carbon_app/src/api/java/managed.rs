@@ -9,6 +9,8 @@ use crate::domain::java::JavaVersion;
 #[serde(rename_all = "camelCase")]
 pub enum FEVendor {
     Azul,
+    Adoptium,
+    Mojang,
 }
 
 impl From<crate::domain::java::JavaVendor> for FEVendor {
@@ -16,6 +18,8 @@ impl From<crate::domain::java::JavaVendor> for FEVendor {
         use crate::domain::java::JavaVendor;
         match v {
             JavaVendor::Azul => Self::Azul,
+            JavaVendor::Adoptium => Self::Adoptium,
+            JavaVendor::Mojang => Self::Mojang,
         }
     }
 }
@@ -24,6 +28,8 @@ impl From<FEVendor> for crate::domain::java::JavaVendor {
     fn from(v: FEVendor) -> Self {
         match v {
             FEVendor::Azul => Self::Azul,
+            FEVendor::Adoptium => Self::Adoptium,
+            FEVendor::Mojang => Self::Mojang,
         }
     }
 }
@@ -118,6 +124,7 @@ impl TryFrom<FEManagedJavaVersion> for crate::managers::java::managed::ManagedJa
             name: v.name,
             download_url: v.download_url,
             java_version: JavaVersion::try_from(&*v.java_version)?,
+            checksum: None,
         })
     }
 }
@@ -4,6 +4,7 @@ use crate::api::router::router;
 use crate::domain::java::{
     JavaComponentType, JavaVendor, SystemJavaProfileName, SYSTEM_JAVA_PROFILE_NAME_PREFIX,
 };
+use crate::managers::java::java_checker::RealJavaChecker;
 use crate::{api::keys::java::*, domain::java::Java};
 use rspc::RouterBuilder;
 use serde::{Deserialize, Serialize};
@@ -94,6 +95,14 @@ pub(super) fn mount() -> RouterBuilder<App> {
         mutation DELETE_JAVA_VERSION[app, args: String] {
             app.java_manager().delete_java_version(args).await
         }
+
+        mutation GARBAGE_COLLECT_MANAGED_JAVAS[app, args: ()] {
+            let removed = app
+                .java_manager()
+                .garbage_collect_managed_javas(&RealJavaChecker)
+                .await?;
+            Ok(removed.len() as u32)
+        }
     }
 }
 
@@ -43,8 +43,18 @@ keys! {
         ENROLL_CANCEL                               = "enroll.cancel";
         ENROLL_GET_STATUS                           = "enroll.getStatus";
         ENROLL_FINALIZE                             = "enroll.finalize";
+        ADD_OFFLINE_ACCOUNT                         = "addOfflineAccount";
+        ADD_YGGDRASIL_ACCOUNT                       = "addYggdrasilAccount";
         REFRESH_ACCOUNT                             = "refreshAccount";
         GET_HEAD                                    = "getHead";
+        UPLOAD_SKIN                                 = "uploadSkin";
+        RESET_SKIN                                  = "resetSkin";
+        GET_CAPES                                   = "getCapes";
+        SET_ACTIVE_CAPE                             = "setActiveCape";
+        GET_SAVED_SKINS                             = "getSavedSkins";
+        SAVE_SKIN                                   = "saveSkin";
+        DELETE_SAVED_SKIN                           = "deleteSavedSkin";
+        APPLY_SAVED_SKIN                            = "applySavedSkin";
 
         PEEK_GDL_ACCOUNT                            = "peekGdlAccount";
         GET_GDL_ACCOUNT                             = "getGdlAccount";
@@ -72,6 +82,7 @@ keys! {
         VALIDATE_CUSTOM_JAVA_PATH                   = "validateCustomJavaPath";
         CREATE_CUSTOM_JAVA_VERSION                  = "createCustomJavaVersion";
         DELETE_JAVA_VERSION                         = "deleteJavaVersion";
+        GARBAGE_COLLECT_MANAGED_JAVAS               = "garbageCollectManagedJavas";
         SYSTEM_JAVA_PROFILE_ASSIGNMENTS             = "systemJavaProfileAssignments";
     }
 
@@ -90,6 +101,9 @@ keys! {
         CREATE_GROUP                                = "createGroup";
         CREATE_INSTANCE                             = "createInstance";
         CHANGE_MODPACK                              = "changeModpack";
+        PREVIEW_MODPACK_CHANGE                      = "previewModpackChange";
+        GET_MODPACK_UPDATE_PLAN                     = "getModpackUpdatePlan";
+        CANCEL_MODPACK_CHANGE                       = "cancelModpackChange";
         LOAD_ICON_URL                               = "loadIconUrl";
         DELETE_GROUP                                = "deleteGroup";
         DELETE_INSTANCE                             = "deleteInstance";
@@ -114,6 +128,7 @@ keys! {
         UPDATE_MOD                                  = "updateMod";
         FIND_MOD_UPDATE                             = "findModUpdate";
         GET_MOD_SOURCES                             = "getModSources";
+        ANALYZE_MOD_DEPENDENCIES                    = "analyzeModDependencies";
         GET_IMPORTABLE_ENTITIES                     = "getImportableEntities";
         GET_IMPORT_ENTITY_DEFAULT_PATH              = "getImportEntityDefaultPath";
         SET_IMPORT_SCAN_TARGET                      = "setImportScanTarget";
@@ -172,5 +187,12 @@ keys! {
         MODRINTH_GET_TEAM                           = "modrinth.getTeam";
 
         UNIFIED_SEARCH                              = "unifiedSearch";
+        FEDERATED_SEARCH                            = "federatedSearch";
+
+        SOURCES_GET_SOURCES                         = "sources.getSources";
+        SOURCES_GET_PROJECT_VERSIONS                = "sources.getProjectVersions";
+        SOURCES_GET_CREDENTIALS                     = "sources.getCredentials";
+        SOURCES_SET_CREDENTIAL                      = "sources.setCredential";
+        SOURCES_REMOVE_CREDENTIAL                   = "sources.removeCredential";
     }
 }
@@ -1,9 +1,12 @@
 use crate::managers::{App, AppInner};
+use crate::session::SessionToken;
 use crate::{app_version, managers};
 use async_stream::stream;
 use axum::extract::ws::Message;
 use axum::extract::{State, WebSocketUpgrade};
-use axum::response::IntoResponse;
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::Extension;
 use rspc::RouterBuilder;
 use serde::{Deserialize, Serialize};
 use specta::Type;
@@ -80,26 +83,33 @@ pub fn build_axum_vanilla_router() -> axum::Router<Arc<AppInner>> {
 
 async fn invalidation_ws_handler(
     req: WebSocketUpgrade,
+    headers: HeaderMap,
     State(app): State<Arc<AppInner>>,
-) -> impl IntoResponse {
-    req.on_upgrade(|mut socket| async move {
-        let mut channel = app.invalidation_channel.subscribe();
-        info!("Invalidation channel connected");
-        while let Ok(event) = channel.recv().await {
-            let Ok(message) = serde_json::to_string(&event) else {
-                error!("Failed to serialize invalidation event: {:?}", event);
-                continue;
-            };
-            match socket.send(Message::Text(message)).await {
-                Ok(_) => {}
-                Err(e) => {
-                    error!("Failed to send invalidation event: {:?}", e);
+    Extension(token): Extension<SessionToken>,
+) -> Response {
+    let Some(protocol) = token.verify_protocol(&headers) else {
+        return StatusCode::UNAUTHORIZED.into_response();
+    };
+
+    req.protocols([protocol])
+        .on_upgrade(|mut socket| async move {
+            let mut channel = app.invalidation_channel.subscribe();
+            info!("Invalidation channel connected");
+            while let Ok(event) = channel.recv().await {
+                let Ok(message) = serde_json::to_string(&event) else {
+                    error!("Failed to serialize invalidation event: {:?}", event);
+                    continue;
+                };
+                match socket.send(Message::Text(message)).await {
+                    Ok(_) => {}
+                    Err(e) => {
+                        error!("Failed to send invalidation event: {:?}", e);
+                    }
                 }
             }
-        }
 
-        info!("Invalidation channel disconnected");
-    })
+            info!("Invalidation channel disconnected");
+        })
 }
 
 #[derive(Type, Debug, Deserialize, Clone)]
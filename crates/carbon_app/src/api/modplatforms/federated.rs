@@ -0,0 +1,778 @@
+//! Searches CurseForge and Modrinth at once and merges the hits into a single
+//! ranked list, pairing up projects published on both platforms.
+
+use std::collections::{HashMap, HashSet};
+
+use carbon_platforms::modrinth::project::ProjectType;
+use carbon_platforms::modrinth::search::VersionHashesQuery;
+use carbon_platforms::modrinth::version::HashAlgorithm;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use tracing::warn;
+
+use super::curseforge::filters::{CFFEModSearchSortField, CFFEModSearchSortOrder};
+use super::curseforge::structs::{CFFEHashAlgo, CFFEMod};
+use super::filters::{
+    And, FEUnifiedModLoaderType, FEUnifiedModSortIndex, FEUnifiedSearchCategoryID,
+    FEUnifiedSearchParameters, FEUnifiedSearchType, Or,
+};
+use super::modrinth::filters::MRFESearchIndex;
+use super::modrinth::structs::MRFEProjectSearchResult;
+use super::responses::FEUnifiedPagination;
+use super::{curseforge, modrinth, FESearchAPI};
+use crate::managers::App;
+
+/// Constant of the reciprocal rank fusion, dampens the weight of the top hits.
+const RANK_FUSION_K: f64 = 60.0;
+
+/// CurseForge filters by a single game version, so it is searched once per
+/// requested version, up to this many.
+const MAX_CURSEFORGE_GAME_VERSIONS: usize = 4;
+
+#[derive(Type, Debug, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FEFederatedSearchParameters {
+    pub search_query: Option<String>,
+    pub categories: Option<And<FEUnifiedSearchCategoryID>>,
+    pub game_versions: Option<Or<String>>,
+    pub modloaders: Option<Or<FEUnifiedModLoaderType>>,
+    pub project_type: Option<FEUnifiedSearchType>,
+    pub sort_index: Option<FEFederatedSortIndex>,
+    /// Offset applied to each platform.
+    pub index: Option<u32>,
+    /// Page size of each platform, pages hold up to twice as many hits.
+    pub page_size: Option<u32>,
+}
+
+/// Sort orders both platforms can apply.
+#[derive(Type, Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum FEFederatedSortIndex {
+    #[default]
+    Relevance,
+    Popularity,
+    Downloads,
+    Updated,
+}
+
+#[derive(Type, Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum FEFederatedMatch {
+    Slug,
+    Name,
+    FileHash,
+}
+
+#[derive(Type, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FEFederatedSearchResult {
+    pub platforms: Vec<FESearchAPI>,
+    /// How the CurseForge and Modrinth projects were found to be the same.
+    pub matched_by: Option<FEFederatedMatch>,
+    pub curseforge: Option<CFFEMod>,
+    pub modrinth: Option<MRFEProjectSearchResult>,
+}
+
+#[derive(Type, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FEFederatedSearchResponse {
+    pub data: Vec<FEFederatedSearchResult>,
+    /// Totals are the sum of both platforms, projects on both are counted twice.
+    pub pagination: FEUnifiedPagination,
+    /// Platforms left out because they cannot apply the filters or did not respond.
+    pub skipped_apis: Vec<FESearchAPI>,
+}
+
+pub async fn search(
+    app: &App,
+    mut params: FEFederatedSearchParameters,
+) -> anyhow::Result<FEFederatedSearchResponse> {
+    if let Some(categories) = params.categories.take() {
+        params.categories = Some(map_categories(app, categories, params.project_type).await);
+    }
+
+    params.game_versions = params
+        .game_versions
+        .take()
+        .map(normalize_game_versions)
+        .filter(|versions| !versions.0.is_empty());
+
+    let search_curseforge = async {
+        if !params.supported_by(&FESearchAPI::Curseforge) {
+            return None;
+        }
+
+        let modplatforms = app.modplatforms_manager();
+        let searches = params
+            .curseforge_searches()
+            .into_iter()
+            .map(|search_params| {
+                let search_params: curseforge::filters::CFFEModSearchParameters =
+                    search_params.into();
+                modplatforms.curseforge.search(search_params.into())
+            });
+
+        let responses = futures::future::try_join_all(searches)
+            .await
+            .map_err(|e| warn!("Federated search failed on curseforge: {e:?}"))
+            .ok()?;
+
+        let mut responses = responses
+            .into_iter()
+            .map(curseforge::responses::FEModSearchResponse::from)
+            .collect::<Vec<_>>();
+
+        if responses.len() == 1 {
+            return responses.pop();
+        }
+
+        // a project listed under several versions is only counted once
+        let pagination = responses
+            .iter_mut()
+            .filter_map(|response| response.pagination.take())
+            .max_by_key(|pagination| pagination.total_count);
+
+        let mut seen = HashSet::new();
+        let data = round_robin(responses.into_iter().map(|response| response.data))
+            .filter(|hit| seen.insert(hit.id))
+            .collect();
+
+        Some(curseforge::responses::FEModSearchResponse { data, pagination })
+    };
+
+    let search_modrinth = async {
+        if !params.supported_by(&FESearchAPI::Modrinth) {
+            return None;
+        }
+
+        let search_params: modrinth::filters::MRFEProjectSearchParameters =
+            params.unified(FESearchAPI::Modrinth).into();
+
+        let response = app
+            .modplatforms_manager()
+            .modrinth
+            .search(search_params.into())
+            .await
+            .map_err(|e| warn!("Federated search failed on modrinth: {e:?}"))
+            .ok()?;
+
+        Some(modrinth::responses::MRFEProjectSearchResponse::from(
+            response,
+        ))
+    };
+
+    let (curseforge, modrinth) = tokio::join!(search_curseforge, search_modrinth);
+
+    let mut skipped_apis = Vec::new();
+    let mut total_count = 0;
+
+    let cf_hits = match curseforge {
+        Some(response) => {
+            total_count += response
+                .pagination
+                .map(|pagination| pagination.total_count as u32)
+                .unwrap_or(response.data.len() as u32);
+            response.data
+        }
+        None => {
+            skipped_apis.push(FESearchAPI::Curseforge);
+            Vec::new()
+        }
+    };
+
+    let mr_hits = match modrinth {
+        Some(response) => {
+            total_count += response.total_hits;
+            response.hits
+        }
+        None => {
+            skipped_apis.push(FESearchAPI::Modrinth);
+            Vec::new()
+        }
+    };
+
+    if skipped_apis.len() == 2 {
+        anyhow::bail!("Neither curseforge nor modrinth could be searched with these filters");
+    }
+
+    let cf_keys = cf_hits
+        .iter()
+        .map(|hit| HitKey {
+            slug: &hit.slug,
+            name: &hit.name,
+            downloads: hit.download_count as u64,
+        })
+        .collect::<Vec<_>>();
+
+    let mr_keys = mr_hits
+        .iter()
+        .map(|hit| HitKey {
+            slug: &hit.slug,
+            name: &hit.title,
+            downloads: hit.downloads as u64,
+        })
+        .collect::<Vec<_>>();
+
+    let hash_pairs = match cf_hits.is_empty() || mr_hits.is_empty() {
+        true => HashSet::new(),
+        false => pair_by_file_hashes(app, &cf_hits, &mr_hits).await,
+    };
+
+    let merged = merge_hits(
+        &cf_keys,
+        &mr_keys,
+        &hash_pairs,
+        params.sort_index.unwrap_or_default(),
+    );
+
+    let mut cf_hits = cf_hits.into_iter().map(Some).collect::<Vec<_>>();
+    let mut mr_hits = mr_hits.into_iter().map(Some).collect::<Vec<_>>();
+
+    let data = merged
+        .into_iter()
+        .map(|hit| {
+            let curseforge = hit.curseforge.and_then(|i| cf_hits[i].take());
+            let modrinth = hit.modrinth.and_then(|i| mr_hits[i].take());
+
+            FEFederatedSearchResult {
+                platforms: [
+                    curseforge.as_ref().map(|_| FESearchAPI::Curseforge),
+                    modrinth.as_ref().map(|_| FESearchAPI::Modrinth),
+                ]
+                .into_iter()
+                .flatten()
+                .collect(),
+                matched_by: hit.matched_by,
+                curseforge,
+                modrinth,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    Ok(FEFederatedSearchResponse {
+        pagination: FEUnifiedPagination {
+            index: params.index.unwrap_or(0),
+            page_size: params.page_size.unwrap_or(data.len() as u32),
+            result_count: data.len() as u32,
+            total_count,
+        },
+        data,
+        skipped_apis,
+    })
+}
+
+impl FEFederatedSearchParameters {
+    /// A platform that cannot apply a filter would return unfiltered results.
+    fn supported_by(&self, api: &FESearchAPI) -> bool {
+        let loaders = self.modloaders.as_ref().map_or(true, |loaders| {
+            loaders.iter().any(|loader| match api {
+                FESearchAPI::Curseforge => {
+                    curseforge::structs::CFFEModLoaderType::try_from(loader.clone()).is_ok()
+                }
+                FESearchAPI::Modrinth => {
+                    modrinth::structs::MRFELoaderType::try_from(loader.clone()).is_ok()
+                }
+            })
+        });
+
+        let categories = self.categories.as_ref().map_or(true, |categories| {
+            categories.iter().all(|group| {
+                group.iter().any(|category| {
+                    matches!(
+                        (api, category),
+                        (
+                            FESearchAPI::Curseforge,
+                            FEUnifiedSearchCategoryID::Curseforge(_)
+                        ) | (
+                            FESearchAPI::Modrinth,
+                            FEUnifiedSearchCategoryID::Modrinth(_)
+                        )
+                    )
+                })
+            })
+        });
+
+        let game_versions = match api {
+            FESearchAPI::Curseforge => self.game_versions.as_ref().map_or(true, |versions| {
+                versions.0.len() <= MAX_CURSEFORGE_GAME_VERSIONS
+            }),
+            FESearchAPI::Modrinth => true,
+        };
+
+        loaders && categories && game_versions
+    }
+
+    /// One search per requested game version, as CurseForge only applies the first.
+    fn curseforge_searches(&self) -> Vec<FEUnifiedSearchParameters> {
+        match &self.game_versions {
+            Some(versions) if versions.0.len() > 1 => versions
+                .iter()
+                .map(|version| FEUnifiedSearchParameters {
+                    game_versions: Some(Or(vec![version.clone()])),
+                    ..self.unified(FESearchAPI::Curseforge)
+                })
+                .collect(),
+            _ => vec![self.unified(FESearchAPI::Curseforge)],
+        }
+    }
+
+    fn unified(&self, api: FESearchAPI) -> FEUnifiedSearchParameters {
+        let sort_index = self.sort_index.unwrap_or_default();
+
+        let (sort_index, sort_order) = match api {
+            FESearchAPI::Curseforge => {
+                let field = match sort_index {
+                    // curseforge ranks by relevance when there is no sort field
+                    FEFederatedSortIndex::Relevance => None,
+                    FEFederatedSortIndex::Popularity => Some(CFFEModSearchSortField::Popularity),
+                    FEFederatedSortIndex::Downloads => Some(CFFEModSearchSortField::TotalDownloads),
+                    FEFederatedSortIndex::Updated => Some(CFFEModSearchSortField::LastUpdated),
+                };
+
+                let order = field.as_ref().map(|_| CFFEModSearchSortOrder::Descending);
+                (field.map(FEUnifiedModSortIndex::CurseForge), order)
+            }
+            FESearchAPI::Modrinth => {
+                let index = match sort_index {
+                    FEFederatedSortIndex::Relevance => MRFESearchIndex::Relevance,
+                    FEFederatedSortIndex::Popularity => MRFESearchIndex::Follows,
+                    FEFederatedSortIndex::Downloads => MRFESearchIndex::Downloads,
+                    FEFederatedSortIndex::Updated => MRFESearchIndex::Updated,
+                };
+
+                (Some(FEUnifiedModSortIndex::Modrinth(index)), None)
+            }
+        };
+
+        FEUnifiedSearchParameters {
+            search_query: self.search_query.clone(),
+            categories: self.categories.clone(),
+            game_versions: self.game_versions.clone(),
+            modloaders: self.modloaders.clone(),
+            project_type: self.project_type,
+            sort_index,
+            sort_order,
+            index: self.index,
+            page_size: self.page_size,
+            search_api: api,
+        }
+    }
+}
+
+/// Adds the category of the other platform with the same name to each
+/// group, so the filter applies on both.
+///
+/// Categories of a platform whose category list can't be fetched are left
+/// unmapped, that platform is then skipped by the search instead of failing it.
+async fn map_categories(
+    app: &App,
+    categories: And<FEUnifiedSearchCategoryID>,
+    project_type: Option<FEUnifiedSearchType>,
+) -> And<FEUnifiedSearchCategoryID> {
+    let modplatforms = app.modplatforms_manager();
+    let (cf_categories, mr_categories) = tokio::join!(
+        modplatforms.curseforge.get_categories(),
+        modplatforms.modrinth.get_categories(),
+    );
+
+    let cf_categories = cf_categories
+        .map(|categories| categories.data)
+        .unwrap_or_else(|e| {
+            warn!("Could not fetch curseforge categories to map them: {e:?}");
+            Vec::new()
+        });
+
+    let mr_categories = mr_categories
+        .map(|categories| categories.0)
+        .unwrap_or_else(|e| {
+            warn!("Could not fetch modrinth categories to map them: {e:?}");
+            Vec::new()
+        });
+
+    // curseforge class ids
+    let (cf_class, mr_type) = match project_type {
+        Some(FEUnifiedSearchType::ModPack) => (4471, ProjectType::Modpack),
+        _ => (6, ProjectType::Mod),
+    };
+
+    let cf_categories = cf_categories
+        .into_iter()
+        .filter(|category| category.class_id == Some(cf_class))
+        .collect::<Vec<_>>();
+
+    let mr_categories = mr_categories
+        .iter()
+        .filter(|category| category.project_type == mr_type)
+        .map(|category| category.name.clone())
+        .collect::<Vec<_>>();
+
+    categories
+        .into_iter()
+        .map(|group| {
+            let mut mapped = group
+                .iter()
+                .flat_map(|category| match category {
+                    FEUnifiedSearchCategoryID::Curseforge(id) => cf_categories
+                        .iter()
+                        .filter(|cf| cf.id == *id)
+                        .flat_map(|cf| {
+                            mr_categories
+                                .iter()
+                                .filter(|mr| same_name(&cf.slug, mr))
+                                .map(|mr| FEUnifiedSearchCategoryID::Modrinth(mr.clone()))
+                        })
+                        .collect::<Vec<_>>(),
+                    FEUnifiedSearchCategoryID::Modrinth(name) => cf_categories
+                        .iter()
+                        .filter(|cf| same_name(&cf.slug, name))
+                        .map(|cf| FEUnifiedSearchCategoryID::Curseforge(cf.id))
+                        .collect::<Vec<_>>(),
+                })
+                .collect::<Vec<_>>();
+
+            // the requested categories go first as curseforge only takes one
+            let mut group = group.0;
+            mapped.retain(|category| !group.contains(category));
+            group.extend(mapped);
+            Or(group)
+        })
+        .collect()
+}
+
+/// Pairs hits whose latest CurseForge files are known to Modrinth.
+async fn pair_by_file_hashes(
+    app: &App,
+    cf_hits: &[CFFEMod],
+    mr_hits: &[MRFEProjectSearchResult],
+) -> HashSet<(usize, usize)> {
+    let hashes = cf_hits
+        .iter()
+        .enumerate()
+        .flat_map(|(i, hit)| {
+            hit.latest_files
+                .iter()
+                .flat_map(|file| &file.hashes)
+                .filter(|hash| matches!(hash.algo, CFFEHashAlgo::Sha1))
+                .map(move |hash| (hash.value.to_lowercase(), i))
+        })
+        .collect::<HashMap<_, _>>();
+
+    if hashes.is_empty() {
+        return HashSet::new();
+    }
+
+    let query = VersionHashesQuery {
+        hashes: hashes.keys().cloned().collect(),
+        algorithm: HashAlgorithm::SHA1,
+    };
+
+    let versions = match app
+        .modplatforms_manager()
+        .modrinth
+        .get_versions_from_hash(&query)
+        .await
+    {
+        Ok(versions) => versions,
+        Err(e) => {
+            warn!("Could not look up curseforge file hashes on modrinth: {e:?}");
+            return HashSet::new();
+        }
+    };
+
+    let mr_index = mr_hits
+        .iter()
+        .enumerate()
+        .map(|(i, hit)| (&hit.project_id as &str, i))
+        .collect::<HashMap<_, _>>();
+
+    versions
+        .iter()
+        .filter_map(|(hash, version)| {
+            Some((
+                *hashes.get(&hash.to_lowercase())?,
+                *mr_index.get(&version.project_id as &str)?,
+            ))
+        })
+        .collect()
+}
+
+#[derive(Debug)]
+struct HitKey<'a> {
+    slug: &'a str,
+    name: &'a str,
+    downloads: u64,
+}
+
+#[derive(Debug, PartialEq)]
+struct MergedHit {
+    curseforge: Option<usize>,
+    modrinth: Option<usize>,
+    matched_by: Option<FEFederatedMatch>,
+}
+
+/// Trims the requested versions and drops empty and repeated ones.
+fn normalize_game_versions(versions: Or<String>) -> Or<String> {
+    let mut seen = HashSet::new();
+    versions
+        .into_iter()
+        .map(|version| version.trim().to_owned())
+        .filter(|version| !version.is_empty() && seen.insert(version.clone()))
+        .collect()
+}
+
+/// Takes the first item of each list, then the second of each and so on.
+fn round_robin<T>(lists: impl IntoIterator<Item = Vec<T>>) -> impl Iterator<Item = T> {
+    let mut lists = lists.into_iter().map(Vec::into_iter).collect::<Vec<_>>();
+
+    std::iter::from_fn(move || {
+        while !lists.is_empty() {
+            let item = lists[0].next();
+            let list = lists.remove(0);
+            if let Some(item) = item {
+                lists.push(list);
+                return Some(item);
+            }
+        }
+
+        None
+    })
+}
+
+/// Case and punctuation insensitive, so `World Gen` matches `world-gen`.
+fn same_name(a: &str, b: &str) -> bool {
+    let normalize = |s: &str| {
+        s.chars()
+            .filter(char::is_ascii_alphanumeric)
+            .map(|c| c.to_ascii_lowercase())
+            .collect::<String>()
+    };
+
+    let a = normalize(a);
+    !a.is_empty() && a == normalize(b)
+}
+
+/// Pairs up the hits of both platforms and ranks them, by combined downloads
+/// or by how high each platform ranked them.
+fn merge_hits(
+    cf_hits: &[HitKey<'_>],
+    mr_hits: &[HitKey<'_>],
+    hash_pairs: &HashSet<(usize, usize)>,
+    sort_index: FEFederatedSortIndex,
+) -> Vec<MergedHit> {
+    let mut mr_paired = vec![false; mr_hits.len()];
+    let mut cf_pairs = vec![None; cf_hits.len()];
+
+    // each kind of match is tried on all hits before the next, so a loose name
+    // match can't take a project that another hit shares its slug with
+    for matched_by in [
+        FEFederatedMatch::Slug,
+        FEFederatedMatch::Name,
+        FEFederatedMatch::FileHash,
+    ] {
+        for (cf, cf_hit) in cf_hits.iter().enumerate() {
+            if cf_pairs[cf].is_some() {
+                continue;
+            }
+
+            let mr = (0..mr_hits.len()).find(|&mr| {
+                !mr_paired[mr]
+                    && match matched_by {
+                        FEFederatedMatch::Slug => {
+                            cf_hit.slug.eq_ignore_ascii_case(mr_hits[mr].slug)
+                        }
+                        FEFederatedMatch::Name => same_name(cf_hit.name, mr_hits[mr].name),
+                        FEFederatedMatch::FileHash => hash_pairs.contains(&(cf, mr)),
+                    }
+            });
+
+            if let Some(mr) = mr {
+                mr_paired[mr] = true;
+                cf_pairs[cf] = Some((mr, matched_by));
+            }
+        }
+    }
+
+    let mut merged = Vec::with_capacity(cf_hits.len() + mr_hits.len());
+
+    merged.extend(
+        cf_pairs
+            .into_iter()
+            .enumerate()
+            .map(|(cf, pair)| MergedHit {
+                curseforge: Some(cf),
+                modrinth: pair.map(|(mr, _)| mr),
+                matched_by: pair.map(|(_, matched_by)| matched_by),
+            }),
+    );
+
+    merged.extend(
+        mr_paired
+            .iter()
+            .enumerate()
+            .filter(|(_, paired)| !**paired)
+            .map(|(mr, _)| MergedHit {
+                curseforge: None,
+                modrinth: Some(mr),
+                matched_by: None,
+            }),
+    );
+
+    let downloads = |hit: &MergedHit| {
+        hit.curseforge.map_or(0, |i| cf_hits[i].downloads)
+            + hit.modrinth.map_or(0, |i| mr_hits[i].downloads)
+    };
+
+    let rank_score = |hit: &MergedHit| {
+        [hit.curseforge, hit.modrinth]
+            .into_iter()
+            .flatten()
+            .map(|rank| 1.0 / (RANK_FUSION_K + rank as f64 + 1.0))
+            .sum::<f64>()
+    };
+
+    // stable, so equal hits keep curseforge first
+    match sort_index {
+        FEFederatedSortIndex::Downloads => {
+            merged.sort_by(|a, b| downloads(b).cmp(&downloads(a)));
+        }
+        _ => merged.sort_by(|a, b| {
+            rank_score(b)
+                .total_cmp(&rank_score(a))
+                .then_with(|| downloads(b).cmp(&downloads(a)))
+        }),
+    }
+
+    merged
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn key<'a>(slug: &'a str, name: &'a str, downloads: u64) -> HitKey<'a> {
+        HitKey {
+            slug,
+            name,
+            downloads,
+        }
+    }
+
+    fn hit(
+        curseforge: Option<usize>,
+        modrinth: Option<usize>,
+        matched_by: Option<FEFederatedMatch>,
+    ) -> MergedHit {
+        MergedHit {
+            curseforge,
+            modrinth,
+            matched_by,
+        }
+    }
+
+    #[test]
+    fn pairs_hits_across_platforms() {
+        let cf_hits = [
+            key("jei", "Just Enough Items (JEI)", 300),
+            key("create", "Create", 200),
+            key("sodium-cf", "Sodium", 100),
+            key("mekanism", "Mekanism", 50),
+        ];
+
+        let mr_hits = [
+            key("sodium", "Sodium", 150),
+            key("create", "Create", 250),
+            key("jei-mr", "JEI", 10),
+            key("lithium", "Lithium", 5),
+        ];
+
+        let hash_pairs = HashSet::from([(0, 2)]);
+
+        let merged = merge_hits(
+            &cf_hits,
+            &mr_hits,
+            &hash_pairs,
+            FEFederatedSortIndex::Relevance,
+        );
+
+        assert_eq!(
+            merged,
+            [
+                // a first place on either platform outranks two second places
+                hit(Some(0), Some(2), Some(FEFederatedMatch::FileHash)),
+                hit(Some(2), Some(0), Some(FEFederatedMatch::Name)),
+                hit(Some(1), Some(1), Some(FEFederatedMatch::Slug)),
+                hit(Some(3), None, None),
+                hit(None, Some(3), None),
+            ]
+        );
+
+        let merged = merge_hits(
+            &cf_hits,
+            &mr_hits,
+            &hash_pairs,
+            FEFederatedSortIndex::Downloads,
+        );
+
+        assert_eq!(
+            merged,
+            [
+                hit(Some(1), Some(1), Some(FEFederatedMatch::Slug)),
+                hit(Some(0), Some(2), Some(FEFederatedMatch::FileHash)),
+                hit(Some(2), Some(0), Some(FEFederatedMatch::Name)),
+                hit(Some(3), None, None),
+                hit(None, Some(3), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn slugs_pair_before_names() {
+        let cf_hits = [
+            key("sodium-fabric", "Sodium", 100),
+            key("sodium", "Sodium (Fabric)", 50),
+        ];
+
+        let mr_hits = [key("sodium", "Sodium", 150)];
+
+        let merged = merge_hits(
+            &cf_hits,
+            &mr_hits,
+            &HashSet::new(),
+            FEFederatedSortIndex::Downloads,
+        );
+
+        assert_eq!(
+            merged,
+            [
+                hit(Some(1), Some(0), Some(FEFederatedMatch::Slug)),
+                hit(Some(0), None, None),
+            ]
+        );
+    }
+
+    #[test]
+    fn game_versions_are_normalized() {
+        let versions = Or(vec![
+            String::from(" 1.20.1"),
+            String::from("1.20.1 "),
+            String::new(),
+            String::from("1.19.2"),
+        ]);
+
+        assert_eq!(normalize_game_versions(versions).0, ["1.20.1", "1.19.2"]);
+    }
+
+    #[test]
+    fn round_robin_takes_from_each_list() {
+        let merged = round_robin([vec![1, 2, 3], vec![], vec![4, 5]]).collect::<Vec<_>>();
+        assert_eq!(merged, [1, 4, 2, 5, 3]);
+    }
+
+    #[test]
+    fn names_match_loosely() {
+        assert!(same_name("world-gen", "worldgen"));
+        assert!(same_name("Just Enough Items", "just-enough-items"));
+        assert!(!same_name("sodium", "lithium"));
+        assert!(!same_name("!!", "??"));
+    }
+}
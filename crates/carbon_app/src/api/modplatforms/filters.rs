@@ -9,7 +9,7 @@ use super::curseforge::filters::CFFEModSearchParametersQuery;
 use super::modrinth;
 use super::{curseforge, FESearchAPI};
 
-#[derive(Type, Debug, Deserialize, Serialize)]
+#[derive(Type, Debug, Deserialize, Serialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct Or<T>(pub Vec<T>);
 
@@ -47,7 +47,7 @@ impl<T> FromIterator<T> for Or<T> {
     }
 }
 
-#[derive(Type, Debug, Deserialize, Serialize)]
+#[derive(Type, Debug, Deserialize, Serialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct And<T>(pub Vec<Or<T>>);
 
@@ -214,7 +214,7 @@ impl TryFrom<FEUnifiedModLoaderType> for modrinth::structs::MRFELoaderType {
     }
 }
 
-#[derive(Type, Debug, Deserialize, Serialize)]
+#[derive(Type, Debug, Deserialize, Serialize, Clone, Copy)]
 #[serde(rename_all = "camelCase")]
 pub enum FEUnifiedSearchType {
     Mod,
@@ -240,7 +240,7 @@ impl From<FEUnifiedSearchType> for curseforge::structs::CFFEClassId {
     }
 }
 
-#[derive(Type, Debug, Deserialize, Serialize)]
+#[derive(Type, Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
 pub enum FEUnifiedSearchCategoryID {
     Curseforge(i32),
@@ -10,10 +10,11 @@ use crate::{
             CURSEFORGE_GET_CATEGORIES, CURSEFORGE_GET_FILES, CURSEFORGE_GET_MOD,
             CURSEFORGE_GET_MODLOADERS, CURSEFORGE_GET_MODS, CURSEFORGE_GET_MOD_DESCRIPTION,
             CURSEFORGE_GET_MOD_FILE, CURSEFORGE_GET_MOD_FILES, CURSEFORGE_GET_MOD_FILE_CHANGELOG,
-            CURSEFORGE_SEARCH, MODRINTH_GET_CATEGORIES, MODRINTH_GET_LOADERS, MODRINTH_GET_PROJECT,
-            MODRINTH_GET_PROJECTS, MODRINTH_GET_PROJECT_TEAM, MODRINTH_GET_PROJECT_VERSIONS,
-            MODRINTH_GET_TEAM, MODRINTH_GET_VERSION, MODRINTH_GET_VERSIONS, MODRINTH_SEARCH,
-            UNIFIED_SEARCH,
+            CURSEFORGE_SEARCH, FEDERATED_SEARCH, MODRINTH_GET_CATEGORIES, MODRINTH_GET_LOADERS,
+            MODRINTH_GET_PROJECT, MODRINTH_GET_PROJECTS, MODRINTH_GET_PROJECT_TEAM,
+            MODRINTH_GET_PROJECT_VERSIONS, MODRINTH_GET_TEAM, MODRINTH_GET_VERSION,
+            MODRINTH_GET_VERSIONS, MODRINTH_SEARCH, SOURCES_GET_PROJECT_VERSIONS,
+            SOURCES_GET_SOURCES, UNIFIED_SEARCH,
         },
         modplatforms::curseforge::structs::CFFEModLoaderType,
         router::router,
@@ -28,9 +29,11 @@ use self::{
 };
 
 mod curseforge;
+mod federated;
 mod filters;
 mod modrinth;
 mod responses;
+pub mod source;
 
 #[derive(Type, Debug, Deserialize, Serialize, Clone)]
 #[serde(rename_all = "lowercase")]
@@ -129,6 +132,7 @@ mirror_into!(ModSources, carbon_platforms::ModSources, |value| Self {
 pub enum RemoteVersion {
     Curseforge(CFFEFile),
     Modrinth(MRFEVersion),
+    Source(source::FESourceVersion),
 }
 
 impl From<carbon_platforms::RemoteVersion> for RemoteVersion {
@@ -138,6 +142,7 @@ impl From<carbon_platforms::RemoteVersion> for RemoteVersion {
         match value {
             Other::Curseforge(cf) => Self::Curseforge(cf.into()),
             Other::Modrinth(mr) => Self::Modrinth(mr.into()),
+            Other::Source(version) => Self::Source(version.into()),
         }
     }
 }
@@ -293,5 +298,55 @@ pub(super) fn mount() -> RouterBuilder<App> {
                 }
             }
         }
+
+        query FEDERATED_SEARCH[app, search_params: federated::FEFederatedSearchParameters] {
+            federated::search(&app, search_params).await
+        }
+
+        // Mod sources
+        query SOURCES_GET_SOURCES[app, _args: ()] {
+            Ok(app
+                .modplatforms_manager()
+                .sources
+                .ids()
+                .into_iter()
+                .map(String::from)
+                .collect::<Vec<_>>())
+        }
+
+        query SOURCES_GET_PROJECT_VERSIONS[app, params: source::FESourceProjectVersionsParameters] {
+            let mod_source = app
+                .modplatforms_manager()
+                .mod_source(&params.source_id, params.repository.as_deref())
+                .await?;
+            let response = mod_source.list_versions(&params.project_id, &params.filters()).await?;
+
+            Ok(response
+                .into_iter()
+                .map(source::FESourceVersion::from)
+                .collect::<Vec<_>>())
+        }
+
+        query SOURCES_GET_CREDENTIALS[app, _args: ()] {
+            Ok(app
+                .modplatforms_manager()
+                .source_credentials()
+                .await?
+                .into_iter()
+                .map(source::FESourceCredentialEntry::from)
+                .collect::<Vec<_>>())
+        }
+
+        mutation SOURCES_SET_CREDENTIAL[app, args: source::FESetSourceCredential] {
+            app.modplatforms_manager()
+                .set_source_credential(&args.source_id, args.repository.as_deref(), args.credential.into())
+                .await
+        }
+
+        mutation SOURCES_REMOVE_CREDENTIAL[app, args: source::FESourceRepository] {
+            app.modplatforms_manager()
+                .remove_source_credential(&args.source_id, args.repository.as_deref())
+                .await
+        }
     }
 }
@@ -0,0 +1,126 @@
+//! Mod sources that are not full mod platforms, such as GitHub releases and
+//! maven repositories.
+
+use carbon_platforms::source::{SourceVersion, SourceVersionFilters};
+use carbon_repos::db::mod_source_credential;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+use super::ModChannel;
+use crate::managers::modplatforms::source::SourceCredential;
+
+#[derive(Type, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FESourceProjectVersionsParameters {
+    pub source_id: String,
+    /// Repository url, the source's default repository is used if missing.
+    pub repository: Option<String>,
+    pub project_id: String,
+    pub game_version: Option<String>,
+    pub loaders: Option<Vec<String>>,
+}
+
+impl FESourceProjectVersionsParameters {
+    pub fn filters(&self) -> SourceVersionFilters {
+        SourceVersionFilters {
+            game_version: self.game_version.clone(),
+            loaders: self.loaders.clone().unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Type, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FESourceVersion {
+    pub source_id: String,
+    pub repository: String,
+    pub project_id: String,
+    pub id: String,
+    pub name: String,
+    pub file_name: String,
+    pub url: String,
+    pub sha1: Option<String>,
+    pub sha256: Option<String>,
+    pub size: Option<u64>,
+    /// Empty if the source does not declare compatible game versions.
+    pub game_versions: Vec<String>,
+    /// Empty if the source does not declare compatible modloaders.
+    pub loaders: Vec<String>,
+    pub channel: ModChannel,
+    pub published: DateTime<Utc>,
+}
+
+impl From<SourceVersion> for FESourceVersion {
+    fn from(value: SourceVersion) -> Self {
+        Self {
+            source_id: value.source,
+            repository: value.repository,
+            project_id: value.project_id,
+            id: value.id,
+            name: value.name,
+            file_name: value.file_name,
+            url: value.url,
+            sha1: value.sha1,
+            sha256: value.sha256,
+            size: value.size,
+            game_versions: value.game_versions,
+            loaders: value.loaders,
+            channel: value.channel.into(),
+            published: value.published,
+        }
+    }
+}
+
+#[derive(Type, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FESetSourceCredential {
+    pub source_id: String,
+    /// Repository url, the source's default repository is used if missing.
+    pub repository: Option<String>,
+    pub credential: FESourceCredential,
+}
+
+#[derive(Type, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum FESourceCredential {
+    Token { token: String },
+    Basic { username: String, password: String },
+}
+
+impl From<FESourceCredential> for SourceCredential {
+    fn from(value: FESourceCredential) -> Self {
+        match value {
+            FESourceCredential::Token { token } => Self::Token { token },
+            FESourceCredential::Basic { username, password } => Self::Basic { username, password },
+        }
+    }
+}
+
+#[derive(Type, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FESourceRepository {
+    pub source_id: String,
+    /// Repository url, the source's default repository is used if missing.
+    pub repository: Option<String>,
+}
+
+/// A repository with a stored credential, without the credential itself.
+#[derive(Type, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FESourceCredentialEntry {
+    pub source_id: String,
+    pub repository: String,
+    /// `token` or `basic`
+    pub kind: String,
+}
+
+impl From<mod_source_credential::Data> for FESourceCredentialEntry {
+    fn from(value: mod_source_credential::Data) -> Self {
+        Self {
+            source_id: value.source_id,
+            repository: value.repository,
+            kind: value.kind,
+        }
+    }
+}
@@ -9,7 +9,10 @@ use crate::{
         },
         router::router,
     },
-    managers::App,
+    managers::{
+        settings::mirrors::{DownloadMirror, DownloadMirrors},
+        App,
+    },
 };
 use rspc::RouterBuilder;
 use serde::{Deserialize, Serialize};
@@ -201,6 +204,32 @@ impl TryFrom<String> for InstancesGroupBy {
     }
 }
 
+#[derive(Type, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FEDownloadMirror {
+    /// Url prefix the mirror stands in for, e.g. `https://libraries.minecraft.net`.
+    pub origin: String,
+    pub mirror: String,
+}
+
+impl From<DownloadMirror> for FEDownloadMirror {
+    fn from(value: DownloadMirror) -> Self {
+        Self {
+            origin: value.origin,
+            mirror: value.mirror,
+        }
+    }
+}
+
+impl From<FEDownloadMirror> for DownloadMirror {
+    fn from(value: FEDownloadMirror) -> Self {
+        Self {
+            origin: value.origin,
+            mirror: value.mirror,
+        }
+    }
+}
+
 #[derive(Type, Serialize)]
 #[serde(rename_all = "camelCase")]
 struct FESettings {
@@ -211,6 +240,9 @@ struct FESettings {
     release_channel: FEReleaseChannel,
     last_app_version: Option<String>,
     concurrent_downloads: i32,
+    concurrent_downloads_per_host: Option<i32>,
+    download_speed_limit: Option<i32>,
+    download_mirrors: Vec<FEDownloadMirror>,
     download_dependencies: bool,
     launcher_action_on_game_launch: FELauncherActionOnGameLaunch,
     show_app_close_warning: bool,
@@ -249,6 +281,14 @@ impl TryFrom<carbon_repos::db::app_configuration::Data> for FESettings {
             release_channel: data.release_channel.try_into()?,
             last_app_version: data.last_app_version,
             concurrent_downloads: data.concurrent_downloads,
+            concurrent_downloads_per_host: data.concurrent_downloads_per_host,
+            download_speed_limit: data.download_speed_limit,
+            download_mirrors: DownloadMirrors::from_settings(&data.download_mirrors)?
+                .mirrors()
+                .iter()
+                .cloned()
+                .map(FEDownloadMirror::from)
+                .collect(),
             download_dependencies: data.download_dependencies,
             show_news: data.show_news,
             show_featured: data.show_featured,
@@ -355,6 +395,12 @@ pub struct FESettingsUpdate {
     #[specta(optional)]
     pub concurrent_downloads: Option<Set<i32>>,
     #[specta(optional)]
+    pub concurrent_downloads_per_host: Option<Set<Option<i32>>>,
+    #[specta(optional)]
+    pub download_speed_limit: Option<Set<Option<i32>>>,
+    #[specta(optional)]
+    pub download_mirrors: Option<Set<Vec<FEDownloadMirror>>>,
+    #[specta(optional)]
     pub download_dependencies: Option<Set<bool>>,
     #[specta(optional)]
     pub instances_sort_by: Option<Set<InstancesSortBy>>,
@@ -46,6 +46,23 @@ pub enum Translation {
     InstanceImportMrpackMalformed,
     InstanceImportMrpackMissingManifest,
     InstanceImportMrpackMalformedManifest,
+    InstanceImportMmcBadConfigFile,
+    InstanceImportMmcUnsupportedInstanceType,
+    InstanceImportMmcMissingPack,
+    InstanceImportMmcMalformedPack,
+    InstanceImportMmcUnsupportedComponent {
+        uid: String,
+    },
+    InstanceImportAtlBadConfigFile,
+    InstanceImportAtlUnsupportedLoader {
+        loader: String,
+    },
+    InstanceImportTechnicMissingVersion,
+    InstanceImportTechnicMalformedVersion,
+    InstanceImportFtbBadConfigFile,
+    InstanceImportFtbUnsupportedLoader {
+        loader: String,
+    },
     InstanceExport,
     InstanceExportScanningMods,
     InstanceExportCacheMods,
@@ -0,0 +1,3 @@
+pub fn main() {
+    std::process::exit(carbon_app::cli::main());
+}
@@ -0,0 +1,366 @@
+use std::path::PathBuf;
+use std::process::ExitStatus;
+
+use anyhow::{anyhow, bail};
+use tokio::sync::watch;
+
+use super::{Command, ModSource};
+use crate::domain::instance::info::{GameVersion, ModLoader, StandardVersion};
+use crate::domain::instance::{ExportEntry, ExportTarget, GroupId, InstanceId};
+use crate::managers::instance::importer::{Entity, ImportEntry, ImportScanStatus};
+use crate::managers::instance::log::{GameLog, LogEntry, LogEntryLevel, LogEntrySourceKind};
+use crate::managers::instance::{InstanceVersionSource, ListInstanceStatus};
+use crate::managers::java::{discovery::RealDiscovery, java_checker::RealJavaChecker, JavaManager};
+use crate::managers::App;
+
+/// Runs a command, returning the exit code of the process.
+pub(super) async fn run(app: &App, command: Command) -> anyhow::Result<i32> {
+    match command {
+        Command::Help => unreachable!("help is printed before the app is started"),
+        Command::List => list(app).await?,
+        Command::Create {
+            name,
+            release,
+            modloader,
+            group,
+        } => create(app, name, release, modloader, group).await?,
+        Command::Import {
+            path,
+            entity,
+            name,
+            index,
+        } => import(app, path, entity, name, index).await?,
+        Command::Export {
+            instance,
+            save_path,
+            target,
+            bundle_addons,
+        } => export(app, instance, save_path, target, bundle_addons).await?,
+        Command::InstallMod {
+            instance,
+            source,
+            install_deps,
+        } => install_mod(app, instance, source, install_deps).await?,
+        Command::Prepare {
+            instance,
+            deep_check,
+        } => prepare(app, instance, deep_check).await?,
+        Command::Launch { instance } => return launch(app, instance).await,
+    }
+
+    Ok(0)
+}
+
+async fn list(app: &App) -> anyhow::Result<()> {
+    for group in app.instance_manager().list_groups().await? {
+        for instance in group.instances {
+            let (version, modloader) = match &instance.status {
+                ListInstanceStatus::Valid(valid) => (
+                    valid
+                        .mc_version
+                        .clone()
+                        .unwrap_or_else(|| String::from("-")),
+                    valid
+                        .modloader
+                        .map(|modloader| modloader.to_string())
+                        .unwrap_or_else(|| String::from("vanilla")),
+                ),
+                ListInstanceStatus::Invalid(_) => (String::from("invalid"), String::from("-")),
+            };
+
+            println!(
+                "{}\t{}\t{version}\t{modloader}\t{}",
+                instance.id, instance.name, group.name
+            );
+        }
+    }
+
+    Ok(())
+}
+
+async fn create(
+    app: &App,
+    name: String,
+    release: String,
+    modloader: Option<ModLoader>,
+    group: Option<i32>,
+) -> anyhow::Result<()> {
+    let group = match group {
+        Some(group) => GroupId(group),
+        None => app.instance_manager().get_default_group().await?,
+    };
+
+    let version = GameVersion::Standard(StandardVersion {
+        release,
+        modloaders: modloader.into_iter().collect(),
+    });
+
+    let id = app
+        .instance_manager()
+        .create_instance(
+            group,
+            name,
+            false,
+            InstanceVersionSource::Version(version),
+            String::new(),
+        )
+        .await?;
+
+    println!("{id}");
+
+    Ok(())
+}
+
+async fn import(
+    app: &App,
+    path: PathBuf,
+    entity: Option<Entity>,
+    name: Option<String>,
+    index: Option<u32>,
+) -> anyhow::Result<()> {
+    let entity = match entity {
+        Some(entity) => entity,
+        None => match path.extension().and_then(|ext| ext.to_str()) {
+            Some("zip") => Entity::CurseForgeZip,
+            Some("mrpack") => Entity::MRPack,
+            _ => bail!(
+                "cannot tell what to import from {}, pass --from",
+                path.display()
+            ),
+        },
+    };
+
+    let supported = Entity::list()
+        .into_iter()
+        .any(|(e, supported, _)| e == entity && supported);
+
+    if !supported {
+        bail!("importing from {entity:?} is not supported");
+    }
+
+    let importer = entity.create_importer();
+    importer.scan(app, path.clone()).await?;
+
+    let index = match (importer.get_status().await, index) {
+        (ImportScanStatus::NoResults, _) => {
+            bail!("found nothing to import in {}", path.display())
+        }
+        (ImportScanStatus::SingleResult(ImportEntry::Invalid(invalid)), _) => {
+            bail!("{} cannot be imported: {:?}", invalid.name, invalid.reason)
+        }
+        (ImportScanStatus::SingleResult(ImportEntry::Valid(_)), _) => 0,
+        (ImportScanStatus::MultiResult(_), Some(index)) => index,
+        (ImportScanStatus::MultiResult(entries), None) => {
+            eprintln!("Found several instances, pick one with --index:");
+            for (index, entry) in entries.iter().enumerate() {
+                match entry {
+                    ImportEntry::Valid(valid) => {
+                        eprintln!("  {index}\t{}", valid.instance_name)
+                    }
+                    ImportEntry::Invalid(invalid) => {
+                        eprintln!("  {index}\t{} (cannot be imported)", invalid.name)
+                    }
+                }
+            }
+
+            bail!("no instance picked");
+        }
+    };
+
+    let task = importer.begin_import(app, index, name).await?;
+    app.task_manager().wait(task).await?;
+
+    Ok(())
+}
+
+async fn export(
+    app: &App,
+    instance: InstanceId,
+    save_path: PathBuf,
+    target: ExportTarget,
+    bundle_addons: bool,
+) -> anyhow::Result<()> {
+    // everything in the instance folder
+    let filter = ExportEntry(
+        app.instance_manager()
+            .explore_data(instance, Vec::new())
+            .await?
+            .into_iter()
+            .map(|entry| (entry.name, None))
+            .collect(),
+    );
+
+    let task = app
+        .instance_manager()
+        .export_manager()
+        .export_instance(instance, target, save_path.clone(), bundle_addons, filter)
+        .await?;
+
+    app.task_manager().wait(task).await?;
+
+    println!("{}", save_path.display());
+
+    Ok(())
+}
+
+async fn install_mod(
+    app: &App,
+    instance: InstanceId,
+    source: ModSource,
+    install_deps: bool,
+) -> anyhow::Result<()> {
+    let task = match source {
+        ModSource::Curseforge {
+            project_id,
+            file_id,
+        } => {
+            app.instance_manager()
+                .install_curseforge_mod(instance, project_id, file_id, install_deps, None)
+                .await?
+        }
+        ModSource::Modrinth {
+            project_id,
+            version_id,
+        } => {
+            app.instance_manager()
+                .install_modrinth_mod(instance, project_id, version_id, install_deps, None)
+                .await?
+        }
+    };
+
+    app.task_manager().wait(task).await
+}
+
+async fn prepare(app: &App, instance: InstanceId, deep_check: bool) -> anyhow::Result<()> {
+    sync_javas(app).await?;
+
+    let (handle, task) = app
+        .instance_manager()
+        .prepare_game(instance, None, None, deep_check)
+        .await?;
+
+    handle.await?;
+    app.task_manager().wait(task).await
+}
+
+async fn launch(app: &App, instance: InstanceId) -> anyhow::Result<i32> {
+    sync_javas(app).await?;
+
+    let account = app
+        .account_manager()
+        .get_active_account()
+        .await?
+        .ok_or_else(|| anyhow!("no active account to launch with"))?;
+
+    let (handle, task) = app
+        .instance_manager()
+        .prepare_game(instance, Some(account), None, false)
+        .await?;
+
+    let log_id = app
+        .instance_manager()
+        .get_logs(instance)
+        .await
+        .into_iter()
+        .filter(|log| log.active)
+        .max_by_key(|log| log.id.0)
+        .ok_or_else(|| anyhow!("the game log was not created"))?
+        .id;
+
+    let log = app.instance_manager().get_log(log_id).await?;
+
+    // stop the game on ctrl-c and keep streaming until it is gone
+    let kill_on_interrupt = {
+        let app = app.clone();
+        tokio::spawn(async move {
+            if tokio::signal::ctrl_c().await.is_ok()
+                && app
+                    .instance_manager()
+                    .kill_instance(instance)
+                    .await
+                    .is_err()
+            {
+                // nothing to kill yet
+                std::process::exit(130);
+            }
+        })
+    };
+
+    let exit_status = stream_log(log).await;
+    kill_on_interrupt.abort();
+
+    handle.await?;
+    // fails if the game could not be prepared
+    app.task_manager().wait(task).await?;
+
+    match exit_status {
+        Some(status) => Ok(status.code().unwrap_or(1)),
+        None => bail!("the game did not start"),
+    }
+}
+
+/// Picks up javas installed since the last run, like the app does on startup.
+async fn sync_javas(app: &App) -> anyhow::Result<()> {
+    let auto_manage_java_system_profiles = app
+        .settings_manager()
+        .get_settings()
+        .await?
+        .auto_manage_java_system_profiles;
+
+    JavaManager::scan_and_sync(
+        auto_manage_java_system_profiles,
+        &app.prisma_client,
+        &RealDiscovery::new(app.settings_manager().runtime_path.clone()),
+        &RealJavaChecker,
+    )
+    .await
+}
+
+/// Prints log entries as they come until the log is closed, returning how
+/// the game exited.
+async fn stream_log(mut log: watch::Receiver<GameLog>) -> Option<ExitStatus> {
+    let mut printed = 0;
+
+    loop {
+        let closed = log.changed().await.is_err();
+
+        let (entries, exit_status) = {
+            let log = log.borrow_and_update();
+            (log.get_span(printed..).to_vec(), log.exit_status())
+        };
+
+        printed += entries.len();
+        for entry in &entries {
+            print_entry(entry);
+        }
+
+        if closed {
+            return exit_status;
+        }
+    }
+}
+
+fn print_entry(entry: &LogEntry) {
+    let message = entry.message.trim_end();
+
+    let line = match entry.source_kind {
+        LogEntrySourceKind::System => format!("[{}] {message}", entry.logger),
+        _ if entry.logger == "N/A" => message.to_string(),
+        _ => {
+            let level = match entry.level {
+                LogEntryLevel::Trace => "TRACE",
+                LogEntryLevel::Debug => "DEBUG",
+                LogEntryLevel::Info => "INFO",
+                LogEntryLevel::Warn => "WARN",
+                LogEntryLevel::Error => "ERROR",
+            };
+
+            format!("[{}/{level}] [{}]: {message}", entry.thread, entry.logger)
+        }
+    };
+
+    match entry.source_kind {
+        LogEntrySourceKind::StdErr => eprintln!("{line}"),
+        _ => println!("{line}"),
+    }
+}
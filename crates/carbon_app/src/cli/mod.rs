@@ -0,0 +1,526 @@
+//! `carbon`, a headless frontend for scripting instances without the app.
+//!
+//! Every invocation runs a single command against a runtime path and exits.
+//! `launch` exits with the exit code of the game.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::PathBuf;
+
+use thiserror::Error;
+
+use crate::domain::instance::info::{ModLoader, ModLoaderType};
+use crate::domain::instance::{ExportTarget, InstanceId};
+use crate::managers::instance::importer::Entity;
+use crate::managers::AppInner;
+use crate::{base_api_override, logger, runtime_path_override};
+
+mod commands;
+
+pub const USAGE: &str = "\
+Usage: carbon [--runtime_path <path>] [--base_api <url>] <command>
+
+Commands:
+  list                                   List instances as `id  name  version  modloader  group`
+  create <name> --version <release>      Create an instance and print its id
+         [--modloader <type>:<version>] [--group <id>]
+  import <path> [--from <source>]        Import an instance from a file or launcher folder
+         [--name <name>] [--index <n>]
+  export <instance> <path>               Export an instance as a modpack archive
+         [--format curseforge|modrinth] [--bundle-addons]
+  install-mod <instance>                 Install a mod and its dependencies
+         (--curseforge <project>:<file> | --modrinth <project>:<version>) [--no-deps]
+  prepare <instance> [--deep-check]      Download everything needed to launch an instance
+  launch <instance>                      Launch with the active account and stream the game log
+
+Import sources: legacy-gdlauncher, curseforge-zip, curseforge, mrpack, multimc,
+prismlauncher, atlauncher, technic, ftb
+
+Exit codes: 0 on success, 1 on failure, 2 on invalid usage.
+`launch` exits with the exit code of the game.";
+
+#[derive(Error, Debug, PartialEq, Eq)]
+#[error("{0}")]
+pub struct UsageError(String);
+
+#[derive(Debug, PartialEq, Eq)]
+struct Args {
+    runtime_path: Option<PathBuf>,
+    command: Command,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Command {
+    Help,
+    List,
+    Create {
+        name: String,
+        release: String,
+        modloader: Option<ModLoader>,
+        group: Option<i32>,
+    },
+    Import {
+        path: PathBuf,
+        entity: Option<Entity>,
+        name: Option<String>,
+        index: Option<u32>,
+    },
+    Export {
+        instance: InstanceId,
+        save_path: PathBuf,
+        target: ExportTarget,
+        bundle_addons: bool,
+    },
+    InstallMod {
+        instance: InstanceId,
+        source: ModSource,
+        install_deps: bool,
+    },
+    Prepare {
+        instance: InstanceId,
+        deep_check: bool,
+    },
+    Launch {
+        instance: InstanceId,
+    },
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum ModSource {
+    Curseforge {
+        project_id: u32,
+        file_id: u32,
+    },
+    Modrinth {
+        project_id: String,
+        version_id: String,
+    },
+}
+
+/// Runs the cli with the process arguments, returning the exit code.
+pub fn main() -> i32 {
+    let args = match Args::parse(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("error: {e}\n\n{USAGE}");
+            return 2;
+        }
+    };
+
+    if args.command == Command::Help {
+        println!("{USAGE}");
+        return 0;
+    }
+
+    tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .max_blocking_threads(256)
+        .build()
+        .unwrap()
+        .block_on(async move {
+            daedalus::Branding::set_branding(daedalus::Branding::new(
+                "gdlauncher".to_string(),
+                "".to_string(),
+            ))
+            .expect("Branding not to fail");
+
+            let runtime_path = match args.runtime_path {
+                Some(path) => {
+                    match std::fs::create_dir_all(&path).and_then(|_| dunce::canonicalize(&path)) {
+                        Ok(path) => path,
+                        Err(e) => {
+                            eprintln!("error: invalid runtime path {}: {e}", path.display());
+                            return 1;
+                        }
+                    }
+                }
+                None if cfg!(debug_assertions) => {
+                    runtime_path_override::get_runtime_path_override().await
+                }
+                None => {
+                    eprintln!("error: --runtime_path is required\n\n{USAGE}");
+                    return 2;
+                }
+            };
+
+            let _guard = logger::setup_cli_logger(&runtime_path).await;
+            let base_api = base_api_override::get_base_api_override().await;
+
+            let (invalidation_sender, _) = tokio::sync::broadcast::channel(1000);
+            let app = AppInner::new(invalidation_sender, runtime_path, base_api).await;
+
+            app.instance_manager().wait_for_initial_scan().await;
+
+            match commands::run(&app, args.command).await {
+                Ok(code) => code,
+                Err(e) => {
+                    tracing::error!({ error = ?e }, "cli command failed");
+                    eprintln!("error: {e:#}");
+                    1
+                }
+            }
+        })
+}
+
+impl Args {
+    fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, UsageError> {
+        let mut runtime_path = None;
+        let mut rest = Vec::new();
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match &*arg {
+                "--runtime_path" => {
+                    runtime_path = Some(PathBuf::from(next_value(&mut args, &arg)?))
+                }
+                // read by `get_base_api_override`
+                "--base_api" => {
+                    next_value(&mut args, &arg)?;
+                }
+                "-h" | "--help" => {
+                    return Ok(Self {
+                        runtime_path,
+                        command: Command::Help,
+                    })
+                }
+                _ => rest.push(arg),
+            }
+        }
+
+        let mut rest = rest.into_iter();
+        let Some(command) = rest.next() else {
+            return Ok(Self {
+                runtime_path,
+                command: Command::Help,
+            });
+        };
+
+        let command = match &*command {
+            "help" => Command::Help,
+            "list" => {
+                CommandArgs::parse(rest, &[], &[])?.finish()?;
+                Command::List
+            }
+            "create" => {
+                let mut args =
+                    CommandArgs::parse(rest, &["--version", "--modloader", "--group"], &[])?;
+                let name = args.positional("name")?;
+                if name.trim().is_empty() {
+                    return Err(UsageError(String::from("instance name cannot be empty")));
+                }
+
+                let command = Command::Create {
+                    name,
+                    release: args.required("--version")?,
+                    modloader: args.value("--modloader").map(parse_modloader).transpose()?,
+                    group: args
+                        .value("--group")
+                        .map(|v| parse_number(&v))
+                        .transpose()?,
+                };
+                args.finish()?;
+                command
+            }
+            "import" => {
+                let mut args = CommandArgs::parse(rest, &["--from", "--name", "--index"], &[])?;
+                let command = Command::Import {
+                    path: PathBuf::from(args.positional("path")?),
+                    entity: args.value("--from").map(|v| parse_entity(&v)).transpose()?,
+                    name: args.value("--name"),
+                    index: args
+                        .value("--index")
+                        .map(|v| parse_number(&v))
+                        .transpose()?,
+                };
+                args.finish()?;
+                command
+            }
+            "export" => {
+                let mut args = CommandArgs::parse(rest, &["--format"], &["--bundle-addons"])?;
+                let command = Command::Export {
+                    instance: parse_instance(&args.positional("instance")?)?,
+                    save_path: PathBuf::from(args.positional("path")?),
+                    target: match args.value("--format").as_deref() {
+                        None | Some("curseforge") => ExportTarget::Curseforge,
+                        Some("modrinth") => ExportTarget::Modrinth,
+                        Some(other) => {
+                            return Err(UsageError(format!("unknown export format `{other}`")))
+                        }
+                    },
+                    bundle_addons: args.flag("--bundle-addons"),
+                };
+                args.finish()?;
+                command
+            }
+            "install-mod" => {
+                let mut args =
+                    CommandArgs::parse(rest, &["--curseforge", "--modrinth"], &["--no-deps"])?;
+                let instance = parse_instance(&args.positional("instance")?)?;
+
+                let source = match (args.value("--curseforge"), args.value("--modrinth")) {
+                    (Some(curseforge), None) => {
+                        let (project_id, file_id) = split_pair(&curseforge, "--curseforge")?;
+                        ModSource::Curseforge {
+                            project_id: parse_number(project_id)?,
+                            file_id: parse_number(file_id)?,
+                        }
+                    }
+                    (None, Some(modrinth)) => {
+                        let (project_id, version_id) = split_pair(&modrinth, "--modrinth")?;
+                        ModSource::Modrinth {
+                            project_id: project_id.to_string(),
+                            version_id: version_id.to_string(),
+                        }
+                    }
+                    _ => {
+                        return Err(UsageError(String::from(
+                            "exactly one of --curseforge or --modrinth is required",
+                        )))
+                    }
+                };
+
+                let command = Command::InstallMod {
+                    instance,
+                    source,
+                    install_deps: !args.flag("--no-deps"),
+                };
+                args.finish()?;
+                command
+            }
+            "prepare" => {
+                let mut args = CommandArgs::parse(rest, &[], &["--deep-check"])?;
+                let command = Command::Prepare {
+                    instance: parse_instance(&args.positional("instance")?)?,
+                    deep_check: args.flag("--deep-check"),
+                };
+                args.finish()?;
+                command
+            }
+            "launch" => {
+                let mut args = CommandArgs::parse(rest, &[], &[])?;
+                let command = Command::Launch {
+                    instance: parse_instance(&args.positional("instance")?)?,
+                };
+                args.finish()?;
+                command
+            }
+            other => return Err(UsageError(format!("unknown command `{other}`"))),
+        };
+
+        Ok(Self {
+            runtime_path,
+            command,
+        })
+    }
+}
+
+/// Positional arguments and options following a command
+struct CommandArgs {
+    positional: VecDeque<String>,
+    values: HashMap<String, String>,
+    flags: HashSet<String>,
+}
+
+impl CommandArgs {
+    fn parse(
+        mut args: impl Iterator<Item = String>,
+        value_options: &[&str],
+        flag_options: &[&str],
+    ) -> Result<Self, UsageError> {
+        let mut this = Self {
+            positional: VecDeque::new(),
+            values: HashMap::new(),
+            flags: HashSet::new(),
+        };
+
+        while let Some(arg) = args.next() {
+            if value_options.contains(&&*arg) {
+                let value = next_value(&mut args, &arg)?;
+                this.values.insert(arg, value);
+            } else if flag_options.contains(&&*arg) {
+                this.flags.insert(arg);
+            } else if arg.starts_with("--") {
+                return Err(UsageError(format!("unknown option `{arg}`")));
+            } else {
+                this.positional.push_back(arg);
+            }
+        }
+
+        Ok(this)
+    }
+
+    fn positional(&mut self, name: &str) -> Result<String, UsageError> {
+        self.positional
+            .pop_front()
+            .ok_or_else(|| UsageError(format!("missing argument <{name}>")))
+    }
+
+    fn value(&mut self, option: &str) -> Option<String> {
+        self.values.remove(option)
+    }
+
+    fn required(&mut self, option: &str) -> Result<String, UsageError> {
+        self.value(option)
+            .ok_or_else(|| UsageError(format!("missing option {option}")))
+    }
+
+    fn flag(&mut self, flag: &str) -> bool {
+        self.flags.remove(flag)
+    }
+
+    fn finish(self) -> Result<(), UsageError> {
+        match self.positional.front() {
+            Some(arg) => Err(UsageError(format!("unexpected argument `{arg}`"))),
+            None => Ok(()),
+        }
+    }
+}
+
+fn next_value(args: &mut impl Iterator<Item = String>, option: &str) -> Result<String, UsageError> {
+    args.next()
+        .ok_or_else(|| UsageError(format!("missing value for {option}")))
+}
+
+fn parse_number<T: std::str::FromStr>(value: &str) -> Result<T, UsageError> {
+    value
+        .parse()
+        .map_err(|_| UsageError(format!("`{value}` is not a valid number")))
+}
+
+fn parse_instance(value: &str) -> Result<InstanceId, UsageError> {
+    parse_number(value).map(InstanceId)
+}
+
+fn split_pair<'a>(value: &'a str, option: &str) -> Result<(&'a str, &'a str), UsageError> {
+    value
+        .split_once(':')
+        .filter(|(a, b)| !a.is_empty() && !b.is_empty())
+        .ok_or_else(|| {
+            UsageError(format!(
+                "{option} expects <project>:<version>, got `{value}`"
+            ))
+        })
+}
+
+fn parse_modloader(value: String) -> Result<ModLoader, UsageError> {
+    let (type_, version) = split_pair(&value, "--modloader")?;
+
+    Ok(ModLoader {
+        type_: ModLoaderType::try_from(type_).map_err(|e| UsageError(e.to_string()))?,
+        version: version.to_string(),
+    })
+}
+
+fn parse_entity(value: &str) -> Result<Entity, UsageError> {
+    Ok(match value {
+        "legacy-gdlauncher" => Entity::LegacyGDLauncher,
+        "curseforge-zip" => Entity::CurseForgeZip,
+        "curseforge" => Entity::CurseForge,
+        "mrpack" => Entity::MRPack,
+        "multimc" => Entity::MultiMC,
+        "prismlauncher" => Entity::PrismLauncher,
+        "atlauncher" => Entity::ATLauncher,
+        "technic" => Entity::Technic,
+        "ftb" => Entity::FTB,
+        _ => return Err(UsageError(format!("unknown import source `{value}`"))),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn parse(args: &str) -> Result<Args, UsageError> {
+        Args::parse(args.split_whitespace().map(String::from))
+    }
+
+    #[test]
+    fn parses_commands() {
+        assert_eq!(
+            parse("--runtime_path /tmp/carbon list").unwrap(),
+            Args {
+                runtime_path: Some(PathBuf::from("/tmp/carbon")),
+                command: Command::List,
+            }
+        );
+
+        assert_eq!(
+            parse("create Test --version 1.20.1 --modloader fabric:0.15.7")
+                .unwrap()
+                .command,
+            Command::Create {
+                name: String::from("Test"),
+                release: String::from("1.20.1"),
+                modloader: Some(ModLoader {
+                    type_: ModLoaderType::Fabric,
+                    version: String::from("0.15.7"),
+                }),
+                group: None,
+            }
+        );
+
+        assert_eq!(
+            parse(
+                "install-mod 4 --curseforge 238222:4712868 --no-deps --base_api http://localhost"
+            )
+            .unwrap()
+            .command,
+            Command::InstallMod {
+                instance: InstanceId(4),
+                source: ModSource::Curseforge {
+                    project_id: 238222,
+                    file_id: 4712868,
+                },
+                install_deps: false,
+            }
+        );
+
+        assert_eq!(
+            parse("export 2 pack.mrpack --format modrinth")
+                .unwrap()
+                .command,
+            Command::Export {
+                instance: InstanceId(2),
+                save_path: PathBuf::from("pack.mrpack"),
+                target: ExportTarget::Modrinth,
+                bundle_addons: false,
+            }
+        );
+
+        assert_eq!(parse("").unwrap().command, Command::Help);
+        assert_eq!(parse("launch 1 --help").unwrap().command, Command::Help);
+    }
+
+    #[test]
+    fn rejects_invalid_usage() {
+        assert_eq!(
+            parse("launch").unwrap_err(),
+            UsageError(String::from("missing argument <instance>"))
+        );
+        assert_eq!(
+            parse("launch one").unwrap_err(),
+            UsageError(String::from("`one` is not a valid number"))
+        );
+        assert_eq!(
+            parse("prepare 1 2").unwrap_err(),
+            UsageError(String::from("unexpected argument `2`"))
+        );
+        assert_eq!(
+            parse("create Test").unwrap_err(),
+            UsageError(String::from("missing option --version"))
+        );
+        assert_eq!(
+            parse("install-mod 1 --modrinth AANobbMI").unwrap_err(),
+            UsageError(String::from(
+                "--modrinth expects <project>:<version>, got `AANobbMI`"
+            ))
+        );
+        assert_eq!(
+            parse("import pack.zip --force").unwrap_err(),
+            UsageError(String::from("unknown option `--force`"))
+        );
+        assert_eq!(
+            parse("frobnicate").unwrap_err(),
+            UsageError(String::from("unknown command `frobnicate`"))
+        );
+    }
+}
@@ -15,12 +15,18 @@ pub enum AccountType {
     Offline,
     /// Authenticated MS account.
     Microsoft { email: Option<String> },
+    /// Account authenticated against a custom yggdrasil server, launched
+    /// through authlib-injector.
+    Yggdrasil { server: String },
 }
 
 #[derive(Debug)]
 pub struct AccountWithStatus {
     pub account: Account,
     pub status: AccountStatus,
+    /// Error of the last failed background token refresh, cleared once a
+    /// refresh succeeds.
+    pub last_refresh_error: Option<String>,
 }
 
 #[derive(Debug)]
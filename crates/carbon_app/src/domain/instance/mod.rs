@@ -99,6 +99,7 @@ pub struct Mod {
     pub metadata: Option<ModFileMetadata>,
     pub curseforge: Option<CurseForgeModMetadata>,
     pub modrinth: Option<ModrinthModMetadata>,
+    pub source: Option<SourceModMetadata>,
     pub has_update: bool,
 }
 
@@ -117,6 +118,108 @@ pub struct ModFileMetadata {
     pub has_image: bool,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModDependency {
+    /// The mod loader whose metadata declared the dependency
+    pub modloader: info::ModLoaderType,
+    pub modid: String,
+    pub kind: ModDependencyKind,
+    /// Version ranges any one of which is enough, like `>=1.0 <2.0`.
+    /// Empty if any version is accepted.
+    pub versions: Vec<String>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ModDependencyKind {
+    Required,
+    Optional,
+    /// Can't be loaded together with the dependency
+    Breaks,
+    /// Loads together with the dependency, but is known to misbehave
+    Conflicts,
+}
+
+impl From<ModDependencyKind> for i32 {
+    fn from(value: ModDependencyKind) -> Self {
+        match value {
+            ModDependencyKind::Required => 0,
+            ModDependencyKind::Optional => 1,
+            ModDependencyKind::Breaks => 2,
+            ModDependencyKind::Conflicts => 3,
+        }
+    }
+}
+
+impl TryFrom<i32> for ModDependencyKind {
+    type Error = anyhow::Error;
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::Required),
+            1 => Ok(Self::Optional),
+            2 => Ok(Self::Breaks),
+            3 => Ok(Self::Conflicts),
+            _ => anyhow::bail!("unknown mod dependency kind {value}"),
+        }
+    }
+}
+
+/// A mod id a file provides besides its own, through `provides` or a nested jar
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProvidedModId {
+    pub modid: String,
+    pub version: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModDependencyAnalysis {
+    pub issues: Vec<ModDependencyIssue>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ModDependencyIssue {
+    /// A required dependency no enabled mod provides
+    Missing {
+        mod_id: String,
+        filename: String,
+        dependency: String,
+        versions: Vec<String>,
+    },
+    /// A dependency is present, but none of its versions are in range
+    UnsatisfiedVersion {
+        mod_id: String,
+        filename: String,
+        dependency: String,
+        versions: Vec<String>,
+        found: Vec<DependencyProvider>,
+    },
+    /// A mod declared as breaking or conflicting is present in the declared range
+    Incompatible {
+        mod_id: String,
+        filename: String,
+        dependency: String,
+        versions: Vec<String>,
+        found: Vec<DependencyProvider>,
+        breaking: bool,
+    },
+    /// Several enabled files declare the same mod id
+    DuplicateModId {
+        modid: String,
+        mods: Vec<DependencyProvider>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DependencyProvider {
+    /// The game or one of the instance's mod loaders
+    Instance { version: Option<String> },
+    Mod {
+        mod_id: String,
+        filename: String,
+        version: Option<String>,
+    },
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct CurseForgeModMetadata {
     pub project_id: u32,
@@ -141,6 +244,16 @@ pub struct ModrinthModMetadata {
     pub has_image: bool,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceModMetadata {
+    pub source_id: String,
+    pub repository: String,
+    pub project_id: String,
+    pub version_id: String,
+    pub name: String,
+    pub version: String,
+}
+
 #[derive(Debug, Copy, Clone)]
 pub enum InstanceFolder {
     Root,
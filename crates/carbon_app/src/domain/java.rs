@@ -177,15 +177,19 @@ impl ToString for JavaOs {
     }
 }
 
-#[derive(Debug, EnumIter)]
+#[derive(Debug, Clone, Copy, EnumIter)]
 pub enum JavaVendor {
     Azul,
+    Adoptium,
+    /// The runtimes listed in Mojang's java runtime manifest
+    Mojang,
 }
 
 impl JavaVendor {
     pub fn from_java_dot_vendor(vendor: &str) -> Option<Self> {
         match vendor {
             "Azul Systems, Inc." => Some(Self::Azul),
+            "Eclipse Adoptium" => Some(Self::Adoptium),
             _ => None,
         }
     }
@@ -310,7 +314,7 @@ impl JavaVersion {
 #[derive(Serialize, Deserialize, Debug, Copy, Clone, EnumIter, Eq, PartialEq)]
 pub enum SystemJavaProfileName {
     Legacy,
-    // LegacyFixed1 doesn't natively exist in metadata, it's only used to fix forge on 1.16.5 and patched at run.rs level
+    // LegacyFixed1 doesn't natively exist in metadata, it's only used to fix forge on 1.16.5 when java is not managed automatically
     LegacyFixed1,
     Alpha,
     Beta,
@@ -335,6 +339,19 @@ impl SystemJavaProfileName {
             Self::MinecraftJavaExe => java_version.major == 14,
         }
     }
+
+    /// Name of the component Mojang ships for this profile in its java runtime manifest
+    pub fn mojang_component(&self) -> &'static str {
+        match self {
+            Self::Legacy | Self::LegacyFixed1 => "jre-legacy",
+            Self::Alpha => "java-runtime-alpha",
+            Self::Beta => "java-runtime-beta",
+            Self::Gamma => "java-runtime-gamma",
+            Self::GammaSnapshot => "java-runtime-gamma-snapshot",
+            Self::Delta => "java-runtime-delta",
+            Self::MinecraftJavaExe => "minecraft-java-exe",
+        }
+    }
 }
 
 impl TryFrom<MinecraftJavaProfile> for SystemJavaProfileName {
@@ -42,6 +42,8 @@ pub fn libraries_into_vec_downloadable(
                 path: PathBuf::from(base_path).join(maven_path),
                 checksum,
                 size: None,
+                mirrors: vec![],
+                headers: reqwest::header::HeaderMap::new(),
             });
         }
     }
@@ -105,6 +107,8 @@ pub fn library_into_lib_downloadable(
                 path: PathBuf::from(base_path).join(artifact.path),
                 checksum,
                 size: Some(artifact.size as u64),
+                mirrors: vec![],
+                headers: reqwest::header::HeaderMap::new(),
             });
         }
     } else if let Some(base_url) = &library.url {
@@ -113,6 +117,8 @@ pub fn library_into_lib_downloadable(
             path: base_path.join(library.name.path()),
             checksum: None,
             size: None,
+            mirrors: vec![],
+            headers: reqwest::header::HeaderMap::new(),
         });
     }
     None
@@ -150,6 +156,8 @@ pub fn library_into_natives_downloadable(
         path: PathBuf::from(base_path).join(mapping_class.clone().path),
         checksum,
         size: Some(mapping_class.size as u64),
+        mirrors: vec![],
+        headers: reqwest::header::HeaderMap::new(),
     })
 }
 
@@ -110,3 +110,38 @@ pub async fn setup_logger(runtime_path: &Path) -> Option<WorkerGuard> {
         return Some(guard);
     }
 }
+
+/// Logger for the `carbon` cli. Stdout is reserved for command output, so
+/// everything goes to a file in the logs folder.
+pub async fn setup_cli_logger(runtime_path: &Path) -> WorkerGuard {
+    let logs_path = runtime_path.join("__gdl_logs__");
+
+    if !logs_path.exists() {
+        tokio::fs::create_dir_all(&logs_path).await.unwrap();
+    }
+
+    let filter = match std::env::var("RUST_LOG") {
+        Ok(_) => EnvFilter::builder()
+            .from_env()
+            .expect("logger directives are invalid"),
+        Err(_) => EnvFilter::builder().parse(generate_logs_filters()).unwrap(),
+    };
+
+    let file_name = chrono::Local::now().format("%Y-%m-%d_%H-%M-%S");
+    let file_appender =
+        tracing_appender::rolling::never(logs_path, format!("cli_{}.log", file_name));
+
+    let (non_blocking, guard) = tracing_appender::non_blocking(file_appender);
+
+    let printer = tracing_subscriber::fmt::layer()
+        .with_target(true)
+        .with_ansi(false)
+        .with_thread_names(true);
+
+    tracing_subscriber::registry()
+        .with(printer.with_writer(non_blocking))
+        .with(filter)
+        .init();
+
+    guard
+}
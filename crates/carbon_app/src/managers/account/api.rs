@@ -16,6 +16,30 @@ use crate::error::request::{
     censor_error, MalformedResponseDetails, RequestContext, RequestError, RequestErrorDetails,
 };
 
+/// Base urls of the services microsoft accounts authenticate with,
+/// overridable so tests can use stand-in servers.
+#[derive(Clone)]
+pub struct AuthEndpoints {
+    pub microsoft_login: String,
+    pub xbox_user_auth: String,
+    pub xsts_auth: String,
+    pub minecraft_services: String,
+    /// Verifies the signature of the game entitlements
+    pub entitlement_key: DecodingKey,
+}
+
+impl Default for AuthEndpoints {
+    fn default() -> Self {
+        Self {
+            microsoft_login: "https://login.microsoftonline.com".to_string(),
+            xbox_user_auth: "https://user.auth.xboxlive.com".to_string(),
+            xsts_auth: "https://xsts.auth.xboxlive.com".to_string(),
+            minecraft_services: "https://api.minecraftservices.com".to_string(),
+            entitlement_key: McEntitlement::mojang_jwt_key(),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct DeviceCode {
     pub user_code: String,
@@ -26,7 +50,10 @@ pub struct DeviceCode {
 }
 
 impl DeviceCode {
-    pub async fn request_code(client: &ClientWithMiddleware) -> anyhow::Result<Self> {
+    pub async fn request_code(
+        client: &ClientWithMiddleware,
+        endpoints: &AuthEndpoints,
+    ) -> anyhow::Result<Self> {
         #[derive(Deserialize)]
         struct DeviceCodeResponse {
             user_code: String,
@@ -38,7 +65,10 @@ impl DeviceCode {
         }
 
         let response = client
-            .get("https://login.microsoftonline.com/consumers/oauth2/v2.0/devicecode")
+            .get(format!(
+                "{}/consumers/oauth2/v2.0/devicecode",
+                endpoints.microsoft_login
+            ))
             .query(&[
                 ("client_id", env!("MS_AUTH_CLIENT_ID")),
                 (
@@ -66,8 +96,11 @@ impl DeviceCode {
         })
     }
 
-    #[tracing::instrument(skip(self))]
-    pub async fn poll_ms_auth(&self) -> anyhow::Result<Result<MsAuth, DeviceCodeExpiredError>> {
+    #[tracing::instrument(skip(self, endpoints))]
+    pub async fn poll_ms_auth(
+        &self,
+        endpoints: &AuthEndpoints,
+    ) -> anyhow::Result<Result<MsAuth, DeviceCodeExpiredError>> {
         // Since we're polling, we don't want a retry system
         let bare_client = reqwest::Client::new();
 
@@ -77,7 +110,10 @@ impl DeviceCode {
             trace!("Polling for auth token at {:?}", Utc::now());
 
             let response = bare_client
-                .post("https://login.microsoftonline.com/consumers/oauth2/v2.0/token")
+                .post(format!(
+                    "{}/consumers/oauth2/v2.0/token",
+                    endpoints.microsoft_login
+                ))
                 .form(&[
                     ("client_id", env!("MS_AUTH_CLIENT_ID")),
                     (
@@ -173,9 +209,10 @@ pub struct MsAuth {
 impl MsAuth {
     /// Refresh the auth token, returning a new token if the current one
     /// has expired.
-    #[tracing::instrument(skip(client, refresh_token))]
+    #[tracing::instrument(skip(client, endpoints, refresh_token))]
     pub async fn refresh(
         client: &ClientWithMiddleware,
+        endpoints: &AuthEndpoints,
         refresh_token: &str,
     ) -> anyhow::Result<Self> {
         trace!("Refreshing auth token");
@@ -188,7 +225,10 @@ impl MsAuth {
         }
 
         let response = client
-            .post("https://login.microsoftonline.com/consumers/oauth2/v2.0/token")
+            .post(format!(
+                "{}/consumers/oauth2/v2.0/token",
+                endpoints.microsoft_login
+            ))
             //.post("https://login.live.com/oauth20_token.srf")
             .form(&[
                 ("client_id", env!("MS_AUTH_CLIENT_ID")),
@@ -225,10 +265,11 @@ pub struct XboxAuth {
 
 impl XboxAuth {
     /// Obtain an Xbox account from a MS account (without refreshing it)
-    #[tracing::instrument(skip(ms_auth, client))]
+    #[tracing::instrument(skip(ms_auth, client, endpoints))]
     pub async fn from_ms(
         ms_auth: &MsAuth,
         client: &ClientWithMiddleware,
+        endpoints: &AuthEndpoints,
     ) -> anyhow::Result<Result<Self, XboxError>> {
         trace!("Authenticating Xbox account");
 
@@ -250,7 +291,7 @@ impl XboxAuth {
             });
 
             let response = client
-                .post("https://user.auth.xboxlive.com/user/authenticate")
+                .post(format!("{}/user/authenticate", endpoints.xbox_user_auth))
                 .header("Accept", "application/json")
                 .header("Content-Type", "application/json")
                 .body(reqwest::Body::from(serde_json::to_string(&json)?))
@@ -281,7 +322,7 @@ impl XboxAuth {
         });
 
         let response = client
-            .post("https://xsts.auth.xboxlive.com/xsts/authorize")
+            .post(format!("{}/xsts/authorize", endpoints.xsts_auth))
             .header("Content-Type", "application/json")
             .header("Accept", "application/json")
             .body(reqwest::Body::from(serde_json::to_string(&json)?))
@@ -402,10 +443,11 @@ pub struct McAuth {
 
 impl McAuth {
     /// Authenticate with a MS account (without refreshing it)
-    #[tracing::instrument(skip(xbox_auth, client))]
+    #[tracing::instrument(skip(xbox_auth, client, endpoints))]
     pub async fn auth_ms(
         xbox_auth: XboxAuth,
         client: &ClientWithMiddleware,
+        endpoints: &AuthEndpoints,
     ) -> anyhow::Result<Self> {
         let json = json!({
             "identityToken": format!("XBL3.0 x={};{}", xbox_auth.userhash, xbox_auth.xsts_token)
@@ -420,7 +462,10 @@ impl McAuth {
         trace!("Authenticating Minecraft account");
 
         let response = client
-            .post("https://api.minecraftservices.com/authentication/login_with_xbox")
+            .post(format!(
+                "{}/authentication/login_with_xbox",
+                endpoints.minecraft_services
+            ))
             .header("Accept", "application/json")
             .header("Content-Type", "application/json")
             .body(reqwest::Body::from(serde_json::to_string(&json)?))
@@ -440,10 +485,11 @@ impl McAuth {
         })
     }
 
-    #[tracing::instrument(skip(self, client))]
+    #[tracing::instrument(skip(self, client, endpoints))]
     pub async fn get_entitlement(
         &self,
         client: &ClientWithMiddleware,
+        endpoints: &AuthEndpoints,
     ) -> anyhow::Result<Result<McEntitlement, McEntitlementMissingError>> {
         trace!("Checking game entitlement");
 
@@ -453,7 +499,10 @@ impl McAuth {
         }
 
         let response = client
-            .get("https://api.minecraftservices.com/entitlements/mcstore")
+            .get(format!(
+                "{}/entitlements/mcstore",
+                endpoints.minecraft_services
+            ))
             .bearer_auth(&self.access_token)
             .send()
             .await
@@ -480,7 +529,7 @@ impl McAuth {
         // everything we actually need to check game ownership.
         let entitlements = jsonwebtoken::decode::<SignedEntitlements>(
             &response.signature,
-            &McEntitlement::mojang_jwt_key(),
+            &endpoints.entitlement_key,
             &Validation::new(Algorithm::RS256),
         );
 
@@ -519,15 +568,123 @@ impl McAuth {
 
 pub async fn get_profile(
     client: &ClientWithMiddleware,
+    endpoints: &AuthEndpoints,
     access_token: &str,
 ) -> anyhow::Result<Result<McProfile, GetProfileError>> {
     let response = client
-        .get("https://api.minecraftservices.com/minecraft/profile")
+        .get(format!(
+            "{}/minecraft/profile",
+            endpoints.minecraft_services
+        ))
         .bearer_auth(access_token)
         .send()
         .await
         .with_context(|| format!("Failed to get game profile"))?;
 
+    profile_response(response).await
+}
+
+/// Uploads a new skin, returning the updated profile.
+#[tracing::instrument(skip(client, endpoints, access_token, png))]
+pub async fn upload_skin(
+    client: &ClientWithMiddleware,
+    endpoints: &AuthEndpoints,
+    access_token: &str,
+    variant: SkinVariant,
+    png: &[u8],
+) -> anyhow::Result<Result<McProfile, GetProfileError>> {
+    // built by hand, as streamed multipart bodies can't be retried
+    let boundary = format!("carbon-{}", uuid::Uuid::new_v4().as_simple());
+
+    let mut body = Vec::with_capacity(png.len() + 512);
+    body.extend_from_slice(
+        format!(
+            "--{boundary}\r\n\
+             Content-Disposition: form-data; name=\"variant\"\r\n\r\n\
+             {}\r\n\
+             --{boundary}\r\n\
+             Content-Disposition: form-data; name=\"file\"; filename=\"skin.png\"\r\n\
+             Content-Type: image/png\r\n\r\n",
+            variant.as_str()
+        )
+        .as_bytes(),
+    );
+    body.extend_from_slice(png);
+    body.extend_from_slice(format!("\r\n--{boundary}--\r\n").as_bytes());
+
+    let response = client
+        .post(format!(
+            "{}/minecraft/profile/skins",
+            endpoints.minecraft_services
+        ))
+        .bearer_auth(access_token)
+        .header(
+            "Content-Type",
+            format!("multipart/form-data; boundary={boundary}"),
+        )
+        .body(body)
+        .send()
+        .await
+        .with_context(|| format!("Failed to upload skin"))?;
+
+    profile_response(response).await
+}
+
+/// Resets the skin to the default one, returning the updated profile.
+#[tracing::instrument(skip(client, endpoints, access_token))]
+pub async fn reset_skin(
+    client: &ClientWithMiddleware,
+    endpoints: &AuthEndpoints,
+    access_token: &str,
+) -> anyhow::Result<Result<McProfile, GetProfileError>> {
+    let response = client
+        .delete(format!(
+            "{}/minecraft/profile/skins/active",
+            endpoints.minecraft_services
+        ))
+        .bearer_auth(access_token)
+        .send()
+        .await
+        .with_context(|| format!("Failed to reset skin"))?;
+
+    profile_response(response).await
+}
+
+/// Shows the cape with the given id, or hides the active cape if `None`.
+/// Returns the updated profile.
+#[tracing::instrument(skip(client, endpoints, access_token))]
+pub async fn set_active_cape(
+    client: &ClientWithMiddleware,
+    endpoints: &AuthEndpoints,
+    access_token: &str,
+    cape_id: Option<&str>,
+) -> anyhow::Result<Result<McProfile, GetProfileError>> {
+    let url = format!(
+        "{}/minecraft/profile/capes/active",
+        endpoints.minecraft_services
+    );
+
+    let request = match cape_id {
+        Some(cape_id) => client
+            .put(url)
+            .header("Content-Type", "application/json")
+            .body(serde_json::to_string(&json!({ "capeId": cape_id }))?),
+        None => client.delete(url),
+    };
+
+    let response = request
+        .bearer_auth(access_token)
+        .send()
+        .await
+        .with_context(|| format!("Failed to change the active cape"))?;
+
+    profile_response(response).await
+}
+
+/// Parses the profile returned by the profile endpoints.
+async fn profile_response(
+    response: reqwest::Response,
+) -> anyhow::Result<Result<McProfile, GetProfileError>> {
     match response.status() {
         StatusCode::UNAUTHORIZED => Ok(Err(GetProfileError::AuthTokenInvalid)),
         StatusCode::NOT_FOUND => Ok(Err(GetProfileError::GameProfileMissing)),
@@ -537,6 +694,8 @@ pub async fn get_profile(
                 id: String,
                 name: String,
                 skins: Vec<Skin>,
+                #[serde(default)]
+                capes: Vec<Cape>,
             }
 
             #[derive(Debug, Deserialize)]
@@ -546,6 +705,14 @@ pub async fn get_profile(
                 url: String,
             }
 
+            #[derive(Debug, Deserialize)]
+            struct Cape {
+                id: String,
+                state: String,
+                url: String,
+                alias: String,
+            }
+
             let response = response
                 .json::<McProfileResponse>()
                 .await
@@ -560,10 +727,22 @@ pub async fn get_profile(
                     url: skin.url,
                 });
 
+            let capes = response
+                .capes
+                .into_iter()
+                .map(|cape| McCape {
+                    active: cape.state == "ACTIVE",
+                    id: cape.id,
+                    url: cape.url,
+                    alias: cape.alias,
+                })
+                .collect();
+
             Ok(Ok(McProfile {
                 uuid: response.id,
                 username: response.name,
                 skin,
+                capes,
             }))
         }
         _ => bail!(RequestError::from_status(&response)),
@@ -624,6 +803,7 @@ pub struct McProfile {
     pub uuid: String,
     pub username: String,
     pub skin: Option<McSkin>,
+    pub capes: Vec<McCape>,
 }
 
 #[derive(Debug, Clone)]
@@ -632,6 +812,42 @@ pub struct McSkin {
     pub url: String,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SkinVariant {
+    Classic,
+    Slim,
+}
+
+impl SkinVariant {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Classic => "classic",
+            Self::Slim => "slim",
+        }
+    }
+}
+
+impl std::str::FromStr for SkinVariant {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "classic" => Ok(Self::Classic),
+            "slim" => Ok(Self::Slim),
+            _ => bail!("unknown skin variant `{s}`"),
+        }
+    }
+}
+
+/// A cape owned by the profile
+#[derive(Debug, Clone)]
+pub struct McCape {
+    pub id: String,
+    pub url: String,
+    pub alias: String,
+    pub active: bool,
+}
+
 #[derive(Debug, Clone)]
 pub struct McAccount {
     pub auth: McAuth,
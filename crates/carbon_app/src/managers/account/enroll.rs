@@ -1,8 +1,8 @@
 use std::sync::Arc;
 
 use super::api::{
-    get_profile, DeviceCode, DeviceCodeExpiredError, FullAccount, GetProfileError, McAccount,
-    McAuth, McEntitlementMissingError, MsAuth, XboxAuth, XboxError,
+    get_profile, AuthEndpoints, DeviceCode, DeviceCodeExpiredError, FullAccount, GetProfileError,
+    McAccount, McAuth, McEntitlementMissingError, MsAuth, XboxAuth, XboxError,
 };
 use anyhow::anyhow;
 use async_trait::async_trait;
@@ -35,6 +35,7 @@ impl EnrollmentTask {
     /// whenever the task's status updates.
     pub fn begin(
         client: reqwest_middleware::ClientWithMiddleware,
+        endpoints: AuthEndpoints,
         invalidate: impl InvalidateCtx + Send + Sync + 'static,
     ) -> Self {
         let status = Arc::new(RwLock::new(EnrollmentStatus::RequestingCode));
@@ -48,28 +49,28 @@ impl EnrollmentTask {
 
             let task = || async {
                 // request device code
-                let device_code = DeviceCode::request_code(&client).await?;
+                let device_code = DeviceCode::request_code(&client, &endpoints).await?;
 
                 // poll ms auth
                 update_status(EnrollmentStatus::PollingCode(device_code.clone())).await;
-                let ms_auth = device_code.poll_ms_auth().await??;
+                let ms_auth = device_code.poll_ms_auth(&endpoints).await??;
 
                 update_status(EnrollmentStatus::XboxAuth).await;
 
                 // authenticate with XBox
-                let xbox_auth = XboxAuth::from_ms(&ms_auth, &client).await??;
+                let xbox_auth = XboxAuth::from_ms(&ms_auth, &client, &endpoints).await??;
 
                 update_status(EnrollmentStatus::McLogin).await;
                 // authenticate with MC
-                let mc_auth = McAuth::auth_ms(xbox_auth, &client).await?;
+                let mc_auth = McAuth::auth_ms(xbox_auth, &client, &endpoints).await?;
 
                 update_status(EnrollmentStatus::MCEntitlements).await;
 
-                let entitlements = mc_auth.get_entitlement(&client).await??;
+                let entitlements = mc_auth.get_entitlement(&client, &endpoints).await??;
 
                 update_status(EnrollmentStatus::McProfile).await;
 
-                let mc_profile = get_profile(&client, &mc_auth.access_token).await??;
+                let mc_profile = get_profile(&client, &endpoints, &mc_auth.access_token).await??;
 
                 let account = McAccount {
                     entitlement: entitlements.clone(),
@@ -108,6 +109,7 @@ impl EnrollmentTask {
 
     pub fn refresh(
         client: reqwest_middleware::ClientWithMiddleware,
+        endpoints: AuthEndpoints,
         refresh_token: String,
         invalidate: impl InvalidateCtx + Send + Sync + 'static,
     ) -> Self {
@@ -125,7 +127,7 @@ impl EnrollmentTask {
 
                 trace!("Refreshing MsAuth with refresh token");
                 // attempt to refresh token
-                let ms_auth = MsAuth::refresh(&client, &refresh_token).await?;
+                let ms_auth = MsAuth::refresh(&client, &endpoints, &refresh_token).await?;
 
                 trace!("Successfully refreshed MsAuth with refresh token");
 
@@ -134,7 +136,7 @@ impl EnrollmentTask {
                 trace!("Authenticating with XBox");
 
                 // authenticate with XBox
-                let xbox_auth = XboxAuth::from_ms(&ms_auth, &client).await??;
+                let xbox_auth = XboxAuth::from_ms(&ms_auth, &client, &endpoints).await??;
 
                 trace!("Successfully authenticated with XBox");
 
@@ -143,17 +145,17 @@ impl EnrollmentTask {
                 update_status(EnrollmentStatus::McLogin).await;
 
                 // authenticate with MC
-                let mc_auth = McAuth::auth_ms(xbox_auth, &client).await?;
+                let mc_auth = McAuth::auth_ms(xbox_auth, &client, &endpoints).await?;
 
                 trace!("Successfully authenticated with MC");
 
                 update_status(EnrollmentStatus::MCEntitlements).await;
 
-                let entitlements = mc_auth.get_entitlement(&client).await??;
+                let entitlements = mc_auth.get_entitlement(&client, &endpoints).await??;
 
                 update_status(EnrollmentStatus::McProfile).await;
 
-                let mc_profile = get_profile(&client, &mc_auth.access_token).await??;
+                let mc_profile = get_profile(&client, &endpoints, &mc_auth.access_token).await??;
 
                 let account = McAccount {
                     entitlement: entitlements.clone(),
@@ -19,6 +19,7 @@ use gdl_account::{
     RequestNewEmailChangeError, RequestNewVerificationTokenError,
 };
 use jwt::{Header, Token};
+use md5::{Digest, Md5};
 use reqwest::Client;
 use reqwest_middleware::ClientBuilder;
 use reqwest_retry::{policies::ExponentialBackoff, RetryTransientMiddleware};
@@ -36,39 +37,76 @@ use tokio::sync::{Mutex, RwLock};
 use tracing::{debug, error, info, trace, warn};
 
 pub use self::enroll::{EnrollmentError, EnrollmentStatus};
-use self::{enroll::EnrollmentTask, skin::SkinManager};
+use self::{
+    api::AuthEndpoints,
+    enroll::EnrollmentTask,
+    refresh::{FailureKind, RefreshSchedule},
+    skin::SkinManager,
+    vault::{TokenVault, VaultError},
+    yggdrasil::YggdrasilError,
+};
 
 use super::{AppInner, AppRef, ManagerRef};
 
 pub mod api;
 mod enroll;
 pub mod gdl_account;
+mod refresh;
 pub mod skin;
+pub mod skin_render;
+pub mod vault;
+pub mod yggdrasil;
 
 pub(crate) struct AccountManager {
     currently_refreshing: RwLock<HashMap<String, EnrollmentTask>>,
     active_enrollment: RwLock<Option<EnrollmentTask>>,
     /// Account refreshing will be disabled until this time has passed
     refreshloop_sleep: Mutex<Option<Instant>>,
+    /// Backoff of failed background token refreshes
+    refresh_schedule: Mutex<RefreshSchedule>,
+    auth_endpoints: std::sync::RwLock<AuthEndpoints>,
     skin_manager: SkinManager,
+    /// Seals the tokens stored in the database
+    vault: TokenVault,
 
     gdl_account_task: GDLAccountTask,
 }
 
 impl AccountManager {
-    pub fn new(client: reqwest_middleware::ClientWithMiddleware, gdl_base_api: String) -> Self {
+    pub fn new(
+        client: reqwest_middleware::ClientWithMiddleware,
+        gdl_base_api: String,
+        vault: TokenVault,
+    ) -> Self {
         Self {
             currently_refreshing: RwLock::new(HashMap::new()),
             active_enrollment: RwLock::new(None),
             refreshloop_sleep: Mutex::new(None),
+            refresh_schedule: Mutex::new(RefreshSchedule::default()),
+            auth_endpoints: std::sync::RwLock::new(AuthEndpoints::default()),
             skin_manager: SkinManager {},
+            vault,
 
             gdl_account_task: GDLAccountTask::new(client, gdl_base_api),
         }
     }
+
+    /// Also seals the credentials of private mod source repositories.
+    pub(crate) fn vault(&self) -> &TokenVault {
+        &self.vault
+    }
 }
 
 impl<'s> ManagerRef<'s, AccountManager> {
+    pub fn auth_endpoints(self) -> AuthEndpoints {
+        self.auth_endpoints.read().unwrap().clone()
+    }
+
+    #[cfg(test)]
+    pub fn set_auth_endpoints(self, endpoints: AuthEndpoints) {
+        *self.auth_endpoints.write().unwrap() = endpoints;
+    }
+
     pub async fn get_active_uuid(self) -> anyhow::Result<Option<String>> {
         Ok(self
             .app
@@ -119,7 +157,7 @@ impl<'s> ManagerRef<'s, AccountManager> {
             return Ok(None);
         };
 
-        let account = self
+        let mut account = self
             .app
             .prisma_client
             .account()
@@ -128,30 +166,136 @@ impl<'s> ManagerRef<'s, AccountManager> {
             .await?
             .ok_or_else(|| anyhow!("currenly active account could not be read from database"))?;
 
+        if !self.vault.open_account(&mut account) {
+            bail!(VaultError::ReloginRequired(account.uuid));
+        }
+
         Ok(Some(account.try_into()?))
     }
 
+    /// All accounts, with their tokens decrypted.
     async fn get_account_entries(self) -> anyhow::Result<Vec<db::account::Data>> {
         use db::account::OrderByParam;
 
-        Ok(self
+        let mut accounts = self
             .app
             .prisma_client
             .account()
             .find_many(Vec::new())
             .order_by(OrderByParam::LastUsed(Direction::Desc))
             .exec()
-            .await?)
+            .await?;
+
+        for account in &mut accounts {
+            self.vault.open_account(account);
+        }
+
+        Ok(accounts)
+    }
+
+    /// An account with its tokens decrypted.
+    async fn get_account_entry(self, uuid: String) -> anyhow::Result<Option<db::account::Data>> {
+        use db::account::UniqueWhereParam;
+
+        let account = self
+            .app
+            .prisma_client
+            .account()
+            .find_unique(UniqueWhereParam::UuidEquals(uuid))
+            .exec()
+            .await?;
+
+        Ok(account.map(|mut account| {
+            self.vault.open_account(&mut account);
+            account
+        }))
+    }
+
+    /// Seals the tokens still stored in plaintext by earlier versions.
+    pub async fn seal_plaintext_tokens(self) -> anyhow::Result<()> {
+        use db::account::{SetParam, UniqueWhereParam};
+        use vault::{column_aad, ACCESS_TOKEN, GDL_ACCOUNT_STATUS, ID_TOKEN, MS_REFRESH_TOKEN};
+
+        let accounts = self
+            .app
+            .prisma_client
+            .account()
+            .find_many(Vec::new())
+            .exec()
+            .await?;
+
+        for account in accounts {
+            let columns = [
+                (ACCESS_TOKEN, account.access_token),
+                (MS_REFRESH_TOKEN, account.ms_refresh_token),
+                (ID_TOKEN, account.id_token),
+            ];
+
+            let mut set_params = Vec::new();
+            for (column, value) in columns {
+                let Some(value) = value.filter(|value| !TokenVault::is_sealed(value)) else {
+                    continue;
+                };
+
+                let sealed = Some(
+                    self.vault
+                        .seal(&column_aad(&account.uuid, column), &value)?,
+                );
+
+                set_params.push(match column {
+                    ACCESS_TOKEN => SetParam::SetAccessToken(sealed),
+                    MS_REFRESH_TOKEN => SetParam::SetMsRefreshToken(sealed),
+                    _ => SetParam::SetIdToken(sealed),
+                });
+            }
+
+            if set_params.is_empty() {
+                continue;
+            }
+
+            info!("Sealing plaintext tokens of account {}", account.uuid);
+
+            self.app
+                .prisma_client
+                .account()
+                .update(UniqueWhereParam::UuidEquals(account.uuid), set_params)
+                .exec()
+                .await?;
+        }
+
+        let gdl_account_status = self
+            .app
+            .settings_manager()
+            .get_settings()
+            .await?
+            .gdl_account_status;
+
+        if let Some(status) = gdl_account_status.filter(|s| !TokenVault::is_sealed_bytes(s)) {
+            let sealed = self.vault.seal_bytes(GDL_ACCOUNT_STATUS, &status)?;
+
+            self.app
+                .settings_manager()
+                .set(app_configuration::SetParam::SetGdlAccountStatus(Some(
+                    sealed,
+                )))
+                .await?;
+        }
+
+        Ok(())
     }
 
     pub async fn get_account_list(self) -> anyhow::Result<Vec<AccountWithStatus>> {
         let accounts = self.get_account_entries().await?;
+        let refresh_schedule = self.refresh_schedule.lock().await;
 
         Ok(accounts
             .into_iter()
             .map(|account| {
                 let account = FullAccount::try_from(account).unwrap();
-                let account = AccountWithStatus::from(account);
+                let mut account = AccountWithStatus::from(account);
+                account.last_refresh_error = refresh_schedule
+                    .last_error(&account.account.uuid)
+                    .map(ToString::to_string);
 
                 account
             })
@@ -159,15 +303,7 @@ impl<'s> ManagerRef<'s, AccountManager> {
     }
 
     async fn get_account(self, uuid: String) -> anyhow::Result<Option<AccountWithStatus>> {
-        use db::account::UniqueWhereParam;
-
-        let account = self
-            .app
-            .prisma_client
-            .account()
-            .find_unique(UniqueWhereParam::UuidEquals(uuid))
-            .exec()
-            .await?;
+        let account = self.get_account_entry(uuid).await?;
 
         let Some(account) = account else {
             return Ok(None);
@@ -477,8 +613,35 @@ impl<'s> ManagerRef<'s, AccountManager> {
     }
 
     /// Add or update an account
-    async fn add_account(self, account: FullAccount) -> anyhow::Result<()> {
+    async fn add_account(self, mut account: FullAccount) -> anyhow::Result<()> {
         use db::account::{SetParam, UniqueWhereParam};
+        use vault::{ACCESS_TOKEN, ID_TOKEN, MS_REFRESH_TOKEN};
+
+        let uuid = account.uuid.as_str();
+        match &mut account.type_ {
+            FullAccountType::Offline => {}
+            FullAccountType::Microsoft {
+                access_token,
+                refresh_token,
+                id_token,
+                ..
+            } => {
+                *access_token = self
+                    .vault
+                    .seal_column(uuid, ACCESS_TOKEN, Some(mem::take(access_token)))?
+                    .unwrap_or_default();
+                *refresh_token =
+                    self.vault
+                        .seal_column(uuid, MS_REFRESH_TOKEN, refresh_token.take())?;
+                *id_token = self.vault.seal_column(uuid, ID_TOKEN, id_token.take())?;
+            }
+            FullAccountType::Yggdrasil { access_token, .. } => {
+                *access_token = self
+                    .vault
+                    .seal_column(uuid, ACCESS_TOKEN, Some(mem::take(access_token)))?
+                    .unwrap_or_default();
+            }
+        }
 
         let db_account = self
             .app
@@ -497,6 +660,8 @@ impl<'s> ManagerRef<'s, AccountManager> {
                     SetParam::SetAccessToken(None),
                     SetParam::SetMsRefreshToken(None),
                     SetParam::SetTokenExpires(None),
+                    SetParam::SetYggdrasilServer(None),
+                    SetParam::SetYggdrasilClientToken(None),
                 ]),
                 FullAccountType::Microsoft {
                     access_token,
@@ -513,6 +678,21 @@ impl<'s> ManagerRef<'s, AccountManager> {
                     )),
                     SetParam::SetIdToken(id_token),
                     SetParam::SetSkinId(skin_id),
+                    SetParam::SetYggdrasilServer(None),
+                    SetParam::SetYggdrasilClientToken(None),
+                ]),
+                FullAccountType::Yggdrasil {
+                    api_root,
+                    access_token,
+                    client_token,
+                } => set_params.extend([
+                    SetParam::SetAccessToken(Some(access_token)),
+                    SetParam::SetMsRefreshToken(None),
+                    SetParam::SetTokenExpires(None),
+                    SetParam::SetIdToken(None),
+                    SetParam::SetSkinId(None),
+                    SetParam::SetYggdrasilServer(Some(api_root)),
+                    SetParam::SetYggdrasilClientToken(client_token),
                 ]),
             }
 
@@ -548,6 +728,15 @@ impl<'s> ManagerRef<'s, AccountManager> {
                     SetParam::SetIdToken(id_token),
                     SetParam::SetSkinId(skin_id),
                 ],
+                FullAccountType::Yggdrasil {
+                    api_root,
+                    access_token,
+                    client_token,
+                } => vec![
+                    SetParam::SetAccessToken(Some(access_token)),
+                    SetParam::SetYggdrasilServer(Some(api_root)),
+                    SetParam::SetYggdrasilClientToken(client_token),
+                ],
             };
 
             info!("Creating account {:?}", &account.uuid);
@@ -570,20 +759,135 @@ impl<'s> ManagerRef<'s, AccountManager> {
         Ok(())
     }
 
-    pub async fn refresh_account(self, uuid: String) -> anyhow::Result<()> {
-        use db::account::UniqueWhereParam;
+    /// Add an offline account with the uuid offline mode servers would
+    /// assign to `username`, and make it the active account.
+    pub async fn add_offline_account(self, username: String) -> anyhow::Result<()> {
+        let valid = (3..=16).contains(&username.len())
+            && username
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_');
+
+        if !valid {
+            bail!(AddOfflineAccountError::InvalidUsername(username));
+        }
+
+        let uuid = offline_uuid(&username);
+
+        self.add_account(FullAccount {
+            username,
+            uuid: uuid.clone(),
+            type_: FullAccountType::Offline,
+            last_used: Utc::now().into(),
+        })
+        .await?;
+
+        self.set_active_uuid(Some(uuid)).await
+    }
 
+    /// Log into a custom yggdrasil server and make the account the active one.
+    pub async fn add_yggdrasil_account(
+        self,
+        server: String,
+        username: String,
+        password: String,
+    ) -> anyhow::Result<()> {
+        let client = &self.app.reqwest_client;
+
+        let api_root = yggdrasil::resolve_api_root(client, &server).await?;
+        let client_token = uuid::Uuid::new_v4().as_simple().to_string();
+
+        let session =
+            yggdrasil::authenticate(client, &api_root, &username, &password, &client_token).await?;
+
+        let uuid = session.profile.id.clone();
+
+        self.add_account(FullAccount {
+            username: session.profile.name,
+            uuid: uuid.clone(),
+            type_: FullAccountType::Yggdrasil {
+                api_root,
+                access_token: session.access_token,
+                client_token: Some(session.client_token),
+            },
+            last_used: Utc::now().into(),
+        })
+        .await?;
+
+        self.set_active_uuid(Some(uuid)).await
+    }
+
+    /// Refresh the access token of a yggdrasil account, marking the account
+    /// as requiring relogin if the server rejects it.
+    async fn refresh_yggdrasil_account(self, account: FullAccount) -> anyhow::Result<()> {
+        let FullAccountType::Yggdrasil {
+            api_root,
+            access_token,
+            client_token,
+        } = account.type_
+        else {
+            bail!("account {} is not a yggdrasil account", account.uuid);
+        };
+
+        let Some(client_token) = client_token else {
+            bail!(RefreshAccountError::NoRefreshToken);
+        };
+
+        let session = yggdrasil::refresh(
+            &self.app.reqwest_client,
+            &api_root,
+            &access_token,
+            &client_token,
+        )
+        .await;
+
+        let (username, type_) = match session {
+            Ok(session) => (
+                session.profile.name,
+                FullAccountType::Yggdrasil {
+                    api_root,
+                    access_token: session.access_token,
+                    client_token: Some(session.client_token),
+                },
+            ),
+            Err(e) if e.is::<YggdrasilError>() => {
+                warn!(
+                    "Yggdrasil server rejected the session of {}, marking the account as requiring relogin (Invalid): {e}",
+                    account.uuid
+                );
+
+                (
+                    account.username,
+                    FullAccountType::Yggdrasil {
+                        api_root,
+                        access_token,
+                        client_token: None,
+                    },
+                )
+            }
+            Err(e) => bail!(e),
+        };
+
+        self.add_account(FullAccount {
+            username,
+            uuid: account.uuid,
+            type_,
+            last_used: account.last_used,
+        })
+        .await
+    }
+
+    pub async fn refresh_account(self, uuid: String) -> anyhow::Result<()> {
         info!("Refreshing account {uuid}");
 
         let account = self
-            .app
-            .prisma_client
-            .account()
-            .find_unique(UniqueWhereParam::UuidEquals(uuid.clone()))
-            .exec()
+            .get_account_entry(uuid.clone())
             .await?
             .ok_or(RefreshAccountError::NoAccount)?;
 
+        if account.yggdrasil_server.is_some() {
+            return self.refresh_yggdrasil_account(account.try_into()?).await;
+        }
+
         let Some(refresh_token) = &account.ms_refresh_token else {
             warn!("No refresh token, aborting refresh for {uuid}");
             bail!(RefreshAccountError::NoRefreshToken)
@@ -616,7 +920,14 @@ impl<'s> ManagerRef<'s, AccountManager> {
                         let r = account_manager.add_account(account.clone().into()).await;
 
                         match r {
-                            Ok(_) => info!("Refreshed account {}", &self.account.uuid),
+                            Ok(_) => {
+                                info!("Refreshed account {}", &self.account.uuid);
+                                account_manager
+                                    .refresh_schedule
+                                    .lock()
+                                    .await
+                                    .succeeded(&self.account.uuid);
+                            }
                             Err(e) => {
                                 error!({ error = ?e }, "Failed to update account information {}", &self.account.uuid)
                             }
@@ -624,8 +935,26 @@ impl<'s> ManagerRef<'s, AccountManager> {
 
                         drop(status);
                         refreshing.remove(&self.account.uuid);
+                        drop(refreshing);
+
+                        app.invalidate(GET_ACCOUNTS, Some(self.account.uuid.clone().into()));
                     }
                     EnrollmentStatus::Failed(e) => {
+                        let message = match e {
+                            Ok(e) => e.to_string(),
+                            Err(e) => format!("{e:#}"),
+                        };
+                        let backoff = account_manager.refresh_schedule.lock().await.failed(
+                            &self.account.uuid,
+                            FailureKind::of(e),
+                            message,
+                            Instant::now(),
+                        );
+                        debug!(
+                            "Retrying the refresh of account {} in {backoff:?}",
+                            self.account.uuid
+                        );
+
                         let FullAccountType::Microsoft {
                             access_token,
                             token_expires,
@@ -665,6 +994,9 @@ impl<'s> ManagerRef<'s, AccountManager> {
 
                         drop(status);
                         refreshing.remove(&self.account.uuid);
+                        drop(refreshing);
+
+                        app.invalidate(GET_ACCOUNTS, Some(self.account.uuid.clone().into()));
                     }
                     _ => {}
                 }
@@ -675,6 +1007,7 @@ impl<'s> ManagerRef<'s, AccountManager> {
 
         let enrollment = EnrollmentTask::refresh(
             self.app.reqwest_client.clone(),
+            self.auth_endpoints(),
             refresh_token.clone(),
             Invalidator {
                 app: AppRef(Arc::downgrade(self.app)),
@@ -690,6 +1023,84 @@ impl<'s> ManagerRef<'s, AccountManager> {
         Ok(())
     }
 
+    /// Starts refreshing every microsoft account whose token is about to
+    /// expire, skipping accounts still backing off from a failed refresh.
+    ///
+    /// Accounts are refreshed in order of priority:
+    /// 1. GDL account (if any)
+    /// 2. Currently selected account (if any and different from GDL account)
+    /// 3. All other accounts
+    pub async fn refresh_due_accounts(self) -> anyhow::Result<()> {
+        let current_account = self.get_active_uuid().await?;
+        let saved_gdl_account_uuid = self
+            .app
+            .settings_manager()
+            .get_settings()
+            .await?
+            .gdl_account_uuid;
+
+        let mut accounts = self.get_account_entries().await?;
+        accounts.sort_by(|a, b| {
+            if let Some(gdl_account_uuid) = &saved_gdl_account_uuid {
+                if &a.uuid == gdl_account_uuid {
+                    return Ordering::Less;
+                } else if &b.uuid == gdl_account_uuid {
+                    return Ordering::Greater;
+                }
+            }
+
+            let Some(current_account) = &current_account else {
+                return Ordering::Equal;
+            };
+
+            if &a.uuid == current_account {
+                return Ordering::Less;
+            } else if &b.uuid == current_account {
+                return Ordering::Greater;
+            }
+
+            Ordering::Equal
+        });
+
+        for account in accounts {
+            let uuid = account.uuid.clone();
+            // ignore badly formed account entries since we can't handle them
+            let Ok(account) = FullAccount::try_from(account) else {
+                error!("Badly formed account entry for uuid {uuid}. Cannot check refresh status.");
+                continue;
+            };
+            let FullAccountType::Microsoft {
+                token_expires,
+                refresh_token: Some(_),
+                ..
+            } = account.type_
+            else {
+                continue;
+            };
+
+            let due = self.refresh_schedule.lock().await.is_due(
+                &uuid,
+                token_expires,
+                Utc::now(),
+                Instant::now(),
+            );
+
+            trace!("Checking account {uuid} for token expiration. Expires at {token_expires}, due: {due}");
+
+            if !due || self.currently_refreshing.read().await.contains_key(&uuid) {
+                continue;
+            }
+
+            debug!("Attempting to refresh access token for expiring account {uuid}");
+
+            if let Err(e) = self.refresh_account(uuid.clone()).await {
+                error!({ error = ?e }, "Failed to refresh access token for {uuid}");
+            }
+        }
+
+        Ok(())
+    }
+
     pub async fn delete_account(self, uuid: String) -> anyhow::Result<()> {
         use db::account::{OrderByParam, UniqueWhereParam};
 
@@ -738,6 +1149,8 @@ impl<'s> ManagerRef<'s, AccountManager> {
             Ok(_) => {
                 info!("Deleted account {uuid}");
 
+                self.refresh_schedule.lock().await.forget(&uuid);
+
                 self.app.invalidate(GET_ACCOUNTS, None);
 
                 Ok(())
@@ -773,8 +1186,11 @@ impl<'s> ManagerRef<'s, AccountManager> {
 
                 info!("Beginning account enrollment");
 
-                let active_enrollment =
-                    EnrollmentTask::begin(client, Invalidator(AppRef(Arc::downgrade(self.app))));
+                let active_enrollment = EnrollmentTask::begin(
+                    client,
+                    self.auth_endpoints(),
+                    Invalidator(AppRef(Arc::downgrade(self.app))),
+                );
 
                 *enrollment = Some(active_enrollment);
                 self.app.invalidate(ENROLL_GET_STATUS, None);
@@ -869,7 +1285,47 @@ impl<'s> ManagerRef<'s, AccountManager> {
             }
         };
 
-        let profile = api::get_profile(&self.app.reqwest_client, &access_token).await;
+        if let AccountType::Yggdrasil { .. } = &account.account.type_ {
+            drop(refresh_lock);
+
+            let full_account = self
+                .get_account_entry(uuid.clone())
+                .await?
+                .ok_or_else(|| ValidateAccountError::AccountMissing(uuid.clone()))?;
+            let full_account = FullAccount::try_from(full_account)?;
+
+            let FullAccountType::Yggdrasil {
+                api_root,
+                client_token: Some(client_token),
+                ..
+            } = &full_account.type_
+            else {
+                return Ok(());
+            };
+
+            let valid = yggdrasil::validate(
+                &self.app.reqwest_client,
+                api_root,
+                &access_token,
+                client_token,
+            )
+            .await?;
+
+            if !valid {
+                info!("Yggdrasil access token was invalid, refreshing");
+                self.refresh_yggdrasil_account(full_account).await?;
+                self.app.invalidate(GET_ACCOUNTS, None);
+            }
+
+            return Ok(());
+        }
+
+        let profile = api::get_profile(
+            &self.app.reqwest_client,
+            &self.auth_endpoints(),
+            &access_token,
+        )
+        .await;
 
         if let Some(refresh_lock) = &mut refresh_lock {
             **refresh_lock = Some(Instant::now() + Duration::from_secs(30));
@@ -1016,90 +1472,15 @@ impl AccountRefreshService {
         let notifier_clone = notifier.clone();
 
         tokio::spawn(async move {
-            let mut first_check_done = false;
-
             while let Some(app) = app.upgrade() {
-                let account_manager = app.account_manager();
-
-                let current_account = account_manager.get_active_uuid().await.unwrap_or_default();
-                let saved_gdl_account_uuid = app
-                    .settings_manager()
-                    .get_settings()
-                    .await
-                    .map(|settings| settings.gdl_account_uuid)
-                    .unwrap_or_default();
-
-                // TODO: there's not really a way to handle an error in here
-                if let Ok(accounts) = account_manager.get_account_entries().await {
-                    // Sort accounts by priority. Currently priority is
-                    // 1. GDL account (if any)
-                    // 2. Currently selected account (if any and different from GDL account)
-                    // 3. All other accounts
-
-                    let mut accounts = accounts.into_iter().collect::<Vec<_>>();
-                    accounts.sort_by(|a, b| {
-                        if let Some(gdl_account_uuid) = &saved_gdl_account_uuid {
-                            if &a.uuid == gdl_account_uuid {
-                                return Ordering::Less;
-                            } else if &b.uuid == gdl_account_uuid {
-                                return Ordering::Greater;
-                            }
-                        }
-
-                        let Some(current_account) = &current_account else {
-                            return Ordering::Equal;
-                        };
-
-                        if &a.uuid == current_account {
-                            return Ordering::Less;
-                        } else if &b.uuid == current_account {
-                            return Ordering::Greater;
-                        }
-
-                        Ordering::Equal
-                    });
-
-                    for account in accounts {
-                        let uuid = account.uuid.clone();
-                        // ignore badly formed account entries since we can't handle them
-                        let Ok(account) = FullAccount::try_from(account) else {
-                            tracing::error!("Badly formed account entry for uuid {uuid}. Cannot check refresh status.");
-                            continue;
-                        };
-                        let FullAccountType::Microsoft { token_expires, .. } = account.type_ else {
-                            continue;
-                        };
-
-                        let now = Utc::now();
-                        let token_expiration_threshold =
-                            token_expires - chrono::Duration::hours(12);
-
-                        trace!("Checking account {uuid} for token expiration. Expires at {token_expires}. Current time is {now}. Comparison is {token_expiration_threshold} < {now}", now = Utc::now());
-
-                        if token_expiration_threshold < now {
-                            debug!(
-                                "Attempting to refresh access token for expired account {}",
-                                &account.uuid
-                            );
-                            let r = account_manager.refresh_account(account.uuid.clone()).await;
+                let r = app.account_manager().refresh_due_accounts().await;
 
-                            if let Err(e) = r {
-                                error!({ error = ?e }, "Failed to refresh access token for {}", &account.uuid);
-                            }
-
-                            // This works because GDL account is guaranteed to be first in the list
-                            // and if there is no gdl account, the currently selected account is guaranteed
-                            // to be first
-                            if !first_check_done {
-                                notifier_clone.notify_one();
-                                first_check_done = true;
-                            }
-
-                            break;
-                        }
-                    }
+                if let Err(e) = r {
+                    error!({ error = ?e }, "Failed to refresh expiring accounts");
                 }
 
+                // The GDL account and the currently selected account are refreshed
+                // first, and have started refreshing by now
                 notifier_clone.notify_one();
 
                 tokio::time::sleep(Duration::from_secs(30)).await;
@@ -1167,6 +1548,12 @@ pub enum RefreshAccountError {
     Query(#[from] QueryError),
 }
 
+#[derive(Error, Debug)]
+pub enum AddOfflineAccountError {
+    #[error("invalid offline username `{0}`, expected 3 to 16 letters, digits or underscores")]
+    InvalidUsername(String),
+}
+
 #[derive(Error, Debug)]
 pub enum FinalizeEnrollmentError {
     #[error("no active enrollment")]
@@ -1215,6 +1602,13 @@ pub enum FullAccountType {
         token_expires: DateTime<Utc>,
         skin_id: Option<String>,
     },
+    Yggdrasil {
+        api_root: String,
+        access_token: String,
+        /// `None` once the server rejected the session, the account needs
+        /// to log in again.
+        client_token: Option<String>,
+    },
 }
 
 #[derive(Clone, Serialize, Deserialize)]
@@ -1222,6 +1616,17 @@ struct Claims {
     email: Option<String>,
 }
 
+/// The uuid offline mode servers assign to `username`, same as java's
+/// `UUID.nameUUIDFromBytes("OfflinePlayer:" + username)`.
+pub fn offline_uuid(username: &str) -> String {
+    let hash = Md5::digest(format!("OfflinePlayer:{username}"));
+
+    uuid::Builder::from_md5_bytes(hash.into())
+        .into_uuid()
+        .as_simple()
+        .to_string()
+}
+
 fn extract_email(token: Option<&String>) -> Option<String> {
     token.and_then(|token| {
         let claims: Result<Token<Header, Claims, _>, _> = jwt::Token::parse_unverified(&*token);
@@ -1256,8 +1661,15 @@ impl TryFrom<db::account::Data> for FullAccount {
 
     fn try_from(value: db::account::Data) -> Result<Self, Self::Error> {
         Ok(Self {
-            type_: match value.access_token {
-                Some(access_token) => FullAccountType::Microsoft {
+            type_: match (value.yggdrasil_server, value.access_token) {
+                (Some(api_root), access_token) => FullAccountType::Yggdrasil {
+                    api_root,
+                    access_token: access_token.ok_or_else(|| {
+                        FullAccountLoadError::MissingAccessToken(value.uuid.clone())
+                    })?,
+                    client_token: value.yggdrasil_client_token,
+                },
+                (None, Some(access_token)) => FullAccountType::Microsoft {
                     email: extract_email(value.id_token.as_ref()),
                     access_token,
                     refresh_token: value.ms_refresh_token,
@@ -1270,7 +1682,7 @@ impl TryFrom<db::account::Data> for FullAccount {
                         })?,
                     skin_id: value.skin_id,
                 },
-                None => FullAccountType::Offline,
+                (None, None) => FullAccountType::Offline,
             },
             last_used: value.last_used,
             uuid: value.uuid,
@@ -1289,6 +1701,9 @@ impl From<FullAccount> for AccountWithStatus {
                 type_: match value.type_.clone() {
                     FullAccountType::Microsoft { email, .. } => AccountType::Microsoft { email },
                     FullAccountType::Offline => AccountType::Offline,
+                    FullAccountType::Yggdrasil { api_root, .. } => {
+                        AccountType::Yggdrasil { server: api_root }
+                    }
                 },
                 skin_id: match &value.type_ {
                     FullAccountType::Microsoft { skin_id, .. } => skin_id.clone(),
@@ -1316,7 +1731,14 @@ impl From<FullAccount> for AccountWithStatus {
                     },
                 },
                 FullAccountType::Offline => AccountStatus::Ok { access_token: None },
+                FullAccountType::Yggdrasil {
+                    client_token: None, ..
+                } => AccountStatus::Invalid,
+                FullAccountType::Yggdrasil { access_token, .. } => AccountStatus::Ok {
+                    access_token: Some(access_token),
+                },
             },
+            last_refresh_error: None,
         }
     }
 }
@@ -1343,4 +1765,345 @@ impl From<api::FullAccount> for FullAccount {
 pub enum FullAccountLoadError {
     #[error("attempted to parse microsoft account DB entry(uuid {0}), but was missing refresh token expiration timestamp")]
     MissingExpiration(String),
+
+    #[error(
+        "attempted to parse yggdrasil account DB entry(uuid {0}), but was missing access token"
+    )]
+    MissingAccessToken(String),
+}
+
+#[cfg(test)]
+mod test {
+    use carbon_repos::db::account::{SetParam, UniqueWhereParam};
+    use chrono::Utc;
+
+    use crate::domain::account::{AccountStatus, AccountType};
+    use crate::managers::App;
+
+    use super::api::AuthEndpoints;
+    use super::offline_uuid;
+    use super::vault::{TokenVault, VaultError};
+    use super::{FullAccount, FullAccountType};
+
+    #[test]
+    fn offline_uuid_matches_vanilla() {
+        assert_eq!(offline_uuid("Notch"), "b50ad385829d3141a2167e7d7539ba7f");
+    }
+
+    #[tokio::test]
+    async fn test_add_offline_account() {
+        let app = crate::setup_managers_for_test().await;
+        let account_manager = app.account_manager();
+
+        account_manager
+            .add_offline_account("Dev_Player".to_string())
+            .await
+            .unwrap();
+
+        assert!(account_manager
+            .add_offline_account("not a name".to_string())
+            .await
+            .is_err());
+        assert!(account_manager
+            .add_offline_account("ab".to_string())
+            .await
+            .is_err());
+
+        let uuid = offline_uuid("Dev_Player");
+        assert_eq!(account_manager.get_active_uuid().await.unwrap(), Some(uuid));
+
+        let accounts = account_manager.get_account_list().await.unwrap();
+        assert_eq!(accounts.len(), 1);
+        assert_eq!(accounts[0].account.username, "Dev_Player");
+        assert!(matches!(accounts[0].account.type_, AccountType::Offline));
+        assert!(matches!(
+            accounts[0].status,
+            AccountStatus::Ok { access_token: None }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_plaintext_tokens_are_sealed() {
+        let app = crate::setup_managers_for_test().await;
+        let account_manager = app.account_manager();
+
+        let uuid = "0123456789abcdef0123456789abcdef".to_string();
+        let expires = Utc::now() + chrono::Duration::hours(1);
+
+        // as written by earlier versions
+        app.prisma_client
+            .account()
+            .create(
+                uuid.clone(),
+                "Steve".to_string(),
+                Utc::now().into(),
+                vec![
+                    SetParam::SetAccessToken(Some("plain-access".to_string())),
+                    SetParam::SetMsRefreshToken(Some("plain-refresh".to_string())),
+                    SetParam::SetIdToken(Some("plain-id".to_string())),
+                    SetParam::SetTokenExpires(Some(expires.into())),
+                ],
+            )
+            .exec()
+            .await
+            .unwrap();
+
+        account_manager.seal_plaintext_tokens().await.unwrap();
+
+        let raw = app
+            .prisma_client
+            .account()
+            .find_unique(UniqueWhereParam::UuidEquals(uuid.clone()))
+            .exec()
+            .await
+            .unwrap()
+            .unwrap();
+
+        for token in [&raw.access_token, &raw.ms_refresh_token, &raw.id_token] {
+            let token = token.as_ref().unwrap();
+            assert!(TokenVault::is_sealed(token));
+            assert!(!token.contains("plain"));
+        }
+
+        let entry = account_manager
+            .get_account_entry(uuid.clone())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(entry.access_token.as_deref(), Some("plain-access"));
+        assert_eq!(entry.ms_refresh_token.as_deref(), Some("plain-refresh"));
+        assert_eq!(entry.id_token.as_deref(), Some("plain-id"));
+
+        // sealed values don't open in another column
+        app.prisma_client
+            .account()
+            .update(
+                UniqueWhereParam::UuidEquals(uuid.clone()),
+                vec![SetParam::SetAccessToken(raw.ms_refresh_token.clone())],
+            )
+            .exec()
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            account_manager
+                .get_account_status(uuid.clone())
+                .await
+                .unwrap(),
+            Some(AccountStatus::Invalid)
+        ));
+
+        account_manager
+            .set_active_uuid(Some(uuid.clone()))
+            .await
+            .unwrap();
+        let e = account_manager.get_active_account().await.unwrap_err();
+        assert!(matches!(
+            e.downcast_ref::<VaultError>(),
+            Some(VaultError::ReloginRequired(_))
+        ));
+    }
+
+    const REFRESH_TEST_UUID: &str = "fedcba9876543210fedcba9876543210";
+
+    fn jwt(
+        algorithm: jsonwebtoken::Algorithm,
+        key: &jsonwebtoken::EncodingKey,
+        claims: serde_json::Value,
+    ) -> String {
+        jsonwebtoken::encode(&jsonwebtoken::Header::new(algorithm), &claims, key).unwrap()
+    }
+
+    /// Mocks the microsoft, xbox and minecraft services, returning the mock
+    /// of the minecraft login. Entitlements are signed with `entitlement_key`.
+    async fn mock_auth_services(
+        app: &App,
+        server: &mut mockito::ServerGuard,
+        entitlement_alg: jsonwebtoken::Algorithm,
+        entitlement_key: &jsonwebtoken::EncodingKey,
+    ) -> mockito::Mock {
+        app.account_manager().set_auth_endpoints(AuthEndpoints {
+            microsoft_login: server.url(),
+            xbox_user_auth: server.url(),
+            xsts_auth: server.url(),
+            minecraft_services: server.url(),
+            entitlement_key: jsonwebtoken::DecodingKey::from_rsa_pem(include_bytes!(
+                "test_entitlement_key.pub.pem"
+            ))
+            .unwrap(),
+        });
+
+        let id_token = jwt(
+            jsonwebtoken::Algorithm::HS256,
+            &jsonwebtoken::EncodingKey::from_secret(b"id"),
+            serde_json::json!({ "email": "steve@example.com" }),
+        );
+
+        server
+            .mock("POST", "/consumers/oauth2/v2.0/token")
+            .with_body(
+                serde_json::json!({
+                    "access_token": "ms-access",
+                    "id_token": id_token,
+                    "refresh_token": "new-refresh",
+                    "expires_in": 3600,
+                })
+                .to_string(),
+            )
+            .create_async()
+            .await;
+        server
+            .mock("POST", "/user/authenticate")
+            .with_body(r#"{"Token":"xbl"}"#)
+            .create_async()
+            .await;
+        server
+            .mock("POST", "/xsts/authorize")
+            .with_body(r#"{"Token":"xsts","DisplayClaims":{"xui":[{"uhs":"hash"}]}}"#)
+            .create_async()
+            .await;
+
+        let entitlements = jwt(
+            entitlement_alg,
+            entitlement_key,
+            serde_json::json!({
+                "entitlements": [{ "name": "product_minecraft" }],
+                "exp": (Utc::now() + chrono::Duration::hours(1)).timestamp(),
+            }),
+        );
+        server
+            .mock("GET", "/entitlements/mcstore")
+            .with_body(serde_json::json!({ "signature": entitlements }).to_string())
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/minecraft/profile")
+            .with_body(
+                serde_json::json!({ "id": REFRESH_TEST_UUID, "name": "Steve", "skins": [] })
+                    .to_string(),
+            )
+            .create_async()
+            .await;
+
+        server
+            .mock("POST", "/authentication/login_with_xbox")
+            .with_body(r#"{"access_token":"new-access","expires_in":86400}"#)
+            .expect(1)
+            .create_async()
+            .await
+    }
+
+    /// Adds an account whose token expires within the refresh window.
+    async fn add_expiring_account(app: &App) {
+        app.account_manager()
+            .add_account(FullAccount {
+                username: "Steve".to_string(),
+                uuid: REFRESH_TEST_UUID.to_string(),
+                type_: FullAccountType::Microsoft {
+                    access_token: "old-access".to_string(),
+                    refresh_token: Some("old-refresh".to_string()),
+                    id_token: Some(jwt(
+                        jsonwebtoken::Algorithm::HS256,
+                        &jsonwebtoken::EncodingKey::from_secret(b"id"),
+                        serde_json::json!({ "email": "steve@example.com" }),
+                    )),
+                    email: Some("steve@example.com".to_string()),
+                    token_expires: Utc::now() + chrono::Duration::hours(1),
+                    skin_id: None,
+                },
+                last_used: Utc::now().into(),
+            })
+            .await
+            .unwrap();
+    }
+
+    async fn wait_for_refresh(app: &App) {
+        for _ in 0..100 {
+            let status = app
+                .account_manager()
+                .get_account_status(REFRESH_TEST_UUID.to_string())
+                .await
+                .unwrap();
+
+            if !matches!(status, Some(AccountStatus::Refreshing)) {
+                return;
+            }
+
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        }
+
+        panic!("account refresh did not complete");
+    }
+
+    #[tokio::test]
+    async fn test_background_refresh() {
+        let app = crate::setup_managers_for_test().await;
+        let account_manager = app.account_manager();
+        let mut server = mockito::Server::new_async().await;
+
+        let login = mock_auth_services(
+            &app,
+            &mut server,
+            jsonwebtoken::Algorithm::RS256,
+            &jsonwebtoken::EncodingKey::from_rsa_pem(include_bytes!("test_entitlement_key.pem"))
+                .unwrap(),
+        )
+        .await;
+        add_expiring_account(&app).await;
+
+        account_manager.refresh_due_accounts().await.unwrap();
+        wait_for_refresh(&app).await;
+
+        let entry = account_manager
+            .get_account_entry(REFRESH_TEST_UUID.to_string())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(entry.access_token.as_deref(), Some("new-access"));
+        assert_eq!(entry.ms_refresh_token.as_deref(), Some("new-refresh"));
+
+        // the new token isn't due for a refresh yet
+        account_manager.refresh_due_accounts().await.unwrap();
+        wait_for_refresh(&app).await;
+        login.assert_async().await;
+
+        let accounts = account_manager.get_account_list().await.unwrap();
+        assert!(matches!(accounts[0].status, AccountStatus::Ok { .. }));
+        assert_eq!(accounts[0].last_refresh_error, None);
+    }
+
+    #[tokio::test]
+    async fn test_background_refresh_backoff() {
+        let app = crate::setup_managers_for_test().await;
+        let account_manager = app.account_manager();
+        let mut server = mockito::Server::new_async().await;
+
+        // not signed by the expected key
+        let login = mock_auth_services(
+            &app,
+            &mut server,
+            jsonwebtoken::Algorithm::HS256,
+            &jsonwebtoken::EncodingKey::from_secret(b"forged"),
+        )
+        .await;
+        add_expiring_account(&app).await;
+
+        account_manager.refresh_due_accounts().await.unwrap();
+        wait_for_refresh(&app).await;
+
+        // backing off, so the services aren't contacted again
+        account_manager.refresh_due_accounts().await.unwrap();
+        wait_for_refresh(&app).await;
+        login.assert_async().await;
+
+        let accounts = account_manager.get_account_list().await.unwrap();
+        assert!(accounts[0].last_refresh_error.is_some());
+
+        let entry = account_manager
+            .get_account_entry(REFRESH_TEST_UUID.to_string())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(entry.access_token.as_deref(), Some("old-access"));
+    }
 }
@@ -0,0 +1,196 @@
+//! Scheduling of background microsoft token refreshes.
+//!
+//! Tokens are refreshed some time before they expire, so launching the game
+//! doesn't have to wait on re-authentication. Failed refreshes are retried
+//! with an exponential backoff, longer for errors caused by the account
+//! itself as retrying those is unlikely to help.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+
+use super::api::{McEntitlementError, XboxError};
+use super::enroll::EnrollmentError;
+
+/// How long before `tokenExpires` an account gets refreshed
+pub const REFRESH_AHEAD: chrono::Duration = chrono::Duration::hours(12);
+
+const ACCOUNT_BACKOFF: (Duration, Duration) = (
+    Duration::from_secs(30 * 60),
+    Duration::from_secs(24 * 60 * 60),
+);
+const TRANSIENT_BACKOFF: (Duration, Duration) =
+    (Duration::from_secs(60), Duration::from_secs(30 * 60));
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailureKind {
+    /// Rejected by xbox or mojang, e.g. `XboxError` or `McEntitlementError`
+    Account,
+    /// Network errors, unexpected responses, ...
+    Transient,
+}
+
+impl FailureKind {
+    pub fn of(error: &Result<EnrollmentError, anyhow::Error>) -> Self {
+        match error {
+            Ok(_) => Self::Account,
+            Err(e) if e.is::<McEntitlementError>() || e.is::<XboxError>() => Self::Account,
+            Err(_) => Self::Transient,
+        }
+    }
+
+    fn backoff(self, failures: u32) -> Duration {
+        let (initial, max) = match self {
+            Self::Account => ACCOUNT_BACKOFF,
+            Self::Transient => TRANSIENT_BACKOFF,
+        };
+
+        initial
+            .saturating_mul(2u32.saturating_pow(failures.saturating_sub(1)))
+            .min(max)
+    }
+}
+
+#[derive(Debug)]
+struct FailedRefresh {
+    failures: u32,
+    retry_at: Instant,
+    error: String,
+}
+
+#[derive(Debug, Default)]
+pub struct RefreshSchedule {
+    failed: HashMap<String, FailedRefresh>,
+}
+
+impl RefreshSchedule {
+    /// If the account's token should be refreshed now, which is once it's
+    /// about to expire and the backoff of a previous failure has passed.
+    pub fn is_due(
+        &self,
+        uuid: &str,
+        token_expires: DateTime<Utc>,
+        now: DateTime<Utc>,
+        instant: Instant,
+    ) -> bool {
+        if token_expires - REFRESH_AHEAD > now {
+            return false;
+        }
+
+        self.failed
+            .get(uuid)
+            .map_or(true, |failed| failed.retry_at <= instant)
+    }
+
+    pub fn succeeded(&mut self, uuid: &str) {
+        self.failed.remove(uuid);
+    }
+
+    /// Records a failed refresh, returning how long to wait before retrying.
+    pub fn failed(
+        &mut self,
+        uuid: &str,
+        kind: FailureKind,
+        error: String,
+        instant: Instant,
+    ) -> Duration {
+        let failures = self.failed.get(uuid).map_or(0, |f| f.failures) + 1;
+        let backoff = kind.backoff(failures);
+
+        self.failed.insert(
+            uuid.to_string(),
+            FailedRefresh {
+                failures,
+                retry_at: instant + backoff,
+                error,
+            },
+        );
+
+        backoff
+    }
+
+    pub fn last_error(&self, uuid: &str) -> Option<&str> {
+        self.failed.get(uuid).map(|failed| &failed.error as &str)
+    }
+
+    pub fn forget(&mut self, uuid: &str) {
+        self.failed.remove(uuid);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::{Duration, Instant};
+
+    use anyhow::anyhow;
+    use chrono::Utc;
+
+    use super::{FailureKind, RefreshSchedule, REFRESH_AHEAD};
+    use crate::managers::account::api::{McEntitlementError, XboxError};
+    use crate::managers::account::enroll::EnrollmentError;
+
+    #[test]
+    fn refreshes_ahead_of_expiry() {
+        let schedule = RefreshSchedule::default();
+        let now = Utc::now();
+        let instant = Instant::now();
+
+        let far = now + REFRESH_AHEAD + chrono::Duration::hours(1);
+        let close = now + REFRESH_AHEAD - chrono::Duration::hours(1);
+        let expired = now - chrono::Duration::hours(1);
+
+        assert!(!schedule.is_due("a", far, now, instant));
+        assert!(schedule.is_due("a", close, now, instant));
+        assert!(schedule.is_due("a", expired, now, instant));
+    }
+
+    #[test]
+    fn backs_off_after_failures() {
+        let mut schedule = RefreshSchedule::default();
+        let now = Utc::now();
+        let instant = Instant::now();
+
+        let backoff = schedule.failed("a", FailureKind::Transient, "e1".into(), instant);
+        assert_eq!(backoff, Duration::from_secs(60));
+        assert!(!schedule.is_due("a", now, now, instant));
+        assert!(schedule.is_due("a", now, now, instant + backoff));
+        assert!(schedule.is_due("b", now, now, instant));
+        assert_eq!(schedule.last_error("a"), Some("e1"));
+
+        let backoff = schedule.failed("a", FailureKind::Transient, "e2".into(), instant);
+        assert_eq!(backoff, Duration::from_secs(120));
+        assert_eq!(schedule.last_error("a"), Some("e2"));
+
+        for _ in 0..10 {
+            schedule.failed("a", FailureKind::Transient, "e".into(), instant);
+        }
+        assert_eq!(
+            schedule.failed("a", FailureKind::Transient, "e".into(), instant),
+            Duration::from_secs(30 * 60)
+        );
+
+        schedule.succeeded("a");
+        assert!(schedule.is_due("a", now, now, instant));
+        assert_eq!(schedule.last_error("a"), None);
+
+        let backoff = schedule.failed("a", FailureKind::Account, "e".into(), instant);
+        assert_eq!(backoff, Duration::from_secs(30 * 60));
+    }
+
+    #[test]
+    fn classifies_failures() {
+        assert_eq!(
+            FailureKind::of(&Ok(EnrollmentError::XboxError(XboxError::NoAccount))),
+            FailureKind::Account
+        );
+        assert_eq!(
+            FailureKind::of(&Err(anyhow!(McEntitlementError::InvalidSignature))),
+            FailureKind::Account
+        );
+        assert_eq!(
+            FailureKind::of(&Err(anyhow!("connection reset"))),
+            FailureKind::Transient
+        );
+    }
+}
@@ -1,10 +1,20 @@
-use super::api::McSkin as ApiSkin;
+use super::api::{self, GetProfileError, McCape, McProfile, McSkin as ApiSkin, SkinVariant};
+use super::skin_render::{render_skin, SkinRenderKind};
+use crate::api::keys::account::*;
+use crate::domain::account::{AccountStatus, AccountType};
 use crate::managers::ManagerRef;
-use anyhow::ensure;
-use carbon_repos::db::{self, read_filters::StringFilter};
-use image::{GenericImageView, ImageFormat};
+use anyhow::{bail, ensure, Context};
+use carbon_repos::db::{
+    self,
+    read_filters::{IntFilter, StringFilter},
+};
+use carbon_repos::pcr::Direction;
+use chrono::{DateTime, Utc};
+use image::{GenericImageView, ImageFormat, RgbaImage};
 use std::io::Cursor;
+use std::path::PathBuf;
 use thiserror::Error;
+use tracing::info;
 
 pub struct SkinManager {}
 
@@ -15,10 +25,8 @@ impl ManagerRef<'_, SkinManager> {
 
         let account = self
             .app
-            .prisma_client
-            .account()
-            .find_unique(db::account::UniqueWhereParam::UuidEquals(uuid.clone()))
-            .exec()
+            .account_manager()
+            .get_account_entry(uuid.clone())
             .await?
             .ok_or_else(|| GetSkinError::AccountDoesNotExist(uuid.clone()))?;
 
@@ -37,19 +45,24 @@ impl ManagerRef<'_, SkinManager> {
 
         Ok(match cached_skin {
             Some(skin) => Skin {
+                id: skin.id,
                 data: skin.skin.into(),
             },
             None => {
                 let skin = match account.access_token.as_ref() {
-                    Some(token) => super::api::get_profile(&self.app.reqwest_client, token)
-                        .await
-                        .ok()
-                        .map(Result::ok)
-                        .flatten()
-                        .map(|profile| profile.skin)
-                        // use the default if there is no skin or an error occured.
-                        .flatten()
-                        .unwrap_or_else(|| DefaultSkin::from_uuid(uuid.clone()).make_api_skin()),
+                    Some(token) => super::api::get_profile(
+                        &self.app.reqwest_client,
+                        &self.app.account_manager().auth_endpoints(),
+                        token,
+                    )
+                    .await
+                    .ok()
+                    .map(Result::ok)
+                    .flatten()
+                    .map(|profile| profile.skin)
+                    // use the default if there is no skin or an error occured.
+                    .flatten()
+                    .unwrap_or_else(|| DefaultSkin::from_uuid(uuid.clone()).make_api_skin()),
                     None => DefaultSkin::from_uuid(uuid.clone()).make_api_skin(),
                 };
 
@@ -85,6 +98,7 @@ impl ManagerRef<'_, SkinManager> {
                     .await?;
 
                 Skin {
+                    id: skin.id,
                     data: skin_data.to_vec(),
                 }
             }
@@ -96,6 +110,353 @@ impl ManagerRef<'_, SkinManager> {
         let head = carbon_scheduler::cpu_block(|| stitch_head(&skin)).await?;
         Ok(head)
     }
+
+    /// Renders the account's skin, reusing an earlier render of the same skin.
+    pub async fn render(self, uuid: String, kind: SkinRenderKind) -> anyhow::Result<Vec<u8>> {
+        use db::skin_render::{SetParam, UniqueWhereParam};
+
+        let skin = self.get_skin(uuid).await?;
+        let unique =
+            || UniqueWhereParam::SkinIdKindEquals(skin.id.clone(), kind.as_str().to_string());
+
+        let cached = self
+            .app
+            .prisma_client
+            .skin_render()
+            .find_unique(unique())
+            .exec()
+            .await?;
+
+        if let Some(cached) = cached {
+            return Ok(cached.image);
+        }
+
+        let data = skin.data.clone();
+        let image = carbon_scheduler::cpu_block(move || render_skin(&data, kind)).await?;
+
+        self.app
+            .prisma_client
+            .skin_render()
+            .upsert(
+                unique(),
+                db::skin_render::create(
+                    skin.id.clone(),
+                    kind.as_str().to_string(),
+                    image.clone(),
+                    vec![],
+                ),
+                vec![SetParam::SetImage(image.clone())],
+            )
+            .exec()
+            .await?;
+
+        Ok(image)
+    }
+
+    /// Access token of a microsoft account able to change its profile.
+    async fn profile_access_token(self, uuid: &str) -> anyhow::Result<String> {
+        let account = self
+            .app
+            .account_manager()
+            .get_account(uuid.to_string())
+            .await?
+            .ok_or_else(|| GetSkinError::AccountDoesNotExist(uuid.to_string()))?;
+
+        ensure!(
+            matches!(account.account.type_, AccountType::Microsoft { .. }),
+            ChangeSkinError::NotMicrosoftAccount
+        );
+
+        match account.status {
+            AccountStatus::Ok {
+                access_token: Some(access_token),
+            } => Ok(access_token),
+            _ => bail!(ChangeSkinError::AccountNotReady),
+        }
+    }
+
+    /// Stores the skin of a profile returned after changing it. `png` is the
+    /// skin that was uploaded, cached so it doesn't have to be downloaded.
+    async fn update_profile(
+        self,
+        uuid: String,
+        profile: anyhow::Result<Result<McProfile, GetProfileError>>,
+        png: Option<Vec<u8>>,
+    ) -> anyhow::Result<McProfile> {
+        use db::skin::WhereParam;
+
+        let profile = profile??;
+        let skin_id = profile.skin.as_ref().map(|skin| skin.id.clone());
+
+        if let (Some(skin_id), Some(png)) = (&skin_id, png) {
+            self.app
+                .prisma_client
+                ._batch((
+                    // won't error on 0 deleted
+                    self.app
+                        .prisma_client
+                        .skin()
+                        .delete_many(vec![WhereParam::Id(StringFilter::Equals(skin_id.clone()))]),
+                    self.app
+                        .prisma_client
+                        .skin()
+                        .create(skin_id.clone(), png, vec![]),
+                ))
+                .await?;
+        }
+
+        self.app
+            .prisma_client
+            .account()
+            .update(
+                db::account::UniqueWhereParam::UuidEquals(uuid.clone()),
+                vec![db::account::SetParam::SetSkinId(skin_id)],
+            )
+            .exec()
+            .await?;
+
+        self.app.invalidate(GET_HEAD, Some(uuid.clone().into()));
+        self.app.invalidate(GET_CAPES, Some(uuid.into()));
+
+        Ok(profile)
+    }
+
+    /// Uploads the PNG at `path` as the account's skin.
+    pub async fn upload_skin(
+        self,
+        uuid: String,
+        path: PathBuf,
+        variant: SkinVariant,
+    ) -> anyhow::Result<()> {
+        let png = tokio::fs::read(&path)
+            .await
+            .with_context(|| format!("reading skin {}", path.display()))?;
+
+        self.upload_skin_data(uuid, png, variant).await
+    }
+
+    async fn upload_skin_data(
+        self,
+        uuid: String,
+        png: Vec<u8>,
+        variant: SkinVariant,
+    ) -> anyhow::Result<()> {
+        let png = carbon_scheduler::cpu_block(move || validate_skin(&png)).await?;
+        let access_token = self.profile_access_token(&uuid).await?;
+
+        info!("Uploading a new {} skin for {uuid}", variant.as_str());
+
+        let profile = api::upload_skin(
+            &self.app.reqwest_client,
+            &self.app.account_manager().auth_endpoints(),
+            &access_token,
+            variant,
+            &png,
+        )
+        .await;
+
+        self.update_profile(uuid, profile, Some(png)).await?;
+        Ok(())
+    }
+
+    /// Resets the account's skin to the default one.
+    pub async fn reset_skin(self, uuid: String) -> anyhow::Result<()> {
+        let access_token = self.profile_access_token(&uuid).await?;
+
+        info!("Resetting the skin of {uuid}");
+
+        let profile = api::reset_skin(
+            &self.app.reqwest_client,
+            &self.app.account_manager().auth_endpoints(),
+            &access_token,
+        )
+        .await;
+
+        self.update_profile(uuid, profile, None).await?;
+        Ok(())
+    }
+
+    /// Capes owned by the account.
+    pub async fn get_capes(self, uuid: String) -> anyhow::Result<Vec<McCape>> {
+        let access_token = self.profile_access_token(&uuid).await?;
+
+        let profile = api::get_profile(
+            &self.app.reqwest_client,
+            &self.app.account_manager().auth_endpoints(),
+            &access_token,
+        )
+        .await??;
+
+        Ok(profile.capes)
+    }
+
+    /// Shows the cape with the given id, or hides the active cape if `None`.
+    pub async fn set_active_cape(
+        self,
+        uuid: String,
+        cape_id: Option<String>,
+    ) -> anyhow::Result<()> {
+        let access_token = self.profile_access_token(&uuid).await?;
+
+        let profile = api::set_active_cape(
+            &self.app.reqwest_client,
+            &self.app.account_manager().auth_endpoints(),
+            &access_token,
+            cape_id.as_deref(),
+        )
+        .await;
+
+        self.update_profile(uuid, profile, None).await?;
+        Ok(())
+    }
+
+    /// Saves the PNG at `path` to the skin library, returning its id.
+    pub async fn save_skin(
+        self,
+        name: String,
+        path: PathBuf,
+        variant: SkinVariant,
+    ) -> anyhow::Result<i32> {
+        let png = tokio::fs::read(&path)
+            .await
+            .with_context(|| format!("reading skin {}", path.display()))?;
+
+        self.save_skin_data(name, png, variant).await
+    }
+
+    async fn save_skin_data(
+        self,
+        name: String,
+        png: Vec<u8>,
+        variant: SkinVariant,
+    ) -> anyhow::Result<i32> {
+        let png = carbon_scheduler::cpu_block(move || validate_skin(&png)).await?;
+
+        let saved = self
+            .app
+            .prisma_client
+            .saved_skin()
+            .create(name, variant.as_str().to_string(), png, vec![])
+            .exec()
+            .await?;
+
+        self.app.invalidate(GET_SAVED_SKINS, None);
+        Ok(saved.id)
+    }
+
+    pub async fn get_saved_skins(self) -> anyhow::Result<Vec<SavedSkin>> {
+        use db::saved_skin::OrderByParam;
+
+        self.app
+            .prisma_client
+            .saved_skin()
+            .find_many(vec![])
+            .order_by(OrderByParam::CreatedAt(Direction::Desc))
+            .exec()
+            .await?
+            .into_iter()
+            .map(|saved| {
+                Ok(SavedSkin {
+                    id: saved.id,
+                    name: saved.name,
+                    variant: saved.variant.parse()?,
+                    created_at: saved.created_at.into(),
+                })
+            })
+            .collect()
+    }
+
+    async fn get_saved_skin(self, id: i32) -> anyhow::Result<db::saved_skin::Data> {
+        self.app
+            .prisma_client
+            .saved_skin()
+            .find_unique(db::saved_skin::UniqueWhereParam::IdEquals(id))
+            .exec()
+            .await?
+            .ok_or_else(|| anyhow::anyhow!(SavedSkinError::DoesNotExist(id)))
+    }
+
+    pub async fn get_saved_skin_image(self, id: i32) -> anyhow::Result<Vec<u8>> {
+        Ok(self.get_saved_skin(id).await?.skin)
+    }
+
+    pub async fn delete_saved_skin(self, id: i32) -> anyhow::Result<()> {
+        self.app
+            .prisma_client
+            .saved_skin()
+            .delete_many(vec![db::saved_skin::WhereParam::Id(IntFilter::Equals(id))])
+            .exec()
+            .await?;
+
+        self.app.invalidate(GET_SAVED_SKINS, None);
+        Ok(())
+    }
+
+    /// Uploads a skin from the library as the account's skin.
+    pub async fn apply_saved_skin(self, uuid: String, id: i32) -> anyhow::Result<()> {
+        let saved = self.get_saved_skin(id).await?;
+
+        self.upload_skin_data(uuid, saved.skin, saved.variant.parse()?)
+            .await
+    }
+}
+
+/// Checks `png` is a skin the profile api accepts, converting legacy 64x32
+/// skins to the current 64x64 layout.
+pub fn validate_skin(png: &[u8]) -> anyhow::Result<Vec<u8>> {
+    ensure!(
+        matches!(image::guess_format(png), Ok(ImageFormat::Png)),
+        InvalidSkinError::NotPng
+    );
+
+    let image = image::load_from_memory_with_format(png, ImageFormat::Png)
+        .map_err(|_| InvalidSkinError::NotPng)?;
+
+    match image.dimensions() {
+        (64, 64) => Ok(png.to_vec()),
+        (64, 32) => {
+            let skin = convert_legacy_skin(&image.to_rgba8());
+
+            let mut output = Vec::<u8>::new();
+            skin.write_to(&mut Cursor::new(&mut output), ImageFormat::Png)?;
+            Ok(output)
+        }
+        (width, height) => bail!(InvalidSkinError::Dimensions(width, height)),
+    }
+}
+
+/// Legacy skins have no separate left limbs, they mirror the right ones.
+pub(super) fn convert_legacy_skin(legacy: &RgbaImage) -> RgbaImage {
+    use image::imageops::{flip_horizontal, replace};
+
+    // (x, y, width, height) of a right limb face and where it goes on the left limb
+    #[rustfmt::skip]
+    const MIRRORED: [((u32, u32, u32, u32), (i64, i64)); 12] = [
+        // leg: top, bottom, outer, front, inner, back
+        ((4, 16, 4, 4), (20, 48)),
+        ((8, 16, 4, 4), (24, 48)),
+        ((0, 20, 4, 12), (24, 52)),
+        ((4, 20, 4, 12), (20, 52)),
+        ((8, 20, 4, 12), (16, 52)),
+        ((12, 20, 4, 12), (28, 52)),
+        // arm: top, bottom, outer, front, inner, back
+        ((44, 16, 4, 4), (36, 48)),
+        ((48, 16, 4, 4), (40, 48)),
+        ((40, 20, 4, 12), (40, 52)),
+        ((44, 20, 4, 12), (36, 52)),
+        ((48, 20, 4, 12), (32, 52)),
+        ((52, 20, 4, 12), (44, 52)),
+    ];
+
+    let mut skin = RgbaImage::new(64, 64);
+    replace(&mut skin, legacy, 0, 0);
+
+    for ((x, y, width, height), (target_x, target_y)) in MIRRORED {
+        let face = flip_horizontal(&legacy.view(x, y, width, height).to_image());
+        replace(&mut skin, &face, target_x, target_y);
+    }
+
+    skin
 }
 
 fn stitch_head(image: &[u8]) -> anyhow::Result<Vec<u8>> {
@@ -143,9 +504,19 @@ fn stitch_head(image: &[u8]) -> anyhow::Result<Vec<u8>> {
 }
 
 pub struct Skin {
+    id: String,
     data: Vec<u8>,
 }
 
+/// A skin in the skin library
+#[derive(Debug)]
+pub struct SavedSkin {
+    pub id: i32,
+    pub name: String,
+    pub variant: SkinVariant,
+    pub created_at: DateTime<Utc>,
+}
+
 #[derive(Copy, Clone)]
 pub enum DefaultSkin {
     Steve,
@@ -211,6 +582,30 @@ pub enum GetSkinError {
     AccountDoesNotExist(String),
 }
 
+#[derive(Debug, Error)]
+pub enum ChangeSkinError {
+    #[error("only microsoft accounts can change their skin or cape")]
+    NotMicrosoftAccount,
+
+    #[error("account needs to be refreshed before changing its skin or cape")]
+    AccountNotReady,
+}
+
+#[derive(Debug, Error)]
+pub enum InvalidSkinError {
+    #[error("skin is not a PNG image")]
+    NotPng,
+
+    #[error("skin must be 64x64 or 64x32 (legacy), got {0}x{1}")]
+    Dimensions(u32, u32),
+}
+
+#[derive(Debug, Error)]
+pub enum SavedSkinError {
+    #[error("saved skin {0} does not exist")]
+    DoesNotExist(i32),
+}
+
 /*
 #[cfg(test)]
 mod test {
@@ -241,3 +636,206 @@ mod test {
     }
 }
 */
+
+#[cfg(test)]
+mod test {
+    use std::io::Cursor;
+
+    use chrono::Utc;
+    use image::{GenericImageView, ImageFormat, Rgba, RgbaImage};
+
+    use super::{validate_skin, DefaultSkin, InvalidSkinError};
+    use crate::managers::account::api::{AuthEndpoints, SkinVariant};
+    use crate::managers::account::skin_render::SkinRenderKind;
+    use crate::managers::account::{FullAccount, FullAccountType};
+
+    const UUID: &str = "0123456789abcdef0123456789abcdef";
+
+    fn png(image: &RgbaImage) -> Vec<u8> {
+        let mut output = Vec::new();
+        image
+            .write_to(&mut Cursor::new(&mut output), ImageFormat::Png)
+            .unwrap();
+        output
+    }
+
+    #[test]
+    fn validates_skins() {
+        let skin = png(&RgbaImage::new(64, 64));
+        assert_eq!(validate_skin(&skin).unwrap(), skin);
+
+        let e = validate_skin(&png(&RgbaImage::new(32, 32))).unwrap_err();
+        assert!(matches!(
+            e.downcast_ref::<InvalidSkinError>(),
+            Some(InvalidSkinError::Dimensions(32, 32))
+        ));
+
+        let e = validate_skin(b"not an image").unwrap_err();
+        assert!(matches!(
+            e.downcast_ref::<InvalidSkinError>(),
+            Some(InvalidSkinError::NotPng)
+        ));
+    }
+
+    #[test]
+    fn converts_legacy_skins() {
+        let mut legacy = RgbaImage::new(64, 32);
+        let red = Rgba([255, 0, 0, 255]);
+        // leftmost column of the right leg's front
+        for y in 20..32 {
+            legacy.put_pixel(4, y, red);
+        }
+        legacy.put_pixel(8, 8, red);
+
+        let skin = validate_skin(&png(&legacy)).unwrap();
+        let skin = image::load_from_memory(&skin).unwrap();
+
+        assert_eq!(skin.dimensions(), (64, 64));
+        assert_eq!(skin.get_pixel(8, 8), red);
+        // mirrored onto the rightmost column of the left leg's front
+        for y in 52..64 {
+            assert_eq!(skin.get_pixel(23, y), red);
+            assert_eq!(skin.get_pixel(20, y), Rgba([0, 0, 0, 0]));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_upload_and_saved_skins() {
+        let app = crate::setup_managers_for_test().await;
+        let account_manager = app.account_manager();
+        let skin_manager = account_manager.skin_manager();
+        let mut server = mockito::Server::new_async().await;
+
+        let mut endpoints = AuthEndpoints::default();
+        endpoints.minecraft_services = server.url();
+        account_manager.set_auth_endpoints(endpoints);
+
+        let id_token = jsonwebtoken::encode(
+            &jsonwebtoken::Header::default(),
+            &serde_json::json!({ "email": "steve@example.com" }),
+            &jsonwebtoken::EncodingKey::from_secret(b"id"),
+        )
+        .unwrap();
+
+        account_manager
+            .add_account(FullAccount {
+                username: "Steve".to_string(),
+                uuid: UUID.to_string(),
+                type_: FullAccountType::Microsoft {
+                    access_token: "access".to_string(),
+                    refresh_token: Some("refresh".to_string()),
+                    id_token: Some(id_token),
+                    email: Some("steve@example.com".to_string()),
+                    token_expires: Utc::now() + chrono::Duration::days(1),
+                    skin_id: None,
+                },
+                last_used: Utc::now().into(),
+            })
+            .await
+            .unwrap();
+
+        let profile = serde_json::json!({
+            "id": UUID,
+            "name": "Steve",
+            "skins": [{ "id": "uploaded", "state": "ACTIVE", "url": "http://localhost/skin" }],
+            "capes": [{ "id": "cape", "state": "INACTIVE", "url": "http://localhost/cape", "alias": "Migrator" }],
+        });
+
+        let upload = server
+            .mock("POST", "/minecraft/profile/skins")
+            .match_header("authorization", "Bearer access")
+            .match_header(
+                "content-type",
+                mockito::Matcher::Regex("^multipart/form-data; boundary=".to_string()),
+            )
+            .with_body(profile.to_string())
+            .expect(2)
+            .create_async()
+            .await;
+
+        let legacy = png(&RgbaImage::new(64, 32));
+        skin_manager
+            .upload_skin_data(UUID.to_string(), legacy.clone(), SkinVariant::Slim)
+            .await
+            .unwrap();
+
+        // the uploaded skin is cached, so this doesn't download anything
+        let head = skin_manager.make_head(UUID.to_string()).await.unwrap();
+        assert!(!head.is_empty());
+
+        let id = skin_manager
+            .save_skin_data("legacy".to_string(), legacy, SkinVariant::Classic)
+            .await
+            .unwrap();
+
+        let saved = skin_manager.get_saved_skins().await.unwrap();
+        assert_eq!(saved.len(), 1);
+        assert_eq!(saved[0].name, "legacy");
+        assert_eq!(saved[0].variant, SkinVariant::Classic);
+
+        // stored converted
+        let image = skin_manager.get_saved_skin_image(id).await.unwrap();
+        assert_eq!(
+            image::load_from_memory(&image).unwrap().dimensions(),
+            (64, 64)
+        );
+
+        skin_manager
+            .apply_saved_skin(UUID.to_string(), id)
+            .await
+            .unwrap();
+        upload.assert_async().await;
+
+        skin_manager.delete_saved_skin(id).await.unwrap();
+        assert!(skin_manager.get_saved_skins().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_renders_are_cached() {
+        use carbon_repos::db::skin_render::UniqueWhereParam;
+
+        let app = crate::setup_managers_for_test().await;
+        let skin_manager = app.account_manager().skin_manager();
+
+        app.account_manager()
+            .add_offline_account("Steve".to_string())
+            .await
+            .unwrap();
+        let uuid = crate::managers::account::offline_uuid("Steve");
+        let skin_id = DefaultSkin::from_uuid(uuid.clone()).skin_id();
+
+        // so the default skin isn't downloaded
+        app.prisma_client
+            .skin()
+            .create(skin_id.to_string(), png(&RgbaImage::new(64, 64)), vec![])
+            .exec()
+            .await
+            .unwrap();
+
+        let front = skin_manager
+            .render(uuid.clone(), SkinRenderKind::Front)
+            .await
+            .unwrap();
+
+        let cached = app
+            .prisma_client
+            .skin_render()
+            .find_unique(UniqueWhereParam::SkinIdKindEquals(
+                skin_id.to_string(),
+                "front".to_string(),
+            ))
+            .exec()
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(cached.image, front);
+
+        assert_eq!(
+            skin_manager
+                .render(uuid, SkinRenderKind::Front)
+                .await
+                .unwrap(),
+            front
+        );
+    }
+}
@@ -0,0 +1,425 @@
+//! Software renders of a player's skin.
+//!
+//! Skins are rendered as a flat front or back view of the whole player, or as
+//! an isometric bust, both including the overlay layers. Rendering only needs
+//! the texture, so no GPU is involved.
+
+use std::io::Cursor;
+
+use anyhow::bail;
+use image::{GenericImageView, ImageFormat, Pixel, RgbaImage};
+
+use super::skin::convert_legacy_skin;
+
+/// Size of a skin pixel in flat renders
+const FLAT_SCALE: u32 = 8;
+/// Size of a skin pixel in isometric renders
+const ISOMETRIC_SCALE: f32 = 8.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SkinRenderKind {
+    /// The whole player seen from the front
+    Front,
+    /// The whole player seen from the back
+    Back,
+    /// Head and upper body seen from above the front right
+    Bust,
+}
+
+impl SkinRenderKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Front => "front",
+            Self::Back => "back",
+            Self::Bust => "bust",
+        }
+    }
+}
+
+/// A cuboid part of the player model, with `(u, v)` the origin of its
+/// texture and `size` its width, height and depth in skin pixels.
+#[derive(Clone, Copy)]
+struct Part {
+    u: u32,
+    v: u32,
+    size: (u32, u32, u32),
+}
+
+impl Part {
+    const fn new(u: u32, v: u32, size: (u32, u32, u32)) -> Self {
+        Self { u, v, size }
+    }
+
+    /// Texture rect of the face pointing towards the player's front
+    fn front(self) -> (u32, u32, u32, u32) {
+        let (w, h, d) = self.size;
+        (self.u + d, self.v + d, w, h)
+    }
+
+    fn back(self) -> (u32, u32, u32, u32) {
+        let (w, h, d) = self.size;
+        (self.u + 2 * d + w, self.v + d, w, h)
+    }
+
+    fn top(self) -> (u32, u32, u32, u32) {
+        let (w, _, d) = self.size;
+        (self.u + d, self.v, w, d)
+    }
+
+    /// Texture rect of the face on the player's right side
+    fn right(self) -> (u32, u32, u32, u32) {
+        let (_, h, d) = self.size;
+        (self.u, self.v + d, d, h)
+    }
+}
+
+/// The parts of the model, with their base and overlay layer.
+struct Model {
+    head: (Part, Part),
+    body: (Part, Part),
+    right_arm: (Part, Part),
+    left_arm: (Part, Part),
+    right_leg: (Part, Part),
+    left_leg: (Part, Part),
+    arm_width: u32,
+}
+
+impl Model {
+    fn new(slim: bool) -> Self {
+        let arm_width = if slim { 3 } else { 4 };
+        let arm = (arm_width, 12, 4);
+        let leg = (4, 12, 4);
+
+        Self {
+            head: (Part::new(0, 0, (8, 8, 8)), Part::new(32, 0, (8, 8, 8))),
+            body: (Part::new(16, 16, (8, 12, 4)), Part::new(16, 32, (8, 12, 4))),
+            right_arm: (Part::new(40, 16, arm), Part::new(40, 32, arm)),
+            left_arm: (Part::new(32, 48, arm), Part::new(48, 48, arm)),
+            right_leg: (Part::new(0, 16, leg), Part::new(0, 32, leg)),
+            left_leg: (Part::new(16, 48, leg), Part::new(0, 48, leg)),
+            arm_width,
+        }
+    }
+}
+
+/// Renders `png`, a 64x64 or legacy 64x32 skin, returning a PNG.
+pub fn render_skin(png: &[u8], kind: SkinRenderKind) -> anyhow::Result<Vec<u8>> {
+    let skin = image::load_from_memory_with_format(png, ImageFormat::Png)?.to_rgba8();
+
+    let skin = match skin.dimensions() {
+        (64, 64) => skin,
+        (64, 32) => convert_legacy_skin(&skin),
+        (width, height) => bail!("cannot render unsupported skin image of {width}x{height}"),
+    };
+
+    let model = Model::new(is_slim(&skin));
+
+    let render = match kind {
+        SkinRenderKind::Front => render_flat(&skin, &model, false),
+        SkinRenderKind::Back => render_flat(&skin, &model, true),
+        SkinRenderKind::Bust => render_bust(&skin, &model),
+    };
+
+    let mut output = Vec::<u8>::new();
+    render.write_to(&mut Cursor::new(&mut output), ImageFormat::Png)?;
+    Ok(output)
+}
+
+/// Slim arms leave the last two columns of the right arm's texture unused.
+///
+/// Cached skins don't record their variant, so it's inferred from the
+/// texture instead.
+fn is_slim(skin: &RgbaImage) -> bool {
+    (20..32).all(|y| skin.get_pixel(54, y)[3] == 0 && skin.get_pixel(55, y)[3] == 0)
+}
+
+fn render_flat(skin: &RgbaImage, model: &Model, back: bool) -> RgbaImage {
+    use image::imageops::{overlay, resize, FilterType};
+
+    let mut target = RgbaImage::new(16 * FLAT_SCALE, 32 * FLAT_SCALE);
+    let w = model.arm_width;
+
+    // x of each part as seen from the front, the back view is mirrored
+    let parts = [
+        (model.head, 4, 0, 8),
+        (model.body, 4, 8, 8),
+        (model.right_arm, 4 - w, 8, w),
+        (model.left_arm, 12, 8, w),
+        (model.right_leg, 4, 20, 4),
+        (model.left_leg, 8, 20, 4),
+    ];
+
+    for layer in 0..2 {
+        for ((base, overlay_part), x, y, width) in parts {
+            let part = if layer == 0 { base } else { overlay_part };
+            let (u, v, tw, th) = if back { part.back() } else { part.front() };
+            let x = if back { 16 - x - width } else { x };
+
+            let face = resize(
+                &skin.view(u, v, tw, th).to_image(),
+                tw * FLAT_SCALE,
+                th * FLAT_SCALE,
+                FilterType::Nearest,
+            );
+
+            overlay(
+                &mut target,
+                &face,
+                (x * FLAT_SCALE) as i64,
+                (y * FLAT_SCALE) as i64,
+            );
+        }
+    }
+
+    target
+}
+
+/// Projects model space, x to the player's left, y up and z to the player's
+/// front, seen from above the front right of the player.
+fn project((x, y, z): (f32, f32, f32)) -> (f32, f32) {
+    let sqrt2 = std::f32::consts::SQRT_2;
+    let sqrt6 = 6f32.sqrt();
+
+    (
+        (x + z) / sqrt2 * ISOMETRIC_SCALE,
+        (z - x - 2.0 * y) / sqrt6 * ISOMETRIC_SCALE,
+    )
+}
+
+/// A face of a cuboid: its corner where the texture starts, and the model
+/// space vectors the texture's u and v run along.
+struct Face {
+    origin: (f32, f32, f32),
+    u: (f32, f32, f32),
+    v: (f32, f32, f32),
+    texture: (u32, u32, u32, u32),
+}
+
+impl Face {
+    fn corners(&self) -> [(f32, f32, f32); 4] {
+        let add = |a: (f32, f32, f32), b: (f32, f32, f32)| (a.0 + b.0, a.1 + b.1, a.2 + b.2);
+
+        [
+            self.origin,
+            add(self.origin, self.u),
+            add(self.origin, self.v),
+            add(add(self.origin, self.u), self.v),
+        ]
+    }
+}
+
+/// The faces seen from above the front right of a part at `(x, y, z)` (its
+/// minimum corner), grown by `inflate` on each side.
+fn visible_faces(part: Part, (x, y, z): (f32, f32, f32), inflate: f32) -> [Face; 3] {
+    let (w, h, d) = part.size;
+    let (w, h, d) = (
+        w as f32 + 2.0 * inflate,
+        h as f32 + 2.0 * inflate,
+        d as f32 + 2.0 * inflate,
+    );
+    let (x, y, z) = (x - inflate, y - inflate, z - inflate);
+
+    [
+        Face {
+            origin: (x, y + h, z),
+            u: (w, 0.0, 0.0),
+            v: (0.0, 0.0, d),
+            texture: part.top(),
+        },
+        Face {
+            origin: (x, y + h, z),
+            u: (0.0, 0.0, d),
+            v: (0.0, -h, 0.0),
+            texture: part.right(),
+        },
+        Face {
+            origin: (x, y + h, z + d),
+            u: (w, 0.0, 0.0),
+            v: (0.0, -h, 0.0),
+            texture: part.front(),
+        },
+    ]
+}
+
+fn render_bust(skin: &RgbaImage, model: &Model) -> RgbaImage {
+    let w = model.arm_width as f32;
+
+    // minimum corners, with the bottom of the body at y = 0
+    let head = (0.0, 12.0, -2.0);
+    let body = (0.0, 0.0, 0.0);
+    let right_arm = (-w, 0.0, 0.0);
+    let left_arm = (8.0, 0.0, 0.0);
+
+    // back to front, so nearer faces are drawn over farther ones
+    let faces = [
+        (model.left_arm, left_arm, 0.25),
+        (model.body, body, 0.25),
+        (model.right_arm, right_arm, 0.25),
+        (model.head, head, 0.5),
+    ]
+    .into_iter()
+    .flat_map(|((base, overlay), position, inflate)| {
+        visible_faces(base, position, 0.0)
+            .into_iter()
+            .chain(visible_faces(overlay, position, inflate))
+    })
+    .collect::<Vec<_>>();
+
+    let corners = faces
+        .iter()
+        .flat_map(|face| face.corners())
+        .map(project)
+        .collect::<Vec<_>>();
+    let min_x = corners.iter().map(|p| p.0).fold(f32::MAX, f32::min).floor();
+    let max_x = corners.iter().map(|p| p.0).fold(f32::MIN, f32::max).ceil();
+    let min_y = corners.iter().map(|p| p.1).fold(f32::MAX, f32::min).floor();
+    let max_y = corners.iter().map(|p| p.1).fold(f32::MIN, f32::max).ceil();
+
+    let mut target = RgbaImage::new((max_x - min_x) as u32, (max_y - min_y) as u32);
+
+    for face in faces {
+        draw_face(&mut target, skin, &face, (min_x, min_y));
+    }
+
+    target
+}
+
+/// Draws the parallelogram a face projects to, sampling its texture for
+/// every pixel it covers.
+fn draw_face(target: &mut RgbaImage, skin: &RgbaImage, face: &Face, offset: (f32, f32)) {
+    let origin = project(face.origin);
+    let origin = (origin.0 - offset.0, origin.1 - offset.1);
+    let u = project(face.u);
+    let v = project(face.v);
+
+    let determinant = u.0 * v.1 - u.1 * v.0;
+    if determinant.abs() < f32::EPSILON {
+        return;
+    }
+
+    let corners = face.corners().map(project);
+
+    let min_x = corners.iter().map(|p| p.0).fold(f32::MAX, f32::min) - offset.0;
+    let max_x = corners.iter().map(|p| p.0).fold(f32::MIN, f32::max) - offset.0;
+    let min_y = corners.iter().map(|p| p.1).fold(f32::MAX, f32::min) - offset.1;
+    let max_y = corners.iter().map(|p| p.1).fold(f32::MIN, f32::max) - offset.1;
+
+    let (tu, tv, tw, th) = face.texture;
+
+    for py in (min_y.floor().max(0.0) as u32)..(max_y.ceil() as u32).min(target.height()) {
+        for px in (min_x.floor().max(0.0) as u32)..(max_x.ceil() as u32).min(target.width()) {
+            // pixel center relative to the face origin, in face coordinates
+            let dx = px as f32 + 0.5 - origin.0;
+            let dy = py as f32 + 0.5 - origin.1;
+            let s = (dx * v.1 - dy * v.0) / determinant;
+            let t = (u.0 * dy - u.1 * dx) / determinant;
+
+            if !(0.0..1.0).contains(&s) || !(0.0..1.0).contains(&t) {
+                continue;
+            }
+
+            let texel = *skin.get_pixel(
+                tu + ((s * tw as f32) as u32).min(tw - 1),
+                tv + ((t * th as f32) as u32).min(th - 1),
+            );
+
+            if texel[3] == 0 {
+                continue;
+            }
+
+            target.get_pixel_mut(px, py).blend(&texel);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use image::{GenericImageView, ImageFormat, Rgba, RgbaImage};
+
+    use super::{render_skin, SkinRenderKind};
+
+    fn skin(slim: bool) -> Vec<u8> {
+        let mut skin = RgbaImage::new(64, 64);
+        let red = Rgba([255, 0, 0, 255]);
+        let blue = Rgba([0, 0, 255, 255]);
+
+        // head front and back
+        for y in 8..16 {
+            for x in 8..16 {
+                skin.put_pixel(x, y, red);
+                skin.put_pixel(x + 16, y, blue);
+            }
+        }
+
+        // right arm front and back
+        let arm_width = if slim { 3 } else { 4 };
+        for y in 20..32 {
+            for x in 44..44 + arm_width {
+                skin.put_pixel(x, y, red);
+                skin.put_pixel(x + 4 + arm_width, y, blue);
+            }
+        }
+
+        let mut png = Vec::new();
+        skin.write_to(&mut std::io::Cursor::new(&mut png), ImageFormat::Png)
+            .unwrap();
+        png
+    }
+
+    fn render(png: &[u8], kind: SkinRenderKind) -> image::DynamicImage {
+        image::load_from_memory(&render_skin(png, kind).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn renders_flat_views() {
+        let classic = skin(false);
+
+        let front = render(&classic, SkinRenderKind::Front);
+        assert_eq!(front.dimensions(), (128, 256));
+        // head
+        assert_eq!(front.get_pixel(64, 32), Rgba([255, 0, 0, 255]));
+        // right arm on the viewer's left
+        assert_eq!(front.get_pixel(4, 100), Rgba([255, 0, 0, 255]));
+        assert_eq!(front.get_pixel(124, 100)[3], 0);
+
+        let back = render(&classic, SkinRenderKind::Back);
+        assert_eq!(back.get_pixel(64, 32), Rgba([0, 0, 255, 255]));
+        // right arm on the viewer's right
+        assert_eq!(back.get_pixel(124, 100), Rgba([0, 0, 255, 255]));
+        assert_eq!(back.get_pixel(4, 100)[3], 0);
+
+        // slim arms leave the outermost column empty
+        let slim = render(&skin(true), SkinRenderKind::Front);
+        assert_eq!(slim.get_pixel(4, 100)[3], 0);
+        assert_eq!(slim.get_pixel(12, 100), Rgba([255, 0, 0, 255]));
+    }
+
+    #[test]
+    fn renders_bust() {
+        let bust = render(&skin(false), SkinRenderKind::Bust);
+        let (width, height) = bust.dimensions();
+        assert!(width > 0 && height > 0);
+
+        // the front of the head faces the viewer
+        let red = bust
+            .pixels()
+            .filter(|(_, _, pixel)| *pixel == Rgba([255, 0, 0, 255]))
+            .count();
+        assert!(red > 0);
+
+        // while the back is hidden
+        assert!(bust
+            .pixels()
+            .all(|(_, _, pixel)| pixel != Rgba([0, 0, 255, 255])));
+    }
+
+    #[test]
+    fn rejects_unsupported_sizes() {
+        let mut png = Vec::new();
+        RgbaImage::new(16, 16)
+            .write_to(&mut std::io::Cursor::new(&mut png), ImageFormat::Png)
+            .unwrap();
+
+        assert!(render_skin(&png, SkinRenderKind::Front).is_err());
+    }
+}
@@ -0,0 +1,441 @@
+//! Encryption at rest for the account tokens stored in the database, also
+//! used for the credentials of private mod source repositories.
+//!
+//! Tokens are sealed with AES-256-GCM, bound to the row and column they are
+//! stored in. The key lives in the OS secret service, or in a key file only
+//! readable by the current user when no secret service is available.
+
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+use anyhow::{anyhow, Context};
+use carbon_repos::db;
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN};
+use ring::rand::{SecureRandom, SystemRandom};
+use thiserror::Error;
+use tracing::{error, info, warn};
+
+const KEYRING_SERVICE: &str = "gdlauncher-carbon";
+const KEYRING_USER: &str = "account-tokens";
+const KEY_FILE: &str = "account_tokens.key";
+const KEY_LEN: usize = 32;
+
+/// Marks a sealed value, the version allows changing the scheme later on.
+const SEALED_PREFIX: &str = "sealed:v1:";
+
+pub(super) const ACCESS_TOKEN: &str = "accessToken";
+pub(super) const MS_REFRESH_TOKEN: &str = "msRefreshToken";
+pub(super) const ID_TOKEN: &str = "idToken";
+pub(super) const GDL_ACCOUNT_STATUS: &str = "gdlAccountStatus";
+
+#[derive(Error, Debug)]
+pub enum VaultError {
+    #[error("account token encryption key is unavailable")]
+    KeyUnavailable,
+
+    #[error("value is not sealed")]
+    NotSealed,
+
+    #[error("sealed value is corrupted or was sealed with a different key")]
+    Corrupted,
+
+    #[error("account tokens could not be decrypted, re-login required (uuid {0})")]
+    ReloginRequired(String),
+}
+
+pub struct TokenVault {
+    key: Option<LessSafeKey>,
+    rng: SystemRandom,
+}
+
+impl TokenVault {
+    /// Loads the key, creating one on first use. The vault fails closed if no
+    /// key could be loaded: nothing can be sealed or opened.
+    pub fn load(runtime_path: &Path, use_os_keyring: bool) -> Self {
+        let key = match load_key(runtime_path, use_os_keyring) {
+            Ok(key) => Some(LessSafeKey::new(
+                UnboundKey::new(&AES_256_GCM, &key).expect("key has the length AES-256 expects"),
+            )),
+            Err(e) => {
+                error!({ error = ?e }, "Failed to load account token encryption key");
+                None
+            }
+        };
+
+        Self {
+            key,
+            rng: SystemRandom::new(),
+        }
+    }
+
+    fn key(&self) -> Result<&LessSafeKey, VaultError> {
+        self.key.as_ref().ok_or(VaultError::KeyUnavailable)
+    }
+
+    pub fn is_sealed(value: &str) -> bool {
+        value.starts_with(SEALED_PREFIX)
+    }
+
+    pub fn is_sealed_bytes(value: &[u8]) -> bool {
+        value.starts_with(SEALED_PREFIX.as_bytes())
+    }
+
+    /// `aad` identifies where the value is stored, so a sealed value copied
+    /// to another row or column doesn't open.
+    pub fn seal_bytes(&self, aad: &str, value: &[u8]) -> Result<Vec<u8>, VaultError> {
+        let key = self.key()?;
+
+        let mut nonce = [0u8; NONCE_LEN];
+        self.rng
+            .fill(&mut nonce)
+            .map_err(|_| VaultError::KeyUnavailable)?;
+
+        let mut in_out = value.to_vec();
+        key.seal_in_place_append_tag(
+            Nonce::assume_unique_for_key(nonce),
+            Aad::from(aad.as_bytes()),
+            &mut in_out,
+        )
+        .map_err(|_| VaultError::Corrupted)?;
+
+        let mut sealed = SEALED_PREFIX.as_bytes().to_vec();
+        sealed.extend_from_slice(&nonce);
+        sealed.extend_from_slice(&in_out);
+
+        Ok(sealed)
+    }
+
+    pub fn open_bytes(&self, aad: &str, sealed: &[u8]) -> Result<Vec<u8>, VaultError> {
+        let key = self.key()?;
+
+        let sealed = sealed
+            .strip_prefix(SEALED_PREFIX.as_bytes())
+            .ok_or(VaultError::NotSealed)?;
+
+        if sealed.len() < NONCE_LEN {
+            return Err(VaultError::Corrupted);
+        }
+
+        let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+        let nonce = Nonce::try_assume_unique_for_key(nonce).map_err(|_| VaultError::Corrupted)?;
+
+        let mut in_out = ciphertext.to_vec();
+        let plaintext = key
+            .open_in_place(nonce, Aad::from(aad.as_bytes()), &mut in_out)
+            .map_err(|_| VaultError::Corrupted)?;
+
+        Ok(plaintext.to_vec())
+    }
+
+    pub fn seal(&self, aad: &str, value: &str) -> Result<String, VaultError> {
+        let sealed = self.seal_bytes(aad, value.as_bytes())?;
+
+        Ok(format!(
+            "{SEALED_PREFIX}{}",
+            hex::encode(&sealed[SEALED_PREFIX.len()..])
+        ))
+    }
+
+    pub fn open(&self, aad: &str, sealed: &str) -> Result<String, VaultError> {
+        let encoded = sealed
+            .strip_prefix(SEALED_PREFIX)
+            .ok_or(VaultError::NotSealed)?;
+
+        let mut bytes = SEALED_PREFIX.as_bytes().to_vec();
+        bytes.extend(hex::decode(encoded).map_err(|_| VaultError::Corrupted)?);
+
+        String::from_utf8(self.open_bytes(aad, &bytes)?).map_err(|_| VaultError::Corrupted)
+    }
+
+    pub(super) fn seal_column(
+        &self,
+        uuid: &str,
+        column: &str,
+        value: Option<String>,
+    ) -> Result<Option<String>, VaultError> {
+        value
+            .map(|value| self.seal(&column_aad(uuid, column), &value))
+            .transpose()
+    }
+
+    /// Decrypts the token columns of `account` in place.
+    ///
+    /// Returns `false` if they couldn't be decrypted. The tokens are cleared in
+    /// that case, so the account shows up as requiring a new login.
+    pub(super) fn open_account(&self, account: &mut db::account::Data) -> bool {
+        let uuid = account.uuid.clone();
+
+        let opened = [
+            (ACCESS_TOKEN, &mut account.access_token),
+            (MS_REFRESH_TOKEN, &mut account.ms_refresh_token),
+            (ID_TOKEN, &mut account.id_token),
+        ]
+        .into_iter()
+        .try_for_each(|(column, value)| {
+            if let Some(sealed) = value {
+                *sealed = self.open(&column_aad(&uuid, column), sealed)?;
+            }
+
+            Ok::<_, VaultError>(())
+        });
+
+        match opened {
+            Ok(()) => true,
+            Err(e) => {
+                warn!("Failed to decrypt the tokens of account {uuid}: {e}");
+
+                // an online account stays online, but without anything usable
+                account.access_token = account.access_token.as_ref().map(|_| String::new());
+                account.ms_refresh_token = None;
+                account.id_token = None;
+                account.yggdrasil_client_token = None;
+
+                false
+            }
+        }
+    }
+}
+
+pub(super) fn column_aad(uuid: &str, column: &str) -> String {
+    format!("Account/{uuid}/{column}")
+}
+
+fn load_key(runtime_path: &Path, use_os_keyring: bool) -> anyhow::Result<[u8; KEY_LEN]> {
+    let key_file = runtime_path.join(KEY_FILE);
+
+    // once tokens were sealed with a key file it has to be kept using it
+    if key_file.exists() || !use_os_keyring {
+        return key_from_file(&key_file);
+    }
+
+    match key_from_keyring() {
+        Ok(key) => Ok(key),
+        Err(KeyringError::NoSecretService(e)) => {
+            warn!({ error = ?e }, "OS secret service is unavailable, using a key file instead");
+            key_from_file(&key_file)
+        }
+        // a locked or briefly unreachable secret service must not switch the
+        // tokens over to a key file for good
+        Err(KeyringError::Failed(e)) => Err(e.context("OS secret service failed")),
+    }
+}
+
+#[derive(Error, Debug)]
+enum KeyringError {
+    #[error("no OS secret service is available: {0}")]
+    NoSecretService(anyhow::Error),
+
+    #[error(transparent)]
+    Failed(anyhow::Error),
+}
+
+impl From<keyring::Error> for KeyringError {
+    fn from(value: keyring::Error) -> Self {
+        if is_missing_secret_service(&value) {
+            Self::NoSecretService(value.into())
+        } else {
+            Self::Failed(value.into())
+        }
+    }
+}
+
+/// Whether the error means there is no secret service at all, as opposed to
+/// one that is locked, denied access or failed to respond.
+fn is_missing_secret_service(error: &keyring::Error) -> bool {
+    match error {
+        keyring::Error::PlatformFailure(e) => {
+            let message = e.to_string().to_lowercase();
+
+            [
+                // no secret service registered on the session bus
+                "org.freedesktop.dbus.error.serviceunknown",
+                "org.freedesktop.dbus.error.namehasnoowner",
+                "not provided by any .service files",
+                "secret service is not available",
+                // no session bus to look for one
+                "dbus_session_bus_address",
+            ]
+            .iter()
+            .any(|pattern| message.contains(pattern))
+        }
+        _ => false,
+    }
+}
+
+fn generate_key() -> anyhow::Result<[u8; KEY_LEN]> {
+    let mut key = [0u8; KEY_LEN];
+    SystemRandom::new()
+        .fill(&mut key)
+        .map_err(|_| anyhow!("failed to generate an encryption key"))?;
+
+    Ok(key)
+}
+
+fn decode_key(encoded: &str) -> anyhow::Result<[u8; KEY_LEN]> {
+    let mut key = [0u8; KEY_LEN];
+    hex::decode_to_slice(encoded.trim(), &mut key).context("malformed encryption key")?;
+
+    Ok(key)
+}
+
+fn key_from_keyring() -> Result<[u8; KEY_LEN], KeyringError> {
+    // not tied to the runtime path, so moving the data folder keeps the tokens
+    let entry = keyring::Entry::new(KEYRING_SERVICE, KEYRING_USER)?;
+
+    match entry.get_password() {
+        Ok(encoded) => decode_key(&encoded).map_err(KeyringError::Failed),
+        Err(keyring::Error::NoEntry) => {
+            let key = generate_key().map_err(KeyringError::Failed)?;
+            entry.set_password(&hex::encode(key))?;
+
+            // some secret services accept writes they don't persist
+            if decode_key(&entry.get_password()?).map_err(KeyringError::Failed)? != key {
+                return Err(KeyringError::NoSecretService(anyhow!(
+                    "OS secret service did not persist the encryption key"
+                )));
+            }
+
+            info!("Created account token encryption key in the OS secret service");
+            Ok(key)
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn key_from_file(path: &Path) -> anyhow::Result<[u8; KEY_LEN]> {
+    if path.exists() {
+        restrict_permissions(path)?;
+        return decode_key(&fs::read_to_string(path)?);
+    }
+
+    let key = generate_key()?;
+
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    let mut file = options
+        .open(path)
+        .with_context(|| format!("creating key file {}", path.display()))?;
+    file.write_all(hex::encode(key).as_bytes())?;
+    file.sync_all()?;
+
+    info!("Created account token encryption key file");
+    Ok(key)
+}
+
+/// Makes sure the key file is only accessible by the current user.
+fn restrict_permissions(path: &Path) -> anyhow::Result<()> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+
+        let mode = fs::metadata(path)?.permissions().mode();
+        if mode & 0o077 != 0 {
+            warn!(
+                "Key file {} was accessible by other users, restricting it",
+                path.display()
+            );
+            fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn seal_roundtrip() {
+        let dir = tempdir::TempDir::new("carbon_vault_test").unwrap();
+        let vault = TokenVault::load(dir.path(), false);
+
+        let sealed = vault.seal("Account/a/accessToken", "secret").unwrap();
+        assert!(TokenVault::is_sealed(&sealed));
+        assert!(!sealed.contains("secret"));
+        assert_eq!(
+            vault.open("Account/a/accessToken", &sealed).unwrap(),
+            "secret"
+        );
+
+        // bound to where it's stored
+        assert!(matches!(
+            vault.open("Account/b/accessToken", &sealed),
+            Err(VaultError::Corrupted)
+        ));
+        assert!(matches!(
+            vault.open("Account/a/accessToken", "secret"),
+            Err(VaultError::NotSealed)
+        ));
+
+        let mut tampered = sealed.clone();
+        let flipped = if tampered.ends_with('0') { "1" } else { "0" };
+        tampered.replace_range(tampered.len() - 1.., flipped);
+        assert!(vault.open("Account/a/accessToken", &tampered).is_err());
+
+        let sealed = vault.seal_bytes("gdlAccountStatus", &[1, 2, 3]).unwrap();
+        assert!(TokenVault::is_sealed_bytes(&sealed));
+        assert_eq!(
+            vault.open_bytes("gdlAccountStatus", &sealed).unwrap(),
+            [1, 2, 3]
+        );
+    }
+
+    #[test]
+    fn key_file_is_reused() {
+        let dir = tempdir::TempDir::new("carbon_vault_test").unwrap();
+
+        let sealed = TokenVault::load(dir.path(), false)
+            .seal("aad", "secret")
+            .unwrap();
+
+        let vault = TokenVault::load(dir.path(), false);
+        assert_eq!(vault.open("aad", &sealed).unwrap(), "secret");
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+
+            let key_file = dir.path().join(KEY_FILE);
+            let mode = fs::metadata(key_file).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        // a different key fails closed
+        fs::remove_file(dir.path().join(KEY_FILE)).unwrap();
+        let vault = TokenVault::load(dir.path(), false);
+        assert!(matches!(
+            vault.open("aad", &sealed),
+            Err(VaultError::Corrupted)
+        ));
+    }
+
+    #[test]
+    fn only_missing_secret_service_falls_back() {
+        let missing = keyring::Error::PlatformFailure(
+            "org.freedesktop.DBus.Error.ServiceUnknown: The name org.freedesktop.secrets was not provided by any .service files"
+                .into(),
+        );
+        assert!(is_missing_secret_service(&missing));
+
+        let locked = keyring::Error::NoStorageAccess("collection is locked".into());
+        assert!(!is_missing_secret_service(&locked));
+
+        let timeout = keyring::Error::PlatformFailure(
+            "org.freedesktop.DBus.Error.NoReply: Did not receive a reply".into(),
+        );
+        assert!(!is_missing_secret_service(&timeout));
+        assert!(matches!(
+            KeyringError::from(timeout),
+            KeyringError::Failed(_)
+        ));
+    }
+}
@@ -0,0 +1,459 @@
+//! Client for custom yggdrasil servers, as understood by authlib-injector.
+//!
+//! See <https://github.com/yushijinhun/authlib-injector/wiki> for the protocol.
+
+use std::path::PathBuf;
+
+use anyhow::{bail, Context};
+use carbon_net::{Checksum, Downloadable};
+use reqwest::header::{HeaderMap, CONTENT_TYPE};
+use reqwest::StatusCode;
+use reqwest_middleware::ClientWithMiddleware;
+use serde::Deserialize;
+use serde_json::json;
+use thiserror::Error;
+use tracing::{info, warn};
+
+use crate::managers::App;
+
+const AUTHLIB_INJECTOR_LATEST: &str = "https://authlib-injector.yushi.moe/artifact/latest.json";
+const API_LOCATION_HEADER: &str = "x-authlib-injector-api-location";
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Profile {
+    pub id: String,
+    pub name: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct Session {
+    pub access_token: String,
+    pub client_token: String,
+    pub profile: Profile,
+}
+
+#[derive(Error, Debug)]
+pub enum YggdrasilError {
+    #[error("yggdrasil server rejected the request: {error}: {message}")]
+    Rejected { error: String, message: String },
+
+    #[error("yggdrasil account has no game profile")]
+    NoProfile,
+
+    #[error("yggdrasil account has several game profiles and none is selected")]
+    ProfileNotSelected,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ErrorResponse {
+    error: String,
+    #[serde(default)]
+    error_message: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SessionResponse {
+    access_token: String,
+    client_token: String,
+    #[serde(default)]
+    available_profiles: Vec<Profile>,
+    selected_profile: Option<Profile>,
+}
+
+impl TryFrom<SessionResponse> for Session {
+    type Error = YggdrasilError;
+
+    fn try_from(value: SessionResponse) -> Result<Self, Self::Error> {
+        let profile = match value.selected_profile {
+            Some(profile) => profile,
+            None if value.available_profiles.is_empty() => return Err(YggdrasilError::NoProfile),
+            None => return Err(YggdrasilError::ProfileNotSelected),
+        };
+
+        Ok(Self {
+            access_token: value.access_token,
+            client_token: value.client_token,
+            profile,
+        })
+    }
+}
+
+/// Follows the api location indication of `url`, returning the api root
+/// without a trailing slash.
+pub async fn resolve_api_root(client: &ClientWithMiddleware, url: &str) -> anyhow::Result<String> {
+    let url = url::Url::parse(url).with_context(|| format!("invalid yggdrasil url `{url}`"))?;
+
+    if !matches!(url.scheme(), "http" | "https") {
+        bail!("yggdrasil url `{url}` is not an http url");
+    }
+
+    let response = client
+        .get(url.clone())
+        .header("avoid-caching", "")
+        .send()
+        .await?;
+
+    let api_root = match response
+        .headers()
+        .get(API_LOCATION_HEADER)
+        .and_then(|location| location.to_str().ok())
+    {
+        Some(location) => url
+            .join(location)
+            .with_context(|| format!("invalid yggdrasil api location `{location}`"))?,
+        None => url,
+    };
+
+    Ok(api_root.as_str().trim_end_matches('/').to_string())
+}
+
+async fn post(
+    client: &ClientWithMiddleware,
+    api_root: &str,
+    endpoint: &str,
+    body: serde_json::Value,
+) -> anyhow::Result<reqwest::Response> {
+    let mut headers = HeaderMap::new();
+    headers.insert(CONTENT_TYPE, "application/json".parse()?);
+
+    Ok(client
+        .post(format!("{api_root}/authserver/{endpoint}"))
+        .headers(headers)
+        .header("avoid-caching", "")
+        .body(serde_json::to_string(&body)?)
+        .send()
+        .await?)
+}
+
+async fn error_from_response(response: reqwest::Response) -> anyhow::Error {
+    let status = response.status();
+
+    match response.json::<ErrorResponse>().await {
+        Ok(e) => YggdrasilError::Rejected {
+            error: e.error,
+            message: e.error_message,
+        }
+        .into(),
+        Err(_) => anyhow::anyhow!("yggdrasil server responded with {status}"),
+    }
+}
+
+pub async fn authenticate(
+    client: &ClientWithMiddleware,
+    api_root: &str,
+    username: &str,
+    password: &str,
+    client_token: &str,
+) -> anyhow::Result<Session> {
+    let response = post(
+        client,
+        api_root,
+        "authenticate",
+        json!({
+            "agent": { "name": "Minecraft", "version": 1 },
+            "username": username,
+            "password": password,
+            "clientToken": client_token,
+            "requestUser": false,
+        }),
+    )
+    .await?;
+
+    if !response.status().is_success() {
+        return Err(error_from_response(response).await);
+    }
+
+    Ok(response.json::<SessionResponse>().await?.try_into()?)
+}
+
+pub async fn refresh(
+    client: &ClientWithMiddleware,
+    api_root: &str,
+    access_token: &str,
+    client_token: &str,
+) -> anyhow::Result<Session> {
+    let response = post(
+        client,
+        api_root,
+        "refresh",
+        json!({
+            "accessToken": access_token,
+            "clientToken": client_token,
+            "requestUser": false,
+        }),
+    )
+    .await?;
+
+    if !response.status().is_success() {
+        return Err(error_from_response(response).await);
+    }
+
+    Ok(response.json::<SessionResponse>().await?.try_into()?)
+}
+
+/// Whether the server still accepts `access_token`.
+pub async fn validate(
+    client: &ClientWithMiddleware,
+    api_root: &str,
+    access_token: &str,
+    client_token: &str,
+) -> anyhow::Result<bool> {
+    let response = post(
+        client,
+        api_root,
+        "validate",
+        json!({
+            "accessToken": access_token,
+            "clientToken": client_token,
+        }),
+    )
+    .await?;
+
+    match response.status() {
+        StatusCode::NO_CONTENT => Ok(true),
+        StatusCode::FORBIDDEN => Ok(false),
+        _ => Err(error_from_response(response).await),
+    }
+}
+
+pub fn authlib_injector_path(runtime_path: &carbon_rt_path::RuntimePath) -> PathBuf {
+    runtime_path
+        .get_libraries()
+        .get_library_path("moe/yushi/authlibinjector/authlib-injector.jar".to_string())
+}
+
+/// Downloads the latest authlib-injector, keeping the current one if the
+/// update check fails.
+pub async fn download_authlib_injector(app: &App) -> anyhow::Result<PathBuf> {
+    #[derive(Deserialize)]
+    struct Checksums {
+        sha256: String,
+    }
+
+    #[derive(Deserialize)]
+    struct Artifact {
+        version: String,
+        download_url: String,
+        checksums: Checksums,
+    }
+
+    let path = authlib_injector_path(&app.settings_manager().runtime_path);
+
+    let artifact = async {
+        Ok::<_, anyhow::Error>(
+            app.reqwest_client
+                .get(AUTHLIB_INJECTOR_LATEST)
+                .header("avoid-caching", "")
+                .send()
+                .await?
+                .error_for_status()?
+                .json::<Artifact>()
+                .await?,
+        )
+    }
+    .await;
+
+    let artifact = match artifact {
+        Ok(artifact) => artifact,
+        Err(e) if path.exists() => {
+            warn!({ error = ?e }, "Failed to check for authlib-injector updates");
+            return Ok(path);
+        }
+        Err(e) => return Err(e.context("fetching authlib-injector metadata")),
+    };
+
+    let downloadable = Downloadable::new(artifact.download_url, &path)
+        .with_checksum(Some(Checksum::Sha256(artifact.checksums.sha256)));
+
+    carbon_net::download_multiple(
+        &[downloadable],
+        app.settings_manager().download_options().build(),
+    )
+    .await
+    .context("downloading authlib-injector")?;
+
+    info!("Using authlib-injector {}", artifact.version);
+
+    Ok(path)
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::{Arc, Mutex};
+
+    use axum::{
+        extract::State,
+        http::StatusCode,
+        response::{IntoResponse, Response},
+        routing::{get, post},
+        Json, Router,
+    };
+    use serde_json::{json, Value};
+    use tokio::net::TcpListener;
+
+    use super::YggdrasilError;
+    use crate::domain::account::{AccountStatus, AccountType};
+    use crate::managers::App;
+
+    const PROFILE_ID: &str = "6e5b8a0c4f2d4e0b9d1a3c5e7f901234";
+
+    #[derive(Default)]
+    struct ServerState {
+        access_token: String,
+        issued: u32,
+        // access token is still known, but has to be refreshed
+        expired: bool,
+    }
+
+    type SharedState = Arc<Mutex<ServerState>>;
+
+    fn forbidden() -> Response {
+        (
+            StatusCode::FORBIDDEN,
+            Json(json!({
+                "error": "ForbiddenOperationException",
+                "errorMessage": "Invalid credentials.",
+            })),
+        )
+            .into_response()
+    }
+
+    fn session(state: &mut ServerState, client_token: &Value) -> Response {
+        state.issued += 1;
+        state.access_token = format!("token-{}", state.issued);
+        state.expired = false;
+
+        Json(json!({
+            "accessToken": state.access_token,
+            "clientToken": client_token,
+            "availableProfiles": [{ "id": PROFILE_ID, "name": "Steve" }],
+            "selectedProfile": { "id": PROFILE_ID, "name": "Steve" },
+        }))
+        .into_response()
+    }
+
+    async fn authenticate(State(state): State<SharedState>, Json(body): Json<Value>) -> Response {
+        if body["username"] != "steve@example.com" || body["password"] != "hunter2" {
+            return forbidden();
+        }
+
+        session(&mut state.lock().unwrap(), &body["clientToken"])
+    }
+
+    async fn refresh(State(state): State<SharedState>, Json(body): Json<Value>) -> Response {
+        let mut state = state.lock().unwrap();
+
+        if body["accessToken"] != state.access_token.as_str() {
+            return forbidden();
+        }
+
+        session(&mut state, &body["clientToken"])
+    }
+
+    async fn validate(State(state): State<SharedState>, Json(body): Json<Value>) -> Response {
+        let state = state.lock().unwrap();
+
+        if body["accessToken"] != state.access_token.as_str() || state.expired {
+            return forbidden();
+        }
+
+        StatusCode::NO_CONTENT.into_response()
+    }
+
+    async fn stand_in_server(state: SharedState) -> String {
+        let tcp_listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = tcp_listener.local_addr().unwrap().port();
+
+        let server = Router::new()
+            .route(
+                "/",
+                get(|| async { ([(super::API_LOCATION_HEADER, "/api/yggdrasil/")], "") }),
+            )
+            .route("/api/yggdrasil/authserver/authenticate", post(authenticate))
+            .route("/api/yggdrasil/authserver/refresh", post(refresh))
+            .route("/api/yggdrasil/authserver/validate", post(validate))
+            .with_state(state);
+
+        tokio::spawn(async {
+            axum::serve(tcp_listener, server.into_make_service())
+                .await
+                .unwrap();
+        });
+
+        format!("http://127.0.0.1:{port}")
+    }
+
+    async fn account_status(app: &App, server: &str) -> AccountStatus {
+        let accounts = app.account_manager().get_account_list().await.unwrap();
+        assert_eq!(accounts.len(), 1);
+
+        let account = accounts.into_iter().next().unwrap();
+        assert!(matches!(
+            account.account.type_,
+            AccountType::Yggdrasil { server: api_root } if api_root == format!("{server}/api/yggdrasil")
+        ));
+
+        account.status
+    }
+
+    #[tokio::test]
+    async fn test_yggdrasil_account() {
+        let app = crate::setup_managers_for_test().await;
+        let account_manager = app.account_manager();
+
+        let state = SharedState::default();
+        let server = stand_in_server(state.clone()).await;
+
+        let e = account_manager
+            .add_yggdrasil_account(
+                server.clone(),
+                "steve@example.com".to_string(),
+                "wrong".to_string(),
+            )
+            .await
+            .unwrap_err();
+        assert!(e.is::<YggdrasilError>());
+
+        account_manager
+            .add_yggdrasil_account(
+                server.clone(),
+                "steve@example.com".to_string(),
+                "hunter2".to_string(),
+            )
+            .await
+            .unwrap();
+
+        let account = account_manager.get_active_account().await.unwrap().unwrap();
+        assert_eq!(account.uuid, PROFILE_ID);
+        assert_eq!(account.username, "Steve");
+
+        assert!(matches!(
+            account_status(&app, &server).await,
+            AccountStatus::Ok { access_token: Some(token) } if token == "token-1"
+        ));
+
+        // an expired session is refreshed transparently
+        state.lock().unwrap().expired = true;
+        account_manager
+            .refresh_account_status(PROFILE_ID.to_string(), false)
+            .await
+            .unwrap();
+        assert!(matches!(
+            account_status(&app, &server).await,
+            AccountStatus::Ok { access_token: Some(token) } if token == "token-2"
+        ));
+
+        // a revoked session needs a new login
+        state.lock().unwrap().access_token = "revoked".to_string();
+        account_manager
+            .refresh_account_status(PROFILE_ID.to_string(), false)
+            .await
+            .unwrap();
+        assert!(matches!(
+            account_status(&app, &server).await,
+            AccountStatus::Invalid
+        ));
+    }
+}
@@ -0,0 +1,791 @@
+//! Offline checks of the declared dependencies of an instance's mods.
+//!
+//! Only the metadata cached from the mod files is used: dependencies are
+//! resolved against the enabled mods, the ids they provide and the game and
+//! mod loaders of the instance.
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use carbon_repos::db::{mod_dependency as depdb, mod_file_cache as fcdb, mod_metadata as metadb};
+use tracing::error;
+
+use super::{InstanceId, InstanceManager, InvalidInstanceIdError};
+use crate::domain::instance::info::{GameVersion, ModLoaderType, StandardVersion};
+use crate::domain::instance::{
+    self as domain, DependencyProvider, ModDependency, ModDependencyIssue, ModDependencyKind,
+    ProvidedModId,
+};
+use crate::managers::ManagerRef;
+
+impl ManagerRef<'_, InstanceManager> {
+    /// Check the enabled mods of an instance for missing dependencies, versions
+    /// out of range and duplicate mod ids, using only cached metadata.
+    pub async fn analyze_mod_dependencies(
+        self,
+        instance_id: InstanceId,
+    ) -> anyhow::Result<domain::ModDependencyAnalysis> {
+        let version = {
+            let instances = self.instances.read().await;
+            let instance = instances
+                .get(&instance_id)
+                .ok_or(InvalidInstanceIdError(instance_id))?;
+
+            match &instance.data()?.config.game_configuration.version {
+                Some(GameVersion::Standard(version)) => Some(version.clone()),
+                _ => None,
+            }
+        };
+
+        let mods = self
+            .app
+            .prisma_client
+            .mod_file_cache()
+            .find_many(vec![
+                fcdb::instance_id::equals(*instance_id),
+                fcdb::enabled::equals(true),
+            ])
+            .with(
+                fcdb::metadata::fetch()
+                    .with(metadb::dependencies::fetch(vec![]))
+                    .with(metadb::provided_ids::fetch(vec![])),
+            )
+            .exec()
+            .await?
+            .into_iter()
+            .filter_map(|m| {
+                let metadata = m.metadata?;
+
+                let dependencies = metadata
+                    .dependencies
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter_map(|dependency| {
+                        dependency_from_db(dependency)
+                            .map_err(
+                                |e| error!({ error = ?e }, "Invalid mod dependency in database"),
+                            )
+                            .ok()
+                    })
+                    .collect();
+
+                let provides = metadata
+                    .provided_ids
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|provided| ProvidedModId {
+                        modid: provided.modid,
+                        version: provided.version,
+                    })
+                    .collect();
+
+                Some(AnalyzedMod {
+                    id: m.id,
+                    filename: m.filename,
+                    modid: metadata.modid,
+                    version: metadata.version,
+                    modloaders: metadata
+                        .modloaders
+                        .split(',')
+                        // ignore unknown modloaders
+                        .flat_map(|loader| ModLoaderType::try_from(loader).ok())
+                        .collect(),
+                    dependencies,
+                    provides,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        Ok(analyze(version.as_ref(), mods))
+    }
+}
+
+fn dependency_from_db(dependency: depdb::Data) -> anyhow::Result<ModDependency> {
+    Ok(ModDependency {
+        modloader: ModLoaderType::try_from(&dependency.modloader[..])?,
+        modid: dependency.modid,
+        kind: ModDependencyKind::try_from(dependency.kind)?,
+        versions: serde_json::from_str(&dependency.versions)?,
+    })
+}
+
+/// A mod file as seen by the analysis
+#[derive(Debug, Clone)]
+struct AnalyzedMod {
+    id: String,
+    filename: String,
+    modid: Option<String>,
+    version: Option<String>,
+    /// Mod loaders the file has metadata for
+    modloaders: Vec<ModLoaderType>,
+    dependencies: Vec<ModDependency>,
+    provides: Vec<ProvidedModId>,
+}
+
+/// Something providing a mod id, `owner` being the index of the mod or `None`
+/// for the instance itself
+struct Provided<'a> {
+    owner: Option<usize>,
+    version: Option<&'a str>,
+}
+
+fn analyze(
+    version: Option<&StandardVersion>,
+    mut mods: Vec<AnalyzedMod>,
+) -> domain::ModDependencyAnalysis {
+    mods.sort_by(|a, b| a.filename.cmp(&b.filename));
+
+    let instance_ids = version.map(instance_provided_ids).unwrap_or_default();
+    let instance_loaders = version
+        .map(|version| {
+            version
+                .modloaders
+                .iter()
+                .map(|modloader| modloader.type_)
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+
+    let mut provided = HashMap::<&str, Vec<Provided>>::new();
+
+    for (modid, version) in &instance_ids {
+        provided.entry(modid).or_default().push(Provided {
+            owner: None,
+            version: version.as_deref(),
+        });
+    }
+
+    for (i, m) in mods.iter().enumerate() {
+        let own_id = m.modid.iter().map(|modid| (modid, m.version.as_deref()));
+        let provided_ids = m
+            .provides
+            .iter()
+            .map(|provided| (&provided.modid, provided.version.as_deref()));
+
+        for (modid, version) in own_id.chain(provided_ids) {
+            let providers = provided.entry(modid).or_default();
+
+            // nested jars may repeat ids the file already provides
+            if !providers.iter().any(|p| p.owner == Some(i)) {
+                providers.push(Provided {
+                    owner: Some(i),
+                    version,
+                });
+            }
+        }
+    }
+
+    let provider = |p: &Provided| match p.owner {
+        Some(owner) => DependencyProvider::Mod {
+            mod_id: mods[owner].id.clone(),
+            filename: mods[owner].filename.clone(),
+            version: p.version.map(ToString::to_string),
+        },
+        None => DependencyProvider::Instance {
+            version: p.version.map(ToString::to_string),
+        },
+    };
+
+    let mut issues = Vec::new();
+
+    for (i, m) in mods.iter().enumerate() {
+        // only the metadata of the loader that will load the file counts
+        let Some(modloader) = instance_loaders
+            .iter()
+            .flat_map(|loader| loaded_metadata(*loader))
+            .find(|loader| m.modloaders.contains(loader))
+        else {
+            continue;
+        };
+
+        for dependency in m.dependencies.iter().filter(|d| d.modloader == *modloader) {
+            if m.modid.as_ref() == Some(&dependency.modid) {
+                continue;
+            }
+
+            let providers = provided
+                .get(&dependency.modid[..])
+                .map(|providers| {
+                    providers
+                        .iter()
+                        .filter(|p| p.owner != Some(i))
+                        .collect::<Vec<_>>()
+                })
+                .unwrap_or_default();
+
+            // providers that are in range, or might be
+            let in_range = providers
+                .iter()
+                .filter(|p| match p.version {
+                    Some(version) => satisfies(version, &dependency.versions) != Some(false),
+                    None => true,
+                })
+                .count();
+
+            let found = || providers.iter().map(|p| provider(p)).collect::<Vec<_>>();
+
+            match dependency.kind {
+                ModDependencyKind::Required if providers.is_empty() => {
+                    issues.push(ModDependencyIssue::Missing {
+                        mod_id: m.id.clone(),
+                        filename: m.filename.clone(),
+                        dependency: dependency.modid.clone(),
+                        versions: dependency.versions.clone(),
+                    });
+                }
+                ModDependencyKind::Required | ModDependencyKind::Optional
+                    if !providers.is_empty() && in_range == 0 =>
+                {
+                    issues.push(ModDependencyIssue::UnsatisfiedVersion {
+                        mod_id: m.id.clone(),
+                        filename: m.filename.clone(),
+                        dependency: dependency.modid.clone(),
+                        versions: dependency.versions.clone(),
+                        found: found(),
+                    });
+                }
+                ModDependencyKind::Breaks | ModDependencyKind::Conflicts => {
+                    // only report incompatibilities that are certain
+                    let incompatible = providers
+                        .iter()
+                        .filter(|p| match p.version {
+                            Some(version) => satisfies(version, &dependency.versions) == Some(true),
+                            None => dependency.versions.is_empty(),
+                        })
+                        .map(|p| provider(p))
+                        .collect::<Vec<_>>();
+
+                    if !incompatible.is_empty() {
+                        issues.push(ModDependencyIssue::Incompatible {
+                            mod_id: m.id.clone(),
+                            filename: m.filename.clone(),
+                            dependency: dependency.modid.clone(),
+                            versions: dependency.versions.clone(),
+                            found: incompatible,
+                            breaking: dependency.kind == ModDependencyKind::Breaks,
+                        });
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    let mut duplicates = HashMap::<&str, Vec<usize>>::new();
+    for (i, m) in mods.iter().enumerate() {
+        if let Some(modid) = &m.modid {
+            duplicates.entry(modid).or_default().push(i);
+        }
+    }
+
+    let mut duplicates = duplicates
+        .into_iter()
+        .filter(|(_, files)| files.len() > 1)
+        .collect::<Vec<_>>();
+    duplicates.sort();
+
+    issues.extend(duplicates.into_iter().map(|(modid, files)| {
+        ModDependencyIssue::DuplicateModId {
+            modid: modid.to_string(),
+            mods: files
+                .into_iter()
+                .map(|i| DependencyProvider::Mod {
+                    mod_id: mods[i].id.clone(),
+                    filename: mods[i].filename.clone(),
+                    version: mods[i].version.clone(),
+                })
+                .collect(),
+        }
+    }));
+
+    domain::ModDependencyAnalysis { issues }
+}
+
+/// Metadata a mod loader reads, in order of preference
+fn loaded_metadata(modloader: ModLoaderType) -> &'static [ModLoaderType] {
+    match modloader {
+        ModLoaderType::Forge => &[ModLoaderType::Forge],
+        // neoforge mods.toml are read as forge metadata
+        ModLoaderType::Neoforge => &[ModLoaderType::Neoforge, ModLoaderType::Forge],
+        ModLoaderType::Fabric => &[ModLoaderType::Fabric],
+        ModLoaderType::Quilt => &[ModLoaderType::Quilt, ModLoaderType::Fabric],
+    }
+}
+
+/// Mod ids provided by the game and the mod loaders of an instance
+fn instance_provided_ids(version: &StandardVersion) -> Vec<(String, Option<String>)> {
+    let mut ids = vec![
+        (String::from("minecraft"), Some(version.release.clone())),
+        // checked against the java in use when launching
+        (String::from("java"), None),
+    ];
+
+    for modloader in &version.modloaders {
+        // forge versions may be prefixed by the game version
+        let loader_version = modloader
+            .version
+            .strip_prefix(&format!("{}-", version.release))
+            .unwrap_or(&modloader.version)
+            .to_string();
+
+        match modloader.type_ {
+            ModLoaderType::Forge => ids.push((String::from("forge"), Some(loader_version))),
+            ModLoaderType::Neoforge => {
+                ids.push((String::from("neoforge"), Some(loader_version)));
+                // the first neoforge releases still load forge mods
+                ids.push((String::from("forge"), None));
+            }
+            ModLoaderType::Fabric => ids.push((String::from("fabricloader"), Some(loader_version))),
+            ModLoaderType::Quilt => {
+                ids.push((String::from("quilt_loader"), Some(loader_version)));
+                ids.push((String::from("fabricloader"), None));
+            }
+        }
+    }
+
+    ids
+}
+
+/// A version compared like fabric's semantic versions, with extra components
+/// allowed and missing ones read as 0. Build metadata is ignored.
+#[derive(Debug, Clone)]
+struct Version {
+    components: Vec<String>,
+    prerelease: Option<String>,
+}
+
+impl Version {
+    fn parse(version: &str) -> Option<Self> {
+        let version = version.trim();
+        let version = version
+            .split_once('+')
+            .map_or(version, |(version, _)| version);
+
+        let (core, prerelease) = match version.split_once('-') {
+            Some((core, prerelease)) => (core, Some(prerelease.to_string())),
+            None => (version, None),
+        };
+
+        let components = core.split('.').map(ToString::to_string).collect::<Vec<_>>();
+
+        let numeric_start = components[0].chars().all(|c| c.is_ascii_digit());
+        if !numeric_start || components.iter().any(|c| c.is_empty()) {
+            return None;
+        }
+
+        Some(Self {
+            components,
+            prerelease,
+        })
+    }
+
+    /// The lowest version above every version sharing the first `index + 1`
+    /// components, `None` if that component isn't numeric
+    fn bump(&self, index: usize) -> Option<Self> {
+        let mut components = self.components.clone();
+        components.resize(index + 1, String::from("0"));
+        components[index] = (components[index].parse::<u64>().ok()? + 1).to_string();
+
+        Some(Self {
+            components,
+            prerelease: None,
+        })
+    }
+}
+
+fn compare_identifiers(a: &str, b: &str) -> Ordering {
+    match (a.parse::<u64>(), b.parse::<u64>()) {
+        (Ok(a), Ok(b)) => a.cmp(&b),
+        // numeric identifiers are lower than alphanumeric ones
+        (Ok(_), Err(_)) => Ordering::Less,
+        (Err(_), Ok(_)) => Ordering::Greater,
+        (Err(_), Err(_)) => a.cmp(b),
+    }
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let len = self.components.len().max(other.components.len());
+
+        for i in 0..len {
+            let a = self.components.get(i).map_or("0", String::as_str);
+            let b = other.components.get(i).map_or("0", String::as_str);
+
+            match compare_identifiers(a, b) {
+                Ordering::Equal => {}
+                ordering => return ordering,
+            }
+        }
+
+        match (&self.prerelease, &other.prerelease) {
+            (None, None) => Ordering::Equal,
+            (None, Some(_)) => Ordering::Greater,
+            (Some(_), None) => Ordering::Less,
+            (Some(a), Some(b)) => {
+                let mut a = a.split('.');
+                let mut b = b.split('.');
+
+                loop {
+                    match (a.next(), b.next()) {
+                        (None, None) => return Ordering::Equal,
+                        (None, Some(_)) => return Ordering::Less,
+                        (Some(_), None) => return Ordering::Greater,
+                        (Some(a), Some(b)) => match compare_identifiers(a, b) {
+                            Ordering::Equal => {}
+                            ordering => return ordering,
+                        },
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Version {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Version {}
+
+/// If `version` is in any of `ranges`, `None` if that can't be told
+fn satisfies(version: &str, ranges: &[String]) -> Option<bool> {
+    if ranges.is_empty() {
+        return Some(true);
+    }
+
+    let Some(parsed) = Version::parse(version) else {
+        // versions that aren't semantic can only be matched exactly
+        let exact = ranges
+            .iter()
+            .any(|range| range.trim().trim_start_matches('=') == version);
+
+        return exact.then_some(true);
+    };
+
+    let mut result = Some(false);
+
+    for range in ranges {
+        match satisfies_range(&parsed, range) {
+            Some(true) => return Some(true),
+            Some(false) => {}
+            None => result = None,
+        }
+    }
+
+    result
+}
+
+/// If `version` satisfies every space separated predicate of `range`
+fn satisfies_range(version: &Version, range: &str) -> Option<bool> {
+    let mut result = Some(true);
+
+    for predicate in range.split_whitespace() {
+        match satisfies_predicate(version, predicate) {
+            Some(true) => {}
+            Some(false) => return Some(false),
+            None => result = None,
+        }
+    }
+
+    result
+}
+
+fn satisfies_predicate(version: &Version, predicate: &str) -> Option<bool> {
+    let split = predicate
+        .find(|c: char| !matches!(c, '<' | '>' | '=' | '^' | '~'))
+        .unwrap_or(predicate.len());
+    let (op, bound) = predicate.split_at(split);
+
+    // `1.20.x` covers every 1.20 version
+    let wildcard = bound
+        .split('.')
+        .position(|component| matches!(component, "x" | "X" | "*"));
+
+    if let Some(position) = wildcard {
+        if position == 0 {
+            return Some(true);
+        }
+
+        let prefix = bound
+            .split('.')
+            .take(position)
+            .collect::<Vec<_>>()
+            .join(".");
+
+        return match op {
+            "" | "=" => {
+                let lower = Version::parse(&prefix)?;
+                let upper = lower.bump(position - 1)?;
+                Some(*version >= lower && *version < upper)
+            }
+            _ => satisfies_predicate(version, &format!("{op}{prefix}")),
+        };
+    }
+
+    let bound = Version::parse(bound)?;
+
+    Some(match op {
+        ">=" => *version >= bound,
+        ">" => *version > bound,
+        "<=" => *version <= bound,
+        "<" => *version < bound,
+        "" | "=" => *version == bound,
+        "^" => *version >= bound && *version < bound.bump(0)?,
+        "~" => *version >= bound && *version < bound.bump(1)?,
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashSet;
+
+    use crate::domain::instance::info::{ModLoader, ModLoaderType, StandardVersion};
+    use crate::domain::instance::{
+        DependencyProvider, ModDependency, ModDependencyIssue, ModDependencyKind, ProvidedModId,
+    };
+
+    use super::{analyze, satisfies, AnalyzedMod};
+
+    fn ranges(ranges: &[&str]) -> Vec<String> {
+        ranges.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn version_ranges() {
+        assert_eq!(satisfies("0.15.3", &ranges(&[">=0.14.0"])), Some(true));
+        assert_eq!(satisfies("0.13.3", &ranges(&[">=0.14.0"])), Some(false));
+        assert_eq!(
+            satisfies("1.20.1", &ranges(&["1.20", "1.20.1"])),
+            Some(true)
+        );
+        assert_eq!(satisfies("1.20", &ranges(&["1.20.0"])), Some(true));
+        assert_eq!(satisfies("1.20.4", &ranges(&["1.20.x"])), Some(true));
+        assert_eq!(satisfies("1.21", &ranges(&["1.20.x"])), Some(false));
+        assert_eq!(satisfies("1.20.4", &ranges(&[">=1.20 <1.21"])), Some(true));
+        assert_eq!(satisfies("1.21", &ranges(&[">=1.20 <1.21"])), Some(false));
+        assert_eq!(satisfies("1.9.2", &ranges(&["^1.2.0"])), Some(true));
+        assert_eq!(satisfies("2.0.0", &ranges(&["^1.2.0"])), Some(false));
+        assert_eq!(satisfies("1.2.9", &ranges(&["~1.2.3"])), Some(true));
+        assert_eq!(satisfies("1.3.0", &ranges(&["~1.2.3"])), Some(false));
+        assert_eq!(
+            satisfies("0.92.2+1.20.1", &ranges(&[">=0.92.0"])),
+            Some(true)
+        );
+        assert_eq!(
+            satisfies("1.0.0-beta.2", &ranges(&[">=1.0.0"])),
+            Some(false)
+        );
+        assert_eq!(
+            satisfies("1.0.0-beta.11", &ranges(&[">1.0.0-beta.2"])),
+            Some(true)
+        );
+        assert_eq!(satisfies("47.2.0", &ranges(&[">=47"])), Some(true));
+        assert_eq!(satisfies("anything", &ranges(&[])), Some(true));
+        assert_eq!(satisfies("mc1.20-1.0", &ranges(&[">=1.0"])), None);
+        assert_eq!(
+            satisfies("mc1.20-1.0", &ranges(&["=mc1.20-1.0"])),
+            Some(true)
+        );
+        assert_eq!(satisfies("1.0", &ranges(&["latest"])), None);
+    }
+
+    fn fabric_mod(
+        id: &str,
+        modid: &str,
+        version: &str,
+        dependencies: &[(&str, ModDependencyKind, &[&str])],
+    ) -> AnalyzedMod {
+        AnalyzedMod {
+            id: id.to_string(),
+            filename: format!("{id}.jar"),
+            modid: Some(modid.to_string()),
+            version: Some(version.to_string()),
+            modloaders: vec![ModLoaderType::Fabric],
+            dependencies: dependencies
+                .iter()
+                .map(|(modid, kind, versions)| ModDependency {
+                    modloader: ModLoaderType::Fabric,
+                    modid: modid.to_string(),
+                    kind: *kind,
+                    versions: ranges(versions),
+                })
+                .collect(),
+            provides: Vec::new(),
+        }
+    }
+
+    fn fabric_instance() -> StandardVersion {
+        StandardVersion {
+            release: String::from("1.20.1"),
+            modloaders: HashSet::from([ModLoader {
+                type_: ModLoaderType::Fabric,
+                version: String::from("0.15.3"),
+            }]),
+        }
+    }
+
+    fn provider(id: &str, version: &str) -> DependencyProvider {
+        DependencyProvider::Mod {
+            mod_id: id.to_string(),
+            filename: format!("{id}.jar"),
+            version: Some(version.to_string()),
+        }
+    }
+
+    #[test]
+    fn reports_dependency_issues() {
+        use ModDependencyKind::*;
+
+        let mut api = fabric_mod("api", "fabric-api", "0.92.2+1.20.1", &[]);
+        api.provides.push(ProvidedModId {
+            modid: String::from("fabric"),
+            version: Some(String::from("0.92.2+1.20.1")),
+        });
+
+        let mods = vec![
+            fabric_mod(
+                "a",
+                "mod_a",
+                "1.0.0",
+                &[
+                    ("fabricloader", Required, &[">=0.14.0"]),
+                    ("minecraft", Required, &["1.20.x"]),
+                    ("fabric", Required, &[]),
+                    ("cloth-config", Required, &[]),
+                    ("sodium", Breaks, &["<0.5.0"]),
+                ],
+            ),
+            fabric_mod(
+                "b",
+                "mod_b",
+                "2.0.0",
+                &[
+                    ("minecraft", Required, &["1.19.4"]),
+                    ("mod_a", Optional, &[">=1.1.0"]),
+                    ("mod_c", Conflicts, &[]),
+                ],
+            ),
+            fabric_mod("c", "mod_c", "1.0.0", &[]),
+            fabric_mod("c2", "mod_c", "1.1.0", &[]),
+            fabric_mod("sodium", "sodium", "0.4.10", &[]),
+            api,
+        ];
+
+        let analysis = analyze(Some(&fabric_instance()), mods);
+
+        assert_eq!(
+            analysis.issues,
+            vec![
+                ModDependencyIssue::Missing {
+                    mod_id: String::from("a"),
+                    filename: String::from("a.jar"),
+                    dependency: String::from("cloth-config"),
+                    versions: vec![],
+                },
+                ModDependencyIssue::Incompatible {
+                    mod_id: String::from("a"),
+                    filename: String::from("a.jar"),
+                    dependency: String::from("sodium"),
+                    versions: ranges(&["<0.5.0"]),
+                    found: vec![provider("sodium", "0.4.10")],
+                    breaking: true,
+                },
+                ModDependencyIssue::UnsatisfiedVersion {
+                    mod_id: String::from("b"),
+                    filename: String::from("b.jar"),
+                    dependency: String::from("minecraft"),
+                    versions: ranges(&["1.19.4"]),
+                    found: vec![DependencyProvider::Instance {
+                        version: Some(String::from("1.20.1")),
+                    }],
+                },
+                ModDependencyIssue::UnsatisfiedVersion {
+                    mod_id: String::from("b"),
+                    filename: String::from("b.jar"),
+                    dependency: String::from("mod_a"),
+                    versions: ranges(&[">=1.1.0"]),
+                    found: vec![provider("a", "1.0.0")],
+                },
+                ModDependencyIssue::Incompatible {
+                    mod_id: String::from("b"),
+                    filename: String::from("b.jar"),
+                    dependency: String::from("mod_c"),
+                    versions: vec![],
+                    found: vec![provider("c", "1.0.0"), provider("c2", "1.1.0")],
+                    breaking: false,
+                },
+                ModDependencyIssue::DuplicateModId {
+                    modid: String::from("mod_c"),
+                    mods: vec![provider("c", "1.0.0"), provider("c2", "1.1.0")],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn uses_metadata_of_the_instance_loader() {
+        let mut multiloader = fabric_mod(
+            "multi",
+            "multi",
+            "1.0.0",
+            &[("fabric", ModDependencyKind::Required, &[])],
+        );
+        multiloader.modloaders.push(ModLoaderType::Forge);
+        multiloader.dependencies.push(ModDependency {
+            modloader: ModLoaderType::Forge,
+            modid: String::from("forge"),
+            kind: ModDependencyKind::Required,
+            versions: ranges(&[">=47"]),
+        });
+
+        let forge = StandardVersion {
+            release: String::from("1.20.1"),
+            modloaders: HashSet::from([ModLoader {
+                type_: ModLoaderType::Forge,
+                version: String::from("1.20.1-47.2.0"),
+            }]),
+        };
+
+        assert_eq!(
+            analyze(Some(&forge), vec![multiloader.clone()]).issues,
+            vec![]
+        );
+
+        // only the fabric metadata is read on fabric
+        assert_eq!(
+            analyze(Some(&fabric_instance()), vec![multiloader.clone()]).issues,
+            vec![ModDependencyIssue::Missing {
+                mod_id: String::from("multi"),
+                filename: String::from("multi.jar"),
+                dependency: String::from("fabric"),
+                versions: vec![],
+            }]
+        );
+
+        // mods for other loaders aren't loaded at all
+        let neoforge = StandardVersion {
+            release: String::from("1.21"),
+            modloaders: HashSet::from([ModLoader {
+                type_: ModLoaderType::Neoforge,
+                version: String::from("21.0.10"),
+            }]),
+        };
+        let fabric_only = fabric_mod(
+            "fabric_only",
+            "fabric_only",
+            "1.0.0",
+            &[("fabric", ModDependencyKind::Required, &[])],
+        );
+
+        assert_eq!(analyze(Some(&neoforge), vec![fabric_only]).issues, vec![]);
+    }
+}
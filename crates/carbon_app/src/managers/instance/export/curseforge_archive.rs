@@ -5,19 +5,19 @@ use crate::{
         vtask::VisualTaskId,
     },
     managers::{
-        instance::{export::ZipMode, InstanceType, InvalidInstanceIdError},
+        instance::{InstanceType, InvalidInstanceIdError},
         modplatforms::curseforge::convert_standard_version_to_cf_version,
         vtask::{TaskState, VisualTask},
         AppInner,
     },
 };
 use anyhow::anyhow;
+use carbon_compression::{ArchiveFormat, CompressOptions, Progress};
 use carbon_platforms::curseforge::manifest::{
     Manifest, ManifestFileReference, Minecraft, ModLoaders,
 };
-use std::{collections::HashMap, fs::File, io::Write, path::PathBuf, sync::Arc};
-use tokio::sync::mpsc;
-use tracing::trace;
+use std::{collections::HashMap, path::PathBuf, sync::Arc};
+use tokio::sync::watch;
 
 use carbon_repos::db::{mod_file_cache as fcdb, mod_metadata as metadb};
 
@@ -131,17 +131,6 @@ pub async fn export_curseforge(
 
             t_calc_size.start_opaque();
 
-            let mut file_count = 0;
-            super::zip_excluding(
-                ZipMode::<File, ()>::Count(&mut file_count),
-                &basepath,
-                "overrides",
-                &filter,
-            )?;
-
-            t_calc_size.complete_opaque();
-            t_create_bundle.update_items(0, file_count);
-
             let manifest = Manifest {
                 minecraft: convert_standard_version_to_cf_version(version.clone())?,
                 manifest_type: String::from("minecraftModpack"),
@@ -167,40 +156,36 @@ pub async fn export_curseforge(
                 .maketmpfile()
                 .await?;
 
-            let send_path = tmpfile.to_path_buf();
-            let (notify_tx, mut notify_rx) = mpsc::channel::<()>(1);
-
-            let ziptask = tokio::task::spawn_blocking(move || {
-                let mut zip = zip::ZipWriter::new(File::create(&send_path)?);
-                let options = zip::write::FileOptions::<()>::default();
-                zip.start_file("manifest.json", options)?;
-                zip.write_all(&serde_json::to_vec_pretty(&manifest)?)?;
-
-                super::zip_excluding(
-                    ZipMode::Create(&mut zip, options, notify_tx),
-                    &basepath,
-                    "overrides",
-                    &filter,
-                )?;
+            let (progress_tx, mut progress_rx) = watch::channel(Progress::new());
 
-                zip.finish()?;
-                trace!("finished writing `{}`", send_path.to_string_lossy());
-                Ok::<_, anyhow::Error>(())
-            });
+            let options = CompressOptions::builder()
+                .format(ArchiveFormat::Zip)
+                .prefix("overrides")
+                .extra_file("manifest.json", serde_json::to_vec_pretty(&manifest)?)
+                .filter(super::export_filter(filter))
+                .progress_sender(progress_tx)
+                .build();
 
             tokio::select! {
-                r = ziptask => r??,
+                r = carbon_compression::compress(&basepath, &tmpfile, options) => r?,
                 _ = async {
-                    let mut counter = 0;
-
-                    loop {
-                        if notify_rx.recv().await.is_some() {
-                            counter += 1;
-                            t_create_bundle.update_items(counter, file_count);
-                        } else {
-                            futures::future::pending().await
+                    let mut size_known = false;
+
+                    while progress_rx.changed().await.is_ok() {
+                        let progress = *progress_rx.borrow();
+
+                        if !size_known {
+                            t_calc_size.complete_opaque();
+                            size_known = true;
                         }
+
+                        t_create_bundle.update_items(
+                            progress.current_count as u32,
+                            progress.total_count as u32,
+                        );
                     }
+
+                    futures::future::pending::<()>().await
                 } => {},
             }
 
@@ -1,22 +1,11 @@
-use std::{
-    fs::{self, File},
-    io,
-    path::{Path, PathBuf},
-};
-
-use itertools::Itertools;
-use tokio::sync::mpsc;
-use zip::{
-    write::{FileOptionExtension, FileOptions},
-    ZipWriter,
-};
+use std::path::{Path, PathBuf};
 
 use crate::{
     domain::{
         instance::{ExportEntry, ExportTarget, InstanceId},
         vtask::VisualTaskId,
     },
-    managers::{vtask::Subtask, ManagerRef},
+    managers::ManagerRef,
 };
 
 mod curseforge_archive;
@@ -65,59 +54,23 @@ impl ManagerRef<'_, InstanceExportManager> {
     }
 }
 
-enum ZipMode<'a, W: io::Write + io::Seek, T: FileOptionExtension + Clone> {
-    Count(&'a mut u32),
-    Create(&'a mut ZipWriter<W>, FileOptions<'a, T>, mpsc::Sender<()>),
-}
+/// Turns the export selection tree into a path filter for
+/// `carbon_compression::compress`. A `None` node selects everything below it.
+fn export_filter(filter: ExportEntry) -> impl Fn(&Path) -> bool + Send + 'static {
+    move |path: &Path| {
+        let mut node = Some(&filter);
 
-fn zip_excluding<W: io::Write + io::Seek, T: FileOptionExtension + Clone>(
-    mut mode: ZipMode<W, T>,
-    base_path: &Path,
-    prefix: &str,
-    filter: &ExportEntry,
-) -> anyhow::Result<()> {
-    fn walk_recursive<W: io::Write + io::Seek, T: FileOptionExtension + Clone>(
-        mode: &mut ZipMode<W, T>,
-        path: &Path,
-        prefix: &str,
-        relpath: &[&str],
-        filter: Option<&ExportEntry>,
-    ) -> anyhow::Result<()> {
-        for entry in fs::read_dir(path)? {
-            let entry = entry?;
-            let name = entry.file_name();
-            let name = name.to_string_lossy();
-
-            let Some(subfilter) = filter
-                .as_ref()
-                .map(|f| f.0.get(&*name))
-                .unwrap_or(Some(&None))
-            else {
-                continue;
+        for component in path.components() {
+            let Some(entry) = node else {
+                return true;
             };
 
-            let pathstr =
-                String::from(prefix) + "/" + &relpath.iter().chain([&*name].iter()).join("/");
-
-            if entry.metadata()?.is_dir() {
-                let relpath = &[relpath, &[&*name][..]].concat()[..];
-                walk_recursive(mode, &entry.path(), prefix, relpath, subfilter.as_ref())?;
-            } else {
-                match mode {
-                    ZipMode::Count(counter) => {
-                        **counter += 1;
-                    }
-                    ZipMode::Create(zip, options, notify) => {
-                        zip.start_file(pathstr, options.clone())?;
-                        io::copy(&mut File::open(entry.path())?, zip)?;
-                        let _ = notify.blocking_send(());
-                    }
-                }
+            match entry.0.get(&*component.as_os_str().to_string_lossy()) {
+                Some(subentry) => node = subentry.as_ref(),
+                None => return false,
             }
         }
 
-        Ok(())
+        true
     }
-
-    walk_recursive(&mut mode, base_path, prefix, &[], Some(filter))
 }
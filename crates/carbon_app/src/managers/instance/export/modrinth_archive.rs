@@ -15,16 +15,15 @@ use crate::{
     },
 };
 use anyhow::anyhow;
+use carbon_compression::{ArchiveFormat, CompressOptions, Progress};
 use carbon_platforms::modrinth::version::{
     Hashes, ModpackIndex, ModrinthFile, ModrinthGame, ModrinthPackDependencies,
 };
-use std::{collections::HashMap, fs::File, io::Write, path::PathBuf, sync::Arc};
-use tokio::sync::mpsc;
+use std::{collections::HashMap, path::PathBuf, sync::Arc};
+use tokio::sync::watch;
 
 use carbon_repos::db::{mod_file_cache as fcdb, mod_metadata as metadb};
 
-use super::ZipMode;
-
 pub async fn export_modrinth(
     app: Arc<AppInner>,
     instance_id: InstanceId,
@@ -142,17 +141,6 @@ pub async fn export_modrinth(
 
             t_calc_size.start_opaque();
 
-            let mut file_count = 0;
-            super::zip_excluding(
-                ZipMode::<File, ()>::Count(&mut file_count),
-                &basepath,
-                "overrides",
-                &filter,
-            )?;
-
-            t_calc_size.complete_opaque();
-            t_create_bundle.update_items(0, file_count);
-
             let manifest = ModpackIndex {
                 format_version: 1,
                 game: ModrinthGame::Minecraft,
@@ -185,39 +173,36 @@ pub async fn export_modrinth(
                 .maketmpfile()
                 .await?;
 
-            let send_path = tmpfile.to_path_buf();
-            let (notify_tx, mut notify_rx) = mpsc::channel::<()>(1);
-
-            let ziptask = tokio::task::spawn_blocking(move || {
-                let mut zip = zip::ZipWriter::new(File::create(send_path)?);
-                let options = zip::write::FileOptions::<()>::default();
-                zip.start_file("modrinth.index.json", options)?;
-                zip.write_all(&serde_json::to_vec_pretty(&manifest)?)?;
-
-                super::zip_excluding(
-                    ZipMode::Create(&mut zip, options, notify_tx),
-                    &basepath,
-                    "overrides",
-                    &filter,
-                )?;
+            let (progress_tx, mut progress_rx) = watch::channel(Progress::new());
 
-                zip.finish()?;
-                Ok::<_, anyhow::Error>(())
-            });
+            let options = CompressOptions::builder()
+                .format(ArchiveFormat::Zip)
+                .prefix("overrides")
+                .extra_file("modrinth.index.json", serde_json::to_vec_pretty(&manifest)?)
+                .filter(super::export_filter(filter))
+                .progress_sender(progress_tx)
+                .build();
 
             tokio::select! {
-                r = ziptask => r??,
+                r = carbon_compression::compress(&basepath, &tmpfile, options) => r?,
                 _ = async {
-                    let mut counter = 0;
-
-                    loop {
-                        if notify_rx.recv().await.is_some() {
-                            counter += 1;
-                            t_create_bundle.update_items(counter, file_count);
-                        } else {
-                            futures::future::pending().await
+                    let mut size_known = false;
+
+                    while progress_rx.changed().await.is_ok() {
+                        let progress = *progress_rx.borrow();
+
+                        if !size_known {
+                            t_calc_size.complete_opaque();
+                            size_known = true;
                         }
+
+                        t_create_bundle.update_items(
+                            progress.current_count as u32,
+                            progress.total_count as u32,
+                        );
                     }
+
+                    futures::future::pending::<()>().await
                 } => {},
             }
 
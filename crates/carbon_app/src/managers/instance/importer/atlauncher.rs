@@ -0,0 +1,473 @@
+use super::{
+    ImportScanStatus, ImportableInstance, ImporterState, InstanceImporter, InternalImportEntry,
+    InvalidImportEntry, GET_IMPORT_SCAN_STATUS,
+};
+use crate::{
+    api::translation::Translation,
+    domain::{
+        instance::info::{CurseforgeModpack, GameVersion, Modpack, ModrinthModpack},
+        vtask::VisualTaskId,
+    },
+    managers::{
+        instance::InstanceVersionSource,
+        metadata::cache::{KnownModFile, KnownModSource},
+        modplatforms::modrinth::convert_mr_version_to_standard_version,
+        AppInner,
+    },
+};
+use anyhow::anyhow;
+use carbon_platforms::modrinth::version::ModrinthPackDependencies;
+use serde::Deserialize;
+use std::{path::PathBuf, sync::Arc};
+use tokio::sync::RwLock;
+use tracing::trace;
+
+#[derive(Debug, Clone)]
+struct Importable {
+    filename: String,
+    path: PathBuf,
+    config: AtlInstance,
+}
+
+impl From<Importable> for ImportableInstance {
+    fn from(value: Importable) -> Self {
+        Self {
+            filename: value.filename,
+            instance_name: value.config.launcher.name,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ATLauncherImporter {
+    state: RwLock<ImporterState<Importable>>,
+}
+
+impl ATLauncherImporter {
+    pub fn new() -> Self {
+        Self {
+            state: RwLock::new(ImporterState::NoResults),
+        }
+    }
+
+    pub async fn get_default_scan_path() -> anyhow::Result<PathBuf> {
+        let basedirs = directories::BaseDirs::new().ok_or(anyhow!("Cannot build basedirs"))?;
+
+        #[cfg(target_os = "linux")]
+        let p = basedirs.data_dir().join("atlauncher");
+        #[cfg(not(target_os = "linux"))]
+        let p = basedirs.data_dir().join("ATLauncher");
+
+        Ok(p.join("instances"))
+    }
+
+    async fn scan_instance(
+        &self,
+        path: PathBuf,
+    ) -> anyhow::Result<Option<InternalImportEntry<Importable>>> {
+        let config = path.join("instance.json");
+        if !config.is_file() {
+            return Ok(None);
+        }
+
+        let filename = path
+            .file_name()
+            .expect("filename cannot be empty")
+            .to_string_lossy()
+            .to_string();
+
+        let config = tokio::fs::read_to_string(config).await?;
+        let config = serde_json::from_str::<AtlInstance>(&config);
+
+        let invalid = |reason| {
+            Ok(Some(InternalImportEntry::Invalid(InvalidImportEntry {
+                name: filename.clone(),
+                reason,
+            })))
+        };
+
+        let Ok(config) = config else {
+            return invalid(Translation::InstanceImportAtlBadConfigFile);
+        };
+
+        if let Some(loader) = &config.launcher.loader_version {
+            if loader.loader_type().is_none() {
+                return invalid(Translation::InstanceImportAtlUnsupportedLoader {
+                    loader: loader.type_.clone(),
+                });
+            }
+        }
+
+        Ok(Some(InternalImportEntry::Valid(Importable {
+            filename,
+            path,
+            config,
+        })))
+    }
+}
+
+#[async_trait::async_trait]
+impl InstanceImporter for ATLauncherImporter {
+    async fn scan(&self, app: &Arc<AppInner>, scan_path: PathBuf) -> anyhow::Result<()> {
+        if scan_path.join("instance.json").is_file() {
+            if let Ok(Some(entry)) = self.scan_instance(scan_path).await {
+                self.state.write().await.set_single(entry).await;
+                app.invalidate(GET_IMPORT_SCAN_STATUS, None);
+            }
+        } else if scan_path.is_dir() {
+            let Ok(mut dir) = tokio::fs::read_dir(&scan_path).await else {
+                return Ok(());
+            };
+
+            while let Some(path) = dir.next_entry().await? {
+                if path.metadata().await?.is_dir() {
+                    if let Ok(Some(entry)) = self.scan_instance(path.path()).await {
+                        self.state.write().await.push_multi(entry).await;
+                        app.invalidate(GET_IMPORT_SCAN_STATUS, None);
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn get_status(&self) -> ImportScanStatus {
+        self.state.read().await.clone().into()
+    }
+
+    async fn begin_import(
+        &self,
+        app: &Arc<AppInner>,
+        index: u32,
+        name: Option<String>,
+    ) -> anyhow::Result<VisualTaskId> {
+        trace!(?index, ?name, "Beginning atlauncher import");
+
+        let instance = self
+            .state
+            .read()
+            .await
+            .get(index)
+            .await
+            .cloned()
+            .ok_or_else(|| anyhow!("invalid importable instance index {index}"))?;
+
+        let launcher = &instance.config.launcher;
+
+        let mut dependencies = ModrinthPackDependencies {
+            minecraft: Some(instance.config.id.clone()),
+            forge: None,
+            neoforge: None,
+            fabric_loader: None,
+            quilt_loader: None,
+        };
+
+        if let Some(loader) = &launcher.loader_version {
+            let slot = match loader.loader_type() {
+                Some(AtlLoaderType::Forge) => &mut dependencies.forge,
+                Some(AtlLoaderType::NeoForge) => &mut dependencies.neoforge,
+                Some(AtlLoaderType::Fabric) => &mut dependencies.fabric_loader,
+                Some(AtlLoaderType::Quilt) => &mut dependencies.quilt_loader,
+                None => return Err(anyhow!("unsupported loader type {}", loader.type_)),
+            };
+
+            *slot = Some(loader.version.clone());
+        }
+
+        let standard_version =
+            convert_mr_version_to_standard_version(app.clone(), dependencies).await?;
+
+        let version = GameVersion::Standard(standard_version);
+
+        let modpack = match (
+            &launcher.curse_forge_project,
+            &launcher.curse_forge_file,
+            &launcher.modrinth_project,
+            &launcher.modrinth_version,
+        ) {
+            (Some(project), Some(file), _, _) => Some(Modpack::Curseforge(CurseforgeModpack {
+                project_id: project.id,
+                file_id: file.id,
+            })),
+            (_, _, Some(project), Some(version)) => Some(Modpack::Modrinth(ModrinthModpack {
+                project_id: project.id.clone(),
+                version_id: version.id.clone(),
+            })),
+            _ => None,
+        };
+
+        let instance_version_source = match modpack {
+            Some(modpack) => {
+                InstanceVersionSource::ModpackWithKnownVersion(version, modpack, false)
+            }
+            None => InstanceVersionSource::Version(version),
+        };
+
+        let icon_path = instance.path.join("instance.png");
+        let icon = match icon_path.is_file() {
+            true => app.instance_manager().load_icon(icon_path).await.ok(),
+            false => None,
+        };
+
+        let known_mods = launcher
+            .mods
+            .iter()
+            .filter(|m| m.type_ == "mods")
+            .filter_map(|m| {
+                let source = match m {
+                    AtlMod {
+                        curse_forge_project_id: Some(project_id),
+                        curse_forge_file_id: Some(file_id),
+                        ..
+                    } => KnownModSource::Curseforge {
+                        project_id: *project_id,
+                        file_id: *file_id,
+                    },
+                    AtlMod {
+                        modrinth_version: Some(version),
+                        ..
+                    } => KnownModSource::Modrinth {
+                        project_id: version.project_id.clone(),
+                        version_id: version.id.clone(),
+                    },
+                    _ => return None,
+                };
+
+                Some(KnownModFile {
+                    filename: m.file.clone(),
+                    source,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let initializer = |instance_path: PathBuf| {
+            let instance = &instance;
+            async move {
+                let path = instance_path.join("instance");
+
+                tokio::fs::create_dir_all(instance_path.join(".setup").join("modpack-complete"))
+                    .await?;
+
+                trace!("Copying files from atlauncher instance");
+                carbon_rt_path::copy_dir_filter(&instance.path, &path, |path| {
+                    match path
+                        .components()
+                        .next()
+                        .and_then(|c| c.as_os_str().to_str())
+                    {
+                        Some(
+                            "instance.json" | "instance.png" | "disabledmods" | "libraries"
+                            | "versions" | "natives" | "bin" | "logs",
+                        ) => false,
+                        _ => true,
+                    }
+                })
+                .await?;
+
+                // ATLauncher moves disabled mods out of the mods folder entirely
+                let disabled_path = instance.path.join("disabledmods");
+                let disabled_mods = instance
+                    .config
+                    .launcher
+                    .mods
+                    .iter()
+                    .filter(|m| m.disabled && m.type_ == "mods");
+
+                for m in disabled_mods {
+                    let source = disabled_path.join(&m.file);
+                    if !source.is_file() {
+                        continue;
+                    }
+
+                    let target = path.join("mods").join(format!("{}.disabled", m.file));
+                    tokio::fs::create_dir_all(path.join("mods")).await?;
+                    tokio::fs::copy(source, target).await?;
+                }
+
+                Ok(())
+            }
+        };
+
+        let id = app
+            .instance_manager()
+            .create_instance_ext(
+                app.instance_manager().get_default_group().await?,
+                name.unwrap_or_else(|| launcher.name.clone()),
+                icon,
+                None,
+                None,
+                instance_version_source,
+                launcher.description.clone().unwrap_or_default(),
+                initializer,
+            )
+            .await?;
+
+        if !known_mods.is_empty() {
+            app.meta_cache_manager()
+                .cache_known_mods(id, known_mods)
+                .await;
+        }
+
+        app.instance_manager()
+            .prepare_game(id, None, None, true)
+            .await
+            .map(|r| r.1)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AtlLoaderType {
+    Forge,
+    NeoForge,
+    Fabric,
+    Quilt,
+}
+
+/// ATLauncher's `instance.json` is the minecraft version json with
+/// the launcher specific data stored under `launcher`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AtlInstance {
+    id: String,
+    launcher: AtlLauncher,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AtlLauncher {
+    name: String,
+    description: Option<String>,
+    loader_version: Option<AtlLoaderVersion>,
+    #[serde(default)]
+    mods: Vec<AtlMod>,
+    curse_forge_project: Option<AtlCurseforgeId>,
+    curse_forge_file: Option<AtlCurseforgeId>,
+    modrinth_project: Option<AtlModrinthProject>,
+    modrinth_version: Option<AtlModrinthVersion>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AtlLoaderVersion {
+    version: String,
+    #[serde(rename = "type")]
+    type_: String,
+}
+
+impl AtlLoaderVersion {
+    fn loader_type(&self) -> Option<AtlLoaderType> {
+        match &*self.type_ {
+            "Forge" => Some(AtlLoaderType::Forge),
+            "NeoForge" => Some(AtlLoaderType::NeoForge),
+            "Fabric" => Some(AtlLoaderType::Fabric),
+            "Quilt" => Some(AtlLoaderType::Quilt),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AtlMod {
+    file: String,
+    #[serde(rename = "type")]
+    type_: String,
+    #[serde(default)]
+    disabled: bool,
+    curse_forge_project_id: Option<i32>,
+    curse_forge_file_id: Option<i32>,
+    modrinth_version: Option<AtlModrinthVersion>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct AtlCurseforgeId {
+    id: u32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct AtlModrinthProject {
+    id: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct AtlModrinthVersion {
+    id: String,
+    project_id: String,
+}
+
+#[cfg(test)]
+mod test {
+    use super::{AtlInstance, AtlLoaderType};
+
+    #[test]
+    fn parse_instance_json() {
+        let json = r#"{
+            "id": "1.20.1",
+            "type": "release",
+            "launcher": {
+                "name": "All The Mods 9",
+                "pack": "All The Mods 9",
+                "version": "0.2.60",
+                "loaderVersion": {
+                    "version": "47.2.0",
+                    "rawVersion": "1.20.1-47.2.0",
+                    "recommended": false,
+                    "type": "Forge"
+                },
+                "mods": [
+                    {
+                        "name": "JEI",
+                        "version": "15.3.0.4",
+                        "file": "jei-1.20.1-forge-15.3.0.4.jar",
+                        "type": "mods",
+                        "disabled": false,
+                        "curseForgeProjectId": 238222,
+                        "curseForgeFileId": 5101366
+                    },
+                    {
+                        "name": "Sodium",
+                        "file": "sodium.jar",
+                        "type": "mods",
+                        "disabled": true,
+                        "modrinthVersion": {
+                            "id": "OihdIimA",
+                            "project_id": "AANobbMI"
+                        }
+                    }
+                ],
+                "curseForgeProject": { "id": 715572, "name": "All the Mods 9" },
+                "curseForgeFile": { "id": 5125809 }
+            }
+        }"#;
+
+        let instance = serde_json::from_str::<AtlInstance>(json).unwrap();
+
+        assert_eq!(instance.id, "1.20.1");
+        assert_eq!(
+            instance
+                .launcher
+                .loader_version
+                .as_ref()
+                .and_then(|l| l.loader_type()),
+            Some(AtlLoaderType::Forge)
+        );
+        assert_eq!(instance.launcher.mods.len(), 2);
+        assert_eq!(
+            instance.launcher.mods[0].curse_forge_project_id,
+            Some(238222)
+        );
+        assert!(instance.launcher.mods[1].disabled);
+        assert_eq!(
+            instance.launcher.mods[1]
+                .modrinth_version
+                .as_ref()
+                .map(|v| &*v.project_id),
+            Some("AANobbMI")
+        );
+        assert_eq!(
+            instance.launcher.curse_forge_file.map(|f| f.id),
+            Some(5125809)
+        );
+    }
+}
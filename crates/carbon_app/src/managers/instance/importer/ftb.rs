@@ -0,0 +1,465 @@
+use super::{
+    ImportScanStatus, ImportableInstance, ImporterState, InstanceImporter, InternalImportEntry,
+    InvalidImportEntry, GET_IMPORT_SCAN_STATUS,
+};
+use crate::{
+    api::translation::Translation,
+    domain::{
+        instance::info::{CurseforgeModpack, GameVersion, Modpack},
+        vtask::VisualTaskId,
+    },
+    managers::{
+        instance::InstanceVersionSource,
+        modplatforms::modrinth::convert_mr_version_to_standard_version, AppInner,
+    },
+};
+use anyhow::anyhow;
+use carbon_platforms::modrinth::version::ModrinthPackDependencies;
+use serde::Deserialize;
+use std::{path::PathBuf, sync::Arc};
+use tokio::sync::RwLock;
+use tracing::trace;
+
+/// `packType` used by the FTB App for packs installed from CurseForge
+const PACK_TYPE_CURSEFORGE: u8 = 1;
+
+#[derive(Debug, Clone)]
+struct Importable {
+    filename: String,
+    path: PathBuf,
+    config: FtbInstance,
+    dependencies: ModrinthPackDependencies,
+}
+
+impl From<Importable> for ImportableInstance {
+    fn from(value: Importable) -> Self {
+        Self {
+            filename: value.filename,
+            instance_name: value.config.name,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct FTBImporter {
+    state: RwLock<ImporterState<Importable>>,
+}
+
+impl FTBImporter {
+    pub fn new() -> Self {
+        Self {
+            state: RwLock::new(ImporterState::NoResults),
+        }
+    }
+
+    pub async fn get_default_scan_path() -> anyhow::Result<PathBuf> {
+        let basedirs = directories::BaseDirs::new().ok_or(anyhow!("Cannot build basedirs"))?;
+
+        #[cfg(target_os = "windows")]
+        let p = basedirs.data_local_dir().join(".ftba");
+        #[cfg(target_os = "macos")]
+        let p = basedirs.data_dir().join(".ftba");
+        #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+        let p = basedirs.home_dir().join(".ftba");
+
+        Ok(p.join("instances"))
+    }
+
+    async fn scan_instance(
+        &self,
+        path: PathBuf,
+    ) -> anyhow::Result<Option<InternalImportEntry<Importable>>> {
+        let config = path.join("instance.json");
+        if !config.is_file() {
+            return Ok(None);
+        }
+
+        let filename = path
+            .file_name()
+            .expect("filename cannot be empty")
+            .to_string_lossy()
+            .to_string();
+
+        let invalid = |reason| {
+            Ok(Some(InternalImportEntry::Invalid(InvalidImportEntry {
+                name: filename.clone(),
+                reason,
+            })))
+        };
+
+        let config = tokio::fs::read_to_string(config).await?;
+        let Ok(config) = serde_json::from_str::<FtbInstance>(&config) else {
+            return invalid(Translation::InstanceImportFtbBadConfigFile);
+        };
+
+        // the pack version manifest is the most reliable source for the loader,
+        // older instances only have the summary stored in instance.json
+        let manifest = tokio::fs::read_to_string(path.join("version.json"))
+            .await
+            .ok()
+            .and_then(|text| serde_json::from_str::<FtbVersionManifest>(&text).ok());
+
+        let dependencies = match manifest {
+            Some(manifest) => targets_to_dependencies(&manifest.targets),
+            None => instance_to_dependencies(&config),
+        };
+
+        let dependencies = match dependencies {
+            Ok(dependencies) => dependencies,
+            Err(reason) => return invalid(reason),
+        };
+
+        Ok(Some(InternalImportEntry::Valid(Importable {
+            filename,
+            path,
+            config,
+            dependencies,
+        })))
+    }
+}
+
+#[async_trait::async_trait]
+impl InstanceImporter for FTBImporter {
+    async fn scan(&self, app: &Arc<AppInner>, scan_path: PathBuf) -> anyhow::Result<()> {
+        if scan_path.join("instance.json").is_file() {
+            if let Ok(Some(entry)) = self.scan_instance(scan_path).await {
+                self.state.write().await.set_single(entry).await;
+                app.invalidate(GET_IMPORT_SCAN_STATUS, None);
+            }
+        } else if scan_path.is_dir() {
+            let Ok(mut dir) = tokio::fs::read_dir(&scan_path).await else {
+                return Ok(());
+            };
+
+            while let Some(path) = dir.next_entry().await? {
+                if path.metadata().await?.is_dir() {
+                    if let Ok(Some(entry)) = self.scan_instance(path.path()).await {
+                        self.state.write().await.push_multi(entry).await;
+                        app.invalidate(GET_IMPORT_SCAN_STATUS, None);
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn get_status(&self) -> ImportScanStatus {
+        self.state.read().await.clone().into()
+    }
+
+    async fn begin_import(
+        &self,
+        app: &Arc<AppInner>,
+        index: u32,
+        name: Option<String>,
+    ) -> anyhow::Result<VisualTaskId> {
+        trace!(?index, ?name, "Beginning ftb import");
+
+        let instance = self
+            .state
+            .read()
+            .await
+            .get(index)
+            .await
+            .cloned()
+            .ok_or_else(|| anyhow!("invalid importable instance index {index}"))?;
+
+        let standard_version =
+            convert_mr_version_to_standard_version(app.clone(), instance.dependencies.clone())
+                .await?;
+
+        let version = GameVersion::Standard(standard_version);
+
+        // Packs from the FTB modpacks api have no equivalent platform here,
+        // they are imported as a plain instance with the files as they are
+        // and the pack they came from is kept in the notes.
+        let notes = ftb_pack_notes(&instance.config).unwrap_or_default();
+        let instance_version_source = match &instance.config {
+            FtbInstance {
+                id: Some(project_id),
+                version_id: Some(file_id),
+                pack_type: Some(PACK_TYPE_CURSEFORGE),
+                ..
+            } => {
+                let project_id = u32::try_from(*project_id).map_err(|_| {
+                    anyhow!("ftb pack id {project_id} is not a valid curseforge project id")
+                })?;
+                let file_id = u32::try_from(*file_id).map_err(|_| {
+                    anyhow!("ftb pack version id {file_id} is not a valid curseforge file id")
+                })?;
+
+                InstanceVersionSource::ModpackWithKnownVersion(
+                    version,
+                    Modpack::Curseforge(CurseforgeModpack {
+                        project_id,
+                        file_id,
+                    }),
+                    false,
+                )
+            }
+            _ => {
+                trace!(
+                    ftb_id = ?instance.config.id,
+                    ftb_version_id = ?instance.config.version_id,
+                    "Importing ftb instance without modpack tracking"
+                );
+
+                InstanceVersionSource::Version(version)
+            }
+        };
+
+        let icon_path = instance.path.join("folder.jpg");
+        let icon = match icon_path.is_file() {
+            true => app.instance_manager().load_icon(icon_path).await.ok(),
+            false => None,
+        };
+
+        let initializer = |instance_path: PathBuf| {
+            let instance = &instance;
+            async move {
+                let path = instance_path.join("instance");
+
+                tokio::fs::create_dir_all(instance_path.join(".setup").join("modpack-complete"))
+                    .await?;
+
+                trace!("Copying files from ftb instance");
+                carbon_rt_path::copy_dir_filter(&instance.path, &path, |path| {
+                    match path
+                        .components()
+                        .next()
+                        .and_then(|c| c.as_os_str().to_str())
+                    {
+                        Some(
+                            "instance.json" | "version.json" | "folder.jpg" | ".ftbapp"
+                            | "libraries" | "versions" | "natives" | "bin" | "logs",
+                        ) => false,
+                        _ => true,
+                    }
+                })
+                .await?;
+
+                Ok(())
+            }
+        };
+
+        let id = app
+            .instance_manager()
+            .create_instance_ext(
+                app.instance_manager().get_default_group().await?,
+                name.unwrap_or_else(|| instance.config.name.clone()),
+                icon,
+                None,
+                None,
+                instance_version_source,
+                notes,
+                initializer,
+            )
+            .await?;
+
+        app.instance_manager()
+            .prepare_game(id, None, None, true)
+            .await
+            .map(|r| r.1)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FtbInstance {
+    name: String,
+    /// FTB or CurseForge project id, depending on `pack_type`
+    id: Option<i64>,
+    version_id: Option<i64>,
+    /// Name of the pack version
+    version: Option<String>,
+    pack_type: Option<u8>,
+    mc_version: Option<String>,
+    mod_loader: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct FtbVersionManifest {
+    targets: Vec<FtbTarget>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct FtbTarget {
+    name: String,
+    version: String,
+    #[serde(rename = "type")]
+    type_: String,
+}
+
+/// Notes recording the FTB pack an instance was installed from, as it can't be
+/// tracked as a modpack.
+fn ftb_pack_notes(config: &FtbInstance) -> Option<String> {
+    if config.pack_type == Some(PACK_TYPE_CURSEFORGE) {
+        return None;
+    }
+
+    let (id, version_id) = (config.id?, config.version_id?);
+    let version = match &config.version {
+        Some(version) => format!("{version} ({version_id})"),
+        None => version_id.to_string(),
+    };
+
+    Some(format!(
+        "Imported from the FTB App.\nFTB pack id: {id}\nFTB pack version: {version}"
+    ))
+}
+
+fn empty_dependencies() -> ModrinthPackDependencies {
+    ModrinthPackDependencies {
+        minecraft: None,
+        forge: None,
+        neoforge: None,
+        fabric_loader: None,
+        quilt_loader: None,
+    }
+}
+
+fn set_loader(
+    dependencies: &mut ModrinthPackDependencies,
+    loader: &str,
+    version: String,
+) -> Result<(), Translation> {
+    let slot = match loader {
+        "forge" => &mut dependencies.forge,
+        "neoforge" => &mut dependencies.neoforge,
+        "fabric" => &mut dependencies.fabric_loader,
+        "quilt" => &mut dependencies.quilt_loader,
+        loader => {
+            return Err(Translation::InstanceImportFtbUnsupportedLoader {
+                loader: loader.to_string(),
+            })
+        }
+    };
+
+    *slot = Some(version);
+    Ok(())
+}
+
+fn targets_to_dependencies(targets: &[FtbTarget]) -> Result<ModrinthPackDependencies, Translation> {
+    let mut dependencies = empty_dependencies();
+
+    for target in targets {
+        match &*target.type_ {
+            "game" if target.name == "minecraft" => {
+                dependencies.minecraft = Some(target.version.clone())
+            }
+            "modloader" => set_loader(&mut dependencies, &target.name, target.version.clone())?,
+            // runtime targets are handled by our own java management
+            _ => {}
+        }
+    }
+
+    match dependencies.minecraft {
+        Some(_) => Ok(dependencies),
+        None => Err(Translation::InstanceImportFtbBadConfigFile),
+    }
+}
+
+/// `modLoader` is stored in the form `<mc version>-<loader>-<loader version>`
+fn instance_to_dependencies(
+    instance: &FtbInstance,
+) -> Result<ModrinthPackDependencies, Translation> {
+    let mut dependencies = empty_dependencies();
+
+    dependencies.minecraft = Some(
+        instance
+            .mc_version
+            .clone()
+            .ok_or(Translation::InstanceImportFtbBadConfigFile)?,
+    );
+
+    if let Some(mod_loader) = instance.mod_loader.as_ref().filter(|l| !l.is_empty()) {
+        let mut parts = mod_loader
+            .split('-')
+            .skip_while(|part| !matches!(*part, "forge" | "neoforge" | "fabric" | "quilt"));
+
+        let (Some(loader), Some(version)) = (parts.next(), parts.next()) else {
+            return Err(Translation::InstanceImportFtbUnsupportedLoader {
+                loader: mod_loader.clone(),
+            });
+        };
+
+        set_loader(&mut dependencies, loader, version.to_string())?;
+    }
+
+    Ok(dependencies)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::api::translation::Translation;
+
+    use super::{
+        ftb_pack_notes, instance_to_dependencies, targets_to_dependencies, FtbInstance,
+        FtbVersionManifest,
+    };
+
+    #[test]
+    fn parse_version_manifest() {
+        let json = r#"{
+            "id": 6,
+            "parent": 100,
+            "name": "1.11.0",
+            "targets": [
+                { "version": "17.0.8+7", "name": "java", "type": "runtime", "id": 1 },
+                { "version": "47.2.0", "name": "forge", "type": "modloader", "id": 2 },
+                { "version": "1.20.1", "name": "minecraft", "type": "game", "id": 3 }
+            ]
+        }"#;
+
+        let manifest = serde_json::from_str::<FtbVersionManifest>(json).unwrap();
+        let dependencies = targets_to_dependencies(&manifest.targets).unwrap();
+
+        assert_eq!(dependencies.minecraft.as_deref(), Some("1.20.1"));
+        assert_eq!(dependencies.forge.as_deref(), Some("47.2.0"));
+    }
+
+    #[test]
+    fn parse_instance_summary() {
+        let json = r#"{
+            "uuid": "0b2b3c5e-8c4b-4e7e-9a5b-1f7a6c1d2e3f",
+            "id": 100,
+            "versionId": 6,
+            "version": "1.11.0",
+            "name": "FTB Skies",
+            "packType": 0,
+            "mcVersion": "1.19.2",
+            "modLoader": "1.19.2-forge-43.3.5"
+        }"#;
+
+        let instance = serde_json::from_str::<FtbInstance>(json).unwrap();
+        let dependencies = instance_to_dependencies(&instance).unwrap();
+
+        assert_eq!(instance.id, Some(100));
+        assert_eq!(instance.version_id, Some(6));
+        assert_eq!(dependencies.minecraft.as_deref(), Some("1.19.2"));
+        assert_eq!(dependencies.forge.as_deref(), Some("43.3.5"));
+        assert_eq!(
+            ftb_pack_notes(&instance).as_deref(),
+            Some("Imported from the FTB App.\nFTB pack id: 100\nFTB pack version: 1.11.0 (6)")
+        );
+    }
+
+    #[test]
+    fn reject_unknown_loader() {
+        let json = r#"{
+            "name": "Broken",
+            "mcVersion": "1.12.2",
+            "modLoader": "1.12.2-liteloader-1.12.2"
+        }"#;
+
+        let instance = serde_json::from_str::<FtbInstance>(json).unwrap();
+
+        assert_eq!(
+            instance_to_dependencies(&instance).unwrap_err(),
+            Translation::InstanceImportFtbUnsupportedLoader {
+                loader: String::from("1.12.2-liteloader-1.12.2")
+            }
+        );
+    }
+}
@@ -14,16 +14,23 @@ use crate::{
 };
 
 use self::{
-    curseforge::CurseforgeImporter, curseforge_archive::CurseforgeArchiveImporter,
+    atlauncher::ATLauncherImporter, curseforge::CurseforgeImporter,
+    curseforge_archive::CurseforgeArchiveImporter, ftb::FTBImporter,
     legacy_gdlauncher::LegacyGDLauncherImporter, modrinth_archive::ModrinthArchiveImporter,
+    multimc::{MmcFlavor, MultiMCImporter},
+    technic::TechnicImporter,
 };
 
 use super::{export::InstanceExportManager, InstanceManager};
 
+mod atlauncher;
 mod curseforge;
 mod curseforge_archive;
+mod ftb;
 mod legacy_gdlauncher;
 mod modrinth_archive;
+mod multimc;
+mod technic;
 
 #[derive(Debug)]
 pub struct InstanceImportManager {
@@ -197,11 +204,16 @@ impl Entity {
     pub fn list() -> Vec<(Self, bool, SelectionType)> {
         use strum::IntoEnumIterator;
 
-        const SUPPORT: [Entity; 4] = [
+        const SUPPORT: [Entity; 9] = [
             Entity::LegacyGDLauncher,
             Entity::CurseForgeZip,
             Entity::MRPack,
             Entity::CurseForge,
+            Entity::MultiMC,
+            Entity::PrismLauncher,
+            Entity::ATLauncher,
+            Entity::Technic,
+            Entity::FTB,
         ];
 
         Self::iter()
@@ -215,6 +227,10 @@ impl Entity {
             Self::CurseForgeZip => Arc::new(CurseforgeArchiveImporter::new()),
             Self::MRPack => Arc::new(ModrinthArchiveImporter::new()),
             Self::CurseForge => Arc::new(CurseforgeImporter::new()),
+            Self::MultiMC | Self::PrismLauncher => Arc::new(MultiMCImporter::new()),
+            Self::ATLauncher => Arc::new(ATLauncherImporter::new()),
+            Self::Technic => Arc::new(TechnicImporter::new()),
+            Self::FTB => Arc::new(FTBImporter::new()),
             _ => todo!(),
         }
     }
@@ -225,6 +241,15 @@ impl Entity {
                 Some(LegacyGDLauncherImporter::get_default_scan_path().await?)
             }
             Self::CurseForge => Some(CurseforgeImporter::get_default_scan_path().await?),
+            Self::MultiMC => {
+                Some(MultiMCImporter::get_default_scan_path(MmcFlavor::MultiMC).await?)
+            }
+            Self::PrismLauncher => {
+                Some(MultiMCImporter::get_default_scan_path(MmcFlavor::PrismLauncher).await?)
+            }
+            Self::ATLauncher => Some(ATLauncherImporter::get_default_scan_path().await?),
+            Self::Technic => Some(TechnicImporter::get_default_scan_path().await?),
+            Self::FTB => Some(FTBImporter::get_default_scan_path().await?),
             _ => None,
         })
     }
@@ -0,0 +1,548 @@
+use super::{
+    ImportScanStatus, ImportableInstance, ImporterState, InstanceImporter, InternalImportEntry,
+    InvalidImportEntry, GET_IMPORT_SCAN_STATUS,
+};
+use crate::{
+    api::translation::Translation,
+    domain::{
+        instance::info::{CurseforgeModpack, GameVersion, Modpack, ModrinthModpack},
+        vtask::VisualTaskId,
+    },
+    managers::{
+        instance::InstanceVersionSource,
+        modplatforms::modrinth::convert_mr_version_to_standard_version, AppInner,
+    },
+};
+use anyhow::anyhow;
+use carbon_platforms::modrinth::version::ModrinthPackDependencies;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use tokio::sync::RwLock;
+use tracing::trace;
+
+/// Components that only pin libraries for the loaders above them
+/// and can be safely dropped during conversion.
+const IGNORED_COMPONENTS: [&str; 4] = [
+    "org.lwjgl",
+    "org.lwjgl3",
+    "net.fabricmc.intermediary",
+    "org.quiltmc.hashed",
+];
+
+const ICON_EXTENSIONS: [&str; 5] = ["png", "jpg", "jpeg", "gif", "ico"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MmcFlavor {
+    MultiMC,
+    PrismLauncher,
+}
+
+#[derive(Debug, Clone)]
+struct Importable {
+    filename: String,
+    path: PathBuf,
+    config: InstanceCfg,
+    dependencies: ModrinthPackDependencies,
+}
+
+impl From<Importable> for ImportableInstance {
+    fn from(value: Importable) -> Self {
+        Self {
+            filename: value.filename.clone(),
+            instance_name: value.config.name.unwrap_or(value.filename),
+        }
+    }
+}
+
+/// The subset of `instance.cfg` we care about.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct InstanceCfg {
+    instance_type: Option<String>,
+    name: Option<String>,
+    icon_key: Option<String>,
+    notes: Option<String>,
+    total_time_played: Option<u32>,
+    last_launch_time: Option<i64>,
+    managed_pack: Option<ManagedPack>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ManagedPack {
+    type_: String,
+    id: String,
+    version_id: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MmcPack {
+    components: Vec<MmcComponent>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MmcComponent {
+    uid: String,
+    version: Option<String>,
+    cached_version: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct MultiMCImporter {
+    state: RwLock<ImporterState<Importable>>,
+}
+
+impl MultiMCImporter {
+    pub fn new() -> Self {
+        Self {
+            state: RwLock::new(ImporterState::NoResults),
+        }
+    }
+
+    pub async fn get_default_scan_path(flavor: MmcFlavor) -> anyhow::Result<PathBuf> {
+        let basedirs = directories::BaseDirs::new().ok_or(anyhow!("Cannot build basedirs"))?;
+
+        let p = match flavor {
+            MmcFlavor::PrismLauncher => basedirs.data_dir().join("PrismLauncher"),
+            // MultiMC is distributed as a portable archive, the only
+            // standard location is the one used by the linux packages
+            #[cfg(target_os = "linux")]
+            MmcFlavor::MultiMC => basedirs.data_dir().join("multimc"),
+            #[cfg(not(target_os = "linux"))]
+            MmcFlavor::MultiMC => basedirs.home_dir().join("MultiMC"),
+        };
+
+        Ok(p.join("instances"))
+    }
+
+    async fn scan_instance(
+        &self,
+        path: PathBuf,
+    ) -> anyhow::Result<Option<InternalImportEntry<Importable>>> {
+        let config = path.join("instance.cfg");
+        if !config.is_file() {
+            return Ok(None);
+        }
+
+        let filename = path
+            .file_name()
+            .expect("filename cannot be empty")
+            .to_string_lossy()
+            .to_string();
+
+        let invalid = |reason| {
+            Ok(Some(InternalImportEntry::Invalid(InvalidImportEntry {
+                name: filename.clone(),
+                reason,
+            })))
+        };
+
+        let Ok(config) = tokio::fs::read_to_string(config).await else {
+            return invalid(Translation::InstanceImportMmcBadConfigFile);
+        };
+        let config = parse_instance_cfg(&config);
+
+        if !matches!(config.instance_type.as_deref(), None | Some("OneSix")) {
+            return invalid(Translation::InstanceImportMmcUnsupportedInstanceType);
+        }
+
+        let Ok(pack) = tokio::fs::read_to_string(path.join("mmc-pack.json")).await else {
+            return invalid(Translation::InstanceImportMmcMissingPack);
+        };
+        let Ok(pack) = serde_json::from_str::<MmcPack>(&pack) else {
+            return invalid(Translation::InstanceImportMmcMalformedPack);
+        };
+
+        let dependencies = match components_to_dependencies(&pack.components) {
+            Ok(dependencies) => dependencies,
+            Err(reason) => return invalid(reason),
+        };
+
+        Ok(Some(InternalImportEntry::Valid(Importable {
+            filename,
+            path,
+            config,
+            dependencies,
+        })))
+    }
+}
+
+#[async_trait::async_trait]
+impl InstanceImporter for MultiMCImporter {
+    async fn scan(&self, app: &Arc<AppInner>, scan_path: PathBuf) -> anyhow::Result<()> {
+        if scan_path.join("instance.cfg").is_file() {
+            if let Ok(Some(entry)) = self.scan_instance(scan_path).await {
+                self.state.write().await.set_single(entry).await;
+                app.invalidate(GET_IMPORT_SCAN_STATUS, None);
+            }
+        } else if scan_path.is_dir() {
+            let Ok(mut dir) = tokio::fs::read_dir(&scan_path).await else {
+                return Ok(());
+            };
+
+            while let Some(path) = dir.next_entry().await? {
+                if path.metadata().await?.is_dir() {
+                    if let Ok(Some(entry)) = self.scan_instance(path.path()).await {
+                        self.state.write().await.push_multi(entry).await;
+                        app.invalidate(GET_IMPORT_SCAN_STATUS, None);
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn get_status(&self) -> ImportScanStatus {
+        self.state.read().await.clone().into()
+    }
+
+    async fn begin_import(
+        &self,
+        app: &Arc<AppInner>,
+        index: u32,
+        name: Option<String>,
+    ) -> anyhow::Result<VisualTaskId> {
+        trace!(?index, ?name, "Beginning multimc import");
+
+        let instance = self
+            .state
+            .read()
+            .await
+            .get(index)
+            .await
+            .cloned()
+            .ok_or_else(|| anyhow!("invalid importable instance index {index}"))?;
+
+        let standard_version =
+            convert_mr_version_to_standard_version(app.clone(), instance.dependencies.clone())
+                .await?;
+
+        let version = GameVersion::Standard(standard_version);
+
+        let modpack = instance
+            .config
+            .managed_pack
+            .as_ref()
+            .and_then(|pack| match &*pack.type_ {
+                "flame" => Some(Modpack::Curseforge(CurseforgeModpack {
+                    project_id: pack.id.parse().ok()?,
+                    file_id: pack.version_id.parse().ok()?,
+                })),
+                "modrinth" => Some(Modpack::Modrinth(ModrinthModpack {
+                    project_id: pack.id.clone(),
+                    version_id: pack.version_id.clone(),
+                })),
+                _ => None,
+            });
+
+        let instance_version_source = match modpack {
+            Some(modpack) => {
+                InstanceVersionSource::ModpackWithKnownVersion(version, modpack, false)
+            }
+            None => InstanceVersionSource::Version(version),
+        };
+
+        let icon = match instance
+            .config
+            .icon_key
+            .as_ref()
+            .and_then(|key| find_icon(&instance.path, key))
+        {
+            Some(icon) => app.instance_manager().load_icon(icon).await.ok(),
+            None => None,
+        };
+
+        let last_played = instance
+            .config
+            .last_launch_time
+            .filter(|time| *time > 0)
+            .and_then(DateTime::<Utc>::from_timestamp_millis);
+
+        let initializer = |instance_path: PathBuf| {
+            let instance = &instance;
+            async move {
+                let path = instance_path.join("instance");
+
+                tokio::fs::create_dir_all(instance_path.join(".setup").join("modpack-complete"))
+                    .await?;
+
+                // older MultiMC versions used `minecraft` instead of `.minecraft`
+                let game_dir = [".minecraft", "minecraft"]
+                    .into_iter()
+                    .map(|dir| instance.path.join(dir))
+                    .find(|dir| dir.is_dir());
+
+                if let Some(game_dir) = game_dir {
+                    trace!("Copying files from multimc instance");
+                    carbon_rt_path::copy_dir_filter(&game_dir, &path, |path| {
+                        !path.starts_with("natives")
+                    })
+                    .await?;
+                }
+
+                Ok(())
+            }
+        };
+
+        let id = app
+            .instance_manager()
+            .create_instance_ext(
+                app.instance_manager().get_default_group().await?,
+                name.unwrap_or_else(|| {
+                    instance
+                        .config
+                        .name
+                        .clone()
+                        .unwrap_or_else(|| instance.filename.clone())
+                }),
+                icon,
+                instance.config.total_time_played,
+                last_played,
+                instance_version_source,
+                instance.config.notes.clone().unwrap_or_default(),
+                initializer,
+            )
+            .await?;
+
+        app.instance_manager()
+            .prepare_game(id, None, None, true)
+            .await
+            .map(|r| r.1)
+    }
+}
+
+/// Icons are stored in the `icons` folder next to `instances`, keyed by name.
+fn find_icon(instance_path: &Path, key: &str) -> Option<PathBuf> {
+    let icons = instance_path.parent()?.parent()?.join("icons");
+
+    ICON_EXTENSIONS
+        .iter()
+        .map(|ext| icons.join(format!("{key}.{ext}")))
+        .find(|path| path.is_file())
+}
+
+fn components_to_dependencies(
+    components: &[MmcComponent],
+) -> Result<ModrinthPackDependencies, Translation> {
+    let mut dependencies = ModrinthPackDependencies {
+        minecraft: None,
+        forge: None,
+        neoforge: None,
+        fabric_loader: None,
+        quilt_loader: None,
+    };
+
+    for component in components {
+        let version = component
+            .version
+            .clone()
+            .or_else(|| component.cached_version.clone());
+
+        let slot = match &*component.uid {
+            "net.minecraft" => &mut dependencies.minecraft,
+            "net.minecraftforge" => &mut dependencies.forge,
+            "net.neoforged" => &mut dependencies.neoforge,
+            "net.fabricmc.fabric-loader" => &mut dependencies.fabric_loader,
+            "org.quiltmc.quilt-loader" => &mut dependencies.quilt_loader,
+            uid if IGNORED_COMPONENTS.contains(&uid) => continue,
+            uid => {
+                return Err(Translation::InstanceImportMmcUnsupportedComponent {
+                    uid: uid.to_string(),
+                })
+            }
+        };
+
+        let Some(version) = version else {
+            return Err(Translation::InstanceImportMmcMalformedPack);
+        };
+
+        *slot = Some(version);
+    }
+
+    if dependencies.minecraft.is_none() {
+        return Err(Translation::InstanceImportMmcMalformedPack);
+    }
+
+    Ok(dependencies)
+}
+
+/// Parses the `[General]` section of a QSettings style `instance.cfg`.
+/// Very old MultiMC instances have no section header at all.
+fn parse_instance_cfg(text: &str) -> InstanceCfg {
+    let mut values = HashMap::<&str, String>::new();
+    let mut in_general = true;
+
+    for line in text.lines() {
+        let line = line.trim();
+
+        if line.is_empty() || line.starts_with(';') || line.starts_with('#') {
+            continue;
+        }
+
+        if line.starts_with('[') && line.ends_with(']') {
+            in_general = line == "[General]";
+            continue;
+        }
+
+        if !in_general {
+            continue;
+        }
+
+        if let Some((key, value)) = line.split_once('=') {
+            values.insert(key.trim(), unquote(value.trim()));
+        }
+    }
+
+    let mut take = |key| values.remove(key).filter(|value| !value.is_empty());
+
+    let managed_pack = match take("ManagedPack").as_deref() {
+        Some("true") => match (
+            take("ManagedPackType"),
+            take("ManagedPackID"),
+            take("ManagedPackVersionID"),
+        ) {
+            (Some(type_), Some(id), Some(version_id)) => Some(ManagedPack {
+                type_,
+                id,
+                version_id,
+            }),
+            _ => None,
+        },
+        _ => None,
+    };
+
+    InstanceCfg {
+        instance_type: take("InstanceType"),
+        name: take("name"),
+        icon_key: take("iconKey"),
+        notes: take("notes"),
+        total_time_played: take("totalTimePlayed").and_then(|v| v.parse().ok()),
+        last_launch_time: take("lastLaunchTime").and_then(|v| v.parse().ok()),
+        managed_pack,
+    }
+}
+
+fn unquote(value: &str) -> String {
+    let Some(value) = value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+    else {
+        return value.to_string();
+    };
+
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('t') => out.push('\t'),
+            Some(c) => out.push(c),
+            None => {}
+        }
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use crate::api::translation::Translation;
+
+    use super::{components_to_dependencies, parse_instance_cfg, ManagedPack, MmcPack};
+
+    #[test]
+    fn parse_prism_instance_cfg() {
+        let cfg = r#"
+[General]
+ConfigVersion=1.2
+InstanceType=OneSix
+ManagedPack=true
+ManagedPackID=AANobbMI
+ManagedPackType=modrinth
+ManagedPackVersionID=Rr5X1zUv
+iconKey=fabulously-optimized
+lastLaunchTime=1700000000000
+name=Fabulously Optimized
+notes="first line\nsecond \"line\""
+totalTimePlayed=3600
+"#;
+
+        let cfg = parse_instance_cfg(cfg);
+
+        assert_eq!(cfg.instance_type.as_deref(), Some("OneSix"));
+        assert_eq!(cfg.name.as_deref(), Some("Fabulously Optimized"));
+        assert_eq!(cfg.icon_key.as_deref(), Some("fabulously-optimized"));
+        assert_eq!(cfg.notes.as_deref(), Some("first line\nsecond \"line\""));
+        assert_eq!(cfg.total_time_played, Some(3600));
+        assert_eq!(cfg.last_launch_time, Some(1700000000000));
+        assert_eq!(
+            cfg.managed_pack,
+            Some(ManagedPack {
+                type_: String::from("modrinth"),
+                id: String::from("AANobbMI"),
+                version_id: String::from("Rr5X1zUv"),
+            })
+        );
+    }
+
+    #[test]
+    fn parse_legacy_instance_cfg() {
+        let cfg = "InstanceType=OneSix\nname=Old Instance\nManagedPack=false\n";
+
+        let cfg = parse_instance_cfg(cfg);
+
+        assert_eq!(cfg.name.as_deref(), Some("Old Instance"));
+        assert_eq!(cfg.managed_pack, None);
+    }
+
+    #[test]
+    fn map_components() {
+        let pack = r#"{
+            "components": [
+                { "uid": "org.lwjgl3", "version": "3.3.1" },
+                { "uid": "net.minecraft", "version": "1.20.1" },
+                { "uid": "net.fabricmc.intermediary", "version": "1.20.1" },
+                { "uid": "net.fabricmc.fabric-loader", "cachedVersion": "0.15.11" }
+            ],
+            "formatVersion": 1
+        }"#;
+
+        let pack = serde_json::from_str::<MmcPack>(pack).unwrap();
+        let dependencies = components_to_dependencies(&pack.components).unwrap();
+
+        assert_eq!(dependencies.minecraft.as_deref(), Some("1.20.1"));
+        assert_eq!(dependencies.fabric_loader.as_deref(), Some("0.15.11"));
+        assert_eq!(dependencies.forge, None);
+    }
+
+    #[test]
+    fn reject_unsupported_components() {
+        let pack = r#"{
+            "components": [
+                { "uid": "net.minecraft", "version": "1.12.2" },
+                { "uid": "com.mumfrey.liteloader", "version": "1.12.2" }
+            ]
+        }"#;
+
+        let pack = serde_json::from_str::<MmcPack>(pack).unwrap();
+
+        assert_eq!(
+            components_to_dependencies(&pack.components).unwrap_err(),
+            Translation::InstanceImportMmcUnsupportedComponent {
+                uid: String::from("com.mumfrey.liteloader")
+            }
+        );
+    }
+}
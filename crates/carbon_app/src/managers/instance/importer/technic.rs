@@ -0,0 +1,393 @@
+use super::{
+    ImportScanStatus, ImportableInstance, ImporterState, InstanceImporter, InternalImportEntry,
+    InvalidImportEntry, GET_IMPORT_SCAN_STATUS,
+};
+use crate::{
+    api::translation::Translation,
+    domain::{instance::info::GameVersion, vtask::VisualTaskId},
+    managers::{
+        instance::InstanceVersionSource,
+        modplatforms::modrinth::convert_mr_version_to_standard_version, AppInner,
+    },
+};
+use anyhow::anyhow;
+use carbon_platforms::modrinth::version::ModrinthPackDependencies;
+use serde::Deserialize;
+use std::{fs, io::Read, path::PathBuf, sync::Arc};
+use tokio::sync::RwLock;
+use tracing::trace;
+
+#[derive(Debug, Clone)]
+struct Importable {
+    filename: String,
+    path: PathBuf,
+    dependencies: ModrinthPackDependencies,
+}
+
+impl From<Importable> for ImportableInstance {
+    fn from(value: Importable) -> Self {
+        Self {
+            filename: value.filename.clone(),
+            instance_name: value.filename,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct TechnicImporter {
+    state: RwLock<ImporterState<Importable>>,
+}
+
+impl TechnicImporter {
+    pub fn new() -> Self {
+        Self {
+            state: RwLock::new(ImporterState::NoResults),
+        }
+    }
+
+    pub async fn get_default_scan_path() -> anyhow::Result<PathBuf> {
+        let basedirs = directories::BaseDirs::new().ok_or(anyhow!("Cannot build basedirs"))?;
+
+        #[cfg(target_os = "windows")]
+        let p = basedirs.data_dir().join(".technic");
+        #[cfg(target_os = "macos")]
+        let p = basedirs.data_dir().join("technic");
+        #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+        let p = basedirs.home_dir().join(".technic");
+
+        Ok(p.join("modpacks"))
+    }
+
+    async fn scan_instance(
+        &self,
+        path: PathBuf,
+    ) -> anyhow::Result<Option<InternalImportEntry<Importable>>> {
+        let bin = path.join("bin");
+        if !bin.is_dir() {
+            return Ok(None);
+        }
+
+        let filename = path
+            .file_name()
+            .expect("filename cannot be empty")
+            .to_string_lossy()
+            .to_string();
+
+        let r = tokio::task::spawn_blocking(move || {
+            let version_json = bin.join("version.json");
+            let data = match version_json.is_file() {
+                true => fs::read(version_json)
+                    .map_err(|_| Translation::InstanceImportTechnicMalformedVersion)?,
+                // forge based packs ship the version json inside of modpack.jar
+                false => {
+                    let file = fs::File::open(bin.join("modpack.jar"))
+                        .map_err(|_| Translation::InstanceImportTechnicMissingVersion)?;
+
+                    let mut zip = zip::ZipArchive::new(file)
+                        .map_err(|_| Translation::InstanceImportTechnicMalformedVersion)?;
+
+                    let mut version = zip
+                        .by_name("version.json")
+                        .map_err(|_| Translation::InstanceImportTechnicMissingVersion)?;
+
+                    let mut data = Vec::new();
+                    version
+                        .read_to_end(&mut data)
+                        .map_err(|_| Translation::InstanceImportTechnicMalformedVersion)?;
+
+                    data
+                }
+            };
+
+            let version = serde_json::from_slice::<TechnicVersion>(&data)
+                .map_err(|_| Translation::InstanceImportTechnicMalformedVersion)?;
+
+            version_to_dependencies(&version)
+                .ok_or(Translation::InstanceImportTechnicMalformedVersion)
+        })
+        .await?;
+
+        match r {
+            Ok(dependencies) => Ok(Some(InternalImportEntry::Valid(Importable {
+                filename,
+                path,
+                dependencies,
+            }))),
+            Err(reason) => Ok(Some(InternalImportEntry::Invalid(InvalidImportEntry {
+                name: filename,
+                reason,
+            }))),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl InstanceImporter for TechnicImporter {
+    async fn scan(&self, app: &Arc<AppInner>, scan_path: PathBuf) -> anyhow::Result<()> {
+        if scan_path.join("bin").is_dir() {
+            if let Ok(Some(entry)) = self.scan_instance(scan_path).await {
+                self.state.write().await.set_single(entry).await;
+                app.invalidate(GET_IMPORT_SCAN_STATUS, None);
+            }
+        } else if scan_path.is_dir() {
+            let Ok(mut dir) = tokio::fs::read_dir(&scan_path).await else {
+                return Ok(());
+            };
+
+            while let Some(path) = dir.next_entry().await? {
+                if path.metadata().await?.is_dir() {
+                    if let Ok(Some(entry)) = self.scan_instance(path.path()).await {
+                        self.state.write().await.push_multi(entry).await;
+                        app.invalidate(GET_IMPORT_SCAN_STATUS, None);
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn get_status(&self) -> ImportScanStatus {
+        self.state.read().await.clone().into()
+    }
+
+    async fn begin_import(
+        &self,
+        app: &Arc<AppInner>,
+        index: u32,
+        name: Option<String>,
+    ) -> anyhow::Result<VisualTaskId> {
+        trace!(?index, ?name, "Beginning technic import");
+
+        let instance = self
+            .state
+            .read()
+            .await
+            .get(index)
+            .await
+            .cloned()
+            .ok_or_else(|| anyhow!("invalid importable instance index {index}"))?;
+
+        let standard_version =
+            convert_mr_version_to_standard_version(app.clone(), instance.dependencies.clone())
+                .await?;
+
+        let instance_version_source =
+            InstanceVersionSource::Version(GameVersion::Standard(standard_version));
+
+        // the launcher keeps pack art in `.technic/assets/packs/<slug>`
+        let icon_path = instance.path.parent().and_then(|modpacks| {
+            Some(
+                modpacks
+                    .parent()?
+                    .join("assets")
+                    .join("packs")
+                    .join(&instance.filename)
+                    .join("icon.png"),
+            )
+        });
+
+        let icon = match icon_path {
+            Some(icon_path) if icon_path.is_file() => {
+                app.instance_manager().load_icon(icon_path).await.ok()
+            }
+            _ => None,
+        };
+
+        let initializer = |instance_path: PathBuf| {
+            let instance = &instance;
+            async move {
+                let path = instance_path.join("instance");
+
+                tokio::fs::create_dir_all(instance_path.join(".setup").join("modpack-complete"))
+                    .await?;
+
+                trace!("Copying files from technic modpack");
+                carbon_rt_path::copy_dir_filter(&instance.path, &path, |path| {
+                    !path.starts_with("bin") && !path.starts_with("logs")
+                })
+                .await?;
+
+                Ok(())
+            }
+        };
+
+        let id = app
+            .instance_manager()
+            .create_instance_ext(
+                app.instance_manager().get_default_group().await?,
+                name.unwrap_or_else(|| instance.filename.clone()),
+                icon,
+                None,
+                None,
+                instance_version_source,
+                String::new(),
+                initializer,
+            )
+            .await?;
+
+        app.instance_manager()
+            .prepare_game(id, None, None, true)
+            .await
+            .map(|r| r.1)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TechnicVersion {
+    id: String,
+    inherits_from: Option<String>,
+    #[serde(default)]
+    libraries: Vec<TechnicLibrary>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct TechnicLibrary {
+    name: String,
+}
+
+/// Technic packs ship a full version json, so the loader has to be
+/// recovered from the library list.
+fn version_to_dependencies(version: &TechnicVersion) -> Option<ModrinthPackDependencies> {
+    let mut dependencies = ModrinthPackDependencies {
+        minecraft: version.inherits_from.clone(),
+        forge: None,
+        neoforge: None,
+        fabric_loader: None,
+        quilt_loader: None,
+    };
+
+    let mut has_loader = false;
+
+    for library in &version.libraries {
+        let mut parts = library.name.splitn(3, ':');
+        let (Some(group), Some(artifact), Some(lib_version)) =
+            (parts.next(), parts.next(), parts.next())
+        else {
+            continue;
+        };
+
+        match (group, artifact) {
+            ("net.minecraftforge", "forge") => {
+                has_loader = true;
+                match lib_version.split_once('-') {
+                    Some((mc, forge)) => {
+                        dependencies.minecraft.get_or_insert_with(|| mc.to_string());
+                        // 1.7.10 era versions carry a trailing `-<mc version>`
+                        let forge = forge.split('-').next().unwrap_or(forge);
+                        dependencies.forge = Some(forge.to_string());
+                    }
+                    None => dependencies.forge = Some(lib_version.to_string()),
+                }
+            }
+            ("net.minecraftforge", "minecraftforge") => {
+                has_loader = true;
+                dependencies.forge = Some(lib_version.to_string());
+            }
+            ("net.neoforged", "neoforge") => {
+                has_loader = true;
+                dependencies.neoforge = Some(lib_version.to_string());
+            }
+            ("net.fabricmc", "fabric-loader") => {
+                has_loader = true;
+                dependencies.fabric_loader = Some(lib_version.to_string());
+            }
+            ("net.fabricmc", "intermediary") | ("org.quiltmc", "hashed") => {
+                dependencies
+                    .minecraft
+                    .get_or_insert_with(|| lib_version.to_string());
+            }
+            ("org.quiltmc", "quilt-loader") => {
+                has_loader = true;
+                dependencies.quilt_loader = Some(lib_version.to_string());
+            }
+            _ => {}
+        }
+    }
+
+    if dependencies.minecraft.is_none() {
+        dependencies.minecraft = match has_loader {
+            // modded ids look like `1.7.10-Forge10.13.4.1614-1.7.10`
+            true => version.id.split('-').next().map(ToString::to_string),
+            false => Some(version.id.clone()),
+        };
+    }
+
+    if dependencies
+        .minecraft
+        .as_deref()
+        .map_or(true, str::is_empty)
+    {
+        return None;
+    }
+
+    Some(dependencies)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{version_to_dependencies, TechnicVersion};
+
+    #[test]
+    fn forge_version_json() {
+        let json = r#"{
+            "id": "1.12.2-forge1.12.2-14.23.5.2847",
+            "libraries": [
+                { "name": "net.minecraftforge:forge:1.12.2-14.23.5.2847" },
+                { "name": "org.ow2.asm:asm-debug-all:5.2" }
+            ]
+        }"#;
+
+        let version = serde_json::from_str::<TechnicVersion>(json).unwrap();
+        let dependencies = version_to_dependencies(&version).unwrap();
+
+        assert_eq!(dependencies.minecraft.as_deref(), Some("1.12.2"));
+        assert_eq!(dependencies.forge.as_deref(), Some("14.23.5.2847"));
+    }
+
+    #[test]
+    fn legacy_forge_version_json() {
+        let json = r#"{
+            "id": "1.7.10-Forge10.13.4.1614-1.7.10",
+            "libraries": [
+                { "name": "net.minecraftforge:forge:1.7.10-10.13.4.1614-1.7.10" }
+            ]
+        }"#;
+
+        let version = serde_json::from_str::<TechnicVersion>(json).unwrap();
+        let dependencies = version_to_dependencies(&version).unwrap();
+
+        assert_eq!(dependencies.minecraft.as_deref(), Some("1.7.10"));
+        assert_eq!(dependencies.forge.as_deref(), Some("10.13.4.1614"));
+    }
+
+    #[test]
+    fn fabric_version_json() {
+        let json = r#"{
+            "id": "fabric-loader-0.15.11-1.20.1",
+            "inheritsFrom": "1.20.1",
+            "libraries": [
+                { "name": "net.fabricmc:intermediary:1.20.1" },
+                { "name": "net.fabricmc:fabric-loader:0.15.11" }
+            ]
+        }"#;
+
+        let version = serde_json::from_str::<TechnicVersion>(json).unwrap();
+        let dependencies = version_to_dependencies(&version).unwrap();
+
+        assert_eq!(dependencies.minecraft.as_deref(), Some("1.20.1"));
+        assert_eq!(dependencies.fabric_loader.as_deref(), Some("0.15.11"));
+    }
+
+    #[test]
+    fn vanilla_version_json() {
+        let json = r#"{ "id": "1.8.9", "libraries": [] }"#;
+
+        let version = serde_json::from_str::<TechnicVersion>(json).unwrap();
+        let dependencies = version_to_dependencies(&version).unwrap();
+
+        assert_eq!(dependencies.minecraft.as_deref(), Some("1.8.9"));
+        assert_eq!(dependencies.forge, None);
+    }
+}
@@ -11,22 +11,25 @@ use crate::{
     },
     managers::{
         instance::Mod,
+        metadata::cache::{KnownModFile, KnownModSource},
+        modplatforms::source::ModSource,
         vtask::{TaskState, VisualTask},
         AppInner,
     },
 };
 use anyhow::{bail, Context};
-use carbon_net::{Checksum, DownloadOptions, Downloadable};
+use carbon_net::{Checksum, Downloadable};
 use carbon_platforms::{
     curseforge::{
         self,
-        filters::{ModFileParameters, ModFilesParameters, ModFilesParametersQuery},
+        filters::{ModFilesParameters, ModFilesParametersQuery},
     },
     modrinth::{
         self,
         project::ProjectVersionsFilters,
         search::{ProjectID, VersionID},
     },
+    source::SourceVersion,
     ModChannel,
 };
 use carbon_repos::db::{
@@ -35,6 +38,7 @@ use carbon_repos::db::{
 };
 use carbon_rt_path::InstancePath;
 use futures::future::Future;
+use reqwest::header::HeaderMap;
 use std::{ops::Deref, pin::Pin, sync::Arc, time::Duration};
 use tokio::{sync::Mutex, task::AbortHandle};
 
@@ -95,6 +99,10 @@ pub trait ResourceInstaller: Sync {
     ) -> anyhow::Result<bool>;
     fn display_name(&self) -> String;
     async fn rollback(&self, instance_data: &mut InstanceData) -> anyhow::Result<()>;
+    /// The remote origin of the installed file, if it can not be found by hash.
+    fn known_source(&self) -> Option<KnownModSource> {
+        None
+    }
 }
 
 #[async_trait::async_trait]
@@ -137,6 +145,11 @@ impl<I: ResourceInstaller + ?Sized + Send> ResourceInstaller for Box<I> {
     async fn rollback(&self, instance_data: &mut InstanceData) -> anyhow::Result<()> {
         (**self).rollback(instance_data).await
     }
+
+    #[inline]
+    fn known_source(&self) -> Option<KnownModSource> {
+        (**self).known_source()
+    }
 }
 
 pub struct InstallResult {
@@ -480,12 +493,24 @@ impl Installer {
 
                             carbon_net::download_multiple(
                                 &[downloadable.clone()],
-                                DownloadOptions::builder().concurrency(1).build(),
+                                app_clone
+                                    .settings_manager()
+                                    .download_options()
+                                    .concurrency(1)
+                                    .build(),
                             )
                             .await
                             .with_context(|| {
                                 format!("Failed to download addon file for `{:?}`", downloadable)
                             })?;
+
+                            app_clone
+                                .instance_manager()
+                                .store_instance_files(
+                                    &instance_path,
+                                    vec![downloadable.path.clone()],
+                                )
+                                .await?;
                         }
 
                         if let Some(id) = replaces_mod_id {
@@ -495,6 +520,23 @@ impl Installer {
                                 .await?;
                         }
 
+                        let known_source = inner.lock().await.known_source();
+                        if let (Some(source), Some(downloadable)) = (known_source, &downloadable) {
+                            let filename = downloadable
+                                .path
+                                .file_name()
+                                .map(|name| name.to_string_lossy().into_owned())
+                                .ok_or_else(|| anyhow::anyhow!("downloaded mod has no filename"))?;
+
+                            app_clone
+                                .meta_cache_manager()
+                                .cache_known_mods(
+                                    instance_id,
+                                    vec![KnownModFile { filename, source }],
+                                )
+                                .await;
+                        }
+
                         // ensure the task stays alive until the mod is cached
                         app_clone
                             .meta_cache_manager()
@@ -540,32 +582,6 @@ pub struct CurseforgeModInstaller {
 }
 
 impl CurseforgeModInstaller {
-    pub async fn create(
-        app: &Arc<AppInner>,
-        project_id: u32,
-        file_id: u32,
-    ) -> anyhow::Result<Self> {
-        let file = app
-            .modplatforms_manager()
-            .curseforge
-            .get_mod_file(ModFileParameters {
-                mod_id: project_id as i32,
-                file_id: file_id as i32,
-            })
-            .await?
-            .data;
-
-        let download_url = file.download_url.clone().ok_or_else(|| {
-            anyhow::anyhow!("mod cannot be downloaded without privileged api key")
-        })?;
-
-        Ok(Self {
-            file,
-            download_url,
-            applied_data: Arc::new(Mutex::new(None)),
-        })
-    }
-
     pub fn from_file(file: curseforge::File) -> anyhow::Result<Self> {
         let download_url = file.download_url.clone().ok_or_else(|| {
             anyhow::anyhow!("mod cannot be downloaded without privileged api key")
@@ -810,40 +826,6 @@ pub struct ModrinthModInstaller {
 }
 
 impl ModrinthModInstaller {
-    pub async fn create(
-        app: &Arc<AppInner>,
-        project_id: String,
-        version_id: String,
-    ) -> anyhow::Result<Self> {
-        let version = app
-            .modplatforms_manager()
-            .modrinth
-            .get_version(VersionID(version_id.clone()))
-            .await?;
-
-        let file = version
-            .files
-            .iter()
-            .reduce(|a, b| if b.primary { b } else { a })
-            .cloned()
-            .ok_or_else(|| {
-                anyhow::anyhow!(
-                    "Modrinth project '{}' version '{}' does not have a file",
-                    &project_id,
-                    &version_id
-                )
-            })?;
-
-        let download_url = file.url.clone();
-
-        Ok(Self {
-            version,
-            file,
-            download_url,
-            applied_data: Arc::new(Mutex::new(None)),
-        })
-    }
-
     pub fn from_version(version: modrinth::version::Version) -> anyhow::Result<Self> {
         let file = version
             .files
@@ -1099,3 +1081,115 @@ impl IntoInstaller for ModrinthModInstaller {
         Installer::new(Box::new(self) as BoxedResourceInstaller)
     }
 }
+
+// mod sources
+pub struct SourceModInstaller {
+    version: SourceVersion,
+    /// Credentials of private repositories, the version url never carries them.
+    download_headers: HeaderMap,
+    applied_data: Arc<Mutex<Option<(Mod, Downloadable)>>>,
+}
+
+impl SourceModInstaller {
+    pub fn from_version<S: ModSource + ?Sized>(
+        mod_source: &S,
+        version: SourceVersion,
+    ) -> anyhow::Result<Self> {
+        Ok(Self {
+            download_headers: mod_source.download_headers(&version)?,
+            version,
+            applied_data: Arc::new(Mutex::new(None)),
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl ResourceInstaller for SourceModInstaller {
+    fn id(&self) -> String {
+        format!(
+            "{}:{}:{}",
+            &self.version.source, &self.version.project_id, &self.version.id
+        )
+    }
+
+    async fn downloadable(&self, instance_path: &InstancePath) -> Option<Downloadable> {
+        let install_path = instance_path.get_mods_path().join(&self.version.file_name);
+
+        let checksum = match (&self.version.sha1, &self.version.sha256) {
+            (Some(sha1), _) => Some(Checksum::Sha1(sha1.clone())),
+            (None, Some(sha256)) => Some(Checksum::Sha256(sha256.clone())),
+            (None, None) => None,
+        };
+
+        let downloadable = Downloadable::new(&self.version.url, install_path)
+            .with_checksum(checksum)
+            .with_headers(self.download_headers.clone());
+
+        Some(match self.version.size {
+            Some(size) => downloadable.with_size(size),
+            None => downloadable,
+        })
+    }
+
+    fn dependencies(
+        &self,
+        _app: &Arc<AppInner>,
+        _instance_id: InstanceId,
+        _instance_data: &InstanceData,
+        _preferred_channel: ModChannel,
+    ) -> DependencyIterator {
+        // mod sources do not declare dependencies
+        DependencyIterator::new(std::iter::empty())
+    }
+
+    async fn is_already_installed(
+        &self,
+        app: &Arc<AppInner>,
+        instance_id: InstanceId,
+    ) -> anyhow::Result<bool> {
+        let is_installed = app
+            .prisma_client
+            .mod_file_cache()
+            .find_unique(fcdb::UniqueWhereParam::InstanceIdFilenameEquals(
+                *instance_id,
+                self.version.file_name.clone(),
+            ))
+            .exec()
+            .await?
+            .is_some();
+
+        Ok(is_installed)
+    }
+
+    fn display_name(&self) -> String {
+        self.version.file_name.clone()
+    }
+
+    async fn rollback(&self, _instance_data: &mut InstanceData) -> anyhow::Result<()> {
+        let mut lock = self.applied_data.lock().await;
+        if let Some((_, downloadable)) = &*lock {
+            if let Ok(true) = tokio::fs::try_exists(&downloadable.path).await {
+                tokio::fs::remove_file(&downloadable.path).await?;
+            }
+        }
+
+        *lock = None;
+
+        Ok(())
+    }
+
+    fn known_source(&self) -> Option<KnownModSource> {
+        Some(KnownModSource::Source {
+            source_id: self.version.source.clone(),
+            repository: Some(self.version.repository.clone()),
+            project_id: self.version.project_id.clone(),
+            version_id: self.version.id.clone(),
+        })
+    }
+}
+
+impl IntoInstaller for SourceModInstaller {
+    fn into_installer(self) -> Installer {
+        Installer::new(Box::new(self) as BoxedResourceInstaller)
+    }
+}
@@ -4,6 +4,7 @@ use itertools::Itertools;
 use serde::Serialize;
 use std::{
     ops::{Bound, RangeBounds},
+    process::ExitStatus,
     sync::atomic::{AtomicI32, Ordering},
 };
 
@@ -23,7 +24,10 @@ use crate::{
 use super::InstanceManager;
 
 #[derive(Debug, Default)]
-pub struct GameLog(Vec<LogEntry>);
+pub struct GameLog {
+    entries: Vec<LogEntry>,
+    exit_status: Option<ExitStatus>,
+}
 
 /// Represents a log entry.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize)]
@@ -139,12 +143,12 @@ impl GameLog {
 
     /// Inserts a new entry into the log.
     pub fn add_entry(&mut self, entry: LogEntry) {
-        self.0.push(entry)
+        self.entries.push(entry)
     }
 
     /// Retrieves the requested entry from the log.
     pub fn get_entry(&self, line: usize) -> Option<&LogEntry> {
-        self.0.get(line)
+        self.entries.get(line)
     }
 
     /// Get a region of log entries containing the given start and end lines
@@ -157,21 +161,31 @@ impl GameLog {
         };
 
         let end = match lines.end_bound() {
-            Bound::Included(e) if *e <= self.0.len() => *e + 1, // normalize to excluded
-            Bound::Excluded(e) if *e < self.0.len() => *e,
-            _ => self.0.len(),
+            Bound::Included(e) if *e <= self.entries.len() => *e + 1, // normalize to excluded
+            Bound::Excluded(e) if *e < self.entries.len() => *e,
+            _ => self.entries.len(),
         };
 
         if start >= end {
             return Default::default();
         }
 
-        &self.0[start..end]
+        &self.entries[start..end]
     }
 
     /// Get the number of entries contained in the log.
     pub fn len(&self) -> usize {
-        self.0.len()
+        self.entries.len()
+    }
+
+    /// Records how the game process exited.
+    pub fn set_exit_status(&mut self, status: ExitStatus) {
+        self.exit_status = Some(status)
+    }
+
+    /// How the game process exited, if it ran and has exited.
+    pub fn exit_status(&self) -> Option<ExitStatus> {
+        self.exit_status
     }
 }
 
@@ -48,6 +48,7 @@ use tokio::sync::{watch, Mutex, MutexGuard, RwLock};
 use tracing::{info, trace};
 use unicode_segmentation::UnicodeSegmentation;
 
+mod dependencies;
 pub mod explore;
 pub mod export;
 pub mod importer;
@@ -57,6 +58,7 @@ pub mod modpack;
 mod mods;
 mod run;
 mod schema;
+pub mod store;
 
 #[derive(Debug)]
 pub struct InstanceManager {
@@ -79,8 +81,12 @@ pub struct InstanceManager {
         >,
     >,
     modpack_info_semaphore: Mutex<()>,
+    // held for writing while the store is garbage collected
+    store_lock: RwLock<()>,
     pub any_instance_running: Arc<watch::Sender<bool>>,
     instance_running_tracker: Arc<LivenessTracker>,
+    // set once the instances folder has been scanned at startup
+    initial_scan_done: watch::Sender<bool>,
 }
 
 impl Default for InstanceManager {
@@ -103,10 +109,12 @@ impl InstanceManager {
             export_manager: InstanceExportManager::new(),
             game_logs: RwLock::new(HashMap::new()),
             modpack_info_semaphore: Mutex::new(()),
+            store_lock: RwLock::new(()),
             any_instance_running: any_instance_running.clone(),
             instance_running_tracker: LivenessTracker::new(move |count| {
                 drop(any_instance_running.send_replace(count != 0))
             }),
+            initial_scan_done: watch::channel(false).0,
         }
     }
 }
@@ -162,7 +170,21 @@ fn path_length(path: &Path) -> usize {
 impl<'s> ManagerRef<'s, InstanceManager> {
     pub async fn launch_background_tasks(self) {
         let _ = self.scan_instances().await;
+        self.initial_scan_done.send_replace(true);
         self.import_manager().launch_background_tasks();
+
+        if let Err(e) = self.garbage_collect_store().await {
+            tracing::error!("Could not garbage collect the store: {e:?}");
+        }
+    }
+
+    /// Waits for the instances scan started by `launch_background_tasks`.
+    pub async fn wait_for_initial_scan(self) {
+        let _ = self
+            .initial_scan_done
+            .subscribe()
+            .wait_for(|done| *done)
+            .await;
     }
 
     pub async fn scan_instances(self) -> anyhow::Result<()> {
@@ -1401,12 +1423,18 @@ impl<'s> ManagerRef<'s, InstanceManager> {
 
         instances.remove(&instance_id);
         self.remove_instance(instance_id).await?;
+        drop(instances);
 
         self.app.invalidate(GET_GROUPS, None);
         self.app.invalidate(GET_ALL_INSTANCES, None);
         self.app
             .invalidate(INSTANCE_DETAILS, Some(instance_id.0.into()));
 
+        // the deleted instance may have been the last one using some blobs
+        if let Err(e) = self.garbage_collect_store().await {
+            tracing::error!("Could not garbage collect the store: {e:?}");
+        }
+
         Ok(())
     }
 
@@ -1,5 +1,6 @@
-use super::{InstanceData, InstanceManager, InstanceType};
+use super::{InstanceData, InstanceManager, InstanceType, LaunchState};
 use crate::{
+    api::keys::instance::GET_MODPACK_UPDATE_PLAN,
     domain::{
         instance::{
             info::{self, CurseforgeModpack, Modpack, ModpackInfo, ModrinthModpack},
@@ -22,8 +23,12 @@ use carbon_platforms::{
 };
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::path::PathBuf;
+
+use self::plan::{FileOverride, ModpackUpdatePlan};
 
 pub mod packinfo;
+pub mod plan;
 
 impl ManagerRef<'_, InstanceManager> {
     pub async fn check_curseforge_modpack_updates(
@@ -134,11 +139,135 @@ impl ManagerRef<'_, InstanceManager> {
     /// Change the modpack (hence modpack version) of an instance.
     /// While this could also change between different modpack (and modplatforms), the usual use case is to change
     /// between modpack versions.
+    ///
+    /// If the same change was previewed with [`Self::preview_modpack_change`], the previewed plan is applied
+    /// with the given overrides, or not at all if the instance changed in the meantime.
     pub async fn change_modpack(
         self,
         instance_id: InstanceId,
         modpack: Modpack,
+        overrides: HashMap<String, FileOverride>,
     ) -> anyhow::Result<VisualTaskId> {
+        let setup_path = self.setup_path(instance_id).await?;
+
+        if plan::is_preview(&setup_path) && plan::read_plan(&setup_path).await?.is_some() {
+            let previewed =
+                tokio::fs::read_to_string(setup_path.join("change-pack-version.json")).await?;
+
+            if serde_json::from_str::<PackVersionFile>(&previewed)?
+                != PackVersionFile::from(modpack)
+            {
+                anyhow::bail!("A different modpack change is being previewed for this instance");
+            }
+
+            plan::write_overrides(&setup_path, &overrides).await?;
+            plan::clear_preview(&setup_path).await?;
+        } else {
+            self.stage_modpack_change(instance_id, modpack, false)
+                .await?;
+            plan::write_overrides(&setup_path, &overrides).await?;
+        }
+
+        self.app
+            .instance_manager()
+            .prepare_game(instance_id, None, None, true)
+            .await
+            .map(|r| r.1)
+    }
+
+    /// Stages a modpack change and plans it without touching the instance.
+    /// The plan can be read with [`Self::get_modpack_update_plan`] once the task is done.
+    pub async fn preview_modpack_change(
+        self,
+        instance_id: InstanceId,
+        modpack: Modpack,
+    ) -> anyhow::Result<VisualTaskId> {
+        let setup_path = self.setup_path(instance_id).await?;
+
+        // previewing again replaces the pending change
+        if plan::read_plan(&setup_path).await?.is_some() {
+            self.cancel_modpack_change(instance_id).await?;
+        }
+
+        self.stage_modpack_change(instance_id, modpack, true)
+            .await?;
+
+        self.app
+            .instance_manager()
+            .prepare_game(instance_id, None, None, true)
+            .await
+            .map(|r| r.1)
+    }
+
+    pub async fn get_modpack_update_plan(
+        self,
+        instance_id: InstanceId,
+    ) -> anyhow::Result<Option<ModpackUpdatePlan>> {
+        let setup_path = self.setup_path(instance_id).await?;
+
+        match plan::is_preview(&setup_path) {
+            true => plan::read_plan(&setup_path).await,
+            false => Ok(None),
+        }
+    }
+
+    /// Drops a planned modpack change that has not been applied yet.
+    pub async fn cancel_modpack_change(self, instance_id: InstanceId) -> anyhow::Result<()> {
+        let instances = self.instances.read().await;
+        let instance = instances
+            .get(&instance_id)
+            .ok_or(InvalidInstanceIdError(instance_id))?;
+
+        if !matches!(instance.data()?.state, LaunchState::Inactive { .. }) {
+            anyhow::bail!("Cannot cancel a modpack change while the instance is in use");
+        }
+
+        let setup_path = self
+            .app
+            .settings_manager()
+            .runtime_path
+            .get_instances()
+            .get_instance_path(&instance.shortpath)
+            .get_root()
+            .join(".setup");
+
+        drop(instances);
+
+        // once staging is gone the change has been applied
+        if plan::read_plan(&setup_path).await?.is_none() || !setup_path.join("staging").is_dir() {
+            anyhow::bail!("Instance does not have a pending modpack change");
+        }
+
+        tokio::fs::remove_dir_all(&setup_path).await?;
+
+        self.app
+            .invalidate(GET_MODPACK_UPDATE_PLAN, Some(instance_id.0.into()));
+
+        Ok(())
+    }
+
+    async fn setup_path(self, instance_id: InstanceId) -> anyhow::Result<PathBuf> {
+        let instances = self.instances.read().await;
+        let instance = instances
+            .get(&instance_id)
+            .ok_or(InvalidInstanceIdError(instance_id))?;
+
+        Ok(self
+            .app
+            .settings_manager()
+            .runtime_path
+            .get_instances()
+            .get_instance_path(&instance.shortpath)
+            .get_root()
+            .join(".setup"))
+    }
+
+    async fn stage_modpack_change(
+        self,
+        instance_id: InstanceId,
+        modpack: Modpack,
+        preview: bool,
+    ) -> anyhow::Result<()> {
         let instances = self.instances.read().await;
         let instance = instances
             .get(&instance_id)
@@ -173,15 +302,15 @@ impl ManagerRef<'_, InstanceManager> {
             .write_file_atomic(update_file_path, pack_version_text)
             .await?;
 
-        self.app
-            .instance_manager()
-            .prepare_game(instance_id, None, None, true)
-            .await
-            .map(|r| r.1)
+        if preview {
+            plan::mark_preview(&setup_path).await?;
+        }
+
+        Ok(())
     }
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, PartialEq, Eq)]
 #[serde(tag = "platform")]
 pub enum PackVersionFile {
     Curseforge {
@@ -0,0 +1,575 @@
+//! Three-way comparison between the pack files recorded in `packinfo.json`,
+//! the files currently in the instance and the staged new version of the pack.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use md5::{Digest, Md5};
+use serde::{Deserialize, Serialize};
+use tracing::trace;
+
+use super::packinfo;
+use crate::managers::metadata::mods::{parse_metadata, ModFileMetadata};
+
+/// Marks a staged update that waits for its plan to be confirmed.
+const PREVIEW_MARKER: &str = "update-preview";
+const PLAN_FILE: &str = "update-plan.json";
+const OVERRIDES_FILE: &str = "update-overrides.json";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FileOverride {
+    /// Apply the pack's version of the file, even over changes made by the user.
+    TakePack,
+    /// Leave the file in the instance as it is.
+    KeepMine,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SkipReason {
+    DeletedByUser,
+    ModifiedByUser {
+        original_md5: String,
+        current_md5: String,
+    },
+    /// A file not tracked by the pack is where a new pack file would go.
+    CreatedByUser,
+    InSaveFolder,
+    KeptByUser,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlannedFile {
+    /// Path of the file in the pack, as in `packinfo.json`.
+    pub path: String,
+    /// The file in the instance, relative to the instance root.
+    pub target: PathBuf,
+    /// The staged new version, relative to the instance root.
+    pub staged: Option<PathBuf>,
+    /// Planned because of a [`FileOverride::TakePack`].
+    pub overridden: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SkippedFile {
+    pub path: String,
+    pub reason: SkipReason,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModVersionChange {
+    pub mod_id: String,
+    pub name: Option<String>,
+    pub old_version: Option<String>,
+    pub new_version: Option<String>,
+    pub old_path: String,
+    pub new_path: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModpackUpdatePlan {
+    pub added: Vec<PlannedFile>,
+    pub removed: Vec<PlannedFile>,
+    pub replaced: Vec<PlannedFile>,
+    pub skipped: Vec<SkippedFile>,
+    pub mod_changes: Vec<ModVersionChange>,
+}
+
+impl ModpackUpdatePlan {
+    pub fn audit(&self) -> String {
+        let mut audit_txt = "GDLauncher Modpack Install/Update Audit\n".to_string();
+
+        if !self.skipped.is_empty() {
+            audit_txt += "\nFiles that could not be replaced:\n";
+
+            for SkippedFile { path, reason } in &self.skipped {
+                match reason {
+                    SkipReason::DeletedByUser => audit_txt += &format!(" - {path}: deleted by user\n"),
+                    SkipReason::ModifiedByUser { original_md5, current_md5 } => audit_txt += &format!(
+                        " - {path}: modified by user\n     original md5: {original_md5}\n     current md5:  {current_md5}\n",
+                    ),
+                    SkipReason::CreatedByUser => audit_txt += &format!(" - {path}: created by user\n"),
+                    SkipReason::InSaveFolder => audit_txt += &format!(" - {path}: files in /saves will never be modified\n"),
+                    SkipReason::KeptByUser => audit_txt += &format!(" - {path}: kept by user\n"),
+                }
+            }
+        }
+
+        for (title, files) in [
+            ("Files deleted", &self.removed),
+            ("Files replaced", &self.replaced),
+            ("Files created", &self.added),
+        ] {
+            if files.is_empty() {
+                continue;
+            }
+
+            audit_txt += &format!("\n{title}:\n");
+
+            for file in files {
+                match file.overridden {
+                    true => audit_txt += &format!(" - {} (overriding user changes)\n", file.path),
+                    false => audit_txt += &format!(" - {}\n", file.path),
+                }
+            }
+        }
+
+        audit_txt
+    }
+}
+
+/// Compares the instance against the staged pack. Files the user has changed
+/// are skipped unless `overrides` says otherwise.
+pub async fn plan_update(
+    instance_root: &Path,
+    overrides: &HashMap<String, FileOverride>,
+) -> anyhow::Result<ModpackUpdatePlan> {
+    let setup_path = instance_root.join(".setup");
+    let staging_dir = setup_path.join("staging");
+
+    let staged_text = tokio::fs::read_to_string(setup_path.join("staging-packinfo.json")).await?;
+    let staging_snapshot = serde_json::from_str::<HashSet<String>>(&staged_text)
+        .context("could not parse staging snapshot")?;
+
+    let packinfo = match tokio::fs::read_to_string(instance_root.join("packinfo.json")).await {
+        Ok(text) => Some(packinfo::parse_packinfo(&text).context("while parsing packinfo json")?),
+        Err(_) => None,
+    };
+
+    let relative = |path: &Path| -> anyhow::Result<PathBuf> {
+        Ok(path.strip_prefix(instance_root)?.to_path_buf())
+    };
+
+    let mut plan = ModpackUpdatePlan::default();
+    // staged files of tracked pack files, already decided on below
+    let mut tracked = HashSet::<PathBuf>::new();
+
+    for (oldfile, oldfilehash) in packinfo.iter().flat_map(|packinfo| &packinfo.files) {
+        let file_override = overrides.get(oldfile).copied();
+        let take_pack = file_override == Some(FileOverride::TakePack);
+
+        let enabled_file = instance_root.join("instance").join(&oldfile[1..]);
+        let staged_file = staging_dir.join("instance").join(&oldfile[1..]);
+        let staged = staged_file
+            .is_file()
+            .then(|| relative(&staged_file))
+            .transpose()?;
+        tracked.insert(staged_file);
+
+        trace!("Checking for replacement for packinfo file: {enabled_file:?}");
+
+        let mut original_file = enabled_file.clone();
+        if !original_file.exists() {
+            let mut name = original_file.file_name().unwrap().to_owned();
+            name.push(".disabled");
+            original_file.set_file_name(name);
+        }
+
+        if !original_file.exists() {
+            match (take_pack, staged) {
+                (true, Some(staged)) => plan.added.push(PlannedFile {
+                    path: oldfile.clone(),
+                    target: relative(&enabled_file)?,
+                    staged: Some(staged),
+                    overridden: true,
+                }),
+                _ => plan.skipped.push(SkippedFile {
+                    path: oldfile.clone(),
+                    reason: SkipReason::DeletedByUser,
+                }),
+            }
+
+            continue;
+        }
+
+        let mut original_md5 = Md5::new();
+        let mut file = tokio::fs::File::open(&original_file).await?;
+        carbon_scheduler::buffered_digest(&mut file, |chunk| {
+            original_md5.update(chunk);
+        })
+        .await?;
+        drop(file);
+        let original_md5: [u8; 16] = original_md5.finalize().into();
+
+        let modified_by_user = SkipReason::ModifiedByUser {
+            original_md5: hex::encode(oldfilehash.md5),
+            current_md5: hex::encode(original_md5),
+        };
+        let overridden = original_md5 != oldfilehash.md5;
+
+        if overridden && !take_pack {
+            // the user has modified this file so we shouldn't touch it
+            plan.skipped.push(SkippedFile {
+                path: oldfile.clone(),
+                reason: modified_by_user,
+            });
+            continue;
+        }
+
+        let planned = PlannedFile {
+            path: oldfile.clone(),
+            target: relative(&original_file)?,
+            staged,
+            overridden,
+        };
+
+        let skip_reason = match file_override {
+            Some(FileOverride::KeepMine) => SkipReason::KeptByUser,
+            _ => modified_by_user,
+        };
+
+        if !staging_snapshot.contains(oldfile) {
+            if oldfile.starts_with("/saves") {
+                plan.skipped.push(SkippedFile {
+                    path: oldfile.clone(),
+                    reason: SkipReason::InSaveFolder,
+                });
+            } else if file_override == Some(FileOverride::KeepMine) {
+                plan.skipped.push(SkippedFile {
+                    path: oldfile.clone(),
+                    reason: skip_reason,
+                });
+            } else {
+                // not present in the new version, delete
+                plan.removed.push(planned);
+            }
+        } else if planned.staged.is_some() && file_override != Some(FileOverride::KeepMine) {
+            // a new version was staged for a file that matches the old version, replace
+            plan.replaced.push(planned);
+        } else if planned.staged.is_some() || overridden {
+            // kept by the user, or unchanged in the pack and so not staged to restore
+            plan.skipped.push(SkippedFile {
+                path: oldfile.clone(),
+                reason: skip_reason,
+            });
+        }
+    }
+
+    for entry in walkdir::WalkDir::new(&staging_dir) {
+        let entry = entry?;
+
+        if !entry.file_type().is_file() || tracked.contains(entry.path()) {
+            continue;
+        }
+
+        let relpath = entry.path().strip_prefix(&staging_dir)?;
+        let original_file = instance_root.join(relpath);
+        let path = pack_path(relpath);
+        let file_override = overrides.get(&path).copied();
+
+        let planned = PlannedFile {
+            path: path.clone(),
+            target: relative(&original_file)?,
+            staged: Some(relative(entry.path())?),
+            overridden: original_file.exists(),
+        };
+
+        match (original_file.exists(), file_override) {
+            (true, Some(FileOverride::TakePack)) => plan.replaced.push(planned),
+            (true, _) => plan.skipped.push(SkippedFile {
+                path,
+                reason: SkipReason::CreatedByUser,
+            }),
+            (false, Some(FileOverride::KeepMine)) => plan.skipped.push(SkippedFile {
+                path,
+                reason: SkipReason::KeptByUser,
+            }),
+            (false, _) => plan.added.push(planned),
+        }
+    }
+
+    plan.added.sort_by(|a, b| a.path.cmp(&b.path));
+    plan.removed.sort_by(|a, b| a.path.cmp(&b.path));
+    plan.replaced.sort_by(|a, b| a.path.cmp(&b.path));
+    plan.skipped.sort_by(|a, b| a.path.cmp(&b.path));
+    plan.mod_changes = mod_changes(instance_root, &plan).await?;
+
+    Ok(plan)
+}
+
+/// `instance/mods/a.jar` in the staging folder is `/mods/a.jar` in the pack.
+fn pack_path(relpath: &Path) -> String {
+    let relpath = relpath.strip_prefix("instance").unwrap_or(relpath);
+    format!("/{}", relpath.to_string_lossy().replace('\\', "/"))
+}
+
+fn is_mod(file: &&PlannedFile) -> bool {
+    file.path.starts_with("/mods/")
+}
+
+/// Mods replaced by a different version of themselves, matched by mod id.
+async fn mod_changes(
+    instance_root: &Path,
+    plan: &ModpackUpdatePlan,
+) -> anyhow::Result<Vec<ModVersionChange>> {
+    let old_mods = plan
+        .removed
+        .iter()
+        .chain(&plan.replaced)
+        .filter(is_mod)
+        .map(|file| (file.path.clone(), instance_root.join(&file.target)))
+        .collect::<Vec<_>>();
+
+    let new_mods = plan
+        .added
+        .iter()
+        .chain(&plan.replaced)
+        .filter(is_mod)
+        .filter_map(|file| Some((file.path.clone(), instance_root.join(file.staged.as_ref()?))))
+        .collect::<Vec<_>>();
+
+    let changes = tokio::task::spawn_blocking(move || {
+        let read = |mods: Vec<(String, PathBuf)>| {
+            mods.into_iter()
+                .filter_map(|(path, file)| {
+                    let mut file = std::fs::File::open(file).ok()?;
+                    let metadata = parse_metadata(&mut file).ok()??;
+                    Some((metadata.modid.clone()?, (path, metadata)))
+                })
+                .collect::<HashMap<String, (String, ModFileMetadata)>>()
+        };
+
+        let old_mods = read(old_mods);
+
+        let mut changes = read(new_mods)
+            .into_iter()
+            .filter_map(|(mod_id, (new_path, new))| {
+                let (old_path, old) = old_mods.get(&mod_id)?;
+
+                (old.version != new.version).then(|| ModVersionChange {
+                    mod_id,
+                    name: new.name,
+                    old_version: old.version.clone(),
+                    new_version: new.version,
+                    old_path: old_path.clone(),
+                    new_path,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        changes.sort_by(|a, b| a.mod_id.cmp(&b.mod_id));
+        changes
+    })
+    .await?;
+
+    Ok(changes)
+}
+
+pub fn is_preview(setup_path: &Path) -> bool {
+    setup_path.join(PREVIEW_MARKER).exists()
+}
+
+pub async fn mark_preview(setup_path: &Path) -> anyhow::Result<()> {
+    tokio::fs::write(setup_path.join(PREVIEW_MARKER), "").await?;
+    Ok(())
+}
+
+pub async fn clear_preview(setup_path: &Path) -> anyhow::Result<()> {
+    tokio::fs::remove_file(setup_path.join(PREVIEW_MARKER)).await?;
+    Ok(())
+}
+
+pub async fn write_overrides(
+    setup_path: &Path,
+    overrides: &HashMap<String, FileOverride>,
+) -> anyhow::Result<()> {
+    tokio::fs::write(
+        setup_path.join(OVERRIDES_FILE),
+        serde_json::to_string_pretty(overrides)?,
+    )
+    .await?;
+    Ok(())
+}
+
+pub async fn read_plan(setup_path: &Path) -> anyhow::Result<Option<ModpackUpdatePlan>> {
+    match tokio::fs::read_to_string(setup_path.join(PLAN_FILE)).await {
+        Ok(text) => Ok(Some(
+            serde_json::from_str(&text).context("could not parse update plan")?,
+        )),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub async fn write_plan(setup_path: &Path, plan: &ModpackUpdatePlan) -> anyhow::Result<()> {
+    tokio::fs::write(
+        setup_path.join(PLAN_FILE),
+        serde_json::to_string_pretty(plan)?,
+    )
+    .await?;
+    Ok(())
+}
+
+pub async fn read_overrides(setup_path: &Path) -> anyhow::Result<HashMap<String, FileOverride>> {
+    match tokio::fs::read_to_string(setup_path.join(OVERRIDES_FILE)).await {
+        Ok(text) => serde_json::from_str(&text).context("could not parse update overrides"),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(HashMap::new()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::managers::instance::modpack::packinfo::{FileHashes, PackInfo};
+
+    fn write(root: &Path, path: &str, contents: &str) {
+        let path = root.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents).unwrap();
+    }
+
+    /// Writes the old version of the pack, as installed, and stages the new one.
+    fn setup(root: &Path) {
+        let old_files = [
+            ("/config/unchanged.cfg", "same"),
+            ("/config/updated.cfg", "old"),
+            ("/config/edited.cfg", "old"),
+            ("/config/removed.cfg", "old"),
+            ("/config/edited-removed.cfg", "old"),
+            ("/config/deleted.cfg", "old"),
+            ("/saves/world/level.dat", "old"),
+        ];
+
+        let packinfo = PackInfo {
+            files: old_files
+                .iter()
+                .map(|(path, contents)| {
+                    let hashes = FileHashes {
+                        sha512: [0; 64],
+                        md5: Md5::digest(contents).into(),
+                    };
+
+                    (path.to_string(), hashes)
+                })
+                .collect(),
+        };
+
+        write(
+            root,
+            "packinfo.json",
+            &packinfo::make_packinfo(packinfo).unwrap(),
+        );
+
+        for (path, contents) in old_files {
+            write(root, &format!("instance{path}"), contents);
+        }
+
+        write(root, "instance/config/edited.cfg", "mine");
+        write(root, "instance/config/edited-removed.cfg", "mine");
+        write(root, "instance/config/untracked.cfg", "mine");
+        std::fs::remove_file(root.join("instance/config/deleted.cfg")).unwrap();
+
+        let new_files = [
+            "/config/updated.cfg",
+            "/config/edited.cfg",
+            "/config/deleted.cfg",
+            "/config/untracked.cfg",
+            "/config/added.cfg",
+        ];
+
+        for path in new_files {
+            write(root, &format!(".setup/staging/instance{path}"), "new");
+        }
+
+        let mut snapshot = new_files.to_vec();
+        // unchanged files are not staged again
+        snapshot.push("/config/unchanged.cfg");
+        write(
+            root,
+            ".setup/staging-packinfo.json",
+            &serde_json::to_string(&snapshot).unwrap(),
+        );
+    }
+
+    fn paths(files: &[PlannedFile]) -> Vec<(&str, bool)> {
+        files
+            .iter()
+            .map(|file| (&file.path as &str, file.overridden))
+            .collect()
+    }
+
+    fn reasons(plan: &ModpackUpdatePlan) -> Vec<(&str, &SkipReason)> {
+        plan.skipped
+            .iter()
+            .map(|file| (&file.path as &str, &file.reason))
+            .collect()
+    }
+
+    fn edited() -> SkipReason {
+        SkipReason::ModifiedByUser {
+            original_md5: hex::encode(Md5::digest("old")),
+            current_md5: hex::encode(Md5::digest("mine")),
+        }
+    }
+
+    #[tokio::test]
+    async fn plan_without_overrides() {
+        let dir = tempdir::TempDir::new("carbon_plan_test").unwrap();
+        setup(dir.path());
+
+        let plan = plan_update(dir.path(), &HashMap::new()).await.unwrap();
+
+        assert_eq!(paths(&plan.added), [("/config/added.cfg", false)]);
+        assert_eq!(paths(&plan.removed), [("/config/removed.cfg", false)]);
+        assert_eq!(paths(&plan.replaced), [("/config/updated.cfg", false)]);
+        assert_eq!(
+            reasons(&plan),
+            [
+                ("/config/deleted.cfg", &SkipReason::DeletedByUser),
+                ("/config/edited-removed.cfg", &edited()),
+                ("/config/edited.cfg", &edited()),
+                ("/config/untracked.cfg", &SkipReason::CreatedByUser),
+                ("/saves/world/level.dat", &SkipReason::InSaveFolder),
+            ]
+        );
+
+        let replaced = &plan.replaced[0];
+        assert_eq!(replaced.target, Path::new("instance/config/updated.cfg"));
+        assert_eq!(
+            replaced.staged.as_deref(),
+            Some(Path::new(".setup/staging/instance/config/updated.cfg"))
+        );
+    }
+
+    #[tokio::test]
+    async fn plan_with_overrides() {
+        let dir = tempdir::TempDir::new("carbon_plan_test").unwrap();
+        setup(dir.path());
+
+        let overrides = [
+            ("/config/edited.cfg", FileOverride::TakePack),
+            ("/config/edited-removed.cfg", FileOverride::TakePack),
+            ("/config/deleted.cfg", FileOverride::TakePack),
+            ("/config/untracked.cfg", FileOverride::TakePack),
+            ("/config/updated.cfg", FileOverride::KeepMine),
+            ("/config/removed.cfg", FileOverride::KeepMine),
+            ("/config/added.cfg", FileOverride::KeepMine),
+            ("/saves/world/level.dat", FileOverride::TakePack),
+        ]
+        .into_iter()
+        .map(|(path, file_override)| (path.to_string(), file_override))
+        .collect();
+
+        let plan = plan_update(dir.path(), &overrides).await.unwrap();
+
+        assert_eq!(paths(&plan.added), [("/config/deleted.cfg", true)]);
+        assert_eq!(paths(&plan.removed), [("/config/edited-removed.cfg", true)]);
+        assert_eq!(
+            paths(&plan.replaced),
+            [
+                ("/config/edited.cfg", true),
+                ("/config/untracked.cfg", true)
+            ]
+        );
+        assert_eq!(
+            reasons(&plan),
+            [
+                ("/config/added.cfg", &SkipReason::KeptByUser),
+                ("/config/removed.cfg", &SkipReason::KeptByUser),
+                ("/config/updated.cfg", &SkipReason::KeptByUser),
+                ("/saves/world/level.dat", &SkipReason::InSaveFolder),
+            ]
+        );
+    }
+}
@@ -1,11 +1,11 @@
-use super::{
-    installer::{CurseforgeModInstaller, IntoInstaller, ModrinthModInstaller},
-    InstanceId, InstanceManager, InvalidInstanceIdError,
-};
+use super::{InstanceId, InstanceManager, InvalidInstanceIdError};
 use crate::api::keys::instance::INSTANCE_MODS;
 use crate::domain::instance::info::{GameVersion, ModLoaderType};
 use crate::domain::instance::{self as domain, info};
 use crate::managers::instance::InstanceType;
+use crate::managers::modplatforms::source::{
+    curseforge as curseforge_source, modrinth as modrinth_source, remote_version_source,
+};
 use crate::managers::AppInner;
 use crate::{domain::vtask::VisualTaskId, managers::ManagerRef};
 use anyhow::{anyhow, bail};
@@ -16,12 +16,13 @@ use carbon_platforms::curseforge::FileReleaseType;
 use carbon_platforms::modrinth::project::ProjectVersionsFilters;
 use carbon_platforms::modrinth::search::ProjectID;
 use carbon_platforms::modrinth::version::VersionType;
+use carbon_platforms::source::SourceVersionFilters;
 use carbon_platforms::{
     curseforge, modrinth, ModChannel, ModChannelWithUsage, ModPlatform, ModSources, RemoteVersion,
 };
 use carbon_repos::db::{
     curse_forge_mod_cache as cfdb, mod_file_cache as fcdb, mod_metadata as metadb,
-    modrinth_mod_cache as mrdb,
+    modrinth_mod_cache as mrdb, source_mod_cache as srcdb,
 };
 use chrono::{DateTime, FixedOffset, Utc};
 use futures::Future;
@@ -116,7 +117,8 @@ impl ManagerRef<'_, InstanceManager> {
                 fcdb::metadata::fetch()
                     .with(metadb::logo_image::fetch())
                     .with(metadb::curseforge::fetch().with(cfdb::logo_image::fetch()))
-                    .with(metadb::modrinth::fetch().with(mrdb::logo_image::fetch())),
+                    .with(metadb::modrinth::fetch().with(mrdb::logo_image::fetch()))
+                    .with(metadb::source::fetch()),
             )
             .exec()
             .await?
@@ -209,6 +211,19 @@ impl ManagerRef<'_, InstanceManager> {
                             .flatten()
                             .is_some(),
                     }),
+                    source: m
+                        .metadata
+                        .as_ref()
+                        .and_then(|m| m.source.clone())
+                        .flatten()
+                        .map(|m| domain::SourceModMetadata {
+                            source_id: m.source_id,
+                            repository: m.repository,
+                            project_id: m.project_id,
+                            version_id: m.version_id,
+                            name: m.name,
+                            version: m.version,
+                        }),
                     modrinth: m.metadata.and_then(|m| m.modrinth).flatten().map(|m| {
                         domain::ModrinthModMetadata {
                             project_id: m.project_id,
@@ -407,9 +422,16 @@ impl ManagerRef<'_, InstanceManager> {
     ) -> anyhow::Result<VisualTaskId> {
         self.ensure_modpack_not_locked(instance_id).await?;
 
-        let installer = CurseforgeModInstaller::create(self.app, project_id, file_id)
-            .await?
-            .into_installer();
+        let installer = self
+            .app
+            .modplatforms_manager()
+            .installer(
+                curseforge_source::SOURCE_ID,
+                None,
+                &project_id.to_string(),
+                &file_id.to_string(),
+            )
+            .await?;
 
         let task_id = installer
             .install(self.app, instance_id, install_deps, replaces_mod_id)
@@ -476,9 +498,16 @@ impl ManagerRef<'_, InstanceManager> {
             .id
             .try_into()?;
 
-        let installer = CurseforgeModInstaller::create(self.app, project_id, file_id)
-            .await?
-            .into_installer();
+        let installer = self
+            .app
+            .modplatforms_manager()
+            .installer(
+                curseforge_source::SOURCE_ID,
+                None,
+                &project_id.to_string(),
+                &file_id.to_string(),
+            )
+            .await?;
 
         let task_id = installer.install(self.app, instance_id, true, None).await?;
 
@@ -495,9 +524,11 @@ impl ManagerRef<'_, InstanceManager> {
     ) -> anyhow::Result<VisualTaskId> {
         self.ensure_modpack_not_locked(instance_id).await?;
 
-        let installer = ModrinthModInstaller::create(self.app, project_id, version_id)
-            .await?
-            .into_installer();
+        let installer = self
+            .app
+            .modplatforms_manager()
+            .installer(modrinth_source::SOURCE_ID, None, &project_id, &version_id)
+            .await?;
 
         let task_id = installer
             .install(self.app, instance_id, install_deps, replaces_mod_id)
@@ -557,15 +588,93 @@ impl ManagerRef<'_, InstanceManager> {
             .id
             .clone();
 
-        let installer = ModrinthModInstaller::create(self.app, project_id, version_id)
-            .await?
-            .into_installer();
+        let installer = self
+            .app
+            .modplatforms_manager()
+            .installer(modrinth_source::SOURCE_ID, None, &project_id, &version_id)
+            .await?;
 
         let task_id = installer.install(self.app, instance_id, true, None).await?;
 
         Ok(task_id)
     }
 
+    pub async fn install_source_mod(
+        &self,
+        instance_id: InstanceId,
+        source_id: String,
+        repository: Option<String>,
+        project_id: String,
+        version_id: String,
+        replaces_mod_id: Option<String>,
+    ) -> anyhow::Result<VisualTaskId> {
+        self.ensure_modpack_not_locked(instance_id).await?;
+
+        let installer = self
+            .app
+            .modplatforms_manager()
+            .installer(&source_id, repository.as_deref(), &project_id, &version_id)
+            .await?;
+
+        let task_id = installer
+            .install(self.app, instance_id, false, replaces_mod_id)
+            .await?;
+
+        Ok(task_id)
+    }
+
+    pub async fn install_latest_source_mod(
+        &self,
+        instance_id: InstanceId,
+        source_id: String,
+        repository: Option<String>,
+        project_id: String,
+    ) -> anyhow::Result<VisualTaskId> {
+        self.ensure_modpack_not_locked(instance_id).await?;
+
+        let filters = {
+            let instances = self.instances.read().await;
+            let config = &instances
+                .get(&instance_id)
+                .ok_or(InvalidInstanceIdError(instance_id))?
+                .data()?
+                .config;
+
+            source_version_filters(&config.game_configuration.version)
+        };
+
+        let mod_source = self
+            .app
+            .modplatforms_manager()
+            .mod_source(&source_id, repository.as_deref())
+            .await?;
+
+        let mut installer = None;
+
+        // listed versions are not checked, skip the ones without a usable file
+        for listed in mod_source.list_versions(&project_id, &filters).await? {
+            match mod_source.installer(&project_id, &listed.id).await {
+                Ok(resolved) => {
+                    installer = Some(resolved);
+                    break;
+                }
+                Err(e) => tracing::warn!(
+                    { error = ?e },
+                    "Skipping unresolvable version {} of {project_id}",
+                    listed.id
+                ),
+            }
+        }
+
+        let installer = installer.ok_or(anyhow!("Can't find a valid version for this instance"))?;
+
+        let task_id = installer
+            .install(self.app, instance_id, false, None)
+            .await?;
+
+        Ok(task_id)
+    }
+
     /// Attempt to find an update for a mod respecting the instance's (and the global) channel preference.
     pub async fn find_mod_update(
         self,
@@ -581,7 +690,7 @@ impl ManagerRef<'_, InstanceManager> {
         let config = data.config.clone();
         drop(instances);
 
-        let Some(GameVersion::Standard(version)) = &config.game_configuration.version else {
+        let Some(GameVersion::Standard(_)) = &config.game_configuration.version else {
             bail!("Instance uses a custom game version file. Cannot resolve minecraft version for mod installation");
         };
 
@@ -595,7 +704,8 @@ impl ManagerRef<'_, InstanceManager> {
             .with(
                 fcdb::metadata::fetch()
                     .with(metadb::curseforge::fetch())
-                    .with(metadb::modrinth::fetch()),
+                    .with(metadb::modrinth::fetch())
+                    .with(metadb::source::fetch()),
             )
             .exec()
             .await?
@@ -613,54 +723,54 @@ impl ManagerRef<'_, InstanceManager> {
             .modrinth
             .expect("modrinth metadata was queried but not returned");
 
-        let mut versions = Vec::new();
-
-        if let Some(cf) = &cf {
-            let response = self
-                .app
-                .modplatforms_manager()
-                .curseforge
-                .get_mod_files(ModFilesParameters {
-                    mod_id: cf.project_id,
-                    query: ModFilesParametersQuery {
-                        game_version: Some(version.release.clone()),
-                        game_version_type_id: None,
-                        mod_loader_type: version.modloaders.iter().next().map(|v| v.type_.into()),
-                        index: None,
-                        page_size: None,
-                    },
-                })
-                .await?;
+        let source = metadata
+            .source
+            .expect("source metadata was queried but not returned");
+
+        // source id, repository, project id and installed version id
+        let installed = [
+            cf.map(|cf| {
+                (
+                    curseforge_source::SOURCE_ID.to_string(),
+                    None,
+                    cf.project_id.to_string(),
+                    cf.file_id.to_string(),
+                )
+            }),
+            mr.map(|mr| {
+                (
+                    modrinth_source::SOURCE_ID.to_string(),
+                    None,
+                    mr.project_id,
+                    mr.version_id,
+                )
+            }),
+            source.map(|source| {
+                (
+                    source.source_id,
+                    Some(source.repository),
+                    source.project_id,
+                    source.version_id,
+                )
+            }),
+        ]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
 
-            versions.extend(
-                response
-                    .data
-                    .into_iter()
-                    .map(|f| RemoteVersion::Curseforge(f)),
-            );
-        }
+        let filters = source_version_filters(&config.game_configuration.version);
+        let mut versions = Vec::new();
 
-        if let Some(mr) = &mr {
+        for (source_id, repository, project_id, _) in &installed {
             let response = self
                 .app
                 .modplatforms_manager()
-                .modrinth
-                .get_project_versions(ProjectVersionsFilters {
-                    project_id: ProjectID(mr.project_id.clone()),
-                    game_versions: Some(vec![version.release.clone()]),
-                    loaders: Some(
-                        version
-                            .modloaders
-                            .iter()
-                            .map(|ml| ml.type_.to_string())
-                            .collect(),
-                    ),
-                    limit: None,
-                    offset: None,
-                })
+                .mod_source(source_id, repository.as_deref())
+                .await?
+                .remote_versions(project_id, &filters)
                 .await?;
 
-            versions.extend(response.into_iter().map(|v| RemoteVersion::Modrinth(v)));
+            versions.extend(response);
         }
 
         versions.sort();
@@ -675,22 +785,14 @@ impl ManagerRef<'_, InstanceManager> {
 
                 if version.channel() >= channel.channel {
                     let version = versions.remove(i);
+                    let (source_id, _) = remote_version_source(&version);
 
-                    match &version {
-                        RemoteVersion::Curseforge(file) => {
-                            let cf = cf.expect("curseforge metadata must be present if operating on a curseforge version");
-
-                            if cf.file_id == file.id {
-                                break 'select;
-                            }
-                        }
-                        RemoteVersion::Modrinth(version) => {
-                            let mr = mr.expect("modrinth metadata must be present if operating on a modrinth version");
+                    let is_installed = installed.iter().any(|(installed_source, .., id)| {
+                        installed_source == source_id && *id == version.id()
+                    });
 
-                            if mr.version_id == version.id {
-                                break 'select;
-                            }
-                        }
+                    if is_installed {
+                        break 'select;
                     }
 
                     return Ok(Some(version));
@@ -710,31 +812,18 @@ impl ManagerRef<'_, InstanceManager> {
 
         let update = self.find_mod_update(instance_id, id.clone()).await?;
 
-        match update {
-            Some(RemoteVersion::Curseforge(file)) => {
-                self.install_curseforge_mod(
-                    instance_id,
-                    file.mod_id as u32,
-                    file.id as u32,
-                    false,
-                    Some(id),
-                )
-                .await
-            }
-            Some(RemoteVersion::Modrinth(version)) => {
-                self.install_modrinth_mod(
-                    instance_id,
-                    version.project_id,
-                    version.id,
-                    false,
-                    Some(id),
-                )
-                .await
-            }
-            None => Err(anyhow!(
-                "unable to find newer mod version in availible update channels"
-            )),
-        }
+        let Some(version) = update else {
+            bail!("unable to find newer mod version in availible update channels");
+        };
+
+        let (source_id, repository) = remote_version_source(&version);
+
+        self.app
+            .modplatforms_manager()
+            .installer(source_id, repository, &version.project_id(), &version.id())
+            .await?
+            .install(self.app, instance_id, false, Some(id))
+            .await
     }
 
     pub async fn update_curseforge_mod(
@@ -940,6 +1029,20 @@ impl ManagerRef<'_, InstanceManager> {
     }
 }
 
+fn source_version_filters(version: &Option<GameVersion>) -> SourceVersionFilters {
+    match version {
+        Some(GameVersion::Standard(version)) => SourceVersionFilters {
+            game_version: Some(version.release.clone()),
+            loaders: version
+                .modloaders
+                .iter()
+                .map(|ml| ml.type_.to_string().to_lowercase())
+                .collect(),
+        },
+        _ => SourceVersionFilters::default(),
+    }
+}
+
 #[derive(Error, Debug)]
 #[error("invalid mod id '{1}' given for instance '{0}'")]
 pub struct InvalidInstanceModIdError(InstanceId, String);
@@ -14,6 +14,8 @@ use crate::managers::instance::modpack::{packinfo, PackVersionFile};
 use crate::managers::instance::schema::make_instance_config;
 use crate::managers::java::java_checker::{JavaChecker, RealJavaChecker};
 use crate::managers::java::managed::Step;
+use crate::managers::java::requirement::JavaRequirement;
+use crate::managers::metadata::mods::parse_java_dependency;
 use crate::managers::minecraft::assets::get_assets_dir;
 use crate::managers::minecraft::minecraft::get_lwjgl_meta;
 use crate::managers::minecraft::modrinth;
@@ -24,7 +26,7 @@ use crate::managers::vtask::Subtask;
 use crate::managers::AppInner;
 use crate::util::NormalizedWalkdir;
 use crate::{
-    domain::instance::info::{GameVersion, ModLoader, ModLoaderType},
+    domain::instance::info::{GameVersion, ModLoader},
     managers::{
         self,
         account::FullAccount,
@@ -59,28 +61,37 @@ pub async fn check_and_install(
     version_info: &VersionInfo,
     t_subtasks: &TSubtasks,
     version: &StandardVersion,
+    mods_path: PathBuf,
     java_override: Option<JavaOverride>,
     auto_manage_java_system_profiles: bool,
     log: &watch::Sender<GameLog>,
     mut file: Option<&mut File>,
 ) -> anyhow::Result<JavaComponent> {
-    let java_profile = daedalus::minecraft::MinecraftJavaProfile::try_from(
-        version_info
-            .java_version
-            .as_ref()
-            .ok_or_else(|| anyhow::anyhow!("Java version not provided"))?
-            .component
-            .as_str(),
+    let java_version = version_info
+        .java_version
+        .as_ref()
+        .ok_or_else(|| anyhow::anyhow!("Java version not provided"))?;
+
+    let java_profile =
+        daedalus::minecraft::MinecraftJavaProfile::try_from(java_version.component.as_str())
+            .with_context(|| anyhow!("instance java version unsupported"))?;
+
+    log_system_message(
+        log,
+        &mut file,
+        format!("Suggested Java Profile: {java_profile:?}"),
     )
-    .with_context(|| anyhow!("instance java version unsupported"))?;
+    .await?;
 
-    let msg = format!("Suggested Java Profile: {java_profile:?}");
+    let mod_dependencies = scan_mod_java_dependencies(mods_path).await;
+    let requirement = JavaRequirement::for_instance(
+        java_version.major_version as u16,
+        &version.release,
+        &version.modloaders,
+        &mod_dependencies,
+    );
 
-    if let Some(ref mut file) = file {
-        log.send_modify(|log| log.add_entry(LogEntry::system_message(msg.clone())));
-        file.write_all(format_message_as_log4j_event(&msg).as_bytes())
-            .await?;
-    }
+    log_system_message(log, &mut file, format!("Java requirement: {requirement}")).await?;
 
     let mut required_java_system_profile = SystemJavaProfileName::try_from(java_profile)
         .with_context(|| anyhow!("System java version unsupported"))?;
@@ -174,19 +185,20 @@ pub async fn check_and_install(
 
     let java = {
         if let Some(java_component_override) = java_component_override {
-            java_component_override?
-        } else {
-            // Forge 1.16.5 requires an older java 8 version so we inject the legacy fixed 1 profile
-            if &version.release == "1.16.5"
-                && *&version
-                    .modloaders
-                    .iter()
-                    .find(|v| v.type_ == ModLoaderType::Forge)
-                    .is_some()
-            {
-                required_java_system_profile = SystemJavaProfileName::LegacyFixed1;
+            let java = java_component_override?;
+
+            // the user picked this java, only warn about it
+            if let Err(reason) = requirement.check(&java.version) {
+                log_system_message(
+                    log,
+                    &mut file,
+                    format!("Java override may not work with this instance: {reason}"),
+                )
+                .await?;
             }
 
+            java
+        } else {
             let instance_manager = app.instance_manager();
             let _guard = instance_manager
                 .persistence_manager
@@ -194,90 +206,165 @@ pub async fn check_and_install(
                 .lock()
                 .await;
 
-            let usable_java = app
-                .java_manager()
-                .get_usable_java_for_profile_name(required_java_system_profile)
-                .await?;
+            // Forge 1.16.5 requires an older java 8 version so we use the legacy fixed 1 profile
+            if requirement.java8_update_cap.is_some()
+                && required_java_system_profile == SystemJavaProfileName::Legacy
+            {
+                required_java_system_profile = SystemJavaProfileName::LegacyFixed1;
+            }
 
-            tracing::debug!("Usable java: {:?}", usable_java);
+            if auto_manage_java_system_profiles {
+                let (progress_watch_tx, mut progress_watch_rx) = watch::channel(Step::Idle);
 
-            match usable_java {
-                Some(path) => path,
-                None => {
-                    if !auto_manage_java_system_profiles {
-                        bail!("No usable java found and auto manage java is disabled");
-                    }
+                let t_subtasks_clone = Arc::clone(t_subtasks);
 
-                    let (progress_watch_tx, mut progress_watch_rx) = watch::channel(Step::Idle);
+                // dropped when the sender is dropped
+                let completion = tokio::spawn(async move {
+                    let mut started = false;
+                    let mut dl_completed = false;
 
-                    let t_subtasks_clone = Arc::clone(t_subtasks);
+                    while progress_watch_rx.changed().await.is_ok() {
+                        let step = progress_watch_rx.borrow();
 
-                    // dropped when the sender is dropped
-                    let completion = tokio::spawn(async move {
-                        let mut started = false;
-                        let mut dl_completed = false;
+                        if !started && !matches!(*step, Step::Idle) {
+                            started = true;
+                        }
 
-                        while progress_watch_rx.changed().await.is_ok() {
-                            let step = progress_watch_rx.borrow();
+                        match *step {
+                            Step::Downloading(downloaded, total) => t_subtasks_clone
+                                .t_download_java
+                                .update_download(downloaded as u32, total as u32, true),
+                            Step::Extracting(count, total) => {
+                                if !dl_completed {
+                                    t_subtasks_clone.t_download_java.complete_opaque();
+                                    dl_completed = true;
+                                }
 
-                            if !started && !matches!(*step, Step::Idle) {
-                                started = true;
+                                t_subtasks_clone
+                                    .t_extract_java
+                                    .update_items(count as u32, total as u32);
                             }
 
-                            match *step {
-                                Step::Downloading(downloaded, total) => t_subtasks_clone
-                                    .t_download_java
-                                    .update_download(downloaded as u32, total as u32, true),
-                                Step::Extracting(count, total) => {
-                                    if !dl_completed {
-                                        t_subtasks_clone.t_download_java.complete_opaque();
-                                        dl_completed = true;
-                                    }
+                            Step::Done => {
+                                t_subtasks_clone.t_download_java.complete_opaque();
+                                t_subtasks_clone.t_extract_java.complete_opaque();
+                            }
 
-                                    t_subtasks_clone
-                                        .t_extract_java
-                                        .update_items(count as u32, total as u32);
-                                }
+                            Step::Idle => {}
+                        }
 
-                                Step::Done => {
-                                    t_subtasks_clone.t_download_java.complete_opaque();
-                                    t_subtasks_clone.t_extract_java.complete_opaque();
-                                }
+                        // this is already debounced in setup_managed
+                    }
+                });
 
-                                Step::Idle => {}
-                            }
+                let resolution = app
+                    .java_manager()
+                    .resolve_java(
+                        &requirement,
+                        true,
+                        Some(required_java_system_profile),
+                        Some(progress_watch_tx),
+                    )
+                    .await;
 
-                            // this is already debounced in setup_managed
-                        }
-                    });
-
-                    let path = app
-                        .java_manager()
-                        .require_java_install(
-                            required_java_system_profile,
-                            true,
-                            Some(progress_watch_tx),
-                        )
-                        .await?;
+                completion.await?;
 
-                    completion.await?;
+                let resolution = resolution?;
 
-                    match path {
-                        Some(path) => path,
-                        None => bail!("No usable java found after installation attempt"),
-                    }
+                tracing::debug!("Java resolution: {:?}", resolution);
+
+                for rejected in &resolution.rejected {
+                    let version = rejected
+                        .version
+                        .as_ref()
+                        .map(ToString::to_string)
+                        .unwrap_or_else(|| String::from("unknown version"));
+
+                    log_system_message(
+                        log,
+                        &mut file,
+                        format!(
+                            "Skipped Java {version} at {}: {}",
+                            rejected.path, rejected.reason
+                        ),
+                    )
+                    .await?;
                 }
+
+                match resolution.java {
+                    Some(java) => java,
+                    None => bail!("No usable java found after installation attempt"),
+                }
+            } else {
+                let usable_java = app
+                    .java_manager()
+                    .get_usable_java_for_profile_name(required_java_system_profile)
+                    .await?;
+
+                tracing::debug!("Usable java: {:?}", usable_java);
+
+                let Some(java) = usable_java else {
+                    bail!("No usable java found and auto manage java is disabled");
+                };
+
+                if let Err(reason) = requirement.check(&java.version) {
+                    bail!(
+                        "Java of profile {} can't run this instance and auto manage java is disabled: {reason}",
+                        required_java_system_profile.to_string()
+                    );
+                }
+
+                java
             }
         }
     };
 
-    let msg = format!("Using Java: {java:#?}");
+    log_system_message(log, &mut file, format!("Using Java: {java:#?}")).await?;
 
+    Ok(java)
+}
+
+async fn log_system_message(
+    log: &watch::Sender<GameLog>,
+    file: &mut Option<&mut File>,
+    msg: String,
+) -> anyhow::Result<()> {
     if let Some(file) = file {
         log.send_modify(|log| log.add_entry(LogEntry::system_message(msg.clone())));
         file.write_all(format_message_as_log4j_event(&msg).as_bytes())
             .await?;
     }
 
-    Ok(java)
+    Ok(())
+}
+
+/// `depends.java` of the enabled fabric and quilt mods, by file name
+async fn scan_mod_java_dependencies(mods_path: PathBuf) -> Vec<(String, Vec<String>)> {
+    let scan = tokio::task::spawn_blocking(move || {
+        let Ok(entries) = std::fs::read_dir(&mods_path) else {
+            return Vec::new();
+        };
+
+        entries
+            .flatten()
+            .filter_map(|entry| {
+                let file_name = entry.file_name().to_string_lossy().to_string();
+                if !file_name.ends_with(".jar") {
+                    return None;
+                }
+
+                let mut file = std::fs::File::open(entry.path()).ok()?;
+
+                match parse_java_dependency(&mut file) {
+                    Ok(ranges) => ranges.map(|ranges| (file_name, ranges)),
+                    Err(e) => {
+                        debug!("Could not read java dependency of {file_name}: {e}");
+                        None
+                    }
+                }
+            })
+            .collect()
+    });
+
+    scan.await.unwrap_or_default()
 }
@@ -0,0 +1,619 @@
+//! Crash-safe application of a staged modpack to the instance folder.
+//!
+//! Every change is planned and written to a journal before the instance is
+//! touched. Files are only ever renamed, and files leaving the instance are
+//! kept in a backup folder until the journal is done with, so an interrupted
+//! apply can always be finished or undone on the next launch.
+
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use tracing::{info, trace, warn};
+
+const JOURNAL_FILE: &str = "staging-journal.json";
+const BACKUP_DIR: &str = "staging-backup";
+
+/// Called before every change to the filesystem, failing stops the apply.
+pub type Checkpoint<'a> = &'a mut dyn FnMut() -> io::Result<()>;
+
+/// A single change to the instance. Paths are relative to the instance root.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "camelCase")]
+pub enum JournalOp {
+    /// Removes a file that is no longer part of the pack.
+    Delete { target: PathBuf },
+    /// Replaces a file with its staged new version.
+    Replace { staged: PathBuf, target: PathBuf },
+    /// Adds a staged file that does not exist in the instance yet.
+    Create { staged: PathBuf, target: PathBuf },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum JournalState {
+    /// Changes may be partially applied.
+    Planned,
+    /// All changes are applied, only cleanup is left.
+    Applied,
+    /// All changes are undone, only cleanup is left.
+    RolledBack,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Recovery {
+    RolledForward,
+    RolledBack,
+    /// The journal was unreadable, the staged files were discarded.
+    Discarded,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StagingJournal {
+    pub state: JournalState,
+    pub ops: Vec<JournalOp>,
+    /// Contents of the install audit written once the changes are applied.
+    pub audit: String,
+}
+
+// Each op is idempotent: its progress is read back from which of the staged,
+// target and backup files exist, so it can be resumed or undone from any point.
+impl JournalOp {
+    fn is_applicable(&self, root: &Path, backup: &Path) -> bool {
+        match self {
+            Self::Delete { .. } => true,
+            Self::Replace { staged, target } => {
+                root.join(staged).exists() || (root.join(target).exists() && backup.exists())
+            }
+            Self::Create { staged, target } => {
+                root.join(staged).exists() || root.join(target).exists()
+            }
+        }
+    }
+
+    fn apply(&self, root: &Path, backup: &Path, checkpoint: Checkpoint<'_>) -> io::Result<()> {
+        match self {
+            Self::Delete { target } => {
+                let target = root.join(target);
+                if target.exists() && !backup.exists() {
+                    rename(&target, backup, checkpoint)?;
+                }
+            }
+            Self::Replace { staged, target } => {
+                let (staged, target) = (root.join(staged), root.join(target));
+                if target.exists() && !backup.exists() {
+                    rename(&target, backup, checkpoint)?;
+                }
+                if staged.exists() {
+                    rename(&staged, &target, checkpoint)?;
+                }
+            }
+            Self::Create { staged, target } => {
+                let staged = root.join(staged);
+                if staged.exists() {
+                    rename(&staged, &root.join(target), checkpoint)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn undo(&self, root: &Path, backup: &Path, checkpoint: Checkpoint<'_>) -> io::Result<()> {
+        match self {
+            Self::Delete { target } => {
+                if backup.exists() {
+                    rename(backup, &root.join(target), checkpoint)?;
+                }
+            }
+            Self::Replace { staged, target } => {
+                let (staged, target) = (root.join(staged), root.join(target));
+                if !staged.exists() && target.exists() && backup.exists() {
+                    rename(&target, &staged, checkpoint)?;
+                }
+                if backup.exists() {
+                    rename(backup, &target, checkpoint)?;
+                }
+            }
+            Self::Create { staged, target } => {
+                let (staged, target) = (root.join(staged), root.join(target));
+                if !staged.exists() && target.exists() {
+                    rename(&target, &staged, checkpoint)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl StagingJournal {
+    pub fn new(ops: Vec<JournalOp>, audit: String) -> Self {
+        Self {
+            state: JournalState::Planned,
+            ops,
+            audit,
+        }
+    }
+
+    fn path(instance_root: &Path) -> PathBuf {
+        instance_root.join(".setup").join(JOURNAL_FILE)
+    }
+
+    fn backup_path(instance_root: &Path, index: usize) -> PathBuf {
+        instance_root
+            .join(".setup")
+            .join(BACKUP_DIR)
+            .join(index.to_string())
+    }
+
+    pub fn read(instance_root: &Path) -> anyhow::Result<Option<Self>> {
+        let text = match fs::read_to_string(Self::path(instance_root)) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e).context("could not read staging journal"),
+        };
+
+        serde_json::from_str(&text)
+            .map(Some)
+            .context("could not parse staging journal")
+    }
+
+    fn write(&self, instance_root: &Path, checkpoint: Checkpoint<'_>) -> anyhow::Result<()> {
+        let path = Self::path(instance_root);
+        let tmp_path = path.with_extension("json.tmp");
+
+        checkpoint()?;
+        let mut file = fs::File::create(&tmp_path)?;
+        file.write_all(serde_json::to_string_pretty(self)?.as_bytes())?;
+        // the journal must be complete on disk before it replaces the old one
+        file.sync_all()?;
+        drop(file);
+
+        rename(&tmp_path, &path, checkpoint)?;
+        sync_dir(path.parent().expect("journal is in the setup folder"))?;
+
+        Ok(())
+    }
+
+    /// Records the planned changes. Nothing in the instance is touched before this.
+    pub fn begin(&self, instance_root: &Path, checkpoint: Checkpoint<'_>) -> anyhow::Result<()> {
+        // left over from an apply interrupted before its journal was written
+        remove_dir(&instance_root.join(".setup").join(BACKUP_DIR), checkpoint)?;
+
+        self.write(instance_root, checkpoint)
+            .context("could not write staging journal")
+    }
+
+    pub fn roll_forward(
+        mut self,
+        instance_root: &Path,
+        checkpoint: Checkpoint<'_>,
+    ) -> anyhow::Result<()> {
+        if self.state == JournalState::Planned {
+            checkpoint()?;
+            fs::create_dir_all(instance_root.join(".setup").join(BACKUP_DIR))?;
+
+            for (index, op) in self.ops.iter().enumerate() {
+                trace!("Applying {op:?}");
+                let backup = Self::backup_path(instance_root, index);
+
+                if let Some(target) = op_target(op) {
+                    checkpoint()?;
+                    fs::create_dir_all(instance_root.join(target).parent().unwrap())?;
+                }
+
+                op.apply(instance_root, &backup, checkpoint)
+                    .with_context(|| format!("could not apply {op:?}"))?;
+            }
+
+            self.state = JournalState::Applied;
+            self.write(instance_root, checkpoint)?;
+        }
+
+        let setup_path = instance_root.join(".setup");
+
+        let audit_dir = instance_root.join(".install_audit");
+        remove_dir(&audit_dir, checkpoint)?;
+        checkpoint()?;
+        fs::create_dir(&audit_dir)?;
+        checkpoint()?;
+        fs::write(audit_dir.join("audit.txt"), &self.audit)?;
+
+        let tmp_packinfo = instance_root.join("tmp-packinfo.json");
+        if tmp_packinfo.exists() {
+            rename(
+                &tmp_packinfo,
+                &instance_root.join("packinfo.json"),
+                checkpoint,
+            )?;
+        }
+
+        checkpoint()?;
+        fs::write(setup_path.join("modpack-complete"), "")?;
+
+        remove_dir(&setup_path.join("staging"), checkpoint)?;
+        remove_dir(&setup_path.join(BACKUP_DIR), checkpoint)?;
+        remove_file(&Self::path(instance_root), checkpoint)?;
+
+        Ok(())
+    }
+
+    /// Undoes any applied changes and discards the staged files, so the modpack
+    /// is staged again from scratch.
+    pub fn roll_back(
+        mut self,
+        instance_root: &Path,
+        checkpoint: Checkpoint<'_>,
+    ) -> anyhow::Result<()> {
+        if self.state == JournalState::Planned {
+            for (index, op) in self.ops.iter().enumerate().rev() {
+                trace!("Undoing {op:?}");
+                let backup = Self::backup_path(instance_root, index);
+
+                if let Some(staged) = op_staged(op) {
+                    checkpoint()?;
+                    fs::create_dir_all(instance_root.join(staged).parent().unwrap())?;
+                }
+
+                op.undo(instance_root, &backup, checkpoint)
+                    .with_context(|| format!("could not undo {op:?}"))?;
+            }
+
+            self.state = JournalState::RolledBack;
+            self.write(instance_root, checkpoint)?;
+        }
+
+        Self::discard(instance_root, checkpoint)
+    }
+
+    /// Removes the staged files and the journal.
+    fn discard(instance_root: &Path, checkpoint: Checkpoint<'_>) -> anyhow::Result<()> {
+        let setup_path = instance_root.join(".setup");
+
+        remove_dir(&setup_path.join("staging"), checkpoint)?;
+        remove_file(&setup_path.join("staging-packinfo.json"), checkpoint)?;
+        remove_file(&instance_root.join("tmp-packinfo.json"), checkpoint)?;
+        remove_dir(&setup_path.join(BACKUP_DIR), checkpoint)?;
+        remove_file(&Self::path(instance_root), checkpoint)?;
+
+        Ok(())
+    }
+
+    /// Finishes an interrupted apply. It is rolled forward if every remaining
+    /// change can still be made, and rolled back otherwise.
+    pub fn recover(
+        instance_root: &Path,
+        checkpoint: Checkpoint<'_>,
+    ) -> anyhow::Result<Option<Recovery>> {
+        let journal = match Self::read(instance_root) {
+            Ok(Some(journal)) => journal,
+            Ok(None) => return Ok(None),
+            // treated as nothing committed, so the modpack is staged again
+            Err(e) if e.downcast_ref::<serde_json::Error>().is_some() => {
+                warn!({ error = ?e }, "Discarding unreadable modpack staging journal");
+                Self::discard(instance_root, checkpoint)?;
+                return Ok(Some(Recovery::Discarded));
+            }
+            Err(e) => return Err(e),
+        };
+
+        let recovery = match journal.state {
+            JournalState::Applied => Recovery::RolledForward,
+            JournalState::RolledBack => Recovery::RolledBack,
+            JournalState::Planned => {
+                let applicable = journal.ops.iter().enumerate().all(|(index, op)| {
+                    op.is_applicable(instance_root, &Self::backup_path(instance_root, index))
+                });
+
+                match applicable {
+                    true => Recovery::RolledForward,
+                    false => Recovery::RolledBack,
+                }
+            }
+        };
+
+        info!("Recovering interrupted modpack staging: {recovery:?}");
+
+        match recovery {
+            Recovery::RolledForward => journal.roll_forward(instance_root, checkpoint)?,
+            Recovery::RolledBack | Recovery::Discarded => {
+                journal.roll_back(instance_root, checkpoint)?
+            }
+        }
+
+        Ok(Some(recovery))
+    }
+}
+
+fn op_target(op: &JournalOp) -> Option<&Path> {
+    match op {
+        JournalOp::Delete { .. } => None,
+        JournalOp::Replace { target, .. } | JournalOp::Create { target, .. } => Some(target),
+    }
+}
+
+fn op_staged(op: &JournalOp) -> Option<&Path> {
+    match op {
+        JournalOp::Delete { .. } => None,
+        JournalOp::Replace { staged, .. } | JournalOp::Create { staged, .. } => Some(staged),
+    }
+}
+
+fn rename(from: &Path, to: &Path, checkpoint: Checkpoint<'_>) -> io::Result<()> {
+    checkpoint()?;
+    fs::rename(from, to)
+}
+
+/// Makes renames into `path` durable. Directories can't be synced on windows,
+/// where renames are durable once they return.
+fn sync_dir(path: &Path) -> io::Result<()> {
+    #[cfg(unix)]
+    fs::File::open(path)?.sync_all()?;
+
+    #[cfg(not(unix))]
+    let _ = path;
+
+    Ok(())
+}
+
+fn remove_dir(path: &Path, checkpoint: Checkpoint<'_>) -> io::Result<()> {
+    if path.exists() {
+        checkpoint()?;
+        fs::remove_dir_all(path)?;
+    }
+
+    Ok(())
+}
+
+fn remove_file(path: &Path, checkpoint: Checkpoint<'_>) -> io::Result<()> {
+    if path.exists() {
+        checkpoint()?;
+        fs::remove_file(path)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+
+    use super::*;
+
+    type Snapshot = BTreeMap<String, String>;
+
+    /// Instance with one untouched, one removed, two replaced (one of them
+    /// disabled by the user) and one new file.
+    fn setup(root: &Path) -> StagingJournal {
+        let files = [
+            ("instance/mods/keep.jar", "keep"),
+            ("instance/mods/old.jar", "old"),
+            ("instance/config/a.cfg", "a1"),
+            ("instance/mods/b.jar.disabled", "b1"),
+            ("packinfo.json", "packinfo 1"),
+            ("tmp-packinfo.json", "packinfo 2"),
+            (".setup/staging-packinfo.json", "[]"),
+            (".setup/staging/instance/config/a.cfg", "a2"),
+            (".setup/staging/instance/mods/b.jar", "b2"),
+            (".setup/staging/instance/mods/new.jar", "new"),
+        ];
+
+        for (path, contents) in files {
+            let path = root.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, contents).unwrap();
+        }
+
+        StagingJournal::new(
+            vec![
+                JournalOp::Delete {
+                    target: "instance/mods/old.jar".into(),
+                },
+                JournalOp::Replace {
+                    staged: ".setup/staging/instance/config/a.cfg".into(),
+                    target: "instance/config/a.cfg".into(),
+                },
+                JournalOp::Replace {
+                    staged: ".setup/staging/instance/mods/b.jar".into(),
+                    target: "instance/mods/b.jar.disabled".into(),
+                },
+                JournalOp::Create {
+                    staged: ".setup/staging/instance/mods/new.jar".into(),
+                    target: "instance/mods/new.jar".into(),
+                },
+            ],
+            String::from("audit"),
+        )
+    }
+
+    /// Every file in the instance, outside of the staging and setup files.
+    fn snapshot(root: &Path) -> Snapshot {
+        let mut files = walkdir::WalkDir::new(root.join("instance"))
+            .into_iter()
+            .map(Result::unwrap)
+            .filter(|entry| entry.file_type().is_file())
+            .map(|entry| {
+                let path = entry.path().strip_prefix(root).unwrap();
+                let contents = fs::read_to_string(entry.path()).unwrap();
+                (path.to_string_lossy().replace('\\', "/"), contents)
+            })
+            .collect::<Snapshot>();
+
+        files.insert(
+            String::from("packinfo.json"),
+            fs::read_to_string(root.join("packinfo.json")).unwrap(),
+        );
+
+        files
+    }
+
+    fn expected(files: &[(&str, &str)]) -> Snapshot {
+        files
+            .iter()
+            .map(|(path, contents)| (path.to_string(), contents.to_string()))
+            .collect()
+    }
+
+    fn old_instance() -> Snapshot {
+        expected(&[
+            ("instance/config/a.cfg", "a1"),
+            ("instance/mods/b.jar.disabled", "b1"),
+            ("instance/mods/keep.jar", "keep"),
+            ("instance/mods/old.jar", "old"),
+            ("packinfo.json", "packinfo 1"),
+        ])
+    }
+
+    fn new_instance() -> Snapshot {
+        expected(&[
+            ("instance/config/a.cfg", "a2"),
+            ("instance/mods/b.jar.disabled", "b2"),
+            ("instance/mods/keep.jar", "keep"),
+            ("instance/mods/new.jar", "new"),
+            ("packinfo.json", "packinfo 2"),
+        ])
+    }
+
+    fn assert_recovered(root: &Path, recovery: Option<Recovery>) {
+        let setup = root.join(".setup");
+
+        match recovery {
+            Some(Recovery::RolledForward) => {
+                assert_eq!(snapshot(root), new_instance());
+                assert!(setup.join("modpack-complete").exists());
+                assert!(!setup.join("staging").exists());
+                assert_eq!(
+                    fs::read_to_string(root.join(".install_audit/audit.txt")).unwrap(),
+                    "audit"
+                );
+                assert!(!setup.join(BACKUP_DIR).exists());
+            }
+            Some(Recovery::RolledBack | Recovery::Discarded) => {
+                assert_eq!(snapshot(root), old_instance());
+                assert!(!setup.join("modpack-complete").exists());
+                assert!(!setup.join("staging").exists());
+                assert!(!root.join("tmp-packinfo.json").exists());
+                assert!(!setup.join(BACKUP_DIR).exists());
+            }
+            // interrupted before the journal was written
+            None => assert_eq!(snapshot(root), old_instance()),
+        }
+
+        assert!(!setup.join(JOURNAL_FILE).exists());
+    }
+
+    fn counter(count: &mut usize) -> impl FnMut() -> io::Result<()> + '_ {
+        move || {
+            *count += 1;
+            Ok(())
+        }
+    }
+
+    /// Fails on the step with the given index.
+    fn interrupt_at(step: usize) -> impl FnMut() -> io::Result<()> {
+        let mut current = 0;
+        move || {
+            current += 1;
+            match current > step {
+                true => Err(io::Error::new(io::ErrorKind::Interrupted, "interrupted")),
+                false => Ok(()),
+            }
+        }
+    }
+
+    fn apply(
+        root: &Path,
+        journal: StagingJournal,
+        checkpoint: Checkpoint<'_>,
+    ) -> anyhow::Result<()> {
+        journal.begin(root, checkpoint)?;
+        journal.roll_forward(root, checkpoint)
+    }
+
+    fn apply_steps() -> usize {
+        let dir = tempdir::TempDir::new("carbon_journal_test").unwrap();
+        let journal = setup(dir.path());
+
+        let mut steps = 0;
+        apply(dir.path(), journal, &mut counter(&mut steps)).unwrap();
+        assert_recovered(dir.path(), Some(Recovery::RolledForward));
+
+        steps
+    }
+
+    #[test]
+    fn interrupted_apply_rolls_forward() {
+        for step in 0..apply_steps() {
+            let dir = tempdir::TempDir::new("carbon_journal_test").unwrap();
+            let root = dir.path();
+            let journal = setup(root);
+
+            apply(root, journal, &mut interrupt_at(step)).unwrap_err();
+
+            let recovery = StagingJournal::recover(root, &mut || Ok(())).unwrap();
+            assert_ne!(recovery, Some(Recovery::RolledBack));
+            assert_recovered(root, recovery);
+        }
+    }
+
+    #[test]
+    fn interrupted_recovery_is_resumed() {
+        for step in 0..apply_steps() {
+            let mut recovery_step = 0;
+
+            loop {
+                let dir = tempdir::TempDir::new("carbon_journal_test").unwrap();
+                let root = dir.path();
+                let journal = setup(root);
+
+                apply(root, journal, &mut interrupt_at(step)).unwrap_err();
+
+                if StagingJournal::recover(root, &mut interrupt_at(recovery_step)).is_ok() {
+                    break;
+                }
+
+                let recovery = StagingJournal::recover(root, &mut || Ok(())).unwrap();
+                assert_recovered(root, recovery);
+                recovery_step += 1;
+            }
+        }
+    }
+
+    #[test]
+    fn interrupted_apply_rolls_back_without_staged_files() {
+        for step in 0..apply_steps() {
+            let dir = tempdir::TempDir::new("carbon_journal_test").unwrap();
+            let root = dir.path();
+            let journal = setup(root);
+
+            apply(root, journal, &mut interrupt_at(step)).unwrap_err();
+
+            // the staging folder got lost along with the crash
+            let staging = root.join(".setup/staging");
+            if staging.exists() {
+                fs::remove_dir_all(&staging).unwrap();
+            }
+
+            let recovery = StagingJournal::recover(root, &mut || Ok(())).unwrap();
+            assert_recovered(root, recovery);
+        }
+    }
+
+    #[test]
+    fn unreadable_journal_is_discarded() {
+        let dir = tempdir::TempDir::new("carbon_journal_test").unwrap();
+        let root = dir.path();
+        setup(root);
+
+        // truncated by a crash
+        fs::write(root.join(".setup").join(JOURNAL_FILE), "{\"state\": \"pla").unwrap();
+
+        let recovery = StagingJournal::recover(root, &mut || Ok(())).unwrap();
+        assert_eq!(recovery, Some(Recovery::Discarded));
+        assert_recovered(root, recovery);
+        assert!(!root.join(".setup/staging-packinfo.json").exists());
+    }
+}
@@ -176,6 +176,7 @@ pub async fn process_minecraft(
         .await?;
 
     downloads.extend(version_files);
+    app.settings_manager().download_mirrors().apply(downloads);
 
     t_subtasks.t_request_minecraft_files.complete_opaque();
 
@@ -256,7 +257,8 @@ pub async fn process_minecraft(
 
         carbon_net::download_multiple(
             &downloads[..],
-            DownloadOptions::builder()
+            app.settings_manager()
+                .download_options()
                 .concurrency(concurrency as usize)
                 .deep_check(deep_check)
                 .progress_sender(progress_watch_tx)
@@ -13,7 +13,7 @@ use crate::{
     managers::instance::log::{
         format_message_as_log4j_event, GameLog, LogEntry, LogEntrySourceKind,
     },
-    managers::instance::modpack::packinfo,
+    managers::instance::modpack::{packinfo, plan},
     managers::instance::schema::make_instance_config,
     managers::java::java_checker::{JavaChecker, RealJavaChecker},
     managers::java::managed::Step,
@@ -26,7 +26,7 @@ use crate::{
     managers::vtask::Subtask,
     managers::{
         self,
-        account::FullAccount,
+        account::{yggdrasil, FullAccount, FullAccountType},
         vtask::{NonFailedDismissError, TaskState, VisualTask},
         ManagerRef,
     },
@@ -56,6 +56,7 @@ use tokio::{io::AsyncReadExt, sync::mpsc};
 use tracing::{debug, info, trace};
 
 mod java;
+mod journal;
 mod minecraft;
 mod modpack;
 
@@ -216,6 +217,11 @@ impl ManagerRef<'_, InstanceManager> {
 
         tracing::debug!("instance path: {:?}", instance_path);
 
+        let setup_path = instance_path.get_root().join(".setup");
+        if plan::is_preview(&setup_path) && plan::read_plan(&setup_path).await?.is_some() {
+            bail!("A modpack change is waiting to be confirmed or cancelled");
+        }
+
         let version = match config.game_configuration.version {
             Some(GameVersion::Standard(ref v)) => Some(v.clone()),
             Some(GameVersion::Custom(_)) => bail!("Custom versions are not supported yet"),
@@ -329,6 +335,9 @@ impl ManagerRef<'_, InstanceManager> {
             let try_result: anyhow::Result<_> = async {
                 let mut downloads = Vec::new();
 
+                modpack::recover_modpack_staging(Arc::clone(&app), instance_shortpath.clone())
+                    .await?;
+
                 let (t_subtasks, modpack_version) = modpack::process_modpack(
                     Arc::clone(&app),
                     instance_id.clone(),
@@ -339,6 +348,28 @@ impl ManagerRef<'_, InstanceManager> {
                     callback_task.is_some(),
                 )
                 .await?;
+
+                if plan::is_preview(&setup_path) {
+                    modpack::preview_modpack_staging(
+                        Arc::clone(&app),
+                        instance_shortpath.clone(),
+                        &t_subtasks,
+                    )
+                    .await?;
+
+                    app.invalidate(GET_MODPACK_UPDATE_PLAN, Some(instance_id.0.into()));
+
+                    let _ = app
+                        .instance_manager()
+                        .change_launch_state(
+                            instance_id,
+                            LaunchState::Inactive { failed_task: None },
+                        )
+                        .await;
+
+                    return Ok(None);
+                }
+
                 modpack::process_modpack_staging(
                     Arc::clone(&app),
                     instance_shortpath.clone(),
@@ -372,6 +403,7 @@ impl ManagerRef<'_, InstanceManager> {
                     &version_info,
                     &t_subtasks,
                     &version,
+                    instance_path.get_mods_path(),
                     java_override,
                     auto_manage_java_system_profiles,
                     &log,
@@ -434,6 +466,10 @@ impl ManagerRef<'_, InstanceManager> {
                             );
                         }
 
+                        if let FullAccountType::Yggdrasil { .. } = &account.type_ {
+                            yggdrasil::download_authlib_injector(&app).await?;
+                        }
+
                         Ok(Some(
                             managers::minecraft::minecraft::launch_minecraft(
                                 java,
@@ -572,6 +608,8 @@ impl ManagerRef<'_, InstanceManager> {
                     }
 
                     if let Ok(exitcode) = child.wait().await {
+                        log.send_modify(|log| log.set_exit_status(exitcode));
+
                         let msg = format!("{exitcode}");
 
                         if let Some(file) = file.as_mut() {
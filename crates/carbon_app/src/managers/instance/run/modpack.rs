@@ -1,3 +1,4 @@
+use super::journal::{Checkpoint, JournalOp, StagingJournal};
 use crate::api::keys::instance::*;
 use crate::api::translation::Translation;
 use crate::domain::instance::info::{
@@ -10,7 +11,7 @@ use crate::domain::vtask::VisualTaskId;
 use crate::managers::instance::log::{
     format_message_as_log4j_event, GameLog, LogEntry, LogEntrySourceKind,
 };
-use crate::managers::instance::modpack::{packinfo, PackVersionFile};
+use crate::managers::instance::modpack::{packinfo, plan, PackVersionFile};
 use crate::managers::instance::schema::make_instance_config;
 use crate::managers::java::java_checker::{JavaChecker, RealJavaChecker};
 use crate::managers::java::managed::Step;
@@ -33,7 +34,7 @@ use crate::{
     },
 };
 use anyhow::{anyhow, bail, Context};
-use carbon_net::{DownloadOptions, Downloadable};
+use carbon_net::Downloadable;
 use carbon_parsing::log::{LogParser, ParsedItem};
 use carbon_platforms::curseforge::filters::ModFileParameters;
 use carbon_platforms::modrinth::search::VersionID;
@@ -41,7 +42,7 @@ use carbon_rt_path::InstancePath;
 use chrono::{DateTime, Local, Utc};
 use futures::Future;
 use md5::{Digest, Md5};
-use std::collections::HashSet;
+use std::collections::HashMap;
 use std::fmt::Debug;
 use std::io;
 use std::path::{Path, PathBuf};
@@ -477,7 +478,8 @@ pub async fn process_modpack(
         // Actually downloads the modpack files
         carbon_net::download_multiple(
             &modpack_downloads[..],
-            DownloadOptions::builder()
+            app.settings_manager()
+                .download_options()
                 .concurrency(concurrency as usize)
                 .progress_sender(progress_watch_tx)
                 .deep_check(deep_check)
@@ -490,7 +492,18 @@ pub async fn process_modpack(
 
         completion.await?;
 
-        if let Some(v) = v {
+        app.instance_manager()
+            .store_instance_files(
+                &instance_prep_path,
+                modpack_downloads
+                    .iter()
+                    .map(|downloadable| downloadable.path.clone())
+                    .collect(),
+            )
+            .await?;
+
+        // the config is only updated once a previewed change is confirmed
+        if let Some(v) = v.filter(|_| !plan::is_preview(&setup_path)) {
             tracing::info!("Modpack version: {v:?}");
 
             version = Some(v.clone());
@@ -593,8 +606,57 @@ pub async fn process_modpack(
     Ok((Arc::new(subtasks), version))
 }
 
-// TODO: Modpack staging is not atomic and does not track applied changes, so if the process is interrupted,
-// the instance will be in an inconsistent state.
+/// Finishes or undoes a staged modpack apply that was interrupted, before
+/// anything else looks at the instance files.
+pub async fn recover_modpack_staging(
+    app: Arc<AppInner>,
+    instance_shortpath: String,
+) -> anyhow::Result<()> {
+    let instance_root = app
+        .settings_manager()
+        .runtime_path
+        .get_instances()
+        .get_instance_path(&instance_shortpath)
+        .get_root();
+
+    tokio::task::spawn_blocking(move || StagingJournal::recover(&instance_root, &mut || Ok(())))
+        .await?
+        .context("Failed to recover interrupted modpack staging")?;
+
+    Ok(())
+}
+
+/// Plans the staged modpack change without applying it, so it can be
+/// reviewed before being confirmed.
+pub async fn preview_modpack_staging(
+    app: Arc<AppInner>,
+    instance_shortpath: String,
+    t_subtasks: &TSubtasks,
+) -> anyhow::Result<()> {
+    let instance_root = app
+        .settings_manager()
+        .runtime_path
+        .get_instances()
+        .get_instance_path(&instance_shortpath)
+        .get_root();
+
+    let setup_path = instance_root.join(".setup");
+
+    t_subtasks.t_apply_staging.start_opaque();
+
+    let plan = plan::plan_update(&instance_root, &HashMap::new())
+        .await
+        .context("Failed to plan staged instance changes")?;
+    plan::write_plan(&setup_path, &plan).await?;
+
+    t_subtasks.t_apply_staging.complete_opaque();
+
+    Ok(())
+}
+
+/// Applies the staged modpack files to the instance.
+///
+/// The changes are planned up front and journaled, see [`StagingJournal`].
 pub async fn process_modpack_staging(
     app: Arc<AppInner>,
     instance_shortpath: String,
@@ -614,191 +676,58 @@ pub async fn process_modpack_staging(
     if staging_dir.exists() {
         t_subtasks.t_apply_staging.start_opaque();
 
-        let change_version_path = setup_path.join("change-pack-version.json");
-        let overwrite_changed = !change_version_path.exists(); // TODO
-
-        let staging_packinfo = setup_path.join("staging-packinfo.json");
-
-        let staged_text = tokio::fs::read_to_string(&staging_packinfo).await?;
-        let staging_snapshot = serde_json::from_str::<Vec<&str>>(&staged_text)
-            .context("could not parse staging snapshot")?;
-
-        #[derive(Debug)]
-        enum SkipReplaceReason {
-            DeletedByUser,
-            ModifiedByUser([u8; 16], [u8; 16]),
-            InSaveFolder,
-        }
-
-        let mut new_files = Vec::<String>::new();
-        let mut deleted_files = Vec::<String>::new();
-        let mut replaced_files = Vec::<String>::new();
-        let mut skipped_replacements = Vec::<(String, SkipReplaceReason)>::new();
-
-        let packinfo_path = instance_root.join("packinfo.json");
-        let packinfo = match tokio::fs::read_to_string(packinfo_path).await {
-            Ok(text) => {
-                Some(packinfo::parse_packinfo(&text).context("while parsing packinfo json")?)
-            }
-            Err(_) => None,
-        };
-
-        debug!("Applying staged instance files");
-        let r: anyhow::Result<_> = async {
-            if let Some(packinfo) = packinfo {
-                for (oldfile, oldfilehash) in &packinfo.files {
-                    let mut original_file = instance_root.join("instance").join(&oldfile[1..]);
-
-                    trace!("Checking for replacement for packinfo file: {original_file:?}");
-
-                    if !original_file.exists() {
-                        let mut name = original_file.file_name().unwrap().to_owned();
-                        name.push(".disabled");
-                        original_file.set_file_name(name);
-
-                        if !original_file.exists() {
-                            // either the user deleted it or we already deleted it in the next check, skip
-                            skipped_replacements
-                                .push((oldfile.clone(), SkipReplaceReason::DeletedByUser));
-                            continue;
-                        }
-                    }
-
-                    let mut original_md5 = Md5::new();
-                    let mut file = tokio::fs::File::open(&original_file).await?;
-                    carbon_scheduler::buffered_digest(&mut file, |chunk| {
-                        original_md5.update(chunk);
-                    })
-                    .await?;
-                    drop(file);
-                    let original_md5: [u8; 16] = original_md5.finalize().into();
-
-                    if original_md5 != oldfilehash.md5 {
-                        // the user has modified this file so we shouldn't touch it
-                        skipped_replacements.push((
-                            oldfile.clone(),
-                            SkipReplaceReason::ModifiedByUser(oldfilehash.md5, original_md5),
-                        ));
-                        continue;
-                    }
-
-                    if !staging_snapshot.contains(&(&oldfile as &str)) {
-                        if oldfile.starts_with("/saves") {
-                            skipped_replacements
-                                .push((oldfile.clone(), SkipReplaceReason::InSaveFolder));
-                            continue;
-                        }
-
-                        // file is not present in new version and old version was not changed, delete
-                        tokio::fs::remove_file(original_file).await?;
-                        deleted_files.push(oldfile.clone());
-                        continue;
-                    }
-
-                    let staged_file = staging_dir.join("instance").join(&oldfile[1..]);
-
-                    if staged_file.is_file() {
-                        // old file matches the snapshotted version and new file is present, replace
-                        tokio::fs::rename(staged_file, original_file).await?;
-                        replaced_files.push(oldfile.clone());
-                    }
-                }
-            }
-
-            for entry in walkdir::WalkDir::new(&staging_dir) {
-                let entry = entry?;
+        let overrides = plan::read_overrides(&setup_path).await?;
 
-                let staged_file = entry.path().to_path_buf();
-                let relpath = staged_file.strip_prefix(&staging_dir).unwrap();
-                let original_file = instance_root.join(relpath);
+        debug!("Planning staged instance changes");
+        let plan = plan::plan_update(&instance_root, &overrides)
+            .await
+            .context("Failed to plan staged instance changes")?;
 
-                if entry.metadata()?.is_file() && !original_file.exists() {
-                    // there was no record of this file in the packinfo or it would've been moved previously,
-                    // and the user has not created one in its place, add the file
-
-                    new_files.push(relpath.to_string_lossy().to_string());
-                    tokio::fs::create_dir_all(original_file.parent().unwrap()).await?;
-                    tokio::fs::rename(staged_file, original_file).await?;
-                }
-            }
-
-            Ok(())
-        }
-        .await;
-
-        if let Err(e) = r {
-            return Err(e.context("Failed to apply staged instance changes"));
-        }
-
-        trace!("Creating update audit files");
-        let audit_dir = instance_root.join(".install_audit");
-
-        // delete old audit dir if it exists
-        if (audit_dir.exists()) {
-            tokio::fs::remove_dir_all(&audit_dir).await?;
-        }
-
-        tokio::fs::create_dir(&audit_dir).await?;
-
-        let audit_file = audit_dir.join("audit.txt");
-        let mut audit_txt = "GDLauncher Modpack Install/Update Audit\n".to_string();
-
-        if (!skipped_replacements.is_empty()) {
-            audit_txt += "\nFiles that could not be replaced:\n";
-
-            for (file, reason) in skipped_replacements {
-                match reason {
-                    SkipReplaceReason::DeletedByUser => audit_txt += &format!(" - {file}: deleted by user\n"),
-                    SkipReplaceReason::ModifiedByUser(original, current) => audit_txt += &format!(
-                        " - {file}: modified by user\n     original md5: {}\n     current md5:  {}\n",
-                        hex::encode(original),
-                        hex::encode(current),
-                    ),
-                    SkipReplaceReason::InSaveFolder => audit_txt += &format!(" - {file}: files in /saves will never be modified\n"),
-                }
-            }
-        }
-
-        if (!deleted_files.is_empty()) {
-            audit_txt += "\nFiles deleted:\n";
-
-            for file in deleted_files {
-                audit_txt += &format!(" - {file}\n");
-            }
-        }
-
-        if (!replaced_files.is_empty()) {
-            audit_txt += "\nFiles replaced:\n";
-
-            for file in replaced_files {
-                audit_txt += &format!(" - {file}\n");
-            }
-        }
-
-        if (!new_files.is_empty()) {
-            audit_txt += "\nFiles created:\n";
+        // a previewed change must apply what was shown to the user
+        if let Some(previewed) = plan::read_plan(&setup_path).await? {
+            let current = plan::plan_update(&instance_root, &HashMap::new())
+                .await
+                .context("Failed to plan staged instance changes")?;
 
-            for file in new_files {
-                audit_txt += &format!(" - {file}\n");
+            if current != previewed {
+                bail!("The instance has changed since the modpack change was previewed, cancel it and preview it again");
             }
         }
 
-        tokio::fs::write(audit_file, audit_txt).await?;
+        let audit_txt = plan.audit();
+        let ops = plan
+            .removed
+            .into_iter()
+            .map(|file| JournalOp::Delete {
+                target: file.target,
+            })
+            .chain(plan.replaced.into_iter().filter_map(|file| {
+                Some(JournalOp::Replace {
+                    staged: file.staged?,
+                    target: file.target,
+                })
+            }))
+            .chain(plan.added.into_iter().filter_map(|file| {
+                Some(JournalOp::Create {
+                    staged: file.staged?,
+                    target: file.target,
+                })
+            }))
+            .collect::<Vec<_>>();
+
+        debug!("Applying staged instance changes");
+        let journal = StagingJournal::new(ops, audit_txt);
+        let journal_root = instance_root.clone();
+        tokio::task::spawn_blocking(move || {
+            let checkpoint: Checkpoint<'_> = &mut || Ok(());
+            journal.begin(&journal_root, checkpoint)?;
+            journal.roll_forward(&journal_root, checkpoint)
+        })
+        .await?
+        .context("Failed to apply staged instance changes")?;
 
-        trace!("Cleaning up staging directory");
-        tokio::fs::remove_dir_all(staging_dir).await?;
         trace!("Staging complete");
         t_subtasks.t_apply_staging.complete_opaque();
-
-        if instance_root.join("tmp-packinfo.json").exists() {
-            tokio::fs::rename(
-                instance_root.join("tmp-packinfo.json"),
-                instance_root.join("packinfo.json"),
-            )
-            .await?;
-        }
-
-        tokio::fs::write(setup_path.join("modpack-complete"), "").await?;
     }
 
     Ok(())
@@ -0,0 +1,410 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use carbon_rt_path::{InstancePath, StorePath};
+use sha1::{Digest, Sha1};
+use tracing::{debug, info, warn};
+
+use super::InstanceManager;
+use crate::managers::ManagerRef;
+
+const STAGING_POSTFIX: &str = ".__gdl_store~";
+
+/// How a file in an instance shares its contents with its blob in the store.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkKind {
+    /// Copy on write clone, writes to either file don't affect the other.
+    Reflink,
+    /// The same file on disk, modifying it in place modifies the blob too.
+    Hardlink,
+    /// Plain copy, when the filesystem supports neither kind of link.
+    Copy,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct GcReport {
+    pub removed_blobs: u64,
+    pub freed_bytes: u64,
+}
+
+/// Folders of an instance whose files are moved to the store.
+fn shared_folders(instance: &InstancePath) -> [PathBuf; 3] {
+    [
+        instance.get_mods_path(),
+        instance.get_resourcepacks_path(),
+        instance.get_shaderpacks_path(),
+    ]
+}
+
+fn is_shared_file(instance: &InstancePath, path: &Path) -> bool {
+    shared_folders(instance)
+        .iter()
+        .any(|folder| path.parent() == Some(folder))
+}
+
+fn hash_file(path: &Path) -> io::Result<String> {
+    let mut hasher = Sha1::new();
+    io::copy(&mut fs::File::open(path)?, &mut hasher)?;
+    Ok(hex::encode(hasher.finalize()))
+}
+
+#[cfg(unix)]
+fn is_same_file(a: &fs::Metadata, b: &fs::Metadata) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    a.dev() == b.dev() && a.ino() == b.ino()
+}
+
+#[cfg(not(unix))]
+fn is_same_file(_a: &fs::Metadata, _b: &fs::Metadata) -> bool {
+    false
+}
+
+fn staging_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(STAGING_POSTFIX);
+    path.with_file_name(name)
+}
+
+/// Creates `to` with the contents of `from`, sharing them on disk if the
+/// filesystem allows it.
+fn link_file(from: &Path, to: &Path) -> io::Result<LinkKind> {
+    if reflink_copy::reflink(from, to).is_ok() {
+        return Ok(LinkKind::Reflink);
+    }
+
+    match fs::hard_link(from, to) {
+        Ok(()) => Ok(LinkKind::Hardlink),
+        Err(e) => {
+            debug!("Could not link {} to {}: {e}", from.display(), to.display());
+            fs::copy(from, to)?;
+            Ok(LinkKind::Copy)
+        }
+    }
+}
+
+/// Atomically replaces `to` with a link to `from`.
+fn replace_with_link(from: &Path, to: &Path) -> io::Result<LinkKind> {
+    let staged = staging_path(to);
+    let _ = fs::remove_file(&staged);
+
+    let kind = link_file(from, &staged)?;
+    if let Err(e) = fs::rename(&staged, to) {
+        let _ = fs::remove_file(&staged);
+        return Err(e);
+    }
+
+    Ok(kind)
+}
+
+fn store_file(store: &StorePath, path: &Path) -> anyhow::Result<LinkKind> {
+    let sha1 = hash_file(path).with_context(|| format!("hashing {}", path.display()))?;
+    let blob = store.get_blob_path(&sha1);
+
+    if let Ok(blob_metadata) = fs::metadata(&blob) {
+        let metadata = fs::metadata(path)?;
+
+        if is_same_file(&metadata, &blob_metadata) {
+            return Ok(LinkKind::Hardlink);
+        }
+
+        // a hardlinked instance file modified in place also modifies the blob
+        if blob_metadata.len() == metadata.len() && hash_file(&blob)? == sha1 {
+            return replace_with_link(&blob, path)
+                .with_context(|| format!("linking {} to the store", path.display()));
+        }
+
+        warn!("Blob {} is corrupted, replacing it", blob.display());
+        fs::remove_file(&blob)?;
+    }
+
+    fs::create_dir_all(blob.parent().expect("blob paths always have a parent"))?;
+
+    // a blob is only visible once it's complete
+    let staged = staging_path(&blob);
+    let _ = fs::remove_file(&staged);
+    let kind = link_file(path, &staged)?;
+    fs::rename(&staged, &blob)?;
+
+    Ok(kind)
+}
+
+fn collect_blobs(store: &StorePath) -> io::Result<Vec<(PathBuf, fs::Metadata)>> {
+    let mut blobs = Vec::new();
+
+    let prefixes = match fs::read_dir(store.to_path()) {
+        Ok(prefixes) => prefixes,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(blobs),
+        Err(e) => return Err(e),
+    };
+
+    for prefix in prefixes {
+        let prefix = prefix?;
+        if !prefix.file_type()?.is_dir() {
+            continue;
+        }
+
+        for blob in fs::read_dir(prefix.path())? {
+            let blob = blob?;
+            blobs.push((blob.path(), blob.metadata()?));
+        }
+    }
+
+    Ok(blobs)
+}
+
+fn garbage_collect(store: &StorePath, instances: &[InstancePath]) -> io::Result<GcReport> {
+    let mut blobs = HashMap::new();
+    let mut unreferenced_sizes = HashMap::<u64, usize>::new();
+    let mut report = GcReport::default();
+
+    for (path, metadata) in collect_blobs(store)? {
+        let is_blob = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.len() == 40 && name.bytes().all(|b| b.is_ascii_hexdigit()));
+
+        // leftovers of an interrupted `store_file`
+        if !is_blob {
+            fs::remove_file(&path)?;
+            continue;
+        }
+
+        *unreferenced_sizes.entry(metadata.len()).or_default() += 1;
+        blobs.insert(path, metadata);
+    }
+
+    let mut referenced = HashSet::new();
+
+    for instance in instances {
+        for folder in shared_folders(instance) {
+            let Ok(entries) = fs::read_dir(&folder) else {
+                continue;
+            };
+
+            for entry in entries {
+                let entry = entry?;
+                let metadata = entry.metadata()?;
+
+                // only files of the same size can share a blob
+                if !metadata.is_file() || !unreferenced_sizes.contains_key(&metadata.len()) {
+                    continue;
+                }
+
+                let linked_blob = blobs
+                    .iter()
+                    .find(|(_, blob)| is_same_file(&metadata, blob))
+                    .map(|(path, _)| path.clone());
+
+                let blob = match linked_blob {
+                    Some(blob) => blob,
+                    None => store.get_blob_path(&hash_file(&entry.path())?),
+                };
+
+                if blobs.contains_key(&blob) && referenced.insert(blob) {
+                    let count = unreferenced_sizes
+                        .get_mut(&metadata.len())
+                        .expect("sizes of unreferenced blobs are tracked");
+                    *count -= 1;
+
+                    if *count == 0 {
+                        unreferenced_sizes.remove(&metadata.len());
+                    }
+                }
+            }
+        }
+    }
+
+    for (path, metadata) in blobs {
+        if referenced.contains(&path) {
+            continue;
+        }
+
+        fs::remove_file(&path)?;
+        report.removed_blobs += 1;
+        report.freed_bytes += metadata.len();
+
+        // fails as long as the prefix folder still holds other blobs
+        if let Some(prefix) = path.parent() {
+            let _ = fs::remove_dir(prefix);
+        }
+    }
+
+    Ok(report)
+}
+
+impl ManagerRef<'_, InstanceManager> {
+    /// Moves the mods, resource packs and shaderpacks among `files` to the
+    /// shared store, leaving links to them in the instance. Files that can't
+    /// be stored are left untouched.
+    pub async fn store_instance_files(
+        self,
+        instance: &InstancePath,
+        files: Vec<PathBuf>,
+    ) -> anyhow::Result<()> {
+        let files = files
+            .into_iter()
+            .filter(|file| is_shared_file(instance, file))
+            .collect::<Vec<_>>();
+
+        if files.is_empty() {
+            return Ok(());
+        }
+
+        let store = self.app.settings_manager().runtime_path.get_store();
+        let _lock = self.store_lock.read().await;
+
+        tokio::task::spawn_blocking(move || {
+            for file in files {
+                match store_file(&store, &file) {
+                    Ok(kind) => debug!("Stored {} as {kind:?}", file.display()),
+                    Err(e) => warn!("Could not store {}: {e:?}", file.display()),
+                }
+            }
+        })
+        .await?;
+
+        Ok(())
+    }
+
+    /// Removes the blobs no instance links to anymore.
+    pub async fn garbage_collect_store(self) -> anyhow::Result<GcReport> {
+        let runtime_path = &self.app.settings_manager().runtime_path;
+        let store = runtime_path.get_store();
+        let instances_path = runtime_path.get_instances();
+
+        let instances = self
+            .instances
+            .read()
+            .await
+            .values()
+            .flat_map(|instance| {
+                let instance = instances_path.get_instance_path(&instance.shortpath);
+                // modpack files waiting to be moved into the instance
+                let staging = InstancePath::new(instance.get_root().join(".setup").join("staging"));
+
+                [instance, staging]
+            })
+            .collect::<Vec<_>>();
+
+        let _lock = self.store_lock.write().await;
+        let report =
+            tokio::task::spawn_blocking(move || garbage_collect(&store, &instances)).await??;
+
+        info!(
+            "Removed {} unused blobs from the store, freeing {} MB",
+            report.removed_blobs,
+            report.freed_bytes / 1024 / 1024
+        );
+
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use carbon_rt_path::{InstancePath, RuntimePath};
+
+    use super::{garbage_collect, store_file, GcReport};
+
+    fn instance(runtime_path: &RuntimePath, name: &str) -> InstancePath {
+        let instance = runtime_path.get_instances().get_instance_path(name);
+        fs::create_dir_all(instance.get_mods_path()).unwrap();
+        fs::create_dir_all(instance.get_resourcepacks_path()).unwrap();
+        instance
+    }
+
+    #[test]
+    fn test_store_deduplicates() {
+        let temp_dir = tempdir::TempDir::new("carbon_app_test").unwrap();
+        let runtime_path = RuntimePath::new(temp_dir.path().to_path_buf());
+        let store = runtime_path.get_store();
+
+        let a = instance(&runtime_path, "a")
+            .get_mods_path()
+            .join("sodium.jar");
+        let b = instance(&runtime_path, "b")
+            .get_mods_path()
+            .join("sodium.jar");
+        fs::write(&a, "sodium").unwrap();
+        fs::write(&b, "sodium").unwrap();
+
+        store_file(&store, &a).unwrap();
+        store_file(&store, &b).unwrap();
+
+        let blob = store.get_blob_path("6f07dc1d0a287f925d7672e6884d85ceb3f339ed");
+        assert_eq!(fs::read_to_string(&blob).unwrap(), "sodium");
+        assert_eq!(fs::read_to_string(&a).unwrap(), "sodium");
+        assert_eq!(fs::read_to_string(&b).unwrap(), "sodium");
+
+        // storing an already stored file is a no-op
+        store_file(&store, &a).unwrap();
+        assert_eq!(fs::read_to_string(&a).unwrap(), "sodium");
+    }
+
+    #[test]
+    fn test_store_replaces_corrupted_blob() {
+        let temp_dir = tempdir::TempDir::new("carbon_app_test").unwrap();
+        let runtime_path = RuntimePath::new(temp_dir.path().to_path_buf());
+        let store = runtime_path.get_store();
+
+        let a = instance(&runtime_path, "a")
+            .get_mods_path()
+            .join("sodium.jar");
+        fs::write(&a, "sodium").unwrap();
+
+        let blob = store.get_blob_path("6f07dc1d0a287f925d7672e6884d85ceb3f339ed");
+        fs::create_dir_all(blob.parent().unwrap()).unwrap();
+        fs::write(&blob, "sodiuN").unwrap();
+
+        store_file(&store, &a).unwrap();
+
+        assert_eq!(fs::read_to_string(&blob).unwrap(), "sodium");
+        assert_eq!(fs::read_to_string(&a).unwrap(), "sodium");
+    }
+
+    #[test]
+    fn test_garbage_collect() {
+        let temp_dir = tempdir::TempDir::new("carbon_app_test").unwrap();
+        let runtime_path = RuntimePath::new(temp_dir.path().to_path_buf());
+        let store = runtime_path.get_store();
+
+        let a = instance(&runtime_path, "a");
+        let b = instance(&runtime_path, "b");
+
+        let sodium = a.get_mods_path().join("sodium.jar");
+        let pack = a.get_resourcepacks_path().join("faithful.zip");
+        let iris = b.get_mods_path().join("iris.jar");
+        fs::write(&sodium, "sodium").unwrap();
+        fs::write(&pack, "faithful").unwrap();
+        fs::write(&iris, "irises").unwrap();
+
+        for file in [&sodium, &pack, &iris] {
+            store_file(&store, file).unwrap();
+        }
+
+        // deleting the instance leaves its blobs unreferenced
+        fs::remove_dir_all(b.get_root()).unwrap();
+
+        let report = garbage_collect(&store, &[a.clone(), b.clone()]).unwrap();
+        assert_eq!(
+            report,
+            GcReport {
+                removed_blobs: 1,
+                freed_bytes: 6,
+            }
+        );
+
+        assert!(store
+            .get_blob_path("6f07dc1d0a287f925d7672e6884d85ceb3f339ed")
+            .exists());
+        assert_eq!(fs::read_to_string(&sodium).unwrap(), "sodium");
+
+        let report = garbage_collect(&store, &[a]).unwrap();
+        assert_eq!(report, GcReport::default());
+    }
+}
@@ -0,0 +1,278 @@
+use super::{
+    download_with_progress, find_java_binary, install_archive, register_managed_java, Managed,
+    ManagedJavaArchMap, ManagedJavaOsMap, ManagedJavaVersion, Step,
+};
+use crate::{
+    domain::java::{JavaArch, JavaOs, JavaVersion},
+    managers::java::java_checker::JavaChecker,
+};
+use carbon_net::{Checksum, DownloadOptionsBuilder, Downloadable};
+use carbon_repos::db::PrismaClient;
+use carbon_rt_path::{ManagedJavasPath, TempPath};
+use serde::Deserialize;
+use std::{collections::HashMap, sync::Arc};
+use tokio::sync::{watch::Sender, RwLock};
+use tracing::{instrument, trace};
+
+#[derive(Debug, Default)]
+pub struct Adoptium {
+    versions: Arc<RwLock<ManagedJavaOsMap>>,
+}
+
+#[async_trait::async_trait]
+impl Managed for Adoptium {
+    #[instrument(skip(self, java_checker, db_client, download_options, progress_report))]
+    async fn setup<G: JavaChecker + Send + Sync>(
+        &self,
+        version: &ManagedJavaVersion,
+        tmp_path: TempPath,
+        base_managed_java_path: ManagedJavasPath,
+        java_checker: &G,
+        db_client: &Arc<PrismaClient>,
+        download_options: DownloadOptionsBuilder,
+        progress_report: Sender<Step>,
+    ) -> anyhow::Result<String> {
+        let progress_report = Arc::new(progress_report);
+
+        let sha256 = version
+            .checksum
+            .clone()
+            .ok_or_else(|| anyhow::anyhow!("No checksum published for {}", version.name))?;
+
+        let archive_path = tmp_path.to_path().join(&version.name);
+        let downloadable = Downloadable::new(&version.download_url, &archive_path)
+            .with_checksum(Some(Checksum::Sha256(sha256)));
+
+        trace!("Downloadable: {:?}", downloadable);
+
+        download_with_progress(
+            &[downloadable],
+            download_options.concurrency(1),
+            &progress_report,
+        )
+        .await?;
+
+        trace!("Download complete");
+
+        // archives contain a single `jdk-<version>-jre` folder
+        let result = install_archive(
+            &archive_path,
+            &version.name,
+            &base_managed_java_path.to_path(),
+            &progress_report,
+        )
+        .await;
+
+        if let Err(e) = std::fs::remove_file(&archive_path) {
+            tracing::warn!("Could not delete downloaded file: {}", e);
+        }
+
+        let install_path = result?;
+        let main_binary_path = find_java_binary(&install_path)
+            .ok_or_else(|| anyhow::anyhow!("No main binary found in {:?}", install_path))?;
+
+        progress_report.send(Step::Done)?;
+
+        register_managed_java(java_checker, db_client, main_binary_path).await
+    }
+
+    async fn fetch_all_versions(&self) -> anyhow::Result<ManagedJavaOsMap> {
+        let mut versions = self.versions.write().await;
+        if versions.is_empty() {
+            let results = AdoptiumAPI::get_all_versions().await?;
+            *versions = results;
+        }
+
+        Ok(versions.clone())
+    }
+}
+
+const ADOPTIUM_BASE_URL: &str = "https://api.adoptium.net/v3/";
+struct AdoptiumAPI;
+
+impl AdoptiumAPI {
+    async fn get_all_versions() -> anyhow::Result<ManagedJavaOsMap> {
+        let releases = reqwest::get(format!("{ADOPTIUM_BASE_URL}info/available_releases"))
+            .await?
+            .error_for_status()?
+            .json::<AvailableReleases>()
+            .await?;
+
+        let tasks = releases
+            .available_releases
+            .into_iter()
+            .map(|feature_version| tokio::spawn(Self::get_latest_assets(feature_version)))
+            .collect::<Vec<_>>();
+
+        let mut assets = Vec::new();
+        for task in tasks {
+            assets.append(&mut task.await??);
+        }
+
+        versions_from_assets(assets)
+    }
+
+    /// Latest JRE builds of a feature version, for every os and arch
+    async fn get_latest_assets(feature_version: u16) -> anyhow::Result<Vec<AdoptiumAsset>> {
+        let url = format!(
+            "{ADOPTIUM_BASE_URL}assets/latest/{feature_version}/hotspot?image_type=jre&vendor=eclipse"
+        );
+
+        Ok(reqwest::get(&url)
+            .await?
+            .error_for_status()?
+            .json::<Vec<AdoptiumAsset>>()
+            .await?)
+    }
+}
+
+fn versions_from_assets(assets: Vec<AdoptiumAsset>) -> anyhow::Result<ManagedJavaOsMap> {
+    let mut results = ManagedJavaOsMap::default();
+
+    for asset in assets {
+        let os = match &*asset.binary.os {
+            "windows" => JavaOs::Windows,
+            "linux" => JavaOs::Linux,
+            "mac" => JavaOs::MacOs,
+            // alpine-linux, aix, solaris
+            _ => continue,
+        };
+
+        let arch = match &*asset.binary.architecture {
+            "x64" => JavaArch::X86_64,
+            "x86" | "x32" => JavaArch::X86_32,
+            "arm" => JavaArch::Arm32,
+            "aarch64" => JavaArch::Arm64,
+            _ => continue,
+        };
+
+        results
+            .entry(os)
+            .or_insert_with(|| ManagedJavaArchMap(HashMap::new()))
+            .entry(arch)
+            .or_insert_with(Vec::new)
+            .push(ManagedJavaVersion {
+                // name of the folder inside of the archive
+                id: format!("{}-{}", asset.release_name, asset.binary.image_type),
+                name: asset.binary.package.name,
+                download_url: asset.binary.package.link,
+                checksum: Some(asset.binary.package.checksum),
+                java_version: JavaVersion {
+                    major: asset.version.major,
+                    minor: asset.version.minor,
+                    patch: asset.version.security.to_string(),
+                    update_number: None,
+                    prerelease: None,
+                    build_metadata: None,
+                },
+            });
+    }
+
+    for versions in results.values_mut().flat_map(|archs| archs.values_mut()) {
+        versions.sort_by(|a, b| b.java_version.major.cmp(&a.java_version.major));
+    }
+
+    Ok(results)
+}
+
+#[derive(Deserialize, Debug)]
+struct AvailableReleases {
+    available_releases: Vec<u16>,
+}
+
+#[derive(Deserialize, Debug)]
+struct AdoptiumAsset {
+    binary: AdoptiumBinary,
+    release_name: String,
+    version: AdoptiumVersion,
+}
+
+#[derive(Deserialize, Debug)]
+struct AdoptiumBinary {
+    architecture: String,
+    image_type: String,
+    os: String,
+    package: AdoptiumPackage,
+}
+
+#[derive(Deserialize, Debug)]
+struct AdoptiumPackage {
+    checksum: String,
+    link: String,
+    name: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct AdoptiumVersion {
+    major: u16,
+    minor: u16,
+    security: u16,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_versions_from_assets() {
+        let assets: Vec<AdoptiumAsset> = serde_json::from_str(
+            r#"[
+                {
+                    "binary": {
+                        "architecture": "x64",
+                        "image_type": "jre",
+                        "os": "linux",
+                        "package": {
+                            "checksum": "5b5ccb3d7c3b4ab1d1b5a2a9e5e3f0c3b7f9c3e1f0a4b7d2c9e8f6a5b4c3d2e1",
+                            "link": "https://example.com/OpenJDK17U-jre_x64_linux_hotspot_17.0.9_9.tar.gz",
+                            "name": "OpenJDK17U-jre_x64_linux_hotspot_17.0.9_9.tar.gz"
+                        }
+                    },
+                    "release_name": "jdk-17.0.9+9",
+                    "version": { "major": 17, "minor": 0, "security": 9 }
+                },
+                {
+                    "binary": {
+                        "architecture": "x64",
+                        "image_type": "jre",
+                        "os": "alpine-linux",
+                        "package": {
+                            "checksum": "5b5ccb3d7c3b4ab1d1b5a2a9e5e3f0c3b7f9c3e1f0a4b7d2c9e8f6a5b4c3d2e1",
+                            "link": "https://example.com/OpenJDK17U-jre_x64_alpine-linux_hotspot_17.0.9_9.tar.gz",
+                            "name": "OpenJDK17U-jre_x64_alpine-linux_hotspot_17.0.9_9.tar.gz"
+                        }
+                    },
+                    "release_name": "jdk-17.0.9+9",
+                    "version": { "major": 17, "minor": 0, "security": 9 }
+                },
+                {
+                    "binary": {
+                        "architecture": "x64",
+                        "image_type": "jre",
+                        "os": "linux",
+                        "package": {
+                            "checksum": "5b5ccb3d7c3b4ab1d1b5a2a9e5e3f0c3b7f9c3e1f0a4b7d2c9e8f6a5b4c3d2e1",
+                            "link": "https://example.com/OpenJDK8U-jre_x64_linux_hotspot_8u392b08.tar.gz",
+                            "name": "OpenJDK8U-jre_x64_linux_hotspot_8u392b08.tar.gz"
+                        }
+                    },
+                    "release_name": "jdk8u392-b08",
+                    "version": { "major": 8, "minor": 0, "security": 392 }
+                }
+            ]"#,
+        )
+        .unwrap();
+
+        let versions = versions_from_assets(assets).unwrap();
+
+        assert_eq!(versions.len(), 1);
+
+        let linux = &versions[&JavaOs::Linux][&JavaArch::X86_64];
+        assert_eq!(linux.len(), 2);
+        assert_eq!(linux[0].id, "jdk-17.0.9+9-jre");
+        assert!(linux[0].checksum.is_some());
+        assert_eq!(linux[0].java_version.to_string(), "17.0.9");
+        assert_eq!(linux[1].id, "jdk8u392-b08-jre");
+        assert_eq!(linux[1].java_version.to_string(), "8.0.392");
+    }
+}
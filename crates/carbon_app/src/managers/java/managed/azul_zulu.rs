@@ -1,23 +1,18 @@
-use super::{Managed, ManagedJavaArchMap, ManagedJavaOsMap, ManagedJavaVersion, Step};
+use super::{
+    download_with_progress, find_java_binary, install_archive, register_managed_java, Managed,
+    ManagedJavaArchMap, ManagedJavaOsMap, ManagedJavaVersion, Step,
+};
 use crate::{
     domain::java::{JavaArch, JavaOs, JavaVersion},
-    managers::java::{java_checker::JavaChecker, scan_and_sync::upsert_java_component_to_db},
+    managers::java::java_checker::JavaChecker,
 };
-use anyhow::Context;
-use carbon_net::{DownloadOptions, Downloadable, Progress};
+use carbon_net::{Checksum, DownloadOptionsBuilder, Downloadable};
 use carbon_repos::db::PrismaClient;
 use carbon_rt_path::{ManagedJavasPath, TempPath};
 use serde::Deserialize;
-use std::{
-    collections::HashMap,
-    path::{Path, PathBuf},
-    sync::Arc,
-};
+use std::{collections::HashMap, sync::Arc};
 use strum::IntoEnumIterator;
-use tokio::{
-    sync::{watch::Sender, RwLock},
-    task::spawn_blocking,
-};
+use tokio::sync::{watch::Sender, RwLock};
 use tracing::{instrument, trace};
 
 #[derive(Debug, Default)]
@@ -27,7 +22,7 @@ pub struct AzulZulu {
 
 #[async_trait::async_trait]
 impl Managed for AzulZulu {
-    #[instrument(skip(self, java_checker, db_client, progress_report))]
+    #[instrument(skip(self, java_checker, db_client, download_options, progress_report))]
     async fn setup<G: JavaChecker + Send + Sync>(
         &self,
         version: &ManagedJavaVersion,
@@ -35,10 +30,16 @@ impl Managed for AzulZulu {
         base_managed_java_path: ManagedJavasPath,
         java_checker: &G,
         db_client: &Arc<PrismaClient>,
+        download_options: DownloadOptionsBuilder,
         progress_report: Sender<Step>,
     ) -> anyhow::Result<String> {
         let progress_report = Arc::new(progress_report);
 
+        let sha256 = version
+            .checksum
+            .clone()
+            .ok_or_else(|| anyhow::anyhow!("No checksum published for {}", version.name))?;
+
         let download_temp_path = tmp_path.to_path().join(&version.name);
 
         trace!("Download path: {:?}", download_temp_path);
@@ -47,168 +48,32 @@ impl Managed for AzulZulu {
 
         let content_length = reqwest::get(download_url).await?.content_length();
 
+        let downloadable = Downloadable::new(download_url, download_temp_path)
+            .with_checksum(Some(Checksum::Sha256(sha256)));
         let downloadable = if let Some(content_length) = content_length {
-            Downloadable::new(download_url, download_temp_path).with_size(content_length)
+            downloadable.with_size(content_length)
         } else {
-            Downloadable::new(download_url, download_temp_path)
+            downloadable
         };
 
         trace!("Downloadable: {:?}", downloadable);
 
-        let (p_sender, mut p_recv) = tokio::sync::watch::channel(Progress::new());
-
-        let progress_report_clone = progress_report.clone();
-        let progress_proxy = tokio::spawn(async move {
-            while p_recv.changed().await.is_ok() {
-                let progress = p_recv.borrow();
-                progress_report_clone.send(Step::Downloading(
-                    progress.current_size,
-                    progress.total_size,
-                ))?;
-            }
+        download_with_progress(
+            &[downloadable.clone()],
+            download_options.concurrency(1),
+            &progress_report,
+        )
+        .await?;
 
-            Ok::<(), anyhow::Error>(())
-        });
-
-        let result = {
-            carbon_net::download_multiple(
-                &[downloadable.clone()],
-                DownloadOptions::builder()
-                    .concurrency(1)
-                    .progress_sender(p_sender)
-                    .build(),
-            )
-            .await?;
-
-            trace!("Download complete");
-
-            progress_proxy.await??;
-
-            let file_handle = std::fs::File::open(&downloadable.path).with_context(|| {
-                format!("Could not open downloaded file: {:?}", &downloadable.path)
-            })?;
-            let mut archive = zip::ZipArchive::new(file_handle).with_context(|| {
-                format!(
-                    "Could not open downloaded file as zip: {:?}",
-                    &downloadable.path
-                )
-            })?;
-
-            let progress_report_clone = progress_report.clone();
-            let version_name = version.name.clone();
-            let main_binary_path = spawn_blocking(move || {
-                let total_archive_files = archive.len() as u64;
-
-                let root_dir = {
-                    let root: PathBuf = archive
-                        .by_index(0)?
-                        .enclosed_name()
-                        .ok_or_else(|| {
-                            anyhow::anyhow!(
-                                "Invalid zip. Cannot get enclosed name for item 0 of zip"
-                            )
-                        })?
-                        .to_owned();
-
-                    root.components()
-                        .next()
-                        .ok_or_else(|| anyhow::anyhow!("No root component"))?
-                        .as_os_str()
-                        .to_owned()
-                };
-
-                let is_single_root_dir = archive.file_names().all(|file_name| {
-                    let path = Path::new(file_name);
-                    let Some(os_str) = path.components().next() else {
-                        return false;
-                    };
-                    os_str.as_os_str() == root_dir
-                });
-
-                let java_managed_path = if is_single_root_dir {
-                    base_managed_java_path.to_path()
-                } else {
-                    let removed_extension = PathBuf::from(version_name).with_extension("");
-                    base_managed_java_path.to_path().join(removed_extension)
-                };
-
-                std::fs::create_dir_all(&java_managed_path).with_context(|| {
-                    format!("Could not create directory: {:?}", &java_managed_path)
-                })?;
-
-                let mut main_binary_path = None;
-
-                for i in 0..archive.len() {
-                    let mut file = archive.by_index(i)?;
-                    let outpath = match file.enclosed_name() {
-                        Some(path) => Path::new(&java_managed_path).join(path),
-                        None => continue,
-                    };
-
-                    if (*file.name()).ends_with("bin/java")
-                        || (*file.name()).ends_with("bin/java.exe")
-                    {
-                        main_binary_path = Some(outpath.clone());
-                    }
+        trace!("Download complete");
 
-                    if (*file.name()).ends_with('/') {
-                        std::fs::create_dir_all(&outpath)?;
-                    } else {
-                        if let Some(p) = outpath.parent() {
-                            if !p.exists() {
-                                std::fs::create_dir_all(p).map_err(|err| {
-                                    anyhow::anyhow!("Can't create directory {:?} - {}", p, err)
-                                })?;
-                            }
-                        }
-
-                        if !outpath.exists() || file.size() != outpath.metadata()?.len() {
-                            let mut outfile = std::fs::File::create(&outpath).map_err(|err| {
-                                anyhow::anyhow!("Can't create file {:?} - {}", outpath, err)
-                            })?;
-
-                            std::io::copy(&mut file, &mut outfile).map_err(|err| {
-                                anyhow::anyhow!(
-                                    "Can't copy file {} -> {:?} - {}",
-                                    file.name(),
-                                    outpath,
-                                    err
-                                )
-                            })?;
-                        }
-                    }
-
-                    // Get and Set permissions
-                    #[cfg(unix)]
-                    {
-                        use std::os::unix::fs::PermissionsExt;
-
-                        if let Some(mode) = file.unix_mode() {
-                            std::fs::set_permissions(
-                                &outpath,
-                                std::fs::Permissions::from_mode(mode),
-                            )
-                            .map_err(|err| {
-                                anyhow::anyhow!(
-                                    "Can't set file permission on {} - {}",
-                                    file.name(),
-                                    err
-                                )
-                            })?;
-                        }
-                    }
-
-                    progress_report_clone.send(Step::Extracting(i as u64, total_archive_files))?;
-                }
-
-                main_binary_path.ok_or_else(|| anyhow::anyhow!("No main binary found"))
-            })
-            .await??;
-
-            progress_report.send(Step::Done)?;
-
-            Ok::<_, anyhow::Error>(main_binary_path)
-        };
+        let result = install_archive(
+            &downloadable.path,
+            &version.name,
+            &base_managed_java_path.to_path(),
+            &progress_report,
+        )
+        .await;
 
         let delete = std::fs::remove_file(&downloadable.path);
 
@@ -216,30 +81,13 @@ impl Managed for AzulZulu {
             tracing::warn!("Could not delete downloaded file: {}", e);
         }
 
-        let main_binary_path = {
-            let tmp = result?;
-            match dunce::canonicalize(&tmp) {
-                Ok(p) => p,
-                Err(_) => tmp,
-            }
-        };
+        let install_path = result?;
+        let main_binary_path = find_java_binary(&install_path)
+            .ok_or_else(|| anyhow::anyhow!("No main binary found"))?;
+
+        progress_report.send(Step::Done)?;
 
-        let java_component = java_checker
-            .get_bin_info(
-                &main_binary_path,
-                crate::domain::java::JavaComponentType::Managed,
-            )
-            .await
-            .with_context(|| {
-                format!(
-                    "Could not get bin info for main binary: {:?}",
-                    &main_binary_path
-                )
-            })?;
-
-        let java_id = upsert_java_component_to_db(db_client, java_component).await?;
-
-        Ok(java_id)
+        register_managed_java(java_checker, db_client, main_binary_path).await
     }
 
     async fn fetch_all_versions(&self) -> anyhow::Result<ManagedJavaOsMap> {
@@ -283,6 +131,7 @@ impl AzulAPI {
                             name: version.name.clone(),
                             download_url: version.download_url.clone(),
                             id: version.package_uuid.clone(),
+                            checksum: version.sha256_hash.clone(),
                             java_version: JavaVersion {
                                 major: version.java_version.first().cloned().ok_or(
                                     anyhow::anyhow!("No major version found for {}", version.name),
@@ -325,7 +174,7 @@ impl AzulAPI {
 
         loop {
             let url = format!(
-                "{AZUL_BASE_URL}?java_package_type=jre&javafx_bundled=false&release_status=ga&availability_types=CA&archive_type=zip&page={}&os={}&arch={}",
+                "{AZUL_BASE_URL}?java_package_type=jre&javafx_bundled=false&release_status=ga&availability_types=CA&archive_type=zip&include_fields=sha256_hash&page={}&os={}&arch={}",
                 page,
                 match os {
                     JavaOs::Windows => "windows",
@@ -382,6 +231,7 @@ pub struct AzulZuluVersion {
     product: String,
     distro_version: Vec<u8>,
     availability_type: String,
+    sha256_hash: Option<String>,
 }
 
 #[cfg(test)]
@@ -1,15 +1,22 @@
-use self::azul_zulu::AzulZulu;
-use super::java_checker::{JavaChecker, RealJavaChecker};
+use self::{adoptium::Adoptium, azul_zulu::AzulZulu, mojang::MojangRuntimes};
+use super::{
+    java_checker::{JavaChecker, RealJavaChecker},
+    scan_and_sync::upsert_java_component_to_db,
+};
 use crate::{
     api::keys::java::GET_SETUP_MANAGED_JAVA_PROGRESS,
-    domain::java::{JavaArch, JavaOs, JavaVendor, JavaVersion},
+    domain::java::{JavaArch, JavaComponentType, JavaOs, JavaVendor, JavaVersion},
 };
+use anyhow::Context;
+use carbon_compression::DecompressOptions;
+use carbon_net::{DownloadOptionsBuilder, Downloadable, Progress};
 use carbon_repos::db::PrismaClient;
 use carbon_rt_path::{ManagedJavasPath, TempPath};
 use serde::Serialize;
 use std::{
     collections::HashMap,
     ops::{Deref, DerefMut},
+    path::{Path, PathBuf},
     sync::Arc,
 };
 use strum::IntoEnumIterator;
@@ -18,9 +25,9 @@ use tokio::sync::{
     Mutex,
 };
 
-// mod adoptopenjdk;
-// mod mojang;
+pub mod adoptium;
 pub mod azul_zulu;
+pub mod mojang;
 
 #[derive(Debug, Default, Clone)]
 pub enum Step {
@@ -37,8 +44,15 @@ pub struct ManagedJavaVersion {
     pub name: String,
     pub download_url: String,
     pub java_version: JavaVersion,
+    /// Published by the vendor, the SHA-256 of the archive for Azul and
+    /// Adoptium and the SHA-1 of the runtime manifest for Mojang.
+    pub checksum: Option<String>,
 }
 
+/// Runtimes are extracted into `<managed javas>/.extracting-<name>` and only
+/// moved next to the other runtimes once complete.
+pub const EXTRACT_DIR_PREFIX: &str = ".extracting-";
+
 #[derive(Debug, Clone, Serialize)]
 pub struct ManagedJavaArchMap(pub HashMap<JavaArch, Vec<ManagedJavaVersion>>);
 
@@ -82,16 +96,194 @@ pub trait Managed {
         base_managed_java_path: ManagedJavasPath,
         java_checker: &G,
         db_client: &Arc<PrismaClient>,
+        download_options: DownloadOptionsBuilder,
         progress_report: Sender<Step>,
     ) -> anyhow::Result<String>;
 
     async fn fetch_all_versions(&self) -> anyhow::Result<ManagedJavaOsMap>;
 }
 
+/// Downloads `files`, reporting the progress as `Step::Downloading`.
+async fn download_with_progress(
+    files: &[Downloadable],
+    download_options: DownloadOptionsBuilder,
+    progress_report: &Arc<Sender<Step>>,
+) -> anyhow::Result<()> {
+    let (p_sender, mut p_recv) = watch::channel(Progress::new());
+
+    let progress_report = progress_report.clone();
+    let progress_proxy = tokio::spawn(async move {
+        while p_recv.changed().await.is_ok() {
+            let progress = p_recv.borrow().clone();
+            progress_report.send(Step::Downloading(
+                progress.current_size,
+                progress.total_size,
+            ))?;
+        }
+
+        Ok::<(), anyhow::Error>(())
+    });
+
+    carbon_net::download_multiple(files, download_options.progress_sender(p_sender).build())
+        .await?;
+
+    progress_proxy.await??;
+
+    Ok(())
+}
+
+/// Extracts `archive` into `dest`, reporting the progress as `Step::Extracting`.
+async fn extract_with_progress(
+    archive: &Path,
+    dest: &Path,
+    progress_report: &Arc<Sender<Step>>,
+) -> anyhow::Result<()> {
+    let (p_sender, mut p_recv) = watch::channel(carbon_compression::Progress::new());
+
+    let progress_report = progress_report.clone();
+    let progress_proxy = tokio::spawn(async move {
+        while p_recv.changed().await.is_ok() {
+            let progress = *p_recv.borrow();
+            progress_report.send(Step::Extracting(progress.current_size, progress.total_size))?;
+        }
+
+        Ok::<(), anyhow::Error>(())
+    });
+
+    carbon_compression::decompress_with_options(
+        archive,
+        dest,
+        DecompressOptions::builder()
+            .progress_sender(p_sender)
+            .build(),
+    )
+    .await
+    .with_context(|| format!("Could not extract {archive:?}"))?;
+
+    progress_proxy.await??;
+
+    Ok(())
+}
+
+/// Removes the extension of an archive, `zulu17-linux_x64.tar.gz` -> `zulu17-linux_x64`
+fn archive_stem(name: &str) -> &str {
+    [".tar.gz", ".tgz", ".zip", ".tar"]
+        .iter()
+        .find_map(|ext| name.strip_suffix(ext))
+        .unwrap_or(name)
+}
+
+/// Replaces `dest` with `source`, which must be on the same filesystem.
+async fn move_into_place(source: &Path, dest: &Path) -> anyhow::Result<()> {
+    if dest.exists() {
+        tokio::fs::remove_dir_all(dest)
+            .await
+            .with_context(|| format!("Could not remove previous install at {dest:?}"))?;
+    }
+
+    tokio::fs::rename(source, dest)
+        .await
+        .with_context(|| format!("Could not move {source:?} to {dest:?}"))?;
+
+    Ok(())
+}
+
+/// Extracts a downloaded runtime archive into its own folder of
+/// `managed_javas`, returning the path of that folder.
+///
+/// Archives with a single root folder keep its name, the others are named
+/// after the archive. Nothing is left behind if the extraction fails.
+async fn install_archive(
+    archive: &Path,
+    archive_name: &str,
+    managed_javas: &Path,
+    progress_report: &Arc<Sender<Step>>,
+) -> anyhow::Result<PathBuf> {
+    let stem = archive_stem(archive_name);
+    let extract_path = managed_javas.join(format!("{EXTRACT_DIR_PREFIX}{stem}"));
+
+    let result = async {
+        if extract_path.exists() {
+            tokio::fs::remove_dir_all(&extract_path).await?;
+        }
+
+        extract_with_progress(archive, &extract_path, progress_report).await?;
+
+        let entries = std::fs::read_dir(&extract_path)?.collect::<Result<Vec<_>, _>>()?;
+        let (source, install_path) = match &entries[..] {
+            [root] if root.file_type()?.is_dir() => {
+                (root.path(), managed_javas.join(root.file_name()))
+            }
+            _ => (extract_path.clone(), managed_javas.join(stem)),
+        };
+
+        move_into_place(&source, &install_path).await?;
+
+        Ok::<_, anyhow::Error>(install_path)
+    }
+    .await;
+
+    if extract_path.exists() {
+        if let Err(e) = tokio::fs::remove_dir_all(&extract_path).await {
+            tracing::warn!("Could not delete extraction folder {extract_path:?}: {e}");
+        }
+    }
+
+    result
+}
+
+/// Finds the `java` binary of an extracted runtime, at `bin/java` for most
+/// of them but nested deeper into macOS bundles.
+fn find_java_binary(root: &Path) -> Option<PathBuf> {
+    let binary_name = if cfg!(windows) { "java.exe" } else { "java" };
+
+    walkdir::WalkDir::new(root)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|entry| {
+            entry.file_type().is_file()
+                && entry.file_name() == binary_name
+                && entry
+                    .path()
+                    .parent()
+                    .and_then(Path::file_name)
+                    .is_some_and(|parent| parent == "bin")
+        })
+        .min_by_key(|entry| entry.depth())
+        .map(|entry| entry.into_path())
+}
+
+/// Checks that the installed binary runs and stores it as a managed java,
+/// returning its id.
+async fn register_managed_java<G: JavaChecker + Send + Sync>(
+    java_checker: &G,
+    db_client: &Arc<PrismaClient>,
+    main_binary_path: PathBuf,
+) -> anyhow::Result<String> {
+    let main_binary_path = dunce::canonicalize(&main_binary_path).unwrap_or(main_binary_path);
+
+    let java_component = java_checker
+        .get_bin_info(&main_binary_path, JavaComponentType::Managed)
+        .await
+        .with_context(|| {
+            format!(
+                "Could not get bin info for main binary: {:?}",
+                &main_binary_path
+            )
+        })?;
+
+    upsert_java_component_to_db(db_client, java_component).await
+}
+
 #[derive(Debug, Default)]
 pub struct ManagedService {
     azul_zulu: AzulZulu,
+    adoptium: Adoptium,
+    mojang: MojangRuntimes,
     pub setup_progress: Arc<Mutex<Step>>,
+    /// Held while a runtime is being installed, so garbage collection
+    /// doesn't remove its extraction folder
+    pub(super) install_lock: Mutex<()>,
 }
 
 impl ManagedService {
@@ -117,6 +309,8 @@ impl ManagedService {
     ) -> anyhow::Result<ManagedJavaOsMap> {
         let versions = match vendor {
             JavaVendor::Azul => self.azul_zulu.fetch_all_versions().await?,
+            JavaVendor::Adoptium => self.adoptium.fetch_all_versions().await?,
+            JavaVendor::Mojang => self.mojang.fetch_all_versions().await?,
         };
 
         Ok(versions)
@@ -131,42 +325,44 @@ impl ManagedService {
         app: crate::App,
         progress_sender: Option<watch::Sender<Step>>,
     ) -> anyhow::Result<String> {
+        let _guard = self.install_lock.lock().await;
+
+        let versions = self.get_versions_for_vendor(vendor).await?;
+        let version = versions
+            .get(&os)
+            .ok_or_else(|| anyhow::anyhow!("No versions for os: {:?}", os))?
+            .get(&arch)
+            .ok_or_else(|| anyhow::anyhow!("No versions for arch: {:?}", arch))?
+            .iter()
+            .find(|v| v.id == id)
+            .ok_or_else(|| anyhow::anyhow!("No version for id: {}", id))?;
+
+        let tmp_path = app.settings_manager().runtime_path.get_temp();
+        let base_managed_java_path = app.settings_manager().runtime_path.get_managed_javas();
+        let db_client = &app.prisma_client.clone();
+        let download_options = app.settings_manager().download_options();
+
+        let (sender, mut recv) = tokio::sync::watch::channel(Step::Idle);
+
+        let progress_ref = Arc::clone(&self.setup_progress);
+
+        tokio::spawn(async move {
+            let app = app.clone();
+
+            while recv.changed().await.is_ok() {
+                let mut progress_ref = progress_ref.lock().await;
+                *progress_ref = recv.borrow().clone();
+                if let Some(progress_sender) = &progress_sender {
+                    let _ = progress_sender.send(recv.borrow().clone());
+                }
+                app.invalidate(GET_SETUP_MANAGED_JAVA_PROGRESS, None);
+                drop(progress_ref);
+                tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+            }
+        });
+
         let java_id = match vendor {
             JavaVendor::Azul => {
-                let versions = self.azul_zulu.fetch_all_versions().await?;
-                let version = versions
-                    .get(&os)
-                    .ok_or_else(|| anyhow::anyhow!("No versions for os: {:?}", os))?
-                    .get(&arch)
-                    .ok_or_else(|| anyhow::anyhow!("No versions for arch: {:?}", arch))?
-                    .iter()
-                    .find(|v| v.id == id)
-                    .ok_or_else(|| anyhow::anyhow!("No version for id: {}", id))?;
-
-                let tmp_path = app.settings_manager().runtime_path.get_temp();
-                let base_managed_java_path =
-                    app.settings_manager().runtime_path.get_managed_javas();
-                let db_client = &app.prisma_client.clone();
-
-                let (sender, mut recv) = tokio::sync::watch::channel(Step::Idle);
-
-                let progress_ref = Arc::clone(&self.setup_progress);
-
-                tokio::spawn(async move {
-                    let app = app.clone();
-
-                    while recv.changed().await.is_ok() {
-                        let mut progress_ref = progress_ref.lock().await;
-                        *progress_ref = recv.borrow().clone();
-                        if let Some(progress_sender) = &progress_sender {
-                            let _ = progress_sender.send(recv.borrow().clone());
-                        }
-                        app.invalidate(GET_SETUP_MANAGED_JAVA_PROGRESS, None);
-                        drop(progress_ref);
-                        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
-                    }
-                });
-
                 self.azul_zulu
                     .setup(
                         version,
@@ -174,6 +370,33 @@ impl ManagedService {
                         base_managed_java_path,
                         &RealJavaChecker,
                         db_client,
+                        download_options,
+                        sender,
+                    )
+                    .await?
+            }
+            JavaVendor::Adoptium => {
+                self.adoptium
+                    .setup(
+                        version,
+                        tmp_path,
+                        base_managed_java_path,
+                        &RealJavaChecker,
+                        db_client,
+                        download_options,
+                        sender,
+                    )
+                    .await?
+            }
+            JavaVendor::Mojang => {
+                self.mojang
+                    .setup(
+                        version,
+                        tmp_path,
+                        base_managed_java_path,
+                        &RealJavaChecker,
+                        db_client,
+                        download_options,
                         sender,
                     )
                     .await?
@@ -183,3 +406,84 @@ impl ManagedService {
         Ok(java_id)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::{io::Write, path::Path, sync::Arc};
+
+    use zip::{write::SimpleFileOptions, ZipWriter};
+
+    use super::{archive_stem, install_archive, Step, EXTRACT_DIR_PREFIX};
+
+    fn write_zip(path: &Path, files: &[(&str, &[u8])]) {
+        let mut zip = ZipWriter::new(std::fs::File::create(path).unwrap());
+
+        for (name, content) in files {
+            zip.start_file(*name, SimpleFileOptions::default()).unwrap();
+            zip.write_all(content).unwrap();
+        }
+
+        zip.finish().unwrap();
+    }
+
+    #[test]
+    fn strips_archive_extensions() {
+        assert_eq!(archive_stem("zulu17-linux_x64.tar.gz"), "zulu17-linux_x64");
+        assert_eq!(archive_stem("zulu17-win_x64.zip"), "zulu17-win_x64");
+        assert_eq!(archive_stem("jre"), "jre");
+    }
+
+    #[tokio::test]
+    async fn test_install_archive() {
+        let dir = tempdir::TempDir::new("carbon_managed_java_test").unwrap();
+        let managed_javas = dir.path().join("managed_javas");
+        std::fs::create_dir_all(&managed_javas).unwrap();
+
+        let (sender, _recv) = tokio::sync::watch::channel(Step::Idle);
+        let progress = Arc::new(sender);
+
+        // single root folder, kept as is
+        let archive = dir.path().join("jdk-17.zip");
+        write_zip(&archive, &[("jdk-17-jre/bin/java", b"java")]);
+
+        let install_path = install_archive(&archive, "jdk-17.zip", &managed_javas, &progress)
+            .await
+            .unwrap();
+        assert_eq!(install_path, managed_javas.join("jdk-17-jre"));
+        assert!(install_path.join("bin/java").exists());
+
+        // multiple roots, named after the archive
+        let archive = dir.path().join("jre-8.zip");
+        write_zip(
+            &archive,
+            &[("bin/java", b"java"), ("release", b"JAVA_VERSION=1.8")],
+        );
+
+        let install_path = install_archive(&archive, "jre-8.zip", &managed_javas, &progress)
+            .await
+            .unwrap();
+        assert_eq!(install_path, managed_javas.join("jre-8"));
+        assert!(install_path.join("bin/java").exists());
+
+        // broken archives leave nothing behind
+        let archive = dir.path().join("broken.zip");
+        std::fs::write(&archive, b"PK\x03\x04 definitely not a zip").unwrap();
+
+        assert!(
+            install_archive(&archive, "broken.zip", &managed_javas, &progress)
+                .await
+                .is_err()
+        );
+
+        let mut installed = std::fs::read_dir(&managed_javas)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect::<Vec<_>>();
+        installed.sort();
+
+        assert_eq!(installed, vec!["jdk-17-jre", "jre-8"]);
+        assert!(!installed
+            .iter()
+            .any(|name| name.starts_with(EXTRACT_DIR_PREFIX)));
+    }
+}
@@ -1,318 +1,433 @@
-use super::{JavaAuto, JavaMeta, JavaProgress};
-use crate::managers::java::constants::JAVA_RUNTIMES_FOLDER;
-use chrono::{DateTime, FixedOffset};
+use super::{
+    download_with_progress, move_into_place, register_managed_java, Managed, ManagedJavaArchMap,
+    ManagedJavaOsMap, ManagedJavaVersion, Step, EXTRACT_DIR_PREFIX,
+};
+use crate::{
+    domain::java::{JavaArch, JavaOs, JavaVersion, SystemJavaProfileName},
+    managers::java::java_checker::JavaChecker,
+};
+use anyhow::{bail, Context};
+use carbon_net::{Checksum, DownloadOptionsBuilder, Downloadable};
+use carbon_repos::db::PrismaClient;
+use carbon_rt_path::{ManagedJavasPath, TempPath};
+use serde::Deserialize;
+use sha1::{Digest, Sha1};
 use std::{
-    borrow::BorrowMut,
     collections::HashMap,
-    path::{Path, PathBuf},
+    path::{Component, Path, PathBuf},
+    sync::Arc,
 };
-use tokio::sync::watch::Sender;
-
-#[derive(Debug, Clone)]
-pub enum RuntimeEdition {
-    Alpha,
-    Beta,
-    Gamma,
-    LegacyJRE,
-    MinecraftExe,
-}
+use strum::IntoEnumIterator;
+use tokio::{
+    sync::{watch::Sender, RwLock},
+    task::spawn_blocking,
+};
+use tracing::{instrument, trace};
 
-impl From<RuntimeEdition> for String {
-    fn from(edition: RuntimeEdition) -> Self {
-        match edition {
-            RuntimeEdition::Alpha => "alpha".to_string(),
-            RuntimeEdition::Beta => "beta".to_string(),
-            RuntimeEdition::Gamma => "gamma".to_string(),
-            RuntimeEdition::LegacyJRE => "legacy-jre".to_string(),
-            RuntimeEdition::MinecraftExe => "minecraft-exe".to_string(),
-        }
-    }
-}
+const MOJANG_RUNTIMES_URL: &str = "https://piston-meta.mojang.com/v1/products/java-runtime/2ec0cc96c44e5a76b9c8b7c39df7210883d12871/all.json";
 
-pub struct MojangRuntime {
-    pub version: RuntimeEdition,
-    extract_folder_name: Option<String>,
-    release_date: Option<DateTime<FixedOffset>>,
-}
-
-impl MojangRuntime {
-    pub fn new(version: RuntimeEdition) -> Self {
-        Self {
-            version,
-            extract_folder_name: None,
-            release_date: None,
-        }
-    }
+/// The runtimes the vanilla launcher installs. Version ids are the
+/// component names, see `SystemJavaProfileName::mojang_component`.
+#[derive(Debug, Default)]
+pub struct MojangRuntimes {
+    versions: Arc<RwLock<ManagedJavaOsMap>>,
 }
 
 #[async_trait::async_trait]
-impl JavaAuto for MojangRuntime {
-    async fn setup(
-        &mut self,
-        base_path: &Path,
-        // TODO: implement progress reporting
-        _progress_report: Sender<JavaProgress>,
-    ) -> anyhow::Result<()> {
-        let mojang_assets = self
-            .get_runtime_assets(&base_path.join(JAVA_RUNTIMES_FOLDER))
+impl Managed for MojangRuntimes {
+    #[instrument(skip(self, java_checker, db_client, download_options, progress_report))]
+    async fn setup<G: JavaChecker + Send + Sync>(
+        &self,
+        version: &ManagedJavaVersion,
+        _tmp_path: TempPath,
+        base_managed_java_path: ManagedJavasPath,
+        java_checker: &G,
+        db_client: &Arc<PrismaClient>,
+        download_options: DownloadOptionsBuilder,
+        progress_report: Sender<Step>,
+    ) -> anyhow::Result<String> {
+        let progress_report = Arc::new(progress_report);
+
+        let sha1 = version
+            .checksum
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("No checksum published for {}", version.name))?;
+
+        let manifest = reqwest::get(&version.download_url)
+            .await?
+            .error_for_status()?
+            .bytes()
             .await?;
 
-        self.release_date = Some(mojang_assets.last_updated);
-        self.extract_folder_name = Some(mojang_assets.extract_folder_name);
+        if hex::encode(Sha1::digest(&manifest)) != sha1.to_lowercase() {
+            bail!(
+                "Checksum mismatch for the runtime manifest of {}",
+                version.name
+            );
+        }
+
+        let manifest = serde_json::from_slice::<RuntimeManifest>(&manifest)?;
 
-        let (progress, mut recv) = tokio::sync::watch::channel(carbon_net::Progress {
-            current_count: 0,
-            current_size: 0,
-        });
+        let install_path = base_managed_java_path
+            .to_path()
+            .join(format!("mojang-{}", version.name));
+        let runtime_path = base_managed_java_path
+            .to_path()
+            .join(format!("{EXTRACT_DIR_PREFIX}mojang-{}", version.name));
 
-        let length = &mojang_assets.download.len();
+        let result = self
+            .install(&manifest, &runtime_path, download_options, &progress_report)
+            .await;
 
-        let task_handle = tokio::spawn(async move {
-            carbon_net::download_multiple(mojang_assets.download, progress).await
-        });
+        let result = match result {
+            Ok(()) => move_into_place(&runtime_path, &install_path).await,
+            Err(e) => Err(e),
+        };
 
-        while (recv.borrow_mut().changed().await).is_ok() {
-            println!("{} / {}", recv.borrow().current_count, length);
+        if runtime_path.exists() {
+            if let Err(e) = tokio::fs::remove_dir_all(&runtime_path).await {
+                tracing::warn!("Could not delete extraction folder {runtime_path:?}: {e}");
+            }
         }
 
-        task_handle.await.unwrap().unwrap();
+        result?;
 
-        // Fix permissions
-        #[cfg(unix)]
-        {
-            use std::os::unix::prelude::PermissionsExt;
-            let java_path = self.locate_binary(base_path).unwrap();
-            let mut perms = std::fs::metadata(&java_path).unwrap().permissions();
-            perms.set_mode(0o777);
-            std::fs::set_permissions(&java_path, perms).unwrap();
-        }
+        // macOS runtimes are bundles, with the binary nested deeper
+        let main_binary_path = manifest
+            .files
+            .iter()
+            .filter(|(name, file)| {
+                matches!(file, RuntimeFile::File { .. })
+                    && (name.ends_with("bin/java") || name.ends_with("bin/java.exe"))
+            })
+            .min_by_key(|(name, _)| name.len())
+            .map(|(name, _)| join_manifest_path(&install_path, name))
+            .ok_or_else(|| anyhow::anyhow!("No main binary found"))??;
 
-        Ok(())
-    }
+        progress_report.send(Step::Done)?;
 
-    async fn get_runtime_assets(&self, runtime_path: &Path) -> anyhow::Result<JavaMeta> {
-        let java_os = match std::env::consts::OS {
-            "linux" => "linux",
-            "windows" => "windows",
-            "macos" => "mac",
-            _ => unreachable!("Unsupported OS"),
-        };
+        register_managed_java(java_checker, db_client, main_binary_path).await
+    }
 
-        let java_arch = match std::env::consts::ARCH {
-            "x86_64" => "x64",
-            "x86" => "x86",
-            "aarch64" => "aarch64",
-            _ => unreachable!("Unsupported architecture"),
-        };
+    async fn fetch_all_versions(&self) -> anyhow::Result<ManagedJavaOsMap> {
+        let mut versions = self.versions.write().await;
+        if versions.is_empty() {
+            let meta = reqwest::get(MOJANG_RUNTIMES_URL)
+                .await?
+                .error_for_status()?
+                .json::<RuntimesMeta>()
+                .await?;
 
-        let url = "https://piston-meta.mojang.com/v1/products/java-runtime/2ec0cc96c44e5a76b9c8b7c39df7210883d12871/all.json".to_string();
+            *versions = versions_from_meta(meta)?;
+        }
 
-        let res = reqwest::get(url).await?;
+        Ok(versions.clone())
+    }
+}
 
-        let mojang_meta = res.json::<MojangMeta>().await?;
+impl MojangRuntimes {
+    /// Downloads the files of a runtime into `runtime_path` and sets up its
+    /// directories, links and permissions.
+    async fn install(
+        &self,
+        manifest: &RuntimeManifest,
+        runtime_path: &Path,
+        download_options: DownloadOptionsBuilder,
+        progress_report: &Arc<Sender<Step>>,
+    ) -> anyhow::Result<()> {
+        if runtime_path.exists() {
+            tokio::fs::remove_dir_all(runtime_path).await?;
+        }
 
-        let runtime_meta = match java_os {
-            "linux" => {
-                if java_arch == "x86" {
-                    mojang_meta.linux_i386
-                } else {
-                    mojang_meta.linux
-                }
-            }
-            "windows" => {
-                if java_arch == "x86" {
-                    mojang_meta.windows_x86
-                } else {
-                    mojang_meta.windows_x64
+        let mut downloadables = Vec::new();
+
+        for (name, file) in &manifest.files {
+            match file {
+                RuntimeFile::File { downloads, .. } => downloadables.push(
+                    Downloadable::new(&downloads.raw.url, join_manifest_path(runtime_path, name)?)
+                        .with_checksum(Some(Checksum::Sha1(downloads.raw.sha1.clone())))
+                        .with_size(downloads.raw.size),
+                ),
+                // make sure links stay inside of the runtime
+                RuntimeFile::Link { target } => {
+                    join_manifest_path(runtime_path, &resolve_link(name, target)?)?;
                 }
+                RuntimeFile::Directory => {}
             }
-            "mac" => {
-                if java_arch == "aarch64" {
-                    mojang_meta.mac_os_arm64.or(mojang_meta.mac_os)
-                } else {
-                    mojang_meta.mac_os
+        }
+
+        trace!("Downloading {} files", downloadables.len());
+
+        download_with_progress(&downloadables, download_options, progress_report).await?;
+
+        trace!("Download complete");
+
+        // the manifest lists files one by one, what's left to "extract" are
+        // directories, links and permissions
+        let files = manifest
+            .files
+            .iter()
+            .map(|(name, file)| Ok((join_manifest_path(runtime_path, name)?, file.clone())))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let runtime_path = runtime_path.to_path_buf();
+        let progress_report = progress_report.clone();
+        spawn_blocking(move || {
+            let total = files.len() as u64;
+
+            for (i, (path, file)) in files.iter().enumerate() {
+                match file {
+                    RuntimeFile::Directory => {
+                        std::fs::create_dir_all(path)
+                            .with_context(|| format!("Can't create directory {path:?}"))?;
+                    }
+                    RuntimeFile::File { executable, .. } => {
+                        #[cfg(unix)]
+                        {
+                            use std::os::unix::fs::PermissionsExt;
+
+                            let mode = if *executable { 0o755 } else { 0o644 };
+                            std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode))
+                                .with_context(|| format!("Can't set file permission on {path:?}"))?;
+                        }
+                        #[cfg(not(unix))]
+                        let _ = executable;
+                    }
+                    #[cfg(unix)]
+                    RuntimeFile::Link { target } => {
+                        let parent = path.parent().unwrap_or(&runtime_path);
+                        std::fs::create_dir_all(parent)?;
+                        if path.symlink_metadata().is_ok() {
+                            std::fs::remove_file(path)?;
+                        }
+                        std::os::unix::fs::symlink(target, path)
+                            .with_context(|| format!("Can't create link {path:?}"))?;
+                    }
+                    // only macOS runtimes contain links
+                    #[cfg(not(unix))]
+                    RuntimeFile::Link { .. } => {}
                 }
+
+                progress_report.send(Step::Extracting(i as u64 + 1, total))?;
             }
-            _ => unreachable!("Unsupported OS"),
-        }
-        .ok_or(anyhow::anyhow!(
-            "No Java runtime found for your OS and architecture"
-        ))?;
-
-        let runtime_list = match self.version {
-            RuntimeEdition::Alpha => runtime_meta.java_runtime_alpha,
-            RuntimeEdition::Beta => runtime_meta.java_runtime_beta,
-            RuntimeEdition::Gamma => runtime_meta.java_runtime_gamma,
-            RuntimeEdition::LegacyJRE => runtime_meta.jre_legacy,
-            RuntimeEdition::MinecraftExe => runtime_meta.minecraft_java_exe,
-        };
 
-        let runtime = runtime_list.first().ok_or(anyhow::anyhow!(
-            "No Java runtime found for your OS and architecture"
-        ))?;
+            Ok::<_, anyhow::Error>(())
+        })
+        .await??;
+
+        Ok(())
+    }
+}
 
-        let res = reqwest::get(&runtime.manifest.url).await?;
+/// Joins a path from a runtime manifest onto `root`, refusing paths that
+/// would end up outside of it.
+fn join_manifest_path(root: &Path, name: &str) -> anyhow::Result<PathBuf> {
+    let relative = Path::new(name);
 
-        let runtime_meta = res.json::<MojangRuntimeJDKMeta>().await?;
+    if !relative
+        .components()
+        .all(|component| matches!(component, Component::Normal(_)))
+    {
+        bail!("Invalid path in runtime manifest: {name}");
+    }
 
-        let mut assets = JavaMeta {
-            last_updated: chrono::DateTime::parse_from_rfc3339(&runtime.version.released)?,
-            extract_folder_name: "jre.bundle".to_string(),
-            download: vec![],
-        };
+    Ok(root.join(relative))
+}
 
-        for (name, asset) in runtime_meta.files {
-            let path_buf = PathBuf::from(Into::<String>::into(self.version.clone()));
-            let path = runtime_path.join("mojang").join(path_buf).join(name);
-            let downloadable = asset
-                .downloads
-                .and_then(|d| d.raw)
-                .map(|d| carbon_net::Downloadable::new(d.url, path));
-
-            if let Some(downloadable) = downloadable {
-                if asset._type == "file" {
-                    assets.download.push(downloadable);
+/// Resolves the target of a link relative to the root of the runtime.
+fn resolve_link(name: &str, target: &str) -> anyhow::Result<String> {
+    if target.starts_with('/') {
+        bail!("Link {name} points outside of the runtime: {target}");
+    }
+
+    let mut resolved = name.split('/').collect::<Vec<_>>();
+    resolved.pop();
+
+    for part in target.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                if resolved.pop().is_none() {
+                    bail!("Link {name} points outside of the runtime: {target}");
                 }
             }
+            part => resolved.push(part),
         }
-
-        Ok(assets)
     }
 
-    fn locate_binary(&self, base_path: &Path) -> anyhow::Result<PathBuf> {
-        let path = match std::env::consts::OS {
-            "linux" => base_path
-                .join(JAVA_RUNTIMES_FOLDER)
-                .join("mojang")
-                .join(Into::<String>::into(self.version.clone()))
-                .join("bin")
-                .join("java"),
-            "windows" => base_path
-                .join(JAVA_RUNTIMES_FOLDER)
-                .join("mojang")
-                .join(Into::<String>::into(self.version.clone()))
-                .join("bin")
-                .join("java.exe"),
-            "macos" => base_path
-                .join(JAVA_RUNTIMES_FOLDER)
-                .join("mojang")
-                .join(Into::<String>::into(self.version.clone()))
-                .join("jre.bundle")
-                .join("Contents")
-                .join("Home")
-                .join("bin")
-                .join("java"),
-            _ => unreachable!("Unsupported OS"),
-        };
+    Ok(resolved.join("/"))
+}
 
-        Ok(path)
+/// Mojang writes java 8 versions as `8u51`
+fn parse_runtime_version(name: &str) -> anyhow::Result<JavaVersion> {
+    match name.split_once('u') {
+        Some((major, update)) => {
+            let mut version = JavaVersion::from_major(major.parse()?);
+            version.patch = update.parse::<u32>()?.to_string();
+            Ok(version)
+        }
+        None => JavaVersion::try_from(name),
     }
+}
 
-    async fn check_for_updates(&self, runtime_path: &Path) -> anyhow::Result<bool> {
-        let mojang_assets = self.get_runtime_assets(runtime_path).await?;
+fn versions_from_meta(meta: RuntimesMeta) -> anyhow::Result<ManagedJavaOsMap> {
+    let mut results = ManagedJavaOsMap::default();
+
+    for (platform, components) in meta {
+        let (os, arch) = match &*platform {
+            "linux" => (JavaOs::Linux, JavaArch::X86_64),
+            "linux-i386" => (JavaOs::Linux, JavaArch::X86_32),
+            "mac-os" => (JavaOs::MacOs, JavaArch::X86_64),
+            "mac-os-arm64" => (JavaOs::MacOs, JavaArch::Arm64),
+            "windows-x64" => (JavaOs::Windows, JavaArch::X86_64),
+            "windows-x86" => (JavaOs::Windows, JavaArch::X86_32),
+            "windows-arm64" => (JavaOs::Windows, JavaArch::Arm64),
+            // gamecore
+            _ => continue,
+        };
 
-        let updated_at = mojang_assets.last_updated.timestamp();
+        let versions = results
+            .entry(os)
+            .or_insert_with(|| ManagedJavaArchMap(HashMap::new()))
+            .entry(arch)
+            .or_insert_with(Vec::new);
 
-        if updated_at
-            > self
-                .release_date
-                .ok_or(anyhow::anyhow!("No release date found for this runtime"))?
-                .timestamp()
-        {
-            return Ok(false);
-        }
+        for profile in SystemJavaProfileName::iter() {
+            let component = profile.mojang_component();
 
-        Ok(true)
-    }
+            if versions.iter().any(|v| v.id == component) {
+                continue;
+            }
 
-    async fn update(&mut self) -> anyhow::Result<()> {
-        todo!()
+            let Some(runtime) = components.get(component).and_then(|r| r.first()) else {
+                continue;
+            };
+
+            versions.push(ManagedJavaVersion {
+                id: component.to_string(),
+                name: format!("{component}-{}", runtime.version.name),
+                download_url: runtime.manifest.url.clone(),
+                checksum: Some(runtime.manifest.sha1.clone()),
+                java_version: parse_runtime_version(&runtime.version.name)?,
+            });
+        }
     }
-}
 
-#[derive(Debug, serde::Deserialize)]
-#[serde(rename_all = "kebab-case")]
-struct MojangMeta {
-    linux: Option<OsRuntime>,
-    linux_i386: Option<OsRuntime>,
-    mac_os: Option<OsRuntime>,
-    mac_os_arm64: Option<OsRuntime>,
-    windows_x86: Option<OsRuntime>,
-    windows_x64: Option<OsRuntime>,
+    Ok(results)
 }
 
-#[derive(Debug, serde::Deserialize)]
-#[serde(rename_all = "kebab-case")]
-struct OsRuntime {
-    java_runtime_alpha: Vec<Runtime>,
-    java_runtime_beta: Vec<Runtime>,
-    java_runtime_gamma: Vec<Runtime>,
-    jre_legacy: Vec<Runtime>,
-    minecraft_java_exe: Vec<Runtime>,
-}
+/// Platform -> component -> runtimes
+type RuntimesMeta = HashMap<String, HashMap<String, Vec<Runtime>>>;
 
-#[derive(Debug, serde::Deserialize)]
+#[derive(Debug, Deserialize)]
 struct Runtime {
-    manifest: MojangDownloadable,
-    version: Version,
+    manifest: MojangDownload,
+    version: RuntimeVersion,
 }
 
-#[derive(Debug, serde::Deserialize)]
-struct MojangDownloadable {
+#[derive(Debug, Clone, Deserialize)]
+struct MojangDownload {
     sha1: String,
     size: u64,
     url: String,
 }
 
-#[derive(Debug, serde::Deserialize)]
-struct Version {
+#[derive(Debug, Deserialize)]
+struct RuntimeVersion {
     name: String,
-    released: String,
 }
 
-#[derive(Debug, serde::Deserialize)]
-struct MojangRuntimeJDKMeta {
-    files: HashMap<String, MojangRuntimeJDKMetaAsset>,
+#[derive(Debug, Deserialize)]
+struct RuntimeManifest {
+    files: HashMap<String, RuntimeFile>,
 }
 
-#[derive(Debug, serde::Deserialize)]
-struct MojangRuntimeJDKMetaAsset {
-    #[serde(rename = "type")]
-    _type: String,
-    downloads: Option<MojangRuntimeJDKMetaAssetDownloads>,
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum RuntimeFile {
+    Directory,
+    File {
+        downloads: RuntimeFileDownloads,
+        #[serde(default)]
+        executable: bool,
+    },
+    Link {
+        target: String,
+    },
 }
 
-#[derive(Debug, serde::Deserialize)]
-struct MojangRuntimeJDKMetaAssetDownloads {
-    lzma: Option<MojangDownloadable>,
-    raw: Option<MojangDownloadable>,
+#[derive(Debug, Clone, Deserialize)]
+struct RuntimeFileDownloads {
+    raw: MojangDownload,
 }
 
 #[cfg(test)]
-mod tests {
-    use tokio::sync::watch::channel;
-
+mod test {
     use super::*;
 
-    #[tokio::test]
-    async fn test_setup_mojang_runtime_jre() {
-        let current_path = std::env::current_dir().unwrap();
-
-        let mut mojang = MojangRuntime::new(RuntimeEdition::Gamma);
-
-        let (tx, _) = channel(JavaProgress::default());
-
-        mojang.setup(&current_path, tx).await.unwrap();
-
-        let java_path = mojang.locate_binary(&current_path).unwrap();
-
-        assert!(java_path.exists());
-
-        let java_version = std::process::Command::new(java_path)
-            .arg("-version")
-            .output()
-            .unwrap();
+    #[test]
+    fn test_versions_from_meta() {
+        let meta: RuntimesMeta = serde_json::from_str(
+            r#"{
+                "gamecore": {},
+                "linux": {
+                    "java-runtime-gamma": [{
+                        "availability": { "group": 1, "progress": 100 },
+                        "manifest": { "sha1": "a", "size": 1, "url": "https://example.com/gamma.json" },
+                        "version": { "name": "17.0.8", "released": "2023-07-27T07:50:32+00:00" }
+                    }],
+                    "jre-legacy": [{
+                        "availability": { "group": 1, "progress": 100 },
+                        "manifest": { "sha1": "b", "size": 1, "url": "https://example.com/legacy.json" },
+                        "version": { "name": "8u51", "released": "2022-03-11T17:09:41+00:00" }
+                    }],
+                    "java-runtime-omega": [{
+                        "availability": { "group": 1, "progress": 100 },
+                        "manifest": { "sha1": "c", "size": 1, "url": "https://example.com/omega.json" },
+                        "version": { "name": "99.0.0", "released": "2099-01-01T00:00:00+00:00" }
+                    }]
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let versions = versions_from_meta(meta).unwrap();
+        let linux = &versions[&JavaOs::Linux][&JavaArch::X86_64];
+
+        assert_eq!(linux.len(), 2);
+
+        let legacy = linux.iter().find(|v| v.id == "jre-legacy").unwrap();
+        assert_eq!(legacy.name, "jre-legacy-8u51");
+        assert_eq!(legacy.java_version.to_string(), "8.0.51");
+        assert!(
+            SystemJavaProfileName::LegacyFixed1.is_java_version_compatible(&legacy.java_version)
+        );
+
+        let gamma = linux.iter().find(|v| v.id == "java-runtime-gamma").unwrap();
+        assert_eq!(gamma.download_url, "https://example.com/gamma.json");
+        assert_eq!(gamma.checksum.as_deref(), Some("a"));
+        assert!(SystemJavaProfileName::Gamma.is_java_version_compatible(&gamma.java_version));
+    }
 
-        assert!(java_version.status.success());
+    #[test]
+    fn test_manifest_paths() {
+        let root = Path::new("runtime");
+
+        assert_eq!(
+            join_manifest_path(root, "bin/java").unwrap(),
+            root.join("bin/java")
+        );
+        assert!(join_manifest_path(root, "../bin/java").is_err());
+        assert!(join_manifest_path(root, "/bin/java").is_err());
+
+        assert_eq!(
+            resolve_link(
+                "jre.bundle/Contents/MacOS/libjli.dylib",
+                "../Home/lib/libjli.dylib"
+            )
+            .unwrap(),
+            "jre.bundle/Contents/Home/lib/libjli.dylib"
+        );
+        assert!(resolve_link("lib/a", "../../b").is_err());
+        assert!(resolve_link("lib/a", "/usr/lib/b").is_err());
     }
 }
@@ -1,16 +1,17 @@
 use self::{
-    discovery::Discovery,
+    discovery::{Discovery, RealDiscovery},
     java_checker::JavaChecker,
     managed::{ManagedService, Step},
+    requirement::{JavaRejection, JavaRequirement},
 };
 use super::ManagerRef;
 use crate::{
     api::keys::java::{GET_AVAILABLE_JAVAS, GET_JAVA_PROFILES},
     domain::{
-        instance::info::StandardVersion,
+        instance::info::{JavaOverride, StandardVersion},
         java::{
             Java, JavaArch, JavaComponent, JavaComponentType, JavaOs, JavaProfile, JavaVendor,
-            SystemJavaProfileName, SYSTEM_JAVA_PROFILE_NAME_PREFIX,
+            JavaVersion, SystemJavaProfileName, SYSTEM_JAVA_PROFILE_NAME_PREFIX,
         },
     },
     managers::java::java_checker::RealJavaChecker,
@@ -21,7 +22,8 @@ use carbon_repos::{
     pcr::{prisma_errors::query_engine::UniqueKeyViolation, QueryError},
 };
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
+    ffi::OsString,
     path::{Path, PathBuf},
     sync::Arc,
 };
@@ -34,6 +36,7 @@ pub mod discovery;
 pub mod java_checker;
 pub mod managed;
 mod parser;
+pub mod requirement;
 pub mod scan_and_sync;
 pub mod utils;
 
@@ -383,6 +386,127 @@ impl ManagerRef<'_, JavaManager> {
         Ok(())
     }
 
+    /// Removes the managed javas no profile or instance java path override
+    /// uses, along with the folders of `managed_javas` not holding a used java,
+    /// like the leftovers of interrupted installs. Returns the removed folders.
+    pub async fn garbage_collect_managed_javas<G>(
+        &self,
+        java_checker: &G,
+    ) -> anyhow::Result<Vec<PathBuf>>
+    where
+        G: JavaChecker,
+    {
+        use carbon_repos::db::java;
+
+        let _guard = self.managed_service.install_lock.lock().await;
+
+        let runtime_path = &self.app.settings_manager().runtime_path;
+        let root_managed_path = runtime_path.get_managed_javas().to_path();
+
+        let override_folders = {
+            let instance_manager = self.app.instance_manager();
+            let instances = instance_manager.instances.read().await;
+
+            instances
+                .values()
+                .filter_map(|instance| instance.data().ok())
+                .filter_map(|data| match &data.config.game_configuration.java_override {
+                    Some(JavaOverride::Path(Some(path))) => {
+                        managed_java_folder(&root_managed_path, Path::new(path))
+                    }
+                    _ => None,
+                })
+                .collect::<HashSet<_>>()
+        };
+
+        // the sync would delete the broken javas only an override uses
+        scan_and_sync::scan_and_sync_managed_keeping(
+            &self.app.prisma_client,
+            &RealDiscovery::new(runtime_path.clone()),
+            java_checker,
+            |path| {
+                managed_java_folder(&root_managed_path, path)
+                    .is_some_and(|folder| override_folders.contains(&folder))
+            },
+        )
+        .await?;
+
+        let used_java_ids = self
+            .app
+            .prisma_client
+            .java_profile()
+            .find_many(vec![])
+            .exec()
+            .await?
+            .into_iter()
+            .filter_map(|profile| profile.java_id)
+            .collect::<HashSet<_>>();
+
+        let managed_javas = self
+            .app
+            .prisma_client
+            .java()
+            .find_many(vec![java::r#type::equals(
+                JavaComponentType::Managed.to_string(),
+            )])
+            .exec()
+            .await?;
+
+        let mut used_paths = Vec::new();
+
+        for managed_java in managed_javas {
+            let overridden = managed_java_folder(&root_managed_path, Path::new(&managed_java.path))
+                .is_some_and(|folder| override_folders.contains(&folder));
+
+            if used_java_ids.contains(&managed_java.id) || overridden {
+                used_paths.push(PathBuf::from(managed_java.path));
+            } else {
+                self.app
+                    .prisma_client
+                    .java()
+                    .delete(java::id::equals(managed_java.id))
+                    .exec()
+                    .await?;
+            }
+        }
+
+        let mut removed = Vec::new();
+
+        let Ok(entries) = std::fs::read_dir(&root_managed_path) else {
+            return Ok(removed);
+        };
+
+        for entry in entries {
+            let entry = entry?;
+            let path = entry.path();
+
+            // paths in the db are canonicalized
+            let canonical_path = dunce::canonicalize(&path).unwrap_or_else(|_| path.clone());
+            if used_paths
+                .iter()
+                .any(|used| used.starts_with(&canonical_path) || used.starts_with(&path))
+                || override_folders.contains(&entry.file_name())
+            {
+                continue;
+            }
+
+            debug!("Removing unused managed java folder {path:?}");
+
+            if entry.file_type()?.is_dir() {
+                tokio::fs::remove_dir_all(&path).await?;
+            } else {
+                tokio::fs::remove_file(&path).await?;
+            }
+
+            removed.push(path);
+        }
+
+        self.app.invalidate(GET_JAVA_PROFILES, None);
+        self.app.invalidate(GET_AVAILABLE_JAVAS, None);
+
+        Ok(removed)
+    }
+
     #[tracing::instrument(skip(self))]
     pub async fn get_usable_java_for_profile_name(
         self,
@@ -479,22 +603,121 @@ impl ManagerRef<'_, JavaManager> {
         }))
     }
 
-    /// Will return Some(path) if configured to automatically install.
-    /// Will return None if user intervention is required.
-    pub async fn require_java_install(
+    /// Picks the best java satisfying `requirement` among the known ones,
+    /// installing a managed one if none does and `install` is set.
+    ///
+    /// An installed java is assigned to `target_profile`, or to the first system
+    /// profile it is compatible with, so it stays in use.
+    pub async fn resolve_java(
         self,
-        target_profile: SystemJavaProfileName,
-        update_target_profile: bool,
+        requirement: &JavaRequirement,
+        install: bool,
+        target_profile: Option<SystemJavaProfileName>,
         progress: Option<watch::Sender<Step>>,
-    ) -> anyhow::Result<Option<JavaComponent>> {
-        use carbon_repos::db::java::UniqueWhereParam;
+    ) -> anyhow::Result<JavaResolution> {
+        use carbon_repos::db::java;
+
+        if let (true, Some(max)) = (requirement.is_conflicting(), &requirement.max) {
+            bail!(
+                "No java can satisfy both Java {} or newer required by {} and Java {} or older required by {}",
+                requirement.min.major,
+                requirement.min.source,
+                max.major,
+                max.source
+            );
+        }
+
+        let mut rejected = Vec::new();
+        let mut candidates = Vec::new();
+
+        for java in self
+            .app
+            .prisma_client
+            .java()
+            .find_many(vec![])
+            .exec()
+            .await?
+        {
+            let version = JavaVersion::try_from(&*java.full_version).ok();
+
+            let reason = match &version {
+                _ if !java.is_valid => JavaRejection::Invalid,
+                None => JavaRejection::Invalid,
+                Some(version) => match requirement.check(version) {
+                    Ok(()) => {
+                        candidates.push(java);
+                        continue;
+                    }
+                    Err(reason) => reason,
+                },
+            };
+
+            rejected.push(RejectedJava {
+                path: java.path,
+                version,
+                reason,
+            });
+        }
+
+        candidates.sort_by_cached_key(|java| {
+            let version = JavaVersion::try_from(&*java.full_version)
+                .expect("candidates have a valid version");
+            std::cmp::Reverse(requirement.rank(&version))
+        });
+
+        for java in candidates {
+            let bin_result = RealJavaChecker::get_bin_info(
+                &RealJavaChecker,
+                Path::new(&java.path),
+                (&*java.r#type).try_into()?,
+            )
+            .await;
+
+            match bin_result {
+                // the binary could have been swapped since it was last checked
+                Ok(component) => match requirement.check(&component.version) {
+                    Ok(()) => {
+                        return Ok(JavaResolution {
+                            java: Some(component),
+                            rejected,
+                        })
+                    }
+                    Err(reason) => rejected.push(RejectedJava {
+                        path: java.path,
+                        version: Some(component.version),
+                        reason,
+                    }),
+                },
+                Err(err) => {
+                    tracing::warn!("Java {} is not usable: {}", java.id, err);
+
+                    self.app
+                        .prisma_client
+                        .java()
+                        .update(java::id::equals(java.id), vec![java::is_valid::set(false)])
+                        .exec()
+                        .await?;
+
+                    rejected.push(RejectedJava {
+                        path: java.path,
+                        version: JavaVersion::try_from(&*java.full_version).ok(),
+                        reason: JavaRejection::Invalid,
+                    });
+                }
+            }
+        }
+
+        if !install {
+            return Ok(JavaResolution {
+                java: None,
+                rejected,
+            });
+        }
 
         static LOCK: Mutex<()> = Mutex::const_new(());
         let _guard = LOCK.lock().await;
 
         let versions = self
-            .app
-            .java_manager()
             .managed_service
             .get_versions_for_vendor(JavaVendor::Azul)
             .await?;
@@ -502,25 +725,26 @@ impl ManagerRef<'_, JavaManager> {
         let current_os = JavaOs::get_current_os()?;
         let current_arch = JavaArch::get_current_arch()?;
 
+        let version = versions
+            .get(&current_os)
+            .and_then(|for_arch| for_arch.get(&current_arch))
+            .and_then(|versions| {
+                versions
+                    .iter()
+                    .filter(|v| requirement.check(&v.java_version).is_ok())
+                    .max_by_key(|v| requirement.rank(&v.java_version))
+            })
+            .ok_or_else(|| {
+                anyhow::anyhow!("unable to find an automatically installable {requirement}")
+            })?;
+
         let id = self
             .managed_service
             .setup_managed(
                 current_os,
                 current_arch,
                 JavaVendor::Azul,
-                versions
-                    .get(&current_os)
-                    .and_then(|for_arch| for_arch.get(&current_arch))
-                    .and_then(|versions| {
-                        versions
-                            .iter()
-                            .find(|v| target_profile.is_java_version_compatible(&v.java_version))
-                    })
-                    .ok_or_else(|| {
-                        anyhow::anyhow!("unable to find automatically installable java version")
-                    })?
-                    .id
-                    .clone(),
+                version.id.clone(),
                 self.app.clone(),
                 progress,
             )
@@ -530,93 +754,258 @@ impl ManagerRef<'_, JavaManager> {
             .app
             .prisma_client
             .java()
-            .find_unique(carbon_repos::db::java::id::equals(id.clone()))
+            .find_unique(java::id::equals(id.clone()))
             .exec()
-            .await?;
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("downloaded java was not present in db"))?;
 
-        let java = match java {
-            Some(java) => RealJavaChecker::get_bin_info(
-                &RealJavaChecker,
-                Path::new(&java.path),
-                (&*java.r#type).try_into()?,
-            )
-            .await
-            .map_err(|_| anyhow::anyhow!("downloaded java was not runnable"))?,
-            None => anyhow::bail!("downloaded java was not present in db"),
-        };
+        let java = RealJavaChecker::get_bin_info(
+            &RealJavaChecker,
+            Path::new(&java.path),
+            (&*java.r#type).try_into()?,
+        )
+        .await
+        .map_err(|_| anyhow::anyhow!("downloaded java was not runnable"))?;
+
+        let matching_profile = target_profile
+            .filter(|profile| profile.is_java_version_compatible(&java.version))
+            .or_else(|| {
+                SystemJavaProfileName::iter()
+                    .find(|profile| profile.is_java_version_compatible(&java.version))
+            });
 
-        if update_target_profile {
+        if let Some(profile) = matching_profile {
             self.app
                 .prisma_client
                 .java_profile()
                 .update(
-                    carbon_repos::db::java_profile::name::equals(target_profile.to_string()),
+                    carbon_repos::db::java_profile::name::equals(profile.to_string()),
                     vec![carbon_repos::db::java_profile::java::connect(
-                        carbon_repos::db::java::id::equals(id.clone()),
+                        java::id::equals(id.clone()),
                     )],
                 )
                 .exec()
                 .await?;
+        }
 
-            let system_profiles_in_db = self
-                .app
-                .prisma_client
-                .java_profile()
-                .find_many(vec![
-                    carbon_repos::db::java_profile::is_system_profile::equals(true),
-                ])
-                .exec()
-                .await?;
+        // fill the other system profiles left empty with the new java
+        scan_and_sync::sync_system_java_profiles(&self.app.prisma_client).await?;
+        self.app.invalidate(GET_JAVA_PROFILES, None);
+        self.app.invalidate(GET_AVAILABLE_JAVAS, None);
 
-            for system_profile in system_profiles_in_db {
-                let system_profile_name = SystemJavaProfileName::try_from(&*system_profile.name)?;
-                if system_profile_name == target_profile
-                    || !system_profile_name.is_java_version_compatible(&java.version)
-                    || system_profile.java_id.is_some()
-                {
-                    continue;
-                }
+        Ok(JavaResolution {
+            java: Some(java),
+            rejected,
+        })
+    }
+}
 
-                self.app
-                    .prisma_client
-                    .java_profile()
-                    .update(
-                        carbon_repos::db::java_profile::name::equals(system_profile.name),
-                        vec![carbon_repos::db::java_profile::java::connect(
-                            carbon_repos::db::java::id::equals(id.clone()),
-                        )],
-                    )
-                    .exec()
-                    .await?;
-            }
-            self.app.invalidate(GET_JAVA_PROFILES, None);
-        }
+/// The folder of `managed_javas` holding `path`, if it is in one
+fn managed_java_folder(root_managed_path: &Path, path: &Path) -> Option<OsString> {
+    let canonical_root = dunce::canonicalize(root_managed_path).ok();
+    let canonical_path = dunce::canonicalize(path).ok();
+
+    [
+        (Some(root_managed_path), Some(path)),
+        (canonical_root.as_deref(), canonical_path.as_deref()),
+    ]
+    .into_iter()
+    .find_map(|(root, path)| {
+        path?
+            .strip_prefix(root?)
+            .ok()?
+            .components()
+            .next()
+            .map(|folder| folder.as_os_str().to_os_string())
+    })
+}
 
-        Ok(Some(java))
-    }
+#[derive(Debug)]
+pub struct JavaResolution {
+    pub java: Option<JavaComponent>,
+    /// Known javas that were not picked and why
+    pub rejected: Vec<RejectedJava>,
+}
+
+#[derive(Debug)]
+pub struct RejectedJava {
+    pub path: String,
+    pub version: Option<JavaVersion>,
+    pub reason: JavaRejection,
 }
 
 #[cfg(test)]
 mod test {
+    use std::collections::HashSet;
+
+    use super::{java_checker::MockJavaCheckerInvalid, requirement::JavaRequirement};
     use crate::{
-        domain::java::{JavaArch, JavaOs, JavaVendor, SystemJavaProfileName},
+        domain::{
+            instance::{
+                info::{GameVersion, JavaOverride, StandardVersion},
+                InstanceSettingsUpdate,
+            },
+            java::{JavaArch, JavaOs, JavaVendor, SystemJavaProfileName},
+        },
+        managers::instance::InstanceVersionSource,
         setup_managers_for_test,
     };
 
+    #[tokio::test]
+    async fn test_garbage_collect_managed_javas() {
+        let app = setup_managers_for_test().await;
+
+        let root = app
+            .settings_manager()
+            .runtime_path
+            .get_managed_javas()
+            .to_path();
+
+        for folder in [
+            "used",
+            "overridden",
+            "unused",
+            ".extracting-partial",
+            "orphan",
+        ] {
+            std::fs::create_dir_all(root.join(folder).join("bin")).unwrap();
+            std::fs::write(root.join(folder).join("bin").join("java"), "").unwrap();
+        }
+
+        let mut ids = Vec::new();
+        for folder in ["used", "overridden", "unused"] {
+            let java = app
+                .prisma_client
+                .java()
+                .create(
+                    root.join(folder)
+                        .join("bin")
+                        .join("java")
+                        .to_string_lossy()
+                        .to_string(),
+                    17,
+                    "17.0.9".to_string(),
+                    "managed".to_string(),
+                    "linux".to_string(),
+                    "x64".to_string(),
+                    "Eclipse Adoptium".to_string(),
+                    vec![],
+                )
+                .exec()
+                .await
+                .unwrap();
+            ids.push(java.id);
+        }
+
+        app.prisma_client
+            .java_profile()
+            .create(
+                "custom".to_string(),
+                vec![carbon_repos::db::java_profile::java::connect(
+                    carbon_repos::db::java::id::equals(ids[0].clone()),
+                )],
+            )
+            .exec()
+            .await
+            .unwrap();
+
+        let default_group = app.instance_manager().get_default_group().await.unwrap();
+        let instance_id = app
+            .instance_manager()
+            .create_instance(
+                default_group,
+                String::from("overridden java"),
+                false,
+                InstanceVersionSource::Version(GameVersion::Standard(StandardVersion {
+                    release: String::from("1.20.1"),
+                    modloaders: HashSet::new(),
+                })),
+                String::new(),
+            )
+            .await
+            .unwrap();
+
+        app.instance_manager()
+            .update_instance(InstanceSettingsUpdate {
+                instance_id,
+                name: None,
+                use_loaded_icon: None,
+                notes: None,
+                version: None,
+                modloader: None,
+                global_java_args: None,
+                extra_java_args: None,
+                memory: None,
+                java_override: Some(Some(JavaOverride::Path(Some(
+                    root.join("overridden")
+                        .join("bin")
+                        .join("java")
+                        .to_string_lossy()
+                        .to_string(),
+                )))),
+                pre_launch_hook: None,
+                post_exit_hook: None,
+                wrapper_command: None,
+                game_resolution: None,
+                modpack_locked: None,
+                mod_sources: None,
+            })
+            .await
+            .unwrap();
+
+        let mut removed = app
+            .java_manager()
+            .garbage_collect_managed_javas(&MockJavaCheckerInvalid)
+            .await
+            .unwrap();
+        removed.sort();
+
+        assert_eq!(
+            removed,
+            vec![
+                root.join(".extracting-partial"),
+                root.join("orphan"),
+                root.join("unused"),
+            ]
+        );
+        assert!(root.join("used").exists());
+        assert!(root.join("overridden").exists());
+
+        let javas = app
+            .prisma_client
+            .java()
+            .find_many(vec![])
+            .exec()
+            .await
+            .unwrap();
+        let mut java_ids = javas.into_iter().map(|java| java.id).collect::<Vec<_>>();
+        java_ids.sort();
+        let mut kept = vec![ids[0].clone(), ids[1].clone()];
+        kept.sort();
+        assert_eq!(java_ids, kept);
+    }
+
     #[tokio::test]
     #[ignore]
-    async fn test_require_java_install() {
+    async fn test_resolve_java() {
         let app = setup_managers_for_test().await;
 
         let java_manager = app.java_manager();
 
-        // Should update both gamma and beta
-        let _ = java_manager
-            .require_java_install(SystemJavaProfileName::Gamma, true, None)
+        let resolution = java_manager
+            .resolve_java(
+                &JavaRequirement::new(17),
+                true,
+                Some(SystemJavaProfileName::Gamma),
+                None,
+            )
             .await
-            .unwrap()
             .unwrap();
 
+        assert_eq!(resolution.java.unwrap().version.major, 17);
+
+        // Should fill beta, gamma and gamma snapshot
+
         let profiles_in_db = app
             .prisma_client
             .java_profile()
@@ -632,7 +1021,7 @@ mod test {
                 .iter()
                 .filter(|p| p.java_id.is_some())
                 .count(),
-            2
+            3
         );
     }
 
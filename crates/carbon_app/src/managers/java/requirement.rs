@@ -0,0 +1,458 @@
+//! Java versions an instance can run on.
+//!
+//! The game version sets the minimum through the `javaVersion` of its version
+//! json, mod loaders and mods can narrow it further. Runtimes outside of the
+//! range are rejected with the constraint they failed.
+
+use std::fmt::Display;
+
+use thiserror::Error;
+use tracing::debug;
+
+use crate::domain::instance::info::{ModLoader, ModLoaderType};
+use crate::domain::java::JavaVersion;
+
+/// Forge builds for 1.16.5 older than this crash on java 8u312 and newer
+const FORGE_1_16_5_JAVA8_FIX: (u32, u32, u32) = (36, 2, 26);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RequirementSource {
+    /// `javaVersion` of the version json
+    GameVersion,
+    ModLoader(ModLoaderType),
+    /// A mod's `depends.java`, by file name
+    Mod(String),
+}
+
+impl Display for RequirementSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::GameVersion => write!(f, "the game version"),
+            Self::ModLoader(loader) => write!(f, "{}", loader.to_string()),
+            Self::Mod(file_name) => write!(f, "mod {file_name}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JavaBound {
+    pub major: u16,
+    pub source: RequirementSource,
+}
+
+/// Highest usable update of java 8
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Java8UpdateCap {
+    pub update: u32,
+    pub source: RequirementSource,
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum JavaRejection {
+    #[error("Java {found} is older than Java {} required by {}", .required.major, .required.source)]
+    TooOld { found: u16, required: JavaBound },
+    #[error("Java {found} is newer than Java {} supported by {}", .required.major, .required.source)]
+    TooNew { found: u16, required: JavaBound },
+    #[error("Java 8 update {found} is newer than update {} supported by {}", .required.update, .required.source)]
+    Java8UpdateTooNew {
+        found: u32,
+        required: Java8UpdateCap,
+    },
+    #[error("Java could not be run the last time it was checked")]
+    Invalid,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JavaRequirement {
+    /// The major version of the version json, preferred when several fit
+    pub recommended: u16,
+    pub min: JavaBound,
+    pub max: Option<JavaBound>,
+    pub java8_update_cap: Option<Java8UpdateCap>,
+}
+
+impl JavaRequirement {
+    pub fn new(recommended: u16) -> Self {
+        Self {
+            recommended,
+            min: JavaBound {
+                major: recommended,
+                source: RequirementSource::GameVersion,
+            },
+            max: None,
+            java8_update_cap: None,
+        }
+    }
+
+    /// Requirement for a game version, given the `javaVersion.majorVersion`
+    /// of its version json, its mod loaders and the `depends.java` of its mods.
+    pub fn for_instance<'a>(
+        recommended: u16,
+        release: &str,
+        modloaders: impl IntoIterator<Item = &'a ModLoader>,
+        mod_dependencies: &[(String, Vec<String>)],
+    ) -> Self {
+        let mut requirement = Self::new(recommended);
+        let release = parse_release(release);
+
+        for modloader in modloaders {
+            let source = RequirementSource::ModLoader(modloader.type_);
+
+            match modloader.type_ {
+                // LaunchWrapper and early ModLauncher only work on java 8
+                ModLoaderType::Forge if release.is_some_and(|r| r < (1, 17, 0)) => {
+                    requirement.require_max(8, source.clone());
+
+                    let forge_version =
+                        modloader.version.rsplit('-').next().and_then(parse_release);
+
+                    if release == Some((1, 16, 5))
+                        && forge_version.map_or(true, |v| v < FORGE_1_16_5_JAVA8_FIX)
+                    {
+                        requirement.java8_update_cap = Some(Java8UpdateCap {
+                            update: 311,
+                            source,
+                        });
+                    }
+                }
+                ModLoaderType::Neoforge => requirement.require_min(17, source),
+                _ => {}
+            }
+        }
+
+        for (file_name, ranges) in mod_dependencies {
+            let source = RequirementSource::Mod(file_name.clone());
+
+            let Some((min, max)) = parse_major_ranges(ranges) else {
+                debug!("Ignoring unsupported java dependency {ranges:?} of {file_name}");
+                continue;
+            };
+
+            if let Some(min) = min {
+                requirement.require_min(min, source.clone());
+            }
+
+            if let Some(max) = max {
+                requirement.require_max(max, source);
+            }
+        }
+
+        requirement
+    }
+
+    pub fn require_min(&mut self, major: u16, source: RequirementSource) {
+        if major > self.min.major {
+            self.min = JavaBound { major, source };
+        }
+    }
+
+    pub fn require_max(&mut self, major: u16, source: RequirementSource) {
+        if self.max.as_ref().map_or(true, |max| major < max.major) {
+            self.max = Some(JavaBound { major, source });
+        }
+    }
+
+    /// If no java can satisfy every constraint at once
+    pub fn is_conflicting(&self) -> bool {
+        self.max
+            .as_ref()
+            .is_some_and(|max| max.major < self.min.major)
+    }
+
+    pub fn check(&self, version: &JavaVersion) -> Result<(), JavaRejection> {
+        if version.major < self.min.major {
+            return Err(JavaRejection::TooOld {
+                found: version.major,
+                required: self.min.clone(),
+            });
+        }
+
+        if let Some(max) = &self.max {
+            if version.major > max.major {
+                return Err(JavaRejection::TooNew {
+                    found: version.major,
+                    required: max.clone(),
+                });
+            }
+        }
+
+        if let Some(cap) = &self.java8_update_cap {
+            let update = version.patch.parse().unwrap_or(0);
+            if version.major == 8 && update > cap.update {
+                return Err(JavaRejection::Java8UpdateTooNew {
+                    found: update,
+                    required: cap.clone(),
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The major version to look for first, the recommended one moved into
+    /// the allowed range
+    pub fn preferred_major(&self) -> u16 {
+        let preferred = self.recommended.max(self.min.major);
+
+        match &self.max {
+            Some(max) => preferred.min(max.major),
+            None => preferred,
+        }
+    }
+
+    /// Orders versions satisfying the requirement, higher is better: the
+    /// preferred major first, then the closest to it, then the newest.
+    pub fn rank(&self, version: &JavaVersion) -> impl Ord {
+        let preferred = self.preferred_major();
+
+        (
+            version.major == preferred,
+            std::cmp::Reverse(version.major.abs_diff(preferred)),
+            version.major,
+            version.minor,
+            version.patch.parse::<u32>().unwrap_or(0),
+        )
+    }
+}
+
+impl Display for JavaRequirement {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Java {} ({})", self.min.major, self.min.source)?;
+
+        match &self.max {
+            Some(max) if max.major == self.min.major => {}
+            Some(max) => write!(f, " to {} ({})", max.major, max.source)?,
+            None => write!(f, " or newer")?,
+        }
+
+        if let Some(cap) = &self.java8_update_cap {
+            write!(f, ", up to 8u{} ({})", cap.update, cap.source)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// `1.16.5` -> `(1, 16, 5)`, `None` for snapshots
+fn parse_release(release: &str) -> Option<(u32, u32, u32)> {
+    let mut parts = release.split('.').map(str::parse::<u32>);
+
+    let major = parts.next()?.ok()?;
+    let minor = parts.next().unwrap_or(Ok(0)).ok()?;
+    let patch = parts.next().unwrap_or(Ok(0)).ok()?;
+
+    Some((major, minor, patch))
+}
+
+/// Java major version of a version in a range, `1.8` being java 8
+fn parse_major(version: &str) -> Option<u16> {
+    let mut parts = version.split(['.', '-', '+']);
+
+    match parts.next()?.parse().ok()? {
+        1 => parts.next()?.parse().ok(),
+        major => Some(major),
+    }
+}
+
+/// Bounds on the major version of a single range, like `>=17` or `>=8 <21`.
+/// `None` if the range can't be understood.
+fn parse_major_range(range: &str) -> Option<(Option<u16>, Option<u16>)> {
+    let mut min = None;
+    let mut max = None;
+
+    for predicate in range.split_whitespace() {
+        let (op, version) = match predicate {
+            "*" => continue,
+            _ => {
+                let split = predicate
+                    .find(|c: char| c.is_ascii_digit())
+                    .unwrap_or(predicate.len());
+                predicate.split_at(split)
+            }
+        };
+
+        let major = parse_major(version.trim_end_matches(['x', 'X', '*', '.']))?;
+
+        let (low, high) = match op {
+            ">=" | "^" | "~" => (Some(major), if op == ">=" { None } else { Some(major) }),
+            ">" => (Some(major), None),
+            "<=" => (None, Some(major)),
+            // `<21` and `<21.0.0` exclude 21, `<17.0.2` doesn't exclude 17
+            "<" if version.split('.').skip(1).all(|part| part == "0") => {
+                (None, Some(major.checked_sub(1)?))
+            }
+            "<" => (None, Some(major)),
+            "=" | "" => (Some(major), Some(major)),
+            _ => return None,
+        };
+
+        min = min.max(low);
+        max = match (max, high) {
+            (Some(max), Some(high)) => Some(u16::min(max, high)),
+            (max, high) => max.or(high),
+        };
+    }
+
+    Some((min, max))
+}
+
+/// Bounds covering any of `ranges`, `None` if one can't be understood
+fn parse_major_ranges(ranges: &[String]) -> Option<(Option<u16>, Option<u16>)> {
+    let mut bounds = ranges.iter().map(|range| parse_major_range(range));
+
+    let first = bounds.next()??;
+
+    bounds.try_fold(first, |(min, max), bound| {
+        let (other_min, other_max) = bound?;
+        Some((
+            min.zip(other_min).map(|(a, b)| a.min(b)),
+            max.zip(other_max).map(|(a, b)| a.max(b)),
+        ))
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn version(s: &str) -> JavaVersion {
+        JavaVersion::try_from(s).unwrap()
+    }
+
+    fn loader(type_: ModLoaderType, version: &str) -> ModLoader {
+        ModLoader {
+            type_,
+            version: version.to_string(),
+        }
+    }
+
+    #[test]
+    fn parses_ranges() {
+        assert_eq!(parse_major_range(">=17"), Some((Some(17), None)));
+        assert_eq!(parse_major_range(">=1.8"), Some((Some(8), None)));
+        assert_eq!(parse_major_range(">=8 <21"), Some((Some(8), Some(20))));
+        assert_eq!(parse_major_range("<=17"), Some((None, Some(17))));
+        assert_eq!(parse_major_range("17"), Some((Some(17), Some(17))));
+        assert_eq!(parse_major_range("17.x"), Some((Some(17), Some(17))));
+        assert_eq!(parse_major_range("*"), Some((None, None)));
+        assert_eq!(parse_major_range("latest"), None);
+
+        assert_eq!(
+            parse_major_ranges(&[">=17".to_string(), ">=21".to_string()]),
+            Some((Some(17), None))
+        );
+        assert_eq!(
+            parse_major_ranges(&["8".to_string(), "11".to_string()]),
+            Some((Some(8), Some(11)))
+        );
+    }
+
+    #[test]
+    fn applies_mod_loader_constraints() {
+        let forge = JavaRequirement::for_instance(
+            8,
+            "1.12.2",
+            &[loader(ModLoaderType::Forge, "1.12.2-14.23.5.2860")],
+            &[],
+        );
+        assert_eq!(forge.max.unwrap().major, 8);
+        assert!(forge.java8_update_cap.is_none());
+
+        let old_forge = JavaRequirement::for_instance(
+            8,
+            "1.16.5",
+            &[loader(ModLoaderType::Forge, "1.16.5-36.1.0")],
+            &[],
+        );
+        assert!(old_forge.check(&version("1.8.0_302")).is_ok());
+        assert_eq!(
+            old_forge.check(&version("1.8.0_392")),
+            Err(JavaRejection::Java8UpdateTooNew {
+                found: 392,
+                required: Java8UpdateCap {
+                    update: 311,
+                    source: RequirementSource::ModLoader(ModLoaderType::Forge),
+                },
+            })
+        );
+
+        let fixed_forge = JavaRequirement::for_instance(
+            8,
+            "1.16.5",
+            &[loader(ModLoaderType::Forge, "1.16.5-36.2.39")],
+            &[],
+        );
+        assert!(fixed_forge.check(&version("1.8.0_392")).is_ok());
+
+        let neoforge = JavaRequirement::for_instance(
+            17,
+            "1.20.1",
+            &[loader(ModLoaderType::Neoforge, "47.1.0")],
+            &[],
+        );
+        assert_eq!(neoforge.min.major, 17);
+        assert!(neoforge.max.is_none());
+    }
+
+    #[test]
+    fn applies_mod_constraints() {
+        let requirement = JavaRequirement::for_instance(
+            17,
+            "1.20.1",
+            &[loader(ModLoaderType::Fabric, "0.15.0")],
+            &[
+                ("sodium.jar".to_string(), vec![">=17".to_string()]),
+                ("newer.jar".to_string(), vec![">=21".to_string()]),
+                ("weird.jar".to_string(), vec!["latest".to_string()]),
+            ],
+        );
+
+        assert_eq!(
+            requirement.min,
+            JavaBound {
+                major: 21,
+                source: RequirementSource::Mod("newer.jar".to_string()),
+            }
+        );
+        assert_eq!(requirement.preferred_major(), 21);
+
+        assert_eq!(
+            requirement
+                .check(&version("17.0.9"))
+                .unwrap_err()
+                .to_string(),
+            "Java 17 is older than Java 21 required by mod newer.jar"
+        );
+        assert!(requirement.check(&version("21.0.1")).is_ok());
+    }
+
+    #[test]
+    fn detects_conflicts() {
+        let requirement = JavaRequirement::for_instance(
+            8,
+            "1.12.2",
+            &[loader(ModLoaderType::Forge, "1.12.2-14.23.5.2860")],
+            &[("modern.jar".to_string(), vec![">=17".to_string()])],
+        );
+
+        assert!(requirement.is_conflicting());
+    }
+
+    #[test]
+    fn ranks_preferred_major_first() {
+        let requirement = JavaRequirement::new(17);
+
+        let mut versions = vec![
+            version("21.0.1"),
+            version("17.0.2"),
+            version("17.0.9"),
+            version("18.0.1"),
+        ];
+        versions.sort_by_key(|v| std::cmp::Reverse(requirement.rank(v)));
+
+        assert_eq!(
+            versions.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            vec!["17.0.9", "17.0.2", "18.0.1", "21.0.1"]
+        );
+    }
+}
@@ -3,7 +3,10 @@ use crate::domain::java::{
     JavaArch, JavaComponent, JavaComponentType, JavaVersion, SystemJavaProfileName,
 };
 use carbon_repos::db::{read_filters::StringFilter, PrismaClient};
-use std::{path::PathBuf, sync::Arc};
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 use strum::IntoEnumIterator;
 use tracing::{info, trace, warn};
 
@@ -285,12 +288,27 @@ where
     Ok(())
 }
 
-#[tracing::instrument(level = "trace", skip_all)]
 pub async fn scan_and_sync_managed<T, G>(
     db: &Arc<PrismaClient>,
     discovery: &T,
     java_checker: &G,
 ) -> anyhow::Result<()>
+where
+    T: Discovery,
+    G: JavaChecker,
+{
+    scan_and_sync_managed_keeping(db, discovery, java_checker, |_| false).await
+}
+
+/// Like `scan_and_sync_managed`, but broken javas for which `is_kept` returns
+/// true are marked invalid instead of deleted, as a profile using them would.
+#[tracing::instrument(level = "trace", skip_all)]
+pub async fn scan_and_sync_managed_keeping<T, G>(
+    db: &Arc<PrismaClient>,
+    discovery: &T,
+    java_checker: &G,
+    is_kept: impl Fn(&Path) -> bool,
+) -> anyhow::Result<()>
 where
     T: Discovery,
     G: JavaChecker,
@@ -334,13 +352,15 @@ where
             managed_java.path, is_java_used_in_profile
         );
 
+        let is_java_kept = is_java_used_in_profile || is_kept(Path::new(&managed_java.path));
+
         match (java_bin_info, managed_java.is_valid) {
             (Ok(java_component), true) => {}
             (Ok(java_component), false) => {
                 upsert_java_component_to_db(db, java_component).await?;
             }
             (Err(_), true) => {
-                if is_java_used_in_profile {
+                if is_java_kept {
                     update_java_component_in_db_to_invalid(db, managed_java.path.clone()).await?;
                 } else {
                     db.java()
@@ -352,7 +372,7 @@ where
                 }
             }
             (Err(_), false) => {
-                if !is_java_used_in_profile {
+                if !is_java_kept {
                     db.java()
                         .delete(carbon_repos::db::java::UniqueWhereParam::PathEquals(
                             managed_java.path.clone(),
@@ -4,6 +4,7 @@ use super::UpdateNotifier;
 use crate::domain::instance::info::ModLoaderType;
 use crate::domain::instance::InstanceId;
 use crate::managers::App;
+use carbon_platforms::curseforge::filters::ModFileParameters;
 use carbon_platforms::curseforge::filters::ModFilesParameters;
 use carbon_platforms::curseforge::filters::ModFilesParametersQuery;
 use carbon_platforms::curseforge::filters::ModParameters;
@@ -356,6 +357,67 @@ impl ModplatformCacher for CurseforgeModCacher {
     }
 }
 
+// Cache curseforge metadata for a mod whose project and file ids are already known
+pub(crate) async fn cache_known_curseforge_file(
+    app: &App,
+    metadata_id: String,
+    murmur2: u32,
+    project_id: i32,
+    file_id: i32,
+) -> anyhow::Result<()> {
+    let mpm = app.modplatforms_manager();
+
+    let modinfo = mpm
+        .curseforge
+        .get_mod(ModParameters { mod_id: project_id })
+        .await?
+        .data;
+
+    let mod_files = mpm
+        .curseforge
+        .get_mod_files(ModFilesParameters {
+            mod_id: project_id,
+            query: ModFilesParametersQuery {
+                game_version: None,
+                mod_loader_type: None,
+                game_version_type_id: None,
+                index: None,
+                page_size: None,
+            },
+        })
+        .await?
+        .data;
+
+    let fileinfo = match mod_files.iter().find(|file| file.id == file_id) {
+        Some(file) => file.clone(),
+        None => {
+            mpm.curseforge
+                .get_mod_file(ModFileParameters {
+                    mod_id: project_id,
+                    file_id,
+                })
+                .await?
+                .data
+        }
+    };
+
+    if fileinfo.file_fingerprint != murmur2 {
+        anyhow::bail!(
+            "curseforge file {project_id}/{file_id} does not match the local file fingerprint"
+        );
+    }
+
+    cache_curseforge_meta_unchecked(
+        app,
+        metadata_id,
+        &fileinfo,
+        murmur2,
+        &modinfo,
+        &mod_files[..],
+    )
+    .await
+}
+
 // Cache curseforge metadata for a mod without downloading the icon
 async fn cache_curseforge_meta_unchecked(
     app: &App,
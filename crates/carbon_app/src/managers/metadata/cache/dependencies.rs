@@ -0,0 +1,62 @@
+use crate::domain::instance::{ModDependency, ProvidedModId};
+use crate::managers::App;
+use carbon_repos::db::{mod_dependency as mddb, mod_metadata as metadb, mod_provided_id as mpdb};
+
+// Record the dependencies and provided mod ids parsed from a mod file, replacing
+// any recorded before for the same metadata entry
+pub(super) async fn save_mod_dependencies(
+    app: &App,
+    metadata_id: String,
+    dependencies: Vec<ModDependency>,
+    provides: Vec<ProvidedModId>,
+) -> anyhow::Result<()> {
+    let o_insert_dependencies = dependencies
+        .into_iter()
+        .map(|dependency| {
+            Ok(app.prisma_client.mod_dependency().create(
+                metadb::UniqueWhereParam::IdEquals(metadata_id.clone()),
+                dependency.modloader.to_string(),
+                dependency.modid,
+                dependency.kind.into(),
+                serde_json::to_string(&dependency.versions)?,
+                Vec::new(),
+            ))
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    let o_insert_provided = provides
+        .into_iter()
+        .map(|provided| {
+            app.prisma_client.mod_provided_id().create(
+                metadb::UniqueWhereParam::IdEquals(metadata_id.clone()),
+                provided.modid,
+                vec![mpdb::SetParam::SetVersion(provided.version)],
+            )
+        })
+        .collect::<Vec<_>>();
+
+    app.prisma_client
+        ._batch((
+            app.prisma_client
+                .mod_dependency()
+                .delete_many(vec![mddb::metadata_id::equals(metadata_id.clone())]),
+            app.prisma_client
+                .mod_provided_id()
+                .delete_many(vec![mpdb::metadata_id::equals(metadata_id.clone())]),
+            o_insert_dependencies,
+            o_insert_provided,
+        ))
+        .await?;
+
+    // marked last so an interrupted save is retried on the next scan
+    app.prisma_client
+        .mod_metadata()
+        .update(
+            metadb::UniqueWhereParam::IdEquals(metadata_id),
+            vec![metadb::SetParam::SetDependenciesScanned(true)],
+        )
+        .exec()
+        .await?;
+
+    Ok(())
+}
@@ -1,5 +1,6 @@
 use crate::api::keys::instance::INSTANCE_MODS;
 use crate::domain::instance::InstanceId;
+use crate::managers::modplatforms::source as mpsource;
 use crate::managers::App;
 use crate::managers::ManagerRef;
 use anyhow::anyhow;
@@ -46,7 +47,72 @@ use tracing::warn;
 use uuid::Uuid;
 
 pub mod curseforge;
+mod dependencies;
 pub mod modrinth;
+mod source;
+
+pub(crate) use source::cache_known_source_version;
+
+/// A mod file whose remote origin is already known,
+/// usually from the instance data of another launcher.
+#[derive(Debug, Clone)]
+pub struct KnownModFile {
+    pub filename: String,
+    pub source: KnownModSource,
+}
+
+#[derive(Debug, Clone)]
+pub enum KnownModSource {
+    Curseforge {
+        project_id: i32,
+        file_id: i32,
+    },
+    Modrinth {
+        project_id: String,
+        version_id: String,
+    },
+    /// A mod installed from a [`ModSource`](crate::managers::modplatforms::source::ModSource).
+    /// `repository` is `None` for the source's default repository.
+    Source {
+        source_id: String,
+        repository: Option<String>,
+        project_id: String,
+        version_id: String,
+    },
+}
+
+impl KnownModSource {
+    /// The source id, repository, project id and version id to resolve the mod
+    /// with, see `ModplatformsManager::mod_source`.
+    pub fn into_parts(self) -> (String, Option<String>, String, String) {
+        match self {
+            Self::Curseforge {
+                project_id,
+                file_id,
+            } => (
+                mpsource::curseforge::SOURCE_ID.to_string(),
+                None,
+                project_id.to_string(),
+                file_id.to_string(),
+            ),
+            Self::Modrinth {
+                project_id,
+                version_id,
+            } => (
+                mpsource::modrinth::SOURCE_ID.to_string(),
+                None,
+                project_id,
+                version_id,
+            ),
+            Self::Source {
+                source_id,
+                repository,
+                project_id,
+                version_id,
+            } => (source_id, repository, project_id, version_id),
+        }
+    }
+}
 
 pub struct MetaCacheManager {
     //waiting_instances: RwLock<HashSet<InstanceId>>,
@@ -57,6 +123,8 @@ pub struct MetaCacheManager {
     failed_mr_instances: RwLock<HashMap<InstanceId, (std::time::Instant, u32)>>,
     failed_cf_thumbs: RwLock<HashMap<i32, (std::time::Instant, u32)>>,
     failed_mr_thumbs: RwLock<HashMap<String, (std::time::Instant, u32)>>,
+    /// Origin of mods waiting for the local scan, by instance and filename.
+    known_mods: RwLock<HashMap<InstanceId, HashMap<String, KnownModSource>>>,
     local_targets: LockNotify<CacheTargets>,
     curseforge_targets: LockNotify<CacheTargets>,
     modrinth_targets: LockNotify<CacheTargets>,
@@ -78,6 +146,7 @@ impl MetaCacheManager {
             failed_mr_instances: RwLock::new(HashMap::new()),
             failed_cf_thumbs: RwLock::new(HashMap::new()),
             failed_mr_thumbs: RwLock::new(HashMap::new()),
+            known_mods: RwLock::new(HashMap::new()),
             local_targets: LockNotify::new(CacheTargets::new()),
             curseforge_targets: LockNotify::new(CacheTargets::new()),
             modrinth_targets: LockNotify::new(CacheTargets::new()),
@@ -657,6 +726,8 @@ impl ManagerRef<'_, MetaCacheManager> {
                 .send_modify(|targets| targets.revoke_target(instance_id)),
         );
 
+        self.known_mods.write().await.remove(&instance_id);
+
         let _ = self
             .app
             .prisma_client
@@ -908,6 +979,65 @@ impl ManagerRef<'_, MetaCacheManager> {
         );
     }
 
+    /// Remember the origin of mods, usually from the instance data of another
+    /// launcher. The local scan of the instance then fills the remote caches for
+    /// them directly, skipping the hash lookups.
+    pub async fn cache_known_mods(self, instance_id: InstanceId, mods: Vec<KnownModFile>) {
+        self.known_mods
+            .write()
+            .await
+            .entry(instance_id)
+            .or_default()
+            .extend(mods.into_iter().map(|known| (known.filename, known.source)));
+
+        self.queue_caching(instance_id, false).await;
+    }
+
+    /// Fill the remote cache for a mod registered with `cache_known_mods` once
+    /// its file has been cached. Mods that fail to resolve are left for the
+    /// regular hash lookups.
+    async fn cache_known_mod(
+        self,
+        instance_id: InstanceId,
+        filename: &str,
+        metadata_id: &str,
+        source: KnownModSource,
+    ) {
+        let r = async {
+            let metadata = self
+                .app
+                .prisma_client
+                .mod_metadata()
+                .find_unique(metadb::UniqueWhereParam::IdEquals(metadata_id.to_string()))
+                .exec()
+                .await?
+                .ok_or_else(|| anyhow!("mod metadata {metadata_id} was not saved"))?;
+
+            let (source_id, repository, project_id, version_id) = source.into_parts();
+
+            self.app
+                .modplatforms_manager()
+                .mod_source(&source_id, repository.as_deref())
+                .await?
+                .cache_known_version(self.app, &metadata, &project_id, &version_id)
+                .await
+        }
+        .await;
+
+        if let Err(e) = r {
+            warn!({ error = ?e }, "could not cache known mod {filename} for instance {instance_id}");
+        }
+
+        let mut known_mods = self.known_mods.write().await;
+        if let Some(instance_mods) = known_mods.get_mut(&instance_id) {
+            instance_mods.remove(filename);
+
+            if instance_mods.is_empty() {
+                known_mods.remove(&instance_id);
+            }
+        }
+    }
+
     /// Cache a mod file without first checking the validity of the instance
     async fn cache_mod_file_unchecked(
         self,
@@ -1004,6 +1134,11 @@ impl ManagerRef<'_, MetaCacheManager> {
             }
         };
 
+        let (meta_dependencies, meta_provides) = meta
+            .as_ref()
+            .map(|meta| (meta.dependencies.clone(), meta.provides.clone()))
+            .unwrap_or_default();
+
         let dbmeta = self
             .app
             .prisma_client
@@ -1016,8 +1151,8 @@ impl ManagerRef<'_, MetaCacheManager> {
             .exec()
             .await?;
 
-        let (meta_id, meta_insert, logo_insert) = match dbmeta {
-            Some(meta) => (meta.id, None, None),
+        let (meta_id, dependencies_scanned, meta_insert, logo_insert) = match dbmeta {
+            Some(meta) => (meta.id, meta.dependencies_scanned, None, None),
             None => {
                 let meta_id = Uuid::new_v4().to_string();
 
@@ -1085,7 +1220,7 @@ impl ManagerRef<'_, MetaCacheManager> {
                     },
                 );
 
-                (meta_id, Some(meta_insert), logo_insert)
+                (meta_id, false, Some(meta_insert), logo_insert)
             }
         };
 
@@ -1118,6 +1253,16 @@ impl ManagerRef<'_, MetaCacheManager> {
             ))
             .await?;
 
+        if !dependencies_scanned {
+            dependencies::save_mod_dependencies(
+                self.app,
+                meta_id.clone(),
+                meta_dependencies,
+                meta_provides,
+            )
+            .await?;
+        }
+
         Ok(meta_id)
     }
 }
@@ -1176,6 +1321,7 @@ fn cache_local(app: App, rx: LockNotify<CacheTargets>, update_notifier: UpdateNo
                     .find_many(vec![fcdb::WhereParam::InstanceId(IntFilter::Equals(
                         *instance_id,
                     ))])
+                    .with(fcdb::metadata::fetch())
                     .exec()
                     .await
             });
@@ -1245,6 +1391,17 @@ fn cache_local(app: App, rx: LockNotify<CacheTargets>, update_notifier: UpdateNo
                 );
             }
 
+            let known_mods = app
+                .meta_cache_manager()
+                .known_mods
+                .read()
+                .await
+                .get(&instance_id)
+                .cloned()
+                .unwrap_or_default();
+
+            // up to date files whose origin became known after they were cached
+            let mut known_cached = Vec::new();
             let mut has_outdated_entries = false;
 
             if let Ok(Ok(cached_entries)) = cached_entries.await {
@@ -1252,9 +1409,23 @@ fn cache_local(app: App, rx: LockNotify<CacheTargets>, update_notifier: UpdateNo
 
                 for entry in cached_entries {
                     if let Some((enabled, real_size)) = modpaths.get(&entry.filename) {
+                        // entries cached before dependencies were recorded are rescanned once
+                        let dependencies_scanned = entry
+                            .metadata
+                            .as_ref()
+                            .map_or(true, |metadata| metadata.dependencies_scanned);
+
                         // enabled probably shouldn't be here
-                        if *real_size == entry.filesize as u64 && *enabled == entry.enabled {
+                        if *real_size == entry.filesize as u64
+                            && *enabled == entry.enabled
+                            && dependencies_scanned
+                        {
                             modpaths.remove(&entry.filename);
+
+                            if known_mods.contains_key(&entry.filename) {
+                                known_cached.push((entry.filename, entry.metadata_id));
+                            }
+
                             // trace!(
                             //     "up to data metadata entry for mod `{}`, skipping",
                             //     &entry.filename
@@ -1290,6 +1461,8 @@ fn cache_local(app: App, rx: LockNotify<CacheTargets>, update_notifier: UpdateNo
 
             let rate_limiter = Arc::new(tokio::sync::Semaphore::new(default_parallelism_approx));
 
+            let known_mods = &known_mods;
+
             let entry_futures = modpaths.into_iter().map(|(subpath, (enabled, _))| {
                 let pathbuf = &pathbuf;
                 let update_notifier = &update_notifier;
@@ -1299,10 +1472,17 @@ fn cache_local(app: App, rx: LockNotify<CacheTargets>, update_notifier: UpdateNo
                 async move {
                     let _permit = rate_limiter.acquire().await.expect("rate limiter");
 
-                    app.meta_cache_manager()
-                        .cache_mod_file_unchecked(instance_id, pathbuf, subpath, enabled)
+                    let metadata_id = app
+                        .meta_cache_manager()
+                        .cache_mod_file_unchecked(instance_id, pathbuf, subpath.clone(), enabled)
                         .await?;
 
+                    if let Some(source) = known_mods.get(&subpath) {
+                        app.meta_cache_manager()
+                            .cache_known_mod(instance_id, &subpath, &metadata_id, source.clone())
+                            .await;
+                    }
+
                     tokio::time::sleep(std::time::Duration::from_millis(50)).await;
 
                     update_notifier.send(instance_id);
@@ -1311,10 +1491,33 @@ fn cache_local(app: App, rx: LockNotify<CacheTargets>, update_notifier: UpdateNo
                 }
             });
 
-            let r = futures::future::join_all(entry_futures)
-                .await
-                .into_iter()
-                .collect::<anyhow::Result<()>>();
+            let known_futures = known_cached.into_iter().map(|(filename, metadata_id)| {
+                let update_notifier = &update_notifier;
+
+                let rate_limiter = Arc::clone(&rate_limiter);
+
+                async move {
+                    let _permit = rate_limiter.acquire().await.expect("rate limiter");
+
+                    app.meta_cache_manager()
+                        .cache_known_mod(
+                            instance_id,
+                            &filename,
+                            &metadata_id,
+                            known_mods[&filename].clone(),
+                        )
+                        .await;
+
+                    update_notifier.send(instance_id);
+                }
+            });
+
+            let (r, _) = join!(
+                futures::future::join_all(entry_futures),
+                futures::future::join_all(known_futures),
+            );
+
+            let r = r.into_iter().collect::<anyhow::Result<()>>();
 
             if let Err(e) = r {
                 error!({ error = ?e }, "could not store mod scan results for instance {instance_id} in db");
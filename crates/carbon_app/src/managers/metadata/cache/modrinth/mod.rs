@@ -3,10 +3,10 @@ use crate::domain::instance::info::ModLoaderType;
 use crate::domain::instance::InstanceId;
 use crate::managers::App;
 use anyhow::anyhow;
-use carbon_platforms::modrinth::search::VersionIDs;
+use carbon_platforms::modrinth::search::{ProjectID, VersionID, VersionIDs};
 use carbon_platforms::modrinth::version::Version;
 use carbon_platforms::modrinth::{
-    project::Project,
+    project::{Project, ProjectVersionsFilters},
     responses::{ProjectsResponse, TeamResponse, VersionHashesResponse},
     search::{ProjectIDs, TeamIDs, VersionHashesQuery},
     version::HashAlgorithm,
@@ -404,6 +404,75 @@ impl ModplatformCacher for ModrinthModCacher {
     }
 }
 
+// Cache modrinth metadata for a mod whose project and version ids are already known
+pub(crate) async fn cache_known_modrinth_version(
+    app: &App,
+    metadata_id: String,
+    sha512: String,
+    project_id: String,
+    version_id: String,
+) -> anyhow::Result<()> {
+    let mpm = app.modplatforms_manager();
+
+    let version = mpm.modrinth.get_version(VersionID(version_id)).await?;
+
+    let file = version
+        .files
+        .iter()
+        .find(|file| file.hashes.sha512 == sha512)
+        .ok_or_else(|| {
+            anyhow!(
+                "modrinth version {} does not contain the local file",
+                version.id
+            )
+        })?;
+
+    let project = mpm
+        .modrinth
+        .get_project(ProjectID(project_id.clone()))
+        .await?;
+
+    let team = mpm
+        .modrinth
+        .get_project_team(ProjectID(project_id.clone()))
+        .await?;
+
+    let versions = mpm
+        .modrinth
+        .get_project_versions(ProjectVersionsFilters {
+            project_id: ProjectID(project_id),
+            game_versions: None,
+            loaders: None,
+            offset: None,
+            limit: None,
+        })
+        .await?;
+
+    let authors = team
+        .iter()
+        .map(|member| {
+            member
+                .user
+                .name
+                .clone()
+                .unwrap_or_else(|| member.user.username.clone())
+        })
+        .join(", ");
+
+    cache_modrinth_meta_unchecked(
+        app,
+        metadata_id,
+        &version,
+        sha512,
+        file.filename.clone(),
+        file.url.clone(),
+        project,
+        authors,
+        &versions[..],
+    )
+    .await
+}
+
 // Cache modrinth metadata for a mod without downloading the icon
 async fn cache_modrinth_meta_unchecked(
     app: &App,
@@ -0,0 +1,70 @@
+use crate::managers::{modplatforms::source::ModSource, App};
+use anyhow::bail;
+use carbon_repos::db::{mod_metadata as metadb, source_mod_cache as srcdb};
+use tracing::debug;
+
+// Cache source metadata for a mod whose source, project and version are already known
+pub(crate) async fn cache_known_source_version<S: ModSource + ?Sized>(
+    app: &App,
+    source: &S,
+    metadata: &metadb::Data,
+    project_id: &str,
+    version_id: &str,
+) -> anyhow::Result<()> {
+    let source_id = source.id();
+    let metadata_id = metadata.id.clone();
+    let sha1 = hex::encode(&metadata.sha_1);
+
+    let version = source.get_version(project_id, version_id).await?;
+
+    if let Some(remote_sha1) = &version.sha1 {
+        if !remote_sha1.eq_ignore_ascii_case(&sha1) {
+            bail!(
+                "{source_id} version {project_id}/{version_id} does not match the local file (expected sha1 {remote_sha1}, found {sha1})"
+            );
+        }
+    }
+
+    let prev = app
+        .prisma_client
+        .source_mod_cache()
+        .find_unique(srcdb::UniqueWhereParam::MetadataIdEquals(
+            metadata_id.clone(),
+        ))
+        .exec()
+        .await?;
+
+    let o_delete_srcmeta = prev.map(|_| {
+        app.prisma_client
+            .source_mod_cache()
+            .delete(srcdb::UniqueWhereParam::MetadataIdEquals(
+                metadata_id.clone(),
+            ))
+    });
+
+    let o_insert_srcmeta = app.prisma_client.source_mod_cache().create(
+        version.source,
+        version.repository,
+        version.project_id,
+        version.id,
+        version.name.clone(),
+        version.name,
+        version.channel as i32,
+        version.file_name,
+        version.url,
+        chrono::Utc::now().into(),
+        metadb::UniqueWhereParam::IdEquals(metadata_id.clone()),
+        Vec::new(),
+    );
+
+    debug!("updating {source_id} source metadata entry for {metadata_id}");
+
+    app.prisma_client
+        ._batch((
+            o_delete_srcmeta.into_iter().collect::<Vec<_>>(),
+            o_insert_srcmeta,
+        ))
+        .await?;
+
+    Ok(())
+}
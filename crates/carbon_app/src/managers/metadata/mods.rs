@@ -1,15 +1,18 @@
 use std::io::{BufRead, Read};
 use std::{
     collections::HashMap,
-    io::{self, Seek},
+    io::{self, Cursor, Seek},
     num::ParseIntError,
 };
 
 use anyhow::{anyhow, bail};
 use serde::Deserialize;
+use tracing::debug;
 use zip::read::ZipFile;
 
-use crate::domain::instance::{self as domain, info::ModLoaderType};
+use crate::domain::instance::{
+    self as domain, info::ModLoaderType, ModDependency, ModDependencyKind, ProvidedModId,
+};
 
 #[derive(Deserialize)]
 #[serde(untagged)]
@@ -96,6 +99,25 @@ struct ModsToml {
     mods: Vec<ModsTomlEntry>,
 }
 
+#[derive(Deserialize)]
+/// `[[dependencies.<modid>]]` tables of mods.toml, parsed separately so a
+/// malformed dependency does not hide the rest of the metadata
+struct ModsTomlDependencies {
+    dependencies: HashMap<String, Vec<ModsTomlDependency>>,
+}
+
+#[derive(Deserialize)]
+struct ModsTomlDependency {
+    #[serde(rename = "modId")]
+    modid: String,
+    /// Replaced by `type` on neoforge
+    mandatory: Option<bool>,
+    #[serde(rename = "type")]
+    type_: Option<String>,
+    #[serde(rename = "versionRange")]
+    version_range: Option<String>,
+}
+
 #[derive(Deserialize)]
 struct ModsTomlEntry {
     #[serde(rename = "modId")]
@@ -281,6 +303,51 @@ fn fabric_mod_json_schema_version_default() -> u32 {
     0
 }
 
+impl FabricModJsonEntry {
+    fn declared_dependencies(&self) -> Vec<ModDependency> {
+        let declarations = [
+            (&self.depends, ModDependencyKind::Required),
+            (&self.recommends, ModDependencyKind::Optional),
+            (&self.suggests, ModDependencyKind::Optional),
+            (&self.breaks, ModDependencyKind::Breaks),
+            (&self.conflicts, ModDependencyKind::Conflicts),
+        ];
+
+        let mut dependencies = declarations
+            .into_iter()
+            .flat_map(|(declared, kind)| {
+                declared
+                    .iter()
+                    .flatten()
+                    .map(move |(modid, range)| ModDependency {
+                        modloader: ModLoaderType::Fabric,
+                        modid: modid.clone(),
+                        kind,
+                        versions: any_version_as_empty(match range {
+                            FabricVersionRange::Single(range) => vec![range.clone()],
+                            FabricVersionRange::OrList(ranges) => ranges.clone(),
+                        }),
+                    })
+            })
+            .collect::<Vec<_>>();
+
+        // the maps lose the declaration order
+        sort_dependencies(&mut dependencies);
+        dependencies
+    }
+
+    fn provided_ids(&self) -> Vec<ProvidedModId> {
+        self.provides
+            .iter()
+            .flatten()
+            .map(|modid| ProvidedModId {
+                modid: modid.clone(),
+                version: Some(self.version.clone()),
+            })
+            .collect()
+    }
+}
+
 #[derive(Deserialize, Clone)]
 #[serde(untagged)]
 enum FabricModJson {
@@ -289,6 +356,15 @@ enum FabricModJson {
     List(Vec<FabricModJsonEntry>),
 }
 
+impl FabricModJson {
+    fn entries(&self) -> &[FabricModJsonEntry] {
+        match self {
+            Self::Single(entry) => std::slice::from_ref(entry.as_ref()),
+            Self::List(entries) => entries,
+        }
+    }
+}
+
 #[derive(Deserialize, Clone)]
 #[serde(untagged)]
 enum QuiltProvides {
@@ -440,7 +516,7 @@ struct QuiltLoaderMetadata {
 struct QuiltLoader {
     group: String,
     id: String,
-    provides: Option<QuiltProvides>,
+    provides: Option<Vec<QuiltProvides>>,
     version: String,
     entrypoints: Option<HashMap<String, QuiltEntrypoint>>,
     plugins: Option<Vec<QuiltPlugin>>,
@@ -459,6 +535,67 @@ fn quilt_intermediate_mappings_default() -> String {
     String::from("org.quiltmc:hashed")
 }
 
+impl QuiltLoader {
+    fn declared_dependencies(&self) -> Vec<ModDependency> {
+        let depends = self
+            .depends
+            .iter()
+            .flatten()
+            .map(|listing| (listing, false));
+        let breaks = self.breaks.iter().flatten().map(|listing| (listing, true));
+
+        let mut dependencies = depends
+            .chain(breaks)
+            .map(|(listing, breaks)| {
+                let (modid, versions, optional) = match listing {
+                    QuiltDependencyListing::String(modid) => (modid, Vec::new(), false),
+                    QuiltDependencyListing::Object(object) => (
+                        &object.id,
+                        quilt_versions(object.versions.clone()),
+                        object.optional,
+                    ),
+                };
+
+                ModDependency {
+                    modloader: ModLoaderType::Quilt,
+                    modid: quilt_mod_id(modid).to_string(),
+                    kind: match (breaks, optional) {
+                        (true, _) => ModDependencyKind::Breaks,
+                        (false, true) => ModDependencyKind::Optional,
+                        (false, false) => ModDependencyKind::Required,
+                    },
+                    versions: any_version_as_empty(versions),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        sort_dependencies(&mut dependencies);
+        dependencies
+    }
+
+    fn provided_ids(&self) -> Vec<ProvidedModId> {
+        self.provides
+            .iter()
+            .flatten()
+            .map(|provides| match provides {
+                QuiltProvides::String(modid) => ProvidedModId {
+                    modid: quilt_mod_id(modid).to_string(),
+                    version: Some(self.version.clone()),
+                },
+                QuiltProvides::Object { id, version } => ProvidedModId {
+                    modid: quilt_mod_id(id).to_string(),
+                    version: version.clone().or_else(|| Some(self.version.clone())),
+                },
+            })
+            .collect()
+    }
+}
+
+/// Quilt mod ids may be prefixed by a maven group, as in `group:id`
+fn quilt_mod_id(id: &str) -> &str {
+    id.rsplit(':').next().unwrap_or(id)
+}
+
 #[derive(Deserialize, Clone)]
 #[serde(untagged)]
 enum QuiltPathListing {
@@ -503,6 +640,8 @@ pub struct ModFileMetadata {
     pub authors: Option<String>,
     pub modloaders: Vec<domain::info::ModLoaderType>,
     pub logo_file: Option<String>,
+    pub dependencies: Vec<ModDependency>,
+    pub provides: Vec<ProvidedModId>,
 }
 
 impl From<McModInfo> for ModFileMetadata {
@@ -515,6 +654,8 @@ impl From<McModInfo> for ModFileMetadata {
             authors: value.authors.map(|list| list.join(", ")),
             logo_file: value.logo_file,
             modloaders: vec![ModLoaderType::Forge],
+            dependencies: Vec::new(),
+            provides: Vec::new(),
         }
     }
 }
@@ -529,6 +670,8 @@ impl From<ModsTomlEntry> for ModFileMetadata {
             authors: value.authors,
             logo_file: value.logo_file,
             modloaders: vec![ModLoaderType::Forge],
+            dependencies: Vec::new(),
+            provides: Vec::new(),
         }
     }
 }
@@ -555,38 +698,47 @@ impl TryFrom<FabricModJson> for ModFileMetadata {
             Some(authors_string)
         }
 
-        match value {
-            FabricModJson::Single(info) => Ok(Self {
-                modid: Some(info.id),
-                name: info.name,
-                version: Some(info.version),
-                description: info.description,
-                authors: info.authors.and_then(flatten_authors),
-                logo_file: info.icon.map(FabricIcon::pick_best).flatten(),
-                modloaders: vec![ModLoaderType::Fabric],
-            }),
-            FabricModJson::List(mut list) => {
-                if list.is_empty() {
-                    bail!("fabric.mod.json entry list should not be empty");
-                }
+        let mut entries = match value {
+            FabricModJson::Single(info) => vec![*info],
+            FabricModJson::List(list) => list,
+        };
 
-                let info = list.swap_remove(0);
-                Ok(Self {
-                    modid: Some(info.id.clone()),
-                    name: info.name.clone(),
-                    version: Some(info.version.clone()),
-                    description: info.description.clone(),
-                    authors: info.authors.clone().and_then(flatten_authors),
-                    logo_file: info.icon.map(FabricIcon::pick_best).flatten(),
-                    modloaders: vec![ModLoaderType::Fabric],
-                })
-            }
+        if entries.is_empty() {
+            bail!("fabric.mod.json entry list should not be empty");
+        }
+
+        let info = entries.swap_remove(0);
+        let mut dependencies = info.declared_dependencies();
+        let mut provides = info.provided_ids();
+
+        // every other entry of a list is loaded as a mod of its own
+        for entry in entries {
+            dependencies.extend(entry.declared_dependencies());
+            provides.extend(entry.provided_ids());
+            provides.push(ProvidedModId {
+                modid: entry.id,
+                version: Some(entry.version),
+            });
         }
+
+        Ok(Self {
+            modid: Some(info.id),
+            name: info.name,
+            version: Some(info.version),
+            description: info.description,
+            authors: info.authors.and_then(flatten_authors),
+            logo_file: info.icon.map(FabricIcon::pick_best).flatten(),
+            modloaders: vec![ModLoaderType::Fabric],
+            dependencies,
+            provides,
+        })
     }
 }
 
 impl From<QuiltModJson> for ModFileMetadata {
     fn from(value: QuiltModJson) -> Self {
+        let dependencies = value.quilt_loader.declared_dependencies();
+        let provides = value.quilt_loader.provided_ids();
         let (name, description, authors, icon) = if let Some(metadata) = value.quilt_loader.metadata
         {
             let authors = metadata.contributors.map(|contributors| {
@@ -610,6 +762,8 @@ impl From<QuiltModJson> for ModFileMetadata {
             authors,
             logo_file: icon.map(QuiltIcon::pick_best).flatten(),
             modloaders: vec![ModLoaderType::Quilt],
+            dependencies,
+            provides,
         }
     }
 }
@@ -631,6 +785,16 @@ fn merge_mod_metadata(
                 modloaders.append(&mut other.modloaders);
                 modloaders
             },
+            dependencies: {
+                let mut dependencies = metadata.dependencies;
+                dependencies.append(&mut other.dependencies);
+                dependencies
+            },
+            provides: {
+                let mut provides = metadata.provides;
+                provides.append(&mut other.provides);
+                provides
+            },
         }),
         None => Some(other),
     }
@@ -640,6 +804,7 @@ pub fn parse_metadata(reader: &mut (impl Read + Seek)) -> anyhow::Result<Option<
     let mut zip = zip::ZipArchive::new(reader)?;
 
     let mut mod_metadata: Option<ModFileMetadata> = None;
+    let mut nested_jars = Vec::<String>::new();
 
     // Used a block and let else instead of an if let to avoid what appears to be a
     // borrow checker life extension bug.
@@ -650,11 +815,11 @@ pub fn parse_metadata(reader: &mut (impl Read + Seek)) -> anyhow::Result<Option<
         let mut content = String::with_capacity(file.size() as usize);
         file.read_to_string(&mut content)?;
         let modstoml = toml::from_str::<ModsToml>(&content)?;
-        let mut modstoml = modstoml
-            .mods
-            .into_iter()
+        let mut entries = modstoml.mods.into_iter();
+        let mut modstoml = entries
             .next()
             .ok_or_else(|| anyhow!("mods.toml contained no mod entries"))?;
+        let other_entries = entries.collect::<Vec<_>>();
         drop(file);
 
         if modstoml.version == "${file.jarVersion}" {
@@ -679,6 +844,28 @@ pub fn parse_metadata(reader: &mut (impl Read + Seek)) -> anyhow::Result<Option<
             _ => (),
         }
 
+        metadata.dependencies = match toml::from_str::<ModsTomlDependencies>(&content) {
+            Ok(dependencies) => mods_toml_dependencies(dependencies),
+            Err(e) => {
+                debug!("Could not read mods.toml dependencies: {e}");
+                Vec::new()
+            }
+        };
+
+        // every [[mods]] entry shares the jar version
+        metadata.provides = other_entries
+            .into_iter()
+            .filter_map(|entry| {
+                Some(ProvidedModId {
+                    modid: entry.modid?,
+                    version: match entry.version.as_str() {
+                        "${file.jarVersion}" => metadata.version.clone(),
+                        _ => Some(entry.version),
+                    },
+                })
+            })
+            .collect();
+
         mod_metadata = merge_mod_metadata(mod_metadata, metadata);
     }
 
@@ -691,6 +878,14 @@ pub fn parse_metadata(reader: &mut (impl Read + Seek)) -> anyhow::Result<Option<
 
         let fabric_mod_json = serde_json::from_str::<FabricModJson>(&content)?;
 
+        nested_jars.extend(
+            fabric_mod_json
+                .entries()
+                .iter()
+                .flat_map(|entry| entry.jars.iter().flatten())
+                .map(|jar| jar.file.clone()),
+        );
+
         mod_metadata = merge_mod_metadata(mod_metadata, fabric_mod_json.try_into()?);
     }
 
@@ -703,6 +898,8 @@ pub fn parse_metadata(reader: &mut (impl Read + Seek)) -> anyhow::Result<Option<
 
         let quilt_mod_json = serde_json::from_str::<QuiltModJson>(&content)?;
 
+        nested_jars.extend(quilt_mod_json.quilt_loader.jars.iter().flatten().cloned());
+
         mod_metadata = merge_mod_metadata(mod_metadata, quilt_mod_json.into());
     }
 
@@ -734,18 +931,264 @@ pub fn parse_metadata(reader: &mut (impl Read + Seek)) -> anyhow::Result<Option<
         mod_metadata = merge_mod_metadata(mod_metadata, mcmod.into());
     }
 
+    if let Some(metadata) = &mut mod_metadata {
+        for path in nested_jars {
+            match nested_jar_ids(&mut zip, &path) {
+                Ok(ids) => metadata.provides.extend(ids),
+                Err(e) => debug!("Could not read nested jar {path}: {e}"),
+            }
+        }
+    }
+
     Ok(mod_metadata)
 }
 
+/// The mod ids of a jar nested in a mod, the nested mod's own id included
+fn nested_jar_ids(
+    zip: &mut zip::ZipArchive<impl Read + Seek>,
+    path: &str,
+) -> anyhow::Result<Vec<ProvidedModId>> {
+    let mut file = zip.by_name(path)?;
+    let mut content = Vec::with_capacity(file.size() as usize);
+    file.read_to_end(&mut content)?;
+    drop(file);
+
+    let Some(nested) = parse_metadata(&mut Cursor::new(content))? else {
+        return Ok(Vec::new());
+    };
+
+    let own_id = nested.modid.map(|modid| ProvidedModId {
+        modid,
+        version: nested.version,
+    });
+
+    Ok(own_id.into_iter().chain(nested.provides).collect())
+}
+
+fn mods_toml_dependencies(toml: ModsTomlDependencies) -> Vec<ModDependency> {
+    let mut dependencies = toml
+        .dependencies
+        .into_values()
+        .flatten()
+        .map(|dependency| {
+            let type_ = dependency.type_.map(|type_| type_.to_lowercase());
+
+            let kind = match (type_.as_deref(), dependency.mandatory) {
+                (Some("optional"), _) => ModDependencyKind::Optional,
+                (Some("incompatible"), _) => ModDependencyKind::Breaks,
+                (Some("discouraged"), _) => ModDependencyKind::Conflicts,
+                (Some(_), _) => ModDependencyKind::Required,
+                (None, Some(false)) => ModDependencyKind::Optional,
+                (None, _) => ModDependencyKind::Required,
+            };
+
+            let versions = match dependency.version_range.as_deref() {
+                Some(range) => maven_version_ranges(range).unwrap_or_else(|| {
+                    debug!(
+                        "Ignoring unsupported version range {range} of {}",
+                        dependency.modid
+                    );
+                    Vec::new()
+                }),
+                None => Vec::new(),
+            };
+
+            ModDependency {
+                modloader: ModLoaderType::Forge,
+                modid: dependency.modid,
+                kind,
+                versions,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    // every [[mods]] entry usually repeats the loader and game dependencies
+    sort_dependencies(&mut dependencies);
+    dependencies.dedup();
+    dependencies
+}
+
+fn sort_dependencies(dependencies: &mut [ModDependency]) {
+    dependencies.sort_by(|a, b| (a.kind, &a.modid).cmp(&(b.kind, &b.modid)));
+}
+
+/// Drops the ranges if any of them accepts every version
+fn any_version_as_empty(ranges: Vec<String>) -> Vec<String> {
+    match ranges.iter().any(|range| matches!(range.trim(), "" | "*")) {
+        true => Vec::new(),
+        false => ranges,
+    }
+}
+
+/// Converts a maven version range, as used by mods.toml, to ranges any one of
+/// which is enough: `[1.0,2.0),[3.0,)` -> `>=1.0 <2.0`, `>=3.0`.
+/// `None` if the range can't be understood.
+fn maven_version_ranges(spec: &str) -> Option<Vec<String>> {
+    let spec = spec.trim();
+
+    // a bare version is only a recommendation
+    if !spec.starts_with(['[', '(']) {
+        return match spec.is_empty() || spec == "*" || !spec.contains([',', ']', ')']) {
+            true => Some(Vec::new()),
+            false => None,
+        };
+    }
+
+    let mut ranges = Vec::new();
+    let mut rest = spec;
+
+    while !rest.is_empty() {
+        if !rest.starts_with(['[', '(']) {
+            return None;
+        }
+
+        let end = rest.find([']', ')'])?;
+        let (set, tail) = rest.split_at(end + 1);
+
+        let lower_inclusive = set.starts_with('[');
+        let upper_inclusive = set.ends_with(']');
+        let bounds = &set[1..set.len() - 1];
+
+        let range = match bounds.split_once(',') {
+            None if lower_inclusive && upper_inclusive && !bounds.trim().is_empty() => {
+                format!("={}", bounds.trim())
+            }
+            None => return None,
+            Some((_, upper)) if upper.contains(',') => return None,
+            Some((lower, upper)) => {
+                let lower = lower.trim();
+                let upper = upper.trim();
+
+                let mut predicates = Vec::new();
+                if !lower.is_empty() {
+                    let op = if lower_inclusive { ">=" } else { ">" };
+                    predicates.push(format!("{op}{lower}"));
+                }
+                if !upper.is_empty() {
+                    let op = if upper_inclusive { "<=" } else { "<" };
+                    predicates.push(format!("{op}{upper}"));
+                }
+
+                match predicates.is_empty() {
+                    true => String::from("*"),
+                    false => predicates.join(" "),
+                }
+            }
+        };
+
+        ranges.push(range);
+        rest = tail.trim_start();
+        rest = rest.strip_prefix(',').unwrap_or(rest).trim_start();
+    }
+
+    Some(any_version_as_empty(ranges))
+}
+
+/// Version ranges of the `java` dependency declared in fabric.mod.json or
+/// quilt.mod.json, any one of which is enough.
+pub fn parse_java_dependency(
+    reader: &mut (impl Read + Seek),
+) -> anyhow::Result<Option<Vec<String>>> {
+    let mut zip = zip::ZipArchive::new(reader)?;
+
+    'fabric_mod_json: {
+        let Ok(mut file) = zip.by_name("fabric.mod.json") else {
+            break 'fabric_mod_json;
+        };
+        let mut content = String::with_capacity(file.size() as usize);
+        file.read_to_string(&mut content)?;
+
+        let entries = match serde_json::from_str::<FabricModJson>(&content)? {
+            FabricModJson::Single(entry) => vec![*entry],
+            FabricModJson::List(entries) => entries,
+        };
+
+        let java = entries
+            .into_iter()
+            .find_map(|entry| entry.depends?.remove("java"));
+
+        return Ok(java.map(|range| match range {
+            FabricVersionRange::Single(range) => vec![range],
+            FabricVersionRange::OrList(ranges) => ranges,
+        }));
+    }
+
+    'quilt_mod_json: {
+        let Ok(mut file) = zip.by_name("quilt.mod.json") else {
+            break 'quilt_mod_json;
+        };
+        let mut content = String::with_capacity(file.size() as usize);
+        file.read_to_string(&mut content)?;
+
+        let quilt_mod_json = serde_json::from_str::<QuiltModJson>(&content)?;
+
+        let java = quilt_mod_json
+            .quilt_loader
+            .depends
+            .into_iter()
+            .flatten()
+            .find_map(|dependency| match dependency {
+                QuiltDependencyListing::Object(object)
+                    if object.id == "java" && !object.optional =>
+                {
+                    Some(object.versions)
+                }
+                _ => None,
+            });
+
+        return Ok(java.map(quilt_versions));
+    }
+
+    Ok(None)
+}
+
+/// Flattens quilt versions into ranges any of which is enough
+fn quilt_versions(versions: QuiltVersions) -> Vec<String> {
+    match versions {
+        QuiltVersions::Single(listing) => quilt_version_ranges(listing),
+        QuiltVersions::List(listings) => listings
+            .into_iter()
+            .flat_map(quilt_version_ranges)
+            .collect(),
+    }
+}
+
+/// Flattens a quilt version listing into ranges any of which is enough,
+/// `all` listings being joined into a single space separated range
+fn quilt_version_ranges(listing: QuiltVersionsListing) -> Vec<String> {
+    match listing {
+        QuiltVersionsListing::String(range) => vec![range],
+        QuiltVersionsListing::Object(QuiltVersionsObject::Any { any }) => {
+            any.into_iter().flat_map(quilt_version_ranges).collect()
+        }
+        QuiltVersionsListing::Object(QuiltVersionsObject::All { all }) => {
+            let ranges = all
+                .into_iter()
+                .map(|listing| match quilt_version_ranges(listing).as_slice() {
+                    [range] => Some(range.clone()),
+                    _ => None,
+                })
+                .collect::<Option<Vec<_>>>();
+
+            // nested alternatives can't be expressed as a single range
+            ranges
+                .map(|ranges| vec![ranges.join(" ")])
+                .unwrap_or_default()
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use std::io::{Cursor, Write};
 
     use zip::{write::FileOptions, CompressionMethod, ZipWriter};
 
-    use crate::domain::instance::info::ModLoaderType;
+    use crate::domain::instance::{
+        info::ModLoaderType, ModDependency, ModDependencyKind, ProvidedModId,
+    };
 
-    use super::{parse_metadata, ModFileMetadata};
+    use super::{maven_version_ranges, parse_java_dependency, parse_metadata, ModFileMetadata};
 
     pub fn parsemeta(path: &str, content: &str) -> anyhow::Result<Option<ModFileMetadata>> {
         // write meta zip
@@ -783,6 +1226,8 @@ mod test {
             authors: Some(String::from("TestAuthor1, TestAuthor2")),
             logo_file: Some(String::from("/test/logo")),
             modloaders: vec![ModLoaderType::Forge],
+            dependencies: vec![],
+            provides: vec![],
         });
 
         let returned = parsemeta("mcmod.info", mcmodinfo)?;
@@ -807,6 +1252,8 @@ mod test {
             authors: None,
             logo_file: None,
             modloaders: vec![ModLoaderType::Forge],
+            dependencies: vec![],
+            provides: vec![],
         });
 
         let returned = parsemeta("mcmod.info", mcmodinfo)?;
@@ -836,6 +1283,8 @@ mod test {
             authors: None,
             logo_file: None,
             modloaders: vec![ModLoaderType::Forge],
+            dependencies: vec![],
+            provides: vec![],
         });
 
         let returned = parsemeta("mcmod.info", mcmodinfo)?;
@@ -865,6 +1314,8 @@ mod test {
             authors: Some(String::from("TestAuthor1, TestAuthor2")),
             logo_file: Some(String::from("/test/logo")),
             modloaders: vec![ModLoaderType::Forge],
+            dependencies: vec![],
+            provides: vec![],
         });
 
         let returned = parsemeta("cccmod.info", mcmodinfo)?;
@@ -896,6 +1347,8 @@ mod test {
             authors: Some(String::from("TestAuthor1, TestAuthor2")),
             logo_file: Some(String::from("/test/logo")),
             modloaders: vec![ModLoaderType::Forge],
+            dependencies: vec![],
+            provides: vec![],
         });
 
         let returned = parsemeta("mcmod.info", mcmodinfo)?;
@@ -922,6 +1375,8 @@ mod test {
             authors: None,
             logo_file: None,
             modloaders: vec![ModLoaderType::Forge],
+            dependencies: vec![],
+            provides: vec![],
         });
 
         let returned = parsemeta("mcmod.info", mcmodinfo)?;
@@ -949,6 +1404,8 @@ logoFile = "test/logo"
             authors: Some(String::from("TestAuthor1, TestAuthor2")),
             logo_file: Some(String::from("test/logo")),
             modloaders: vec![ModLoaderType::Forge],
+            dependencies: vec![],
+            provides: vec![],
         });
 
         let returned = parsemeta("META-INF/mods.toml", modstoml)?;
@@ -973,6 +1430,8 @@ displayName = "TestMod"
             authors: None,
             logo_file: None,
             modloaders: vec![ModLoaderType::Forge],
+            dependencies: vec![],
+            provides: vec![],
         });
 
         let returned = parsemeta("META-INF/mods.toml", modstoml)?;
@@ -1038,6 +1497,13 @@ displayName = "TestMod"
             authors: Some(String::from("TestAuthor1, TestAuthor2")),
             logo_file: Some(String::from("assets/modid/icon75.png")),
             modloaders: vec![ModLoaderType::Fabric],
+            dependencies: vec![ModDependency {
+                modloader: ModLoaderType::Fabric,
+                modid: String::from("foobob"),
+                kind: ModDependencyKind::Optional,
+                versions: vec![],
+            }],
+            provides: vec![],
         });
 
         let returned = parsemeta("fabric.mod.json", modjson)?;
@@ -1100,12 +1566,305 @@ displayName = "TestMod"
             authors: Some(String::from("TestAuthor1, TestAuthor2")),
             logo_file: Some(String::from("assets/test_mod/icon.png")),
             modloaders: vec![ModLoaderType::Quilt],
+            dependencies: vec![
+                quilt_dependency("minecraft", &[">=1.20"]),
+                quilt_dependency("quilt_loader", &[">=0.19.1"]),
+                quilt_dependency("quilted_fabric_api", &[">=7.0.2"]),
+            ],
+            provides: vec![],
         });
 
+        fn quilt_dependency(modid: &str, versions: &[&str]) -> ModDependency {
+            ModDependency {
+                modloader: ModLoaderType::Quilt,
+                modid: String::from(modid),
+                kind: ModDependencyKind::Required,
+                versions: versions.iter().map(ToString::to_string).collect(),
+            }
+        }
+
         let returned = parsemeta("quilt.mod.json", modjson)?;
 
         assert_eq!(returned, expected);
 
         Ok(())
     }
+
+    #[test]
+    pub fn java_dependency() -> anyhow::Result<()> {
+        let fabric = r#"{
+            "schemaVersion": 1,
+            "id": "testmod",
+            "version": "1.0.0",
+            "depends": {
+                "fabricloader": ">=0.14.0",
+                "java": ">=17"
+            }
+        }"#;
+
+        let quilt = r#"{
+            "schema_version": 1,
+            "quilt_loader": {
+                "group": "com.test",
+                "id": "testmod",
+                "version": "1.0.0",
+                "depends": [
+                    "quilt_loader",
+                    { "id": "java", "versions": [">=17 <21", "21"] }
+                ]
+            }
+        }"#;
+
+        let java_dependency = |path: &str, content: &str| -> anyhow::Result<_> {
+            let mut vec = Vec::<u8>::new();
+            let mut zip = ZipWriter::new(Cursor::new(&mut vec));
+            let options =
+                FileOptions::<()>::default().compression_method(CompressionMethod::Stored);
+            zip.start_file(path, options)?;
+            zip.write_all(content.as_bytes())?;
+            zip.finish()?;
+
+            parse_java_dependency(&mut Cursor::new(&vec))
+        };
+
+        assert_eq!(
+            java_dependency("fabric.mod.json", fabric)?,
+            Some(vec![String::from(">=17")])
+        );
+        assert_eq!(
+            java_dependency("quilt.mod.json", quilt)?,
+            Some(vec![String::from(">=17 <21"), String::from("21")])
+        );
+        assert_eq!(java_dependency("mcmod.info", "[]")?, None);
+
+        Ok(())
+    }
+
+    #[test]
+    pub fn forge_toml_dependencies() -> anyhow::Result<()> {
+        let modstoml = r#"[[mods]]
+modId = "testmod"
+version = "1.0.0"
+displayName = "TestMod"
+
+[[mods]]
+modId = "testmod_api"
+version = "${file.jarVersion}"
+displayName = "TestMod API"
+
+[[dependencies.testmod]]
+modId = "forge"
+mandatory = true
+versionRange = "[47,)"
+
+[[dependencies.testmod]]
+modId = "jei"
+mandatory = false
+versionRange = "[15.2,16)"
+
+[[dependencies.testmod_api]]
+modId = "forge"
+mandatory = true
+versionRange = "[47,)"
+
+[[dependencies.testmod_api]]
+modId = "optifine"
+type = "incompatible"
+versionRange = "*"
+        "#;
+
+        let dependency = |modid: &str, kind, versions: &[&str]| ModDependency {
+            modloader: ModLoaderType::Forge,
+            modid: String::from(modid),
+            kind,
+            versions: versions.iter().map(ToString::to_string).collect(),
+        };
+
+        let returned = parsemeta("META-INF/mods.toml", modstoml)?.unwrap();
+
+        assert_eq!(
+            returned.dependencies,
+            vec![
+                dependency("forge", ModDependencyKind::Required, &[">=47"]),
+                dependency("jei", ModDependencyKind::Optional, &[">=15.2 <16"]),
+                dependency("optifine", ModDependencyKind::Breaks, &[]),
+            ]
+        );
+        assert_eq!(
+            returned.provides,
+            vec![ProvidedModId {
+                modid: String::from("testmod_api"),
+                version: Some(String::from("1.0.0")),
+            }]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    pub fn maven_ranges() {
+        let ranges = |spec: &str| {
+            maven_version_ranges(spec).map(|ranges| {
+                ranges
+                    .iter()
+                    .map(String::as_str)
+                    .collect::<Vec<_>>()
+                    .join(" || ")
+            })
+        };
+
+        assert_eq!(ranges("[1.0,2.0)").as_deref(), Some(">=1.0 <2.0"));
+        assert_eq!(ranges("(,1.0]").as_deref(), Some("<=1.0"));
+        assert_eq!(ranges("[1.5]").as_deref(), Some("=1.5"));
+        assert_eq!(ranges("(,1.0], [1.2,)").as_deref(), Some("<=1.0 || >=1.2"));
+        assert_eq!(ranges("1.0").as_deref(), Some(""));
+        assert_eq!(ranges("[1.0,)").as_deref(), Some(">=1.0"));
+        assert_eq!(ranges("[,)").as_deref(), Some(""));
+        assert_eq!(ranges("[1.0"), None);
+        assert_eq!(ranges("(1.0)"), None);
+    }
+
+    #[test]
+    pub fn fabric_provides_and_nested_jars() -> anyhow::Result<()> {
+        let nested = r#"{
+            "schemaVersion": 1,
+            "id": "nested_lib",
+            "version": "2.1.0",
+            "provides": ["nested_lib_alias"]
+        }"#;
+
+        let modjson = r#"{
+            "schemaVersion": 1,
+            "id": "testmod",
+            "version": "1.0.0",
+            "provides": ["testmod_legacy"],
+            "jars": [
+                { "file": "META-INF/jars/nested.jar" },
+                { "file": "META-INF/jars/missing.jar" }
+            ],
+            "depends": {
+                "fabricloader": ">=0.14.0",
+                "minecraft": ["1.20", "1.20.1"],
+                "fabric-api": "*"
+            },
+            "breaks": {
+                "sodium": "<0.5.0"
+            }
+        }"#;
+
+        let zip_bytes = |files: &[(&str, &[u8])]| -> anyhow::Result<Vec<u8>> {
+            let mut vec = Vec::<u8>::new();
+            let mut zip = ZipWriter::new(Cursor::new(&mut vec));
+            let options =
+                FileOptions::<()>::default().compression_method(CompressionMethod::Stored);
+            for (path, content) in files {
+                zip.start_file(*path, options)?;
+                zip.write_all(content)?;
+            }
+            zip.finish()?;
+            Ok(vec)
+        };
+
+        let nested_jar = zip_bytes(&[("fabric.mod.json", nested.as_bytes())])?;
+        let jar = zip_bytes(&[
+            ("fabric.mod.json", modjson.as_bytes()),
+            ("META-INF/jars/nested.jar", &nested_jar),
+        ])?;
+
+        let returned = parse_metadata(&mut Cursor::new(&jar))?.unwrap();
+
+        let dependency = |modid: &str, kind, versions: &[&str]| ModDependency {
+            modloader: ModLoaderType::Fabric,
+            modid: String::from(modid),
+            kind,
+            versions: versions.iter().map(ToString::to_string).collect(),
+        };
+
+        assert_eq!(
+            returned.dependencies,
+            vec![
+                dependency("fabric-api", ModDependencyKind::Required, &[]),
+                dependency("fabricloader", ModDependencyKind::Required, &[">=0.14.0"]),
+                dependency(
+                    "minecraft",
+                    ModDependencyKind::Required,
+                    &["1.20", "1.20.1"]
+                ),
+                dependency("sodium", ModDependencyKind::Breaks, &["<0.5.0"]),
+            ]
+        );
+
+        let provided = |modid: &str, version: &str| ProvidedModId {
+            modid: String::from(modid),
+            version: Some(String::from(version)),
+        };
+
+        assert_eq!(
+            returned.provides,
+            vec![
+                provided("testmod_legacy", "1.0.0"),
+                provided("nested_lib", "2.1.0"),
+                provided("nested_lib_alias", "2.1.0"),
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    pub fn quilt_provides() -> anyhow::Result<()> {
+        let modjson = r#"{
+            "schema_version": 1,
+            "quilt_loader": {
+                "group": "com.test",
+                "id": "testmod",
+                "version": "1.0.0",
+                "provides": [
+                    "com.test:testmod_legacy",
+                    { "id": "testmod_api", "version": "0.3.0" }
+                ],
+                "depends": [
+                    { "id": "sodium", "versions": ">=0.5.0", "optional": true }
+                ],
+                "breaks": [
+                    "optifabric"
+                ]
+            }
+        }"#;
+
+        let returned = parsemeta("quilt.mod.json", modjson)?.unwrap();
+
+        assert_eq!(
+            returned.provides,
+            vec![
+                ProvidedModId {
+                    modid: String::from("testmod_legacy"),
+                    version: Some(String::from("1.0.0")),
+                },
+                ProvidedModId {
+                    modid: String::from("testmod_api"),
+                    version: Some(String::from("0.3.0")),
+                },
+            ]
+        );
+        assert_eq!(
+            returned.dependencies,
+            vec![
+                ModDependency {
+                    modloader: ModLoaderType::Quilt,
+                    modid: String::from("sodium"),
+                    kind: ModDependencyKind::Optional,
+                    versions: vec![String::from(">=0.5.0")],
+                },
+                ModDependency {
+                    modloader: ModLoaderType::Quilt,
+                    modid: String::from("optifabric"),
+                    kind: ModDependencyKind::Breaks,
+                    versions: vec![],
+                },
+            ]
+        );
+
+        Ok(())
+    }
 }
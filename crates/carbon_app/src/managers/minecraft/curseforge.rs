@@ -2,7 +2,8 @@ use crate::managers::instance::modpack::packinfo::PackInfo;
 use crate::managers::vtask::Subtask;
 use crate::managers::App;
 use anyhow::Context;
-use carbon_net::{DownloadOptions, Downloadable, Progress};
+use carbon_compression::{DecompressOptions, ExtractionPolicy};
+use carbon_net::{Downloadable, Progress};
 use carbon_platforms::curseforge::filters::{ModsParameters, ModsParametersBody};
 use carbon_platforms::curseforge::{self, CurseForgeResponse, File, HashAlgo};
 use carbon_rt_path::InstancePath;
@@ -11,6 +12,7 @@ use std::borrow::BorrowMut;
 use std::collections::HashMap;
 use std::path::Path;
 use std::sync::Arc;
+use tokio::sync::watch;
 use tokio::task::spawn_blocking;
 use tracing::trace;
 
@@ -85,7 +87,10 @@ pub async fn download_modpack_zip(
 
     carbon_net::download_multiple(
         &[file_downloadable],
-        DownloadOptions::builder().concurrency(1).build(),
+        app.settings_manager()
+            .download_options()
+            .concurrency(1)
+            .build(),
     )
     .await
     .with_context(|| {
@@ -111,10 +116,8 @@ pub async fn prepare_modpack_from_zip(
     t_addon_metadata: Subtask,
     progress_percentage_sender: tokio::sync::watch::Sender<ProgressState>,
 ) -> anyhow::Result<ModpackInfo> {
-    let progress_percentage_sender = Arc::new(progress_percentage_sender);
-
     let file_path_clone = zip_path.to_path_buf();
-    let (mut archive, manifest) = spawn_blocking(move || {
+    let manifest = spawn_blocking(move || {
         let file = std::fs::File::open(file_path_clone)?;
         let mut archive = zip::ZipArchive::new(file)?;
         let manifest: curseforge::manifest::Manifest = {
@@ -122,7 +125,7 @@ pub async fn prepare_modpack_from_zip(
             serde_json::from_reader(file)?
         };
 
-        Ok::<_, anyhow::Error>((archive, manifest))
+        Ok::<_, anyhow::Error>(manifest)
     })
     .await??;
 
@@ -241,47 +244,35 @@ pub async fn prepare_modpack_from_zip(
     t_addon_metadata.complete_opaque();
 
     if !skip_overrides {
-        let override_folder_name = manifest.overrides.clone();
         let override_full_path = instance_path.get_data_path();
         tokio::fs::create_dir_all(&override_full_path).await?;
-        spawn_blocking(move || {
-            let total_archive_files = archive.len() as u64;
-            for i in 0..archive.len() {
-                let mut file = archive.by_index(i)?;
-
-                if !(*file.name()).starts_with(&override_folder_name) {
-                    continue;
-                }
-
-                let outpath = match file.enclosed_name() {
-                    Some(path) => Path::new(&override_full_path)
-                        .join(path.strip_prefix(&override_folder_name).unwrap()),
-                    None => continue,
-                };
-
-                if (*file.name()).ends_with('/') {
-                    continue;
-                } else {
-                    if let Some(p) = outpath.parent() {
-                        if !p.exists() {
-                            std::fs::create_dir_all(p)?;
-                        }
-                    }
 
-                    let mut outfile = std::fs::File::create(&outpath)?;
-                    std::io::copy(&mut file, &mut outfile)?;
-                }
+        let (p_sender, mut p_recv) = watch::channel(carbon_compression::Progress::new());
 
-                progress_percentage_sender.send_modify(|progress| {
-                    progress
+        let progress_proxy = tokio::spawn(async move {
+            while p_recv.changed().await.is_ok() {
+                let progress = *p_recv.borrow();
+                progress_percentage_sender.send_modify(|state| {
+                    state
                         .extract_addon_overrides
-                        .set((i as u64 + 1, total_archive_files));
+                        .set((progress.current_count, progress.total_count));
                 });
             }
+        });
+
+        carbon_compression::decompress_with_options(
+            zip_path,
+            &override_full_path,
+            DecompressOptions::builder()
+                .subfolder(&manifest.overrides)
+                .policy(ExtractionPolicy::strict())
+                .progress_sender(p_sender)
+                .build(),
+        )
+        .await
+        .context("Could not extract the modpack overrides")?;
 
-            Ok::<(), anyhow::Error>(())
-        })
-        .await??;
+        progress_proxy.await?;
     }
 
     Ok(ModpackInfo {
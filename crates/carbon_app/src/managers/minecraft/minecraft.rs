@@ -1,3 +1,4 @@
+use crate::managers::account::yggdrasil::authlib_injector_path;
 use crate::managers::account::{FullAccount, FullAccountType};
 use crate::{
     app_version::APP_VERSION,
@@ -452,9 +453,19 @@ pub async fn generate_startup_command(
 
     let extra_args_regex = Regex::new(r#"("(?P<quoted>(\\"|[^"])*)"|(?P<raw>([^ ]+)))"#).unwrap();
 
-    let player_token = match full_account.type_ {
-        FullAccountType::Offline => "offline".to_owned(),
-        FullAccountType::Microsoft { access_token, .. } => access_token,
+    // authlib-injector redirects authentication to the account's yggdrasil server
+    let authlib_injector_arg = match &full_account.type_ {
+        FullAccountType::Yggdrasil { api_root, .. } => Some(format!(
+            "-javaagent:{}={api_root}",
+            authlib_injector_path(runtime_path).display()
+        )),
+        _ => None,
+    };
+
+    let (player_token, user_type) = match full_account.type_ {
+        FullAccountType::Offline => ("offline".to_owned(), "msa"),
+        FullAccountType::Microsoft { access_token, .. } => (access_token, "msa"),
+        FullAccountType::Yggdrasil { access_token, .. } => (access_token, "mojang"),
     };
 
     let client_jar_path = runtime_path
@@ -487,7 +498,7 @@ pub async fn generate_startup_command(
         auth_uuid: full_account.uuid,
         auth_access_token: player_token.clone(),
         auth_session: player_token,
-        user_type: "msa".to_owned(),
+        user_type: user_type.to_owned(),
         version_type: version.type_.as_str().to_string(),
         user_properties: "{}".to_owned(),
     };
@@ -569,6 +580,7 @@ pub async fn generate_startup_command(
 
     command.push(format!("-Xmx{xmx_memory}m"));
     command.push(format!("-Xms{xms_memory}m"));
+    command.extend(authlib_injector_arg);
 
     if let Some(logging_xml) = version.logging {
         if let Some(client) = logging_xml.get(&daedalus::minecraft::LoggingConfigName::Client) {
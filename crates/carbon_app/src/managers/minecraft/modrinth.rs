@@ -2,13 +2,15 @@ use super::UpdateValue;
 use crate::managers::instance::modpack::packinfo::PackInfo;
 use crate::managers::App;
 use anyhow::Context;
-use carbon_net::{DownloadOptions, Downloadable, Progress};
+use carbon_compression::{DecompressOptions, ExtractionPolicy};
+use carbon_net::{Downloadable, Progress};
 use carbon_platforms::modrinth::version::{ModpackIndex, ModrinthEnvironmentSupport, VersionFile};
 use carbon_rt_path::InstancePath;
 use std::borrow::BorrowMut;
 use std::path::{Component, Path, PathBuf};
 use std::sync::Arc;
 use thiserror::Error;
+use tokio::sync::watch;
 use tokio::task::spawn_blocking;
 
 #[derive(Error, Debug)]
@@ -144,7 +146,10 @@ pub async fn download_mrpack(
 
     carbon_net::download_multiple(
         &[file_downloadable],
-        DownloadOptions::builder().concurrency(1).build(),
+        app.settings_manager()
+            .download_options()
+            .concurrency(1)
+            .build(),
     )
     .await
     .with_context(|| {
@@ -168,7 +173,7 @@ pub async fn prepare_modpack_from_mrpack(
     progress_percentage_sender: tokio::sync::watch::Sender<ProgressState>,
 ) -> anyhow::Result<ModpackInfo> {
     let file_path_clone = mrpack_path.to_path_buf();
-    let (mut archive, index) = spawn_blocking(move || {
+    let index = spawn_blocking(move || {
         let file = std::fs::File::open(file_path_clone)?;
         let mut archive = zip::ZipArchive::new(file)?;
         let index: ModpackIndex = {
@@ -176,7 +181,7 @@ pub async fn prepare_modpack_from_mrpack(
             serde_json::from_reader(file)?
         };
 
-        Ok::<_, anyhow::Error>((archive, index))
+        Ok::<_, anyhow::Error>(index)
     })
     .await??;
 
@@ -255,47 +260,34 @@ pub async fn prepare_modpack_from_mrpack(
 
     if !skip_overlays {
         let data_path = instance_path.get_data_path();
-        let overrides_folder_name = "overrides";
-        spawn_blocking(move || {
-            let total_archive_files = archive.len() as u64;
-            for i in 0..archive.len() {
-                let mut file = archive.by_index(i)?;
-                if !(file.name().starts_with(&overrides_folder_name)) {
-                    continue;
-                }
-
-                let out_path = match file.enclosed_name() {
-                    Some(path) => secure_path_join(
-                        Path::new(&data_path),
-                        path.strip_prefix(&overrides_folder_name).expect(
-                            "valid path as we skipped paths that did not start with this prefix",
-                        ),
-                    )?,
-                    None => continue,
-                };
-
-                if file.name().ends_with('/') {
-                    continue;
-                } else {
-                    if let Some(parent) = out_path.parent() {
-                        if !parent.exists() {
-                            std::fs::create_dir_all(parent)?;
-                        }
-                    }
-                    let mut out_file = std::fs::File::create(&out_path)?;
 
-                    std::io::copy(&mut file, &mut out_file)?;
-                }
+        let (p_sender, mut p_recv) = watch::channel(carbon_compression::Progress::new());
 
+        let progress_proxy = tokio::spawn(async move {
+            while p_recv.changed().await.is_ok() {
+                let progress = *p_recv.borrow();
                 progress_percentage_sender.send(ProgressState::ExtractingPackOverrides(
-                    i as u64,
-                    total_archive_files,
+                    progress.current_count,
+                    progress.total_count,
                 ))?;
             }
 
             Ok::<(), anyhow::Error>(())
-        })
-        .await??;
+        });
+
+        carbon_compression::decompress_with_options(
+            mrpack_path,
+            &data_path,
+            DecompressOptions::builder()
+                .subfolder("overrides")
+                .policy(ExtractionPolicy::strict())
+                .progress_sender(p_sender)
+                .build(),
+        )
+        .await
+        .context("Could not extract the modpack overrides")?;
+
+        progress_proxy.await??;
     }
 
     Ok(ModpackInfo {
@@ -32,7 +32,7 @@ mod minecraft;
 pub mod modplatforms;
 mod prisma_client;
 pub mod rich_presence;
-mod settings;
+pub mod settings;
 pub mod system_info;
 pub mod vtask;
 
@@ -49,7 +49,9 @@ mod app {
     use sentry::capture_error;
     use tracing::{error, info};
 
-    use crate::{cache_middleware, domain, iridium_client::get_client};
+    use crate::{
+        cache_middleware, domain, iridium_client::get_client, mirror_middleware::MirrorMiddleware,
+    };
 
     use self::java::{
         discovery::{Discovery, RealDiscovery},
@@ -107,13 +109,25 @@ mod app {
                     }
                 };
 
+            // the os secret service is only ever used by the real app, tests use a key file
+            let token_vault = {
+                let runtime_path = runtime_path.clone();
+                tokio::task::spawn_blocking(move || {
+                    account::vault::TokenVault::load(&runtime_path, !cfg!(test))
+                })
+                .await
+                .expect("loading the token vault panicked")
+            };
+
             let app = unsafe {
                 let app = Arc::new(UnsafeCell::new(MaybeUninit::<AppInner>::uninit()));
                 let unsaferef = UnsafeAppRef(Arc::downgrade(&app));
 
+                let download_mirrors = Arc::default();
                 let http_client = cache_middleware::new_client(
                     unsaferef.clone(),
-                    get_client(gdl_base_api.clone()),
+                    get_client(gdl_base_api.clone())
+                        .with(MirrorMiddleware::new(Arc::clone(&download_mirrors))),
                 );
                 let inner = Arc::into_raw(app);
 
@@ -122,10 +136,15 @@ mod app {
                         runtime_path,
                         http_client.clone(),
                         gdl_base_api.clone(),
+                        download_mirrors,
                     ),
                     java_manager: JavaManager::new(),
                     minecraft_manager: MinecraftManager::new(),
-                    account_manager: AccountManager::new(http_client.clone(), gdl_base_api.clone()),
+                    account_manager: AccountManager::new(
+                        http_client.clone(),
+                        gdl_base_api.clone(),
+                        token_vault,
+                    ),
                     modplatforms_manager: ModplatformsManager::new(unsaferef, gdl_base_api.clone()),
                     download_manager: DownloadManager::new(),
                     instance_manager: InstanceManager::new(),
@@ -148,6 +167,14 @@ mod app {
                 Arc::from_raw(inner.cast::<AppInner>())
             };
 
+            if let Err(e) = app.settings_manager().load_download_settings().await {
+                error!("Failed to load download settings: {e:?}");
+            }
+
+            if let Err(e) = app.account_manager().seal_plaintext_tokens().await {
+                error!("Failed to seal plaintext account tokens: {e:?}");
+            }
+
             let timer = tokio::time::Instant::now();
             info!("Starting account refresh service");
             account::AccountRefreshService::start(Arc::downgrade(&app)).await;
@@ -162,24 +189,6 @@ mod app {
                 _app.meta_cache_manager().launch_background_tasks().await;
             });
 
-            let _app = app.clone();
-            tokio::spawn(async move {
-                let settings = _app.settings_manager().get_settings().await;
-
-                match settings {
-                    Ok(settings) => {
-                        let show_app_close_warning = settings.show_app_close_warning;
-                        info!("_SHOW_APP_CLOSE_WARNING_:{}", show_app_close_warning);
-                        println!("_SHOW_APP_CLOSE_WARNING_:{}", show_app_close_warning);
-                        info!("_POTATO_PC_MODE_:{}", settings.reduced_motion);
-                        println!("_POTATO_PC_MODE_:{}", settings.reduced_motion);
-                    }
-                    Err(e) => {
-                        error!("Error getting settings: {e}");
-                    }
-                };
-            });
-
             let _app = app.clone();
             tokio::spawn(async move {
                 let _ = _app.clone().rich_presence_manager().start_presence().await;
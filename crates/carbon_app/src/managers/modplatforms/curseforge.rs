@@ -35,6 +35,10 @@ impl CurseForge {
         }
     }
 
+    pub fn base_url(&self) -> &Url {
+        &self.base_url
+    }
+
     #[tracing::instrument(skip(self))]
     pub async fn get_modloaders(
         &self,
@@ -1,13 +1,22 @@
+use std::sync::Arc;
+
+use anyhow::Context;
+use carbon_repos::db::mod_source_credential as credentialdb;
+
+use crate::api::keys::modplatforms::SOURCES_GET_CREDENTIALS;
 use crate::{cache_middleware, iridium_client::get_client};
 
-use super::{ManagerRef, UnsafeAppRef};
+use super::{instance::installer::Installer, ManagerRef, UnsafeAppRef};
+use source::{ModSource, SourceCredential};
 
 pub mod curseforge;
 pub mod modrinth;
+pub mod source;
 
 pub struct ModplatformsManager {
     pub curseforge: curseforge::CurseForge,
     pub modrinth: modrinth::Modrinth,
+    pub sources: source::ModSourceRegistry,
 }
 
 impl ModplatformsManager {
@@ -18,6 +27,10 @@ impl ModplatformsManager {
                 get_client(gdl_base_api.clone()),
             )),
             modrinth: modrinth::Modrinth::new(cache_middleware::new_client(
+                unsafeappref.clone(),
+                get_client(gdl_base_api.clone()),
+            )),
+            sources: source::ModSourceRegistry::new(cache_middleware::new_client(
                 unsafeappref,
                 get_client(gdl_base_api),
             )),
@@ -25,4 +38,205 @@ impl ModplatformsManager {
     }
 }
 
-impl ManagerRef<'_, ModplatformsManager> {}
+impl ManagerRef<'_, ModplatformsManager> {
+    /// Create a source for a repository, authenticated with the repository's
+    /// credential if one is stored.
+    pub async fn mod_source(
+        self,
+        source_id: &str,
+        repository: Option<&str>,
+    ) -> anyhow::Result<Arc<dyn ModSource>> {
+        let source = self.sources.get(source_id, repository, None)?;
+        let repository = source.repository().to_string();
+
+        match self.source_credential(source_id, &repository).await? {
+            Some(credential) => self
+                .sources
+                .get(source_id, Some(&repository), Some(credential)),
+            None => Ok(source),
+        }
+    }
+
+    /// Installer for a version of a project on any registered source.
+    pub async fn installer(
+        self,
+        source_id: &str,
+        repository: Option<&str>,
+        project_id: &str,
+        version_id: &str,
+    ) -> anyhow::Result<Installer> {
+        self.mod_source(source_id, repository)
+            .await?
+            .installer(project_id, version_id)
+            .await
+    }
+
+    async fn source_credential(
+        self,
+        source_id: &str,
+        repository: &str,
+    ) -> anyhow::Result<Option<SourceCredential>> {
+        let Some(stored) = self
+            .app
+            .prisma_client
+            .mod_source_credential()
+            .find_unique(credentialdb::UniqueWhereParam::SourceIdRepositoryEquals(
+                source_id.to_string(),
+                repository.to_string(),
+            ))
+            .exec()
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        let secret = self
+            .app
+            .account_manager()
+            .vault()
+            .open(&credential_aad(source_id, repository), &stored.secret)
+            .with_context(|| {
+                format!("credential of {source_id} repository {repository} can't be opened")
+            })?;
+
+        Ok(Some(serde_json::from_str(&secret)?))
+    }
+
+    /// Store the credential of a repository, replacing any previous one.
+    pub async fn set_source_credential(
+        self,
+        source_id: &str,
+        repository: Option<&str>,
+        credential: SourceCredential,
+    ) -> anyhow::Result<()> {
+        // keyed like `mod_source` looks them up, with the source's default repository if missing
+        let repository = self
+            .sources
+            .get(source_id, repository, None)?
+            .repository()
+            .to_string();
+
+        // sources without credentials, such as curseforge, reject them here
+        // rather than every time they are used
+        self.sources
+            .get(source_id, Some(&repository), Some(credential.clone()))?;
+
+        let secret = self.app.account_manager().vault().seal(
+            &credential_aad(source_id, &repository),
+            &serde_json::to_string(&credential)?,
+        )?;
+
+        self.app
+            .prisma_client
+            .mod_source_credential()
+            .upsert(
+                credentialdb::UniqueWhereParam::SourceIdRepositoryEquals(
+                    source_id.to_string(),
+                    repository.clone(),
+                ),
+                credentialdb::create(
+                    source_id.to_string(),
+                    repository,
+                    credential.kind().to_string(),
+                    secret.clone(),
+                    Vec::new(),
+                ),
+                vec![
+                    credentialdb::kind::set(credential.kind().to_string()),
+                    credentialdb::secret::set(secret),
+                ],
+            )
+            .exec()
+            .await?;
+
+        self.app.invalidate(SOURCES_GET_CREDENTIALS, None);
+        Ok(())
+    }
+
+    pub async fn remove_source_credential(
+        self,
+        source_id: &str,
+        repository: Option<&str>,
+    ) -> anyhow::Result<()> {
+        let repository = self
+            .sources
+            .get(source_id, repository, None)?
+            .repository()
+            .to_string();
+
+        self.app
+            .prisma_client
+            .mod_source_credential()
+            .delete_many(vec![
+                credentialdb::source_id::equals(source_id.to_string()),
+                credentialdb::repository::equals(repository),
+            ])
+            .exec()
+            .await?;
+
+        self.app.invalidate(SOURCES_GET_CREDENTIALS, None);
+        Ok(())
+    }
+
+    /// Repositories with a stored credential. The secrets stay sealed.
+    pub async fn source_credentials(self) -> anyhow::Result<Vec<credentialdb::Data>> {
+        Ok(self
+            .app
+            .prisma_client
+            .mod_source_credential()
+            .find_many(Vec::new())
+            .exec()
+            .await?)
+    }
+}
+
+/// Binds a sealed credential to its repository, so it doesn't open for another one.
+fn credential_aad(source_id: &str, repository: &str) -> String {
+    format!("ModSourceCredential/{source_id}/{repository}")
+}
+
+#[cfg(test)]
+mod test {
+    use super::source::SourceCredential;
+
+    #[tokio::test]
+    async fn source_credentials_are_sealed() {
+        let app = crate::setup_managers_for_test().await;
+        let modplatforms = app.modplatforms_manager();
+
+        modplatforms
+            .set_source_credential(
+                "maven",
+                Some("https://maven.example.com/private"),
+                SourceCredential::Basic {
+                    username: String::from("user"),
+                    password: String::from("secret"),
+                },
+            )
+            .await
+            .unwrap();
+
+        let stored = modplatforms.source_credentials().await.unwrap();
+        assert_eq!(stored.len(), 1);
+        assert_eq!(stored[0].repository, "https://maven.example.com/private/");
+        assert_eq!(stored[0].kind, "basic");
+        assert!(!stored[0].secret.contains("secret"));
+
+        // looked up with the url as stored alongside installed mods
+        let credential = modplatforms
+            .source_credential("maven", "https://maven.example.com/private/")
+            .await
+            .unwrap();
+        assert!(matches!(
+            credential,
+            Some(SourceCredential::Basic { password, .. }) if password == "secret"
+        ));
+
+        modplatforms
+            .remove_source_credential("maven", Some("https://maven.example.com/private"))
+            .await
+            .unwrap();
+
+        assert!(modplatforms.source_credentials().await.unwrap().is_empty());
+    }
+}
@@ -37,6 +37,10 @@ impl Modrinth {
         }
     }
 
+    pub fn base_url(&self) -> &Url {
+        &self.base_url
+    }
+
     #[tracing::instrument(skip(self))]
     pub async fn get_categories(&self) -> anyhow::Result<CategoriesResponse> {
         let url = self.base_url.join("tag/category")?;
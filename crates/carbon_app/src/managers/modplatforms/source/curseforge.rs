@@ -0,0 +1,241 @@
+use std::sync::Arc;
+
+use anyhow::{anyhow, bail};
+use carbon_platforms::{
+    curseforge::{
+        filters::{ModFileParameters, ModFilesParameters, ModFilesParametersQuery},
+        File, HashAlgo, ModLoaderType,
+    },
+    source::{SourceVersion, SourceVersionFilters},
+    RemoteVersion,
+};
+use carbon_repos::db::mod_metadata as metadb;
+use reqwest_middleware::ClientWithMiddleware;
+use url::Url;
+
+use super::{ModSource, SourceCredential};
+use crate::managers::{
+    instance::installer::{CurseforgeModInstaller, Installer, IntoInstaller},
+    metadata::cache::curseforge::cache_known_curseforge_file,
+    modplatforms::curseforge::CurseForge,
+    App,
+};
+
+pub const SOURCE_ID: &str = "curseforge";
+
+/// Mods hosted on CurseForge, through the api proxy.
+///
+/// Projects are mod ids and versions are file ids. Versions keep the CurseForge
+/// file model so installs pull in the dependencies it declares.
+pub struct CurseforgeSource {
+    curseforge: CurseForge,
+}
+
+impl CurseforgeSource {
+    pub fn factory(
+        client: ClientWithMiddleware,
+        repository: Option<Url>,
+        credential: Option<SourceCredential>,
+    ) -> anyhow::Result<Arc<dyn ModSource>> {
+        let curseforge = CurseForge::new(client);
+
+        let is_base_url = |url: &Url| {
+            url.as_str().trim_end_matches('/')
+                == curseforge.base_url().as_str().trim_end_matches('/')
+        };
+
+        if repository.is_some_and(|repository| !is_base_url(&repository)) {
+            bail!("curseforge has a single repository");
+        }
+
+        if credential.is_some() {
+            bail!("curseforge does not take credentials");
+        }
+
+        Ok(Arc::new(Self { curseforge }))
+    }
+
+    async fn files(
+        &self,
+        project_id: &str,
+        filters: &SourceVersionFilters,
+    ) -> anyhow::Result<Vec<File>> {
+        let mod_loader_type = filters.loaders.iter().find_map(|loader| match &**loader {
+            "forge" => Some(ModLoaderType::Forge),
+            "neoforge" => Some(ModLoaderType::NeoForge),
+            "fabric" => Some(ModLoaderType::Fabric),
+            "quilt" => Some(ModLoaderType::Quilt),
+            _ => None,
+        });
+
+        let files = self
+            .curseforge
+            .get_mod_files(ModFilesParameters {
+                mod_id: project_id.parse()?,
+                query: ModFilesParametersQuery {
+                    game_version: filters.game_version.clone(),
+                    game_version_type_id: None,
+                    mod_loader_type,
+                    index: None,
+                    page_size: None,
+                },
+            })
+            .await?
+            .data;
+
+        Ok(files)
+    }
+
+    async fn file(&self, project_id: &str, version_id: &str) -> anyhow::Result<File> {
+        let file = self
+            .curseforge
+            .get_mod_file(ModFileParameters {
+                mod_id: project_id.parse()?,
+                file_id: version_id.parse()?,
+            })
+            .await?
+            .data;
+
+        Ok(file)
+    }
+
+    /// Files without a download url can only be fetched with a privileged api key.
+    fn version(&self, file: File) -> anyhow::Result<SourceVersion> {
+        let url = file.download_url.ok_or_else(|| {
+            anyhow!(
+                "curseforge file {}/{} cannot be downloaded without privileged api key",
+                file.mod_id,
+                file.id
+            )
+        })?;
+
+        let (loaders, game_versions) = split_game_versions(file.game_versions);
+
+        Ok(SourceVersion {
+            source: SOURCE_ID.to_string(),
+            repository: self.curseforge.base_url().to_string(),
+            project_id: file.mod_id.to_string(),
+            id: file.id.to_string(),
+            name: file.display_name,
+            file_name: file.file_name,
+            url,
+            sha1: file
+                .hashes
+                .into_iter()
+                .find(|hash| matches!(hash.algo, HashAlgo::Sha1))
+                .map(|hash| hash.value),
+            sha256: None,
+            size: Some(file.file_length as u64),
+            game_versions,
+            loaders,
+            channel: file.release_type.into(),
+            published: file.file_date,
+        })
+    }
+}
+
+/// CurseForge lists modloaders and environments among the game versions of a file.
+fn split_game_versions(versions: Vec<String>) -> (Vec<String>, Vec<String>) {
+    let (loaders, versions): (Vec<_>, Vec<_>) = versions
+        .into_iter()
+        .map(|version| version.to_lowercase())
+        .partition(|version| matches!(&**version, "forge" | "neoforge" | "fabric" | "quilt"));
+
+    let game_versions = versions
+        .into_iter()
+        .filter(|version| version.starts_with(|c: char| c.is_ascii_digit()))
+        .collect();
+
+    (loaders, game_versions)
+}
+
+#[async_trait::async_trait]
+impl ModSource for CurseforgeSource {
+    fn id(&self) -> &'static str {
+        SOURCE_ID
+    }
+
+    fn repository(&self) -> &Url {
+        self.curseforge.base_url()
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn list_versions(
+        &self,
+        project_id: &str,
+        filters: &SourceVersionFilters,
+    ) -> anyhow::Result<Vec<SourceVersion>> {
+        let mut versions = self
+            .files(project_id, filters)
+            .await?
+            .into_iter()
+            .filter_map(|file| self.version(file).ok())
+            .filter(|version| filters.matches(version))
+            .collect::<Vec<_>>();
+
+        versions.sort_by(|a, b| b.published.cmp(&a.published));
+        Ok(versions)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn get_version(
+        &self,
+        project_id: &str,
+        version_id: &str,
+    ) -> anyhow::Result<SourceVersion> {
+        self.version(self.file(project_id, version_id).await?)
+    }
+
+    async fn remote_versions(
+        &self,
+        project_id: &str,
+        filters: &SourceVersionFilters,
+    ) -> anyhow::Result<Vec<RemoteVersion>> {
+        Ok(self
+            .files(project_id, filters)
+            .await?
+            .into_iter()
+            .map(RemoteVersion::Curseforge)
+            .collect())
+    }
+
+    async fn installer(&self, project_id: &str, version_id: &str) -> anyhow::Result<Installer> {
+        let file = self.file(project_id, version_id).await?;
+
+        Ok(CurseforgeModInstaller::from_file(file)?.into_installer())
+    }
+
+    async fn cache_known_version(
+        &self,
+        app: &App,
+        metadata: &metadb::Data,
+        project_id: &str,
+        version_id: &str,
+    ) -> anyhow::Result<()> {
+        cache_known_curseforge_file(
+            app,
+            metadata.id.clone(),
+            metadata.murmur_2 as u32,
+            project_id.parse()?,
+            version_id.parse()?,
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::split_game_versions;
+
+    #[test]
+    fn splits_loaders_from_game_versions() {
+        let (loaders, game_versions) = split_game_versions(
+            ["1.20.1", "Forge", "NeoForge", "Client", "Java 17", "1.20"]
+                .map(String::from)
+                .to_vec(),
+        );
+
+        assert_eq!(loaders, ["forge", "neoforge"]);
+        assert_eq!(game_versions, ["1.20.1", "1.20"]);
+    }
+}
@@ -0,0 +1,458 @@
+use std::sync::Arc;
+
+use anyhow::{anyhow, bail};
+use carbon_platforms::{
+    source::{
+        channel_from_version,
+        github::{Release, ReleaseAsset},
+        loaders_from_file_name, SourceVersion, SourceVersionFilters,
+    },
+    ModChannel,
+};
+use reqwest::header::{HeaderMap, ACCEPT, LINK};
+use reqwest_middleware::{ClientWithMiddleware, RequestBuilder};
+use tracing::trace;
+use url::Url;
+
+use super::{ModSource, SourceCredential};
+use crate::error::request::GoodJsonRequestError;
+
+pub const SOURCE_ID: &str = "github";
+pub const GITHUB_API_BASE: &str = "https://api.github.com/";
+
+/// Mods published as jar assets on the releases of a GitHub repository.
+///
+/// Projects are `owner/repo` and versions are `tag/asset name`, as a release
+/// may carry one jar per modloader. Private repositories need a token credential.
+pub struct GithubReleasesSource {
+    client: ClientWithMiddleware,
+    api_base: Url,
+    credential: Option<SourceCredential>,
+}
+
+impl GithubReleasesSource {
+    pub fn new(client: ClientWithMiddleware, api_base: Url) -> Self {
+        Self {
+            client,
+            api_base,
+            credential: None,
+        }
+    }
+
+    pub fn with_credential(mut self, credential: Option<SourceCredential>) -> Self {
+        self.credential = credential;
+        self
+    }
+
+    pub fn factory(
+        client: ClientWithMiddleware,
+        api_base: Option<Url>,
+        credential: Option<SourceCredential>,
+    ) -> anyhow::Result<Arc<dyn ModSource>> {
+        let api_base = match api_base {
+            Some(api_base) => api_base,
+            None => GITHUB_API_BASE.parse()?,
+        };
+
+        Ok(Arc::new(
+            Self::new(client, api_base).with_credential(credential),
+        ))
+    }
+
+    fn request(&self, url: &str) -> RequestBuilder {
+        let request = self.client.get(url);
+
+        match &self.credential {
+            Some(credential) => credential.authorize(request),
+            None => request,
+        }
+    }
+
+    fn repo_url(&self, project_id: &str, path: &[&str]) -> anyhow::Result<Url> {
+        let Some((owner, repo)) = project_id.split_once('/') else {
+            bail!("github project '{project_id}' is not in the form 'owner/repo'");
+        };
+
+        let mut url = self.api_base.clone();
+        url.path_segments_mut()
+            .map_err(|_| anyhow!("github api base {} cannot be a base", self.api_base))?
+            .pop_if_empty()
+            .extend(["repos", owner, repo])
+            .extend(path);
+
+        Ok(url)
+    }
+
+    async fn get<T: serde::de::DeserializeOwned>(&self, url: Url) -> anyhow::Result<T> {
+        Ok(self.get_page(url).await?.0)
+    }
+
+    /// Fetch one page of a listing, along with the url of the next page if any.
+    async fn get_page<T: serde::de::DeserializeOwned>(
+        &self,
+        url: Url,
+    ) -> anyhow::Result<(T, Option<Url>)> {
+        trace!("GET {}", url);
+
+        let response = self
+            .request(url.as_str())
+            .header(ACCEPT, "application/vnd.github+json")
+            .send()
+            .await?;
+
+        let next = next_page(response.headers())
+            .map(|next| self.api_base.join(next))
+            .transpose()?;
+
+        // the token must not leave the api host
+        if let Some(next) = &next {
+            if next.origin() != self.api_base.origin() {
+                bail!("github returned a next page on another host: {next}");
+            }
+        }
+
+        Ok((response.json_with_context().await?, next))
+    }
+
+    fn version(
+        &self,
+        project_id: &str,
+        release: &Release,
+        asset: &ReleaseAsset,
+    ) -> anyhow::Result<SourceVersion> {
+        let channel = match release.prerelease {
+            true => channel_from_version(&release.tag_name).min(ModChannel::Beta),
+            false => ModChannel::Stable,
+        };
+
+        // assets of private repositories can only be downloaded through the api
+        let url = match self.credential {
+            Some(_) => self
+                .repo_url(project_id, &["releases", "assets", &asset.id.to_string()])?
+                .to_string(),
+            None => asset.browser_download_url.clone(),
+        };
+
+        Ok(SourceVersion {
+            source: SOURCE_ID.to_string(),
+            repository: self.api_base.to_string(),
+            project_id: project_id.to_string(),
+            id: format!("{}/{}", release.tag_name, asset.name),
+            name: release
+                .name
+                .clone()
+                .filter(|name| !name.is_empty())
+                .unwrap_or_else(|| release.tag_name.clone()),
+            file_name: asset.name.clone(),
+            url,
+            sha1: None,
+            sha256: asset.sha256().map(ToString::to_string),
+            size: Some(asset.size),
+            game_versions: Vec::new(),
+            loaders: loaders_from_file_name(&asset.name),
+            channel,
+            published: release.published_at.unwrap_or(release.created_at),
+        })
+    }
+}
+
+/// The target of the `rel="next"` entry of a `Link` header.
+fn next_page(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get_all(LINK)
+        .iter()
+        .filter_map(|link| link.to_str().ok())
+        .flat_map(|link| link.split(','))
+        .find_map(|entry| {
+            let (target, params) = entry.split_once(';')?;
+
+            params
+                .split(';')
+                .any(|param| param.trim() == r#"rel="next""#)
+                .then(|| target.trim().strip_prefix('<')?.strip_suffix('>'))?
+        })
+}
+
+#[async_trait::async_trait]
+impl ModSource for GithubReleasesSource {
+    fn id(&self) -> &'static str {
+        SOURCE_ID
+    }
+
+    fn repository(&self) -> &Url {
+        &self.api_base
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn list_versions(
+        &self,
+        project_id: &str,
+        filters: &SourceVersionFilters,
+    ) -> anyhow::Result<Vec<SourceVersion>> {
+        let mut url = self.repo_url(project_id, &["releases"])?;
+        url.set_query(Some("per_page=100"));
+
+        let mut releases = Vec::new();
+        let mut next = Some(url);
+
+        while let Some(url) = next {
+            let (page, next_url) = self.get_page::<Vec<Release>>(url).await?;
+            releases.extend(page);
+            next = next_url;
+        }
+
+        let mut versions = Vec::new();
+
+        for release in releases.iter().filter(|release| !release.draft) {
+            for asset in release.assets.iter().filter(|asset| asset.is_mod_jar()) {
+                let version = self.version(project_id, release, asset)?;
+
+                if filters.matches(&version) {
+                    versions.push(version);
+                }
+            }
+        }
+
+        versions.sort_by(|a, b| b.published.cmp(&a.published));
+        Ok(versions)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn get_version(
+        &self,
+        project_id: &str,
+        version_id: &str,
+    ) -> anyhow::Result<SourceVersion> {
+        let Some((tag, asset_name)) = version_id.rsplit_once('/') else {
+            bail!("github version '{version_id}' is not in the form 'tag/asset'");
+        };
+
+        let url = self.repo_url(project_id, &["releases", "tags", tag])?;
+        let release = self.get::<Release>(url).await?;
+
+        let asset = release
+            .assets
+            .iter()
+            .find(|asset| asset.name == asset_name)
+            .ok_or_else(|| {
+                anyhow!("github release '{tag}' of '{project_id}' has no asset '{asset_name}'")
+            })?;
+
+        self.version(project_id, &release, asset)
+    }
+
+    fn download_headers(&self, version: &SourceVersion) -> anyhow::Result<HeaderMap> {
+        if self.credential.is_none() {
+            return Ok(HeaderMap::new());
+        }
+
+        // the api serves the asset itself rather than its metadata with this
+        let request = self
+            .request(&version.url)
+            .header(ACCEPT, "application/octet-stream")
+            .build()?;
+
+        Ok(request.headers().clone())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use axum::{
+        extract::Path,
+        http::{header, HeaderMap, StatusCode},
+        response::IntoResponse,
+        routing::get,
+        Json, Router,
+    };
+    use carbon_platforms::{source::SourceVersionFilters, ModChannel};
+    use serde_json::{json, Value};
+    use tokio::net::TcpListener;
+
+    use super::{GithubReleasesSource, ModSource, SourceCredential};
+
+    fn release(tag: &str, prerelease: bool, published: &str, assets: &[&str]) -> Value {
+        json!({
+            "id": 1,
+            "tag_name": tag,
+            "name": format!("Example Mod {tag}"),
+            "draft": false,
+            "prerelease": prerelease,
+            "published_at": published,
+            "created_at": published,
+            "assets": assets.iter().map(|name| json!({
+                "id": 2,
+                "name": name,
+                "size": 1024,
+                "browser_download_url": format!("https://github.com/example/mod/releases/download/{tag}/{name}"),
+                "digest": "sha256:abcdef",
+            })).collect::<Vec<_>>(),
+        })
+    }
+
+    async fn stand_in_server() -> String {
+        let tcp_listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = tcp_listener.local_addr().unwrap().port();
+
+        let server = Router::new()
+            .route(
+                "/repos/example/mod/releases",
+                get(move || async move {
+                    // github links the next page through the repository id
+                    let next = format!("http://127.0.0.1:{port}/repositories/1/releases?page=2");
+
+                    (
+                        [(
+                            header::LINK,
+                            format!("<{next}>; rel=\"next\", <{next}>; rel=\"last\""),
+                        )],
+                        Json(json!([release(
+                            "v1.1.0-beta.1",
+                            true,
+                            "2024-12-02T00:00:00Z",
+                            &[
+                                "example-fabric-1.1.0-beta.1.jar",
+                                "example-forge-1.1.0-beta.1.jar"
+                            ],
+                        )])),
+                    )
+                }),
+            )
+            .route(
+                "/repositories/1/releases",
+                get(|| async {
+                    Json(json!([release(
+                        "v1.0.0",
+                        false,
+                        "2024-12-01T00:00:00Z",
+                        &[
+                            "example-fabric-1.0.0.jar",
+                            "example-fabric-1.0.0-sources.jar"
+                        ],
+                    )]))
+                }),
+            )
+            .route(
+                "/repos/example/private/releases/tags/:tag",
+                get(|headers: HeaderMap, Path(tag): Path<String>| async move {
+                    // github hides private repositories from unauthorized requests
+                    if headers.get(header::AUTHORIZATION).map(|v| v.as_bytes())
+                        != Some(b"Bearer secret")
+                    {
+                        return StatusCode::NOT_FOUND.into_response();
+                    }
+
+                    Json(release(
+                        &tag,
+                        false,
+                        "2024-12-01T00:00:00Z",
+                        &["example-fabric-1.0.0.jar"],
+                    ))
+                    .into_response()
+                }),
+            )
+            .route(
+                "/repos/example/mod/releases/tags/:tag",
+                get(|Path(tag): Path<String>| async move {
+                    Json(release(
+                        &tag,
+                        false,
+                        "2024-12-01T00:00:00Z",
+                        &["example-fabric-1.0.0.jar"],
+                    ))
+                }),
+            );
+
+        tokio::spawn(async {
+            axum::serve(tcp_listener, server.into_make_service())
+                .await
+                .unwrap();
+        });
+
+        format!("http://127.0.0.1:{port}/")
+    }
+
+    fn source(api_base: String) -> GithubReleasesSource {
+        let client = reqwest_middleware::ClientBuilder::new(reqwest::Client::new()).build();
+        GithubReleasesSource::new(client, api_base.parse().unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_list_versions() {
+        let source = source(stand_in_server().await);
+
+        let versions = source
+            .list_versions(
+                "example/mod",
+                &SourceVersionFilters {
+                    game_version: Some(String::from("1.20.1")),
+                    loaders: vec![String::from("fabric")],
+                },
+            )
+            .await
+            .unwrap();
+
+        let ids = versions.iter().map(|v| v.id.as_str()).collect::<Vec<_>>();
+        assert_eq!(
+            ids,
+            vec![
+                "v1.1.0-beta.1/example-fabric-1.1.0-beta.1.jar",
+                "v1.0.0/example-fabric-1.0.0.jar",
+            ]
+        );
+
+        assert_eq!(versions[0].channel, ModChannel::Beta);
+        assert_eq!(versions[1].channel, ModChannel::Stable);
+        assert_eq!(versions[1].sha256.as_deref(), Some("abcdef"));
+        assert_eq!(versions[1].loaders, vec!["fabric"]);
+    }
+
+    #[tokio::test]
+    async fn test_get_version() {
+        let source = source(stand_in_server().await);
+
+        let version = source
+            .get_version("example/mod", "v1.0.0/example-fabric-1.0.0.jar")
+            .await
+            .unwrap();
+
+        assert_eq!(version.name, "Example Mod v1.0.0");
+        assert_eq!(version.file_name, "example-fabric-1.0.0.jar");
+        assert_eq!(version.size, Some(1024));
+
+        assert!(source
+            .get_version("example/mod", "v1.0.0/missing.jar")
+            .await
+            .is_err());
+        assert!(source.get_version("example", "v1.0.0/x.jar").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_private_repository() {
+        let api_base = stand_in_server().await;
+
+        assert!(source(api_base.clone())
+            .get_version("example/private", "v1.0.0/example-fabric-1.0.0.jar")
+            .await
+            .is_err());
+
+        let source = source(api_base.clone()).with_credential(Some(SourceCredential::Token {
+            token: String::from("secret"),
+        }));
+
+        let version = source
+            .get_version("example/private", "v1.0.0/example-fabric-1.0.0.jar")
+            .await
+            .unwrap();
+
+        // private assets are downloaded through the api, with the token
+        assert_eq!(
+            version.url,
+            format!("{api_base}repos/example/private/releases/assets/2")
+        );
+
+        let headers = source.download_headers(&version).unwrap();
+        assert_eq!(headers["authorization"], "Bearer secret");
+        assert_eq!(headers["accept"], "application/octet-stream");
+    }
+}
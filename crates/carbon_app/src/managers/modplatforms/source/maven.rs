@@ -0,0 +1,439 @@
+use std::sync::Arc;
+
+use anyhow::{anyhow, bail};
+use carbon_platforms::source::{
+    channel_from_version, loaders_from_file_name, SourceVersion, SourceVersionFilters,
+};
+use chrono::{DateTime, Duration, NaiveDateTime, Utc};
+use reqwest::{header::HeaderMap, Method, StatusCode};
+use reqwest_middleware::{ClientWithMiddleware, RequestBuilder};
+use tracing::trace;
+use url::Url;
+
+use super::{ModSource, SourceCredential};
+
+pub const SOURCE_ID: &str = "maven";
+
+/// Mods published as artifacts of a maven repository.
+///
+/// Projects are `group:artifact` and versions are the artifact versions.
+/// Private repositories take basic auth or a bearer token.
+pub struct MavenSource {
+    client: ClientWithMiddleware,
+    repository: Url,
+    credential: Option<SourceCredential>,
+}
+
+impl MavenSource {
+    pub fn new(client: ClientWithMiddleware, repository: Url) -> Self {
+        Self {
+            client,
+            repository,
+            credential: None,
+        }
+    }
+
+    pub fn with_credential(mut self, credential: Option<SourceCredential>) -> Self {
+        self.credential = credential;
+        self
+    }
+
+    pub fn factory(
+        client: ClientWithMiddleware,
+        repository: Option<Url>,
+        credential: Option<SourceCredential>,
+    ) -> anyhow::Result<Arc<dyn ModSource>> {
+        let repository =
+            repository.ok_or_else(|| anyhow!("maven sources require a repository url"))?;
+
+        Ok(Arc::new(
+            Self::new(client, repository).with_credential(credential),
+        ))
+    }
+
+    fn request(&self, method: Method, url: &str) -> RequestBuilder {
+        let request = self.client.request(method, url);
+
+        match &self.credential {
+            Some(credential) => credential.authorize(request),
+            None => request,
+        }
+    }
+
+    fn artifact_url<'a>(&self, project_id: &'a str) -> anyhow::Result<(Url, &'a str)> {
+        let Some((group, artifact)) = project_id.split_once(':') else {
+            bail!("maven project '{project_id}' is not in the form 'group:artifact'");
+        };
+
+        let url = self
+            .repository
+            .join(&format!("{}/{artifact}/", group.replace('.', "/")))?;
+
+        Ok((url, artifact))
+    }
+
+    async fn get_text(&self, url: Url) -> anyhow::Result<Option<String>> {
+        trace!("GET {}", url);
+
+        let response = self.request(Method::GET, url.as_str()).send().await?;
+
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+
+        Ok(Some(response.error_for_status()?.text().await?))
+    }
+
+    /// A version as listed in the repository metadata, without checking its jar.
+    fn listed_version(
+        &self,
+        project_id: &str,
+        version: &str,
+        published: DateTime<Utc>,
+    ) -> anyhow::Result<SourceVersion> {
+        let (artifact_url, artifact) = self.artifact_url(project_id)?;
+        let file_name = format!("{artifact}-{version}.jar");
+        let url = artifact_url.join(&format!("{version}/{file_name}"))?;
+
+        Ok(SourceVersion {
+            source: SOURCE_ID.to_string(),
+            repository: self.repository.to_string(),
+            project_id: project_id.to_string(),
+            id: version.to_string(),
+            name: version.to_string(),
+            file_name,
+            url: url.to_string(),
+            sha1: None,
+            sha256: None,
+            size: None,
+            game_versions: Vec::new(),
+            loaders: loaders_from_file_name(artifact),
+            channel: channel_from_version(version),
+            published,
+        })
+    }
+
+    /// Resolve a version's jar, `fallback_date` is used when the repository
+    /// does not report when the jar was published.
+    async fn resolve_version(
+        &self,
+        project_id: &str,
+        version: &str,
+        fallback_date: DateTime<Utc>,
+    ) -> anyhow::Result<SourceVersion> {
+        let mut resolved = self.listed_version(project_id, version, fallback_date)?;
+        let url = Url::parse(&resolved.url)?;
+
+        resolved.sha1 = self
+            .get_text(Url::parse(&format!("{}.sha1", resolved.url))?)
+            .await?
+            // some repositories append the file name after the hash
+            .and_then(|sha1| sha1.split_whitespace().next().map(ToString::to_string));
+
+        trace!("HEAD {}", url);
+        let head = self
+            .request(Method::HEAD, url.as_str())
+            .send()
+            .await?
+            .error_for_status()
+            .map_err(|e| anyhow!("maven artifact {project_id}:{version} has no jar: {e}"))?;
+
+        resolved.size = head
+            .headers()
+            .get("content-length")
+            .and_then(|header| header.to_str().ok())
+            .and_then(|header| header.parse::<u64>().ok());
+
+        if let Some(published) = head
+            .headers()
+            .get("last-modified")
+            .and_then(|header| header.to_str().ok())
+            .and_then(|header| httpdate::parse_http_date(header).ok())
+        {
+            resolved.published = DateTime::<Utc>::from(published);
+        }
+
+        Ok(resolved)
+    }
+}
+
+#[async_trait::async_trait]
+impl ModSource for MavenSource {
+    fn id(&self) -> &'static str {
+        SOURCE_ID
+    }
+
+    fn repository(&self) -> &Url {
+        &self.repository
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn list_versions(
+        &self,
+        project_id: &str,
+        filters: &SourceVersionFilters,
+    ) -> anyhow::Result<Vec<SourceVersion>> {
+        let (artifact_url, _) = self.artifact_url(project_id)?;
+
+        let metadata = self
+            .get_text(artifact_url.join("maven-metadata.xml")?)
+            .await?
+            .ok_or_else(|| anyhow!("maven artifact {project_id} does not exist"))?;
+
+        let metadata = carbon_parsing::maven::parse_metadata(&metadata)?;
+
+        // Versions are listed in deployment order. Only the last deployment is
+        // dated, so the newest version gets its date and older ones are a second
+        // apart, keeping that order when sorted by date. Jars are only checked
+        // once a version is picked, through `get_version`.
+        let last_updated = metadata
+            .last_updated
+            .as_deref()
+            .and_then(|date| NaiveDateTime::parse_from_str(date, "%Y%m%d%H%M%S").ok())
+            .map(|date| date.and_utc())
+            .unwrap_or_else(|| {
+                DateTime::<Utc>::UNIX_EPOCH + Duration::seconds(metadata.versions.len() as i64)
+            });
+
+        let mut versions = Vec::new();
+
+        for (i, version) in metadata
+            .versions
+            .iter()
+            .rev()
+            // snapshot jars are deployed under timestamped names
+            .filter(|version| !version.ends_with("-SNAPSHOT"))
+            .enumerate()
+        {
+            let version = self.listed_version(
+                project_id,
+                version,
+                last_updated - Duration::seconds(i as i64),
+            )?;
+
+            if filters.matches(&version) {
+                versions.push(version);
+            }
+        }
+
+        Ok(versions)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn get_version(
+        &self,
+        project_id: &str,
+        version_id: &str,
+    ) -> anyhow::Result<SourceVersion> {
+        self.resolve_version(project_id, version_id, DateTime::<Utc>::UNIX_EPOCH)
+            .await
+    }
+
+    fn download_headers(&self, version: &SourceVersion) -> anyhow::Result<HeaderMap> {
+        if self.credential.is_none() {
+            return Ok(HeaderMap::new());
+        }
+
+        let request = self.request(Method::GET, &version.url).build()?;
+        Ok(request.headers().clone())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use axum::{
+        http::{header, HeaderMap, StatusCode},
+        response::IntoResponse,
+        routing::get,
+        Router,
+    };
+    use carbon_platforms::{source::SourceVersionFilters, ModChannel};
+    use tokio::net::TcpListener;
+
+    use super::{MavenSource, ModSource, SourceCredential};
+
+    const METADATA: &str = r#"<metadata>
+        <groupId>com.example</groupId>
+        <artifactId>example-fabric</artifactId>
+        <versioning>
+            <versions>
+                <version>1.0.0</version>
+                <version>1.0.1</version>
+                <version>1.1.0-beta.1</version>
+                <version>1.2.0-SNAPSHOT</version>
+            </versions>
+            <lastUpdated>20241202000000</lastUpdated>
+        </versioning>
+    </metadata>"#;
+
+    async fn stand_in_server() -> String {
+        let tcp_listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = tcp_listener.local_addr().unwrap().port();
+
+        let artifact = "/releases/com/example/example-fabric";
+        let server = Router::new()
+            .route(
+                &format!("{artifact}/maven-metadata.xml"),
+                get(|| async { METADATA }),
+            )
+            .route(
+                &format!("{artifact}/1.0.0/example-fabric-1.0.0.jar"),
+                get(|| async {
+                    (
+                        [(header::LAST_MODIFIED, "Sun, 01 Dec 2024 00:00:00 GMT")],
+                        "jar contents",
+                    )
+                }),
+            )
+            .route(
+                &format!("{artifact}/1.0.0/example-fabric-1.0.0.jar.sha1"),
+                get(|| async {
+                    "da39a3ee5e6b4b0d3255bfef95601890afd80709  example-fabric-1.0.0.jar"
+                }),
+            )
+            .route(
+                &format!("{artifact}/1.1.0-beta.1/example-fabric-1.1.0-beta.1.jar"),
+                get(|| async {
+                    (
+                        [(header::LAST_MODIFIED, "Mon, 02 Dec 2024 00:00:00 GMT")],
+                        "jar contents",
+                    )
+                }),
+            )
+            .route(
+                "/private/com/example/example-fabric/maven-metadata.xml",
+                get(|headers: HeaderMap| async move {
+                    // user:secret
+                    if headers.get(header::AUTHORIZATION).map(|v| v.as_bytes())
+                        != Some(b"Basic dXNlcjpzZWNyZXQ=")
+                    {
+                        return StatusCode::UNAUTHORIZED.into_response();
+                    }
+
+                    METADATA.into_response()
+                }),
+            );
+
+        tokio::spawn(async {
+            axum::serve(tcp_listener, server.into_make_service())
+                .await
+                .unwrap();
+        });
+
+        format!("http://127.0.0.1:{port}/")
+    }
+
+    fn source(repository: String) -> MavenSource {
+        let client = reqwest_middleware::ClientBuilder::new(reqwest::Client::new()).build();
+        MavenSource::new(client, format!("{repository}releases/").parse().unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_list_versions() {
+        let source = source(stand_in_server().await);
+
+        let versions = source
+            .list_versions(
+                "com.example:example-fabric",
+                &SourceVersionFilters::default(),
+            )
+            .await
+            .unwrap();
+
+        // 1.0.1 only has classifier jars, it is listed but can't be resolved
+        let ids = versions.iter().map(|v| v.id.as_str()).collect::<Vec<_>>();
+        assert_eq!(ids, vec!["1.1.0-beta.1", "1.0.1", "1.0.0"]);
+
+        assert_eq!(versions[0].channel, ModChannel::Beta);
+        assert_eq!(
+            versions[0].published.to_rfc3339(),
+            "2024-12-02T00:00:00+00:00"
+        );
+        assert!(versions[0].published > versions[1].published);
+        assert_eq!(versions[2].channel, ModChannel::Stable);
+        assert_eq!(versions[2].sha1, None);
+        assert_eq!(versions[2].loaders, vec!["fabric"]);
+        assert!(versions[2]
+            .url
+            .ends_with("/releases/com/example/example-fabric/1.0.0/example-fabric-1.0.0.jar"));
+
+        let forge_versions = source
+            .list_versions(
+                "com.example:example-fabric",
+                &SourceVersionFilters {
+                    game_version: None,
+                    loaders: vec![String::from("forge")],
+                },
+            )
+            .await
+            .unwrap();
+
+        assert!(forge_versions.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_get_version() {
+        let source = source(stand_in_server().await);
+
+        let version = source
+            .get_version("com.example:example-fabric", "1.0.0")
+            .await
+            .unwrap();
+
+        assert_eq!(version.file_name, "example-fabric-1.0.0.jar");
+        assert_eq!(version.size, Some(12));
+        assert_eq!(
+            version.sha1.as_deref(),
+            Some("da39a3ee5e6b4b0d3255bfef95601890afd80709")
+        );
+        assert_eq!(version.published.to_rfc3339(), "2024-12-01T00:00:00+00:00");
+
+        assert!(source
+            .get_version("com.example:example-fabric", "1.0.1")
+            .await
+            .is_err());
+
+        assert!(source
+            .get_version("com.example:example-fabric", "2.0.0")
+            .await
+            .is_err());
+        assert!(source
+            .list_versions("com.example:missing", &SourceVersionFilters::default())
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_private_repository() {
+        let repository = format!("{}private/", stand_in_server().await);
+        let client = reqwest_middleware::ClientBuilder::new(reqwest::Client::new()).build();
+
+        let anonymous = MavenSource::new(client.clone(), repository.parse().unwrap());
+        assert!(anonymous
+            .list_versions(
+                "com.example:example-fabric",
+                &SourceVersionFilters::default()
+            )
+            .await
+            .is_err());
+
+        let source = MavenSource::new(client, repository.parse().unwrap()).with_credential(Some(
+            SourceCredential::Basic {
+                username: String::from("user"),
+                password: String::from("secret"),
+            },
+        ));
+
+        let versions = source
+            .list_versions(
+                "com.example:example-fabric",
+                &SourceVersionFilters::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(versions.len(), 3);
+
+        let headers = source.download_headers(&versions[0]).unwrap();
+        assert_eq!(headers["authorization"], "Basic dXNlcjpzZWNyZXQ=");
+    }
+}
@@ -0,0 +1,189 @@
+use std::sync::Arc;
+
+use anyhow::{anyhow, bail};
+use carbon_platforms::{
+    modrinth::{
+        project::ProjectVersionsFilters,
+        search::{ProjectID, VersionID},
+        version::Version,
+    },
+    source::{SourceVersion, SourceVersionFilters},
+    RemoteVersion,
+};
+use carbon_repos::db::mod_metadata as metadb;
+use reqwest_middleware::ClientWithMiddleware;
+use url::Url;
+
+use super::{ModSource, SourceCredential};
+use crate::managers::{
+    instance::installer::{Installer, IntoInstaller, ModrinthModInstaller},
+    metadata::cache::modrinth::cache_known_modrinth_version,
+    modplatforms::modrinth::Modrinth,
+    App,
+};
+
+pub const SOURCE_ID: &str = "modrinth";
+
+/// Mods hosted on Modrinth.
+///
+/// Projects and versions are Modrinth ids. Versions keep the Modrinth version
+/// model so installs pull in the dependencies it declares.
+pub struct ModrinthSource {
+    modrinth: Modrinth,
+}
+
+impl ModrinthSource {
+    pub fn factory(
+        client: ClientWithMiddleware,
+        repository: Option<Url>,
+        credential: Option<SourceCredential>,
+    ) -> anyhow::Result<Arc<dyn ModSource>> {
+        let modrinth = Modrinth::new(client);
+
+        let is_base_url = |url: &Url| {
+            url.as_str().trim_end_matches('/') == modrinth.base_url().as_str().trim_end_matches('/')
+        };
+
+        if repository.is_some_and(|repository| !is_base_url(&repository)) {
+            bail!("modrinth has a single repository");
+        }
+
+        if credential.is_some() {
+            bail!("modrinth does not take credentials");
+        }
+
+        Ok(Arc::new(Self { modrinth }))
+    }
+
+    async fn versions(
+        &self,
+        project_id: &str,
+        filters: &SourceVersionFilters,
+    ) -> anyhow::Result<Vec<Version>> {
+        let versions = self
+            .modrinth
+            .get_project_versions(ProjectVersionsFilters {
+                project_id: ProjectID(project_id.to_string()),
+                game_versions: filters.game_version.clone().map(|version| vec![version]),
+                loaders: Some(filters.loaders.clone()).filter(|loaders| !loaders.is_empty()),
+                limit: None,
+                offset: None,
+            })
+            .await?;
+
+        Ok(versions.0)
+    }
+
+    fn version(&self, version: Version) -> anyhow::Result<SourceVersion> {
+        let file = version
+            .files
+            .iter()
+            .reduce(|a, b| if b.primary { b } else { a })
+            .cloned()
+            .ok_or_else(|| {
+                anyhow!(
+                    "Modrinth project '{}' version '{}' does not have a file",
+                    &version.project_id,
+                    &version.id
+                )
+            })?;
+
+        Ok(SourceVersion {
+            source: SOURCE_ID.to_string(),
+            repository: self.modrinth.base_url().to_string(),
+            project_id: version.project_id,
+            id: version.id,
+            name: version.name,
+            file_name: file.filename,
+            url: file.url,
+            sha1: Some(file.hashes.sha1),
+            sha256: None,
+            size: Some(file.size as u64),
+            game_versions: version.game_versions,
+            loaders: version.loaders,
+            channel: version.version_type.into(),
+            published: version.date_published,
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl ModSource for ModrinthSource {
+    fn id(&self) -> &'static str {
+        SOURCE_ID
+    }
+
+    fn repository(&self) -> &Url {
+        self.modrinth.base_url()
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn list_versions(
+        &self,
+        project_id: &str,
+        filters: &SourceVersionFilters,
+    ) -> anyhow::Result<Vec<SourceVersion>> {
+        let mut versions = self
+            .versions(project_id, filters)
+            .await?
+            .into_iter()
+            .filter_map(|version| self.version(version).ok())
+            .collect::<Vec<_>>();
+
+        versions.sort_by(|a, b| b.published.cmp(&a.published));
+        Ok(versions)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn get_version(
+        &self,
+        _project_id: &str,
+        version_id: &str,
+    ) -> anyhow::Result<SourceVersion> {
+        let version = self
+            .modrinth
+            .get_version(VersionID(version_id.to_string()))
+            .await?;
+
+        self.version(version)
+    }
+
+    async fn remote_versions(
+        &self,
+        project_id: &str,
+        filters: &SourceVersionFilters,
+    ) -> anyhow::Result<Vec<RemoteVersion>> {
+        Ok(self
+            .versions(project_id, filters)
+            .await?
+            .into_iter()
+            .map(RemoteVersion::Modrinth)
+            .collect())
+    }
+
+    async fn installer(&self, _project_id: &str, version_id: &str) -> anyhow::Result<Installer> {
+        let version = self
+            .modrinth
+            .get_version(VersionID(version_id.to_string()))
+            .await?;
+
+        Ok(ModrinthModInstaller::from_version(version)?.into_installer())
+    }
+
+    async fn cache_known_version(
+        &self,
+        app: &App,
+        metadata: &metadb::Data,
+        project_id: &str,
+        version_id: &str,
+    ) -> anyhow::Result<()> {
+        cache_known_modrinth_version(
+            app,
+            metadata.id.clone(),
+            hex::encode(&metadata.sha_512),
+            project_id.to_string(),
+            version_id.to_string(),
+        )
+        .await
+    }
+}
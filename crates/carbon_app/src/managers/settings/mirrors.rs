@@ -0,0 +1,126 @@
+use anyhow::{bail, Context};
+use carbon_net::Downloadable;
+use serde::{Deserialize, Serialize};
+
+/// Serves the files under `origin` from `mirror` when `origin` can't, for
+/// example a corporate maven standing in for the Forge maven.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DownloadMirror {
+    pub origin: String,
+    pub mirror: String,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct DownloadMirrors(Vec<DownloadMirror>);
+
+impl DownloadMirrors {
+    pub fn new(mirrors: Vec<DownloadMirror>) -> anyhow::Result<Self> {
+        for mirror in &mirrors {
+            for url in [&mirror.origin, &mirror.mirror] {
+                let parsed =
+                    url::Url::parse(url).with_context(|| format!("invalid mirror url `{url}`"))?;
+
+                if !matches!(parsed.scheme(), "http" | "https") {
+                    bail!("mirror url `{url}` is not an http url");
+                }
+            }
+        }
+
+        Ok(Self(mirrors))
+    }
+
+    /// Parses the json stored in `AppConfiguration.downloadMirrors`.
+    pub fn from_settings(value: &str) -> anyhow::Result<Self> {
+        Self::new(serde_json::from_str(value).context("malformed download mirrors setting")?)
+    }
+
+    pub fn to_settings(&self) -> anyhow::Result<String> {
+        Ok(serde_json::to_string(&self.0)?)
+    }
+
+    pub fn mirrors(&self) -> &[DownloadMirror] {
+        &self.0
+    }
+
+    /// Mirrored urls for `url`, in the order the mirrors were configured.
+    pub fn mirrors_for(&self, url: &str) -> Vec<String> {
+        self.0
+            .iter()
+            .filter_map(|mirror| {
+                let rest = url.strip_prefix(mirror.origin.trim_end_matches('/'))?;
+
+                // `https://maven.example` must not match `https://maven.example.org`
+                if !(rest.is_empty() || rest.starts_with('/') || rest.starts_with('?')) {
+                    return None;
+                }
+
+                Some(format!("{}{rest}", mirror.mirror.trim_end_matches('/')))
+            })
+            .collect()
+    }
+
+    pub fn apply(&self, downloadables: &mut [Downloadable]) {
+        for downloadable in downloadables {
+            let mirrors = self.mirrors_for(&downloadable.url);
+            downloadable.mirrors.extend(mirrors);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{DownloadMirror, DownloadMirrors};
+
+    fn mirrors() -> DownloadMirrors {
+        DownloadMirrors::new(vec![
+            DownloadMirror {
+                origin: "https://maven.minecraftforge.net/".to_string(),
+                mirror: "https://maven.corp.example/forge".to_string(),
+            },
+            DownloadMirror {
+                origin: "https://libraries.minecraft.net".to_string(),
+                mirror: "https://maven.corp.example/mojang/".to_string(),
+            },
+        ])
+        .unwrap()
+    }
+
+    #[test]
+    fn mirrors_for_matching_origin() {
+        assert_eq!(
+            mirrors().mirrors_for("https://libraries.minecraft.net/com/mojang/brigadier.jar"),
+            ["https://maven.corp.example/mojang/com/mojang/brigadier.jar"]
+        );
+        assert_eq!(
+            mirrors().mirrors_for("https://maven.minecraftforge.net/net/minecraftforge/forge.jar"),
+            ["https://maven.corp.example/forge/net/minecraftforge/forge.jar"]
+        );
+    }
+
+    #[test]
+    fn mirrors_for_respects_host_boundary() {
+        assert!(mirrors()
+            .mirrors_for("https://libraries.minecraft.net.evil.example/brigadier.jar")
+            .is_empty());
+        assert!(mirrors()
+            .mirrors_for("https://resources.download.minecraft.net/ab/abcdef")
+            .is_empty());
+    }
+
+    #[test]
+    fn settings_roundtrip() {
+        let stored = mirrors().to_settings().unwrap();
+        let parsed = DownloadMirrors::from_settings(&stored).unwrap();
+        assert_eq!(parsed.mirrors(), mirrors().mirrors());
+
+        assert!(DownloadMirrors::from_settings("[]")
+            .unwrap()
+            .mirrors()
+            .is_empty());
+        assert!(DownloadMirrors::new(vec![DownloadMirror {
+            origin: "ftp://libraries.minecraft.net".to_string(),
+            mirror: "https://maven.corp.example".to_string(),
+        }])
+        .is_err());
+    }
+}
@@ -1,20 +1,30 @@
 use self::terms_and_privacy::TermsAndPrivacy;
 use super::ManagerRef;
 use crate::api::{keys::settings::*, settings::FESettingsUpdate};
-use anyhow::{anyhow, Context};
+use anyhow::{anyhow, bail, Context};
+use carbon_net::{BandwidthLimiter, DownloadOptions, DownloadOptionsBuilder, HostLimits};
 use carbon_platforms::{ModChannelWithUsage, ModPlatform};
 use carbon_repos::db::app_configuration::{self, hashed_email_accepted, last_app_version};
 use chrono::Utc;
 use itertools::Itertools;
+use mirrors::DownloadMirrors;
 use reqwest_middleware::ClientWithMiddleware;
+use std::num::{NonZeroU32, NonZeroUsize};
 use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
 
+pub mod mirrors;
 pub mod terms_and_privacy;
 
 pub(crate) struct SettingsManager {
     pub runtime_path: carbon_rt_path::RuntimePath,
     pub terms_and_privacy: TermsAndPrivacy,
     pub gdl_base_api_url: String,
+    /// Kept in memory, the http client checks it on every request.
+    pub download_mirrors: Arc<RwLock<DownloadMirrors>>,
+    /// Shared by every download in the app, see [`ManagerRef::download_options`].
+    pub bandwidth_limiter: BandwidthLimiter,
+    pub host_limits: HostLimits,
 }
 
 impl SettingsManager {
@@ -22,15 +32,33 @@ impl SettingsManager {
         runtime_path: PathBuf,
         http_client: ClientWithMiddleware,
         gdl_base_api_url: String,
+        download_mirrors: Arc<RwLock<DownloadMirrors>>,
     ) -> Self {
         Self {
             runtime_path: carbon_rt_path::RuntimePath::new(runtime_path),
             terms_and_privacy: TermsAndPrivacy::new(http_client, gdl_base_api_url.clone()),
             gdl_base_api_url,
+            download_mirrors,
+            bandwidth_limiter: BandwidthLimiter::default(),
+            host_limits: HostLimits::default(),
         }
     }
 }
 
+fn parse_download_speed_limit(value: Option<i32>) -> anyhow::Result<Option<NonZeroU32>> {
+    match value {
+        Some(value) if value <= 0 => bail!("download speed limit must be positive"),
+        value => Ok(value.and_then(|value| NonZeroU32::new(value as u32))),
+    }
+}
+
+fn parse_concurrent_downloads_per_host(value: Option<i32>) -> anyhow::Result<Option<NonZeroUsize>> {
+    match value {
+        Some(value) if value <= 0 => bail!("concurrent downloads per host must be positive"),
+        value => Ok(value.and_then(|value| NonZeroUsize::new(value as usize))),
+    }
+}
+
 impl ManagerRef<'_, SettingsManager> {
     pub async fn get_settings(self) -> anyhow::Result<carbon_repos::db::app_configuration::Data> {
         self.app
@@ -42,6 +70,40 @@ impl ManagerRef<'_, SettingsManager> {
             .ok_or(anyhow!("Can't find this key"))
     }
 
+    /// Loads the settings the downloads check without going through the db.
+    pub async fn load_download_settings(self) -> anyhow::Result<()> {
+        let settings = self.get_settings().await?;
+        let mirrors = DownloadMirrors::from_settings(&settings.download_mirrors)?;
+
+        *self
+            .download_mirrors
+            .write()
+            .expect("download mirrors lock poisoned") = mirrors;
+
+        self.bandwidth_limiter
+            .set_limit(parse_download_speed_limit(settings.download_speed_limit)?);
+        self.host_limits
+            .set_limit(parse_concurrent_downloads_per_host(
+                settings.concurrent_downloads_per_host,
+            )?);
+
+        Ok(())
+    }
+
+    /// Download options honoring the app wide bandwidth and per host limits.
+    pub fn download_options(self) -> DownloadOptionsBuilder {
+        DownloadOptions::builder()
+            .bandwidth_limiter(self.bandwidth_limiter.clone())
+            .host_limits(self.host_limits.clone())
+    }
+
+    pub fn download_mirrors(self) -> DownloadMirrors {
+        self.download_mirrors
+            .read()
+            .expect("download mirrors lock poisoned")
+            .clone()
+    }
+
     #[tracing::instrument(skip(self))]
     pub async fn set_settings(self, incoming_settings: FESettingsUpdate) -> anyhow::Result<()> {
         let db = &self.app.prisma_client;
@@ -126,6 +188,47 @@ impl ManagerRef<'_, SettingsManager> {
             ));
         }
 
+        let mut speed_limit = None;
+        if let Some(download_speed_limit) = incoming_settings.download_speed_limit {
+            let download_speed_limit = download_speed_limit.inner();
+            speed_limit = Some(parse_download_speed_limit(download_speed_limit)?);
+
+            queries.push(self.app.prisma_client.app_configuration().update(
+                app_configuration::id::equals(0),
+                vec![app_configuration::download_speed_limit::set(
+                    download_speed_limit,
+                )],
+            ));
+        }
+
+        let mut per_host_limit = None;
+        if let Some(per_host) = incoming_settings.concurrent_downloads_per_host {
+            let per_host = per_host.inner();
+            per_host_limit = Some(parse_concurrent_downloads_per_host(per_host)?);
+
+            queries.push(self.app.prisma_client.app_configuration().update(
+                app_configuration::id::equals(0),
+                vec![app_configuration::concurrent_downloads_per_host::set(
+                    per_host,
+                )],
+            ));
+        }
+
+        let mut download_mirrors = None;
+        if let Some(mirrors) = incoming_settings.download_mirrors {
+            let mirrors =
+                DownloadMirrors::new(mirrors.inner().into_iter().map(Into::into).collect())?;
+
+            queries.push(self.app.prisma_client.app_configuration().update(
+                app_configuration::id::equals(0),
+                vec![app_configuration::download_mirrors::set(
+                    mirrors.to_settings()?,
+                )],
+            ));
+
+            download_mirrors = Some(mirrors);
+        }
+
         if let Some(download_dependencies) = incoming_settings.download_dependencies {
             queries.push(self.app.prisma_client.app_configuration().update(
                 app_configuration::id::equals(0),
@@ -347,6 +450,21 @@ impl ManagerRef<'_, SettingsManager> {
             db._batch(queries).await?;
             self.app.invalidate(GET_SETTINGS, None);
 
+            if let Some(download_mirrors) = download_mirrors {
+                *self
+                    .download_mirrors
+                    .write()
+                    .expect("download mirrors lock poisoned") = download_mirrors;
+            }
+
+            if let Some(speed_limit) = speed_limit {
+                self.bandwidth_limiter.set_limit(speed_limit);
+            }
+
+            if let Some(per_host_limit) = per_host_limit {
+                self.host_limits.set_limit(per_host_limit);
+            }
+
             if let Some(show_app_close_warning) = incoming_settings.show_app_close_warning {
                 println!(
                     "_SHOW_APP_CLOSE_WARNING_:{}",
@@ -377,3 +495,60 @@ impl ManagerRef<'_, SettingsManager> {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::num::{NonZeroU32, NonZeroUsize};
+
+    use crate::api::settings::FESettingsUpdate;
+
+    fn update(value: serde_json::Value) -> FESettingsUpdate {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_download_limits() {
+        let app = crate::setup_managers_for_test().await;
+
+        app.settings_manager()
+            .set_settings(update(serde_json::json!({
+                "downloadSpeedLimit": { "Set": 512 * 1024 },
+                "concurrentDownloadsPerHost": { "Set": 2 },
+            })))
+            .await
+            .unwrap();
+
+        let settings = app.settings_manager().get_settings().await.unwrap();
+        assert_eq!(settings.download_speed_limit, Some(512 * 1024));
+        assert_eq!(settings.concurrent_downloads_per_host, Some(2));
+        assert_eq!(
+            app.settings_manager().bandwidth_limiter.limit(),
+            NonZeroU32::new(512 * 1024)
+        );
+        assert_eq!(
+            app.settings_manager().host_limits.limit(),
+            NonZeroUsize::new(2)
+        );
+
+        assert!(app
+            .settings_manager()
+            .set_settings(update(serde_json::json!({
+                "downloadSpeedLimit": { "Set": 0 },
+            })))
+            .await
+            .is_err());
+
+        app.settings_manager()
+            .set_settings(update(serde_json::json!({
+                "downloadSpeedLimit": { "Set": null },
+            })))
+            .await
+            .unwrap();
+
+        assert_eq!(app.settings_manager().bandwidth_limiter.limit(), None);
+        assert_eq!(
+            app.settings_manager().host_limits.limit(),
+            NonZeroUsize::new(2)
+        );
+    }
+}
@@ -109,6 +109,28 @@ impl ManagerRef<'_, VisualTaskManager> {
         }
     }
 
+    /// Waits for a task to finish, returning its error if it failed.
+    pub async fn wait(self, task_id: VisualTaskId) -> anyhow::Result<()> {
+        let (data, mut notify) = match self.tasks.read().await.get(&task_id) {
+            Some(task) => (task.data.clone(), task.notify_rx.clone()),
+            None => return Ok(()),
+        };
+
+        loop {
+            if let TaskState::Failed(e) = &data.read().await.state {
+                return Err(anyhow!("{e:#}"));
+            }
+
+            if notify.changed().await.is_err() {
+                return Ok(());
+            }
+
+            if let NotifyState::Drop = *notify.borrow() {
+                return Ok(());
+            }
+        }
+    }
+
     #[cfg(test)]
     pub async fn wait_with_log(self, task_id: VisualTaskId) -> anyhow::Result<()> {
         use tracing::info;
@@ -0,0 +1,128 @@
+use std::sync::{Arc, RwLock};
+
+use axum::http::Extensions;
+use reqwest::{header, Request, Response};
+use reqwest_middleware::{Middleware, Next, Result};
+use tracing::{info, warn};
+
+use crate::managers::settings::mirrors::DownloadMirrors;
+
+/// Retries failed requests against the download mirrors configured in the
+/// settings, so metadata fetches survive an origin being down.
+pub struct MirrorMiddleware {
+    mirrors: Arc<RwLock<DownloadMirrors>>,
+}
+
+impl MirrorMiddleware {
+    pub fn new(mirrors: Arc<RwLock<DownloadMirrors>>) -> Self {
+        Self { mirrors }
+    }
+}
+
+#[async_trait::async_trait]
+impl Middleware for MirrorMiddleware {
+    async fn handle(
+        &self,
+        req: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> Result<Response> {
+        let mirrors = self
+            .mirrors
+            .read()
+            .expect("download mirrors lock poisoned")
+            .mirrors_for(req.url().as_str());
+
+        // requests with streamed bodies can't be sent twice
+        let fallback = match mirrors.is_empty() {
+            true => None,
+            false => req.try_clone(),
+        };
+
+        let result = next.clone().run(req, extensions).await;
+
+        let Some(fallback) = fallback else {
+            return result;
+        };
+
+        if matches!(&result, Ok(response) if response.status().is_success()) {
+            return result;
+        }
+
+        for mirror in mirrors {
+            let Ok(url) = url::Url::parse(&mirror) else {
+                continue;
+            };
+
+            let mut req = fallback
+                .try_clone()
+                .expect("request was cloned successfully before");
+
+            // credentials meant for the origin must not reach a different host
+            if url.host_str() != req.url().host_str() {
+                req.headers_mut().remove(header::AUTHORIZATION);
+                req.headers_mut().remove("x-api-key");
+            }
+
+            *req.url_mut() = url;
+
+            match next.clone().run(req, extensions).await {
+                Ok(response) if response.status().is_success() => {
+                    info!("{} was served by mirror {mirror}", fallback.url());
+                    return Ok(response);
+                }
+                Ok(response) => warn!("Mirror {mirror} responded with {}", response.status()),
+                Err(e) => warn!("Mirror {mirror} failed: {e}"),
+            }
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use axum::{http::StatusCode, routing::get, Router};
+    use tokio::net::TcpListener;
+
+    use crate::managers::settings::mirrors::{DownloadMirror, DownloadMirrors};
+
+    #[tokio::test]
+    async fn test_mirror_fallback() {
+        let app = crate::setup_managers_for_test().await;
+
+        let tcp_listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = tcp_listener.local_addr().unwrap().port();
+
+        let server = Router::new()
+            .route(
+                "/origin/manifest.json",
+                get(|| async { (StatusCode::SERVICE_UNAVAILABLE, "down") }),
+            )
+            .route("/mirror/manifest.json", get(|| async { "mirrored" }));
+
+        tokio::spawn(async {
+            axum::serve(tcp_listener, server.into_make_service())
+                .await
+                .unwrap();
+        });
+
+        *app.settings_manager().download_mirrors.write().unwrap() =
+            DownloadMirrors::new(vec![DownloadMirror {
+                origin: format!("http://127.0.0.1:{port}/origin"),
+                mirror: format!("http://127.0.0.1:{port}/mirror"),
+            }])
+            .unwrap();
+
+        let response = app
+            .reqwest_client
+            .get(format!("http://127.0.0.1:{port}/origin/manifest.json"))
+            .header("avoid-caching", "")
+            .send()
+            .await
+            .unwrap();
+
+        assert!(response.status().is_success());
+        assert_eq!(response.text().await.unwrap(), "mirrored");
+    }
+}
@@ -0,0 +1,174 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::Request,
+    http::{header, HeaderMap, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Extension,
+};
+use ring::rand::{SecureRandom, SystemRandom};
+use tracing::warn;
+
+/// Header carrying the session token on `/rspc` and other plain http requests.
+pub const SESSION_TOKEN_HEADER: &str = "x-carbon-session";
+
+/// Browsers cannot set headers on websockets, so `/invalidations` and
+/// `/instance/log` take the token as a `carbon-session.<token>` subprotocol instead.
+pub const SESSION_TOKEN_PROTOCOL_PREFIX: &str = "carbon-session.";
+
+/// Origins the desktop app loads from. Requests without an origin come from
+/// non-browser clients and are let through to the token check. The opaque
+/// `null` origin of sandboxed frames and data urls is never allowed.
+const ALLOWED_ORIGINS: &[&str] = &["https://app.gdlauncher.com", "file://"];
+
+/// Random token generated at startup, required to use the local API.
+#[derive(Clone)]
+pub struct SessionToken(Arc<str>);
+
+impl SessionToken {
+    pub fn generate() -> Self {
+        // the desktop app doesn't start the core module in development,
+        // both read the token from the environment instead
+        #[cfg(debug_assertions)]
+        if let Some(token) = std::env::var("SESSION_TOKEN")
+            .ok()
+            .filter(|token| !token.is_empty())
+        {
+            return Self(Arc::from(token));
+        }
+
+        let mut bytes = [0u8; 32];
+        SystemRandom::new()
+            .fill(&mut bytes)
+            .expect("system random generator failed");
+
+        Self(Arc::from(hex::encode(bytes)))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    fn matches(&self, candidate: &str) -> bool {
+        ring::constant_time::verify_slices_are_equal(self.0.as_bytes(), candidate.as_bytes())
+            .is_ok()
+    }
+
+    pub fn verify_header(&self, headers: &HeaderMap) -> bool {
+        headers
+            .get(SESSION_TOKEN_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| self.matches(value))
+    }
+
+    /// Returns the offered subprotocol holding this token, which has to be
+    /// echoed back when accepting the websocket.
+    pub fn verify_protocol(&self, headers: &HeaderMap) -> Option<String> {
+        headers
+            .get_all(header::SEC_WEBSOCKET_PROTOCOL)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .map(str::trim)
+            .find(|protocol| {
+                protocol
+                    .strip_prefix(SESSION_TOKEN_PROTOCOL_PREFIX)
+                    .is_some_and(|token| self.matches(token))
+            })
+            .map(str::to_string)
+    }
+}
+
+fn is_allowed_origin(origin: &str) -> bool {
+    if ALLOWED_ORIGINS.contains(&origin) {
+        return true;
+    }
+
+    // the frontend dev server
+    cfg!(debug_assertions)
+        && ["http://localhost", "http://127.0.0.1"].iter().any(|host| {
+            match origin.strip_prefix(host) {
+                Some(rest) => rest.is_empty() || rest.starts_with(':'),
+                None => false,
+            }
+        })
+}
+
+pub async fn check_origin(request: Request, next: Next) -> Response {
+    let origin = request
+        .headers()
+        .get(header::ORIGIN)
+        .map(|origin| origin.to_str().unwrap_or_default());
+
+    match origin {
+        Some(origin) if !is_allowed_origin(origin) => {
+            warn!("Rejected request from origin {origin}");
+            StatusCode::FORBIDDEN.into_response()
+        }
+        _ => next.run(request).await,
+    }
+}
+
+/// Expects the token as an extension, which `start_router` adds to every request.
+pub async fn require_session_token(
+    Extension(token): Extension<SessionToken>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if !token.verify_header(request.headers()) {
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod test {
+    use axum::http::HeaderValue;
+
+    use super::*;
+
+    #[test]
+    fn verify_token() {
+        let token = SessionToken(Arc::from("abcd"));
+
+        let mut headers = HeaderMap::new();
+        assert!(!token.verify_header(&headers));
+        assert_eq!(token.verify_protocol(&headers), None);
+
+        headers.insert(SESSION_TOKEN_HEADER, HeaderValue::from_static("abc"));
+        assert!(!token.verify_header(&headers));
+        headers.insert(SESSION_TOKEN_HEADER, HeaderValue::from_static("abcd"));
+        assert!(token.verify_header(&headers));
+
+        headers.insert(
+            header::SEC_WEBSOCKET_PROTOCOL,
+            HeaderValue::from_static("abcd, carbon-session.abce"),
+        );
+        assert_eq!(token.verify_protocol(&headers), None);
+
+        headers.insert(
+            header::SEC_WEBSOCKET_PROTOCOL,
+            HeaderValue::from_static("json, carbon-session.abcd"),
+        );
+        assert_eq!(
+            token.verify_protocol(&headers).as_deref(),
+            Some("carbon-session.abcd")
+        );
+    }
+
+    #[test]
+    fn allowed_origins() {
+        assert!(is_allowed_origin("file://"));
+        assert!(!is_allowed_origin("null"));
+        assert!(is_allowed_origin("https://app.gdlauncher.com"));
+        assert!(!is_allowed_origin("https://app.gdlauncher.com.evil.com"));
+        assert!(!is_allowed_origin("https://example.com"));
+        assert!(!is_allowed_origin("http://localhost.example.com"));
+        assert_eq!(
+            is_allowed_origin("http://localhost:3000"),
+            cfg!(debug_assertions)
+        );
+    }
+}
@@ -0,0 +1,367 @@
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use tokio::sync::watch::Sender;
+use tracing::trace;
+use zip::{write::SimpleFileOptions, ZipWriter};
+
+use crate::{CompressionError, Progress};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveFormat {
+    Zip,
+    TarZstd,
+}
+
+/// Order in which the files of the source directory are written.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum EntryOrder {
+    /// Whatever order the filesystem returns the entries in.
+    #[default]
+    Filesystem,
+    /// Sorted by relative path, producing reproducible archives.
+    Alphabetical,
+    /// Entries under the listed relative paths first, in the listed order,
+    /// followed by everything else sorted by relative path.
+    Priority(Vec<PathBuf>),
+}
+
+type PathFilter = Box<dyn Fn(&Path) -> bool + Send>;
+
+pub struct CompressOptions {
+    format: ArchiveFormat,
+    prefix: Option<String>,
+    extra_files: Vec<(String, Vec<u8>)>,
+    filter: Option<PathFilter>,
+    order: EntryOrder,
+    progress_sender: Option<Sender<Progress>>,
+}
+
+impl CompressOptions {
+    pub fn builder() -> CompressOptionsBuilder {
+        CompressOptionsBuilder::default()
+    }
+}
+
+#[derive(Default)]
+pub struct CompressOptionsBuilder {
+    format: Option<ArchiveFormat>,
+    prefix: Option<String>,
+    extra_files: Vec<(String, Vec<u8>)>,
+    filter: Option<PathFilter>,
+    order: Option<EntryOrder>,
+    progress_sender: Option<Sender<Progress>>,
+}
+
+impl CompressOptionsBuilder {
+    pub fn format(mut self, format: ArchiveFormat) -> Self {
+        self.format = Some(format);
+        self
+    }
+
+    /// Folder inside of the archive the source directory is written to.
+    pub fn prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = Some(prefix.into());
+        self
+    }
+
+    /// Writes `contents` to `name` at the root of the archive, before any
+    /// file of the source directory.
+    pub fn extra_file(mut self, name: impl Into<String>, contents: Vec<u8>) -> Self {
+        self.extra_files.push((name.into(), contents));
+        self
+    }
+
+    /// Called with the path of every file and folder relative to the source
+    /// directory. Returning false for a folder skips everything inside of it.
+    pub fn filter(mut self, filter: impl Fn(&Path) -> bool + Send + 'static) -> Self {
+        self.filter = Some(Box::new(filter));
+        self
+    }
+
+    pub fn order(mut self, order: EntryOrder) -> Self {
+        self.order = Some(order);
+        self
+    }
+
+    /// Receives an update after every file written to the archive.
+    pub fn progress_sender(mut self, progress_sender: Sender<Progress>) -> Self {
+        self.progress_sender = Some(progress_sender);
+        self
+    }
+
+    pub fn build(self) -> CompressOptions {
+        CompressOptions {
+            format: self.format.unwrap_or(ArchiveFormat::Zip),
+            prefix: self.prefix,
+            extra_files: self.extra_files,
+            filter: self.filter,
+            order: self.order.unwrap_or_default(),
+            progress_sender: self.progress_sender,
+        }
+    }
+}
+
+struct SourceFile {
+    relpath: PathBuf,
+    size: u64,
+}
+
+fn collect_files(
+    base: &Path,
+    relpath: &Path,
+    filter: &Option<PathFilter>,
+    files: &mut Vec<SourceFile>,
+) -> io::Result<()> {
+    for entry in std::fs::read_dir(base.join(relpath))? {
+        let entry = entry?;
+        let relpath = relpath.join(entry.file_name());
+
+        if let Some(filter) = filter {
+            if !filter(&relpath) {
+                continue;
+            }
+        }
+
+        // follows symlinks, the target's contents end up in the archive
+        let metadata = std::fs::metadata(entry.path())?;
+
+        if metadata.is_dir() {
+            collect_files(base, &relpath, filter, files)?;
+        } else {
+            files.push(SourceFile {
+                relpath,
+                size: metadata.len(),
+            });
+        }
+    }
+
+    Ok(())
+}
+
+fn sort_files(files: &mut [SourceFile], order: &EntryOrder) {
+    match order {
+        EntryOrder::Filesystem => {}
+        EntryOrder::Alphabetical => files.sort_by(|a, b| a.relpath.cmp(&b.relpath)),
+        EntryOrder::Priority(priority) => files.sort_by_cached_key(|file| {
+            let rank = priority
+                .iter()
+                .position(|p| file.relpath.starts_with(p))
+                .unwrap_or(priority.len());
+
+            (rank, file.relpath.clone())
+        }),
+    }
+}
+
+/// Archive paths always use `/`, whatever the platform separator is.
+fn archive_name(prefix: Option<&str>, relpath: &Path) -> String {
+    let components = relpath
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy());
+
+    prefix
+        .map(std::borrow::Cow::from)
+        .into_iter()
+        .chain(components)
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+enum ArchiveWriter {
+    Zip(Box<ZipWriter<BufWriter<File>>>),
+    TarZstd(tar::Builder<zstd::stream::write::Encoder<'static, BufWriter<File>>>),
+}
+
+impl ArchiveWriter {
+    fn new(format: ArchiveFormat, dest: &Path) -> Result<Self, CompressionError> {
+        let file = BufWriter::new(File::create(dest)?);
+
+        Ok(match format {
+            ArchiveFormat::Zip => Self::Zip(Box::new(ZipWriter::new(file))),
+            ArchiveFormat::TarZstd => {
+                // level 0 picks zstd's default level
+                let encoder = zstd::stream::write::Encoder::new(file, 0)?;
+                Self::TarZstd(tar::Builder::new(encoder))
+            }
+        })
+    }
+
+    fn append_data(&mut self, name: &str, contents: &[u8]) -> Result<(), CompressionError> {
+        match self {
+            Self::Zip(zip) => {
+                zip.start_file(name, SimpleFileOptions::default())?;
+                zip.write_all(contents)?;
+            }
+            Self::TarZstd(tar) => {
+                let mut header = tar::Header::new_gnu();
+                header.set_size(contents.len() as u64);
+                header.set_mode(0o644);
+                header.set_cksum();
+                tar.append_data(&mut header, name, contents)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn append_file(&mut self, name: &str, path: &Path) -> Result<(), CompressionError> {
+        match self {
+            Self::Zip(zip) => {
+                zip.start_file(name, SimpleFileOptions::default())?;
+                io::copy(&mut File::open(path)?, zip.as_mut())?;
+            }
+            Self::TarZstd(tar) => tar.append_path_with_name(path, name)?,
+        }
+
+        Ok(())
+    }
+
+    fn finish(self) -> Result<(), CompressionError> {
+        let mut file = match self {
+            Self::Zip(zip) => zip.finish()?,
+            Self::TarZstd(tar) => tar.into_inner()?.finish()?,
+        };
+
+        file.flush()?;
+        Ok(())
+    }
+}
+
+fn compress_blocking(
+    source: &Path,
+    dest: &Path,
+    options: CompressOptions,
+) -> Result<(), CompressionError> {
+    let mut files = Vec::new();
+    collect_files(source, Path::new(""), &options.filter, &mut files)?;
+    sort_files(&mut files, &options.order);
+
+    let mut progress = Progress {
+        total_count: files.len() as u64,
+        total_size: files.iter().map(|file| file.size).sum(),
+        ..Default::default()
+    };
+
+    if let Some(sender) = &options.progress_sender {
+        sender.send_replace(progress);
+    }
+
+    trace!(
+        "Writing {} files from {source:?} to {:?} archive {dest:?}",
+        files.len(),
+        options.format
+    );
+
+    let mut writer = ArchiveWriter::new(options.format, dest)?;
+
+    for (name, contents) in &options.extra_files {
+        writer.append_data(name, contents)?;
+    }
+
+    for file in files {
+        let name = archive_name(options.prefix.as_deref(), &file.relpath);
+        writer.append_file(&name, &source.join(&file.relpath))?;
+
+        progress.current_count += 1;
+        progress.current_size += file.size;
+
+        if let Some(sender) = &options.progress_sender {
+            sender.send_replace(progress);
+        }
+    }
+
+    writer.finish()?;
+    trace!("Finished writing {dest:?}");
+
+    Ok(())
+}
+
+/// Builds an archive at `dest` from the contents of the `source` directory.
+pub async fn compress<T>(
+    source: T,
+    dest: &Path,
+    options: CompressOptions,
+) -> Result<(), CompressionError>
+where
+    T: AsRef<Path> + Send + Sync,
+{
+    let source = source.as_ref().to_path_buf();
+    let dest = dest.to_path_buf();
+
+    tokio::task::spawn_blocking(move || compress_blocking(&source, &dest, options)).await??;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decompress;
+
+    fn make_source(name: &str) -> PathBuf {
+        let source = Path::new("tests_decompressed").join(name);
+        std::fs::create_dir_all(source.join("config")).unwrap();
+        std::fs::create_dir_all(source.join("logs")).unwrap();
+        std::fs::write(source.join("options.txt"), "fov:90").unwrap();
+        std::fs::write(source.join("config/a.toml"), "a = 1").unwrap();
+        std::fs::write(source.join("logs/latest.log"), "log").unwrap();
+        source
+    }
+
+    #[tokio::test]
+    async fn test_compress_zip() {
+        let source = make_source("compress_zip_source");
+        let dest = Path::new("tests_decompressed/compress.zip");
+        let (progress_tx, progress_rx) = tokio::sync::watch::channel(Progress::new());
+
+        let options = CompressOptions::builder()
+            .prefix("overrides")
+            .extra_file("manifest.json", b"{}".to_vec())
+            .filter(|path| !path.starts_with("logs"))
+            .order(EntryOrder::Priority(vec![PathBuf::from("options.txt")]))
+            .progress_sender(progress_tx)
+            .build();
+
+        compress(&source, dest, options).await.unwrap();
+
+        let zip = zip::ZipArchive::new(File::open(dest).unwrap()).unwrap();
+        let names = zip.file_names().collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            [
+                "manifest.json",
+                "overrides/options.txt",
+                "overrides/config/a.toml"
+            ]
+        );
+
+        let progress = *progress_rx.borrow();
+        assert_eq!(progress.total_count, 2);
+        assert_eq!(progress.current_count, 2);
+        assert_eq!(progress.current_size, 11);
+    }
+
+    #[tokio::test]
+    async fn test_compress_tar_zstd_roundtrip() {
+        let source = make_source("compress_tar_zstd_source");
+        let dest = Path::new("tests_decompressed/compress.tar.zst");
+
+        let options = CompressOptions::builder()
+            .format(ArchiveFormat::TarZstd)
+            .order(EntryOrder::Alphabetical)
+            .build();
+
+        compress(&source, dest, options).await.unwrap();
+
+        let extracted = Path::new("tests_decompressed/compress_tar_zstd_extracted");
+        decompress(dest, extracted).await.unwrap();
+
+        let content = std::fs::read_to_string(extracted.join("config/a.toml")).unwrap();
+        assert_eq!(content, "a = 1");
+        assert!(extracted.join("logs/latest.log").is_file());
+    }
+}
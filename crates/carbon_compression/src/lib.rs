@@ -1,10 +1,32 @@
 use std::{
-    io::{Read, Seek},
-    path::Path,
+    ffi::OsStr,
+    io::{BufReader, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
 };
 use thiserror::Error;
+use tokio::sync::watch::Sender;
 use tracing::trace;
 
+mod compress;
+mod policy;
+
+pub use compress::{compress, ArchiveFormat, CompressOptions, CompressOptionsBuilder, EntryOrder};
+pub use policy::{ExtractionPolicy, LinkPolicy};
+
+use policy::{check_symlink, sanitize_entry_path, ExtractionBudget};
+
+/// Nested archives are only unpacked this many levels deep in recursive mode.
+const MAX_NESTED_DEPTH: usize = 8;
+
+/// Extensions considered when looking for nested archives in recursive mode.
+/// Jars are zips too, so detection by magic alone would unpack every mod.
+const NESTED_ARCHIVE_EXTENSIONS: [&str; 9] =
+    ["zip", "tar", "gz", "tgz", "xz", "txz", "zst", "tzst", "7z"];
+
 #[derive(Error, Debug)]
 pub enum CompressionError {
     #[error("Failed to open file: {0}")]
@@ -15,13 +37,30 @@ pub enum CompressionError {
     GenericDecompressionError(#[from] tokio::task::JoinError),
     #[error("Failed to process zip file: {0}")]
     ZipError(#[from] zip::result::ZipError),
+    #[error("Failed to process 7z file: {0}")]
+    SevenZipError(#[from] sevenz_rust::Error),
+    #[error("Nested archives exceed the maximum depth of {0}")]
+    NestingTooDeep(usize),
+    #[error("Archive entry `{0}` points outside of the destination folder")]
+    UnsafePath(String),
+    #[error("Archive entry `{0}` is a link, which the extraction policy doesn't allow")]
+    LinkNotAllowed(String),
+    #[error("Archive contains more than {0} files")]
+    TooManyFiles(u64),
+    #[error("Archive expands to more than {0} bytes")]
+    SizeLimitExceeded(u64),
+    #[error("Archive compression ratio exceeds {0}:1")]
+    CompressionRatioExceeded(u64),
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 enum CompressionFormat {
     Zip,
     Tar,
     Gzip,
+    Xz,
+    Zstd,
+    SevenZip,
 }
 
 impl CompressionFormat {
@@ -29,33 +68,251 @@ impl CompressionFormat {
         match bytes {
             [0x50, 0x4B, 0x03, 0x04, ..] => Ok(Self::Zip),
             [0x1F, 0x8B, ..] => Ok(Self::Gzip),
-            [.., 0x75, 0x73, 0x74, 0x61, 0x72] => Ok(Self::Tar),
+            [0xFD, 0x37, 0x7A, 0x58, 0x5A, 0x00, ..] => Ok(Self::Xz),
+            [0x28, 0xB5, 0x2F, 0xFD, ..] => Ok(Self::Zstd),
+            [0x37, 0x7A, 0xBC, 0xAF, 0x27, 0x1C, ..] => Ok(Self::SevenZip),
+            _ if bytes.get(257..262) == Some(&[0x75, 0x73, 0x74, 0x61, 0x72]) => Ok(Self::Tar),
             _ => Err(CompressionError::UnknownFormat),
         }
     }
 }
 
 fn detect_compression_format(file: &mut impl Read) -> Result<CompressionFormat, CompressionError> {
-    let mut header = [0; 262]; // Magic number to hold the max possible offset (tar) of 257 bytes + 5 relevant bytes
-    let _bytes = file.read(&mut header)?;
-    if let Ok(format) = CompressionFormat::from_bytes(&header) {
-        return Ok(format);
+    // Max possible offset (tar) of 257 bytes + 5 relevant bytes. Decoders may
+    // return short reads, so keep reading until the header is full or EOF.
+    let mut header = Vec::with_capacity(262);
+    file.take(262).read_to_end(&mut header)?;
+
+    CompressionFormat::from_bytes(&header)
+}
+
+/// Progress of an archive being created or extracted.
+///
+/// When extracting, sizes are measured on the compressed archive and
+/// `total_count` stays at 0 for formats that don't list their entries
+/// upfront (tar based ones).
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Progress {
+    pub total_count: u64,
+    pub current_count: u64,
+    pub total_size: u64,
+    pub current_size: u64,
+}
+
+impl Progress {
+    pub fn new() -> Self {
+        Self::default()
     }
+}
 
-    Err(CompressionError::UnknownFormat)
+#[derive(Debug, Default)]
+pub struct DecompressOptions {
+    recursive: bool,
+    policy: ExtractionPolicy,
+    subfolder: Option<PathBuf>,
+    progress_sender: Option<Sender<Progress>>,
 }
 
-fn decompress_zip<R>(archive: &mut zip::ZipArchive<R>, dest: &Path) -> Result<(), CompressionError>
+impl DecompressOptions {
+    pub fn builder() -> DecompressOptionsBuilder {
+        DecompressOptionsBuilder::default()
+    }
+}
+
+#[derive(Default)]
+pub struct DecompressOptionsBuilder {
+    recursive: Option<bool>,
+    policy: Option<ExtractionPolicy>,
+    subfolder: Option<PathBuf>,
+    progress_sender: Option<Sender<Progress>>,
+}
+
+impl DecompressOptionsBuilder {
+    /// Also unpack archives found among the extracted files. Each nested
+    /// archive is extracted next to itself, into a folder named after it,
+    /// and then removed.
+    pub fn recursive(mut self, recursive: bool) -> Self {
+        self.recursive = Some(recursive);
+        self
+    }
+
+    /// Limits enforced while extracting, [`ExtractionPolicy::default`] if unset.
+    pub fn policy(mut self, policy: ExtractionPolicy) -> Self {
+        self.policy = Some(policy);
+        self
+    }
+
+    /// Only extract the entries inside of this folder of the archive, relative
+    /// to it, like the overrides of a modpack. Only supported for zip archives,
+    /// other formats fail with [`CompressionError::UnknownFormat`].
+    pub fn subfolder(mut self, subfolder: impl Into<PathBuf>) -> Self {
+        self.subfolder = Some(subfolder.into());
+        self
+    }
+
+    /// Receives an update after every extracted entry of the top level archive.
+    pub fn progress_sender(mut self, progress_sender: Sender<Progress>) -> Self {
+        self.progress_sender = Some(progress_sender);
+        self
+    }
+
+    pub fn build(self) -> DecompressOptions {
+        DecompressOptions {
+            recursive: self.recursive.unwrap_or(false),
+            policy: self.policy.unwrap_or_default(),
+            subfolder: self.subfolder,
+            progress_sender: self.progress_sender,
+        }
+    }
+}
+
+/// Keeps track of how far into the source archive the decoder has read.
+struct PositionReader<R> {
+    inner: R,
+    position: Arc<AtomicU64>,
+}
+
+impl<R: Read> Read for PositionReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.position.fetch_add(read as u64, Ordering::Relaxed);
+        Ok(read)
+    }
+}
+
+impl<R: Seek> Seek for PositionReader<R> {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let position = self.inner.seek(pos)?;
+        self.position.store(position, Ordering::Relaxed);
+        Ok(position)
+    }
+}
+
+struct ProgressReporter {
+    sender: Option<Sender<Progress>>,
+    progress: Progress,
+    position: Arc<AtomicU64>,
+}
+
+impl ProgressReporter {
+    fn new(sender: Option<Sender<Progress>>, total_size: u64) -> Self {
+        Self {
+            sender,
+            progress: Progress {
+                total_size,
+                ..Default::default()
+            },
+            position: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Used for nested archives, whose progress isn't part of the top level bar.
+    fn silent() -> Self {
+        Self::new(None, 0)
+    }
+
+    fn reader<R>(&self, inner: R) -> PositionReader<R> {
+        PositionReader {
+            inner,
+            position: self.position.clone(),
+        }
+    }
+
+    fn set_total_count(&mut self, total_count: u64) {
+        self.progress.total_count = total_count;
+    }
+
+    fn entry_done(&mut self) {
+        self.progress.current_count += 1;
+        self.progress.current_size = self
+            .position
+            .load(Ordering::Relaxed)
+            .min(self.progress.total_size);
+
+        if let Some(sender) = &self.sender {
+            sender.send_replace(self.progress);
+        }
+    }
+
+    fn finish(&mut self) {
+        self.progress.current_size = self.progress.total_size;
+
+        if let Some(sender) = &self.sender {
+            sender.send_replace(self.progress);
+        }
+    }
+}
+
+fn unsafe_path(path: impl AsRef<Path>) -> CompressionError {
+    CompressionError::UnsafePath(path.as_ref().to_string_lossy().to_string())
+}
+
+/// Returns false if the link has to be left out.
+fn allow_link(
+    budget: &ExtractionBudget,
+    relpath: &Path,
+    entry_type: &str,
+) -> Result<bool, CompressionError> {
+    match budget.policy().links {
+        LinkPolicy::Reject => Err(CompressionError::LinkNotAllowed(
+            relpath.to_string_lossy().to_string(),
+        )),
+        LinkPolicy::Skip => {
+            trace!("Skipping {entry_type} {relpath:?}");
+            Ok(false)
+        }
+        LinkPolicy::AllowContained => Ok(true),
+    }
+}
+
+#[cfg(unix)]
+fn create_symlink(target: &Path, link: &Path) -> std::io::Result<()> {
+    if let Some(p) = link.parent() {
+        std::fs::create_dir_all(p)?;
+    }
+
+    std::os::unix::fs::symlink(target, link)
+}
+
+#[cfg(not(unix))]
+fn create_symlink(_target: &Path, link: &Path) -> std::io::Result<()> {
+    trace!("Skipping symlink {link:?}, zip symlinks are only supported on unix");
+    Ok(())
+}
+
+fn decompress_zip<R>(
+    archive: &mut zip::ZipArchive<R>,
+    dest: &Path,
+    subfolder: Option<&Path>,
+    reporter: &mut ProgressReporter,
+    budget: &mut ExtractionBudget,
+) -> Result<Vec<PathBuf>, CompressionError>
 where
     R: Read + Seek,
 {
+    let mut extracted = Vec::new();
+    reporter.set_total_count(archive.len() as u64);
+
     for i in 0..archive.len() {
         let mut file = archive.by_index(i)?;
-        let outpath = match file.enclosed_name() {
-            Some(path) => dest.join(path),
-            None => continue,
+        let relpath = file
+            .enclosed_name()
+            .and_then(|path| sanitize_entry_path(&path))
+            .ok_or_else(|| unsafe_path(file.name()))?;
+
+        let relpath = match subfolder {
+            None => relpath,
+            Some(subfolder) => match relpath.strip_prefix(subfolder) {
+                Ok(relpath) if !relpath.as_os_str().is_empty() => relpath.to_path_buf(),
+                _ => {
+                    reporter.entry_done();
+                    continue;
+                }
+            },
         };
 
+        let outpath = dest.join(&relpath);
+
         {
             let comment = file.comment();
             if !comment.is_empty() {
@@ -66,6 +323,23 @@ where
         if (*file.name()).ends_with('/') {
             trace!("File {} extracted to \"{}\"", i, outpath.display());
             std::fs::create_dir_all(&outpath)?;
+        } else if file.is_symlink() {
+            if allow_link(budget, &relpath, "symlink")? {
+                // the link target is stored as the entry's contents
+                let mut target = String::new();
+                (&mut file).take(4096).read_to_string(&mut target)?;
+
+                check_symlink(dest, &relpath, Path::new(&target))?;
+                trace!(
+                    "File {i} linked from \"{}\" to {target:?}",
+                    outpath.display()
+                );
+                create_symlink(Path::new(&target), &outpath)?;
+            }
+
+            // permissions of a symlink are the ones of its target
+            reporter.entry_done();
+            continue;
         } else {
             trace!(
                 "File {} extracted to \"{}\" ({} bytes)",
@@ -73,66 +347,332 @@ where
                 outpath.display(),
                 file.size()
             );
+            budget.add_file()?;
             if let Some(p) = outpath.parent() {
                 if !p.exists() {
                     std::fs::create_dir_all(p)?;
                 }
             }
             let mut outfile = std::fs::File::create(&outpath)?;
-            std::io::copy(&mut file, &mut outfile)?;
+            budget.copy(&mut file, &mut outfile)?;
+            extracted.push(outpath.clone());
         }
 
-        // Get and Set permissions
+        // Get and Set permissions, without setuid, setgid and sticky bits
         #[cfg(unix)]
         {
             use std::os::unix::fs::PermissionsExt;
 
             if let Some(mode) = file.unix_mode() {
-                std::fs::set_permissions(&outpath, std::fs::Permissions::from_mode(mode))?;
+                std::fs::set_permissions(&outpath, std::fs::Permissions::from_mode(mode & 0o777))?;
             }
         }
+
+        reporter.entry_done();
     }
 
-    Ok(())
+    Ok(extracted)
 }
 
 fn decompress_tar<R>(
     archive: &mut tar::Archive<R>,
     dest_folder: &Path,
-) -> Result<(), CompressionError>
+    reporter: &mut ProgressReporter,
+    budget: &mut ExtractionBudget,
+) -> Result<Vec<PathBuf>, CompressionError>
 where
     R: Read,
 {
-    archive.unpack(dest_folder)?;
-    Ok(())
+    std::fs::create_dir_all(dest_folder)?;
+    // masks modes with 0o777, dropping setuid, setgid and sticky bits
+    archive.set_preserve_permissions(false);
+
+    let mut extracted = Vec::new();
+
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let path = entry.path()?.into_owned();
+        let relpath = sanitize_entry_path(&path).ok_or_else(|| unsafe_path(&path))?;
+        let entry_type = entry.header().entry_type();
+
+        // `./`, usually the first entry of tarballs made from a folder
+        if relpath.as_os_str().is_empty() {
+            reporter.entry_done();
+            continue;
+        }
+
+        if entry_type.is_symlink() || entry_type.is_hard_link() {
+            let kind = match entry_type.is_symlink() {
+                true => "symlink",
+                false => "hardlink",
+            };
+
+            if !allow_link(budget, &relpath, kind)? {
+                reporter.entry_done();
+                continue;
+            }
+
+            let target = entry
+                .link_name()?
+                .ok_or_else(|| unsafe_path(&relpath))?
+                .into_owned();
+
+            if entry_type.is_symlink() {
+                check_symlink(dest_folder, &relpath, &target)?;
+            } else {
+                // hardlink targets are relative to the root of the archive
+                sanitize_entry_path(&target)
+                    .filter(|target| !target.as_os_str().is_empty())
+                    .ok_or_else(|| unsafe_path(&relpath))?;
+            }
+        } else if entry_type.is_file() || entry_type.is_contiguous() {
+            budget.add_file()?;
+            budget.reserve(entry.header().size()?)?;
+        } else if !entry_type.is_dir() {
+            trace!("Skipping {path:?}, unsupported tar entry type {entry_type:?}");
+            reporter.entry_done();
+            continue;
+        }
+
+        if !entry.unpack_in(dest_folder)? {
+            return Err(unsafe_path(&path));
+        }
+
+        if entry_type.is_file() || entry_type.is_contiguous() {
+            extracted.push(dest_folder.join(relpath));
+        }
+
+        reporter.entry_done();
+    }
+
+    Ok(extracted)
 }
 
-fn decompress_gzip(archive_path: &Path, dest_folder: &Path) -> Result<(), CompressionError> {
-    let archive_file = std::fs::File::open(&archive_path)?;
-    let mut archive = flate2::read::GzDecoder::new(archive_file);
+fn decompress_7z(
+    file: std::fs::File,
+    dest_folder: &Path,
+    reporter: &mut ProgressReporter,
+    budget: &mut ExtractionBudget,
+) -> Result<Vec<PathBuf>, CompressionError> {
+    let mut extracted = Vec::new();
+    let reader = reporter.reader(BufReader::new(file));
+
+    // the extract callback can only return 7z errors, keep ours aside
+    let mut violation = None;
+
+    let result =
+        sevenz_rust::decompress_with_extract_fn(reader, dest_folder, |entry, reader, _| {
+            let r = (|| {
+                let relpath = sanitize_entry_path(Path::new(entry.name()))
+                    .ok_or_else(|| unsafe_path(entry.name()))?;
+                let outpath = dest_folder.join(relpath);
+
+                if entry.is_directory() {
+                    std::fs::create_dir_all(&outpath)?;
+                } else {
+                    trace!("7z entry extracted to \"{}\"", outpath.display());
+                    budget.add_file()?;
+                    if let Some(p) = outpath.parent() {
+                        std::fs::create_dir_all(p)?;
+                    }
+                    let mut outfile = std::io::BufWriter::new(std::fs::File::create(&outpath)?);
+                    budget.copy(reader, &mut outfile)?;
+                    outfile.flush()?;
+                    extracted.push(outpath);
+                }
 
-    let format = detect_compression_format(&mut archive);
-    // Redeclare because it's been consumed by detect_compression_format
-    let archive_file = std::fs::File::open(archive_path)?;
-    let archive = flate2::read::GzDecoder::new(archive_file);
+                reporter.entry_done();
+                Ok::<_, CompressionError>(())
+            })();
 
-    if let Ok(format) = format {
-        if CompressionFormat::Tar == format {
-            let mut archive = tar::Archive::new(archive);
-            decompress_tar(&mut archive, dest_folder)?;
-            return Ok(());
-        } else {
-            unimplemented!("{format:?} is not supported yet")
+            match r {
+                Ok(()) => Ok(true),
+                Err(e) => {
+                    violation = Some(e);
+                    Err(sevenz_rust::Error::other("extraction aborted"))
+                }
+            }
+        });
+
+    if let Some(violation) = violation {
+        return Err(violation);
+    }
+
+    result?;
+
+    Ok(extracted)
+}
+
+fn open_stream<'a, R: Read + 'a>(
+    reader: R,
+    format: CompressionFormat,
+) -> Result<Box<dyn Read + 'a>, CompressionError> {
+    let file = BufReader::new(reader);
+
+    Ok(match format {
+        CompressionFormat::Gzip => Box::new(flate2::read::MultiGzDecoder::new(file)),
+        CompressionFormat::Xz => Box::new(xz2::read::XzDecoder::new_multi_decoder(file)),
+        CompressionFormat::Zstd => Box::new(zstd::stream::read::Decoder::with_buffer(file)?),
+        CompressionFormat::Zip | CompressionFormat::Tar | CompressionFormat::SevenZip => {
+            return Err(CompressionError::UnknownFormat)
+        }
+    })
+}
+
+/// Single stream formats (gzip, xz, zstd) either wrap a tarball or a single
+/// plain file, which is written to `dest_folder` under the archive's stem.
+fn decompress_stream(
+    archive_path: &Path,
+    format: CompressionFormat,
+    dest_folder: &Path,
+    reporter: &mut ProgressReporter,
+    budget: &mut ExtractionBudget,
+) -> Result<Vec<PathBuf>, CompressionError> {
+    let inner_format = detect_compression_format(&mut open_stream(
+        std::fs::File::open(archive_path)?,
+        format,
+    )?);
+    // Reopen because the stream has been consumed by detect_compression_format
+    let file = std::fs::File::open(archive_path)?;
+    let mut stream = open_stream(reporter.reader(file), format)?;
+
+    if let Ok(CompressionFormat::Tar) = inner_format {
+        trace!("Detected tarball inside of {format:?} stream");
+        let mut archive = tar::Archive::new(stream);
+        return decompress_tar(&mut archive, dest_folder, reporter, budget);
+    }
+
+    let filename = archive_path
+        .file_stem()
+        .unwrap_or_else(|| OsStr::new("decompressed"));
+    let outpath = dest_folder.join(filename);
+
+    trace!(
+        "Writing plain {format:?} stream to \"{}\"",
+        outpath.display()
+    );
+    budget.add_file()?;
+    std::fs::create_dir_all(dest_folder)?;
+    let mut outfile = std::fs::File::create(&outpath)?;
+    budget.copy(&mut stream, &mut outfile)?;
+    reporter.entry_done();
+
+    Ok(vec![outpath])
+}
+
+fn decompress_file(
+    archive_path: &Path,
+    dest_folder: &Path,
+    subfolder: Option<&Path>,
+    reporter: &mut ProgressReporter,
+    budget: &mut ExtractionBudget,
+) -> Result<Vec<PathBuf>, CompressionError> {
+    let mut file = std::fs::File::open(archive_path)?;
+    let format = detect_compression_format(&mut file)?;
+    trace!("Starting decompression for file: {archive_path:?} to {dest_folder:?}");
+    trace!("Detected compression format: {format:?}");
+
+    if subfolder.is_some() && format != CompressionFormat::Zip {
+        return Err(CompressionError::UnknownFormat);
+    }
+
+    budget.add_archive(file.metadata()?.len());
+    file.rewind()?;
+
+    let extracted = match format {
+        CompressionFormat::Zip => {
+            let mut archive = zip::ZipArchive::new(BufReader::new(reporter.reader(file)))?;
+            decompress_zip(&mut archive, dest_folder, subfolder, reporter, budget)
+        }
+        CompressionFormat::Tar => {
+            let mut archive = tar::Archive::new(BufReader::new(reporter.reader(file)));
+            decompress_tar(&mut archive, dest_folder, reporter, budget)
         }
+        CompressionFormat::SevenZip => decompress_7z(file, dest_folder, reporter, budget),
+        CompressionFormat::Gzip | CompressionFormat::Xz | CompressionFormat::Zstd => {
+            decompress_stream(archive_path, format, dest_folder, reporter, budget)
+        }
+    }?;
+
+    reporter.finish();
+
+    Ok(extracted)
+}
+
+/// Strips every archive extension, so `pack.tar.gz` unpacks into `pack`.
+fn nested_dest_folder(archive_path: &Path) -> PathBuf {
+    let mut name = archive_path.to_path_buf();
+
+    while name
+        .extension()
+        .and_then(OsStr::to_str)
+        .is_some_and(|ext| NESTED_ARCHIVE_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+    {
+        name.set_extension("");
+    }
+
+    // a plain stream may already have been written out under the same name
+    if name.is_file() {
+        name.set_extension("extracted");
     }
 
-    unimplemented!("Gzip is not supported yet");
+    name
 }
 
-// TODO: Ideally decompression would recursively look for another compressed file until it can't find any more.
+fn decompress_nested(
+    files: Vec<PathBuf>,
+    depth: usize,
+    budget: &mut ExtractionBudget,
+) -> Result<(), CompressionError> {
+    for file in files {
+        let has_archive_extension = file
+            .extension()
+            .and_then(OsStr::to_str)
+            .is_some_and(|ext| NESTED_ARCHIVE_EXTENSIONS.contains(&ext.to_lowercase().as_str()));
+
+        if !has_archive_extension {
+            continue;
+        }
+
+        if detect_compression_format(&mut std::fs::File::open(&file)?).is_err() {
+            trace!("Skipping {file:?}, not a supported archive");
+            continue;
+        }
+
+        if depth >= MAX_NESTED_DEPTH {
+            return Err(CompressionError::NestingTooDeep(MAX_NESTED_DEPTH));
+        }
+
+        let dest_folder = nested_dest_folder(&file);
+        trace!("Unpacking nested archive {file:?} to {dest_folder:?}");
+
+        let extracted = decompress_file(
+            &file,
+            &dest_folder,
+            None,
+            &mut ProgressReporter::silent(),
+            budget,
+        )?;
+        std::fs::remove_file(&file)?;
+        decompress_nested(extracted, depth + 1, budget)?;
+    }
+
+    Ok(())
+}
 
 // accept both paths and strings
 pub async fn decompress<T>(path: T, dest_folder: &Path) -> Result<(), CompressionError>
+where
+    T: AsRef<Path> + Send + Sync,
+{
+    decompress_with_options(path, dest_folder, DecompressOptions::default()).await
+}
+
+pub async fn decompress_with_options<T>(
+    path: T,
+    dest_folder: &Path,
+    options: DecompressOptions,
+) -> Result<(), CompressionError>
 where
     T: AsRef<Path> + Send + Sync,
 {
@@ -140,24 +680,19 @@ where
     let dest_folder_clone = dest_folder.to_path_buf();
 
     let task_handler = tokio::task::spawn_blocking(move || {
-        let mut file = std::fs::File::open(&path_clone)?;
-        let file_clone = std::fs::File::open(&path_clone)?;
-        let format = detect_compression_format(&mut file)?;
-        trace!("Starting compression for file: {path_clone:?} to {dest_folder_clone:?}");
-        trace!("Detected compression format: {format:?}");
-
-        match format {
-            CompressionFormat::Zip => {
-                let mut archive = zip::ZipArchive::new(file_clone)?;
-                decompress_zip(&mut archive, &dest_folder_clone)?;
-            }
-            CompressionFormat::Tar => {
-                let mut archive = tar::Archive::new(file_clone);
-                decompress_tar(&mut archive, &dest_folder_clone)?;
-            }
-            CompressionFormat::Gzip => {
-                decompress_gzip(&path_clone, &dest_folder_clone)?;
-            }
+        let total_size = std::fs::metadata(&path_clone)?.len();
+        let mut reporter = ProgressReporter::new(options.progress_sender, total_size);
+        let mut budget = ExtractionBudget::new(options.policy);
+        let extracted = decompress_file(
+            &path_clone,
+            &dest_folder_clone,
+            options.subfolder.as_deref(),
+            &mut reporter,
+            &mut budget,
+        )?;
+
+        if options.recursive {
+            decompress_nested(extracted, 0, &mut budget)?;
         }
 
         Ok::<(), CompressionError>(())
@@ -189,6 +724,18 @@ mod tests {
         let mut file = std::fs::File::open("fixtures/compressed.gz").unwrap();
         let format = detect_compression_format(&mut file).unwrap();
         assert_eq!(CompressionFormat::Gzip, format);
+
+        let mut file = std::fs::File::open("fixtures/compressed.tar.xz").unwrap();
+        let format = detect_compression_format(&mut file).unwrap();
+        assert_eq!(CompressionFormat::Xz, format);
+
+        let mut file = std::fs::File::open("fixtures/compressed.tar.zst").unwrap();
+        let format = detect_compression_format(&mut file).unwrap();
+        assert_eq!(CompressionFormat::Zstd, format);
+
+        let mut file = std::fs::File::open("fixtures/compressed.7z").unwrap();
+        let format = detect_compression_format(&mut file).unwrap();
+        assert_eq!(CompressionFormat::SevenZip, format);
     }
 
     #[tokio::test]
@@ -212,12 +759,118 @@ mod tests {
         decompress(&file_path, &dest_folder).await.unwrap();
     }
 
-    // Not supported yet.. :(
+    #[tokio::test]
+    async fn test_decompress_tar_xz() {
+        let file_path = Path::new("fixtures/compressed.tar.xz");
+        let dest_folder = Path::new("tests_decompressed/tar_xz");
+        decompress(file_path, dest_folder).await.unwrap();
+        assert!(dest_folder.join("compressed.txt").is_file());
+    }
+
+    #[tokio::test]
+    async fn test_decompress_tar_zstd() {
+        let file_path = Path::new("fixtures/compressed.tar.zst");
+        let dest_folder = Path::new("tests_decompressed/tar_zstd");
+        decompress(file_path, dest_folder).await.unwrap();
+        assert!(dest_folder.join("compressed.txt").is_file());
+    }
+
+    #[tokio::test]
+    async fn test_decompress_7z() {
+        let file_path = Path::new("fixtures/compressed.7z");
+        let dest_folder = Path::new("tests_decompressed/7z");
+        decompress(file_path, dest_folder).await.unwrap();
+        assert!(dest_folder.join("compressed.txt").is_file());
+    }
+
+    #[tokio::test]
+    async fn test_decompress_gzip() {
+        let file_path = Path::new("fixtures/compressed.gz");
+        let dest_folder = Path::new("tests_decompressed/gzip");
+        decompress(file_path, dest_folder).await.unwrap();
+
+        let content = std::fs::read_to_string(dest_folder.join("compressed")).unwrap();
+        assert_eq!(content, "Some compressed file");
+    }
+
+    #[tokio::test]
+    async fn test_decompress_recursive() {
+        let file_path = Path::new("fixtures/nested.zip");
+        let dest_folder = Path::new("tests_decompressed/recursive");
+        let options = DecompressOptions::builder().recursive(true).build();
+        decompress_with_options(file_path, dest_folder, options)
+            .await
+            .unwrap();
+
+        assert!(dest_folder.join("compressed.txt").is_file());
+        assert!(dest_folder.join("inner/compressed").is_dir());
+        assert!(!dest_folder.join("inner/compressed.tar.gz").exists());
+    }
+
+    #[tokio::test]
+    async fn test_decompress_progress() {
+        let file_path = Path::new("fixtures/compressed.zip");
+        let dest_folder = Path::new("tests_decompressed/progress");
+        let (progress_tx, progress_rx) = tokio::sync::watch::channel(Progress::new());
+        let options = DecompressOptions::builder()
+            .progress_sender(progress_tx)
+            .build();
+        decompress_with_options(file_path, dest_folder, options)
+            .await
+            .unwrap();
+
+        let progress = *progress_rx.borrow();
+        assert_eq!(progress.total_count, 2);
+        assert_eq!(progress.current_count, 2);
+        assert_eq!(progress.current_size, progress.total_size);
+        assert_eq!(progress.total_size, 526);
+    }
 
-    // #[tokio::test]
-    // async fn test_decompress_gzip() {
-    //     let file_path = PathBuf::from("fixtures/compressed.gz");
-    //     let dest_folder = PathBuf::from("tests_decompressed/gzip");
-    //     decompress(&file_path, &dest_folder).await.unwrap();
-    // }
+    #[tokio::test]
+    async fn test_decompress_subfolder() {
+        use std::io::Write;
+        use zip::write::SimpleFileOptions;
+
+        let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        for (name, data) in [
+            ("manifest.json", "{}"),
+            ("overrides/config/mod.toml", "a = 1"),
+            ("overrides-other/options.txt", "b = 2"),
+        ] {
+            zip.start_file(name, SimpleFileOptions::default()).unwrap();
+            zip.write_all(data.as_bytes()).unwrap();
+        }
+
+        std::fs::create_dir_all("tests_decompressed").unwrap();
+        let file_path = Path::new("tests_decompressed/subfolder.zip");
+        std::fs::write(file_path, zip.finish().unwrap().into_inner()).unwrap();
+
+        let dest_folder = Path::new("tests_decompressed/subfolder");
+        let options = DecompressOptions::builder().subfolder("overrides").build();
+        decompress_with_options(file_path, dest_folder, options)
+            .await
+            .unwrap();
+
+        assert!(dest_folder.join("config/mod.toml").is_file());
+        assert!(!dest_folder.join("manifest.json").exists());
+        assert!(!dest_folder.join("options.txt").exists());
+        assert!(!dest_folder.join("overrides-other").exists());
+
+        let options = DecompressOptions::builder().subfolder("overrides").build();
+        let result = decompress_with_options(
+            Path::new("fixtures/compressed.tar"),
+            Path::new("tests_decompressed/subfolder_tar"),
+            options,
+        )
+        .await;
+        assert!(matches!(result, Err(CompressionError::UnknownFormat)));
+    }
+
+    #[tokio::test]
+    async fn test_decompress_unknown_format() {
+        let file_path = Path::new("fixtures/compressed.txt");
+        let dest_folder = Path::new("tests_decompressed/unknown");
+        let result = decompress(file_path, dest_folder).await;
+        assert!(matches!(result, Err(CompressionError::UnknownFormat)));
+    }
 }
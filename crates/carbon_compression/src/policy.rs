@@ -0,0 +1,434 @@
+use std::{
+    io::{Read, Write},
+    path::{Component, Path, PathBuf},
+};
+
+use crate::CompressionError;
+
+/// The compression ratio is only checked once this many bytes have been
+/// written, small archives of repetitive text easily go past any sane ratio.
+const RATIO_GRACE_SIZE: u64 = 16 * 1024 * 1024;
+
+const COPY_BUFFER_SIZE: usize = 64 * 1024;
+
+/// What to do with symlinks and hardlinks found in an archive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkPolicy {
+    /// Fail the extraction with [`CompressionError::LinkNotAllowed`].
+    Reject,
+    /// Leave links out of the extracted files.
+    Skip,
+    /// Keep links that resolve inside of the destination folder and fail with
+    /// [`CompressionError::UnsafePath`] on the others.
+    AllowContained,
+}
+
+/// Limits enforced while extracting an archive. Entries escaping the
+/// destination folder are always rejected and setuid, setgid and sticky bits
+/// are always dropped, regardless of the policy.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtractionPolicy {
+    /// Maximum number of bytes written to disk.
+    pub max_total_size: Option<u64>,
+    /// Maximum number of files written to disk, folders don't count.
+    pub max_file_count: Option<u64>,
+    /// Maximum ratio between the bytes written and the size of the archive.
+    pub max_compression_ratio: Option<u64>,
+    pub links: LinkPolicy,
+}
+
+impl Default for ExtractionPolicy {
+    fn default() -> Self {
+        Self {
+            max_total_size: Some(8 * 1024 * 1024 * 1024),
+            max_file_count: Some(200_000),
+            max_compression_ratio: Some(100),
+            // java runtimes for macOS ship relative symlinks inside of the bundle
+            links: LinkPolicy::AllowContained,
+        }
+    }
+}
+
+impl ExtractionPolicy {
+    /// For archives coming from third parties, like modpacks.
+    pub fn strict() -> Self {
+        Self {
+            links: LinkPolicy::Reject,
+            ..Self::default()
+        }
+    }
+
+    pub fn unrestricted() -> Self {
+        Self {
+            max_total_size: None,
+            max_file_count: None,
+            max_compression_ratio: None,
+            links: LinkPolicy::AllowContained,
+        }
+    }
+}
+
+/// Running totals for a single extraction, nested archives included.
+pub(crate) struct ExtractionBudget {
+    policy: ExtractionPolicy,
+    compressed_size: u64,
+    written_size: u64,
+    file_count: u64,
+}
+
+impl ExtractionBudget {
+    pub fn new(policy: ExtractionPolicy) -> Self {
+        Self {
+            policy,
+            compressed_size: 0,
+            written_size: 0,
+            file_count: 0,
+        }
+    }
+
+    pub fn policy(&self) -> &ExtractionPolicy {
+        &self.policy
+    }
+
+    pub fn add_archive(&mut self, compressed_size: u64) {
+        self.compressed_size += compressed_size;
+    }
+
+    pub fn add_file(&mut self) -> Result<(), CompressionError> {
+        self.file_count += 1;
+
+        match self.policy.max_file_count {
+            Some(max) if self.file_count > max => Err(CompressionError::TooManyFiles(max)),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn reserve(&mut self, size: u64) -> Result<(), CompressionError> {
+        self.written_size += size;
+
+        if let Some(max) = self.policy.max_total_size {
+            if self.written_size > max {
+                return Err(CompressionError::SizeLimitExceeded(max));
+            }
+        }
+
+        if let Some(max) = self.policy.max_compression_ratio {
+            if self.written_size > RATIO_GRACE_SIZE
+                && self.written_size / self.compressed_size.max(1) > max
+            {
+                return Err(CompressionError::CompressionRatioExceeded(max));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Like [`std::io::copy`], but stops as soon as a limit is hit instead of
+    /// trusting the sizes declared by the archive.
+    pub fn copy(
+        &mut self,
+        reader: &mut (impl Read + ?Sized),
+        writer: &mut impl Write,
+    ) -> Result<u64, CompressionError> {
+        let mut buf = vec![0; COPY_BUFFER_SIZE];
+        let mut copied = 0;
+
+        loop {
+            let read = match reader.read(&mut buf) {
+                Ok(0) => break,
+                Ok(read) => read,
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e.into()),
+            };
+
+            self.reserve(read as u64)?;
+            writer.write_all(&buf[..read])?;
+            copied += read as u64;
+        }
+
+        Ok(copied)
+    }
+}
+
+/// Normalizes an entry path, returning `None` if it's absolute or climbs out
+/// of the destination folder. `./` entries normalize to an empty path.
+pub(crate) fn sanitize_entry_path(path: &Path) -> Option<PathBuf> {
+    let mut sanitized = PathBuf::new();
+
+    for component in path.components() {
+        match component {
+            Component::Normal(part) => sanitized.push(part),
+            Component::CurDir => {}
+            Component::ParentDir | Component::RootDir | Component::Prefix(_) => return None,
+        }
+    }
+
+    Some(sanitized)
+}
+
+/// Checks that a symlink created at `relpath` inside of `dest` resolves
+/// inside of `dest`. Only leading `..` components are allowed in the target,
+/// anything after a named component could go through another link.
+pub(crate) fn check_symlink(
+    dest: &Path,
+    relpath: &Path,
+    target: &Path,
+) -> Result<(), CompressionError> {
+    let unsafe_path = || CompressionError::UnsafePath(relpath.to_string_lossy().to_string());
+
+    // the target of a link placed in a symlinked folder is resolved from the
+    // folder the link points to, not from where the archive places it
+    let mut ancestor = dest.to_path_buf();
+    let parent = relpath.parent().unwrap_or(Path::new(""));
+    for component in parent.components() {
+        ancestor.push(component);
+
+        if ancestor.is_symlink() {
+            return Err(unsafe_path());
+        }
+    }
+
+    let mut depth = parent.components().count();
+    let mut named = false;
+
+    for component in target.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir if !named && depth > 0 => depth -= 1,
+            Component::Normal(_) => named = true,
+            _ => return Err(unsafe_path()),
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{Cursor, Write},
+        path::Path,
+    };
+
+    use zip::write::SimpleFileOptions;
+
+    use crate::{
+        decompress, decompress_with_options, CompressionError, DecompressOptions, ExtractionPolicy,
+        LinkPolicy,
+    };
+
+    fn write_fixture(name: &str, data: Vec<u8>) -> std::path::PathBuf {
+        let path = Path::new("tests_decompressed").join(name);
+        std::fs::create_dir_all("tests_decompressed").unwrap();
+        std::fs::write(&path, data).unwrap();
+        path
+    }
+
+    fn zip_with(build: impl FnOnce(&mut zip::ZipWriter<Cursor<Vec<u8>>>)) -> Vec<u8> {
+        let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        build(&mut zip);
+        zip.finish().unwrap().into_inner()
+    }
+
+    fn tar_with(build: impl FnOnce(&mut tar::Builder<Vec<u8>>)) -> Vec<u8> {
+        let mut tar = tar::Builder::new(Vec::new());
+        build(&mut tar);
+        tar.into_inner().unwrap()
+    }
+
+    /// `tar::Header::set_path` refuses `..`, so write the name bytes directly.
+    fn raw_tar_header(name: &str, entry_type: tar::EntryType, size: u64) -> tar::Header {
+        // ustar magic, archive detection relies on it
+        let mut header = tar::Header::new_ustar();
+        header.as_old_mut().name[..name.len()].copy_from_slice(name.as_bytes());
+        header.set_entry_type(entry_type);
+        header.set_size(size);
+        header.set_mode(0o644);
+        header.set_cksum();
+        header
+    }
+
+    async fn extract(
+        archive: &Path,
+        dest: &str,
+        policy: ExtractionPolicy,
+    ) -> Result<(), CompressionError> {
+        let options = DecompressOptions::builder().policy(policy).build();
+        decompress_with_options(archive, Path::new(dest), options).await
+    }
+
+    #[tokio::test]
+    async fn test_zip_slip() {
+        let archive = write_fixture(
+            "malicious_slip.zip",
+            zip_with(|zip| {
+                zip.start_file("../zip_slip.txt", SimpleFileOptions::default())
+                    .unwrap();
+                zip.write_all(b"escaped").unwrap();
+            }),
+        );
+
+        let result = decompress(&archive, Path::new("tests_decompressed/malicious_slip_zip")).await;
+        assert!(matches!(result, Err(CompressionError::UnsafePath(_))));
+        assert!(!Path::new("tests_decompressed/zip_slip.txt").exists());
+    }
+
+    #[tokio::test]
+    async fn test_tar_slip() {
+        let archive = write_fixture(
+            "malicious_slip.tar",
+            tar_with(|tar| {
+                let header = raw_tar_header("../tar_slip.txt", tar::EntryType::Regular, 7);
+                tar.append(&header, &b"escaped"[..]).unwrap();
+            }),
+        );
+
+        let result = decompress(&archive, Path::new("tests_decompressed/malicious_slip_tar")).await;
+        assert!(matches!(result, Err(CompressionError::UnsafePath(_))));
+        assert!(!Path::new("tests_decompressed/tar_slip.txt").exists());
+    }
+
+    #[tokio::test]
+    async fn test_escaping_symlinks() {
+        let archive = write_fixture(
+            "malicious_symlink.tar",
+            tar_with(|tar| {
+                let mut header = tar::Header::new_gnu();
+                header.set_entry_type(tar::EntryType::Symlink);
+                header.set_size(0);
+                header.set_mode(0o777);
+                tar.append_link(&mut header, "config/link", "../../outside")
+                    .unwrap();
+            }),
+        );
+
+        let result = extract(
+            &archive,
+            "tests_decompressed/malicious_symlink_default",
+            ExtractionPolicy::default(),
+        )
+        .await;
+        assert!(matches!(result, Err(CompressionError::UnsafePath(_))));
+
+        let result = extract(
+            &archive,
+            "tests_decompressed/malicious_symlink_strict",
+            ExtractionPolicy::strict(),
+        )
+        .await;
+        assert!(matches!(result, Err(CompressionError::LinkNotAllowed(_))));
+
+        let skip = ExtractionPolicy {
+            links: LinkPolicy::Skip,
+            ..ExtractionPolicy::default()
+        };
+        extract(&archive, "tests_decompressed/malicious_symlink_skip", skip)
+            .await
+            .unwrap();
+        assert!(!Path::new("tests_decompressed/malicious_symlink_skip/config/link").is_symlink());
+    }
+
+    #[tokio::test]
+    async fn test_zip_symlink() {
+        let archive = write_fixture(
+            "malicious_symlink.zip",
+            zip_with(|zip| {
+                zip.add_symlink("passwd", "/etc/passwd", SimpleFileOptions::default())
+                    .unwrap();
+            }),
+        );
+
+        let result = decompress(
+            &archive,
+            Path::new("tests_decompressed/malicious_symlink_zip"),
+        )
+        .await;
+        assert!(matches!(result, Err(CompressionError::UnsafePath(_))));
+        assert!(!Path::new("tests_decompressed/malicious_symlink_zip/passwd").exists());
+    }
+
+    #[tokio::test]
+    async fn test_hardlink_outside() {
+        let archive = write_fixture(
+            "malicious_hardlink.tar",
+            tar_with(|tar| {
+                let mut header = tar::Header::new_gnu();
+                header.set_entry_type(tar::EntryType::Link);
+                header.set_size(0);
+                header.set_mode(0o644);
+                tar.append_link(&mut header, "passwd", "/etc/passwd")
+                    .unwrap();
+            }),
+        );
+
+        let result = decompress(&archive, Path::new("tests_decompressed/malicious_hardlink")).await;
+        assert!(matches!(result, Err(CompressionError::UnsafePath(_))));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_setuid_is_dropped() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let archive = write_fixture(
+            "malicious_setuid.tar",
+            tar_with(|tar| {
+                let mut header = tar::Header::new_gnu();
+                header.set_size(2);
+                header.set_mode(0o4755);
+                tar.append_data(&mut header, "run.sh", &b"id"[..]).unwrap();
+            }),
+        );
+
+        let dest = Path::new("tests_decompressed/malicious_setuid");
+        decompress(&archive, dest).await.unwrap();
+
+        let mode = std::fs::metadata(dest.join("run.sh"))
+            .unwrap()
+            .permissions()
+            .mode();
+        assert_eq!(mode & 0o7777, 0o755);
+    }
+
+    #[tokio::test]
+    async fn test_compression_bomb() {
+        let archive = write_fixture(
+            "malicious_bomb.zip",
+            zip_with(|zip| {
+                let options = SimpleFileOptions::default()
+                    .compression_method(zip::CompressionMethod::Deflated)
+                    .large_file(false);
+                zip.start_file("zeros.bin", options).unwrap();
+                zip.write_all(&vec![0; 32 * 1024 * 1024]).unwrap();
+            }),
+        );
+
+        let result = decompress(&archive, Path::new("tests_decompressed/malicious_bomb")).await;
+        assert!(matches!(
+            result,
+            Err(CompressionError::CompressionRatioExceeded(100))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_size_and_count_limits() {
+        let archive = Path::new("fixtures/compressed.zip");
+
+        let policy = ExtractionPolicy {
+            max_file_count: Some(1),
+            ..ExtractionPolicy::default()
+        };
+        let result = extract(archive, "tests_decompressed/limit_count", policy).await;
+        assert!(matches!(result, Err(CompressionError::TooManyFiles(1))));
+
+        let policy = ExtractionPolicy {
+            max_total_size: Some(10),
+            ..ExtractionPolicy::default()
+        };
+        let result = extract(archive, "tests_decompressed/limit_size", policy).await;
+        assert!(matches!(
+            result,
+            Err(CompressionError::SizeLimitExceeded(10))
+        ));
+    }
+}
@@ -21,6 +21,9 @@ use tokio::sync::{Mutex, Semaphore};
 use tokio_util::sync::CancellationToken;
 use tracing::{error, info, instrument, warn};
 
+mod limits;
+pub use limits::{BandwidthLimiter, HostLimits};
+
 const PART_POSTFIX: &str = ".__gdl_part~";
 
 #[derive(Error, Debug)]
@@ -58,6 +61,8 @@ pub enum DownloadError {
     CannotCreateDirectory(String),
     #[error("Failed to atomically move file: {0}")]
     CannotMoveFile(std::io::Error, String, String),
+    #[error("Server resumed from byte {actual:?} instead of {expected}")]
+    UnexpectedContentRange { expected: u64, actual: Option<u64> },
 }
 
 #[derive(Debug, Clone)]
@@ -123,6 +128,10 @@ pub struct Downloadable {
     pub path: PathBuf,
     pub checksum: Option<Checksum>,
     pub size: Option<u64>,
+    /// Fallback urls serving the same file, tried in order when `url` fails.
+    pub mirrors: Vec<String>,
+    /// Extra headers sent to `url` only, such as credentials for a private repository.
+    pub headers: reqwest::header::HeaderMap,
 }
 
 impl Display for Downloadable {
@@ -138,6 +147,8 @@ impl Downloadable {
             path: path.as_ref().into(),
             checksum: None,
             size: None,
+            mirrors: vec![],
+            headers: reqwest::header::HeaderMap::new(),
         }
     }
 
@@ -150,6 +161,55 @@ impl Downloadable {
         self.size = Some(size);
         self
     }
+
+    pub fn with_mirrors<I, S>(mut self, mirrors: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.mirrors.extend(mirrors.into_iter().map(Into::into));
+        self
+    }
+
+    /// Headers are marked sensitive, keeping them out of logs.
+    pub fn with_headers(mut self, mut headers: reqwest::header::HeaderMap) -> Self {
+        headers
+            .values_mut()
+            .for_each(|value| value.set_sensitive(true));
+
+        self.headers.extend(headers);
+        self
+    }
+
+    /// The primary url followed by its mirrors.
+    pub fn urls(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(self.url.as_str()).chain(self.mirrors.iter().map(String::as_str))
+    }
+}
+
+/// Records the url each file was served from, keyed by the file's path.
+#[derive(Debug, Default, Clone)]
+pub struct ServedUrls(Arc<std::sync::Mutex<HashMap<PathBuf, String>>>);
+
+impl ServedUrls {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, path: &Path) -> Option<String> {
+        self.0
+            .lock()
+            .expect("served urls lock poisoned")
+            .get(path)
+            .cloned()
+    }
+
+    fn record(&self, path: &Path, url: &str) {
+        self.0
+            .lock()
+            .expect("served urls lock poisoned")
+            .insert(path.to_path_buf(), url.to_string());
+    }
 }
 
 #[derive(Debug, Default, Clone)]
@@ -194,6 +254,9 @@ pub struct DownloadOptions {
     deep_check: bool,
     max_retries: u32,
     progress_sender: Option<Sender<Progress>>,
+    served_urls: Option<ServedUrls>,
+    bandwidth_limiter: BandwidthLimiter,
+    host_limits: HostLimits,
 }
 
 impl Default for DownloadOptions {
@@ -205,6 +268,9 @@ impl Default for DownloadOptions {
             deep_check: true,
             max_retries: 3,
             progress_sender: None,
+            served_urls: None,
+            bandwidth_limiter: BandwidthLimiter::default(),
+            host_limits: HostLimits::default(),
         }
     }
 }
@@ -223,6 +289,9 @@ pub struct DownloadOptionsBuilder {
     deep_check: Option<bool>,
     max_retries: Option<u32>,
     progress_sender: Option<Sender<Progress>>,
+    served_urls: Option<ServedUrls>,
+    bandwidth_limiter: Option<BandwidthLimiter>,
+    host_limits: Option<HostLimits>,
 }
 
 impl DownloadOptionsBuilder {
@@ -256,6 +325,25 @@ impl DownloadOptionsBuilder {
         self
     }
 
+    /// Filled with the url every downloaded file was served from, which
+    /// differs from [`Downloadable::url`] when a mirror had to be used.
+    pub fn served_urls(mut self, served_urls: ServedUrls) -> Self {
+        self.served_urls = Some(served_urls);
+        self
+    }
+
+    /// Shared with every other download using the same limiter, `concurrency`
+    /// only applies to this call.
+    pub fn bandwidth_limiter(mut self, bandwidth_limiter: BandwidthLimiter) -> Self {
+        self.bandwidth_limiter = Some(bandwidth_limiter);
+        self
+    }
+
+    pub fn host_limits(mut self, host_limits: HostLimits) -> Self {
+        self.host_limits = Some(host_limits);
+        self
+    }
+
     pub fn build(self) -> DownloadOptions {
         DownloadOptions {
             concurrency: self.concurrency.unwrap_or(4),
@@ -264,6 +352,9 @@ impl DownloadOptionsBuilder {
             deep_check: self.deep_check.unwrap_or(false),
             max_retries: self.max_retries.unwrap_or(3),
             progress_sender: self.progress_sender,
+            served_urls: self.served_urls,
+            bandwidth_limiter: self.bandwidth_limiter.unwrap_or_default(),
+            host_limits: self.host_limits.unwrap_or_default(),
         }
     }
 }
@@ -454,168 +545,210 @@ async fn download_file(
     let file_processed_bytes = AtomicU64::new(0);
 
     let progress = options.progress_sender.as_ref();
+    let send_progress = || {
+        if let Some(sender) = progress {
+            let _ = sender.send(Progress {
+                total_count: total_files_count,
+                current_count: current_files_count.load(Ordering::SeqCst),
+                total_size: total_files_size,
+                current_size: total_downloaded_size.load(Ordering::SeqCst),
+            });
+        }
+    };
 
-    let (part_file_path, mut file, headers, mut hasher, was_resumed) = prepare_download(
-        &downloadable,
-        false,
-        total_downloaded_size,
-        &file_processed_bytes,
-        current_files_count,
-        total_files_size,
-        total_files_count,
-        progress,
-    )
-    .await?;
-
-    let outcome = _download_file(
-        &downloadable.url,
-        headers,
-        &part_file_path,
-        &mut file,
-        &mut hasher,
-        &downloadable,
-        options.clone(),
-        client.clone(),
-        total_downloaded_size,
-        &file_processed_bytes,
-        current_files_count,
-        total_files_size,
-        total_files_count,
-    )
-    .await;
+    let urls = downloadable.urls().collect::<Vec<_>>();
+    let mut mirror = 0;
+    let mut force_overwrite = false;
+    let mut retries = 0;
+
+    loop {
+        let url = urls[mirror];
+
+        // released at the end of every attempt, a mirror may be another host
+        let _host_permit = options.host_limits.acquire(url).await?;
+
+        let (part_file_path, mut file, mut headers, mut hasher, was_resumed) = prepare_download(
+            &downloadable,
+            force_overwrite,
+            total_downloaded_size,
+            &file_processed_bytes,
+            current_files_count,
+            total_files_size,
+            total_files_count,
+            progress,
+        )
+        .await?;
 
-    if let Some(sender) = progress {
-        let _ = sender.send(Progress {
-            total_count: total_files_count,
-            current_count: current_files_count.load(Ordering::SeqCst),
-            total_size: total_files_size,
-            current_size: total_downloaded_size.load(Ordering::SeqCst),
-        });
-    }
+        // mirrors may be other hosts, they never get the primary url's credentials
+        if mirror == 0 {
+            headers.extend(downloadable.headers.clone());
+        }
 
-    match outcome {
-        Ok(()) => {
-            tokio::fs::rename(&part_file_path, &downloadable.path)
-                .await
-                .map_err(|e| {
-                    DownloadError::CannotMoveFile(
-                        e,
-                        part_file_path.to_string_lossy().to_string(),
-                        downloadable.path.to_string_lossy().to_string(),
-                    )
-                })?;
+        // without range support a part file can't be picked up again
+        let mut accepts_ranges = false;
+
+        let outcome = _download_file(
+            url,
+            headers,
+            &part_file_path,
+            &mut file,
+            &mut hasher,
+            &downloadable,
+            options.clone(),
+            client.clone(),
+            total_downloaded_size,
+            &file_processed_bytes,
+            current_files_count,
+            total_files_size,
+            total_files_count,
+            &mut accepts_ranges,
+        )
+        .await;
+
+        // make sure the part file is complete before reopening or moving it
+        file.flush().await?;
+        drop(file);
+
+        send_progress();
+
+        match outcome {
+            Ok(()) => {
+                tokio::fs::rename(&part_file_path, &downloadable.path)
+                    .await
+                    .map_err(|e| {
+                        DownloadError::CannotMoveFile(
+                            e,
+                            part_file_path.to_string_lossy().to_string(),
+                            downloadable.path.to_string_lossy().to_string(),
+                        )
+                    })?;
+
+                if mirror > 0 {
+                    info!("{} was served by mirror {url}", downloadable.path.display());
+                }
 
-            current_files_count.fetch_add(1, Ordering::SeqCst);
-            if let Some(sender) = progress {
-                let _ = sender.send(Progress {
-                    total_count: total_files_count,
-                    current_count: current_files_count.load(Ordering::SeqCst),
-                    total_size: total_files_size,
-                    current_size: total_downloaded_size.load(Ordering::SeqCst),
-                });
-            }
+                if let Some(served_urls) = &options.served_urls {
+                    served_urls.record(&downloadable.path, url);
+                }
 
-            Ok(())
-        }
-        Err(DownloadError::ChecksumMismatch { .. }) | Err(DownloadError::SizeMismatch { .. })
-            if was_resumed =>
-        {
-            tracing::warn!("Download was resumed, but checksum or size mismatched");
+                current_files_count.fetch_add(1, Ordering::SeqCst);
+                send_progress();
 
-            total_downloaded_size.fetch_sub(
-                file_processed_bytes.load(Ordering::SeqCst),
-                Ordering::SeqCst,
-            );
+                return Ok(());
+            }
+            Err(e) if was_resumed && is_stale_part_error(&e) => {
+                warn!("Download was resumed, but the part file is unusable, starting over: {e}");
 
-            file_processed_bytes.store(0, Ordering::SeqCst);
+                // the next attempt accounts for the part file from scratch
+                total_downloaded_size.fetch_sub(
+                    file_processed_bytes.swap(0, Ordering::SeqCst),
+                    Ordering::SeqCst,
+                );
+                send_progress();
 
-            if let Some(sender) = progress {
-                let _ = sender.send(Progress {
-                    total_count: total_files_count,
-                    current_count: current_files_count.load(Ordering::SeqCst),
-                    total_size: total_files_size,
-                    current_size: total_downloaded_size.load(Ordering::SeqCst),
-                });
+                force_overwrite = true;
             }
+            Err(e)
+                if accepts_ranges && is_interrupted_error(&e) && retries < options.max_retries =>
+            {
+                retries += 1;
+                warn!(
+                    "Download of {} interrupted, resuming ({retries}/{}): {e}",
+                    downloadable.url, options.max_retries
+                );
 
-            let (part_file_path, mut file, headers, mut hasher, _) = prepare_download(
-                &downloadable,
-                true,
-                total_downloaded_size,
-                &file_processed_bytes,
-                current_files_count,
-                total_files_size,
-                total_files_count,
-                progress,
-            )
-            .await?;
-
-            let outcome = _download_file(
-                &downloadable.url,
-                headers,
-                &part_file_path,
-                &mut file,
-                &mut hasher,
-                &downloadable,
-                options.clone(),
-                client,
-                total_downloaded_size,
-                &file_processed_bytes,
-                current_files_count,
-                total_files_size,
-                total_files_count,
-            )
-            .await;
+                total_downloaded_size.fetch_sub(
+                    file_processed_bytes.swap(0, Ordering::SeqCst),
+                    Ordering::SeqCst,
+                );
+                send_progress();
 
-            match outcome {
-                Ok(()) => {
-                    tokio::fs::rename(&part_file_path, &downloadable.path)
-                        .await
-                        .map_err(|e| {
-                            DownloadError::CannotMoveFile(
-                                e,
-                                part_file_path.to_string_lossy().to_string(),
-                                downloadable.path.to_string_lossy().to_string(),
-                            )
-                        })?;
-
-                    current_files_count.fetch_add(1, Ordering::SeqCst);
-                    if let Some(sender) = progress {
-                        let _ = sender.send(Progress {
-                            total_count: total_files_count,
-                            current_count: current_files_count.load(Ordering::SeqCst),
-                            total_size: total_files_size,
-                            current_size: total_downloaded_size.load(Ordering::SeqCst),
-                        });
-                    }
+                force_overwrite = false;
+            }
+            Err(DownloadError::Cancelled) if accepts_ranges => {
+                info!(
+                    "Download of {} cancelled, keeping {} for a later resume",
+                    downloadable.url,
+                    part_file_path.display()
+                );
+                return Err(DownloadError::Cancelled);
+            }
+            Err(e) if mirror + 1 < urls.len() && is_mirror_fallback_error(&e) => {
+                mirror += 1;
+                warn!(
+                    "Download from {url} failed, trying mirror {}: {e}",
+                    urls[mirror]
+                );
 
-                    Ok(())
-                }
-                Err(e) => {
-                    error!(
-                        "Error downloading file {} - {}: {:?}",
-                        part_file_path.to_string_lossy().to_string(),
-                        downloadable.path.to_string_lossy().to_string(),
-                        e
-                    );
-                    remove_file(&part_file_path).await?;
-                    Err(e)
-                }
+                total_downloaded_size.fetch_sub(
+                    file_processed_bytes.swap(0, Ordering::SeqCst),
+                    Ordering::SeqCst,
+                );
+                send_progress();
+
+                // mirrors serve the same bytes, but only a checksum can tell
+                // if the part file is a mix of different files
+                force_overwrite = !(accepts_ranges
+                    && is_interrupted_error(&e)
+                    && downloadable.checksum.is_some());
+                retries = 0;
+            }
+            Err(e) => {
+                error!(
+                    "Error downloading file {} - {}: {:?}",
+                    part_file_path.to_string_lossy().to_string(),
+                    downloadable.path.to_string_lossy().to_string(),
+                    e
+                );
+                remove_file(&part_file_path).await?;
+                return Err(e);
             }
-        }
-        Err(e) => {
-            error!(
-                "Error downloading file {} - {}: {:?}",
-                part_file_path.to_string_lossy().to_string(),
-                downloadable.path.to_string_lossy().to_string(),
-                e
-            );
-            remove_file(&part_file_path).await?;
-            Err(e)
         }
     }
 }
 
+/// Errors caused by a part file that doesn't match the remote file anymore,
+/// downloading from scratch gets rid of them.
+fn is_stale_part_error(err: &DownloadError) -> bool {
+    matches!(
+        err,
+        DownloadError::ChecksumMismatch { .. }
+            | DownloadError::SizeMismatch { .. }
+            | DownloadError::UnexpectedContentRange { .. }
+            | DownloadError::Non200StatusCode(_, 416)
+    )
+}
+
+/// Connection failures, which can be resumed from the part file.
+fn is_interrupted_error(err: &DownloadError) -> bool {
+    matches!(
+        err,
+        DownloadError::NetworkError(_) | DownloadError::NetworkError2(_)
+    )
+}
+
+/// Errors another mirror may not run into.
+fn is_mirror_fallback_error(err: &DownloadError) -> bool {
+    matches!(err, DownloadError::Non200StatusCode(..))
+        || is_interrupted_error(err)
+        || is_stale_part_error(err)
+}
+
+/// Start offset of a `Content-Range: bytes <start>-<end>/<size>` header.
+fn content_range_start(response: &reqwest::Response) -> Option<u64> {
+    response
+        .headers()
+        .get(reqwest::header::CONTENT_RANGE)?
+        .to_str()
+        .ok()?
+        .strip_prefix("bytes ")?
+        .split_once('-')?
+        .0
+        .trim()
+        .parse()
+        .ok()
+}
+
 #[instrument(skip(options, client, hasher, total_downloaded_size, current_count))]
 async fn _download_file(
     url: &str,
@@ -631,11 +764,42 @@ async fn _download_file(
     current_count: &AtomicU64,
     total_files_size: u64,
     total_files_count: u64,
+    accepts_ranges: &mut bool,
 ) -> Result<(), DownloadError> {
+    let resumed_from = file_processed_bytes.load(Ordering::SeqCst);
     let mut response = client.get(url).headers(headers).send().await?;
 
     check_response_status(&response, &downloadable)?;
 
+    let is_partial = response.status() == reqwest::StatusCode::PARTIAL_CONTENT;
+    *accepts_ranges = is_partial
+        || response
+            .headers()
+            .get(reqwest::header::ACCEPT_RANGES)
+            .is_some_and(|value| value.as_bytes().eq_ignore_ascii_case(b"bytes"));
+
+    if resumed_from > 0 {
+        if is_partial {
+            let start = content_range_start(&response);
+            if start != Some(resumed_from) {
+                return Err(DownloadError::UnexpectedContentRange {
+                    expected: resumed_from,
+                    actual: start,
+                });
+            }
+        } else {
+            warn!(
+                "Server ignored the range request for {}, downloading from scratch",
+                downloadable.url
+            );
+
+            file.set_len(0).await?;
+            *hasher = downloadable.checksum.as_ref().map(HashDigest::from);
+            total_downloaded_size.fetch_sub(resumed_from, Ordering::SeqCst);
+            file_processed_bytes.store(0, Ordering::SeqCst);
+        }
+    }
+
     download_content(
         &mut response,
         file,
@@ -677,7 +841,7 @@ async fn _download_file(
             return Err(DownloadError::ChecksumMismatch {
                 expected: expected_hash.clone(),
                 actual: actual_hash,
-                url: downloadable.url.clone(),
+                url: url.to_string(),
                 path: downloadable.path.to_string_lossy().to_string(),
             });
         }
@@ -805,7 +969,11 @@ fn check_response_status(
     {
         error!("Failed to download file: HTTP {}", response.status());
         return Err(DownloadError::Non200StatusCode(
-            file.clone(),
+            // report the mirror that actually failed
+            Downloadable {
+                url: response.url().to_string(),
+                ..file.clone()
+            },
             response.status().as_u16(),
         ));
     }
@@ -830,6 +998,14 @@ async fn download_content(
             return Err(DownloadError::Cancelled);
         }
 
+        tokio::select! {
+            _ = options.bandwidth_limiter.consume(chunk.len()) => {}
+            _ = options.cancel_token.cancelled() => {
+                warn!("Download cancelled");
+                return Err(DownloadError::Cancelled);
+            }
+        }
+
         file.write_all(&chunk).await?;
 
         if let Some(hasher) = hasher.as_mut() {
@@ -1010,6 +1186,8 @@ mod tests {
                 "dffd6021bb2bd5b0af676290809ec3a53191dd81c7f70a4b28688a362182986f".to_string(),
             )),
             size: Some(13),
+            mirrors: vec![],
+            headers: reqwest::header::HeaderMap::new(),
         };
 
         let downloadable1 = Downloadable {
@@ -1019,6 +1197,8 @@ mod tests {
                 "dffd6021bb2bd5b0af676290809ec3a53191dd81c7f70a4b28688a362182986f".to_string(),
             )),
             size: Some(13),
+            mirrors: vec![],
+            headers: reqwest::header::HeaderMap::new(),
         };
 
         let options = DownloadOptions::builder().concurrency(2).build();
@@ -1056,6 +1236,8 @@ mod tests {
             path: file_path,
             checksum: None,
             size: None,
+            mirrors: vec![],
+            headers: reqwest::header::HeaderMap::new(),
         };
 
         let options = DownloadOptions::builder()
@@ -1096,6 +1278,8 @@ mod tests {
             path: file_path,
             checksum: None,
             size: Some(13),
+            mirrors: vec![],
+            headers: reqwest::header::HeaderMap::new(),
         };
 
         let cancel_token = CancellationToken::new();
@@ -1142,6 +1326,8 @@ mod tests {
             path: file_path.clone(),
             checksum: None,
             size: None,
+            mirrors: vec![],
+            headers: reqwest::header::HeaderMap::new(),
         };
 
         let downloadable2 = Downloadable {
@@ -1149,6 +1335,8 @@ mod tests {
             path: file_path.clone(),
             checksum: None,
             size: None,
+            mirrors: vec![],
+            headers: reqwest::header::HeaderMap::new(),
         };
 
         let options = DownloadOptions::builder().concurrency(2).build();
@@ -1249,6 +1437,8 @@ mod tests {
                 "dffd6021bb2bd5b0af676290809ec3a53191dd81c7f70a4b28688a362182986f".to_string(),
             )),
             size: Some(13),
+            mirrors: vec![],
+            headers: reqwest::header::HeaderMap::new(),
         };
 
         let options = DownloadOptions::builder().concurrency(1).build();
@@ -1308,6 +1498,8 @@ mod tests {
                 "dffd6021bb2bd5b0af676290809ec3a53191dd81c7f70a4b28688a362182986f".to_string(),
             )),
             size: Some(13),
+            mirrors: vec![],
+            headers: reqwest::header::HeaderMap::new(),
         };
 
         let options = DownloadOptions::builder()
@@ -1386,6 +1578,8 @@ mod tests {
                 "83cfe479c149983ad66bb9c0e4b47cf2870e33c6".to_string(),
             )),
             size: Some(15),
+            mirrors: vec![],
+            headers: reqwest::header::HeaderMap::new(),
         };
 
         let downloadable2 = Downloadable {
@@ -1395,6 +1589,8 @@ mod tests {
                 "1aefd92d29317a3119827259177ce85329da746818baf6682d1d455ade4263fd".to_string(),
             )),
             size: Some(15),
+            mirrors: vec![],
+            headers: reqwest::header::HeaderMap::new(),
         };
 
         let downloadable3 = Downloadable {
@@ -1404,6 +1600,8 @@ mod tests {
                 "216a3bcccd4c577dcf40e58f19f31367".to_string(),
             )),
             size: Some(15),
+            mirrors: vec![],
+            headers: reqwest::header::HeaderMap::new(),
         };
 
         let options = DownloadOptions::builder().concurrency(2).build();
@@ -1450,6 +1648,8 @@ mod tests {
             path: file_path,
             checksum: None,
             size: None,
+            mirrors: vec![],
+            headers: reqwest::header::HeaderMap::new(),
         };
 
         let options = DownloadOptions::builder().concurrency(1).build();
@@ -1463,6 +1663,161 @@ mod tests {
         mock.assert();
     }
 
+    #[tokio::test]
+    #[traced_test]
+    async fn test_download_mirror_fallback() {
+        let mut server = mockito::Server::new_async().await;
+        let mock_url = server.url();
+
+        let primary = server
+            .mock("GET", "/primary/test.txt")
+            .with_status(404)
+            .expect(1)
+            .create_async()
+            .await;
+
+        // serves a different file, caught by the checksum
+        let stale_mirror = server
+            .mock("GET", "/stale/test.txt")
+            .with_status(200)
+            .with_body("Hello, Mars!!")
+            .expect(1)
+            .create_async()
+            .await;
+
+        let mirror = server
+            .mock("GET", "/mirror/test.txt")
+            .with_status(200)
+            .with_body("Hello, World!")
+            .expect(1)
+            .create_async()
+            .await;
+
+        let temp_dir = tempdir().unwrap();
+        let file_path = temp_dir.path().join("test.txt");
+
+        let downloadable = Downloadable::new(format!("{mock_url}/primary/test.txt"), &file_path)
+            .with_checksum(Some(Checksum::Sha256(
+                "dffd6021bb2bd5b0af676290809ec3a53191dd81c7f70a4b28688a362182986f".to_string(),
+            )))
+            .with_size(13)
+            .with_mirrors([
+                format!("{mock_url}/stale/test.txt"),
+                format!("{mock_url}/mirror/test.txt"),
+            ]);
+
+        let served_urls = ServedUrls::new();
+        let options = DownloadOptions::builder()
+            .served_urls(served_urls.clone())
+            .build();
+
+        download_multiple(&[downloadable], options).await.unwrap();
+
+        assert_eq!(
+            std::fs::read_to_string(&file_path).unwrap(),
+            "Hello, World!"
+        );
+        assert_eq!(
+            served_urls.get(&file_path),
+            Some(format!("{mock_url}/mirror/test.txt"))
+        );
+
+        primary.assert();
+        stale_mirror.assert();
+        mirror.assert();
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_download_headers_not_sent_to_mirrors() {
+        let mut server = mockito::Server::new_async().await;
+        let mock_url = server.url();
+
+        let primary = server
+            .mock("GET", "/primary/test.txt")
+            .match_header("authorization", "Bearer secret")
+            .with_status(404)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let mirror = server
+            .mock("GET", "/mirror/test.txt")
+            .match_header("authorization", mockito::Matcher::Missing)
+            .with_status(200)
+            .with_body("Hello, World!")
+            .expect(1)
+            .create_async()
+            .await;
+
+        let temp_dir = tempdir().unwrap();
+        let file_path = temp_dir.path().join("test.txt");
+
+        let mut headers = reqwest::header::HeaderMap::new();
+        headers.insert(
+            reqwest::header::AUTHORIZATION,
+            reqwest::header::HeaderValue::from_static("Bearer secret"),
+        );
+
+        let downloadable = Downloadable::new(format!("{mock_url}/primary/test.txt"), &file_path)
+            .with_size(13)
+            .with_headers(headers)
+            .with_mirrors([format!("{mock_url}/mirror/test.txt")]);
+
+        assert!(!format!("{downloadable:?}").contains("secret"));
+
+        let options = DownloadOptions::builder().build();
+        download_multiple(&[downloadable], options).await.unwrap();
+
+        assert_eq!(
+            std::fs::read_to_string(&file_path).unwrap(),
+            "Hello, World!"
+        );
+
+        primary.assert();
+        mirror.assert();
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_download_all_mirrors_failing() {
+        let mut server = mockito::Server::new_async().await;
+        let mock_url = server.url();
+
+        let primary = server
+            .mock("GET", "/primary/test.txt")
+            .with_status(404)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let mirror = server
+            .mock("GET", "/mirror/test.txt")
+            .with_status(403)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let temp_dir = tempdir().unwrap();
+        let file_path = temp_dir.path().join("test.txt");
+
+        let downloadable = Downloadable::new(format!("{mock_url}/primary/test.txt"), &file_path)
+            .with_mirrors([format!("{mock_url}/mirror/test.txt")]);
+
+        let options = DownloadOptions::builder().build();
+
+        let failed = match download_multiple(&[downloadable], options).await {
+            Err(DownloadError::Non200StatusCode(failed, 403)) => failed,
+            other => panic!("expected the last mirror to fail, got {other:?}"),
+        };
+
+        assert_eq!(failed.url, format!("{mock_url}/mirror/test.txt"));
+        assert!(!file_path.exists());
+
+        primary.assert();
+        mirror.assert();
+    }
+
     #[tokio::test]
     #[traced_test]
     async fn test_download_file_with_redirect() {
@@ -1491,6 +1846,8 @@ mod tests {
             path: file_path.clone(),
             checksum: None,
             size: None,
+            mirrors: vec![],
+            headers: reqwest::header::HeaderMap::new(),
         };
 
         let options = DownloadOptions::builder().concurrency(1).build();
@@ -1532,6 +1889,8 @@ mod tests {
             path: file_path.clone(),
             checksum: None,
             size: Some(LARGE_FILE_SIZE as u64),
+            mirrors: vec![],
+            headers: reqwest::header::HeaderMap::new(),
         };
 
         let options = DownloadOptions::builder().concurrency(1).build();
@@ -1576,6 +1935,8 @@ mod tests {
             path: file_path1.clone(),
             checksum: None,
             size: Some(13),
+            mirrors: vec![],
+            headers: reqwest::header::HeaderMap::new(),
         };
 
         let downloadable2 = Downloadable {
@@ -1583,6 +1944,8 @@ mod tests {
             path: file_path2.clone(),
             checksum: None,
             size: Some(15),
+            mirrors: vec![],
+            headers: reqwest::header::HeaderMap::new(),
         };
 
         let (progress_tx, mut progress_rx) = watch::channel(Progress::default());
@@ -1665,6 +2028,8 @@ mod tests {
             path: file_path.clone(),
             checksum: None,
             size: Some(13),
+            mirrors: vec![],
+            headers: reqwest::header::HeaderMap::new(),
         };
 
         let (progress_tx, mut progress_rx) = watch::channel(Progress::default());
@@ -1722,6 +2087,8 @@ mod tests {
             path: file_path,
             checksum: None,
             size: Some(1_000_000_000),
+            mirrors: vec![],
+            headers: reqwest::header::HeaderMap::new(),
         };
 
         let (progress_tx, mut progress_rx) = watch::channel(Progress::default());
@@ -1765,4 +2132,342 @@ mod tests {
 
         mock.assert();
     }
+
+    /// Stand-in for a CDN serving `body` in `chunk_size` pieces, honoring
+    /// `Range` headers only if `accept_ranges` is set.
+    #[derive(Clone)]
+    struct StandInServer {
+        body: Arc<Vec<u8>>,
+        accept_ranges: bool,
+        chunk_size: usize,
+        delay: Duration,
+        /// Drops the connection after this many bytes of the first response.
+        cut_after: Option<usize>,
+        ranges: Arc<std::sync::Mutex<Vec<Option<String>>>>,
+        open_connections: Arc<AtomicU64>,
+        peak_connections: Arc<AtomicU64>,
+    }
+
+    /// Counts a response as open until its body is dropped.
+    struct OpenConnection(Arc<AtomicU64>);
+
+    impl Drop for OpenConnection {
+        fn drop(&mut self) {
+            self.0.fetch_sub(1, Ordering::SeqCst);
+        }
+    }
+
+    impl StandInServer {
+        fn new(accept_ranges: bool) -> Self {
+            Self {
+                body: Arc::new((0..64 * 1024).map(|i| (i % 251) as u8).collect()),
+                accept_ranges,
+                chunk_size: 4096,
+                delay: Duration::from_millis(1),
+                cut_after: None,
+                ranges: Arc::default(),
+                open_connections: Arc::default(),
+                peak_connections: Arc::default(),
+            }
+        }
+
+        fn sha1(&self) -> Checksum {
+            use sha1::Digest;
+            Checksum::Sha1(hex::encode(Sha1::digest(self.body.as_slice())))
+        }
+
+        fn ranges(&self) -> Vec<Option<String>> {
+            self.ranges.lock().unwrap().clone()
+        }
+
+        async fn start(&self) -> String {
+            let app = axum::Router::new()
+                .route("/file", axum::routing::get(serve_stand_in))
+                .with_state(self.clone());
+
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let addr = listener.local_addr().unwrap();
+            tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+            format!("http://{addr}/file")
+        }
+    }
+
+    async fn serve_stand_in(
+        axum::extract::State(server): axum::extract::State<StandInServer>,
+        headers: axum::http::HeaderMap,
+    ) -> axum::response::Response {
+        use axum::http::{header, StatusCode};
+        use futures::StreamExt;
+
+        let range = headers
+            .get(header::RANGE)
+            .and_then(|value| value.to_str().ok())
+            .map(ToString::to_string);
+
+        let is_first_request = {
+            let mut ranges = server.ranges.lock().unwrap();
+            ranges.push(range.clone());
+            ranges.len() == 1
+        };
+
+        let start = range
+            .filter(|_| server.accept_ranges)
+            .and_then(|range| {
+                range
+                    .strip_prefix("bytes=")?
+                    .strip_suffix('-')?
+                    .parse()
+                    .ok()
+            })
+            .unwrap_or(0);
+
+        let body = &server.body[start..];
+        let cut_after = server.cut_after.filter(|_| is_first_request);
+
+        let mut chunks = Vec::new();
+        let mut sent = 0;
+        for chunk in body.chunks(server.chunk_size) {
+            if cut_after.is_some_and(|cut_after| sent >= cut_after) {
+                chunks.push(Err(std::io::Error::new(
+                    std::io::ErrorKind::ConnectionReset,
+                    "connection dropped",
+                )));
+                break;
+            }
+
+            sent += chunk.len();
+            chunks.push(Ok(chunk.to_vec()));
+        }
+
+        let open = server.open_connections.fetch_add(1, Ordering::SeqCst) + 1;
+        server.peak_connections.fetch_max(open, Ordering::SeqCst);
+        let connection = Arc::new(std::sync::Mutex::new(Some(OpenConnection(Arc::clone(
+            &server.open_connections,
+        )))));
+
+        let delay = server.delay;
+        let chunk_count = chunks.len();
+        let stream =
+            futures::stream::iter(chunks.into_iter().enumerate()).then(move |(i, chunk)| {
+                let connection = Arc::clone(&connection);
+                async move {
+                    tokio::time::sleep(delay).await;
+
+                    // closed before the client can see the last byte
+                    if i + 1 == chunk_count {
+                        connection.lock().unwrap().take();
+                    }
+
+                    chunk
+                }
+            });
+
+        let mut response =
+            axum::response::Response::builder().header(header::CONTENT_LENGTH, body.len());
+
+        if server.accept_ranges {
+            response = response.header(header::ACCEPT_RANGES, "bytes");
+        }
+
+        if start > 0 {
+            let total = server.body.len();
+            response = response.status(StatusCode::PARTIAL_CONTENT).header(
+                header::CONTENT_RANGE,
+                format!("bytes {start}-{}/{total}", total - 1),
+            );
+        }
+
+        response
+            .body(axum::body::Body::from_stream(stream))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_resume_after_connection_drop() {
+        let server = StandInServer {
+            cut_after: Some(16 * 1024),
+            ..StandInServer::new(true)
+        };
+        let url = server.start().await;
+
+        let temp_dir = tempdir().unwrap();
+        let file_path = temp_dir.path().join("pack.zip");
+
+        let downloadable = Downloadable::new(url, &file_path)
+            .with_checksum(Some(server.sha1()))
+            .with_size(server.body.len() as u64);
+
+        let (progress_tx, progress_rx) = watch::channel(Progress::default());
+        let options = DownloadOptions::builder()
+            .progress_sender(progress_tx)
+            .build();
+
+        download_multiple(&[downloadable], options).await.unwrap();
+
+        assert_eq!(std::fs::read(&file_path).unwrap(), *server.body);
+        assert_eq!(
+            server.ranges(),
+            [None, Some(format!("bytes={}-", 16 * 1024))]
+        );
+        assert_eq!(progress_rx.borrow().current_size, server.body.len() as u64);
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_resume_after_cancellation() {
+        let server = StandInServer {
+            delay: Duration::from_millis(50),
+            ..StandInServer::new(true)
+        };
+        let url = server.start().await;
+
+        let temp_dir = tempdir().unwrap();
+        let file_path = temp_dir.path().join("pack.zip");
+        let part_path = temp_dir.path().join(format!("pack.zip{}", PART_POSTFIX));
+
+        let downloadable = Downloadable::new(url, &file_path)
+            .with_checksum(Some(server.sha1()))
+            .with_size(server.body.len() as u64);
+
+        let cancel_token = CancellationToken::new();
+        let (progress_tx, mut progress_rx) = watch::channel(Progress::default());
+        let options = DownloadOptions::builder()
+            .cancel_token(cancel_token.clone())
+            .progress_sender(progress_tx)
+            .build();
+
+        let files = [downloadable.clone()];
+        let handle = tokio::spawn(async move { download_multiple(&files, options).await });
+
+        progress_rx
+            .wait_for(|progress| progress.current_size > 0)
+            .await
+            .unwrap();
+        cancel_token.cancel();
+
+        let result = handle.await.unwrap();
+        assert!(matches!(result, Err(DownloadError::Cancelled)));
+
+        let part_size = std::fs::metadata(&part_path).unwrap().len();
+        assert!(part_size > 0 && part_size < server.body.len() as u64);
+
+        let options = DownloadOptions::builder().build();
+        download_multiple(&[downloadable], options).await.unwrap();
+
+        assert_eq!(std::fs::read(&file_path).unwrap(), *server.body);
+        assert!(!part_path.exists());
+        assert_eq!(
+            server.ranges().last().unwrap().as_deref(),
+            Some(format!("bytes={part_size}-").as_str())
+        );
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_resume_with_range_ignored() {
+        let server = StandInServer::new(false);
+        let url = server.start().await;
+
+        let temp_dir = tempdir().unwrap();
+        let file_path = temp_dir.path().join("pack.zip");
+        let part_path = temp_dir.path().join(format!("pack.zip{}", PART_POSTFIX));
+
+        std::fs::write(&part_path, &server.body[..1000]).unwrap();
+
+        let downloadable = Downloadable::new(url, &file_path)
+            .with_checksum(Some(server.sha1()))
+            .with_size(server.body.len() as u64);
+
+        let (progress_tx, progress_rx) = watch::channel(Progress::default());
+        let options = DownloadOptions::builder()
+            .progress_sender(progress_tx)
+            .build();
+
+        download_multiple(&[downloadable], options).await.unwrap();
+
+        assert_eq!(std::fs::read(&file_path).unwrap(), *server.body);
+        assert_eq!(server.ranges(), [Some("bytes=1000-".to_string())]);
+        assert_eq!(progress_rx.borrow().current_size, server.body.len() as u64);
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_connection_drop_without_range_support() {
+        let server = StandInServer {
+            cut_after: Some(16 * 1024),
+            ..StandInServer::new(false)
+        };
+        let url = server.start().await;
+
+        let temp_dir = tempdir().unwrap();
+        let file_path = temp_dir.path().join("pack.zip");
+        let part_path = temp_dir.path().join(format!("pack.zip{}", PART_POSTFIX));
+
+        let downloadable = Downloadable::new(url, &file_path).with_size(server.body.len() as u64);
+        let options = DownloadOptions::builder().build();
+
+        let result = download_multiple(&[downloadable], options).await;
+
+        assert!(result.is_err());
+        assert!(!part_path.exists());
+        assert_eq!(server.ranges(), [None]);
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_bandwidth_limit_shared_between_calls() {
+        let server = StandInServer::new(true);
+        let url = server.start().await;
+        let temp_dir = tempdir().unwrap();
+
+        // the bucket starts out full, so only the second 64 KiB have to wait
+        let limiter = BandwidthLimiter::new(std::num::NonZeroU32::new(64 * 1024));
+
+        let download = |name: &str| {
+            let downloadable = Downloadable::new(&url, temp_dir.path().join(name))
+                .with_checksum(Some(server.sha1()));
+            let options = DownloadOptions::builder()
+                .bandwidth_limiter(limiter.clone())
+                .build();
+
+            async move { download_multiple(&[downloadable], options).await }
+        };
+
+        let start = std::time::Instant::now();
+        let (first, second) = tokio::join!(download("first.zip"), download("second.zip"));
+        first.unwrap();
+        second.unwrap();
+
+        assert!(start.elapsed() >= Duration::from_millis(900));
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_host_limits() {
+        let server = StandInServer {
+            delay: Duration::from_millis(5),
+            ..StandInServer::new(true)
+        };
+        let url = server.start().await;
+        let temp_dir = tempdir().unwrap();
+
+        let downloadables = (0..4)
+            .map(|i| {
+                Downloadable::new(&url, temp_dir.path().join(format!("{i}.zip")))
+                    .with_checksum(Some(server.sha1()))
+            })
+            .collect::<Vec<_>>();
+
+        let options = DownloadOptions::builder()
+            .concurrency(4)
+            .host_limits(HostLimits::new(std::num::NonZeroUsize::new(2)))
+            .build();
+
+        download_multiple(&downloadables, options).await.unwrap();
+
+        assert_eq!(server.ranges().len(), 4);
+        assert_eq!(server.peak_connections.load(Ordering::SeqCst), 2);
+    }
 }
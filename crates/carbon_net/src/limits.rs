@@ -0,0 +1,207 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::num::{NonZeroU32, NonZeroUsize};
+use std::sync::{Arc, Mutex, RwLock};
+
+use governor::{DefaultDirectRateLimiter, Quota, RateLimiter};
+use tokio::sync::{AcquireError, OwnedSemaphorePermit, Semaphore};
+
+struct Bucket {
+    bytes_per_second: NonZeroU32,
+    limiter: DefaultDirectRateLimiter,
+}
+
+/// Token bucket capping the combined speed of every download it's handed
+/// to. Clones share the same bucket, so a single limiter can be passed to
+/// all concurrent [`crate::download_multiple`] calls.
+#[derive(Clone, Default)]
+pub struct BandwidthLimiter(Arc<RwLock<Option<Arc<Bucket>>>>);
+
+impl BandwidthLimiter {
+    /// `None` doesn't limit the bandwidth at all.
+    pub fn new(bytes_per_second: Option<NonZeroU32>) -> Self {
+        let limiter = Self::default();
+        limiter.set_limit(bytes_per_second);
+        limiter
+    }
+
+    /// Also applies to downloads that are already running.
+    pub fn set_limit(&self, bytes_per_second: Option<NonZeroU32>) {
+        // the bucket holds at most one second worth of bytes
+        let bucket = bytes_per_second.map(|bytes_per_second| {
+            Arc::new(Bucket {
+                bytes_per_second,
+                limiter: RateLimiter::direct(Quota::per_second(bytes_per_second)),
+            })
+        });
+
+        *self.0.write().expect("bandwidth limiter lock poisoned") = bucket;
+    }
+
+    pub fn limit(&self) -> Option<NonZeroU32> {
+        self.bucket().map(|bucket| bucket.bytes_per_second)
+    }
+
+    fn bucket(&self) -> Option<Arc<Bucket>> {
+        self.0
+            .read()
+            .expect("bandwidth limiter lock poisoned")
+            .clone()
+    }
+
+    /// Waits until `bytes` can be transferred without exceeding the limit.
+    pub(crate) async fn consume(&self, bytes: usize) {
+        let Some(bucket) = self.bucket() else {
+            return;
+        };
+
+        let mut remaining = bytes;
+        while remaining > 0 {
+            // asking for more than the bucket can hold would never succeed
+            let cells = remaining.min(bucket.bytes_per_second.get() as usize);
+
+            bucket
+                .limiter
+                .until_n_ready(NonZeroU32::new(cells as u32).expect("cells is never 0"))
+                .await
+                .expect("cells never exceed the burst size");
+
+            remaining -= cells;
+        }
+    }
+}
+
+impl fmt::Debug for BandwidthLimiter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("BandwidthLimiter")
+            .field(&self.limit())
+            .finish()
+    }
+}
+
+#[derive(Debug, Default)]
+struct HostSemaphores {
+    connections_per_host: Option<NonZeroUsize>,
+    hosts: HashMap<String, Arc<Semaphore>>,
+}
+
+/// Caps the number of simultaneous connections to a single host, across
+/// every download the limits are handed to. Clones share the same limits.
+#[derive(Debug, Clone, Default)]
+pub struct HostLimits(Arc<Mutex<HostSemaphores>>);
+
+impl HostLimits {
+    /// `None` doesn't limit the connections at all.
+    pub fn new(connections_per_host: Option<NonZeroUsize>) -> Self {
+        let limits = Self::default();
+        limits.set_limit(connections_per_host);
+        limits
+    }
+
+    /// Only applies to connections opened after the call, the running ones
+    /// keep counting against the previous limit until they finish.
+    pub fn set_limit(&self, connections_per_host: Option<NonZeroUsize>) {
+        let mut semaphores = self.0.lock().expect("host limits lock poisoned");
+        semaphores.connections_per_host = connections_per_host;
+        semaphores.hosts.clear();
+    }
+
+    pub fn limit(&self) -> Option<NonZeroUsize> {
+        self.0
+            .lock()
+            .expect("host limits lock poisoned")
+            .connections_per_host
+    }
+
+    /// Waits for a free connection slot on the host of `url`, the slot is
+    /// released when the permit is dropped.
+    pub(crate) async fn acquire(
+        &self,
+        url: &str,
+    ) -> Result<Option<OwnedSemaphorePermit>, AcquireError> {
+        let Some(host) = reqwest::Url::parse(url)
+            .ok()
+            .and_then(|url| url.host_str().map(str::to_string))
+        else {
+            return Ok(None);
+        };
+
+        let semaphore = {
+            let mut semaphores = self.0.lock().expect("host limits lock poisoned");
+
+            let Some(connections_per_host) = semaphores.connections_per_host else {
+                return Ok(None);
+            };
+
+            Arc::clone(
+                semaphores
+                    .hosts
+                    .entry(host)
+                    .or_insert_with(|| Arc::new(Semaphore::new(connections_per_host.get()))),
+            )
+        };
+
+        semaphore.acquire_owned().await.map(Some)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_bandwidth_limiter_shared_between_clones() {
+        let limiter = BandwidthLimiter::new(NonZeroU32::new(10_000));
+        let clone = limiter.clone();
+
+        let start = Instant::now();
+        // the first 10 kB are served from the full bucket, the rest has to wait
+        tokio::join!(limiter.consume(7_500), clone.consume(7_500));
+
+        assert!(start.elapsed() >= Duration::from_millis(450));
+    }
+
+    #[tokio::test]
+    async fn test_bandwidth_limiter_unlimited() {
+        let limiter = BandwidthLimiter::new(NonZeroU32::new(1_000));
+        limiter.set_limit(None);
+
+        let start = Instant::now();
+        limiter.consume(10_000_000).await;
+
+        assert!(start.elapsed() < Duration::from_millis(100));
+        assert_eq!(limiter.limit(), None);
+    }
+
+    #[tokio::test]
+    async fn test_host_limits() {
+        let limits = HostLimits::new(NonZeroUsize::new(1));
+
+        let first = limits.acquire("https://cdn.example/a.jar").await.unwrap();
+        assert!(first.is_some());
+
+        // same host, has to wait for the first connection
+        let second = tokio::time::timeout(
+            Duration::from_millis(50),
+            limits.acquire("https://cdn.example/b.jar"),
+        )
+        .await;
+        assert!(second.is_err());
+
+        let other_host = tokio::time::timeout(
+            Duration::from_millis(50),
+            limits.acquire("https://maven.example/c.jar"),
+        )
+        .await;
+        assert!(other_host.unwrap().unwrap().is_some());
+
+        drop(first);
+        assert!(limits
+            .acquire("https://cdn.example/b.jar")
+            .await
+            .unwrap()
+            .is_some());
+    }
+}
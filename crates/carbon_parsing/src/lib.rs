@@ -1,5 +1,6 @@
 //! This crate contains parsers and helpers for parsing data.
 
 pub mod log;
+pub mod maven;
 #[cfg(test)]
 mod tests;
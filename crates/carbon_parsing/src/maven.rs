@@ -0,0 +1,72 @@
+use quick_xml::events::Event;
+use quick_xml::reader::Reader;
+use thiserror::Error;
+
+/// Represents the artifact level `maven-metadata.xml` of a maven repository.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MavenMetadata {
+    pub group_id: String,
+    pub artifact_id: String,
+    pub latest: Option<String>,
+    pub release: Option<String>,
+    /// Versions in the order the repository lists them, usually oldest first.
+    pub versions: Vec<String>,
+    /// Raw `yyyyMMddHHmmss` timestamp of the last deployment.
+    pub last_updated: Option<String>,
+}
+
+#[derive(Debug, Error)]
+pub enum MavenMetadataError {
+    #[error("XML parsing error: {0}")]
+    XmlError(#[from] quick_xml::Error),
+    #[error("Missing required element: {0}")]
+    MissingElement(&'static str),
+}
+
+/// Parses an artifact level `maven-metadata.xml` document.
+pub fn parse_metadata(input: &str) -> Result<MavenMetadata, MavenMetadataError> {
+    let mut reader = Reader::from_str(input);
+    reader.config_mut().trim_text(true);
+
+    let mut metadata = MavenMetadata::default();
+    let mut path = Vec::<String>::new();
+
+    loop {
+        match reader.read_event()? {
+            Event::Start(e) => {
+                path.push(String::from_utf8_lossy(e.name().as_ref()).into_owned());
+            }
+            Event::End(_) => {
+                path.pop();
+            }
+            Event::Text(e) => {
+                let text = e
+                    .unescape()
+                    .map_err(|e| MavenMetadataError::XmlError(e.into()))?
+                    .into_owned();
+
+                let path = path.iter().map(String::as_str).collect::<Vec<_>>();
+
+                match &path[..] {
+                    ["metadata", "groupId"] => metadata.group_id = text,
+                    ["metadata", "artifactId"] => metadata.artifact_id = text,
+                    ["metadata", "versioning", "latest"] => metadata.latest = Some(text),
+                    ["metadata", "versioning", "release"] => metadata.release = Some(text),
+                    ["metadata", "versioning", "lastUpdated"] => metadata.last_updated = Some(text),
+                    ["metadata", "versioning", "versions", "version"] => {
+                        metadata.versions.push(text)
+                    }
+                    _ => {}
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    if metadata.artifact_id.is_empty() {
+        return Err(MavenMetadataError::MissingElement("artifactId"));
+    }
+
+    Ok(metadata)
+}
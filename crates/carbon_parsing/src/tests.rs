@@ -1,4 +1,5 @@
 use crate::log::{LogEntryLevel, LogParser, ParsedItem, ParserError};
+use crate::maven::{parse_metadata, MavenMetadataError};
 
 #[test]
 fn test_single_complete_xml_entry() {
@@ -568,3 +569,41 @@ Caused by: java.lang.IllegalStateException: duplicate ASM classes found on class
 //     let results = parser.parse_available().unwrap();
 //     verify_results(results);
 // }
+
+#[test]
+fn test_maven_metadata() {
+    let input = r#"<?xml version="1.0" encoding="UTF-8"?>
+        <metadata>
+          <groupId>com.example.mods</groupId>
+          <artifactId>example-mod</artifactId>
+          <versioning>
+            <latest>1.2.0-beta.1</latest>
+            <release>1.1.0</release>
+            <versions>
+              <version>1.0.0</version>
+              <version>1.1.0</version>
+              <version>1.2.0-beta.1</version>
+            </versions>
+            <lastUpdated>20241201120000</lastUpdated>
+          </versioning>
+        </metadata>"#;
+
+    let metadata = parse_metadata(input).unwrap();
+
+    assert_eq!(metadata.group_id, "com.example.mods");
+    assert_eq!(metadata.artifact_id, "example-mod");
+    assert_eq!(metadata.latest.as_deref(), Some("1.2.0-beta.1"));
+    assert_eq!(metadata.release.as_deref(), Some("1.1.0"));
+    assert_eq!(metadata.versions, vec!["1.0.0", "1.1.0", "1.2.0-beta.1"]);
+    assert_eq!(metadata.last_updated.as_deref(), Some("20241201120000"));
+}
+
+#[test]
+fn test_maven_metadata_without_artifact() {
+    let input = r#"<metadata><versioning><versions /></versioning></metadata>"#;
+
+    assert!(matches!(
+        parse_metadata(input),
+        Err(MavenMetadataError::MissingElement("artifactId"))
+    ));
+}
@@ -10,6 +10,7 @@ use serde::{
 
 pub mod curseforge;
 pub mod modrinth;
+pub mod source;
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[repr(i32)]
@@ -168,6 +169,7 @@ pub struct ModSources {
 pub enum RemoteVersion {
     Curseforge(curseforge::File),
     Modrinth(modrinth::version::Version),
+    Source(source::SourceVersion),
 }
 
 impl RemoteVersion {
@@ -175,6 +177,7 @@ impl RemoteVersion {
         match self {
             Self::Curseforge(v) => v.file_date,
             Self::Modrinth(v) => v.date_published,
+            Self::Source(v) => v.published,
         }
     }
 
@@ -182,6 +185,23 @@ impl RemoteVersion {
         match self {
             Self::Curseforge(v) => v.release_type.into(),
             Self::Modrinth(v) => v.version_type.into(),
+            Self::Source(v) => v.channel,
+        }
+    }
+
+    pub fn project_id(&self) -> String {
+        match self {
+            Self::Curseforge(v) => v.mod_id.to_string(),
+            Self::Modrinth(v) => v.project_id.clone(),
+            Self::Source(v) => v.project_id.clone(),
+        }
+    }
+
+    pub fn id(&self) -> String {
+        match self {
+            Self::Curseforge(v) => v.id.to_string(),
+            Self::Modrinth(v) => v.id.clone(),
+            Self::Source(v) => v.id.clone(),
         }
     }
 }
@@ -0,0 +1,44 @@
+//! Models for the GitHub releases api
+//!
+//! [documentation](https://docs.github.com/en/rest/releases/releases)
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Release {
+    pub id: u64,
+    pub tag_name: String,
+    pub name: Option<String>,
+    pub draft: bool,
+    pub prerelease: bool,
+    pub published_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub assets: Vec<ReleaseAsset>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReleaseAsset {
+    pub id: u64,
+    pub name: String,
+    pub size: u64,
+    pub browser_download_url: String,
+    /// `<algorithm>:<hex digest>`, only present on assets uploaded recently.
+    pub digest: Option<String>,
+}
+
+impl ReleaseAsset {
+    pub fn sha256(&self) -> Option<&str> {
+        self.digest.as_deref()?.strip_prefix("sha256:")
+    }
+
+    /// Whether this asset is a mod jar rather than a sources or development jar.
+    pub fn is_mod_jar(&self) -> bool {
+        let name = self.name.to_lowercase();
+
+        name.ends_with(".jar")
+            && !["-sources.jar", "-javadoc.jar", "-dev.jar", "-api.jar"]
+                .iter()
+                .any(|suffix| name.ends_with(suffix))
+    }
+}
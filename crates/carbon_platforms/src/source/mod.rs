@@ -0,0 +1,110 @@
+//! Models shared by mod sources that are not full mod platforms,
+//! such as GitHub releases or maven repositories.
+
+use chrono::{DateTime, Utc};
+
+use crate::ModChannel;
+
+pub mod github;
+
+/// A single downloadable version of a project hosted on a mod source.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceVersion {
+    /// id of the source the version was resolved from, e.g. `github`
+    pub source: String,
+    /// base url of the repository or api the source was pointed at
+    pub repository: String,
+    pub project_id: String,
+    pub id: String,
+    pub name: String,
+    pub file_name: String,
+    pub url: String,
+    pub sha1: Option<String>,
+    pub sha256: Option<String>,
+    pub size: Option<u64>,
+    /// Game versions declared by the source. Empty if the source does not declare any.
+    pub game_versions: Vec<String>,
+    /// Modloaders declared by the source. Empty if the source does not declare any.
+    pub loaders: Vec<String>,
+    pub channel: ModChannel,
+    pub published: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SourceVersionFilters {
+    pub game_version: Option<String>,
+    pub loaders: Vec<String>,
+}
+
+impl SourceVersionFilters {
+    /// Versions that do not declare a game version or loader are assumed
+    /// to be compatible, as most sources carry no such information.
+    pub fn matches(&self, version: &SourceVersion) -> bool {
+        let game_version = match &self.game_version {
+            Some(game_version) if !version.game_versions.is_empty() => {
+                version.game_versions.contains(game_version)
+            }
+            _ => true,
+        };
+
+        let loader = self.loaders.is_empty()
+            || version.loaders.is_empty()
+            || version.loaders.iter().any(|l| self.loaders.contains(l));
+
+        game_version && loader
+    }
+}
+
+/// Guess the release channel from a version string such as `1.2.0-beta.1`.
+pub fn channel_from_version(version: &str) -> ModChannel {
+    let version = version.to_lowercase();
+
+    if version.contains("alpha") || version.contains("snapshot") {
+        ModChannel::Alpha
+    } else if version.contains("beta") || version.contains("-rc") || version.contains("pre") {
+        ModChannel::Beta
+    } else {
+        ModChannel::Stable
+    }
+}
+
+/// Guess the modloaders a file targets from its name, e.g. `examplemod-fabric-1.2.0.jar`.
+pub fn loaders_from_file_name(file_name: &str) -> Vec<String> {
+    let tokens = file_name
+        .to_lowercase()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .map(ToString::to_string)
+        .collect::<Vec<_>>();
+
+    ["forge", "neoforge", "fabric", "quilt"]
+        .into_iter()
+        .filter(|loader| tokens.iter().any(|token| token == loader))
+        .map(ToString::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn guesses_channels() {
+        assert_eq!(channel_from_version("1.2.0"), ModChannel::Stable);
+        assert_eq!(channel_from_version("1.2.0-beta.1"), ModChannel::Beta);
+        assert_eq!(channel_from_version("1.2.0-rc1"), ModChannel::Beta);
+        assert_eq!(channel_from_version("1.2.0-SNAPSHOT"), ModChannel::Alpha);
+    }
+
+    #[test]
+    fn guesses_loaders() {
+        assert_eq!(
+            loaders_from_file_name("examplemod-neoforge-1.2.0.jar"),
+            vec!["neoforge"]
+        );
+        assert_eq!(
+            loaders_from_file_name("examplemod-fabric+quilt-1.2.0.jar"),
+            vec!["fabric", "quilt"]
+        );
+        assert!(loaders_from_file_name("examplemod-1.2.0.jar").is_empty());
+    }
+}
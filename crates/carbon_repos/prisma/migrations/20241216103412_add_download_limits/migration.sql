@@ -0,0 +1,3 @@
+-- AlterTable
+ALTER TABLE "AppConfiguration" ADD COLUMN "downloadSpeedLimit" INTEGER;
+ALTER TABLE "AppConfiguration" ADD COLUMN "concurrentDownloadsPerHost" INTEGER;
@@ -0,0 +1,3 @@
+-- AlterTable
+ALTER TABLE "Account" ADD COLUMN "yggdrasilServer" TEXT;
+ALTER TABLE "Account" ADD COLUMN "yggdrasilClientToken" TEXT;
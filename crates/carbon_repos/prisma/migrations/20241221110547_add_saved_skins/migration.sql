@@ -0,0 +1,8 @@
+-- CreateTable
+CREATE TABLE "SavedSkin" (
+    "id" INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    "name" TEXT NOT NULL,
+    "variant" TEXT NOT NULL,
+    "skin" BLOB NOT NULL,
+    "createdAt" DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,8 @@
+-- CreateTable
+CREATE TABLE "SkinRender" (
+    "skinId" TEXT NOT NULL,
+    "kind" TEXT NOT NULL,
+    "image" BLOB NOT NULL,
+
+    PRIMARY KEY ("skinId", "kind")
+);
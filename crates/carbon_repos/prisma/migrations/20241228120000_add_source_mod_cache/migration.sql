@@ -0,0 +1,15 @@
+-- CreateTable
+CREATE TABLE "SourceModCache" (
+    "metadataId" TEXT NOT NULL PRIMARY KEY,
+    "sourceId" TEXT NOT NULL,
+    "repository" TEXT NOT NULL,
+    "projectId" TEXT NOT NULL,
+    "versionId" TEXT NOT NULL,
+    "name" TEXT NOT NULL,
+    "version" TEXT NOT NULL,
+    "releaseType" INTEGER NOT NULL,
+    "filename" TEXT NOT NULL,
+    "fileUrl" TEXT NOT NULL,
+    "cachedAt" DATETIME NOT NULL,
+    CONSTRAINT "SourceModCache_metadataId_fkey" FOREIGN KEY ("metadataId") REFERENCES "ModMetadata" ("id") ON DELETE CASCADE ON UPDATE CASCADE
+);
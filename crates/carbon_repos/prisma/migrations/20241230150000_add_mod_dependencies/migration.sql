@@ -0,0 +1,22 @@
+-- AlterTable
+ALTER TABLE "ModMetadata" ADD COLUMN "dependenciesScanned" BOOLEAN NOT NULL DEFAULT false;
+
+-- CreateTable
+CREATE TABLE "ModDependency" (
+    "id" INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    "metadataId" TEXT NOT NULL,
+    "modloader" TEXT NOT NULL,
+    "modid" TEXT NOT NULL,
+    "kind" INTEGER NOT NULL,
+    "versions" TEXT NOT NULL,
+    CONSTRAINT "ModDependency_metadataId_fkey" FOREIGN KEY ("metadataId") REFERENCES "ModMetadata" ("id") ON DELETE CASCADE ON UPDATE CASCADE
+);
+
+-- CreateTable
+CREATE TABLE "ModProvidedId" (
+    "id" INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    "metadataId" TEXT NOT NULL,
+    "modid" TEXT NOT NULL,
+    "version" TEXT,
+    CONSTRAINT "ModProvidedId_metadataId_fkey" FOREIGN KEY ("metadataId") REFERENCES "ModMetadata" ("id") ON DELETE CASCADE ON UPDATE CASCADE
+);
@@ -0,0 +1,9 @@
+-- CreateTable
+CREATE TABLE "ModSourceCredential" (
+    "sourceId" TEXT NOT NULL,
+    "repository" TEXT NOT NULL,
+    "kind" TEXT NOT NULL,
+    "secret" TEXT NOT NULL,
+
+    PRIMARY KEY ("sourceId", "repository")
+);
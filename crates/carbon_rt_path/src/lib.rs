@@ -76,6 +76,21 @@ impl ManagedJavasPath {
     }
 }
 
+/// Content addressed blobs of the files shared between instances, keyed by
+/// their sha1.
+#[derive(Debug, Clone)]
+pub struct StorePath(PathBuf);
+
+impl StorePath {
+    pub fn to_path(&self) -> PathBuf {
+        self.0.clone()
+    }
+
+    pub fn get_blob_path(&self, sha1: &str) -> PathBuf {
+        self.0.join(&sha1[..2]).join(sha1)
+    }
+}
+
 pub struct LoggingConfigsPath(PathBuf);
 
 impl LoggingConfigsPath {
@@ -363,6 +378,10 @@ impl RuntimePath {
         InstancesPath(self.0.join("instances"))
     }
 
+    pub fn get_store(&self) -> StorePath {
+        StorePath(self.0.join("store"))
+    }
+
     pub fn get_logging_configs(&self) -> LoggingConfigsPath {
         LoggingConfigsPath(self.0.join("logging_configs"))
     }
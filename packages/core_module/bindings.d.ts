@@ -5,11 +5,14 @@ export type Procedures = {
         { key: "account.enroll.getStatus", input: never, result: EnrollmentStatus | null } | 
         { key: "account.getAccounts", input: never, result: AccountEntry[] } | 
         { key: "account.getActiveUuid", input: never, result: string | null } | 
+        { key: "account.getCapes", input: string, result: Cape[] } | 
         { key: "account.getGdlAccount", input: never, result: FEGDLAccountStatus } | 
         { key: "account.getHead", input: string, result: null } | 
+        { key: "account.getSavedSkins", input: never, result: SavedSkin[] } | 
         { key: "account.peekGdlAccount", input: string, result: FEGDLAccount | null } | 
         { key: "echo", input: string, result: string } | 
         { key: "getAppVersion", input: never, result: string } | 
+        { key: "instance.analyzeModDependencies", input: FEInstanceId, result: FEModDependencyAnalysis } | 
         { key: "instance.explore", input: ExploreQuery, result: ExploreEntry[] } | 
         { key: "instance.findModUpdate", input: UpdateMod, result: RemoteVersion | null } | 
         { key: "instance.getAllInstances", input: never, result: ListInstance[] } | 
@@ -23,6 +26,7 @@ export type Procedures = {
         { key: "instance.getLogs", input: FEInstanceId, result: GameLogEntry[] } | 
         { key: "instance.getModSources", input: FEInstanceId, result: ModSources } | 
         { key: "instance.getModpackInfo", input: FEInstanceId | null, result: FEInstanceModpackInfo | null } | 
+        { key: "instance.getModpackUpdatePlan", input: FEInstanceId, result: FEModpackUpdatePlan | null } | 
         { key: "java.getAvailableJavas", input: never, result: { [key: number]: FEJavaComponent[] } } | 
         { key: "java.getJavaProfiles", input: never, result: FEJavaProfile[] } | 
         { key: "java.getManagedArch", input: never, result: FEManagedJavaArch[] } | 
@@ -46,6 +50,7 @@ export type Procedures = {
         { key: "modplatforms.curseforge.getModloaders", input: never, result: CFFEModLoaderType[] } | 
         { key: "modplatforms.curseforge.getMods", input: CFFEModsParameters, result: FEModsResponse } | 
         { key: "modplatforms.curseforge.search", input: CFFEModSearchParameters, result: FEModSearchResponse } | 
+        { key: "modplatforms.federatedSearch", input: FEFederatedSearchParameters, result: FEFederatedSearchResponse } | 
         { key: "modplatforms.modrinth.getCategories", input: never, result: MRFECategoriesResponse } | 
         { key: "modplatforms.modrinth.getLoaders", input: never, result: MRFELoadersResponse } | 
         { key: "modplatforms.modrinth.getProject", input: MRFEProjectID, result: MRFEProject } | 
@@ -60,13 +65,20 @@ export type Procedures = {
         { key: "settings.getPrivacyStatementBody", input: never, result: string } | 
         { key: "settings.getSettings", input: never, result: FESettings } | 
         { key: "settings.getTermsOfServiceBody", input: never, result: string } | 
+        { key: "sources.getCredentials", input: never, result: FESourceCredentialEntry[] } | 
+        { key: "sources.getProjectVersions", input: FESourceProjectVersionsParameters, result: FESourceVersion[] } | 
+        { key: "sources.getSources", input: never, result: string[] } | 
         { key: "systeminfo.getTotalRAM", input: never, result: string } | 
         { key: "systeminfo.getUsedRAM", input: never, result: string } | 
         { key: "vtask.getTask", input: FETaskId | null, result: FETask | null } | 
         { key: "vtask.getTasks", input: never, result: FETask[] },
     mutations: 
+        { key: "account.addOfflineAccount", input: string, result: null } | 
+        { key: "account.addYggdrasilAccount", input: FEYggdrasilLogin, result: null } | 
+        { key: "account.applySavedSkin", input: FEApplySavedSkin, result: null } | 
         { key: "account.changeGdlAccountNickname", input: FEChangeGdlAccountNickname, result: null } | 
         { key: "account.deleteAccount", input: string, result: null } | 
+        { key: "account.deleteSavedSkin", input: number, result: null } | 
         { key: "account.enroll.begin", input: never, result: null } | 
         { key: "account.enroll.cancel", input: never, result: null } | 
         { key: "account.enroll.finalize", input: never, result: null } | 
@@ -76,8 +88,13 @@ export type Procedures = {
         { key: "account.requestEmailChange", input: FERequestEmailChange, result: FERequestNewEmailChangeStatus } | 
         { key: "account.requestGdlAccountDeletion", input: string, result: FERequestDeletionStatus } | 
         { key: "account.requestNewVerificationToken", input: string, result: FERequestNewVerificationTokenStatus } | 
+        { key: "account.resetSkin", input: string, result: null } | 
         { key: "account.saveGdlAccount", input: string | null, result: null } | 
+        { key: "account.saveSkin", input: FESaveSkin, result: number } | 
+        { key: "account.setActiveCape", input: FESetActiveCape, result: null } | 
         { key: "account.setActiveUuid", input: string | null, result: null } | 
+        { key: "account.uploadSkin", input: FEUploadSkin, result: null } | 
+        { key: "instance.cancelModpackChange", input: FEInstanceId, result: null } | 
         { key: "instance.cancelImportScan", input: never, result: null } | 
         { key: "instance.changeModpack", input: ChangeModpack, result: FETaskId } | 
         { key: "instance.createGroup", input: string, result: FEGroupId } | 
@@ -99,6 +116,7 @@ export type Procedures = {
         { key: "instance.moveGroup", input: MoveGroup, result: null } | 
         { key: "instance.moveInstance", input: MoveInstance, result: null } | 
         { key: "instance.openInstanceFolder", input: OpenInstanceFolder, result: null } | 
+        { key: "instance.previewModpackChange", input: PreviewModpackChange, result: FETaskId } | 
         { key: "instance.prepareInstance", input: FEInstanceId, result: FETaskId } | 
         { key: "instance.setFavorite", input: SetFavorite, result: null } | 
         { key: "instance.setImportScanTarget", input: [ImportEntity, string], result: null } | 
@@ -108,12 +126,15 @@ export type Procedures = {
         { key: "java.createJavaProfile", input: FECreateJavaProfileArgs, result: null } | 
         { key: "java.deleteJavaProfile", input: string, result: null } | 
         { key: "java.deleteJavaVersion", input: string, result: null } | 
+        { key: "java.garbageCollectManagedJavas", input: never, result: number } | 
         { key: "java.setupManagedJava", input: FEManagedJavaSetupArgs, result: string } | 
         { key: "java.updateJavaProfile", input: FEUpdateJavaProfileArgs, result: null } | 
         { key: "java.validateCustomJavaPath", input: string, result: boolean } | 
         { key: "longRunning", input: never, result: boolean } | 
         { key: "metrics.sendEvent", input: FEMetricsEvent, result: null } | 
         { key: "settings.setSettings", input: FESettingsUpdate, result: null } | 
+        { key: "sources.removeCredential", input: FESourceRepository, result: null } | 
+        { key: "sources.setCredential", input: FESetSourceCredential, result: null } | 
         { key: "vtask.dismissTask", input: FETaskId, result: null },
     subscriptions: never
 };
@@ -146,7 +167,7 @@ export type MRFEVersion = { name: string; version_number: string; changelog: str
 
 export type CFFESortableGameVersion = { gameVersionName: string; gameVersionPadded: string; gameVersion: string; gameVersionReleaseDate: string; gameVersionTypeId: number | null }
 
-export type AccountEntry = { username: string; uuid: string; lastUsed: string; type: AccountType; status: AccountStatus }
+export type AccountEntry = { username: string; uuid: string; lastUsed: string; type: AccountType; status: AccountStatus; lastRefreshError: string | null }
 
 export type FEManagedJavaArchMap = { [key: FEManagedJavaArch]: FEManagedJavaVersion[] }
 
@@ -166,7 +187,7 @@ export type FEManagedJavaOsMap = { [key: FEManagedJavaOs]: FEManagedJavaArchMap
 
 export type DeviceCode = { userCode: string; verificationUri: string; expiresAt: string }
 
-export type Mod = { id: string; filename: string; enabled: boolean; metadata: ModFileMetadata | null; curseforge: CurseForgeModMetadata | null; modrinth: ModrinthModMetadata | null; has_update: boolean }
+export type Mod = { id: string; filename: string; enabled: boolean; metadata: ModFileMetadata | null; curseforge: CurseForgeModMetadata | null; modrinth: ModrinthModMetadata | null; source: SourceModMetadata | null; has_update: boolean }
 
 export type CreateInstanceVersion = { Version: GameVersion } | { Modpack: Modpack }
 
@@ -192,11 +213,11 @@ export type MRFEProjectID = string
 
 export type GameLogEntry = { id: GameLogId; instance_id: FEInstanceId; active: boolean; timestamp: string }
 
-export type RemoteVersion = ({ platform: "Curseforge" } & CFFEFile) | ({ platform: "Modrinth" } & MRFEVersion)
+export type RemoteVersion = ({ platform: "Curseforge" } & CFFEFile) | ({ platform: "Modrinth" } & MRFEVersion) | ({ platform: "Source" } & FESourceVersion)
 
 export type MRFEProjectSearchParameters = { query: string | null; facets: MRFESearchFacetAnd | null; index: MRFESearchIndex | null; offset: number | null; limit: number | null; filters: string | null }
 
-export type LatestModSource = { Curseforge: number } | { Modrinth: string }
+export type LatestModSource = { Curseforge: number } | { Modrinth: string } | { Source: SourceProject }
 
 export type FEManagedJavaVersion = { id: string; name: string; downloadUrl: string; javaVersion: string }
 
@@ -204,7 +225,7 @@ export type FEModDescriptionResponse = { data: string; pagination: CFFEPaginatio
 
 export type InstallLatestMod = { instance_id: FEInstanceId; mod_source: LatestModSource }
 
-export type FEVendor = "azul"
+export type FEVendor = "azul" | "adoptium" | "mojang"
 
 export type MRFEProjectSearchResponse = { hits: MRFEProjectSearchResult[]; offset: number; limit: number; total_hits: number }
 
@@ -263,8 +284,14 @@ export type ValidListInstance = { mc_version: string | null; modloader: FEInstan
 export type MRFEAdditionalFileType = "requiredResourcePack" | "optionalResourcePack"
 
 export type FESearchAPI = "curseforge" | "modrinth"
+export type FESourceProjectVersionsParameters = { sourceId: string; repository: string | null; projectId: string; gameVersion: string | null; loaders: string[] | null }
+export type FESetSourceCredential = { sourceId: string; repository: string | null; credential: FESourceCredential }
+export type FESourceCredential = { kind: "token"; token: string } | { kind: "basic"; username: string; password: string }
+export type FESourceRepository = { sourceId: string; repository: string | null }
+export type FESourceCredentialEntry = { sourceId: string; repository: string; kind: string }
+export type FESourceVersion = { sourceId: string; repository: string; projectId: string; id: string; name: string; fileName: string; url: string; sha1: string | null; sha256: string | null; size: number | null; gameVersions: string[]; loaders: string[]; channel: ModChannel; published: string }
 
-export type FESettingsUpdate = { theme?: Set<string> | null; language?: Set<string> | null; reducedMotion?: Set<boolean> | null; discordIntegration?: Set<boolean> | null; releaseChannel?: Set<FEReleaseChannel> | null; lastAppVersion?: Set<string | null> | null; concurrentDownloads?: Set<number> | null; downloadDependencies?: Set<boolean> | null; instancesSortBy?: Set<InstancesSortBy> | null; instancesSortByAsc?: Set<boolean> | null; instancesGroupBy?: Set<InstancesGroupBy> | null; instancesGroupByAsc?: Set<boolean> | null; instancesTileSize?: Set<number> | null; deletionThroughRecycleBin?: Set<boolean> | null; showNews?: Set<boolean> | null; showFeatured?: Set<boolean> | null; xmx?: Set<number> | null; xms?: Set<number> | null; preLaunchHook?: Set<string | null> | null; wrapperCommand?: Set<string | null> | null; postExitHook?: Set<string | null> | null; isFirstLaunch?: Set<boolean> | null; launcherActionOnGameLaunch?: Set<FELauncherActionOnGameLaunch> | null; showAppCloseWarning?: Set<boolean> | null; gameResolution?: Set<GameResolution | null> | null; javaCustomArgs?: Set<string> | null; autoManageJavaSystemProfiles?: Set<boolean> | null; modSources?: Set<ModSources> | null; termsAndPrivacyAccepted?: Set<boolean> | null; hashedEmailAccepted?: Set<boolean> | null; gdlAccountId?: Set<string | null> | null }
+export type FESettingsUpdate = { theme?: Set<string> | null; language?: Set<string> | null; reducedMotion?: Set<boolean> | null; discordIntegration?: Set<boolean> | null; releaseChannel?: Set<FEReleaseChannel> | null; lastAppVersion?: Set<string | null> | null; concurrentDownloads?: Set<number> | null; concurrentDownloadsPerHost?: Set<number | null> | null; downloadSpeedLimit?: Set<number | null> | null; downloadMirrors?: Set<FEDownloadMirror[]> | null; downloadDependencies?: Set<boolean> | null; instancesSortBy?: Set<InstancesSortBy> | null; instancesSortByAsc?: Set<boolean> | null; instancesGroupBy?: Set<InstancesGroupBy> | null; instancesGroupByAsc?: Set<boolean> | null; instancesTileSize?: Set<number> | null; deletionThroughRecycleBin?: Set<boolean> | null; showNews?: Set<boolean> | null; showFeatured?: Set<boolean> | null; xmx?: Set<number> | null; xms?: Set<number> | null; preLaunchHook?: Set<string | null> | null; wrapperCommand?: Set<string | null> | null; postExitHook?: Set<string | null> | null; isFirstLaunch?: Set<boolean> | null; launcherActionOnGameLaunch?: Set<FELauncherActionOnGameLaunch> | null; showAppCloseWarning?: Set<boolean> | null; gameResolution?: Set<GameResolution | null> | null; javaCustomArgs?: Set<string> | null; autoManageJavaSystemProfiles?: Set<boolean> | null; modSources?: Set<ModSources> | null; termsAndPrivacyAccepted?: Set<boolean> | null; hashedEmailAccepted?: Set<boolean> | null; gdlAccountId?: Set<string | null> | null }
 
 export type ExportEntry = { entries: { [key: string]: ExportEntry | null } }
 
@@ -312,7 +339,7 @@ export type CurseforgeMod = { project_id: number; file_id: number }
 
 export type McType = "old_alpha" | "old_beta" | "release" | "snapshot"
 
-export type Translation = { translation: "InstanceTaskDeleting" } | { translation: "InstanceTaskLaunch"; args: { name: string } } | { translation: "InstanceTaskPrepare"; args: { name: string } } | { translation: "InstanceTaskLaunchRequestVersions" } | { translation: "InstanceTaskLaunchRequestModpack" } | { translation: "InstanceTaskLaunchDownloadModpack" } | { translation: "InstanceTaskLaunchDownloadModpackFiles" } | { translation: "InstanceTaskLaunchExtractModpackFiles" } | { translation: "InstanceTaskLaunchRequestAddonMetadata" } | { translation: "InstanceTaskLaunchApplyStagedPatches" } | { translation: "InstanceTaskLaunchDownloadJava" } | { translation: "InstanceTaskLaunchExtractJava" } | { translation: "InstanceTaskRequestModloaderInfo" } | { translation: "InstanceTaskRequestMinecraftFiles" } | { translation: "InstanceTaskLaunchCheckingFiles" } | { translation: "InstanceTaskLaunchDownloadFiles" } | { translation: "InstanceTaskGeneratingPackInfo" } | { translation: "InstanceTaskFillCache" } | { translation: "InstanceTaskLaunchExtractNatives" } | { translation: "InstanceTaskReconstructAssets" } | { translation: "InstanceTaskLaunchRunForgeProcessors" } | { translation: "InstanceTaskLaunchRunNeoforgeProcessors" } | { translation: "InstanceTaskInstallMod"; args: { mod_name: string; instance_name: string } } | { translation: "InstanceTaskInstallModDownloadFile" } | { translation: "FinalizingImport" } | { translation: "InstanceImportLegacyBadConfigFile" } | { translation: "InstanceImportCfZipMalformed" } | { translation: "InstanceImportCfZipMissingManifest" } | { translation: "InstanceImportCfZipMalformedManifest" } | { translation: "InstanceImportCfZipNotMinecraftModpack" } | { translation: "InstanceImportMrpackMalformed" } | { translation: "InstanceImportMrpackMissingManifest" } | { translation: "InstanceImportMrpackMalformedManifest" } | { translation: "InstanceExport" } | { translation: "InstanceExportScanningMods" } | { translation: "InstanceExportCacheMods" } | { translation: "InstanceExportCalculateSize" } | { translation: "InstanceExportCreatingBundle" }
+export type Translation = { translation: "InstanceTaskDeleting" } | { translation: "InstanceTaskLaunch"; args: { name: string } } | { translation: "InstanceTaskPrepare"; args: { name: string } } | { translation: "InstanceTaskLaunchRequestVersions" } | { translation: "InstanceTaskLaunchRequestModpack" } | { translation: "InstanceTaskLaunchDownloadModpack" } | { translation: "InstanceTaskLaunchDownloadModpackFiles" } | { translation: "InstanceTaskLaunchExtractModpackFiles" } | { translation: "InstanceTaskLaunchRequestAddonMetadata" } | { translation: "InstanceTaskLaunchApplyStagedPatches" } | { translation: "InstanceTaskLaunchDownloadJava" } | { translation: "InstanceTaskLaunchExtractJava" } | { translation: "InstanceTaskRequestModloaderInfo" } | { translation: "InstanceTaskRequestMinecraftFiles" } | { translation: "InstanceTaskLaunchCheckingFiles" } | { translation: "InstanceTaskLaunchDownloadFiles" } | { translation: "InstanceTaskGeneratingPackInfo" } | { translation: "InstanceTaskFillCache" } | { translation: "InstanceTaskLaunchExtractNatives" } | { translation: "InstanceTaskReconstructAssets" } | { translation: "InstanceTaskLaunchRunForgeProcessors" } | { translation: "InstanceTaskLaunchRunNeoforgeProcessors" } | { translation: "InstanceTaskInstallMod"; args: { mod_name: string; instance_name: string } } | { translation: "InstanceTaskInstallModDownloadFile" } | { translation: "FinalizingImport" } | { translation: "InstanceImportLegacyBadConfigFile" } | { translation: "InstanceImportCfZipMalformed" } | { translation: "InstanceImportCfZipMissingManifest" } | { translation: "InstanceImportCfZipMalformedManifest" } | { translation: "InstanceImportCfZipNotMinecraftModpack" } | { translation: "InstanceImportMrpackMalformed" } | { translation: "InstanceImportMrpackMissingManifest" } | { translation: "InstanceImportMrpackMalformedManifest" } | { translation: "InstanceImportMmcBadConfigFile" } | { translation: "InstanceImportMmcUnsupportedInstanceType" } | { translation: "InstanceImportMmcMissingPack" } | { translation: "InstanceImportMmcMalformedPack" } | { translation: "InstanceImportMmcUnsupportedComponent"; args: { uid: string } } | { translation: "InstanceImportAtlBadConfigFile" } | { translation: "InstanceImportAtlUnsupportedLoader"; args: { loader: string } } | { translation: "InstanceImportTechnicMissingVersion" } | { translation: "InstanceImportTechnicMalformedVersion" } | { translation: "InstanceImportFtbBadConfigFile" } | { translation: "InstanceImportFtbUnsupportedLoader"; args: { loader: string } } | { translation: "InstanceExport" } | { translation: "InstanceExportScanningMods" } | { translation: "InstanceExportCacheMods" } | { translation: "InstanceExportCalculateSize" } | { translation: "InstanceExportCreatingBundle" }
 
 export type MRFETeamResponse = MRFETeamMember[]
 
@@ -347,7 +374,33 @@ export type MRFEProjectIDs = string[]
 
 export type FERegisterAccount = { email: string; nickname: string; uuid: string }
 
-export type ChangeModpack = { instance: FEInstanceId; modpack: Modpack }
+export type FEYggdrasilLogin = { server: string; username: string; password: string }
+
+export type SkinVariant = "classic" | "slim"
+
+export type FEUploadSkin = { uuid: string; path: string; variant: SkinVariant }
+
+export type FESetActiveCape = { uuid: string; capeId: string | null }
+
+export type Cape = { id: string; url: string; alias: string; active: boolean }
+
+export type FESaveSkin = { name: string; path: string; variant: SkinVariant }
+
+export type FEApplySavedSkin = { uuid: string; id: number }
+
+export type SavedSkin = { id: number; name: string; variant: SkinVariant; createdAt: string }
+
+export type ChangeModpack = { instance: FEInstanceId; modpack: Modpack; overrides?: { [key: string]: FEFileOverride } | null }
+export type PreviewModpackChange = { instance: FEInstanceId; modpack: Modpack }
+export type FEFileOverride = "takePack" | "keepMine"
+export type FEModpackUpdatePlan = { added: FEPlannedFile[]; removed: FEPlannedFile[]; replaced: FEPlannedFile[]; skipped: FESkippedFile[]; modChanges: FEModVersionChange[] }
+export type FEPlannedFile = { path: string; overridden: boolean }
+export type FESkippedFile = { path: string; reason: FESkipReason }
+export type FESkipReason = "deletedByUser" | { modifiedByUser: { originalMd5: string; currentMd5: string } } | "createdByUser" | "inSaveFolder" | "keptByUser"
+export type FEModVersionChange = { modId: string; name: string | null; oldVersion: string | null; newVersion: string | null; oldPath: string; newPath: string }
+export type FEModDependencyAnalysis = { issues: FEModDependencyIssue[] }
+export type FEModDependencyIssue = { missing: { modId: string; filename: string; dependency: string; versions: string[] } } | { unsatisfiedVersion: { modId: string; filename: string; dependency: string; versions: string[]; found: FEDependencyProvider[] } } | { incompatible: { modId: string; filename: string; dependency: string; versions: string[]; found: FEDependencyProvider[]; breaking: boolean } } | { duplicateModId: { modid: string; mods: FEDependencyProvider[] } }
+export type FEDependencyProvider = { instance: { version: string | null } } | { mod: { modId: string; filename: string; version: string | null } }
 
 export type XboxError = "noAccount" | "xboxServicesBanned" | "adultVerificationRequired" | "childAccount" | { unknown: number }
 
@@ -360,6 +413,11 @@ export type FERequestNewEmailChangeStatus = { status: "success" } | { status: "f
 export type CFFEModParameters = { modId: number }
 
 export type FEUnifiedPagination = { index: number; pageSize: number; resultCount: number; totalCount: number }
+export type FEFederatedSearchParameters = { searchQuery: string | null; categories: And<FEUnifiedSearchCategoryID> | null; gameVersions: Or<string> | null; modloaders: Or<FEUnifiedModLoaderType> | null; projectType: FEUnifiedSearchType | null; sortIndex: FEFederatedSortIndex | null; index: number | null; pageSize: number | null }
+export type FEFederatedSortIndex = "relevance" | "popularity" | "downloads" | "updated"
+export type FEFederatedMatch = "slug" | "name" | "fileHash"
+export type FEFederatedSearchResult = { platforms: FESearchAPI[]; matchedBy: FEFederatedMatch | null; curseforge: CFFEMod | null; modrinth: MRFEProjectSearchResult | null }
+export type FEFederatedSearchResponse = { data: FEFederatedSearchResult[]; pagination: FEUnifiedPagination; skippedApis: FESearchAPI[] }
 
 export type MRFEProjectSearchResult = { slug: string; title: string; description: string; categories: string[] | null; client_side: MRFEProjectSupportRange; server_side: MRFEProjectSupportRange; project_type: MRFEProjectType; downloads: number; icon_url: string | null; color: number | null; project_id: string; author: string; display_categories: string[] | null; versions: string[]; follows: number; date_created: string; date_modified: string; latest_version: string | null; license: string; gallery: string[] | null; featured_gallery: string | null }
 
@@ -407,6 +465,8 @@ export type ConfigurationParseErrorType = "Syntax" | "Data" | "Eof" | "Unknown"
 
 export type FEReleaseChannel = "stable" | "alpha" | "beta"
 
+export type FEDownloadMirror = { origin: string; mirror: string }
+
 export type MemoryRange = { min_mb: number; max_mb: number }
 
 export type CFFEModAuthor = { id: number; name: string; url: string }
@@ -414,6 +474,7 @@ export type CFFEModAuthor = { id: number; name: string; url: string }
 export type FEGDLAccount = { email: string; microsoftOid: string; nickname: string; friendCode: string; profileIconUrl: string; microsoftEmail: string | null; isEmailVerified: boolean; hasPendingVerification: boolean; verificationTimeout: number | null; hasPendingDeletionRequest: boolean; deletionTimeout: number | null; emailChangeTimeout: number | null }
 
 export type ModrinthModMetadata = { project_id: string; version_id: string; title: string; version: string; urlslug: string; description: string; authors: string; has_image: boolean }
+export type SourceModMetadata = { source_id: string; repository: string; project_id: string; version_id: string; name: string; version: string }
 
 export type ListInstance = { id: FEInstanceId; group_id: FEGroupId; name: string; favorite: boolean; status: ListInstanceStatus; icon_revision: number | null; last_played: string | null; date_created: string; date_updated: string; seconds_played: number }
 
@@ -438,6 +499,8 @@ export type FETask = { name: Translation; progress: Progress; downloaded: number
 export type InstallMod = { instance_id: FEInstanceId; mod_source: ModSource; install_deps: boolean; replaces_mod: string | null }
 
 export type ModrinthMod = { project_id: string; version_id: string }
+export type SourceMod = { source_id: string; repository: string | null; project_id: string; version_id: string }
+export type SourceProject = { source_id: string; repository: string | null; project_id: string }
 
 export type GameVersion = { Standard: StandardVersion }
 
@@ -447,11 +510,11 @@ export type MRFEModeratorMessage = { message: string; body: string | null }
 
 export type CFFEModSearchSortOrder = "ascending" | "descending"
 
-export type FESettings = { theme: string; language: string; reducedMotion: boolean; discordIntegration: boolean; releaseChannel: FEReleaseChannel; lastAppVersion: string | null; concurrentDownloads: number; downloadDependencies: boolean; launcherActionOnGameLaunch: FELauncherActionOnGameLaunch; showAppCloseWarning: boolean; showNews: boolean; showFeatured: boolean; instancesSortBy: InstancesSortBy; instancesSortByAsc: boolean; instancesGroupBy: InstancesGroupBy; instancesGroupByAsc: boolean; instancesTileSize: number; deletionThroughRecycleBin: boolean; xmx: number; xms: number; preLaunchHook: string | null; wrapperCommand: string | null; postExitHook: string | null; isFirstLaunch: boolean; gameResolution: GameResolution | null; javaCustomArgs: string; autoManageJavaSystemProfiles: boolean; modSources: ModSources; termsAndPrivacyAccepted: boolean; hashedEmailAccepted: boolean; gdlAccountId: string | null }
+export type FESettings = { theme: string; language: string; reducedMotion: boolean; discordIntegration: boolean; releaseChannel: FEReleaseChannel; lastAppVersion: string | null; concurrentDownloads: number; concurrentDownloadsPerHost: number | null; downloadSpeedLimit: number | null; downloadMirrors: FEDownloadMirror[]; downloadDependencies: boolean; launcherActionOnGameLaunch: FELauncherActionOnGameLaunch; showAppCloseWarning: boolean; showNews: boolean; showFeatured: boolean; instancesSortBy: InstancesSortBy; instancesSortByAsc: boolean; instancesGroupBy: InstancesGroupBy; instancesGroupByAsc: boolean; instancesTileSize: number; deletionThroughRecycleBin: boolean; xmx: number; xms: number; preLaunchHook: string | null; wrapperCommand: string | null; postExitHook: string | null; isFirstLaunch: boolean; gameResolution: GameResolution | null; javaCustomArgs: string; autoManageJavaSystemProfiles: boolean; modSources: ModSources; termsAndPrivacyAccepted: boolean; hashedEmailAccepted: boolean; gdlAccountId: string | null }
 
 export type CFFEFileIndex = { gameVersion: string; fileId: number; filename: string; releaseType: CFFEFileReleaseType; gameVersionTypeId: number | null; modLoader: CFFEModLoaderType | null }
 
-export type ModSource = { Curseforge: CurseforgeMod } | { Modrinth: ModrinthMod }
+export type ModSource = { Curseforge: CurseforgeMod } | { Modrinth: ModrinthMod } | { Source: SourceMod }
 
 export type MRFEProjectType = "mod" | "shader" | "modpack" | "resourcepack" | "plugin" | "project" | "datapack"
 
@@ -459,7 +522,7 @@ export type MRFELoadersResponse = MRFELoader[]
 
 export type FECategoriesResponse = { data: CFFECategory[]; pagination: CFFEPagination | null }
 
-export type AccountType = { type: "microsoft"; value: { email: string | null } } | { type: "offline" }
+export type AccountType = { type: "microsoft"; value: { email: string | null } } | { type: "offline" } | { type: "yggdrasil"; value: { server: string } }
 
 export type MRFESearchFacetAnd = MRFESearchFacetOr[]
 